@@ -0,0 +1,131 @@
+//! Criterion benches for the per-frame hot paths: the whole FFT processor,
+//! the dB conversion and bin → bar aggregation on their own, and building
+//! the gradient bar meshes.
+//!
+//!     cargo bench --bench processing
+//!
+//! BeSpec is a binary, so the modules are pulled in by path, the same trick
+//! `src/bin/media_test.rs` uses. In-app, Settings → Stats → Stress Test
+//! measures the same paths (plus egui's tessellation) on the user's machine.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+#[allow(dead_code, unused_imports)]
+#[path = "../src/fft_config.rs"]
+mod fft_config;
+
+#[allow(dead_code, unused_imports)]
+#[path = "../src/fft_processor.rs"]
+mod fft_processor;
+
+#[allow(dead_code)]
+#[path = "../src/gui/gradient.rs"]
+mod gradient;
+
+/// The two things `fft_processor` takes from `shared_state`, which would
+/// drag in the whole app
+mod shared_state {
+    pub const SILENCE_DB: f32 = -140.0;
+
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    pub enum AggregationMode {
+        Peak,
+        Average,
+    }
+}
+
+/// `fft_processor`'s tests import this. Their bodies aren't compiled into
+/// the bench, but `clippy --all-targets` still resolves the import.
+#[cfg(test)]
+struct AudioPacket;
+
+use fft_processor::{FFTConfig, FFTProcessor};
+use shared_state::AggregationMode;
+
+/// Bar counts from the default up to the slider's maximum
+const BAR_COUNTS: [usize; 3] = [64, 256, 512];
+
+/// One FFT window of white noise, so every bar has something in it
+fn noise_block() -> Vec<f32> {
+    let mut seed = 0x2545_F491u32;
+    (0..fft_config::FIXED_FFT_SIZE)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as f32 / u32::MAX as f32 * 2.0 - 1.0
+        })
+        .collect()
+}
+
+fn processor(num_bars: usize, aggregation_mode: AggregationMode) -> FFTProcessor {
+    FFTProcessor::new(FFTConfig { num_bars, aggregation_mode, ..Default::default() })
+}
+
+fn bench_process(c: &mut Criterion) {
+    let samples = noise_block();
+    let mut group = c.benchmark_group("fft_process");
+    for bars in BAR_COUNTS {
+        let mut processor = processor(bars, AggregationMode::Peak);
+        group.bench_with_input(BenchmarkId::from_parameter(bars), &samples, |b, samples| {
+            b.iter(|| {
+                processor.process(black_box(samples));
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_aggregation(c: &mut Criterion) {
+    // Linear FFT magnitudes, a spread of levels across the bins
+    let magnitudes: Vec<f32> = (0..=fft_config::FIXED_FFT_SIZE / 2).map(|i| 1.0 + (i % 7) as f32).collect();
+    let mut db = Vec::new();
+
+    c.bench_function("db_conversion", |b| {
+        b.iter(|| fft_processor::magnitudes_to_db(black_box(&magnitudes), 0.044, &mut db))
+    });
+
+    fft_processor::magnitudes_to_db(&magnitudes, 0.044, &mut db);
+    let mut group = c.benchmark_group("bin_aggregation");
+    for mode in [AggregationMode::Peak, AggregationMode::Average] {
+        for bars in BAR_COUNTS {
+            let processor = processor(bars, mode);
+            let mut out = Vec::with_capacity(bars);
+            group.bench_with_input(BenchmarkId::new(format!("{:?}", mode), bars), &db, |b, db| {
+                b.iter(|| processor.group_bins(black_box(db), &mut out))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_bar_mesh(c: &mut Criterion) {
+    let gradient = [
+        (0.0, egui::Color32::from_rgb(0, 80, 255)),
+        (0.5, egui::Color32::from_rgb(0, 255, 120)),
+        (1.0, egui::Color32::from_rgb(255, 40, 0)),
+    ];
+    let mut group = c.benchmark_group("bar_mesh");
+    for bars in BAR_COUNTS {
+        // A 1920 × 1080 view, bars at assorted heights, appended into one mesh
+        let width = 1920.0 / bars as f32;
+        group.bench_function(BenchmarkId::from_parameter(bars), |b| {
+            b.iter(|| {
+                let mut mesh = egui::Mesh::default();
+                for i in 0..bars {
+                    let level = (i % 17) as f32 / 16.0;
+                    let x = i as f32 * width;
+                    mesh.append(gradient::gradient_bar_mesh(&gradient, level, |t| {
+                        let y = 1080.0 * (1.0 - t);
+                        [egui::pos2(x, y), egui::pos2(x + width * 0.8, y)]
+                    }));
+                }
+                black_box(mesh)
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_process, bench_aggregation, bench_bar_mesh);
+criterion_main!(benches);
@@ -0,0 +1,92 @@
+//! A/B compare: two working looks (a visual profile, colors included) to
+//! flip between with one key, like the A/B button on an audio plugin.
+//!
+//! The live profile is always the slot on screen; the other one waits here.
+//! Nothing extra is saved: the slots last for the session, and whatever is
+//! showing when BeSpec closes is what the config keeps.
+
+use crate::shared_state::VisualProfile;
+
+#[derive(Clone, Debug, Default)]
+pub struct AbCompare {
+    /// The slot that isn't showing; `None` until B is first used
+    pub other: Option<VisualProfile>,
+    /// Whether the live profile is slot B
+    pub on_b: bool,
+}
+
+impl AbCompare {
+    /// Letter of the slot on screen
+    pub fn active(&self) -> &'static str {
+        if self.on_b { "B" } else { "A" }
+    }
+
+    /// Show the other slot. B starts out as a copy of A, so the first flip
+    /// changes nothing until B is edited.
+    pub fn flip(&mut self, live: &mut VisualProfile) {
+        let other = self.other.get_or_insert_with(|| live.clone());
+        std::mem::swap(live, other);
+        self.on_b = !self.on_b;
+    }
+
+    /// Overwrite B with A, whichever is showing
+    pub fn copy_a_to_b(&mut self, live: &mut VisualProfile) {
+        if self.on_b {
+            if let Some(a) = &self.other {
+                *live = a.clone();
+            }
+        } else {
+            self.other = Some(live.clone());
+        }
+    }
+
+    /// Exchange the two slots' contents, staying on the same letter
+    pub fn swap(&mut self, live: &mut VisualProfile) {
+        if let Some(other) = &mut self.other {
+            std::mem::swap(live, other);
+        }
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(name: &str) -> VisualProfile {
+        VisualProfile { name: name.to_string(), ..VisualProfile::default() }
+    }
+
+    #[test]
+    fn test_flip_keeps_both_looks() {
+        let mut ab = AbCompare::default();
+        let mut live = named("Chill");
+
+        ab.flip(&mut live);
+        assert_eq!((ab.active(), live.name.as_str()), ("B", "Chill"));
+        live.name = "Club".to_string();
+
+        ab.flip(&mut live);
+        assert_eq!((ab.active(), live.name.as_str()), ("A", "Chill"));
+        ab.flip(&mut live);
+        assert_eq!((ab.active(), live.name.as_str()), ("B", "Club"));
+    }
+
+    #[test]
+    fn test_copy_and_swap() {
+        let mut ab = AbCompare::default();
+        let mut live = named("Chill");
+        ab.flip(&mut live);
+        live.name = "Club".to_string();
+
+        // On B: swapping brings A's look here, still labelled B
+        ab.swap(&mut live);
+        assert_eq!((ab.active(), live.name.as_str()), ("B", "Chill"));
+        assert_eq!(ab.other.as_ref().unwrap().name, "Club");
+
+        // A is now "Club"; copying it over B shows it right away
+        ab.copy_a_to_b(&mut live);
+        assert_eq!(live.name, "Club");
+    }
+}
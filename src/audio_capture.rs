@@ -9,16 +9,23 @@
 ///   ALSA path silently misroutes to the default capture source instead).
 /// - this file (cfg(not(linux))) — Windows / macOS, cpal-based with WASAPI /
 ///   CoreAudio loopback semantics that work as designed on those platforms.
+///
+/// Both implement `CaptureBackend`, which is all the capture thread in
+/// `main.rs` talks to.
 
 #[cfg(target_os = "linux")]
 pub use crate::audio_capture_pw::AudioCaptureManager;
 
 use std::time::Instant;
 
+use crossbeam_channel::Receiver;
+
+use crate::audio_device::{AudioDeviceError, AudioDeviceInfo};
+
 #[cfg(not(target_os = "linux"))]
 use cpal::traits::{DeviceTrait, StreamTrait};
 #[cfg(not(target_os = "linux"))]
-use crossbeam_channel::{bounded, Receiver, Sender};
+use crossbeam_channel::{bounded, Sender};
 #[cfg(not(target_os = "linux"))]
 use std::sync::{Arc, Mutex};
 #[cfg(not(target_os = "linux"))]
@@ -29,7 +36,58 @@ use std::thread;
 use std::time::Duration;
 
 #[cfg(not(target_os = "linux"))]
-use crate::audio_device::{AudioDeviceEnumerator, AudioDeviceInfo, AudioDeviceError};
+use crate::audio_device::{is_asio_id, is_input_id, AudioDeviceEnumerator};
+
+/// What the capture thread needs from a platform backend.
+///
+/// Device ids are backend-specific (a PipeWire node name, a cpal device
+/// name) but always round-trip: an id from `list_devices` can be handed
+/// straight to `switch_device` and stored in `AppConfig::selected_device`.
+pub trait CaptureBackend: Send {
+    /// Short name for logs ("PipeWire", "cpal", ...)
+    fn backend_name(&self) -> &'static str;
+
+    /// Everything this backend can capture from, in picker order.
+    /// Does not include the "Default" sentinel; the GUI adds that itself.
+    fn list_devices() -> Result<Vec<AudioDeviceInfo>, AudioDeviceError>
+    where
+        Self: Sized;
+
+    fn start_capture(&mut self) -> Result<(), AudioDeviceError>;
+    fn stop_capture(&mut self);
+    fn switch_device(&mut self, device_id: &str) -> Result<(), AudioDeviceError>;
+
+    /// Receiver end of the packet channel, cloned out to the FFT thread
+    fn receiver(&self) -> Receiver<AudioPacket>;
+
+    /// Info for the device currently being captured
+    fn device_info(&self) -> AudioDeviceInfo;
+
+    /// Options used the next time the stream is opened (`switch_device`
+    /// reopens it). Backends without these knobs ignore them.
+    fn set_stream_options(&mut self, _options: StreamOptions) {}
+
+    /// A problem the stream worked around since the last call (exclusive
+    /// mode refused, shared mode used), for the GUI to show once
+    fn take_notice(&mut self) -> Option<AudioDeviceError> {
+        None
+    }
+}
+
+/// How the capture stream is opened
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StreamOptions {
+    /// Frames per buffer (callback period) to ask for; `None` leaves it to
+    /// the driver. A size the driver refuses falls back to its default.
+    pub buffer_frames: Option<u32>,
+    /// Open inputs in exclusive mode (Windows); shared mode with a notice
+    /// if the device refuses
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub exclusive: bool,
+}
+
+/// Buffer sizes offered in Settings → Audio (frames; 128 is ~2.7 ms at 48 kHz)
+pub const CAPTURE_BUFFER_SIZES: [u32; 6] = [64, 128, 256, 512, 1024, 2048];
 
 /// Audio packet containing raw samples and metadata
 #[derive(Clone, Debug)]
@@ -37,28 +95,19 @@ pub struct AudioPacket {
     pub samples: Vec<f32>,
     pub sample_rate: u32,
     pub channels: u16,
-    #[allow(dead_code)]
+    /// When the backend's callback delivered the buffer
     pub timestamp: Instant,
 }
 
 impl AudioPacket {
     /// Convert multi-channel audio to mono by averaging channels
-    /// This returns a buffer each call
-    #[allow(dead_code)]
+    /// This returns a buffer each call; the FFT thread uses
+    /// `to_mono_with_buffer` so it doesn't allocate per packet
+    #[cfg(test)]
     pub fn to_mono(&self) -> Vec<f32> {
-        
-        // If already mono, easy day, return!
-        if self.channels == 1{
-            return self.samples.clone();
-        }
-
-        // If more than one channel, chunk the stream out **by the number of chunks**
-        // then sum and devide accordingly. it averages >= 2 channels of audio.
-        self.samples
-            .chunks(self.channels as usize)
-            .map(|frame| frame.iter().sum::<f32>() / self.channels as f32)
-            .collect()
-    
+        let mut mono = Vec::with_capacity(self.samples.len() / self.channels.max(1) as usize);
+        self.to_mono_with_buffer(&mut mono);
+        mono
     }
 
     /// Convert multi-channel audio to mono by averaging channels
@@ -85,11 +134,114 @@ impl AudioPacket {
         output.extend(downmixed_iter);
     }
 
+    /// Pull the first two channels (front L/R) apart into `left` and `right`.
+    /// Both are left empty for mono packets - there's no stereo to show.
+    pub fn split_stereo_with_buffers(&self, left: &mut Vec<f32>, right: &mut Vec<f32>) {
+        left.clear();
+        right.clear();
+        if self.channels < 2 {
+            return;
+        }
+
+        for frame in self.samples.chunks_exact(self.channels as usize) {
+            left.push(frame[0]);
+            right.push(frame[1]);
+        }
+    }
+
+    /// Loudest sample on any channel (absolute, 1.0 is full scale)
+    pub fn peak(&self) -> f32 {
+        self.samples.iter().fold(0.0, |peak, s| peak.max(s.abs()))
+    }
+
     /// Get the duration of audio in this packet (in seconds)
-    #[allow(dead_code)]
     pub fn duration_secs(&self) -> f32 {
-        let num_samples = self.samples.len() / self.channels as usize;
-        num_samples as f32 / self.sample_rate as f32
+        let num_samples = self.samples.len() / self.channels.max(1) as usize;
+        num_samples as f32 / self.sample_rate.max(1) as f32
+    }
+}
+
+// ============================================================================
+//  Sample conversion
+// ============================================================================
+// Devices deliver whatever their driver prefers. Everything is normalized to
+// f32 in -1.0..=1.0: signed ints divide by their magnitude, unsigned ints are
+// re-centered on their midpoint first, floats pass through (f64 narrowed).
+
+/// A raw sample type we know how to turn into a normalized f32
+pub trait ToF32Sample: Copy {
+    fn to_f32_sample(self) -> f32;
+}
+
+macro_rules! signed_to_f32 {
+    ($($t:ty),*) => {$(
+        impl ToF32Sample for $t {
+            #[inline]
+            fn to_f32_sample(self) -> f32 {
+                (self as f64 / -(<$t>::MIN as f64)) as f32
+            }
+        }
+    )*};
+}
+
+macro_rules! unsigned_to_f32 {
+    ($($t:ty),*) => {$(
+        impl ToF32Sample for $t {
+            #[inline]
+            fn to_f32_sample(self) -> f32 {
+                let mid = (<$t>::MAX as f64 + 1.0) / 2.0;
+                ((self as f64 - mid) / mid) as f32
+            }
+        }
+    )*};
+}
+
+signed_to_f32!(i8, i16, i32, i64);
+unsigned_to_f32!(u8, u16, u32, u64);
+
+impl ToF32Sample for f32 {
+    #[inline]
+    fn to_f32_sample(self) -> f32 {
+        self
+    }
+}
+
+impl ToF32Sample for f64 {
+    #[inline]
+    fn to_f32_sample(self) -> f32 {
+        self as f32
+    }
+}
+
+/// Convert one device buffer into normalized f32 samples
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+pub fn convert_samples<T: ToF32Sample>(data: &[T]) -> Vec<f32> {
+    data.iter().map(|s| s.to_f32_sample()).collect()
+}
+
+/// How samples are packed in a raw little-endian device buffer
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SampleLayout {
+    /// Bytes per sample (the container)
+    pub bytes: usize,
+    /// Bits that carry signal, left-aligned in the container
+    pub valid_bits: u16,
+    pub float: bool,
+}
+
+/// Convert a raw interleaved buffer (exclusive-mode WASAPI hands over bytes
+/// in the device's own format) into normalized f32 samples
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn convert_le_bytes(bytes: &[u8], layout: SampleLayout) -> Vec<f32> {
+    let chunks = bytes.chunks_exact(layout.bytes.max(1));
+    match (layout.bytes, layout.float) {
+        (4, true) => chunks.map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect(),
+        (4, false) => chunks.map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]).to_f32_sample()).collect(),
+        // Packed 24-bit: shifted up into the top of an i32
+        (3, false) => chunks.map(|b| i32::from_le_bytes([0, b[0], b[1], b[2]]).to_f32_sample()).collect(),
+        (2, false) => chunks.map(|b| i16::from_le_bytes([b[0], b[1]]).to_f32_sample()).collect(),
+        _ => vec![0.0; chunks.len()],
     }
 }
 
@@ -128,6 +280,12 @@ pub struct AudioCaptureManager {
 
     /// Handle to the capture thread
     capture_thread: Option<thread::JoinHandle<()>>,
+
+    /// Applied when the stream is (re)opened
+    options: StreamOptions,
+
+    /// Set by the capture thread when it had to fall back, see `take_notice`
+    notice: Arc<Mutex<Option<AudioDeviceError>>>,
 }
 
 #[cfg(not(target_os = "linux"))]
@@ -175,18 +333,22 @@ impl AudioCaptureManager {
             rx,
             shutdown,
             capture_thread: None,
+            options: StreamOptions::default(),
+            notice: Arc::new(Mutex::new(None)),
         })  
     }
         
     /// Start capturing audio
     pub fn start_capture(&mut self) -> Result<(), AudioDeviceError> {
-        let device_info = self.device_info.lock().unwrap().clone();
+        let device_info = self.device_info();
         let tx = self.tx.clone();
         let shutdown = Arc::clone(&self.shutdown);
+        let options = self.options;
+        let notice = Arc::clone(&self.notice);
         
         let handle = thread::spawn(move || {
-            if let Err(e) = Self::capture_loop(&device_info, tx, &shutdown) {
-                tracing::error!("[AudioCapture] Error: {}", e);
+            if let Err(e) = Self::capture_loop(&device_info, options, tx, &shutdown, &notice) {
+                e.on_device(&device_info.name).log();
             }
         });
 
@@ -197,8 +359,10 @@ impl AudioCaptureManager {
     /// The main capture loop
     fn capture_loop(
         device_info: &AudioDeviceInfo,
+        options: StreamOptions,
         tx: Sender<AudioPacket>,
         shutdown: &Arc<AtomicBool>,
+        notice: &Mutex<Option<AudioDeviceError>>,
     ) -> Result<(), AudioDeviceError> {
         
         // ===========================================================================
@@ -220,16 +384,28 @@ impl AudioCaptureManager {
         // Ask the device: "What's your default configuration?"
         // This tells us sample rate, bit depth, channels, etc.
         // Why default? Because we're capturing system audio (not recording input)
-        let config = device
-            .default_output_config()
-            .map_err(|_| AudioDeviceError::ConfigurationError(
-                "Failed to get stream config".to_string(),
-            ))?;
-        
+        // ASIO and WASAPI inputs are real inputs, so they use the input side instead.
+        let is_asio = is_asio_id(&device_info.id);
+        let is_input = is_input_id(&device_info.id);
+        let config = if is_asio || is_input {
+            device.default_input_config()
+        } else {
+            device.default_output_config()
+        }
+        .map_err(|_| AudioDeviceError::ConfigurationError(
+            "Failed to get stream config".to_string(),
+        ))?;
+
+        let mut stream_config = config.config();
+
+        // ASIO: open every channel the interface has, not just the first pair
+        if is_asio && device_info.channels > stream_config.channels {
+            stream_config.channels = device_info.channels;
+        }
 
         // Extract useful info from the config
-        let sample_rate = config.sample_rate().0;    // e.g., 48000 Hz
-        let channels = config.channels();           // e.g., 2 (stereo)
+        let sample_rate = stream_config.sample_rate.0;    // e.g., 48000 Hz
+        let channels = stream_config.channels;           // e.g., 2 (stereo)
 
 
         tracing::info!(
@@ -237,129 +413,69 @@ impl AudioCaptureManager {
             device_info.id, sample_rate, channels
         );
 
-        let stream_config = config.config();
+        // Exclusive mode has its own stream (see `wasapi_exclusive`); if the
+        // device won't have it, the shared-mode stream below takes over
+        #[cfg(target_os = "windows")]
+        if options.exclusive && is_input {
+            match crate::wasapi_exclusive::ExclusiveStream::open(&device_info.name, options.buffer_frames) {
+                Ok(stream) => return stream.run(tx, shutdown),
+                Err(e) => {
+                    tracing::warn!("[AudioCapture] ⚠️ Exclusive mode refused ({}), using shared mode", e);
+                    *notice.lock().unwrap_or_else(|p| p.into_inner()) =
+                        Some(AudioDeviceError::ExclusiveModeUnavailable(e.to_string()));
+                }
+            }
+        }
+        #[cfg(not(target_os = "windows"))]
+        let _ = notice;
 
         // ============================================================================
         // STEP 3: BUILD THE AUDIO STREAM
         // ============================================================================
         //
-        // CPAL supports different audio formats (F32, I16, U16).
-        // We need to handle each format differently because they're different data types.
-        // This is a match statement - pick the right handler based on the sample format.
+        // CPAL hands us whatever sample type the device runs at (f32, i16, i32,
+        // u16, f64, ...). `build_stream_for` is generic over all of them and
+        // converts each buffer to f32 in the -1.0 to +1.0 range before it goes
+        // into an AudioPacket, so the FFT side never has to care.
         //
+        use cpal::SampleFormat as F;
+        let format = config.sample_format();
+        tracing::info!("[AudioCapture] Device sample format: {:?}", format);
+
+        let build = |stream_config: &cpal::StreamConfig| match format {
+            F::F32 => Self::build_stream_for::<f32>(&device, stream_config, tx.clone()),
+            F::F64 => Self::build_stream_for::<f64>(&device, stream_config, tx.clone()),
+            F::I8 => Self::build_stream_for::<i8>(&device, stream_config, tx.clone()),
+            F::I16 => Self::build_stream_for::<i16>(&device, stream_config, tx.clone()),
+            F::I32 => Self::build_stream_for::<i32>(&device, stream_config, tx.clone()),
+            F::I64 => Self::build_stream_for::<i64>(&device, stream_config, tx.clone()),
+            F::U8 => Self::build_stream_for::<u8>(&device, stream_config, tx.clone()),
+            F::U16 => Self::build_stream_for::<u16>(&device, stream_config, tx.clone()),
+            F::U32 => Self::build_stream_for::<u32>(&device, stream_config, tx.clone()),
+            F::U64 => Self::build_stream_for::<u64>(&device, stream_config, tx.clone()),
+            _ => Err(AudioDeviceError::UnsupportedFormat),
+        };
 
-       
-        let stream = match config.sample_format() {
-
-             // ========== CASE 1: F32 (32-bit floating point) ==========
-            // This is the "native" format - samples are already in the -1.0 to +1.0 range
-            cpal::SampleFormat::F32 => {
-                device
-                // Build an input stream with these parameters:
-                // &stream_config    = device configuration (sample rate, channels, etc.)
-                // callback function = what to do when audio data arrives
-                // error handler     = what to do if something goes wrong
-                // None              = no extra platform-specific options
-                    .build_input_stream(
-                        &stream_config,
-
-                        // *** THE CALLBACK FUNCTION ***
-                        // This runs every time the audio system has a buffer of samples ready.
-                        // It happens hundreds of times per second!
-                        // 
-                        // Parameters:
-                        //   data: &[f32]  = raw audio samples from the device
-                        //   _info         = metadata (we ignore it with _)
-                        move |data: &[f32], _| {
-                            // Wrap the raw samples in our AudioPacket struct
-                            let packet = AudioPacket {
-                                samples: data.to_vec(),
-                                sample_rate,
-                                channels,
-                                timestamp: Instant::now(),
-                            };
-
-                            if tx.try_send(packet).is_err() {
-                                // The channel buffer is full - FFT thread can't keep up
-                                // This is expected under heavy load, so we just drop this packet
-                                // (The FFT thread will catch up eventually)
-
-                            }
-                        },
-                        |err| tracing::error!("[AudioCapture] Stream Error: {}", err),
-                        None,
-
-                    )
-                    .map_err(|e| AudioDeviceError::StreamCreationFailed(e.to_string()))?
-            }
-
-            // ========== CASE 2: I16 (16-bit signed integer) ==========
-            // Samples are in the range -32768 to +32767
-            // We need to convert to floating point (-1.0 to +1.0)
-            cpal::SampleFormat::I16 => {
-                device
-                    .build_input_stream(
-                        &stream_config,
-                        move |data: &[i16], _| {
-                            // Convert each i16 sample to f32 in the -1.0 to +1.0 range
-                            // Division by 32768.0 is the magic number for i16 normalization
-                            let float_samples: Vec<f32> = data
-                                .iter()
-                                .map(|&s| s as f32 / 32768.0)
-                                .collect();
-
-                            let packet = AudioPacket {
-                                samples: float_samples,
-                                sample_rate,
-                                channels,
-                                timestamp: Instant::now(),
-                            };
-
-                            if tx.try_send(packet).is_err() {
-                                // The channel buffer is full - FFT thread can't keep up
-                            }
-                        },
-                        |err| tracing::error!("[AudioCapture] Stream Error: {}", err),
-                        None,
-                    )
-                    .map_err(|e| AudioDeviceError::StreamCreationFailed(e.to_string()))?
-            }
-            // ========== CASE 3: U16 (16-bit unsigned integer) ==========
-            // Samples are in the range 0 to 65535 (signed at midpoint 32768)
-            // We need to convert to floating point (-1.0 to +1.0)
-            cpal::SampleFormat::U16 => {
-                device
-                    .build_input_stream(
-                        &stream_config,
-                        move |data: &[u16], _| {
-                            // Convert each u16 sample to f32
-                            // First divide by 32768 to get 0.0-2.0 range
-                            // Then subtract 1.0 to get -1.0 to +1.0 range
-                            let float_samples: Vec<f32> = data
-                                .iter()
-                                .map(|&s| (s as f32 / 32768.0) - 1.0)
-                                .collect();
-
-                            let packet = AudioPacket {
-                                samples: float_samples,
-                                sample_rate,
-                                channels,
-                                timestamp: Instant::now(),
-                            };
-
-                            if tx.try_send(packet).is_err() {
-                                // The channel buffer is full - FFT thread can't keep up
-                                
-                            }
-                        },
-                        |err| tracing::error!("[AudioCapture] Stream Error: {}", err),
-                        None,
-                    )
-                    .map_err(|e| AudioDeviceError::StreamCreationFailed(e.to_string()))?
-            }
-            _ => {
-                return Err(AudioDeviceError::UnsupportedFormat);
+        // Requested buffer size instead of the driver's default. Drivers
+        // that refuse the size get the default buffer and a warning in the log.
+        let stream = if let Some(requested) = options.buffer_frames.filter(|_| !is_asio) {
+            let frames = match config.buffer_size() {
+                cpal::SupportedBufferSize::Range { min, max } => requested.clamp(*min, *max),
+                cpal::SupportedBufferSize::Unknown => requested,
+            };
+            let sized = cpal::StreamConfig { buffer_size: cpal::BufferSize::Fixed(frames), ..stream_config.clone() };
+            match build(&sized) {
+                Ok(stream) => {
+                    tracing::info!("[AudioCapture] Capture buffer: {} frames", frames);
+                    stream
+                }
+                Err(e) => {
+                    tracing::warn!("[AudioCapture] {} frame buffer refused ({}), using the default shared-mode buffer", frames, e);
+                    build(&stream_config)?
+                }
             }
+        } else {
+            build(&stream_config)?
         };
 
         // ============================================================================
@@ -405,15 +521,51 @@ impl AudioCaptureManager {
         Ok(())
     }
 
+    /// Build an input stream for sample type `T`, converting every buffer to f32.
+    ///
+    /// The callback runs hundreds of times per second on the audio thread;
+    /// it wraps the samples in an AudioPacket and tries to send it. If the
+    /// channel is full the FFT thread can't keep up, so the packet is dropped
+    /// (the FFT thread will catch up eventually).
+    fn build_stream_for<T>(
+        device: &cpal::Device,
+        stream_config: &cpal::StreamConfig,
+        tx: Sender<AudioPacket>,
+    ) -> Result<cpal::Stream, AudioDeviceError>
+    where
+        T: cpal::SizedSample + ToF32Sample,
+    {
+        let sample_rate = stream_config.sample_rate.0;
+        let channels = stream_config.channels;
+
+        device
+            .build_input_stream(
+                stream_config,
+                move |data: &[T], _| {
+                    let packet = AudioPacket {
+                        samples: convert_samples(data),
+                        sample_rate,
+                        channels,
+                        timestamp: Instant::now(),
+                    };
+                    let _ = tx.try_send(packet);
+                },
+                |err| tracing::error!("[AudioCapture] Stream Error: {}", err),
+                None,
+            )
+            .map_err(|e| AudioDeviceError::StreamCreationFailed(e.to_string()))
+    }
+
     /// get the packet receiver
     pub fn receiver(&self) -> Receiver<AudioPacket> {
         self.rx.clone()
     }
 
     /// get the current device info
-    #[allow(dead_code)]
     pub fn device_info(&self) -> AudioDeviceInfo {
-        self.device_info.lock().unwrap().clone()
+        // A poisoned lock only means a capture thread panicked mid-write;
+        // the info itself is still a valid snapshot.
+        self.device_info.lock().unwrap_or_else(|p| p.into_inner()).clone()
     }
 
     /// Switch to a different audio device (can be called while capturing)
@@ -430,12 +582,14 @@ impl AudioCaptureManager {
             .into_iter()
             .find(|d| d.id == device_id)
             .ok_or_else(|| AudioDeviceError::DeviceNotFound(device_id.to_string()))?;
+        // Refuse before stopping, so the current device keeps running
+        new_device_info.validate()?;
 
         // Stop the current capture
         self.stop_capture();
 
         // Update the device info
-        *self.device_info.lock().unwrap() = new_device_info;
+        *self.device_info.lock().unwrap_or_else(|p| p.into_inner()) = new_device_info;
 
         // Restart capture with the new device
         self.start_capture()?;
@@ -466,6 +620,45 @@ impl AudioCaptureManager {
 
 }
 
+#[cfg(not(target_os = "linux"))]
+impl CaptureBackend for AudioCaptureManager {
+    fn backend_name(&self) -> &'static str {
+        "cpal"
+    }
+
+    fn list_devices() -> Result<Vec<AudioDeviceInfo>, AudioDeviceError> {
+        AudioCaptureManager::list_devices()
+    }
+
+    fn start_capture(&mut self) -> Result<(), AudioDeviceError> {
+        AudioCaptureManager::start_capture(self)
+    }
+
+    fn stop_capture(&mut self) {
+        AudioCaptureManager::stop_capture(self)
+    }
+
+    fn switch_device(&mut self, device_id: &str) -> Result<(), AudioDeviceError> {
+        AudioCaptureManager::switch_device(self, device_id)
+    }
+
+    fn receiver(&self) -> Receiver<AudioPacket> {
+        AudioCaptureManager::receiver(self)
+    }
+
+    fn device_info(&self) -> AudioDeviceInfo {
+        AudioCaptureManager::device_info(self)
+    }
+
+    fn set_stream_options(&mut self, options: StreamOptions) {
+        self.options = options;
+    }
+
+    fn take_notice(&mut self) -> Option<AudioDeviceError> {
+        self.notice.lock().unwrap_or_else(|p| p.into_inner()).take()
+    }
+}
+
 #[cfg(not(target_os = "linux"))]
 impl Drop for AudioCaptureManager {
     fn drop(&mut self) {
@@ -475,6 +668,77 @@ impl Drop for AudioCaptureManager {
 
 // ========== Tests ============
 
+// Sample conversion is pure math, so it's tested on every platform.
+#[cfg(test)]
+mod sample_tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn test_signed_ints_normalize() {
+        assert!(close(i16::MIN.to_f32_sample(), -1.0));
+        assert!(close(0i16.to_f32_sample(), 0.0));
+        assert!(close(16384i16.to_f32_sample(), 0.5));
+        assert!(close(i32::MIN.to_f32_sample(), -1.0));
+        assert!(close(i8::MAX.to_f32_sample(), 127.0 / 128.0));
+    }
+
+    #[test]
+    fn test_unsigned_ints_are_recentered() {
+        assert!(close(0u16.to_f32_sample(), -1.0));
+        assert!(close(32768u16.to_f32_sample(), 0.0));
+        assert!(close(128u8.to_f32_sample(), 0.0));
+        assert!(close(0u32.to_f32_sample(), -1.0));
+    }
+
+    #[test]
+    fn test_floats_pass_through() {
+        assert_eq!(0.25f32.to_f32_sample(), 0.25);
+        assert!(close((-0.75f64).to_f32_sample(), -0.75));
+        assert_eq!(convert_samples(&[0i16, i16::MIN]), vec![0.0, -1.0]);
+    }
+
+    #[test]
+    fn test_raw_bytes() {
+        let layout = |bytes, valid_bits, float| SampleLayout { bytes, valid_bits, float };
+        assert_eq!(convert_le_bytes(&0.5f32.to_le_bytes(), layout(4, 32, true)), vec![0.5]);
+        assert_eq!(convert_le_bytes(&[0x00, 0x40, 0x00, 0x80], layout(2, 16, false)), vec![0.5, -1.0]);
+        // 24-bit packed and 24-in-32 (low byte unused) both read half scale
+        assert!(close(convert_le_bytes(&[0x00, 0x00, 0x40], layout(3, 24, false))[0], 0.5));
+        assert!(close(convert_le_bytes(&[0x00, 0x00, 0x00, 0x40], layout(4, 24, false))[0], 0.5));
+    }
+
+    #[test]
+    fn test_split_stereo() {
+        let packet = |samples: Vec<f32>, channels| AudioPacket {
+            samples,
+            sample_rate: 48000,
+            channels,
+            timestamp: Instant::now(),
+        };
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+
+        // 5.1: only front L/R are kept
+        packet(vec![0.1, 0.2, 0.0, 0.0, 0.0, 0.0, 0.3, 0.4, 0.0, 0.0, 0.0, 0.0], 6)
+            .split_stereo_with_buffers(&mut left, &mut right);
+        assert_eq!(left, vec![0.1, 0.3]);
+        assert_eq!(right, vec![0.2, 0.4]);
+
+        packet(vec![0.5, 0.5], 1).split_stereo_with_buffers(&mut left, &mut right);
+        assert!(left.is_empty() && right.is_empty());
+    }
+
+    #[test]
+    fn test_peak_sees_every_channel() {
+        // Full scale on the right only, which the mono mix would halve
+        let packet = AudioPacket { samples: vec![0.1, -1.0, 0.2, 0.5], sample_rate: 48000, channels: 2, timestamp: Instant::now() };
+        assert_eq!(packet.peak(), 1.0);
+    }
+}
+
 // AudioCaptureManager unit tests on cpal-using platforms only. The pipewire
 // linux backend has its own tests.
 #[cfg(all(test, not(target_os = "linux")))]
@@ -36,7 +36,7 @@ use spa::param::format::{MediaSubtype, MediaType};
 use spa::param::format_utils;
 use spa::pod::{serialize::PodSerializer, Object, Pod, Value};
 
-use crate::audio_capture::AudioPacket;
+use crate::audio_capture::{AudioPacket, CaptureBackend, StreamOptions};
 use crate::audio_device::{AudioDeviceError, AudioDeviceInfo};
 
 /// Default rate / channel layout we request from PipeWire. PipeWire negotiates
@@ -81,6 +81,8 @@ pub struct AudioCaptureManager {
     shutdown: Arc<AtomicBool>,
     meta: Arc<StreamMeta>,
     capture_thread: Option<thread::JoinHandle<()>>,
+    /// Applied when the stream is (re)opened
+    options: StreamOptions,
 }
 
 impl AudioCaptureManager {
@@ -117,13 +119,14 @@ impl AudioCaptureManager {
                 channels: AtomicU32::new(DEFAULT_CHANNELS),
             }),
             capture_thread: None,
+            options: StreamOptions::default(),
         })
     }
 
-    /// Enumerate every audio source visible to PipeWire — both physical
-    /// `Audio/Source` nodes (mics, line-ins) and `.monitor` sources of every
+    /// Enumerate every audio source visible to PipeWire — physical
+    /// `Audio/Source` nodes (mics, line-ins), `.monitor` sources of every
     /// `Audio/Sink` (i.e. one capture point per output device that mirrors
-    /// what's playing on it).
+    /// what's playing on it), and per-application playback streams.
     ///
     /// Does *not* include a synthetic "default" entry: the GUI dropdown in
     /// `gui/widgets.rs` already prepends a hard-coded "Default System Device"
@@ -157,6 +160,7 @@ impl AudioCaptureManager {
         let shutdown = Arc::clone(&self.shutdown);
         let meta = Arc::clone(&self.meta);
         let selected_device = self.selected_device.clone();
+        let buffer_frames = self.options.buffer_frames;
 
         tracing::info!(
             "[AudioCapture] Starting PipeWire capture: {}",
@@ -164,8 +168,9 @@ impl AudioCaptureManager {
         );
 
         let handle = thread::spawn(move || {
-            if let Err(e) = run_pipewire_loop(tx, shutdown, meta, selected_device) {
-                tracing::error!("[AudioCapture] PipeWire backend error: {}", e);
+            let device_label = selected_device.clone();
+            if let Err(e) = run_pipewire_loop(tx, shutdown, meta, selected_device, buffer_frames) {
+                e.on_device(device_label).log();
             }
         });
 
@@ -205,7 +210,6 @@ impl AudioCaptureManager {
         self.rx.clone()
     }
 
-    #[allow(dead_code)]
     pub fn device_info(&self) -> AudioDeviceInfo {
         let mut info = self.device_info.clone();
         // Surface the actual negotiated rate/channels so consumers see what
@@ -216,6 +220,40 @@ impl AudioCaptureManager {
     }
 }
 
+impl CaptureBackend for AudioCaptureManager {
+    fn backend_name(&self) -> &'static str {
+        "PipeWire"
+    }
+
+    fn list_devices() -> Result<Vec<AudioDeviceInfo>, AudioDeviceError> {
+        AudioCaptureManager::list_devices()
+    }
+
+    fn start_capture(&mut self) -> Result<(), AudioDeviceError> {
+        AudioCaptureManager::start_capture(self)
+    }
+
+    fn stop_capture(&mut self) {
+        AudioCaptureManager::stop_capture(self)
+    }
+
+    fn switch_device(&mut self, device_id: &str) -> Result<(), AudioDeviceError> {
+        AudioCaptureManager::switch_device(self, device_id)
+    }
+
+    fn receiver(&self) -> Receiver<AudioPacket> {
+        AudioCaptureManager::receiver(self)
+    }
+
+    fn device_info(&self) -> AudioDeviceInfo {
+        AudioCaptureManager::device_info(self)
+    }
+
+    fn set_stream_options(&mut self, options: StreamOptions) {
+        self.options = options;
+    }
+}
+
 impl Drop for AudioCaptureManager {
     fn drop(&mut self) {
         self.stop_capture();
@@ -232,6 +270,9 @@ fn default_device_info() -> AudioDeviceInfo {
         sample_rates: vec![DEFAULT_RATE],
         default_sample_rate: DEFAULT_RATE,
         channels: DEFAULT_CHANNELS as u16,
+        channel_counts: vec![DEFAULT_CHANNELS as u16],
+        sample_formats: vec!["f32".to_string()],
+        default_sample_format: "f32".to_string(),
         is_default: true,
     }
 }
@@ -248,6 +289,9 @@ fn synthetic_info_for_id(device_id: &str) -> AudioDeviceInfo {
         sample_rates: vec![DEFAULT_RATE],
         default_sample_rate: DEFAULT_RATE,
         channels: DEFAULT_CHANNELS as u16,
+        channel_counts: vec![DEFAULT_CHANNELS as u16],
+        sample_formats: vec!["f32".to_string()],
+        default_sample_format: "f32".to_string(),
         is_default: false,
     }
 }
@@ -278,8 +322,8 @@ enum ResolvedTarget {
     /// A specific Audio/Sink: target it by name with `STREAM_CAPTURE_SINK`
     /// to grab the monitor side.
     SinkMonitor { node_name: String },
-    /// A specific Audio/Source (mic / line-in / loopback): target it
-    /// by name without `STREAM_CAPTURE_SINK`.
+    /// A specific Audio/Source (mic / line-in / loopback) or an app's
+    /// playback stream: target it by name without `STREAM_CAPTURE_SINK`.
     Source { node_name: String },
 }
 
@@ -291,6 +335,7 @@ fn run_pipewire_loop(
     shutdown: Arc<AtomicBool>,
     meta: Arc<StreamMeta>,
     selected_device: impl Into<String>,
+    buffer_frames: Option<u32>,
 ) -> Result<(), AudioDeviceError> {
     let selected_device = selected_device.into();
     pw::init();
@@ -331,6 +376,12 @@ fn run_pipewire_loop(
         *pw::keys::NODE_NAME => "bespec-capture",
         *pw::keys::NODE_DESCRIPTION => "BeSpec Visualizer Capture",
     };
+    // A latency hint, not a promise: the graph runs at the smallest quantum
+    // any node asks for, within the server's min/max quantum
+    if let Some(frames) = buffer_frames {
+        props.insert(*pw::keys::NODE_LATENCY, format!("{}/{}", frames, DEFAULT_RATE));
+        tracing::info!("[AudioCapture] Requesting {} frame quantum", frames);
+    }
     match &target {
         ResolvedTarget::DefaultSink => {
             // No TARGET_OBJECT: pipewire auto-connects to the current default
@@ -578,6 +629,21 @@ pub enum SourceType {
     Monitor,
     /// A physical Audio/Source (mic, line-in, virtual capture node).
     Input,
+    /// A single application's playback stream (`Stream/Output/Audio`) —
+    /// captures just that app instead of the whole sink mix.
+    App,
+}
+
+impl SourceType {
+    /// Picker ordering: monitors (visualizer-typical default), then
+    /// per-app streams, then physical inputs.
+    pub fn sort_rank(self) -> u8 {
+        match self {
+            SourceType::Monitor => 0,
+            SourceType::App => 1,
+            SourceType::Input => 2,
+        }
+    }
 }
 
 /// One Audio/Source node as we read it from the PipeWire registry. Kept
@@ -603,6 +669,7 @@ impl PipewireSource {
         let tag = match self.source_type {
             SourceType::Monitor => "Output Monitor",
             SourceType::Input => "Input",
+            SourceType::App => "App",
         };
         format!("{} ({})", self.description, tag)
     }
@@ -615,6 +682,22 @@ impl PipewireSource {
 /// arrives, tear everything down. The whole thing typically completes in a
 /// few milliseconds on a local pipewire socket.
 pub fn enumerate_pipewire_sources() -> Result<Vec<AudioDeviceInfo>, AudioDeviceError> {
+    Ok(collect_pipewire_sources()?.into_iter().map(source_to_device_info).collect())
+}
+
+/// Every Audio/Sink as (node name, description), for playback (the
+/// measurement mode's pink noise). These are the synthesized monitor entries
+/// from the same registry walk, minus any real `.monitor` sources.
+pub fn enumerate_pipewire_sinks() -> Result<Vec<(String, String)>, AudioDeviceError> {
+    Ok(collect_pipewire_sources()?
+        .into_iter()
+        .filter(|s| s.source_type == SourceType::Monitor && !s.node_name.ends_with(".monitor"))
+        .map(|s| (s.node_name, s.description))
+        .collect())
+}
+
+/// The registry walk behind `enumerate_pipewire_sources`, sorted for the picker
+fn collect_pipewire_sources() -> Result<Vec<PipewireSource>, AudioDeviceError> {
     use std::cell::RefCell;
     use std::rc::Rc;
 
@@ -702,6 +785,28 @@ pub fn enumerate_pipewire_sources() -> Result<Vec<AudioDeviceInfo>, AudioDeviceE
                         source_type: SourceType::Monitor,
                     });
                 }
+                // Per-application playback streams. Several streams from the
+                // same app (e.g. browser tabs) share a node name, and the
+                // capture stream targets by node name anyway, so keep one
+                // entry per app. `application.name` reads better than the
+                // stream's own description, which is often the track title.
+                "Stream/Output/Audio" => {
+                    let mut list = sources_for_global.borrow_mut();
+                    let already_listed = list.iter().any(|s| {
+                        s.source_type == SourceType::App && s.node_name == node_name
+                    });
+                    if !already_listed {
+                        let description = props
+                            .get("application.name")
+                            .map(|s| s.to_string())
+                            .unwrap_or(description);
+                        list.push(PipewireSource {
+                            node_name: node_name.to_string(),
+                            description,
+                            source_type: SourceType::App,
+                        });
+                    }
+                }
                 _ => {}
             }
         })
@@ -715,25 +820,21 @@ pub fn enumerate_pipewire_sources() -> Result<Vec<AudioDeviceInfo>, AudioDeviceE
     }
 
     // Stable ordering — monitors first (visualizer-typical default), then
-    // inputs, each section sorted by description so the picker is
-    // predictable across runs.
+    // app streams, then inputs, each section sorted by description so the
+    // picker is predictable across runs.
     //
     // We clone out of the Rc<RefCell<>> rather than `try_unwrap`-ing it,
     // because the registry/core listener closures still hold their own clones
     // of the same Rc until they're dropped at end-of-function.
     let mut sources = sources.borrow().clone();
     sources.sort_by(|a, b| {
-        // Monitors before Inputs: reverse-sort on the boolean-equivalent
-        // "is this a monitor" — `SourceType` is ordered via derived Ord? No,
-        // we only derive PartialEq/Eq, so compare explicitly.
-        let a_is_mon = matches!(a.source_type, SourceType::Monitor);
-        let b_is_mon = matches!(b.source_type, SourceType::Monitor);
-        b_is_mon
-            .cmp(&a_is_mon)
+        a.source_type
+            .sort_rank()
+            .cmp(&b.source_type.sort_rank())
             .then_with(|| a.description.cmp(&b.description))
     });
 
-    Ok(sources.into_iter().map(source_to_device_info).collect())
+    Ok(sources)
 }
 
 /// Convert a discovered `PipewireSource` into the cross-platform
@@ -745,6 +846,9 @@ fn source_to_device_info(source: PipewireSource) -> AudioDeviceInfo {
         sample_rates: vec![DEFAULT_RATE],
         default_sample_rate: DEFAULT_RATE,
         channels: DEFAULT_CHANNELS as u16,
+        channel_counts: vec![DEFAULT_CHANNELS as u16],
+        sample_formats: vec!["f32".to_string()],
+        default_sample_format: "f32".to_string(),
         is_default: false,
     }
 }
@@ -800,9 +904,12 @@ fn resolve_target(
             if props.get("node.name") != Some(target_name.as_str()) {
                 return;
             }
+            // App streams are linked to directly, same as a plain source.
             match props.get("media.class") {
                 Some("Audio/Sink") => *found_for_listener.borrow_mut() = Some(true),
-                Some("Audio/Source") => *found_for_listener.borrow_mut() = Some(false),
+                Some("Audio/Source") | Some("Stream/Output/Audio") => {
+                    *found_for_listener.borrow_mut() = Some(false)
+                }
                 _ => {}
             }
         })
@@ -856,6 +963,8 @@ mod tests {
         let inp = src("alsa_input.foo", "Foo Card", SourceType::Input);
         assert_eq!(mon.display_name(), "Foo Card (Output Monitor)");
         assert_eq!(inp.display_name(), "Foo Card (Input)");
+        let app = src("Firefox", "Firefox", SourceType::App);
+        assert_eq!(app.display_name(), "Firefox (App)");
     }
 
     #[test]
@@ -885,24 +994,28 @@ mod tests {
     }
 
     #[test]
-    fn sources_sort_monitors_first_then_alphabetical() {
-        // Mirrors the comparator in `enumerate_pipewire_sources`: monitors
-        // before inputs, each section sorted by description.
+    fn sources_sort_monitors_then_apps_then_inputs() {
+        // Mirrors the comparator in `enumerate_pipewire_sources`: monitors,
+        // then app streams, then inputs, each section sorted by description.
         let mut entries = vec![
             src("alsa_input.b", "Bravo Mic", SourceType::Input),
+            src("spotify", "Spotify", SourceType::App),
             src("alsa_output.a", "Alpha Sink", SourceType::Monitor),
             src("alsa_input.a", "Alpha Mic", SourceType::Input),
+            src("Firefox", "Firefox", SourceType::App),
             src("alsa_output.b", "Bravo Sink", SourceType::Monitor),
         ];
         entries.sort_by(|a, b| {
-            let a_is_mon = matches!(a.source_type, SourceType::Monitor);
-            let b_is_mon = matches!(b.source_type, SourceType::Monitor);
-            b_is_mon
-                .cmp(&a_is_mon)
+            a.source_type
+                .sort_rank()
+                .cmp(&b.source_type.sort_rank())
                 .then_with(|| a.description.cmp(&b.description))
         });
         let order: Vec<&str> = entries.iter().map(|s| s.description.as_str()).collect();
-        assert_eq!(order, vec!["Alpha Sink", "Bravo Sink", "Alpha Mic", "Bravo Mic"]);
+        assert_eq!(
+            order,
+            vec!["Alpha Sink", "Bravo Sink", "Firefox", "Spotify", "Alpha Mic", "Bravo Mic"]
+        );
     }
 }
 
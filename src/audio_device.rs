@@ -17,7 +17,6 @@ pub struct AudioDeviceInfo {
     pub name: String,
 
     ///  Sample Rate(s) supported by this device (Hz)
-    #[allow(dead_code)]
     pub sample_rates: Vec<u32>,
 
     /// Default/recommended sample rate for this device (Hz)
@@ -26,6 +25,16 @@ pub struct AudioDeviceInfo {
     /// Number of output channels
     pub channels: u16,
 
+    /// Channel counts the device can be opened with, ascending
+    pub channel_counts: Vec<u16>,
+
+    /// Sample formats the device reports ("f32", "i16", ...)
+    pub sample_formats: Vec<String>,
+
+    /// Sample format of the default config, the one capture opens it with
+    /// (empty when not reported)
+    pub default_sample_format: String,
+
     /// Whether this is the system default device
     pub is_default: bool,
 }
@@ -41,11 +50,34 @@ impl fmt::Display for AudioDeviceInfo {
     }
 }
 
+/// Sample formats the capture backends can convert to f32
+pub const CAPTURE_FORMATS: [&str; 10] = ["f32", "f64", "i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64"];
+
+impl AudioDeviceInfo {
+    /// Check the device before switching to it, so a device that can't work
+    /// is refused up front instead of leaving the visualizer silent.
+    /// Capture opens the default config, so that's what is checked; other
+    /// formats the device lists don't help. An empty default format means
+    /// "not reported" and passes.
+    pub fn validate(&self) -> Result<(), AudioDeviceError> {
+        if self.default_sample_rate == 0 || self.channels == 0 {
+            return Err(AudioDeviceError::ConfigurationError(format!(
+                "{} reports {} Hz, {} channels",
+                self.name, self.default_sample_rate, self.channels
+            )));
+        }
+        if !self.default_sample_format.is_empty() && !CAPTURE_FORMATS.contains(&self.default_sample_format.as_str()) {
+            return Err(AudioDeviceError::UnsupportedFormat);
+        }
+        Ok(())
+    }
+}
+
 /// Error types for audio device operations
 // dead_code allowed: linux backend only constructs `DeviceNotFound` today;
 // the rest exist for the cpal backends and for forward-compat error reporting.
 #[allow(dead_code)]
-#[derive(thiserror::Error, Debug, Clone)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
 pub enum AudioDeviceError {
     /// No audio output devices were found on the system.
     #[error("No audio devices found")]
@@ -67,11 +99,34 @@ pub enum AudioDeviceError {
     /// pod-serialization failure, a mainloop/context setup failure, etc.
     #[error("PipeWire backend error: {0}")]
     PipeWireError(String),
+    /// Exclusive mode was asked for and refused; the stream runs in shared
+    /// mode instead. Reported, not returned: capture goes on.
+    #[error("Exclusive mode unavailable, using shared mode: {0}")]
+    ExclusiveModeUnavailable(String),
 }
 
 /// Enumerates all available audio output devices and their capabilities
 pub struct AudioDeviceEnumerator;
 
+/// Prefix on the IDs of ASIO input devices (Windows, `asio` feature).
+/// Keeps them apart from WASAPI devices that share the same driver name.
+pub const ASIO_ID_PREFIX: &str = "ASIO: ";
+
+/// True if this device ID points at an ASIO input rather than a loopback output
+pub fn is_asio_id(device_id: &str) -> bool {
+    device_id.starts_with(ASIO_ID_PREFIX)
+}
+
+/// Prefix on the IDs of WASAPI inputs (Windows: microphones, line-ins),
+/// captured directly rather than through a loopback output. Only these can
+/// be opened in exclusive mode.
+pub const INPUT_ID_PREFIX: &str = "Input: ";
+
+/// True if this device ID points at a WASAPI input
+pub fn is_input_id(device_id: &str) -> bool {
+    device_id.starts_with(INPUT_ID_PREFIX)
+}
+
 // ============================================================================
 // Linux: PipeWire-native compatibility shim
 // ============================================================================
@@ -115,6 +170,9 @@ impl AudioDeviceEnumerator {
             sample_rates: vec![48000],
             default_sample_rate: 48000,
             channels: 2,
+            channel_counts: vec![2],
+            sample_formats: vec!["f32".to_string()],
+            default_sample_format: "f32".to_string(),
             is_default: true,
         }
     }
@@ -129,6 +187,7 @@ impl AudioDeviceEnumerator {
         let default_device = host.default_output_device();
 
         let mut devices = Vec::new();
+        let mut seen_names: Vec<String> = Vec::new();
 
         // Iterate through all output devices
         for device in host
@@ -138,31 +197,19 @@ impl AudioDeviceEnumerator {
             // === LINUX SAFETY FILTER ===
             // Filter out raw hardware/plugins before probing to prevent hangs/spam
             if let Ok(name) = device.name() {
-                if cfg!(target_os = "linux") {
-                    // 1. Skip 'dmix' (direct mix) and 'dsnoop' (Direct Snoop)
-                    // These are internal ALSA plugins that often fail configuration queries
-                    // and cause the console spam. YUCK.
-                    if name.starts_with("dmix") || name.starts_with("dsnoop") {
-                        continue;
-                    }
-
-                    // 2. Skip 'hw:' and 'plughw:' (Raw Hardware)
-                    // Accessing these can block the app if the device is busy or sleeping
-                    if name.starts_with("hw:") || name.starts_with("plughw:") {
-                        continue;
-                    }
-
-                    // 3. Skip 'Surround' / 'iec958' (Raw Digital)
-                    // These are rarely useful for loopback and slow to probe.
-                    if name.starts_with("surround") || name.starts_with("iec958") {
-                        continue;
-                    }
-
+                if Self::skip_device(&name) {
+                    continue;
                 }
             }
             match Self::extract_device_info(&device, default_device.as_ref()) {
                 
-                Ok(info) => devices.push(info),
+                Ok(mut info) => {
+                    // cpal has no stable device ID, so the name is the ID.
+                    // Two identical interfaces would collide, so suffix repeats.
+                    info.id = stable_device_id(&info.name, &seen_names);
+                    seen_names.push(info.name.clone());
+                    devices.push(info)
+                },
                 Err(e) => {
                     tracing::error!("[Audio] Failed to enumerate device: {}", e);
                     continue;
@@ -170,6 +217,12 @@ impl AudioDeviceEnumerator {
             }
         }
 
+        #[cfg(target_os = "windows")]
+        devices.extend(Self::enumerate_input_devices(&host));
+
+        #[cfg(all(target_os = "windows", feature = "asio"))]
+        devices.extend(Self::enumerate_asio_devices());
+
         if devices.is_empty() {
             return Err(AudioDeviceError::NoDevicesFound);
         }
@@ -177,6 +230,23 @@ impl AudioDeviceEnumerator {
         Ok(devices)
     }
 
+    /// Devices we never probe (see the safety filter in `enumerate_devices`)
+    fn skip_device(name: &str) -> bool {
+        if !cfg!(target_os = "linux") {
+            return false;
+        }
+        // 1. Skip 'dmix' (direct mix) and 'dsnoop' (Direct Snoop)
+        // These are internal ALSA plugins that often fail configuration queries
+        // and cause the console spam. YUCK.
+        // 2. Skip 'hw:' and 'plughw:' (Raw Hardware)
+        // Accessing these can block the app if the device is busy or sleeping
+        // 3. Skip 'Surround' / 'iec958' (Raw Digital)
+        // These are rarely useful for loopback and slow to probe.
+        ["dmix", "dsnoop", "hw:", "plughw:", "surround", "iec958"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+    }
+
     /// Extract metadata from a device
     fn extract_device_info(
         device: &Device,
@@ -205,8 +275,8 @@ impl AudioDeviceEnumerator {
         let default_sample_rate = config.sample_rate().0;
         let channels = config.channels();
 
-        // Discover supported sample rates
-        let sample_rates = Self::get_sample_rates(device)?;
+        // Discover what else the device supports
+        let (sample_rates, channel_counts, sample_formats) = Self::get_capabilities(device, &config);
 
         Ok(AudioDeviceInfo {
             id: name.clone(),
@@ -214,66 +284,196 @@ impl AudioDeviceEnumerator {
             sample_rates,
             default_sample_rate,
             channels,
+            channel_counts,
+            sample_formats,
+            default_sample_format: format_name(config.sample_format()),
             is_default,
         })
     }
 
-    /// Discover all supported sample rates for a device
-    /// Tests common sample rates and returns those that are supported
-    fn get_sample_rates(device: &Device) -> Result<Vec<u32>, AudioDeviceError> {
-        // 1. Just get the current default. this is what we must use for loopback.
-        // the covers 99.9% of use cases
-        if let Ok(config) = device.default_output_config() {
-            return Ok(vec![config.sample_rate().0]);
-        }
-
-        // 2. Fallback we slow scan!
+    /// Sample rates, channel counts and sample formats a device supports.
+    /// Loopback always runs at the default config, which is always included;
+    /// the rest is for the device details and for validating a pick.
+    fn get_capabilities(device: &Device, default: &cpal::SupportedStreamConfig) -> (Vec<u32>, Vec<u16>, Vec<String>) {
         // Common professional and consumer sample rates
-        let common_rates = vec![
+        const COMMON_RATES: [u32; 11] = [
             8000, 11025, 16000,
             22050, 32000, 44100,
             48000, 88200, 96000,
             176400, 192000,
         ];
 
-        let mut supported_rates = Vec::new();
-
-        for &rate in &common_rates {
-
-            // Check if this configuration is supported
-            let is_supported = device
-                .supported_output_configs()
-                .ok()
-                .and_then(|mut configs| {
-                    configs.find(|c| {
-                        c.channels() == 2 &&
-                        c.min_sample_rate() <= cpal::SampleRate(rate) &&
-                        c.max_sample_rate() >= cpal::SampleRate(rate)
-                    })
-                })
-                .is_some();
-
-            if is_supported {
-                supported_rates.push(rate);
+        let mut rates = vec![default.sample_rate().0];
+        let mut channels = vec![default.channels()];
+        let mut formats = vec![format_name(default.sample_format())];
+
+        if let Ok(configs) = device.supported_output_configs() {
+            for range in configs {
+                channels.push(range.channels());
+                formats.push(format_name(range.sample_format()));
+                rates.extend(COMMON_RATES.iter().copied().filter(|&rate| {
+                    range.min_sample_rate() <= cpal::SampleRate(rate) && range.max_sample_rate() >= cpal::SampleRate(rate)
+                }));
+            }
+        }
+
+        rates.sort_unstable();
+        rates.dedup();
+        channels.sort_unstable();
+        channels.dedup();
+        formats.sort();
+        formats.dedup();
+        (rates, channels, formats)
+    }
+
+    /// WASAPI inputs, listed after the loopback outputs at their default
+    /// config. Never fails: an input that can't be queried is skipped.
+    #[cfg(target_os = "windows")]
+    fn enumerate_input_devices(host: &cpal::Host) -> Vec<AudioDeviceInfo> {
+        let Ok(inputs) = host.input_devices() else {
+            return Vec::new();
+        };
+
+        let mut devices = Vec::new();
+        let mut seen_names: Vec<String> = Vec::new();
+        for device in inputs {
+            let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+            let config = match device.default_input_config() {
+                Ok(config) => config,
+                Err(e) => {
+                    tracing::error!("[Audio] Failed to query input {}: {}", name, e);
+                    continue;
+                }
+            };
+            devices.push(AudioDeviceInfo {
+                id: format!("{}{}", INPUT_ID_PREFIX, stable_device_id(&name, &seen_names)),
+                name: name.clone(),
+                sample_rates: vec![config.sample_rate().0],
+                default_sample_rate: config.sample_rate().0,
+                channels: config.channels(),
+                channel_counts: vec![config.channels()],
+                sample_formats: vec![format_name(config.sample_format())],
+                default_sample_format: format_name(config.sample_format()),
+                is_default: false,
+            });
+            seen_names.push(name);
+        }
+        devices
+    }
+
+    /// Look up a WASAPI input by its prefixed ID
+    fn get_input_device(device_id: &str) -> Result<Device, AudioDeviceError> {
+        #[cfg(target_os = "windows")]
+        {
+            let id = device_id.trim_start_matches(INPUT_ID_PREFIX);
+            let inputs = cpal::default_host()
+                .input_devices()
+                .map_err(|_| AudioDeviceError::NoDevicesFound)?;
+            let mut seen_names: Vec<String> = Vec::new();
+            for device in inputs {
+                if let Ok(name) = device.name() {
+                    if stable_device_id(&name, &seen_names) == id {
+                        return Ok(device);
+                    }
+                    seen_names.push(name);
+                }
+            }
+        }
+        Err(AudioDeviceError::DeviceNotFound(device_id.to_string()))
+    }
+
+    /// ASIO drivers are inputs only (no loopback), so they're listed
+    /// separately with every channel the interface exposes.
+    /// Never fails: a missing/broken ASIO runtime just means no entries.
+    #[cfg(all(target_os = "windows", feature = "asio"))]
+    fn enumerate_asio_devices() -> Vec<AudioDeviceInfo> {
+        let host = match cpal::host_from_id(cpal::HostId::Asio) {
+            Ok(host) => host,
+            Err(e) => {
+                tracing::warn!("[Audio] ASIO host unavailable: {}", e);
+                return Vec::new();
             }
+        };
+        let Ok(inputs) = host.input_devices() else {
+            return Vec::new();
+        };
+
+        let mut devices = Vec::new();
+        for device in inputs {
+            let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+            let config = match device.default_input_config() {
+                Ok(config) => config,
+                Err(e) => {
+                    tracing::error!("[Audio] Failed to query ASIO device {}: {}", name, e);
+                    continue;
+                }
+            };
+
+            let max_channels = device
+                .supported_input_configs()
+                .map(|configs| configs.map(|c| c.channels()).max().unwrap_or(0))
+                .unwrap_or(0)
+                .max(config.channels());
+
+            devices.push(AudioDeviceInfo {
+                id: format!("{}{}", ASIO_ID_PREFIX, name),
+                name,
+                sample_rates: vec![config.sample_rate().0],
+                default_sample_rate: config.sample_rate().0,
+                channels: max_channels,
+                channel_counts: vec![max_channels],
+                sample_formats: vec![format_name(config.sample_format())],
+                default_sample_format: format_name(config.sample_format()),
+                is_default: false,
+            });
         }
+        devices
+    }
 
-        Ok(supported_rates)
+    /// Look up an ASIO input by its prefixed ID
+    fn get_asio_device(device_id: &str) -> Result<Device, AudioDeviceError> {
+        #[cfg(all(target_os = "windows", feature = "asio"))]
+        {
+            let name = device_id.trim_start_matches(ASIO_ID_PREFIX);
+            let host = cpal::host_from_id(cpal::HostId::Asio)
+                .map_err(|e| AudioDeviceError::ConfigurationError(e.to_string()))?;
+            let found = host
+                .input_devices()
+                .map_err(|_| AudioDeviceError::NoDevicesFound)?
+                .find(|d| d.name().map(|n| n == name).unwrap_or(false));
+            if let Some(device) = found {
+                return Ok(device);
+            }
+        }
+        Err(AudioDeviceError::DeviceNotFound(device_id.to_string()))
     }
 
     /// Get a specific device by its unique ID.
     #[must_use = "device lookup should be checked for errors"]
     pub fn get_device_by_id(device_id: &str) -> Result<Device, AudioDeviceError> {
+        if is_asio_id(device_id) {
+            return Self::get_asio_device(device_id);
+        }
+        if is_input_id(device_id) {
+            return Self::get_input_device(device_id);
+        }
+
         let host = cpal::default_host();
         let devices = host
             .output_devices()
             .map_err(|_| AudioDeviceError::NoDevicesFound)?;
 
+        // Walk the list the same way `enumerate_devices` does so "#2" suffixes line up
+        let mut seen_names: Vec<String> = Vec::new();
         for device in devices {
             if let Ok(name) = device.name() {
-                if name == device_id {
+                if Self::skip_device(&name) {
+                    continue;
+                }
+                if stable_device_id(&name, &seen_names) == device_id {
                     return Ok(device);
                 }
+                seen_names.push(name);
             }
         }
 
@@ -295,6 +495,72 @@ impl AudioDeviceEnumerator {
 }
 
 
+/// "f32", "i16", ... as listed in `CAPTURE_FORMATS`
+#[cfg(not(target_os = "linux"))]
+fn format_name(format: cpal::SampleFormat) -> String {
+    format!("{:?}", format).to_lowercase()
+}
+
+/// ID for a device given the names already seen during this enumeration.
+/// First "Speakers" -> "Speakers", second -> "Speakers #2", and so on.
+/// Keeps plain names as IDs so existing configs keep working.
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+fn stable_device_id(name: &str, seen_names: &[String]) -> String {
+    let repeats = seen_names.iter().filter(|n| *n == name).count();
+    if repeats == 0 {
+        name.to_string()
+    } else {
+        format!("{} #{}", name, repeats + 1)
+    }
+}
+
+#[cfg(test)]
+mod id_tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_names_get_distinct_ids() {
+        let mut seen = Vec::new();
+        let mut ids = Vec::new();
+        for name in ["Speakers", "USB Audio", "Speakers", "Speakers"] {
+            ids.push(stable_device_id(name, &seen));
+            seen.push(name.to_string());
+        }
+        assert_eq!(ids, vec!["Speakers", "USB Audio", "Speakers #2", "Speakers #3"]);
+    }
+
+    #[test]
+    fn test_validate_refuses_unusable_devices() {
+        let mut info = AudioDeviceInfo {
+            id: "USB".to_string(),
+            name: "USB".to_string(),
+            sample_rates: vec![48000],
+            default_sample_rate: 48000,
+            channels: 2,
+            channel_counts: vec![2],
+            sample_formats: vec!["i16".to_string(), "i24".to_string()],
+            default_sample_format: "i24".to_string(),
+            is_default: false,
+        };
+        // Capture opens the default format; that i16 is listed too doesn't help
+        assert_eq!(info.validate(), Err(AudioDeviceError::UnsupportedFormat));
+        info.default_sample_format = "i16".to_string();
+        assert!(info.validate().is_ok());
+        // Format not reported: nothing to go on, let it through
+        info.default_sample_format.clear();
+        assert!(info.validate().is_ok());
+        info.channels = 0;
+        assert!(matches!(info.validate(), Err(AudioDeviceError::ConfigurationError(_))));
+    }
+
+    #[test]
+    fn test_asio_ids_are_namespaced() {
+        let id = format!("{}{}", ASIO_ID_PREFIX, "Focusrite USB ASIO");
+        assert!(is_asio_id(&id));
+        assert!(!is_asio_id("Focusrite USB ASIO"));
+    }
+}
+
 // ================== Tests ===================
 
 // Cpal-backed enumerator tests are non-linux only — the linux backend has no
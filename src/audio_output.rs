@@ -0,0 +1,304 @@
+//! Audio playback, for the measurement mode's pink noise (`measurement`) and
+//! the signal generator (`signal_gen`).
+//!
+//! The one place BeSpec makes sound. Anything that implements `Source` can be
+//! played; playback runs on its own thread for as long as the `Player` lives:
+//!
+//! - Linux: a PipeWire playback stream, to the default sink or the sink
+//!   named by the output device id (same node names the capture side uses).
+//! - Windows / macOS: a cpal output stream on the chosen output device.
+//!
+//! Every channel gets the same signal.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::measurement::PinkNoise;
+
+/// How often the playback thread checks whether it should stop
+const STOP_POLL: Duration = Duration::from_millis(50);
+
+/// Something to play, one mono sample at a time (about -1..1). Called from
+/// the audio callback, so no locking or allocating.
+pub trait Source: Send + 'static {
+    fn next_sample(&mut self, sample_rate: f32) -> f32;
+}
+
+impl Source for PinkNoise {
+    fn next_sample(&mut self, _sample_rate: f32) -> f32 {
+        PinkNoise::next_sample(self)
+    }
+}
+
+/// Linear gain shared with the audio callback, as f32 bits
+type Gain = Arc<AtomicU32>;
+
+/// Playing source. Dropping it stops.
+pub struct Player {
+    stop: Arc<AtomicBool>,
+    gain: Gain,
+    /// Set by the playback thread when the output couldn't be played
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl Player {
+    /// Change the level (dBFS) without restarting the stream
+    pub fn set_level(&self, level_db: f32) {
+        self.gain.store(db_to_gain(level_db).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Why playback stopped early, if it did
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().ok().and_then(|e| e.clone())
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Start playing `source` at `level_db` (dBFS) on `device_id` (empty for the
+/// system default)
+pub fn start(device_id: &str, level_db: f32, source: impl Source) -> Player {
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread_stop = stop.clone();
+    let device_id = device_id.to_string();
+    let gain: Gain = Arc::new(AtomicU32::new(db_to_gain(level_db).to_bits()));
+    let thread_gain = gain.clone();
+    let source: Box<dyn Source> = Box::new(source);
+    let error = Arc::new(Mutex::new(None));
+    let thread_error = error.clone();
+    let spawned = std::thread::Builder::new().name("bespec-output".to_string()).spawn(move || {
+        tracing::info!("[Output] Playing on {}", if device_id.is_empty() { "the default output" } else { &device_id });
+        if let Err(e) = imp::play(&device_id, source, thread_gain, thread_stop) {
+            tracing::error!("[Output] Playback failed: {}", e);
+            if let Ok(mut error) = thread_error.lock() {
+                *error = Some(e);
+            }
+        }
+        tracing::info!("[Output] Stopped");
+    });
+    if let Err(e) = spawned {
+        tracing::error!("[Output] Failed to start playback thread: {}", e);
+        if let Ok(mut slot) = error.lock() {
+            *slot = Some(e.to_string());
+        }
+    }
+    Player { stop, gain, error }
+}
+
+fn db_to_gain(level_db: f32) -> f32 {
+    10f32.powf(level_db.min(0.0) / 20.0)
+}
+
+/// Output devices as (id, name)
+pub fn list_devices() -> Vec<(String, String)> {
+    imp::list_devices()
+}
+
+/// Pink noise seeded from the clock, so two players don't play the same
+/// sequence
+pub fn pink_noise() -> PinkNoise {
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(1, |d| d.as_nanos() as u64);
+    PinkNoise::new(seed)
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use pipewire as pw;
+    use pw::properties::properties;
+    use pw::spa;
+    use spa::param::audio::{AudioFormat, AudioInfoRaw};
+    use spa::pod::{serialize::PodSerializer, Object, Pod, Value};
+
+    use super::Source;
+
+    const RATE: u32 = 48000;
+    const CHANNELS: usize = 2;
+    const SAMPLE_SIZE: usize = std::mem::size_of::<f32>();
+
+    struct Playback {
+        source: Box<dyn Source>,
+        gain: super::Gain,
+    }
+
+    pub fn play(device_id: &str, source: Box<dyn Source>, gain: super::Gain, stop: Arc<AtomicBool>) -> Result<(), String> {
+        pw::init();
+        let mainloop = pw::main_loop::MainLoopRc::new(None).map_err(|e| format!("MainLoopRc::new failed: {e}"))?;
+        let context = pw::context::ContextRc::new(&mainloop, None).map_err(|e| format!("ContextRc::new failed: {e}"))?;
+        let core = context.connect_rc(None).map_err(|e| format!("connect_rc failed: {e}"))?;
+
+        let mut props = properties! {
+            *pw::keys::MEDIA_TYPE => "Audio",
+            *pw::keys::MEDIA_CATEGORY => "Playback",
+            *pw::keys::MEDIA_ROLE => "Production",
+            *pw::keys::NODE_NAME => "bespec-output",
+            *pw::keys::NODE_DESCRIPTION => "BeSpec Test Signal",
+        };
+        if !device_id.is_empty() {
+            props.insert(*pw::keys::TARGET_OBJECT, device_id.to_string());
+        }
+
+        let stream = pw::stream::StreamBox::new(&core, "bespec-output", props)
+            .map_err(|e| format!("StreamBox::new failed: {e}"))?;
+
+        let _listener = stream
+            .add_local_listener_with_user_data(Playback { source, gain })
+            .process(|stream, state| {
+                let Some(mut buffer) = stream.dequeue_buffer() else { return };
+                let datas = buffer.datas_mut();
+                let Some(data) = datas.first_mut() else { return };
+                let stride = SAMPLE_SIZE * CHANNELS;
+                let gain = f32::from_bits(state.gain.load(Ordering::Relaxed));
+                let frames = match data.data() {
+                    Some(bytes) => {
+                        let frames = bytes.len() / stride;
+                        for frame in bytes.chunks_exact_mut(stride) {
+                            let sample = (state.source.next_sample(RATE as f32) * gain).to_le_bytes();
+                            for channel in frame.chunks_exact_mut(SAMPLE_SIZE) {
+                                channel.copy_from_slice(&sample);
+                            }
+                        }
+                        frames
+                    }
+                    None => 0,
+                };
+                let chunk = data.chunk_mut();
+                *chunk.offset_mut() = 0;
+                *chunk.stride_mut() = stride as _;
+                *chunk.size_mut() = (stride * frames) as _;
+            })
+            .register()
+            .map_err(|e| format!("listener register failed: {e}"))?;
+
+        let mut audio_info = AudioInfoRaw::new();
+        audio_info.set_format(AudioFormat::F32LE);
+        audio_info.set_rate(RATE);
+        audio_info.set_channels(CHANNELS as u32);
+        let pod_obj = Object {
+            type_: pw::spa::utils::SpaTypes::ObjectParamFormat.as_raw(),
+            id: pw::spa::param::ParamType::EnumFormat.as_raw(),
+            properties: audio_info.into(),
+        };
+        let pod_bytes: Vec<u8> = PodSerializer::serialize(std::io::Cursor::new(Vec::new()), &Value::Object(pod_obj))
+            .map_err(|e| format!("pod serialize failed: {e}"))?
+            .0
+            .into_inner();
+        let mut params = [Pod::from_bytes(&pod_bytes).ok_or("failed to parse serialized format pod")?];
+
+        stream
+            .connect(
+                spa::utils::Direction::Output,
+                None,
+                pw::stream::StreamFlags::AUTOCONNECT | pw::stream::StreamFlags::MAP_BUFFERS | pw::stream::StreamFlags::RT_PROCESS,
+                &mut params,
+            )
+            .map_err(|e| format!("stream.connect failed: {e}"))?;
+
+        // Same shutdown polling as the capture loop
+        let mainloop_for_timer = mainloop.clone();
+        let timer = mainloop.loop_().add_timer(move |_expirations| {
+            if stop.load(Ordering::Relaxed) {
+                mainloop_for_timer.quit();
+            }
+        });
+        timer
+            .update_timer(Some(super::STOP_POLL), Some(super::STOP_POLL))
+            .into_result()
+            .map_err(|e| format!("update_timer failed: {e:?}"))?;
+
+        mainloop.run();
+        Ok(())
+    }
+
+    /// Sinks from the PipeWire registry (the capture list's output monitors
+    /// are the sinks themselves)
+    pub fn list_devices() -> Vec<(String, String)> {
+        match crate::audio_capture_pw::enumerate_pipewire_sinks() {
+            Ok(sinks) => sinks,
+            Err(e) => {
+                tracing::warn!("[Output] Couldn't list outputs: {}", e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use cpal::Sample;
+
+    use super::Source;
+    use crate::audio_device::{is_asio_id, is_input_id, AudioDeviceEnumerator};
+
+    pub fn play(device_id: &str, source: Box<dyn Source>, gain: super::Gain, stop: Arc<AtomicBool>) -> Result<(), String> {
+        let device = if device_id.is_empty() {
+            cpal::default_host().default_output_device().ok_or("no default output device")?
+        } else {
+            AudioDeviceEnumerator::get_device_by_id(device_id).map_err(|e| e.to_string())?
+        };
+        let config = device.default_output_config().map_err(|e| e.to_string())?;
+        let stream_config = config.config();
+
+        use cpal::SampleFormat as F;
+        let stream = match config.sample_format() {
+            F::F32 => build::<f32>(&device, &stream_config, source, gain),
+            F::I16 => build::<i16>(&device, &stream_config, source, gain),
+            F::U16 => build::<u16>(&device, &stream_config, source, gain),
+            F::I32 => build::<i32>(&device, &stream_config, source, gain),
+            other => return Err(format!("unsupported sample format {:?}", other)),
+        }?;
+        stream.play().map_err(|e| e.to_string())?;
+
+        // The stream plays for as long as it's alive on this thread
+        while !stop.load(Ordering::Relaxed) {
+            std::thread::sleep(super::STOP_POLL);
+        }
+        Ok(())
+    }
+
+    fn build<T>(device: &cpal::Device, config: &cpal::StreamConfig, mut source: Box<dyn Source>, gain: super::Gain) -> Result<cpal::Stream, String>
+    where
+        T: cpal::SizedSample + cpal::FromSample<f32>,
+    {
+        let channels = (config.channels as usize).max(1);
+        let rate = config.sample_rate.0 as f32;
+        device
+            .build_output_stream(
+                config,
+                move |data: &mut [T], _| {
+                    let gain = f32::from_bits(gain.load(Ordering::Relaxed));
+                    for frame in data.chunks_mut(channels) {
+                        let sample = T::from_sample(source.next_sample(rate) * gain);
+                        frame.fill(sample);
+                    }
+                },
+                |e| tracing::warn!("[Output] Stream error: {}", e),
+                None,
+            )
+            .map_err(|e| e.to_string())
+    }
+
+    /// The same output devices capture uses, minus ASIO and WASAPI inputs
+    pub fn list_devices() -> Vec<(String, String)> {
+        match AudioDeviceEnumerator::enumerate_devices() {
+            Ok(devices) => devices.into_iter().filter(|d| !is_asio_id(&d.id) && !is_input_id(&d.id)).map(|d| (d.id, d.name)).collect(),
+            Err(e) => {
+                tracing::warn!("[Output] Couldn't list outputs: {}", e);
+                Vec::new()
+            }
+        }
+    }
+}
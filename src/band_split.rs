@@ -0,0 +1,117 @@
+//! Cheap bass/treble splitter for coloring the oscilloscope trace.
+//!
+//! Two one-pole filters pull a bass band (below `BASS_HZ`) and a treble band
+//! (above `TREBLE_HZ`) out of the mono signal, and a short envelope follower
+//! tracks the level of each. Every sample then gets a tint in 0..=1:
+//! 0 when bass dominates, 1 when treble does. The GUI blends the low/high
+//! colors with it.
+//!
+//! A few multiplies per sample, no FFT, and state carries across packets so
+//! the tint doesn't jump at block boundaries.
+
+/// Upper edge of the bass band (Hz)
+const BASS_HZ: f32 = 250.0;
+
+/// Lower edge of the treble band (Hz)
+const TREBLE_HZ: f32 = 2500.0;
+
+/// Envelope time constant: fast enough to follow drum hits, slow enough
+/// that the trace doesn't flicker within one waveform cycle
+const ENVELOPE_MS: f32 = 8.0;
+
+pub struct BandSplitter {
+    sample_rate: u32,
+    bass_coeff: f32,
+    treble_coeff: f32,
+    envelope_coeff: f32,
+
+    bass_lp: f32,
+    treble_lp: f32,
+    bass_env: f32,
+    treble_env: f32,
+}
+
+/// One-pole smoothing coefficient for a cutoff / time constant
+fn one_pole(cutoff_hz: f32, sample_rate: u32) -> f32 {
+    1.0 - (-2.0 * std::f32::consts::PI * cutoff_hz / sample_rate as f32).exp()
+}
+
+impl BandSplitter {
+    pub fn new() -> Self {
+        Self {
+            sample_rate: 0,
+            bass_coeff: 0.0,
+            treble_coeff: 0.0,
+            envelope_coeff: 0.0,
+            bass_lp: 0.0,
+            treble_lp: 0.0,
+            bass_env: 0.0,
+            treble_env: 0.0,
+        }
+    }
+
+    fn configure(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        self.bass_coeff = one_pole(BASS_HZ, sample_rate);
+        self.treble_coeff = one_pole(TREBLE_HZ, sample_rate);
+        self.envelope_coeff = 1.0 - (-1000.0 / (ENVELOPE_MS * sample_rate as f32)).exp();
+    }
+
+    /// Tint for every sample of mono `input` at `sample_rate` into `output`
+    /// (cleared first): 0.0 = bass, 1.0 = treble, 0.5 when silent or balanced.
+    pub fn process(&mut self, input: &[f32], sample_rate: u32, output: &mut Vec<f32>) {
+        output.clear();
+        if sample_rate == 0 {
+            return;
+        }
+        if sample_rate != self.sample_rate {
+            self.configure(sample_rate);
+        }
+
+        for &x in input {
+            // Bass: low-passed signal. Treble: what's left above the second cutoff.
+            self.bass_lp += (x - self.bass_lp) * self.bass_coeff;
+            self.treble_lp += (x - self.treble_lp) * self.treble_coeff;
+            let bass = self.bass_lp.abs();
+            let treble = (x - self.treble_lp).abs();
+
+            self.bass_env += (bass - self.bass_env) * self.envelope_coeff;
+            self.treble_env += (treble - self.treble_env) * self.envelope_coeff;
+
+            let total = self.bass_env + self.treble_env;
+            output.push(if total > 1e-6 { self.treble_env / total } else { 0.5 });
+        }
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * std::f32::consts::PI * freq * i as f32 / rate as f32).sin() * 0.5)
+            .collect()
+    }
+
+    fn settled_tint(freq: f32) -> f32 {
+        let mut splitter = BandSplitter::new();
+        let mut tint = Vec::new();
+        splitter.process(&sine(freq, 48000, 4800), 48000, &mut tint);
+        tint[tint.len() - 1000..].iter().sum::<f32>() / 1000.0
+    }
+
+    #[test]
+    fn test_tint_follows_band() {
+        assert!(settled_tint(60.0) < 0.2, "bass should tint low");
+        assert!(settled_tint(8000.0) > 0.8, "treble should tint high");
+
+        let mut splitter = BandSplitter::new();
+        let mut tint = Vec::new();
+        splitter.process(&[0.0; 64], 48000, &mut tint);
+        assert_eq!(tint.len(), 64);
+        assert!(tint.iter().all(|&t| t == 0.5));
+    }
+}
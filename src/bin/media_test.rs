@@ -4,6 +4,11 @@
 #[path = "../media/mod.rs"]
 mod media;
 
+// media/ downloads album art through the shared request manager
+#[allow(dead_code)]
+#[path = "../net.rs"]
+mod net;
+
 use std::io::{self, Write};
 use std::sync::Arc;
 use std::time::Duration;
@@ -26,6 +31,10 @@ fn print_track_info(info: &MediaTrackInfo) {
         None => println!("   Art:    [No Image Data]"),
     }
 
+    if let Some(position) = &info.position {
+        println!("   Time:   {} / {}", media::format_position(position.position), media::format_position(position.duration));
+    }
+
     println!("   State:  {}", if info.is_playing { "▶ Playing" } else { "⏸ Paused" });
 }
 
@@ -0,0 +1,96 @@
+//! Clip indicator: lights up when the input reaches full scale, and stays lit
+//! for a hold time so a single clipped transient can't be missed.
+//!
+//! The FFT thread checks every `AudioPacket` before it's mixed down to mono
+//! (`AudioPacket::peak`), since averaging the channels would hide a clip on
+//! just one of them. Unlike the gain advisor's clipping hint, which waits
+//! for a pattern over seconds, this reacts to one sample over the line,
+//! for someone watching a mic chain while they set levels.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum ClipStyle {
+    /// A red light in the top right corner
+    Led,
+    /// All bars turn red
+    Bars,
+}
+
+impl ClipStyle {
+    pub const ALL: [Self; 2] = [Self::Led, Self::Bars];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Led => "Corner Light",
+            Self::Bars => "Red Bars",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct ClipSettings {
+    pub enabled: bool,
+    pub style: ClipStyle,
+    /// Sample level (dBFS) that counts as clipping
+    pub threshold_db: f32,
+    /// How long the indicator stays lit after the last clip
+    pub hold_ms: f32,
+}
+
+impl Default for ClipSettings {
+    fn default() -> Self {
+        Self { enabled: true, style: ClipStyle::Led, threshold_db: -0.1, hold_ms: 1500.0 }
+    }
+}
+
+/// When the input last went over, kept in `SharedState`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ClipIndicator {
+    last_over: Option<Instant>,
+}
+
+impl ClipIndicator {
+    /// Check a packet's peak sample (linear, 0..1 is in range)
+    pub fn note(&mut self, peak: f32, settings: &ClipSettings, now: Instant) {
+        if settings.enabled && peak >= 10f32.powf(settings.threshold_db / 20.0) {
+            self.last_over = Some(now);
+        }
+    }
+
+    /// Whether to show the indicator
+    pub fn lit(&self, settings: &ClipSettings, now: Instant) -> bool {
+        let hold = Duration::from_secs_f32(settings.hold_ms.max(0.0) / 1000.0);
+        settings.enabled && self.last_over.is_some_and(|t| now.duration_since(t) <= hold)
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lights_on_full_scale_and_holds() {
+        let settings = ClipSettings::default();
+        let mut clip = ClipIndicator::default();
+        let now = Instant::now();
+
+        clip.note(0.9, &settings, now);
+        assert!(!clip.lit(&settings, now));
+
+        clip.note(1.0, &settings, now);
+        assert!(clip.lit(&settings, now + Duration::from_millis(1400)));
+        assert!(!clip.lit(&settings, now + Duration::from_millis(1600)));
+
+        // A lower threshold catches the near misses too
+        let early = ClipSettings { threshold_db: -3.0, ..settings };
+        clip.note(0.9, &early, now);
+        assert!(clip.lit(&early, now));
+        assert!(!clip.lit(&ClipSettings { enabled: false, ..early }, now));
+    }
+}
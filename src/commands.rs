@@ -0,0 +1,308 @@
+//! Commands that outside controllers can send, shared by the HTTP endpoint
+//! (`http_api`) and the Stream Deck socket (`stream_deck`), so a button does
+//! the same thing whichever way it arrives.
+//!
+//! On the wire a command is a JSON object tagged by `cmd`:
+//!
+//!   {"cmd": "status"}
+//!   {"cmd": "levels", "bands": 4}
+//!   {"cmd": "features"}
+//!   {"cmd": "set_profile", "name": "Neon"}
+//!   {"cmd": "set_colors", "name": "Sunset"}
+//!   {"cmd": "next_profile"}       / "prev_profile"
+//!   {"cmd": "next_colors"}        / "prev_colors"
+//!   {"cmd": "next_mode"}          / "prev_mode"
+//!   {"cmd": "toggle_ghost_mode"}
+//!   {"cmd": "set_ghost_mode", "on": true}
+//!
+//! Every command answers with the resulting status, except `levels` and
+//! `features` which answer with the levels and the spectral features.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::shared_state::{ColorProfile, ColorRef, SharedState, VisualMode, VisualProfile};
+
+/// Most bands `levels` folds the bars into (a Stream Deck XL has 32 keys)
+pub const MAX_BANDS: usize = 32;
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Command {
+    Status,
+    Levels {
+        #[serde(default = "default_bands")]
+        bands: usize,
+    },
+    Features,
+    SetProfile { name: String },
+    SetColors { name: String },
+    NextProfile,
+    PrevProfile,
+    NextColors,
+    PrevColors,
+    NextMode,
+    PrevMode,
+    ToggleGhostMode,
+    SetGhostMode { on: bool },
+}
+
+/// Order `next_mode` / `prev_mode` (and the `M` key) cycle through
+pub const MODES: [VisualMode; 7] = [
+    VisualMode::SolidBars,
+    VisualMode::SegmentedBars,
+    VisualMode::DotMatrix,
+    VisualMode::LineSpectrum,
+    VisualMode::Oscilloscope,
+    VisualMode::Mountain,
+    VisualMode::Spectrogram,
+];
+
+fn default_bands() -> usize {
+    4
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CommandError {
+    /// No preset with that name
+    NotFound(String),
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(name) => write!(f, "no preset named '{}'", name),
+        }
+    }
+}
+
+/// Run `command` against the app state
+pub fn execute(command: &Command, state: &mut SharedState) -> Result<Value, CommandError> {
+    match command {
+        Command::Status => {}
+        Command::Levels { bands } => {
+            let levels = fold_levels(&state.visualization.bars, state.config.noise_floor(), *bands);
+            return Ok(json!({ "levels": levels }));
+        }
+        Command::Features => return Ok(features(state)),
+        Command::SetProfile { name } => {
+            let profile = profile_list(state).into_iter().find(|p| p.name == *name);
+            let Some(profile) = profile else { return Err(CommandError::NotFound(name.clone())) };
+            state.config.profile = profile;
+        }
+        Command::SetColors { name } => {
+            if !color_preset_names(state).contains(name) {
+                return Err(CommandError::NotFound(name.clone()));
+            }
+            set_colors(state, name.clone());
+        }
+        Command::NextProfile | Command::PrevProfile => {
+            let delta = if *command == Command::NextProfile { 1 } else { -1 };
+            let profiles = profile_list(state);
+            let current = profiles.iter().position(|p| p.name == state.config.profile.name);
+            if let Some(next) = cycle(profiles.len(), current, delta) {
+                state.config.profile = profiles[next].clone();
+            }
+        }
+        Command::NextColors | Command::PrevColors => {
+            let delta = if *command == Command::NextColors { 1 } else { -1 };
+            let names = color_preset_names(state);
+            let current = match &state.config.profile.color_link {
+                ColorRef::Preset(name) => names.iter().position(|n| n == name),
+                ColorRef::Custom(_) => None,
+            };
+            if let Some(next) = cycle(names.len(), current, delta) {
+                set_colors(state, names[next].clone());
+            }
+        }
+        Command::NextMode | Command::PrevMode => {
+            let delta = if *command == Command::NextMode { 1 } else { -1 };
+            state.config.profile.visual_mode = step_mode(state.config.profile.visual_mode, delta);
+        }
+        Command::ToggleGhostMode => state.config.window_locked = !state.config.window_locked,
+        Command::SetGhostMode { on } => state.config.window_locked = *on,
+    }
+    Ok(status(state))
+}
+
+fn set_colors(state: &mut SharedState, name: String) {
+    state.config.profile.color_link = ColorRef::Preset(name);
+    state.config.profile.background = None;
+}
+
+/// User profiles first, then the built-ins (same order as the settings combo)
+pub fn profile_list(state: &SharedState) -> Vec<VisualProfile> {
+    let mut profiles = state.user_visual_presets.clone();
+    profiles.extend(VisualProfile::built_in());
+    profiles
+}
+
+/// User color presets first, then the built-ins
+pub fn color_preset_names(state: &SharedState) -> Vec<String> {
+    let mut names: Vec<String> = state.user_color_presets.iter().map(|p| p.name.clone()).collect();
+    names.extend(ColorProfile::built_in().into_iter().map(|p| p.name));
+    names
+}
+
+/// Index `delta` steps from `current` in a list of `len`, wrapping around.
+/// Not on the list (deleted or custom) starts at the first entry.
+pub fn cycle(len: usize, current: Option<usize>, delta: i32) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    let Some(current) = current else { return Some(0) };
+    Some((current as i64 + delta as i64).rem_euclid(len as i64) as usize)
+}
+
+/// Mode `delta` steps along `MODES`
+pub fn step_mode(current: VisualMode, delta: i32) -> VisualMode {
+    let i = MODES.iter().position(|m| *m == current).unwrap_or(0) as i32;
+    MODES[(i + delta).rem_euclid(MODES.len() as i32) as usize]
+}
+
+pub fn status(state: &SharedState) -> Value {
+    let profile = &state.config.profile;
+    let device = if state.config.selected_device_name.is_empty() {
+        state.config.selected_device.clone()
+    } else {
+        state.config.selected_device_name.clone()
+    };
+    json!({
+        "fps": state.performance.gui_fps,
+        "device": device,
+        "profile": profile.name,
+        "color_preset": match &profile.color_link {
+            ColorRef::Preset(name) => Some(name.as_str()),
+            ColorRef::Custom(_) => None,
+        },
+        "visual_mode": format!("{:?}", profile.visual_mode),
+        "ghost_mode": state.config.window_locked,
+        "num_bars": state.visualization.bars.len(),
+        "now_playing": now_playing(state),
+    })
+}
+
+/// Brightness of the latest frame, for generative visuals that follow the
+/// music's timbre rather than its level
+pub fn features(state: &SharedState) -> Value {
+    let spectral = &state.performance.spectral;
+    json!({
+        "centroid_hz": spectral.centroid_hz,
+        "rolloff_hz": spectral.rolloff_hz,
+        "flatness": spectral.flatness,
+    })
+}
+
+pub fn now_playing(state: &SharedState) -> Value {
+    let Some(info) = &state.media_info else { return Value::Null };
+    let position = info.position_now(std::time::Instant::now());
+    json!({
+        "title": info.title,
+        "artist": info.artist,
+        "album": info.album,
+        "playing": info.is_playing,
+        "source": info.source_app,
+        "position_secs": position.map(|p| p.as_secs_f32()),
+        "duration_secs": info.position.map(|p| p.duration.as_secs_f32()),
+    })
+}
+
+/// Bars (dB) folded into `bands` equal groups, loudest bar of each, as
+/// 0..1 of the height they'd have on screen
+pub fn fold_levels(bars: &[f32], noise_floor: f32, bands: usize) -> Vec<f32> {
+    let bands = bands.clamp(1, MAX_BANDS);
+    if bars.is_empty() {
+        return vec![0.0; bands];
+    }
+    let range = (0.0 - noise_floor).max(1.0);
+    (0..bands)
+        .map(|b| {
+            let start = b * bars.len() / bands;
+            let end = ((b + 1) * bars.len() / bands).max(start + 1).min(bars.len());
+            let db = bars[start..end].iter().copied().fold(f32::MIN, f32::max);
+            ((db - noise_floor) / range).clamp(0.0, 1.0)
+        })
+        .collect()
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared_state::AppConfig;
+
+    fn parse(raw: &str) -> Command {
+        serde_json::from_str(raw).unwrap()
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse(r#"{"cmd": "toggle_ghost_mode"}"#), Command::ToggleGhostMode);
+        assert_eq!(parse(r#"{"cmd": "levels"}"#), Command::Levels { bands: 4 });
+        assert_eq!(parse(r#"{"cmd": "features"}"#), Command::Features);
+        assert_eq!(parse(r#"{"cmd": "set_colors", "name": "X"}"#), Command::SetColors { name: "X".into() });
+        assert!(serde_json::from_str::<Command>(r#"{"cmd": "format_disk"}"#).is_err());
+        assert!(serde_json::from_str::<Command>(r#"{"cmd": "set_profile"}"#).is_err());
+    }
+
+    #[test]
+    fn test_execute() {
+        let mut state = SharedState::with_config(AppConfig::default());
+        let first = profile_list(&state)[0].name.clone();
+
+        let status = execute(&Command::SetProfile { name: first.clone() }, &mut state).unwrap();
+        assert_eq!(status["profile"], json!(first));
+        assert_eq!(
+            execute(&Command::SetProfile { name: "Nope".into() }, &mut state),
+            Err(CommandError::NotFound("Nope".into()))
+        );
+
+        // Prev from the first wraps to the last
+        execute(&Command::PrevProfile, &mut state).unwrap();
+        assert_eq!(state.config.profile.name, profile_list(&state).last().unwrap().name);
+
+        let status = execute(&Command::ToggleGhostMode, &mut state).unwrap();
+        assert_eq!(status["ghost_mode"], json!(true));
+        execute(&Command::SetGhostMode { on: false }, &mut state).unwrap();
+        assert!(!state.config.window_locked);
+    }
+
+    #[test]
+    fn test_cycle_wraps_both_ways() {
+        assert_eq!(cycle(3, Some(2), 1), Some(0));
+        assert_eq!(cycle(3, Some(0), -1), Some(2));
+        assert_eq!(cycle(3, Some(1), 4), Some(2));
+        assert_eq!(cycle(3, None, -1), Some(0));
+        assert_eq!(cycle(0, Some(0), 1), None);
+
+        assert_eq!(step_mode(VisualMode::Spectrogram, 1), VisualMode::SolidBars);
+        assert_eq!(step_mode(VisualMode::SolidBars, -1), VisualMode::Spectrogram);
+    }
+
+    #[test]
+    fn test_next_colors_starts_over_from_custom_or_deleted() {
+        let mut state = SharedState::with_config(AppConfig::default());
+        let names = color_preset_names(&state);
+
+        state.config.profile.color_link = ColorRef::Custom(ColorProfile::default());
+        execute(&Command::NextColors, &mut state).unwrap();
+        assert_eq!(state.config.profile.color_link, ColorRef::Preset(names[0].clone()));
+        execute(&Command::NextColors, &mut state).unwrap();
+        assert_eq!(state.config.profile.color_link, ColorRef::Preset(names[1].clone()));
+
+        state.config.profile.color_link = ColorRef::Preset("Deleted".into());
+        execute(&Command::NextColors, &mut state).unwrap();
+        assert_eq!(state.config.profile.color_link, ColorRef::Preset(names[0].clone()));
+    }
+
+    #[test]
+    fn test_fold_levels() {
+        let bars = [-60.0, -30.0, -60.0, 0.0, -60.0];
+        assert_eq!(fold_levels(&bars, -60.0, 2), vec![0.5, 1.0]);
+        assert_eq!(fold_levels(&bars, -60.0, 1), vec![1.0]);
+        // More bands than bars repeats bars instead of panicking
+        assert_eq!(fold_levels(&bars[..2], -60.0, 3).len(), 3);
+        assert_eq!(fold_levels(&[], -60.0, 0), vec![0.0]);
+    }
+}
@@ -0,0 +1,260 @@
+//! Timestamped backups of the settings and user presets, taken before
+//! anything that throws work away (deleting a preset, importing a theme,
+//! resetting settings, restoring an older backup) and on request.
+//!
+//! A backup is a folder in `<config_dir>/backups/`, named after when and why
+//! it was taken (`2026-10-17_14-03-22_delete-visual-preset`), holding the
+//! config as it was in memory and a copy of the `presets/` folder. Names sort
+//! oldest first, and only the newest `MAX_BACKUPS` are kept.
+//!
+//! The Maintenance section shows `list()` every frame, so the folder listing
+//! is kept until the next backup is taken or restored.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::shared_state::AppConfig;
+
+/// Backups kept; older ones are deleted as new ones are taken
+const MAX_BACKUPS: usize = 20;
+
+/// Length of the timestamp at the start of a backup's name
+const STAMP_LEN: usize = "2026-10-17_14-03-22".len();
+
+/// `list()` result, dropped by `create` and `restore`
+static LISTING: Mutex<Option<Vec<Backup>>> = Mutex::new(None);
+
+/// One backup folder
+#[derive(Clone, Debug, PartialEq)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// "2026-10-17 14:03:22 · delete visual preset", for the picker
+    pub label: String,
+}
+
+pub fn backups_dir() -> PathBuf {
+    crate::paths::get().config_dir.join("backups")
+}
+
+/// Where backups go and what they copy: the app's folders, or temp ones in tests
+struct Dirs {
+    backups: PathBuf,
+    presets: PathBuf,
+}
+
+impl Dirs {
+    fn app() -> Self {
+        Self { backups: backups_dir(), presets: crate::paths::get().data_dir.join("presets") }
+    }
+}
+
+fn forget_listing() {
+    *LISTING.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Back up `config` and the user presets, `reason` saying why
+pub fn create(config: &AppConfig, reason: &str) -> io::Result<PathBuf> {
+    forget_listing();
+    create_in(&Dirs::app(), config, reason)
+}
+
+fn create_in(dirs: &Dirs, config: &AppConfig, reason: &str) -> io::Result<PathBuf> {
+    let path = save(dirs, config, reason)?;
+    prune(&dirs.backups);
+    Ok(path)
+}
+
+fn save(dirs: &Dirs, config: &AppConfig, reason: &str) -> io::Result<PathBuf> {
+    let dir = &dirs.backups;
+    fs::create_dir_all(dir)?;
+
+    let stamp = time::OffsetDateTime::now_local()
+        .unwrap_or_else(|_| time::OffsetDateTime::now_utc())
+        .format(time::macros::format_description!("[year]-[month]-[day]_[hour]-[minute]-[second]"))
+        .map_err(io::Error::other)?;
+    let name = format!("{}_{}", stamp, slug(reason));
+
+    // Two backups in the same second (reset right after a delete) get a suffix
+    let mut path = dir.join(&name);
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{}-{}", name, n));
+        n += 1;
+    }
+    fs::create_dir(&path)?;
+
+    let json = serde_json::to_string_pretty(config)?;
+    fs::write(path.join("config.json"), json)?;
+    if dirs.presets.exists() {
+        copy_dir(&dirs.presets, &path.join("presets"))?;
+    }
+
+    tracing::info!("[Backup] Saved {:?}", path);
+    Ok(path)
+}
+
+/// All backups, newest first
+pub fn list() -> Vec<Backup> {
+    let mut listing = LISTING.lock().unwrap_or_else(|e| e.into_inner());
+    listing.get_or_insert_with(|| list_in(&backups_dir())).clone()
+}
+
+fn list_in(dir: &Path) -> Vec<Backup> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<Backup> = entries
+        .flatten()
+        .filter(|e| e.path().join("config.json").is_file())
+        .map(|e| Backup { label: describe(&e.file_name().to_string_lossy()), path: e.path() })
+        .collect();
+    backups.sort_by(|a, b| b.path.cmp(&a.path));
+    backups
+}
+
+/// Put a backup's presets back and return its config. The current state is
+/// backed up first, so a restore can itself be undone.
+pub fn restore(current: &AppConfig, backup: &Path) -> io::Result<AppConfig> {
+    forget_listing();
+    restore_in(&Dirs::app(), current, backup)
+}
+
+fn restore_in(dirs: &Dirs, current: &AppConfig, backup: &Path) -> io::Result<AppConfig> {
+    let contents = fs::read_to_string(backup.join("config.json"))?;
+    let config: AppConfig = serde_json::from_str(&contents)?;
+
+    // Not pruned until the end: the backup being restored may be the oldest
+    save(dirs, current, "before restore")?;
+
+    if dirs.presets.exists() {
+        fs::remove_dir_all(&dirs.presets)?;
+    }
+    let saved = backup.join("presets");
+    if saved.exists() {
+        copy_dir(&saved, &dirs.presets)?;
+    }
+
+    tracing::info!("[Backup] Restored {:?}", backup);
+    prune(&dirs.backups);
+    Ok(config)
+}
+
+/// Delete all but the newest `MAX_BACKUPS`
+fn prune(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let mut folders: Vec<PathBuf> = entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()).collect();
+    folders.sort();
+    let excess = folders.len().saturating_sub(MAX_BACKUPS);
+    for old in &folders[..excess] {
+        if let Err(e) = fs::remove_dir_all(old) {
+            tracing::warn!("[Backup] Can't delete old backup {:?}: {}", old, e);
+        }
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)?.flatten() {
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// `reason` as a folder-name-safe word list: "delete-visual-preset"
+fn slug(reason: &str) -> String {
+    reason
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Folder name back into "2026-10-17 14:03:22 · delete visual preset"
+fn describe(name: &str) -> String {
+    if name.len() <= STAMP_LEN || !name.is_char_boundary(STAMP_LEN) {
+        return name.to_string();
+    }
+    let (stamp, reason) = name.split_at(STAMP_LEN);
+    let (date, clock) = stamp.split_once('_').unwrap_or((stamp, ""));
+    format!("{} {} · {}", date, clock.replace('-', ":"), reason.trim_start_matches('_').replace('-', " "))
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_names_read_back() {
+        let name = format!("2026-10-17_14-03-22_{}", slug("Delete visual preset 'Neon/2'"));
+        assert_eq!(name, "2026-10-17_14-03-22_delete-visual-preset-neon-2");
+        assert_eq!(describe(&name), "2026-10-17 14:03:22 · delete visual preset neon 2");
+        // Not one of ours: shown as is
+        assert_eq!(describe("old"), "old");
+    }
+
+    /// Fresh backups + presets folders under the temp dir
+    fn temp_dirs(name: &str) -> (PathBuf, Dirs) {
+        let root = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&root);
+        let dirs = Dirs { backups: root.join("backups"), presets: root.join("presets") };
+        fs::create_dir_all(dirs.presets.join("colors")).unwrap();
+        (root, dirs)
+    }
+
+    #[test]
+    fn test_create_and_restore() {
+        let (root, dirs) = temp_dirs("bespec_test_backup_restore");
+        let preset = dirs.presets.join("colors").join("Mine.json");
+        fs::write(&preset, "v1").unwrap();
+
+        let old = AppConfig { show_stats: true, ..Default::default() };
+        let backup = create_in(&dirs, &old, "delete color preset").unwrap();
+        assert!(backup.file_name().unwrap().to_string_lossy().ends_with("_delete-color-preset"));
+        assert_eq!(fs::read_to_string(backup.join("presets").join("colors").join("Mine.json")).unwrap(), "v1");
+        assert_eq!(list_in(&dirs.backups).len(), 1);
+
+        // Presets that came later go away, the backed up ones come back
+        fs::write(&preset, "v2").unwrap();
+        fs::write(dirs.presets.join("colors").join("New.json"), "new").unwrap();
+        let current = AppConfig { show_stats: false, ..Default::default() };
+        let restored = restore_in(&dirs, &current, &backup).unwrap();
+        assert!(restored.show_stats);
+        assert_eq!(fs::read_to_string(&preset).unwrap(), "v1");
+        assert!(!dirs.presets.join("colors").join("New.json").exists());
+
+        // ...and what was there before the restore is a backup of its own
+        let backups = list_in(&dirs.backups);
+        assert_eq!(backups.len(), 2);
+        assert!(backups.iter().any(|b| b.label.ends_with("before restore")));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_prune_keeps_the_newest() {
+        let (root, dirs) = temp_dirs("bespec_test_backup_prune");
+        for day in 1..=MAX_BACKUPS + 3 {
+            let path = dirs.backups.join(format!("2026-10-{:02}_12-00-00_manual", day));
+            fs::create_dir_all(&path).unwrap();
+            fs::write(path.join("config.json"), "{}").unwrap();
+        }
+
+        prune(&dirs.backups);
+        let backups = list_in(&dirs.backups);
+        assert_eq!(backups.len(), MAX_BACKUPS);
+        assert!(backups[0].label.starts_with(&format!("2026-10-{:02}", MAX_BACKUPS + 3)));
+        assert!(backups[MAX_BACKUPS - 1].label.starts_with("2026-10-04"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
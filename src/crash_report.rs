@@ -0,0 +1,172 @@
+//! Crash reports. A panic hook writes `<log_dir>/crash-<date>_<time>.txt`
+//! with the message, where it happened, a backtrace, a config summary and
+//! the audio format in use, then leaves a marker so the next launch can
+//! offer to open the report.
+//!
+//! Release builds abort on panic and Windows builds have no console, so
+//! without this a crash leaves nothing behind but a log line, if that.
+//! They strip debug info but keep the symbol table (`strip = "debuginfo"`),
+//! so the backtrace has function names but no file and line numbers.
+//!
+//! Device names stay out of the summary for the same reason the support
+//! bundle scrubs them: the report is meant to be attached to an issue.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::shared_state::AppConfig;
+
+/// Points at the last report until the next launch picks it up
+const PENDING_MARKER: &str = "crash-pending.txt";
+
+/// Facts about the running session for the report, e.g. the audio format
+static CONTEXT: Mutex<Vec<(&'static str, String)>> = Mutex::new(Vec::new());
+
+/// Remember `value` under `key` for a future report (replaces older notes)
+pub fn note(key: &'static str, value: impl Into<String>) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        let value = value.into();
+        match context.iter_mut().find(|(k, _)| *k == key) {
+            Some(entry) => entry.1 = value,
+            None => context.push((key, value)),
+        }
+    }
+}
+
+/// Write a report for every panic, on any thread, before the default hook runs
+pub fn install() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = match (info.payload().downcast_ref::<&str>(), info.payload().downcast_ref::<String>()) {
+            (Some(s), _) => s.to_string(),
+            (_, Some(s)) => s.clone(),
+            _ => "(no message)".to_string(),
+        };
+        let location = info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+        let thread = std::thread::current().name().unwrap_or("unnamed").to_string();
+        write_report(&message, location.as_deref(), &thread);
+        default_hook(info);
+    }));
+}
+
+/// The report left by the last crash, if any. The marker is removed, so a
+/// crash is only brought up once.
+pub fn take_pending() -> Option<PathBuf> {
+    let marker = crate::paths::get().log_dir.join(PENDING_MARKER);
+    let path = std::fs::read_to_string(&marker).ok().map(|p| PathBuf::from(p.trim()));
+    let _ = std::fs::remove_file(&marker);
+    path.filter(|p| p.exists())
+}
+
+fn write_report(message: &str, location: Option<&str>, thread: &str) {
+    let log_dir = &crate::paths::get().log_dir;
+    let stamp = time::OffsetDateTime::now_local()
+        .unwrap_or_else(|_| time::OffsetDateTime::now_utc())
+        .format(time::macros::format_description!("[year]-[month]-[day]_[hour]-[minute]-[second]"))
+        .unwrap_or_else(|_| "unknown".to_string());
+
+    let report = Report {
+        message,
+        location,
+        thread,
+        stamp: &stamp,
+        // try_lock: the panic may have happened while holding it
+        context: CONTEXT.try_lock().map(|c| c.clone()).unwrap_or_default(),
+        config: config_summary(),
+        backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+    };
+
+    let path = log_dir.join(format!("crash-{}.txt", stamp));
+    match std::fs::write(&path, report.to_string()) {
+        Ok(()) => {
+            let _ = std::fs::write(log_dir.join(PENDING_MARKER), path.to_string_lossy().as_bytes());
+            eprintln!("[Crash] Report written to {:?}", path);
+        }
+        Err(e) => eprintln!("[Crash] Couldn't write the crash report to {:?}: {}", path, e),
+    }
+}
+
+/// The settings most likely to matter, read back from disk (the live copy
+/// sits behind a lock the crashing thread may hold)
+fn config_summary() -> String {
+    let path = AppConfig::get_config_path();
+    let Some(config) = std::fs::read_to_string(&path).ok().and_then(|c| serde_json::from_str::<AppConfig>(&c).ok()) else {
+        return "(no readable config)".to_string();
+    };
+    let profile = &config.profile;
+    format!(
+        "Profile: {} ({:?}, {} bars)\nFPS cap: {:?}\nNoise floor: {} dB, resample: {}\nWindows: {} extra, wallpaper: {}, mini: {}",
+        profile.name,
+        profile.visual_mode,
+        profile.num_bars,
+        config.fps_cap,
+        config.noise_floor(),
+        config.resample_enabled(),
+        config.extra_windows.len(),
+        config.wallpaper_mode,
+        config.mini_mode,
+    )
+}
+
+/// Everything that goes into one report file
+struct Report<'a> {
+    message: &'a str,
+    location: Option<&'a str>,
+    thread: &'a str,
+    stamp: &'a str,
+    context: Vec<(&'static str, String)>,
+    config: String,
+    backtrace: String,
+}
+
+impl std::fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "BeSpec crash report")?;
+        writeln!(f, "===================")?;
+        writeln!(f, "Version:  v{}", env!("CARGO_PKG_VERSION"))?;
+        writeln!(f, "Platform: {} ({})", std::env::consts::OS, std::env::consts::ARCH)?;
+        writeln!(f, "Time:     {}", self.stamp)?;
+        writeln!(f)?;
+        writeln!(f, "Panic on thread '{}':", self.thread)?;
+        writeln!(f, "  {}", self.message)?;
+        if let Some(location) = self.location {
+            writeln!(f, "  at {}", location)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "--- Session ---")?;
+        for (key, value) in &self.context {
+            writeln!(f, "{}: {}", key, value)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "--- Config ---")?;
+        writeln!(f, "{}", self.config)?;
+        writeln!(f)?;
+        writeln!(f, "--- Backtrace ---")?;
+        writeln!(f, "{}", self.backtrace)
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_layout() {
+        let report = Report {
+            message: "index out of bounds",
+            location: Some("src/main.rs:1:1"),
+            thread: "fft",
+            stamp: "2026-10-17_14-03-22",
+            context: vec![("Audio format", "48000 Hz, 2 ch".to_string())],
+            config: "Profile: Default".to_string(),
+            backtrace: "<bt>".to_string(),
+        }
+        .to_string();
+        assert!(report.contains("Panic on thread 'fft':\n  index out of bounds\n  at src/main.rs:1:1"));
+        assert!(report.contains("Audio format: 48000 Hz, 2 ch"));
+        assert!(report.contains("--- Config ---\nProfile: Default"));
+        assert!(report.ends_with("<bt>\n"));
+    }
+}
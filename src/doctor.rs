@@ -0,0 +1,303 @@
+//! `--doctor`: startup self-test.
+//!
+//! Runs the same steps a normal launch would (read config, enumerate audio
+//! devices, open the selected device) without opening any window, and writes
+//! what it found to `<log_dir>/doctor-<date>_<time>.txt`. Bug reports can then
+//! start with that file instead of a round of "which OS / which device?".
+//!
+//! The report is also printed to stdout. Release builds on Windows have no
+//! console, so the log folder is opened at the end instead.
+
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+
+use crate::audio_capture::{AudioCaptureManager, CaptureBackend};
+use crate::shared_state::AppConfig;
+
+/// How long the test stream may take to deliver its first packet
+const STREAM_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long we keep listening once packets flow
+const STREAM_LISTEN: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl Status {
+    fn tag(self) -> &'static str {
+        match self {
+            Status::Ok => "[ OK ]",
+            Status::Warn => "[WARN]",
+            Status::Fail => "[FAIL]",
+        }
+    }
+}
+
+/// One line of the report, with optional indented detail lines below it
+struct Check {
+    name: &'static str,
+    status: Status,
+    summary: String,
+    details: Vec<String>,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, summary: impl Into<String>) -> Self {
+        Self { name, status, summary: summary.into(), details: Vec::new() }
+    }
+
+    fn detail(mut self, line: impl Into<String>) -> Self {
+        self.details.push(line.into());
+        self
+    }
+}
+
+/// Run every check, write the report and return the process exit code
+/// (0 = nothing failed, 1 = at least one check failed).
+pub fn run() -> i32 {
+    tracing::info!("[Doctor] Running self-test");
+
+    let mut checks = vec![check_environment()];
+    let config = check_config(&mut checks);
+    checks.push(check_presets());
+    let devices = check_devices(&mut checks);
+    if devices {
+        checks.push(check_stream(&config));
+    }
+
+    let report = format_report(&checks);
+    println!("{}", report);
+
+    let stamp = time::OffsetDateTime::now_local()
+        .unwrap_or_else(|_| time::OffsetDateTime::now_utc())
+        .format(time::macros::format_description!("[year]-[month]-[day]_[hour]-[minute]-[second]"))
+        .unwrap_or_else(|_| "report".to_string());
+    let log_dir = &crate::paths::get().log_dir;
+    let path = log_dir.join(format!("doctor-{}.txt", stamp));
+    match std::fs::write(&path, &report) {
+        Ok(()) => {
+            println!("Report written to {}", path.display());
+            tracing::info!("[Doctor] Report written to {:?}", path);
+            #[cfg(all(target_os = "windows", not(debug_assertions)))]
+            let _ = open::that(log_dir);
+        }
+        Err(e) => {
+            eprintln!("Could not write the report to {}: {}", path.display(), e);
+            tracing::error!("[Doctor] Failed to write report {:?}: {}", path, e);
+        }
+    }
+
+    exit_code(&checks)
+}
+
+fn exit_code(checks: &[Check]) -> i32 {
+    if checks.iter().any(|c| c.status == Status::Fail) { 1 } else { 0 }
+}
+
+fn format_report(checks: &[Check]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "BeSpec v{} self-test", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(out, "==========================");
+    for check in checks {
+        let _ = writeln!(out, "{} {}: {}", check.status.tag(), check.name, check.summary);
+        for line in &check.details {
+            let _ = writeln!(out, "         {}", line);
+        }
+    }
+
+    let count = |s: Status| checks.iter().filter(|c| c.status == s).count();
+    let _ = writeln!(out);
+    let _ = write!(
+        out,
+        "{} passed, {} warnings, {} failed",
+        count(Status::Ok),
+        count(Status::Warn),
+        count(Status::Fail)
+    );
+    out
+}
+
+// ====================================================================================
+// Checks
+// ====================================================================================
+
+fn check_environment() -> Check {
+    let paths = crate::paths::get();
+    let mut check = Check::new(
+        "Environment",
+        Status::Ok,
+        format!("{} ({})", std::env::consts::OS, std::env::consts::ARCH),
+    );
+
+    #[cfg(target_os = "linux")]
+    {
+        let session = if crate::shared_state::is_wayland() { "Wayland" } else { "X11" };
+        check = check.detail(format!("Session: {}", session));
+    }
+
+    check = check
+        .detail(format!("User: {}", paths.user.as_deref().unwrap_or("default")))
+        .detail(format!("Portable: {}", if paths.portable { "yes" } else { "no" }))
+        .detail(format!("Config dir: {}", paths.config_dir.display()))
+        .detail(format!("Data dir: {}", paths.data_dir.display()))
+        .detail(format!("Log dir: {}", paths.log_dir.display()));
+    check
+}
+
+/// Read config.json the way `AppConfig::load` does, but keep the error
+fn check_config(checks: &mut Vec<Check>) -> AppConfig {
+    let path = AppConfig::get_config_path();
+    let location = format!("Path: {}", path.display());
+
+    if !path.exists() {
+        checks.push(Check::new("Config", Status::Ok, "no config.json yet, defaults will be used").detail(location));
+        return AppConfig::default();
+    }
+
+    let (check, config) = match std::fs::read_to_string(&path) {
+        Err(e) => (Check::new("Config", Status::Fail, format!("can't read config.json: {}", e)), AppConfig::default()),
+        Ok(contents) => match serde_json::from_str::<AppConfig>(&contents) {
+            Ok(config) => (Check::new("Config", Status::Ok, "config.json is readable"), config),
+            Err(e) => (
+                Check::new("Config", Status::Fail, format!("config.json doesn't parse, BeSpec will start with defaults: {}", e)),
+                AppConfig::default(),
+            ),
+        },
+    };
+
+    let writable = std::fs::metadata(&path).map(|m| !m.permissions().readonly()).unwrap_or(false);
+    let check = check.detail(location).detail(format!(
+        "Selected device: {} ({})",
+        if config.selected_device_name.is_empty() { "-" } else { &config.selected_device_name },
+        config.selected_device
+    ));
+    let check = if writable || check.status == Status::Fail {
+        check
+    } else {
+        Check { status: Status::Warn, ..check }.detail("config.json is read-only, settings won't be saved")
+    };
+    checks.push(check);
+    config
+}
+
+fn check_presets() -> Check {
+    let colors = AppConfig::load_user_color_presets().len();
+    let visuals = AppConfig::load_user_visual_presets().len();
+    Check::new("Presets", Status::Ok, format!("{} color, {} visual user presets", colors, visuals))
+        .detail(format!("Folder: {}", crate::paths::get().data_dir.join("presets").display()))
+}
+
+/// Enumerate capture devices. Returns false if there is nothing to test a stream on.
+fn check_devices(checks: &mut Vec<Check>) -> bool {
+    match AudioCaptureManager::list_devices() {
+        Ok(devices) if devices.is_empty() => {
+            checks.push(Check::new("Audio devices", Status::Fail, "no capture devices found"));
+            false
+        }
+        Ok(devices) => {
+            let mut check = Check::new("Audio devices", Status::Ok, format!("{} found", devices.len()));
+            for dev in &devices {
+                check = check.detail(format!(
+                    "{} — {} Hz, {} ch (id: {})",
+                    dev.name, dev.default_sample_rate, dev.channels, dev.id
+                ));
+            }
+            checks.push(check);
+            true
+        }
+        Err(e) => {
+            checks.push(Check::new("Audio devices", Status::Fail, format!("enumeration failed: {}", e)));
+            false
+        }
+    }
+}
+
+/// Open the selected device (falling back to the default like a normal
+/// launch), wait for audio to arrive, then close it again
+fn check_stream(config: &AppConfig) -> Check {
+    let manager = if config.selected_device == "Default" {
+        AudioCaptureManager::new()
+    } else {
+        AudioCaptureManager::with_device_id(&config.selected_device).or_else(|_| AudioCaptureManager::new())
+    };
+    let mut capture = match manager {
+        Ok(m) => m,
+        Err(e) => return Check::new("Test stream", Status::Fail, format!("can't open a capture device: {}", e)),
+    };
+
+    let device = capture.device_info();
+    let backend = capture.backend_name();
+    if let Err(e) = capture.start_capture() {
+        return Check::new("Test stream", Status::Fail, format!("{} refused to start: {}", backend, e))
+            .detail(format!("Device: {}", device.name));
+    }
+
+    let rx = capture.receiver();
+    let started = Instant::now();
+    let mut first_packet = None;
+    let (mut packets, mut peak, mut rate, mut channels) = (0usize, 0.0f32, 0u32, 0u16);
+
+    while started.elapsed() < STREAM_TIMEOUT
+        && first_packet.map_or(true, |t: Instant| t.elapsed() < STREAM_LISTEN)
+    {
+        if let Ok(packet) = rx.recv_timeout(Duration::from_millis(100)) {
+            first_packet.get_or_insert_with(Instant::now);
+            packets += 1;
+            rate = packet.sample_rate;
+            channels = packet.channels;
+            peak = packet.samples.iter().fold(peak, |m, s| m.max(s.abs()));
+        }
+    }
+    capture.stop_capture();
+
+    let check = match first_packet {
+        None => Check::new(
+            "Test stream",
+            Status::Fail,
+            format!("{} opened the device but no audio arrived within {}s", backend, STREAM_TIMEOUT.as_secs()),
+        ),
+        Some(_) if peak <= 0.0 => Check::new(
+            "Test stream",
+            Status::Warn,
+            format!("{} delivers audio, but it is silent (is anything playing?)", backend),
+        ),
+        Some(_) => Check::new("Test stream", Status::Ok, format!("{} delivers audio", backend)),
+    };
+    check
+        .detail(format!("Device: {}", device.name))
+        .detail(format!("Format: {} Hz, {} ch", rate, channels))
+        .detail(format!(
+            "Packets: {} in {} ms, peak {:.1} dBFS",
+            packets,
+            started.elapsed().as_millis(),
+            20.0 * peak.max(1e-10).log10()
+        ))
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_and_exit_code() {
+        let mut checks = vec![
+            Check::new("Config", Status::Ok, "fine").detail("Path: /tmp/config.json"),
+            Check::new("Test stream", Status::Warn, "silent"),
+        ];
+        assert_eq!(exit_code(&checks), 0);
+
+        let report = format_report(&checks);
+        assert!(report.contains("[ OK ] Config: fine"));
+        assert!(report.contains("         Path: /tmp/config.json"));
+        assert!(report.ends_with("1 passed, 1 warnings, 0 failed"));
+
+        checks.push(Check::new("Audio devices", Status::Fail, "none"));
+        assert_eq!(exit_code(&checks), 1);
+    }
+}
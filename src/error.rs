@@ -0,0 +1,92 @@
+// src/error.rs
+//
+// Crate-wide error type.
+//
+// Backends keep returning their own detailed enums (e.g. `AudioDeviceError`),
+// and get wrapped here together with the context a user needs to act on it
+// (which device, which file, which URL). Everything that ends up in the
+// log, the GUI error banner, or any external API goes through `BeSpecError`
+// so the wording is the same everywhere.
+
+use crate::audio_device::AudioDeviceError;
+
+/// Top level error, carries enough context to be shown to a user as-is
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum BeSpecError {
+    /// Audio capture / enumeration failed for a specific device
+    #[error("Audio device '{device}': {source}")]
+    Audio {
+        device: String,
+        #[source]
+        source: AudioDeviceError,
+    },
+
+    /// The capture stream went away while running
+    #[error("Audio stream for '{0}' disconnected")]
+    StreamDisconnected(String),
+
+    /// The OS credential store refused to save/read/delete an API token
+    #[error("Credentials: {0}")]
+    Credentials(String),
+
+    /// A background job started from the GUI failed
+    #[error("{task}: {reason}")]
+    Task { task: String, reason: String },
+
+    /// The window stopped drawing for a while and came back (seconds stalled)
+    #[error("The window stopped drawing for {0} s (GPU or driver reset?) and has recovered")]
+    RendererStalled(u64),
+}
+
+impl BeSpecError {
+    pub fn audio(device: impl Into<String>, source: AudioDeviceError) -> Self {
+        Self::Audio { device: device.into(), source }
+    }
+
+    /// Short machine-friendly category ("audio", "task", ...)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Audio { .. } | Self::StreamDisconnected(_) => "audio",
+            Self::Credentials(_) => "credentials",
+            Self::Task { .. } => "task",
+            Self::RendererStalled(_) => "renderer",
+        }
+    }
+
+    /// Log prefix matching the rest of the codebase
+    fn log_tag(&self) -> &'static str {
+        match self {
+            Self::Audio { .. } | Self::StreamDisconnected(_) => "[Capture]",
+            Self::Credentials(_) => "[Secrets]",
+            Self::Task { .. } => "[Tasks]",
+            Self::RendererStalled(_) => "[GUI]",
+        }
+    }
+
+    /// Write the error to the log with the usual prefix
+    pub fn log(&self) {
+        tracing::error!("{} {}", self.log_tag(), self);
+    }
+}
+
+impl AudioDeviceError {
+    /// Attach the device this error happened on
+    pub fn on_device(self, device: impl Into<String>) -> BeSpecError {
+        BeSpecError::audio(device, self)
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audio_error_carries_device() {
+        let err = AudioDeviceError::UnsupportedFormat.on_device("USB Interface");
+        assert_eq!(err.kind(), "audio");
+        assert_eq!(err.to_string(), "Audio device 'USB Interface': Unsupported sample format");
+        assert!(std::error::Error::source(&err).is_some());
+    }
+}
@@ -1,6 +1,5 @@
-/// FFT configuration adapter for dynamic sample rate handling
-/// Ensures FFT settings are always optimal for the current device's sample rate
-
+//! FFT configuration adapter for dynamic sample rate handling
+//! Ensures FFT settings are always optimal for the current device's sample rate
 
 /// Fixed FFT size for the application
 /// 2048 provides a good balance of frequency resolution and latency:
@@ -1,6 +1,7 @@
 use realfft::{RealFftPlanner, RealToComplex};
 use core::f64;
 use std::sync::Arc;
+use wide::f32x8;
 use crate::{fft_config::FIXED_FFT_SIZE, shared_state::SILENCE_DB};
 
 
@@ -12,6 +13,20 @@ pub const MAPPING_KNEE_FREQ: f64 = 500.0;            // 0-500Hz is Linear
 pub const MAPPING_MAX_FREQ: f64 = 20000.0;           // Hard limit at 20kHz
 // ===================
 
+/// Spectral peaks kept per frame (strongest first), for harmonic analysis
+const MAX_SPECTRAL_PEAKS: usize = 48;
+/// Peaks this far below the strongest one are ignored
+const SPECTRAL_PEAK_RANGE_DB: f32 = 90.0;
+/// How far a gravity peak drops (dB) in one peak release time, so the
+/// Peak Fall Speed setting still sets the pace
+const PEAK_GRAVITY_DROP_DB: f32 = 60.0;
+/// Share of the spectrum's energy below the rolloff frequency
+const ROLLOFF_ENERGY: f32 = 0.85;
+/// Bins per SIMD step in the per-bin loops (`f32x8`)
+const LANES: usize = 8;
+/// Frames the old bars take to fade out after a bar-count change
+const CROSSFADE_FRAMES: u32 = 8;
+
 // configure for FFT processing and visualization
 #[derive(Clone)]
 pub struct FFTConfig{
@@ -24,6 +39,7 @@ pub struct FFTConfig{
     pub peak_hold_time_ms: f32,         // duration of peak hold
     pub peak_release_time_ms: f32,      // peak fall speed
     pub aggregation_mode: crate::shared_state::AggregationMode,     // bar aggregation peak vs average
+    pub peak_gravity: bool,             // peaks fall with an accelerating drop
 }
 
 impl Default for FFTConfig {
@@ -38,6 +54,7 @@ impl Default for FFTConfig {
             peak_hold_time_ms: 1500.0,
             peak_release_time_ms: 1500.0,
             aggregation_mode: crate::shared_state::AggregationMode::Peak,
+            peak_gravity: false,
         }
      }
 }
@@ -51,6 +68,25 @@ pub struct BarToBinMap {
 }
 
 
+/// A local maximum of the FFT magnitudes, interpolated between bins
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpectralPeak {
+    pub freq_hz: f32,
+    pub db: f32,
+}
+
+/// Brightness descriptors of one frame, all zero for silence
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SpectralFeatures {
+    /// Magnitude-weighted mean frequency (Hz), the spectrum's "center of mass"
+    pub centroid_hz: f32,
+    /// Frequency (Hz) below which `ROLLOFF_ENERGY` of the energy sits
+    pub rolloff_hz: f32,
+    /// Geometric over arithmetic mean of the power spectrum: near 1 for
+    /// noise, near 0 for a few pure tones
+    pub flatness: f32,
+}
+
 /// Main FFT processor - handles windowing, FFT, and bar mapping
 pub struct FFTProcessor{
     config: FFTConfig,
@@ -58,7 +94,10 @@ pub struct FFTProcessor{
     // FFT State (reusable, no per-frame allocation)
     fft: Arc<dyn RealToComplex<f32>>,
     input_buffer: Vec<f32>,     // Windowed inptut samples
+    spectrum: Vec<num_complex::Complex<f32>>,   // Complex FFT output
     output_buffer: Vec<f32>,    // FFT magnitude output
+    db_buffer: Vec<f32>,        // Magnitudes in dB, with sensitivity
+    raw_bars: Vec<f32>,         // Bars before smoothing
     scratch_buffer: Vec<num_complex::Complex<f32>>,   // Scratch space for FFT
 
     // Hann Window (precomputed, never changes)
@@ -71,9 +110,20 @@ pub struct FFTProcessor{
     last_bar_heights: Vec<f32>,
     peak_levels: Vec<f32>,
     peak_hold_timers: Vec<f32>, // Time remaining for peak hold (ms)
+    peak_velocities: Vec<f32>,  // Gravity fall speed (dB/ms)
+
+    // Old bars still showing after a bar-count change
+    crossfade: CrossFade,
+
+    // Strongest spectral peaks of the last frame, by frequency
+    spectral_peaks: Vec<SpectralPeak>,
+    spectral_features: SpectralFeatures,
 
     // Frame Timing for smooth interpoloations
     last_frame_time: std::time::Instant,
+
+    // Time the last frame spent in the FFT and in aggregation, for the stats overlay
+    stage_times: (std::time::Duration, std::time::Duration),
 }
 
 impl FFTProcessor {
@@ -85,7 +135,10 @@ impl FFTProcessor {
         
         // Allocate all buffers upfront (no runtime allocations)
         let input_buffer = vec![0.0; config.fft_size];
+        let spectrum = fft.make_output_vec();
         let output_buffer = vec![0.0; config.fft_size / 2 + 1];
+        let db_buffer = Vec::with_capacity(output_buffer.len());
+        let raw_bars = Vec::with_capacity(config.num_bars);
         let scratch_buffer = fft.make_scratch_vec();
 
         // Precompute Hann Window
@@ -98,26 +151,37 @@ impl FFTProcessor {
         let last_bar_heights = vec![SILENCE_DB; config.num_bars];
         let peak_levels = vec![SILENCE_DB; config.num_bars];
         let peak_hold_timers = vec![0.0; config.num_bars];
+        let peak_velocities = vec![0.0; config.num_bars];
 
         Self {
             config,
             fft,
             input_buffer,
+            spectrum,
             output_buffer,
+            db_buffer,
+            raw_bars,
             scratch_buffer,
             hann_window,
             mapping,
             last_bar_heights,
             peak_levels,
             peak_hold_timers,
+            peak_velocities,
+            crossfade: CrossFade::default(),
+            spectral_peaks: Vec::with_capacity(MAX_SPECTRAL_PEAKS),
+            spectral_features: SpectralFeatures::default(),
             last_frame_time: std::time::Instant::now(),
+            stage_times: Default::default(),
         }
     }
 
     /// Process audio samples and return bar heights
-    /// 
-    /// Returns: (bar_heights, peak_heights)
-    pub fn process(&mut self, samples: &[f32]) -> (Vec<f32>, Vec<f32>) {
+    ///
+    /// Returns: (bar_heights, peak_heights), borrowed from the processor's own
+    /// state. Every buffer is allocated up front, so a frame allocates nothing;
+    /// callers copy the result into buffers of their own (`VisualizationData::set_bars`).
+    pub fn process(&mut self, samples: &[f32]) -> (&[f32], &[f32]) {
         // Calculate delta time for smoothing
         let now = std::time::Instant::now();
         let delta_ms = now.duration_since(self.last_frame_time).as_secs_f32() * 1000.0;
@@ -130,30 +194,60 @@ impl FFTProcessor {
         self.compute_fft();
 
         // Step 3: Convert to magnitudes (dB scale)
-        let magnitudes = self.compute_magnitudes();
-        
-        // Step 4:
-        let raw_bars = self.group_bins(&magnitudes);
+        self.compute_magnitudes();
+        let bin_hz = self.config.sample_rate as f32 / self.config.fft_size as f32;
+        Self::find_spectral_peaks(&self.db_buffer, bin_hz, &mut self.spectral_peaks);
+        self.spectral_features = Self::compute_spectral_features(&self.output_buffer, bin_hz);
+        let transformed = std::time::Instant::now();
+
+        // Step 4: Bins to bars (taken out of self for the duration, the
+        // helpers below borrow the processor)
+        let mut raw_bars = std::mem::take(&mut self.raw_bars);
+        self.group_bins(&self.db_buffer, &mut raw_bars);
 
         // Step 5: Apply smoothing (attack/release)
-        let smoothed_bars = self.apply_smoothing(&raw_bars, delta_ms);
+        self.apply_smoothing(&raw_bars, delta_ms);
+        self.raw_bars = raw_bars;
 
         // step 6: Update peaks
-        let peaks = self.update_peaks(&smoothed_bars, delta_ms);
+        let smoothed_bars = std::mem::take(&mut self.last_bar_heights);
+        self.update_peaks(&smoothed_bars, delta_ms);
+        self.last_bar_heights = smoothed_bars;
 
-        (smoothed_bars, peaks)
+        // Step 7: Mix in the old bars while a bar-count change fades
+        self.crossfade.mix(&self.last_bar_heights, &self.peak_levels);
+
+        self.stage_times = (transformed - now, transformed.elapsed());
+        self.output()
+    }
+
+    /// Time the last `process` spent in the FFT (window, transform, dB and
+    /// features) and in aggregation (bins to bars, smoothing, peaks)
+    pub fn stage_times(&self) -> (std::time::Duration, std::time::Duration) {
+        self.stage_times
+    }
+
+    /// Bars and peaks of the last `process`
+    pub fn output(&self) -> (&[f32], &[f32]) {
+        self.crossfade.output().unwrap_or((self.last_bar_heights.as_slice(), self.peak_levels.as_slice()))
     }
 
-    #[allow(dead_code)]
     /// Update configuration (e.g., user changed the number of bars)
+    ///
+    /// A new bar count resizes the smoothing state in place, each new bar
+    /// starting from the nearest old one, and the old bars cross-fade into
+    /// the new ones over `CROSSFADE_FRAMES` frames, so there's no blink.
     pub fn update_config(&mut self, config: FFTConfig) {
 
         // Sample Rate chanmge triggers a full rebuild, not an update
 
         if config.num_bars != self.config.num_bars {
-            self.last_bar_heights.resize(config.num_bars, SILENCE_DB);
-            self.peak_levels.resize(config.num_bars, SILENCE_DB);
-            self.peak_hold_timers.resize(config.num_bars, 0.0);
+            let new_len = config.num_bars;
+            self.crossfade.start(&self.last_bar_heights, &self.peak_levels, new_len);
+            for levels in [&mut self.last_bar_heights, &mut self.peak_levels, &mut self.peak_hold_timers, &mut self.peak_velocities] {
+                let old = std::mem::take(levels);
+                remap_nearest(&old, levels, new_len);
+            }
             
             // Recomput the mapping
             self.mapping = Self::compute_bar_mapping(config.num_bars, config.sample_rate, config.fft_size);
@@ -162,6 +256,92 @@ impl FFTProcessor {
         self.config = config;
     }
 
+    /// Start from the bar and peak heights of `previous` instead of silence.
+    ///
+    /// Used when the processor has to be rebuilt (sample rate changed, e.g.
+    /// on a track change), so the bars fall from where they were with the
+    /// release time instead of vanishing for a frame.
+    /// Bar counts may differ; each new bar takes the nearest old one.
+    pub fn carry_levels_from(&mut self, previous: &FFTProcessor) {
+        if previous.last_bar_heights.is_empty() {
+            return;
+        }
+        let new_len = self.last_bar_heights.len();
+        remap_nearest(&previous.last_bar_heights, &mut self.last_bar_heights, new_len);
+        remap_nearest(&previous.peak_levels, &mut self.peak_levels, new_len);
+        remap_nearest(&previous.peak_hold_timers, &mut self.peak_hold_timers, new_len);
+        remap_nearest(&previous.peak_velocities, &mut self.peak_velocities, new_len);
+    }
+
+    /// Strongest peaks of the last processed frame, sorted by frequency
+    pub fn spectral_peaks(&self) -> &[SpectralPeak] {
+        &self.spectral_peaks
+    }
+
+    /// Centroid, rolloff and flatness of the last processed frame
+    pub fn spectral_features(&self) -> SpectralFeatures {
+        self.spectral_features
+    }
+
+    /// Features from the raw (linear) FFT magnitudes. DC is left out, and
+    /// sensitivity doesn't matter since every measure is a ratio.
+    fn compute_spectral_features(bins: &[f32], bin_hz: f32) -> SpectralFeatures {
+        let bins = bins.get(1..).unwrap_or(&[]);
+        let freq = |i: usize| (i + 1) as f32 * bin_hz;
+
+        let magnitude_sum: f32 = bins.iter().sum();
+        let power_sum: f32 = bins.iter().map(|m| m * m).sum();
+        if bins.is_empty() || power_sum <= f32::MIN_POSITIVE {
+            return SpectralFeatures::default();
+        }
+
+        let centroid_hz = bins.iter().enumerate().map(|(i, m)| freq(i) * m).sum::<f32>() / magnitude_sum;
+
+        let target = power_sum * ROLLOFF_ENERGY;
+        let mut running = 0.0;
+        let rolloff_bin = bins
+            .iter()
+            .position(|m| {
+                running += m * m;
+                running >= target
+            })
+            .unwrap_or(bins.len() - 1);
+
+        // Geometric mean through the mean log; the floor keeps empty bins
+        // from sending it to zero outright
+        let floor = power_sum * 1e-12;
+        let mean_log = bins.iter().map(|m| (m * m).max(floor).ln()).sum::<f32>() / bins.len() as f32;
+        let flatness = (mean_log.exp() / (power_sum / bins.len() as f32)).clamp(0.0, 1.0);
+
+        SpectralFeatures { centroid_hz, rolloff_hz: freq(rolloff_bin), flatness }
+    }
+
+    /// Peak-finding pass over the magnitudes (dB per bin). Each local maximum
+    /// gets a parabolic fit through its neighbours, which puts a steady
+    /// tone's frequency within a small fraction of a bin.
+    fn find_spectral_peaks(magnitudes: &[f32], bin_hz: f32, peaks: &mut Vec<SpectralPeak>) {
+        peaks.clear();
+        let loudest = magnitudes.iter().copied().fold(SILENCE_DB, f32::max);
+        let threshold = (loudest - SPECTRAL_PEAK_RANGE_DB).max(SILENCE_DB);
+
+        for i in 1..magnitudes.len().saturating_sub(1) {
+            let (a, b, c) = (magnitudes[i - 1], magnitudes[i], magnitudes[i + 1]);
+            if b <= threshold || b <= a || b < c {
+                continue;
+            }
+            let denom = a - 2.0 * b + c;
+            let offset = if denom.abs() > f32::EPSILON { (0.5 * (a - c) / denom).clamp(-0.5, 0.5) } else { 0.0 };
+            peaks.push(SpectralPeak {
+                freq_hz: (i as f32 + offset) * bin_hz,
+                db: b - 0.25 * (a - c) * offset,
+            });
+        }
+
+        peaks.sort_by(|x, y| y.db.total_cmp(&x.db));
+        peaks.truncate(MAX_SPECTRAL_PEAKS);
+        peaks.sort_by(|x, y| x.freq_hz.total_cmp(&y.freq_hz));
+    }
+
     /// Public Helper: Calculate frequency for a specific bar index
     /// Centralized logic to ensure GUI and Audio math always match
     pub fn calculate_bar_frequency(
@@ -221,19 +401,17 @@ impl FFTProcessor {
     /// Compute FFT (modifies output_buffer in place)
     fn compute_fft(&mut self) {
         // realfft requires complex output, but we only need magnitudes
-        let mut spectrum = self.fft.make_output_vec();
-
         self.fft 
-            .process_with_scratch(&mut self.input_buffer, &mut spectrum, &mut self.scratch_buffer)
+            .process_with_scratch(&mut self.input_buffer, &mut self.spectrum, &mut self.scratch_buffer)
             .expect("FFT processing failed");
         
         // Store magnitudes in output_buffer
-        for (i, complex) in spectrum.iter().enumerate() {
+        for (i, complex) in self.spectrum.iter().enumerate() {
             self.output_buffer[i] = complex.norm();
         }
     }
 
-    /// Convert FFT output to dB magnitudes with sensitivity
+    /// Convert FFT output to dB magnitudes with sensitivity, into `db_buffer`
     /// 
     /// Normalization strategy:
     /// - FFT output scales with FFT size, so we normalize by sqrt(N) for energy preservation
@@ -247,7 +425,7 @@ impl FFTProcessor {
     /// - Combined factor: 2.0 / 45.25 ≈ 0.044
     /// - A full-scale sine produces ~22.6 magnitude → ~0.996 normalized → ~0 dB ✓
     /// - But real music with spread energy stays dynamic!
-    fn compute_magnitudes(&self) -> Vec<f32> {
+    fn compute_magnitudes(&mut self) {
        // Hann window correction (window averages 0.5, so multiply by 2)
         const HANN_CORRECTION: f32 = 2.0;
         
@@ -258,24 +436,12 @@ impl FFTProcessor {
         // Combined normalization factor
         let normalization = HANN_CORRECTION * fft_normalization;
 
-        self.output_buffer
-            .iter()
-            .map(|&mag| {
-                // 1. Apply normalization (energy-preserving)
-                let normalized = mag * normalization;
-                
-                // 2. Apply sensitivity BEFORE log (preserves dynamic range perception)
-                //    sensitivity > 1.0 = boost quiet content
-                //    sensitivity < 1.0 = reduce overall level  
-                //    sensitivity = 1.0 = calibrated for loud mastered music (~0 dBFS peaks)
-                let adjusted = normalized * self.config.sensitivity;
-
-                // 3. Convert to dB scale
-                //    Full scale (1.0) → 0 dB
-                //    -6 dB per halving of amplitude
-                20.0 * (adjusted + 1e-10).log10()
-            })
-            .collect()
+        // Sensitivity is applied BEFORE log (preserves dynamic range perception)
+        //    sensitivity > 1.0 = boost quiet content
+        //    sensitivity < 1.0 = reduce overall level
+        //    sensitivity = 1.0 = calibrated for loud mastered music (~0 dBFS peaks)
+        // Then to dB: full scale (1.0) → 0 dB, -6 dB per halving of amplitude
+        magnitudes_to_db(&self.output_buffer, normalization * self.config.sensitivity, &mut self.db_buffer);
     }
 
     /// Perform a linear-log hybrid mapping of the FFT data to visualization bars
@@ -335,8 +501,8 @@ impl FFTProcessor {
     }
 
     // Group FFT bin data into visualization bars
-    fn group_bins(&self, magnitudes: &[f32]) -> Vec<f32> {
-        let mut bars = Vec::with_capacity(self.mapping.len());
+    pub(crate) fn group_bins(&self, magnitudes: &[f32], bars: &mut Vec<f32>) {
+        bars.clear();
 
         for map in &self.mapping {
             if map.start_bin == map.end_bin {
@@ -368,22 +534,18 @@ impl FFTProcessor {
 
                 if self.config.aggregation_mode == crate::shared_state::AggregationMode::Peak {
                     // Peak Aggregation
-                    let peak = bin_slice.iter().copied().fold(f32::MIN, f32::max);
-                    bars.push(peak);
+                    bars.push(slice_max(bin_slice));
                 } else {
                     // Average Aggregation: Sum and divide for total band energy
-                    let sum: f32 = bin_slice.iter().copied().sum();
                     let count = bin_slice.len() as f32;
-                    bars.push(sum / count);
-
+                    bars.push(slice_sum(bin_slice) / count);
                 }
             }
         }
-        bars
     }
 
     // Apply attack/releaser smoothing
-    fn apply_smoothing(&mut self, raw_bars: &[f32], delta_ms: f32) -> Vec<f32> {
+    fn apply_smoothing(&mut self, raw_bars: &[f32], delta_ms: f32) -> &[f32] {
         let attack_factor = (delta_ms / self.config.attack_time_ms).min(1.0);
         let release_factor = (delta_ms / self.config.release_time_ms).min(1.0);
 
@@ -401,33 +563,43 @@ impl FFTProcessor {
             self.last_bar_heights[i] = smoothed;
         }
 
-        self.last_bar_heights.clone()
+        &self.last_bar_heights
     }
 
-    fn update_peaks(&mut self, bars: &[f32], delta_ms: f32) -> Vec<f32> {
+    fn update_peaks(&mut self, bars: &[f32], delta_ms: f32) -> &[f32] {
         for (i, &bar_height) in bars.iter().enumerate() {
             // if current bar exceeds peak, reset the peak
             if bar_height > self.peak_levels[i] {
                 self.peak_levels[i] = bar_height;
                 self.peak_hold_timers[i] = self.config.peak_hold_time_ms;
+                self.peak_velocities[i] = 0.0;
             } else{
                 // decrement the hold timer
                 self.peak_hold_timers[i] -= delta_ms;
 
                 // if the hold expired, let peak fall!
                 if self.peak_hold_timers[i] <= 0.0 {
-                    let release_factor = (delta_ms / self.config.peak_release_time_ms).min(1.0);
-                    self.peak_levels[i] -= (self.peak_levels[i] - bar_height) * release_factor;
+                    if self.config.peak_gravity {
+                        // Accelerating drop: d = g·t²/2 reaches PEAK_GRAVITY_DROP_DB after one release time
+                        let release = self.config.peak_release_time_ms.max(1.0);
+                        let gravity = 2.0 * PEAK_GRAVITY_DROP_DB / (release * release);
+                        self.peak_velocities[i] += gravity * delta_ms;
+                        self.peak_levels[i] -= self.peak_velocities[i] * delta_ms;
+                    } else {
+                        let release_factor = (delta_ms / self.config.peak_release_time_ms).min(1.0);
+                        self.peak_levels[i] -= (self.peak_levels[i] - bar_height) * release_factor;
+                    }
 
-                    // Never fall below current bar
+                    // Never fall below current bar; landing on it stops the fall
                     if self.peak_levels[i] < bar_height {
                        self.peak_levels[i] = bar_height;
+                       self.peak_velocities[i] = 0.0;
                     }
                 }
             }
         }
 
-        self.peak_levels.clone()
+        &self.peak_levels
     }
 
     // Get a copy of the current configuration
@@ -436,6 +608,98 @@ impl FFTProcessor {
     }
 }
 
+/// `old` stretched or squeezed to `new_len` values, each taking the nearest
+/// old one. Empty `old` gives silence.
+fn remap_nearest(old: &[f32], new: &mut Vec<f32>, new_len: usize) {
+    new.clear();
+    if old.is_empty() {
+        new.resize(new_len, SILENCE_DB);
+        return;
+    }
+    let old_len = old.len();
+    new.extend((0..new_len).map(|i| old[(i * old_len / new_len).min(old_len - 1)]));
+}
+
+/// The bars from before a bar-count change, fading out over the new ones
+#[derive(Default)]
+struct CrossFade {
+    /// Old bars and peaks, remapped to the new count
+    from_bars: Vec<f32>,
+    from_peaks: Vec<f32>,
+    frames_left: u32,
+    /// Mixed output of the last frame; empty once the fade is over
+    bars: Vec<f32>,
+    peaks: Vec<f32>,
+}
+
+impl CrossFade {
+    fn start(&mut self, bars: &[f32], peaks: &[f32], new_len: usize) {
+        // Mid-fade, fade from what's on screen
+        let (bars, peaks) = match self.output() {
+            Some((b, p)) => (b.to_vec(), p.to_vec()),
+            None => (bars.to_vec(), peaks.to_vec()),
+        };
+        remap_nearest(&bars, &mut self.from_bars, new_len);
+        remap_nearest(&peaks, &mut self.from_peaks, new_len);
+        self.frames_left = CROSSFADE_FRAMES;
+    }
+
+    /// Mix a new frame with the old bars, a step further along the fade
+    fn mix(&mut self, bars: &[f32], peaks: &[f32]) {
+        self.bars.clear();
+        self.peaks.clear();
+        if self.frames_left == 0 {
+            return;
+        }
+        let old = self.frames_left as f32 / (CROSSFADE_FRAMES + 1) as f32;
+        let blend = |from: &[f32], to: &[f32], out: &mut Vec<f32>| {
+            out.extend(from.iter().zip(to).map(|(&a, &b)| a * old + b * (1.0 - old)));
+        };
+        blend(&self.from_bars, bars, &mut self.bars);
+        blend(&self.from_peaks, peaks, &mut self.peaks);
+        self.frames_left -= 1;
+    }
+
+    fn output(&self) -> Option<(&[f32], &[f32])> {
+        (!self.bars.is_empty()).then_some((self.bars.as_slice(), self.peaks.as_slice()))
+    }
+}
+
+/// `20·log10(magnitude·scale)` for every bin, eight bins per step; the
+/// conversion is most of the per-frame cost at high bar counts.
+/// The 1e-10 keeps silent bins finite (-200 dB).
+pub(crate) fn magnitudes_to_db(magnitudes: &[f32], scale: f32, db: &mut Vec<f32>) {
+    db.clear();
+    let chunks = magnitudes.chunks_exact(LANES);
+    let rest = chunks.remainder();
+    for chunk in chunks {
+        db.extend_from_slice(&((lanes(chunk) * scale + 1e-10).log10() * 20.0).to_array());
+    }
+    db.extend(rest.iter().map(|&mag| 20.0 * (mag * scale + 1e-10).log10()));
+}
+
+/// One `chunks_exact(LANES)` chunk as a vector
+#[inline]
+fn lanes(chunk: &[f32]) -> f32x8 {
+    f32x8::new([chunk[0], chunk[1], chunk[2], chunk[3], chunk[4], chunk[5], chunk[6], chunk[7]])
+}
+
+/// Loudest value, eight at a time (wide treble bars span hundreds of bins)
+fn slice_max(values: &[f32]) -> f32 {
+    let chunks = values.chunks_exact(LANES);
+    let rest = chunks.remainder().iter().copied().fold(f32::MIN, f32::max);
+    let lanes = chunks.fold(f32x8::splat(f32::MIN), |acc, chunk| acc.max(lanes(chunk)));
+    lanes.to_array().into_iter().fold(rest, f32::max)
+}
+
+/// Sum, eight at a time
+fn slice_sum(values: &[f32]) -> f32 {
+    let chunks = values.chunks_exact(LANES);
+    let rest: f32 = chunks.remainder().iter().sum();
+    let lanes = chunks.fold(f32x8::splat(0.0), |acc, chunk| acc + lanes(chunk));
+    lanes.reduce_add() + rest
+}
+
 /// Helper function for smooth low-frequency interpolation.
 /// 
 /// Defined at the module level so it can be called directly by name.
@@ -543,7 +807,76 @@ mod tests {
         let peaks = processor.update_peaks(&bars, 10.0);
         assert_eq!(peaks[0], 50.0);
     }
+
+    #[test]
+    fn test_peak_gravity_accelerates() {
+        let mut config = FFTConfig::default();
+        config.num_bars = 1;
+        config.peak_hold_time_ms = 0.0;
+        config.peak_release_time_ms = 1000.0;
+        config.peak_gravity = true;
+
+        let mut processor = FFTProcessor::new(config);
+        processor.update_peaks(&[0.0], 10.0);
+
+        // Each step drops further than the last
+        let first = -processor.update_peaks(&[-100.0], 100.0)[0];
+        let level = processor.peak_levels[0];
+        let second = level - processor.update_peaks(&[-100.0], 100.0)[0];
+        assert!(first > 0.0 && second > first, "{first} then {second}");
+
+        // And it lands on the bar instead of going through it
+        for _ in 0..20 {
+            processor.update_peaks(&[-100.0], 100.0);
+        }
+        assert_eq!(processor.peak_levels[0], -100.0);
+        assert_eq!(processor.peak_velocities[0], 0.0);
+    }
     
+    #[test]
+    fn test_carry_levels_from() {
+        let mut config = FFTConfig::default();
+        config.num_bars = 2;
+        let mut old = FFTProcessor::new(config.clone());
+        old.last_bar_heights = vec![-10.0, -40.0];
+        old.peak_levels = vec![-5.0, -30.0];
+
+        config.num_bars = 4;
+        config.sample_rate = 44100;
+        let mut new = FFTProcessor::new(config);
+        new.carry_levels_from(&old);
+        assert_eq!(new.last_bar_heights, vec![-10.0, -10.0, -40.0, -40.0]);
+        assert_eq!(new.peak_levels, vec![-5.0, -5.0, -30.0, -30.0]);
+
+        // Silence afterwards falls from there instead of starting at the floor
+        let (bars, _) = new.process(&[]);
+        assert!(bars[0] > SILENCE_DB && bars[0] <= -10.0);
+    }
+
+    #[test]
+    fn test_bar_count_change_crossfades() {
+        // Instant release, so without the fade the bars would drop to silence at once
+        let config = FFTConfig { num_bars: 2, release_time_ms: 1e-6, peak_release_time_ms: 1e-6, peak_hold_time_ms: 0.0, ..Default::default() };
+        let mut processor = FFTProcessor::new(config.clone());
+        processor.last_bar_heights = vec![-10.0, -40.0];
+        processor.peak_levels = vec![-5.0, -30.0];
+
+        processor.update_config(FFTConfig { num_bars: 4, ..config });
+        assert_eq!(processor.last_bar_heights, vec![-10.0, -10.0, -40.0, -40.0]);
+
+        // The old bars fade out a step per frame instead of vanishing
+        let mut previous = -10.0;
+        for _ in 0..CROSSFADE_FRAMES {
+            let bars = processor.process(&[]).0.to_vec();
+            let fallen = processor.last_bar_heights[0];
+            assert_eq!(bars.len(), 4);
+            assert!(bars[0] < previous && bars[0] > fallen, "{} after {}", bars[0], previous);
+            previous = bars[0];
+        }
+        let bars = processor.process(&[]).0.to_vec();
+        assert_eq!(bars, processor.last_bar_heights);
+    }
+
     #[test]
     fn test_frequency_mapping_boundaries() {
         let num_bars = 100;
@@ -671,6 +1004,58 @@ mod tests {
         assert!(max_db > -3.0, "Signal was attenuated too much. Measured: {:.1} dB", max_db);
     }
 
+    #[test]
+    fn test_spectral_peaks_find_tone_and_harmonic() {
+        let sample_rate = 48000;
+        let config = FFTConfig { fft_size: 4096, sample_rate, sensitivity: 1.0, ..Default::default() };
+        let mut processor = FFTProcessor::new(config);
+
+        // 440 Hz with a 2nd harmonic 40 dB down
+        let buffer: Vec<f32> = (0..4096)
+            .map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                (t * 440.0 * std::f32::consts::TAU).sin() + 0.01 * (t * 880.0 * std::f32::consts::TAU).sin()
+            })
+            .collect();
+        processor.process(&buffer);
+
+        let peaks = processor.spectral_peaks();
+        let near = |hz: f32| peaks.iter().filter(|p| (p.freq_hz - hz).abs() < 3.0).map(|p| p.db).fold(SILENCE_DB, f32::max);
+        assert!(near(440.0) - near(880.0) > 35.0 && near(440.0) - near(880.0) < 45.0);
+        assert!(peaks.windows(2).all(|w| w[0].freq_hz <= w[1].freq_hz));
+    }
+
+    #[test]
+    fn test_spectral_features_tone_vs_noise() {
+        let sample_rate = 48000;
+        let config = FFTConfig { fft_size: 4096, sample_rate, ..Default::default() };
+        let mut processor = FFTProcessor::new(config);
+
+        processor.process(&vec![0.0; 4096]);
+        assert_eq!(processor.spectral_features(), SpectralFeatures::default());
+
+        let tone: Vec<f32> = (0..4096).map(|i| (i as f32 / sample_rate as f32 * 1000.0 * std::f32::consts::TAU).sin()).collect();
+        processor.process(&tone);
+        let features = processor.spectral_features();
+        assert!((features.centroid_hz - 1000.0).abs() < 50.0, "centroid {}", features.centroid_hz);
+        assert!((features.rolloff_hz - 1000.0).abs() < 25.0, "rolloff {}", features.rolloff_hz);
+        assert!(features.flatness < 0.05, "flatness {}", features.flatness);
+
+        let mut seed = 1u32;
+        let noise: Vec<f32> = (0..4096)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+            })
+            .collect();
+        processor.process(&noise);
+        let features = processor.spectral_features();
+        // White noise: centroid near the middle, rolloff near 85% of Nyquist
+        assert!((features.centroid_hz - 12000.0).abs() < 1500.0, "centroid {}", features.centroid_hz);
+        assert!((features.rolloff_hz - 20400.0).abs() < 1500.0, "rolloff {}", features.rolloff_hz);
+        assert!(features.flatness > 0.3, "flatness {}", features.flatness);
+    }
+
     #[test]
     fn test_mapping_contiguity() {
         let config = FFTConfig { num_bars: 64, ..Default::default() };
@@ -701,7 +1086,8 @@ mod tests {
         let spike_idx = (treble_map.start_bin + treble_map.end_bin) / 2;
         magnitudes[spike_idx] = 0.0; // Max volume spike
 
-        let bars = processor.group_bins(&magnitudes);
+        let mut bars = Vec::new();
+        processor.group_bins(&magnitudes, &mut bars);
         
         // Peak aggregation should catch the 0.0 spike
         assert_eq!(bars[9], 0.0);
@@ -712,13 +1098,48 @@ mod tests {
         avg_config.aggregation_mode = crate::shared_state::AggregationMode::Average;
         let avg_processor = FFTProcessor::new(avg_config);
         
-        let avg_bars = avg_processor.group_bins(&magnitudes);
+        let mut avg_bars = Vec::new();
+        avg_processor.group_bins(&magnitudes, &mut avg_bars);
         // Average should be much lower than 0.0 since only 1 bin in a large slice is loud
         assert!(avg_bars[9] < -10.0);
     }
-}
 
+    // The scalar loops the SIMD helpers replaced, as the reference
+    fn scalar_db(magnitudes: &[f32], scale: f32) -> Vec<f32> {
+        magnitudes.iter().map(|&mag| 20.0 * (mag * scale + 1e-10).log10()).collect()
+    }
 
+    fn scalar_bars(processor: &FFTProcessor, db: &[f32]) -> Vec<f32> {
+        processor.mapping.iter().map(|map| {
+            let bins = &db[map.start_bin..(map.end_bin + 1).min(db.len())];
+            bins.iter().copied().fold(f32::MIN, f32::max) + bins.iter().sum::<f32>() / bins.len() as f32
+        }).collect()
+    }
 
+    fn simd_bars(processor: &FFTProcessor, db: &[f32]) -> Vec<f32> {
+        processor.mapping.iter().map(|map| {
+            let bins = &db[map.start_bin..(map.end_bin + 1).min(db.len())];
+            slice_max(bins) + slice_sum(bins) / bins.len() as f32
+        }).collect()
+    }
 
-            
\ No newline at end of file
+    fn test_magnitudes() -> Vec<f32> {
+        // 1025 bins isn't a multiple of 8, so the remainder path runs too
+        (0..1025).map(|i| (i as f32 * 0.37).sin().abs() * 10f32.powi(-(i % 9))).collect()
+    }
+
+    #[test]
+    fn test_simd_matches_scalar() {
+        let magnitudes = test_magnitudes();
+        let mut db = Vec::new();
+        magnitudes_to_db(&magnitudes, 0.044, &mut db);
+        for (simd, scalar) in db.iter().zip(scalar_db(&magnitudes, 0.044)) {
+            assert!((simd - scalar).abs() < 1e-3, "{} vs {}", simd, scalar);
+        }
+
+        let processor = FFTProcessor::new(FFTConfig { num_bars: 512, ..Default::default() });
+        for (simd, scalar) in simd_bars(&processor, &db).iter().zip(scalar_bars(&processor, &db)) {
+            assert!((simd - scalar).abs() < 1e-2, "{} vs {}", simd, scalar);
+        }
+    }
+}
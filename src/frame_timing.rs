@@ -0,0 +1,157 @@
+//! Per-stage frame times for the stats overlay, so it's clear whether the
+//! GUI or the DSP side is the bottleneck.
+//!
+//! Four stages make up a frame:
+//!   - lock wait: time spent waiting for the shared state, by the FFT thread
+//!     when it publishes and by the GUI before it draws
+//!   - FFT: windowing, the transform and the dB conversion
+//!   - aggregation: bins to bars, smoothing and peaks
+//!   - paint: building the visualizer's shapes on the GUI thread
+//!
+//! The FFT thread reports its stages for every block it processes; once per
+//! frame the GUI adds its own and closes a sample with the latest of both.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Frames kept for the overlay graph (two seconds at 60 FPS)
+pub const HISTORY: usize = 120;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    LockWait,
+    Fft,
+    Aggregation,
+    Paint,
+}
+
+impl Stage {
+    /// Bottom to top in the stacked graph
+    pub const ALL: [Stage; 4] = [Stage::LockWait, Stage::Fft, Stage::Aggregation, Stage::Paint];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Stage::LockWait => "Lock",
+            Stage::Fft => "FFT",
+            Stage::Aggregation => "Bins",
+            Stage::Paint => "Paint",
+        }
+    }
+}
+
+/// One frame: milliseconds spent in each stage, in `Stage::ALL` order
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StageTimes(pub [f32; 4]);
+
+impl StageTimes {
+    pub fn get(&self, stage: Stage) -> f32 {
+        self.0[stage as usize]
+    }
+
+    pub fn total(&self) -> f32 {
+        self.0.iter().sum()
+    }
+}
+
+/// Which side of the app is taking the time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bottleneck {
+    Gui,
+    Dsp,
+}
+
+impl Bottleneck {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Bottleneck::Gui => "GUI",
+            Bottleneck::Dsp => "DSP",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameTimings {
+    /// Latest block from the FFT thread: lock wait, FFT, aggregation
+    dsp: [Duration; 3],
+    history: VecDeque<StageTimes>,
+}
+
+impl FrameTimings {
+    /// FFT thread: the stages of the block just published
+    pub fn note_dsp(&mut self, lock_wait: Duration, fft: Duration, aggregation: Duration) {
+        self.dsp = [lock_wait, fft, aggregation];
+    }
+
+    /// GUI: close the frame with its own lock wait and paint time
+    pub fn note_frame(&mut self, lock_wait: Duration, paint: Duration) {
+        let ms = |d: Duration| d.as_secs_f32() * 1000.0;
+        let [dsp_lock, fft, aggregation] = self.dsp;
+        self.history.push_back(StageTimes([ms(dsp_lock + lock_wait), ms(fft), ms(aggregation), ms(paint)]));
+        if self.history.len() > HISTORY {
+            self.history.pop_front();
+        }
+    }
+
+    /// Recent frames, oldest first
+    pub fn history(&self) -> impl ExactSizeIterator<Item = &StageTimes> + '_ {
+        self.history.iter()
+    }
+
+    /// Mean of each stage over the history
+    pub fn average(&self) -> StageTimes {
+        let mut sum = StageTimes::default();
+        for frame in &self.history {
+            for (total, ms) in sum.0.iter_mut().zip(frame.0) {
+                *total += ms;
+            }
+        }
+        let n = self.history.len().max(1) as f32;
+        StageTimes(sum.0.map(|ms| ms / n))
+    }
+
+    /// The side with more time on average; lock waits count for neither, as
+    /// they're one side waiting on the other
+    pub fn bottleneck(&self) -> Option<Bottleneck> {
+        if self.history.is_empty() {
+            return None;
+        }
+        let average = self.average();
+        let dsp = average.get(Stage::Fft) + average.get(Stage::Aggregation);
+        let gui = average.get(Stage::Paint);
+        Some(if gui >= dsp { Bottleneck::Gui } else { Bottleneck::Dsp })
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn test_frames_combine_both_threads() {
+        let mut timings = FrameTimings::default();
+        timings.note_dsp(MS, 2 * MS, 3 * MS);
+        timings.note_frame(MS, 6 * MS);
+
+        let frame = *timings.history().next().unwrap();
+        assert_eq!(frame, StageTimes([2.0, 2.0, 3.0, 6.0]));
+        assert_eq!(frame.total(), 13.0);
+        assert_eq!(timings.bottleneck(), Some(Bottleneck::Gui));
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut timings = FrameTimings::default();
+        assert_eq!(timings.bottleneck(), None);
+        timings.note_dsp(Duration::ZERO, 10 * MS, MS);
+        for _ in 0..HISTORY + 10 {
+            timings.note_frame(Duration::ZERO, MS);
+        }
+        assert_eq!(timings.history().len(), HISTORY);
+        assert_eq!(timings.average().get(Stage::Fft), 10.0);
+        assert_eq!(timings.bottleneck(), Some(Bottleneck::Dsp));
+    }
+}
@@ -0,0 +1,308 @@
+//! Passive gain-staging advisor.
+//!
+//! Watches the mono signal going into the FFT and suggests fixes for the most
+//! common level problems novice users run into: a clipping source, a source so
+//! quiet the bars barely move, or a signal limited so hard it looks flat.
+//!
+//! Works on 100 ms blocks. For each block it keeps the K-weighted mean square
+//! (ITU-R BS.1770, for loudness in LUFS), the plain mean square and peak (for
+//! crest factor) and the number of clipped samples. Loudness and crest factor
+//! are read over the last 3 s ("short-term"), clipping over the last 5 s.
+//! It never changes anything itself; the GUI shows the hint and the user
+//! decides.
+
+use std::collections::VecDeque;
+
+/// Block length for all statistics (seconds)
+const BLOCK_SEC: f32 = 0.1;
+/// Blocks in the short-term loudness / crest window (3 s)
+const SHORT_TERM_BLOCKS: usize = 30;
+/// Blocks kept in total, and the clip-counting window (5 s)
+const HISTORY_BLOCKS: usize = 50;
+
+/// Sample magnitude counted as a clip
+const CLIP_LEVEL: f32 = 0.999;
+/// Clipped samples within the history before we complain
+const CLIP_COUNT: u32 = 8;
+
+/// Short-term loudness below this counts as silence, not "quiet"
+const SILENCE_LUFS: f32 = -70.0;
+/// Quieter than this (after sensitivity) is "too quiet"
+const QUIET_LUFS: f32 = -45.0;
+/// Louder than this is "too hot"
+const LOUD_LUFS: f32 = -6.0;
+/// Crest factor below this with a loud signal reads as heavily limited
+const SQUASHED_CREST_DB: f32 = 5.0;
+const SQUASHED_MIN_LUFS: f32 = -20.0;
+
+/// One suggestion. Ordered by priority (first wins).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GainHint {
+    Clipping,
+    TooLoud,
+    Squashed,
+    TooQuiet,
+}
+
+impl GainHint {
+    pub fn message(self) -> &'static str {
+        match self {
+            GainHint::Clipping => "Input clipping — reduce the source volume",
+            GainHint::TooLoud => "Input is very loud — lower the source volume to leave some headroom",
+            GainHint::Squashed => "Signal is heavily limited — peaks are flattened, so bars will look static",
+            GainHint::TooQuiet => "Signal very quiet — raise the sensitivity or the source volume",
+        }
+    }
+}
+
+/// Statistics for one finished block
+#[derive(Clone, Copy, Default)]
+struct Block {
+    /// K-weighted mean square
+    weighted_ms: f64,
+    /// Unweighted mean square
+    raw_ms: f64,
+    peak: f32,
+    clips: u32,
+}
+
+/// Direct form I biquad
+#[derive(Clone, Copy, Default)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 3],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, input: f64) -> f64 {
+        let out = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[1] * self.y[0]
+            - self.a[2] * self.y[1];
+        self.x = [input, self.x[0]];
+        self.y = [out, self.y[0]];
+        out
+    }
+}
+
+/// BS.1770 K-weighting (high shelf + RLB high-pass) for any sample rate
+fn k_weighting(rate: u32) -> [Biquad; 2] {
+    let rate = rate as f64;
+
+    // Stage 1: high shelf, +4 dB above ~1.7 kHz
+    let f0 = 1681.974450955533;
+    let gain = 3.999843853973347;
+    let q = 0.7071752369554196;
+    let k = (std::f64::consts::PI * f0 / rate).tan();
+    let vh = 10f64.powf(gain / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+        a: [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        ..Default::default()
+    };
+
+    // Stage 2: high-pass at ~38 Hz
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+    let k = (std::f64::consts::PI * f0 / rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let highpass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        ..Default::default()
+    };
+
+    [shelf, highpass]
+}
+
+pub struct GainAdvisor {
+    rate: u32,
+    filters: [Biquad; 2],
+
+    /// Block in progress
+    current: Block,
+    current_len: usize,
+
+    /// Finished blocks, newest at the back
+    blocks: VecDeque<Block>,
+}
+
+impl Default for GainAdvisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GainAdvisor {
+    pub fn new() -> Self {
+        Self {
+            rate: 0,
+            filters: [Biquad::default(); 2],
+            current: Block::default(),
+            current_len: 0,
+            blocks: VecDeque::with_capacity(HISTORY_BLOCKS),
+        }
+    }
+
+    /// Feed a block of mono samples at `rate`
+    pub fn process(&mut self, samples: &[f32], rate: u32) {
+        if rate == 0 {
+            return;
+        }
+        if rate != self.rate {
+            // New device / rate: old statistics don't apply any more
+            *self = Self::new();
+            self.rate = rate;
+            self.filters = k_weighting(rate);
+        }
+        let block_len = ((rate as f32 * BLOCK_SEC) as usize).max(1);
+
+        for &s in samples {
+            let shelved = self.filters[0].process(s as f64);
+            let weighted = self.filters[1].process(shelved);
+            self.current.weighted_ms += weighted * weighted;
+            self.current.raw_ms += (s as f64) * (s as f64);
+            self.current.peak = self.current.peak.max(s.abs());
+            if s.abs() >= CLIP_LEVEL {
+                self.current.clips += 1;
+            }
+            self.current_len += 1;
+
+            if self.current_len == block_len {
+                let mut done = std::mem::take(&mut self.current);
+                done.weighted_ms /= block_len as f64;
+                done.raw_ms /= block_len as f64;
+                if self.blocks.len() == HISTORY_BLOCKS {
+                    self.blocks.pop_front();
+                }
+                self.blocks.push_back(done);
+                self.current_len = 0;
+            }
+        }
+    }
+
+    fn short_term(&self) -> impl Iterator<Item = &Block> {
+        self.blocks.iter().skip(self.blocks.len().saturating_sub(SHORT_TERM_BLOCKS))
+    }
+
+    /// Short-term loudness (LUFS) over the last 3 s, `None` until 3 s were seen
+    fn short_term_lufs(&self) -> Option<f32> {
+        if self.blocks.len() < SHORT_TERM_BLOCKS {
+            return None;
+        }
+        let ms = self.short_term().map(|b| b.weighted_ms).sum::<f64>() / SHORT_TERM_BLOCKS as f64;
+        Some((-0.691 + 10.0 * ms.max(1e-20).log10()) as f32)
+    }
+
+    /// Peak-to-RMS ratio (dB) over the last 3 s
+    fn crest_db(&self) -> Option<f32> {
+        if self.blocks.len() < SHORT_TERM_BLOCKS {
+            return None;
+        }
+        let ms = self.short_term().map(|b| b.raw_ms).sum::<f64>() / SHORT_TERM_BLOCKS as f64;
+        let peak = self.short_term().fold(0.0f32, |m, b| m.max(b.peak));
+        if ms <= 0.0 || peak <= 0.0 {
+            return None;
+        }
+        Some(20.0 * peak.log10() - 10.0 * ms.log10() as f32)
+    }
+
+    /// Current suggestion, if any. `sensitivity_db` is the display gain the
+    /// user already applies, so a quiet source they've compensated for is fine.
+    pub fn hint(&self, sensitivity_db: f32) -> Option<GainHint> {
+        let clips: u32 = self.blocks.iter().map(|b| b.clips).sum();
+        if clips >= CLIP_COUNT {
+            return Some(GainHint::Clipping);
+        }
+
+        let lufs = self.short_term_lufs()?;
+        if lufs < SILENCE_LUFS {
+            return None;
+        }
+        if lufs > LOUD_LUFS {
+            return Some(GainHint::TooLoud);
+        }
+        if lufs > SQUASHED_MIN_LUFS && self.crest_db().is_some_and(|c| c < SQUASHED_CREST_DB) {
+            return Some(GainHint::Squashed);
+        }
+        if lufs + sensitivity_db < QUIET_LUFS {
+            return Some(GainHint::TooQuiet);
+        }
+        None
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48000;
+
+    fn sine(amplitude: f32, freq: f32, seconds: f32) -> Vec<f32> {
+        (0..(RATE as f32 * seconds) as usize)
+            .map(|i| amplitude * (i as f32 / RATE as f32 * freq * std::f32::consts::TAU).sin())
+            .collect()
+    }
+
+    fn feed(advisor: &mut GainAdvisor, samples: &[f32]) {
+        for chunk in samples.chunks(1024) {
+            advisor.process(chunk, RATE);
+        }
+    }
+
+    #[test]
+    fn test_full_scale_1k_sine_is_about_minus_3_lufs() {
+        // BS.1770 reference: a 0 dBFS 997 Hz sine in one channel reads -3.01 LUFS
+        let mut advisor = GainAdvisor::new();
+        feed(&mut advisor, &sine(1.0, 997.0, 4.0));
+        let lufs = advisor.short_term_lufs().unwrap();
+        assert!((lufs + 3.01).abs() < 0.2, "got {}", lufs);
+    }
+
+    #[test]
+    fn test_sine_crest_factor_is_3_db() {
+        let mut advisor = GainAdvisor::new();
+        feed(&mut advisor, &sine(0.5, 440.0, 4.0));
+        let crest = advisor.crest_db().unwrap();
+        assert!((crest - 3.01).abs() < 0.2, "got {}", crest);
+    }
+
+    #[test]
+    fn test_hints() {
+        // Nothing until there is enough signal to judge
+        let mut advisor = GainAdvisor::new();
+        feed(&mut advisor, &sine(0.1, 440.0, 1.0));
+        assert_eq!(advisor.hint(0.0), None);
+
+        // Clipping
+        let mut advisor = GainAdvisor::new();
+        feed(&mut advisor, &sine(1.2, 440.0, 1.0).iter().map(|s| s.clamp(-1.0, 1.0)).collect::<Vec<_>>());
+        assert_eq!(advisor.hint(0.0), Some(GainHint::Clipping));
+
+        // Very quiet (-60 dBFS), unless sensitivity already compensates
+        let mut advisor = GainAdvisor::new();
+        feed(&mut advisor, &sine(0.001, 440.0, 4.0));
+        assert_eq!(advisor.hint(0.0), Some(GainHint::TooQuiet));
+        assert_eq!(advisor.hint(30.0), None);
+
+        // Silence is not "quiet"
+        let mut advisor = GainAdvisor::new();
+        feed(&mut advisor, &vec![0.0; RATE as usize * 4]);
+        assert_eq!(advisor.hint(0.0), None);
+
+        // Healthy level
+        let mut advisor = GainAdvisor::new();
+        let music: Vec<f32> = sine(0.3, 440.0, 4.0)
+            .iter()
+            .enumerate()
+            .map(|(i, s)| if i % 4800 < 200 { s * 3.0 } else { *s })
+            .collect();
+        feed(&mut advisor, &music);
+        assert_eq!(advisor.hint(0.0), None);
+    }
+}
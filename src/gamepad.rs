@@ -0,0 +1,168 @@
+//! Gamepad input for living-room PCs without a mouse or keyboard.
+//!
+//! A background thread reads controller events with gilrs and turns them
+//! into a handful of `PadButton`s for the GUI, which polls them once per
+//! frame. The left stick works as a second D-pad.
+//!
+//! gilrs needs libudev on Linux, so it's behind the `gamepad` cargo feature.
+//! Without it `start` hands back a channel that never receives anything and
+//! the setting explains why.
+
+use crossbeam_channel::Receiver;
+use eframe::egui;
+
+/// Whether this build can read controllers at all
+pub const SUPPORTED: bool = cfg!(feature = "gamepad");
+
+/// What the GUI cares about, whatever the controller calls it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PadButton {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// A / Cross
+    Confirm,
+    /// B / Circle
+    Back,
+    /// Start / Options
+    Menu,
+    LeftBumper,
+    RightBumper,
+}
+
+/// Stick deflection that counts as a press, and the one it has to drop
+/// back under before it can press again
+const STICK_PRESS: f32 = 0.6;
+const STICK_RELEASE: f32 = 0.3;
+
+/// Turns one stick axis into discrete presses
+#[derive(Default)]
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+struct StickEdge {
+    held: bool,
+}
+
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+impl StickEdge {
+    /// `Some(true)` pushed positive, `Some(false)` pushed negative
+    fn update(&mut self, value: f32) -> Option<bool> {
+        if self.held {
+            self.held = value.abs() > STICK_RELEASE;
+            return None;
+        }
+        if value.abs() >= STICK_PRESS {
+            self.held = true;
+            return Some(value > 0.0);
+        }
+        None
+    }
+}
+
+/// Start reading controllers. `ctx` is repainted on every press so the GUI
+/// reacts even while auto sleep has it idling. Call once; the thread ends
+/// with the first press after the receiver is dropped.
+pub fn start(ctx: egui::Context) -> Receiver<PadButton> {
+    let (tx, rx) = crossbeam_channel::unbounded();
+    #[cfg(feature = "gamepad")]
+    {
+        let spawned = std::thread::Builder::new()
+            .name("bespec-gamepad".to_string())
+            .spawn(move || imp::run(tx, ctx));
+        if let Err(e) = spawned {
+            tracing::error!("[Gamepad] Failed to start input thread: {}", e);
+        }
+    }
+    #[cfg(not(feature = "gamepad"))]
+    {
+        drop((tx, ctx));
+        tracing::info!("[Gamepad] Built without the 'gamepad' feature, no controller input");
+    }
+    rx
+}
+
+#[cfg(feature = "gamepad")]
+mod imp {
+    use super::{PadButton, StickEdge};
+    use crossbeam_channel::Sender;
+    use eframe::egui;
+    use gilrs::{Axis, Button, EventType, Gilrs};
+
+    fn map_button(button: Button) -> Option<PadButton> {
+        Some(match button {
+            Button::DPadUp => PadButton::Up,
+            Button::DPadDown => PadButton::Down,
+            Button::DPadLeft => PadButton::Left,
+            Button::DPadRight => PadButton::Right,
+            Button::South => PadButton::Confirm,
+            Button::East => PadButton::Back,
+            Button::Start => PadButton::Menu,
+            Button::LeftTrigger => PadButton::LeftBumper,
+            Button::RightTrigger => PadButton::RightBumper,
+            _ => return None,
+        })
+    }
+
+    pub(super) fn run(tx: Sender<PadButton>, ctx: egui::Context) {
+        let mut gilrs = match Gilrs::new() {
+            Ok(g) => g,
+            Err(e) => {
+                tracing::error!("[Gamepad] Couldn't open controller input: {}", e);
+                return;
+            }
+        };
+        for (_, pad) in gilrs.gamepads() {
+            tracing::info!("[Gamepad] Found {}", pad.name());
+        }
+
+        let (mut stick_x, mut stick_y) = (StickEdge::default(), StickEdge::default());
+        loop {
+            let Some(event) = gilrs.next_event_blocking(None) else { continue };
+            let pressed = match event.event {
+                EventType::ButtonPressed(button, _) => map_button(button),
+                EventType::AxisChanged(Axis::LeftStickX, v, _) => {
+                    stick_x.update(v).map(|right| if right { PadButton::Right } else { PadButton::Left })
+                }
+                // Stick up is positive
+                EventType::AxisChanged(Axis::LeftStickY, v, _) => {
+                    stick_y.update(v).map(|up| if up { PadButton::Up } else { PadButton::Down })
+                }
+                EventType::Connected => {
+                    tracing::info!("[Gamepad] Connected: {}", gilrs.gamepad(event.id).name());
+                    None
+                }
+                EventType::Disconnected => {
+                    tracing::info!("[Gamepad] Disconnected");
+                    None
+                }
+                _ => None,
+            };
+            if let Some(button) = pressed {
+                if tx.send(button).is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+            }
+        }
+        tracing::debug!("[Gamepad] Input thread stopped");
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stick_presses_once_per_push() {
+        let mut edge = StickEdge::default();
+        assert_eq!(edge.update(0.2), None);
+        assert_eq!(edge.update(0.7), Some(true));
+        // Held, or easing off a bit, doesn't repeat
+        assert_eq!(edge.update(0.9), None);
+        assert_eq!(edge.update(0.4), None);
+        assert_eq!(edge.update(0.1), None);
+        assert_eq!(edge.update(-0.8), Some(false));
+    }
+}
@@ -0,0 +1,377 @@
+//! Background image behind the visualizer: a still PNG / JPEG, an animated
+//! GIF played on a loop, or the playing track's album art.
+//!
+//! `BackgroundTexture` keeps the decoded frames as textures in the GUI, next
+//! to the album art, and only reloads when the profile's path changes. The
+//! file is decoded on the task pool; the frames come back through
+//! `SharedState::background_decoded` and are uploaded by `sync`. A file that
+//! fails to load is logged once and left blank until the path changes again.
+//! Big pictures are scaled down to `MAX_SIDE` first; there's no point in an
+//! 8K texture behind 150 bars. A long GIF stops at `MAX_FRAMES` or
+//! `MAX_TOTAL_PIXELS`, whichever comes first.
+//!
+//! Album art is blurred on the CPU, once per track: shrunk to `ART_SIDE`
+//! (which already softens it), then a few box blur passes, which add up to
+//! roughly a Gaussian, then darkened so the bars stay readable on top.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+use image::AnimationDecoder;
+
+use crate::gui::tasks::TaskRunner;
+use crate::shared_state::{BackgroundImage, ImageFit, SharedState};
+
+/// Longest side of a background texture (px)
+const MAX_SIDE: u32 = 2048;
+/// Frames kept from an animated GIF; the rest of a long one is dropped
+const MAX_FRAMES: usize = 300;
+/// Pixels kept across all frames (32 Mpx, 128 MB of RGBA)
+const MAX_TOTAL_PIXELS: usize = 32 * 1024 * 1024;
+/// More tiles than this and the image is stretched instead
+const MAX_TILES: usize = 4096;
+/// GIFs asking for (almost) no frame time get this, as browsers do
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// Longest side of the blurred album art (px); scaled up, it's all blur anyway
+const ART_SIDE: usize = 64;
+/// Box blur radius (px at `ART_SIDE`) and passes
+const ART_BLUR_RADIUS: usize = 3;
+const ART_BLUR_PASSES: usize = 3;
+/// Brightness left after dimming
+const ART_DIM: f32 = 0.5;
+
+/// Frames decoded on the task pool, waiting for `BackgroundTexture::sync`
+pub struct Decoded {
+    /// Path they came from, so a late result for an old path is ignored
+    pub path: String,
+    pub frames: Vec<(egui::ColorImage, Duration)>,
+}
+
+/// Decoded frames of the current background image, lives in the GUI
+#[derive(Default)]
+pub struct BackgroundTexture {
+    /// Path the frames came from (also after a failed load)
+    path: String,
+    textures: Vec<egui::TextureHandle>,
+    delays: Vec<Duration>,
+    started: Option<Instant>,
+    /// The current track's art, already blurred
+    album_art: Option<egui::TextureHandle>,
+}
+
+impl BackgroundTexture {
+    /// New track: blur its art (RGBA pixels), or forget the last one's
+    pub fn set_album_art(&mut self, ctx: &egui::Context, art: Option<(&[u8], [usize; 2])>) {
+        self.album_art = art
+            .and_then(|(pixels, size)| blur_album_art(pixels, size))
+            .map(|image| ctx.load_texture("album_art_blurred", image, egui::TextureOptions::LINEAR));
+    }
+
+    /// Start decoding when the profile's path changed, and upload frames that
+    /// finished decoding. Call once per frame.
+    pub fn sync(&mut self, ctx: &egui::Context, tasks: &TaskRunner, state: &mut SharedState) {
+        let path = &state.config.profile.background_image.path;
+        if *path != self.path {
+            self.path = path.clone();
+            self.textures.clear();
+            self.delays.clear();
+            self.started = None;
+            if !path.trim().is_empty() {
+                let path = path.clone();
+                tasks.spawn(ctx, "Background image", move || {
+                    let frames = match decode(Path::new(path.trim())) {
+                        Ok(frames) => {
+                            tracing::info!("[GUI] Background image '{}': {} frame(s)", path, frames.len());
+                            frames
+                        }
+                        Err(e) => {
+                            tracing::warn!("[GUI] Couldn't load background image '{}': {}", path, e);
+                            Vec::new()
+                        }
+                    };
+                    Ok(Box::new(move |state: &mut SharedState| state.background_decoded = Some(Decoded { path, frames })))
+                });
+            }
+        }
+
+        let Some(decoded) = state.background_decoded.take() else { return };
+        if decoded.path != self.path {
+            return;
+        }
+        for (i, (image, delay)) in decoded.frames.into_iter().enumerate() {
+            self.textures.push(ctx.load_texture(format!("background_{}", i), image, egui::TextureOptions::LINEAR));
+            self.delays.push(delay);
+        }
+        self.started = Some(Instant::now());
+    }
+
+    /// Paint the profile's image into `rect` (whatever `sync` has loaded so far)
+    pub fn draw(&self, painter: &egui::Painter, rect: egui::Rect, settings: &BackgroundImage, now: Instant) {
+        if settings.opacity <= 0.0 {
+            return;
+        }
+        let tint = egui::Color32::WHITE.gamma_multiply(settings.opacity.min(1.0));
+        let painter = painter.with_clip_rect(painter.clip_rect().intersect(rect));
+
+        if let Some(art) = self.album_art.as_ref().filter(|_| settings.album_art) {
+            for (dest, uv) in placements(art.size_vec2(), rect, settings.fit) {
+                painter.image(art.id(), dest, uv, tint);
+            }
+            return;
+        }
+        if self.textures.is_empty() {
+            return;
+        }
+
+        let elapsed = now.saturating_duration_since(self.started.unwrap_or(now));
+        let frame = frame_at(&self.delays, elapsed);
+        let texture = &self.textures[frame];
+        for (dest, uv) in placements(texture.size_vec2(), rect, settings.fit) {
+            painter.image(texture.id(), dest, uv, tint);
+        }
+
+        if self.textures.len() > 1 {
+            painter.ctx().request_repaint_after(self.delays[frame]);
+        }
+    }
+}
+
+/// Every frame of the file with how long it shows (zero for a still image)
+fn decode(path: &Path) -> Result<Vec<(egui::ColorImage, Duration)>, image::ImageError> {
+    let is_gif = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("gif"));
+    let frames = if is_gif {
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        let decoder = image::codecs::gif::GifDecoder::new(file)?;
+        let mut frames = Vec::new();
+        let mut pixels = 0;
+        for frame in decoder.into_frames().take(MAX_FRAMES) {
+            let frame = frame?;
+            let (numer, denom) = frame.delay().numer_denom_ms();
+            let delay = Duration::from_micros(numer as u64 * 1000 / denom.max(1) as u64);
+            let delay = if delay < Duration::from_millis(20) { DEFAULT_FRAME_DELAY } else { delay };
+            let rgba = shrink(frame.into_buffer());
+            pixels += rgba.width() as usize * rgba.height() as usize;
+            if pixels > MAX_TOTAL_PIXELS && !frames.is_empty() {
+                tracing::info!("[GUI] Background GIF cut at {} frames", frames.len());
+                break;
+            }
+            frames.push((rgba, delay));
+        }
+        frames
+    } else {
+        vec![(shrink(image::open(path)?.into_rgba8()), Duration::ZERO)]
+    };
+
+    Ok(frames
+        .into_iter()
+        .map(|(rgba, delay)| {
+            let size = [rgba.width() as usize, rgba.height() as usize];
+            (egui::ColorImage::from_rgba_unmultiplied(size, rgba.as_raw()), delay)
+        })
+        .collect())
+}
+
+/// Scale down to `MAX_SIDE`, keeping the aspect ratio
+fn shrink(rgba: image::RgbaImage) -> image::RgbaImage {
+    let (w, h) = rgba.dimensions();
+    if w.max(h) <= MAX_SIDE {
+        return rgba;
+    }
+    let scale = MAX_SIDE as f32 / w.max(h) as f32;
+    let (nw, nh) = (((w as f32 * scale) as u32).max(1), ((h as f32 * scale) as u32).max(1));
+    image::imageops::resize(&rgba, nw, nh, image::imageops::FilterType::Triangle)
+}
+
+/// Album art (RGBA, `size` = [width, height]) shrunk, blurred and dimmed,
+/// `None` if the pixels don't match the size
+pub fn blur_album_art(pixels: &[u8], size: [usize; 2]) -> Option<egui::ColorImage> {
+    let [w, h] = size;
+    if w == 0 || h == 0 || pixels.len() != w * h * 4 {
+        return None;
+    }
+
+    // Shrink by averaging each output pixel's source block
+    let scale = (w.max(h) as f32 / ART_SIDE as f32).max(1.0);
+    let (sw, sh) = (((w as f32 / scale) as usize).max(1), ((h as f32 / scale) as usize).max(1));
+    let mut small = vec![[0.0f32; 3]; sw * sh];
+    for y in 0..sh {
+        for x in 0..sw {
+            let (x0, x1) = (x * w / sw, ((x + 1) * w / sw).max(x * w / sw + 1));
+            let (y0, y1) = (y * h / sh, ((y + 1) * h / sh).max(y * h / sh + 1));
+            let mut sum = [0.0f32; 3];
+            for sy in y0..y1 {
+                for sx in x0..x1 {
+                    let i = (sy * w + sx) * 4;
+                    for (s, &v) in sum.iter_mut().zip(&pixels[i..i + 3]) {
+                        *s += v as f32;
+                    }
+                }
+            }
+            let n = ((x1 - x0) * (y1 - y0)) as f32;
+            small[y * sw + x] = sum.map(|v| v / n);
+        }
+    }
+
+    for _ in 0..ART_BLUR_PASSES {
+        small = box_blur(&small, sw, sh, true);
+        small = box_blur(&small, sw, sh, false);
+    }
+
+    let rgba: Vec<egui::Color32> = small
+        .iter()
+        .map(|p| {
+            let [r, g, b] = p.map(|v| (v * ART_DIM).round().clamp(0.0, 255.0) as u8);
+            egui::Color32::from_rgb(r, g, b)
+        })
+        .collect();
+    Some(egui::ColorImage { size: [sw, sh], pixels: rgba })
+}
+
+/// One box blur pass along rows (or columns), clamping at the edges
+fn box_blur(src: &[[f32; 3]], w: usize, h: usize, horizontal: bool) -> Vec<[f32; 3]> {
+    let (len, lines) = if horizontal { (w, h) } else { (h, w) };
+    let index = |line: usize, pos: usize| if horizontal { line * w + pos } else { pos * w + line };
+    let mut out = vec![[0.0f32; 3]; src.len()];
+    let r = ART_BLUR_RADIUS as isize;
+    for line in 0..lines {
+        for pos in 0..len {
+            let mut sum = [0.0f32; 3];
+            for k in -r..=r {
+                let p = (pos as isize + k).clamp(0, len as isize - 1) as usize;
+                for (s, v) in sum.iter_mut().zip(src[index(line, p)]) {
+                    *s += v;
+                }
+            }
+            out[index(line, pos)] = sum.map(|v| v / (2 * r + 1) as f32);
+        }
+    }
+    out
+}
+
+/// Which frame of a looping animation shows after `elapsed`
+pub fn frame_at(delays: &[Duration], elapsed: Duration) -> usize {
+    let total: Duration = delays.iter().sum();
+    if total.is_zero() {
+        return 0;
+    }
+    let mut t = Duration::from_nanos((elapsed.as_nanos() % total.as_nanos()) as u64);
+    for (i, &delay) in delays.iter().enumerate() {
+        if t < delay {
+            return i;
+        }
+        t -= delay;
+    }
+    delays.len() - 1
+}
+
+/// Where to draw an image of `size` in `rect`: (screen rect, uv rect) pairs.
+/// Anything outside `rect` is clipped by the caller.
+pub fn placements(size: egui::Vec2, rect: egui::Rect, fit: ImageFit) -> Vec<(egui::Rect, egui::Rect)> {
+    let full_uv = egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0));
+    if size.x <= 0.0 || size.y <= 0.0 || rect.width() <= 0.0 || rect.height() <= 0.0 {
+        return Vec::new();
+    }
+
+    match fit {
+        ImageFit::Stretch => vec![(rect, full_uv)],
+        ImageFit::Fit => {
+            let scale = (rect.width() / size.x).min(rect.height() / size.y);
+            vec![(egui::Rect::from_center_size(rect.center(), size * scale), full_uv)]
+        }
+        ImageFit::Fill => {
+            // Show the middle of the image, as much as the window's shape allows
+            let scale = (rect.width() / size.x).max(rect.height() / size.y);
+            let visible = egui::vec2(rect.width() / (size.x * scale), rect.height() / (size.y * scale));
+            vec![(rect, egui::Rect::from_center_size(egui::pos2(0.5, 0.5), visible))]
+        }
+        ImageFit::Tile => {
+            let cols = (rect.width() / size.x).ceil() as usize;
+            let rows = (rect.height() / size.y).ceil() as usize;
+            if cols * rows > MAX_TILES {
+                return vec![(rect, full_uv)];
+            }
+            (0..rows)
+                .flat_map(|row| (0..cols).map(move |col| (row, col)))
+                .map(|(row, col)| {
+                    let min = rect.min + egui::vec2(col as f32 * size.x, row as f32 * size.y);
+                    (egui::Rect::from_min_size(min, size), full_uv)
+                })
+                .collect()
+        }
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window() -> egui::Rect {
+        egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(400.0, 200.0))
+    }
+
+    #[test]
+    fn test_fit_and_fill_keep_the_aspect_ratio() {
+        let square = egui::vec2(100.0, 100.0);
+
+        // Fit: letterboxed, 200 px square in the middle
+        let fit = placements(square, window(), ImageFit::Fit);
+        assert_eq!(fit[0].0, egui::Rect::from_min_max(egui::pos2(100.0, 0.0), egui::pos2(300.0, 200.0)));
+
+        // Fill: covers the window, showing the middle half of the image
+        let fill = placements(square, window(), ImageFit::Fill);
+        assert_eq!(fill[0].0, window());
+        assert_eq!(fill[0].1, egui::Rect::from_min_max(egui::pos2(0.0, 0.25), egui::pos2(1.0, 0.75)));
+
+        assert_eq!(placements(square, window(), ImageFit::Stretch)[0].0, window());
+    }
+
+    #[test]
+    fn test_tiles_cover_the_window() {
+        let tiles = placements(egui::vec2(150.0, 150.0), window(), ImageFit::Tile);
+        // 3 across (the last one clipped), 2 down
+        assert_eq!(tiles.len(), 6);
+        assert_eq!(tiles[5].0.min, egui::pos2(300.0, 150.0));
+
+        // A 1 px image would mean thousands of tiles
+        assert_eq!(placements(egui::vec2(1.0, 1.0), window(), ImageFit::Tile).len(), 1);
+        assert!(placements(egui::Vec2::ZERO, window(), ImageFit::Fill).is_empty());
+    }
+
+    #[test]
+    fn test_album_art_is_shrunk_blurred_and_dimmed() {
+        // Flat gray stays flat, at half the brightness
+        let gray = vec![200u8; 256 * 128 * 4];
+        let image = blur_album_art(&gray, [256, 128]).unwrap();
+        assert_eq!(image.size, [64, 32]);
+        assert!(image.pixels.iter().all(|&p| p == egui::Color32::from_rgb(100, 100, 100)));
+
+        // A hard white / black edge turns into a ramp
+        let mut half = vec![0u8; 32 * 32 * 4];
+        for (i, px) in half.chunks_mut(4).enumerate() {
+            if i % 32 < 16 {
+                px.copy_from_slice(&[255, 255, 255, 255]);
+            }
+        }
+        let image = blur_album_art(&half, [32, 32]).unwrap();
+        let at = |x: usize| image.pixels[10 * 32 + x].r();
+        assert_eq!((at(0), at(31)), (128, 0));
+        assert!(at(14) > at(15) && at(15) > at(16) && at(16) > at(17));
+
+        assert!(blur_album_art(&half, [10, 10]).is_none());
+    }
+
+    #[test]
+    fn test_animation_loops() {
+        let delays = [Duration::from_millis(100), Duration::from_millis(50), Duration::from_millis(100)];
+        assert_eq!(frame_at(&delays, Duration::ZERO), 0);
+        assert_eq!(frame_at(&delays, Duration::from_millis(120)), 1);
+        assert_eq!(frame_at(&delays, Duration::from_millis(200)), 2);
+        // 250 ms a loop
+        assert_eq!(frame_at(&delays, Duration::from_millis(1010)), 0);
+        assert_eq!(frame_at(&[Duration::ZERO], Duration::from_secs(3)), 0);
+    }
+}
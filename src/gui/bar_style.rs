@@ -0,0 +1,83 @@
+//! Bar styling shared by the Solid and Segmented modes: rounded caps at the
+//! tip, outline-only bars, hatched fills and drop shadows. See `BarStyle`.
+//!
+//! The helpers work on the finished bar rectangle in screen space, so they
+//! don't care which way the bars grow; only the rounded corners need to know
+//! where the tip is.
+
+use eframe::egui;
+
+use crate::shared_state::Orientation;
+
+/// How far the shadow falls, down and to the right
+const SHADOW_OFFSET: egui::Vec2 = egui::vec2(3.0, 3.0);
+const SHADOW_ALPHA: u8 = 90;
+/// Distance between hatch lines, measured along the bar
+const HATCH_SPACING: f32 = 6.0;
+/// How bright a hatched bar's fill is under its lines
+pub const HATCH_FILL: f32 = 0.3;
+pub const OUTLINE_WIDTH: f32 = 1.5;
+
+/// Radius of a fully rounded tip for a bar `width` wide and `length` long
+pub fn cap_radius(width: f32, length: f32) -> f32 {
+    (width.min(length) / 2.0).max(0.0)
+}
+
+/// Rounding for a bar's tip corners only; the base stays square
+pub fn tip_rounding(orientation: Orientation, radius: f32) -> egui::Rounding {
+    let r = radius;
+    match orientation {
+        Orientation::BottomUp => egui::Rounding { nw: r, ne: r, sw: 0.0, se: 0.0 },
+        Orientation::TopDown => egui::Rounding { nw: 0.0, ne: 0.0, sw: r, se: r },
+        Orientation::LeftRight => egui::Rounding { nw: 0.0, ne: r, sw: 0.0, se: r },
+        Orientation::RightLeft => egui::Rounding { nw: r, ne: 0.0, sw: r, se: 0.0 },
+    }
+}
+
+/// Soft shadow behind a bar; draw it before the bar
+pub fn shadow(painter: &egui::Painter, rect: egui::Rect, rounding: egui::Rounding) {
+    painter.rect_filled(rect.translate(SHADOW_OFFSET), rounding, egui::Color32::from_black_alpha(SHADOW_ALPHA));
+}
+
+/// Just the bar's edge
+pub fn outline(painter: &egui::Painter, rect: egui::Rect, rounding: egui::Rounding, color: egui::Color32) {
+    painter.rect_stroke(rect.shrink(OUTLINE_WIDTH / 2.0), rounding, egui::Stroke::new(OUTLINE_WIDTH, color));
+}
+
+/// Diagonal lines across a bar; draw them over a dimmed fill
+pub fn hatch(painter: &egui::Painter, rect: egui::Rect, color: egui::Color32) {
+    if rect.width() <= 0.0 || rect.height() <= 0.0 {
+        return;
+    }
+    let painter = painter.with_clip_rect(rect.intersect(painter.clip_rect()));
+    let stroke = egui::Stroke::new(1.0, color);
+    // 45° lines, anchored to the screen so neighbouring bars line up
+    let start = ((rect.left() - rect.bottom()) / HATCH_SPACING).floor() * HATCH_SPACING;
+    let mut x = start;
+    while x < rect.right() - rect.top() {
+        painter.line_segment([egui::pos2(x + rect.bottom(), rect.bottom()), egui::pos2(x + rect.top(), rect.top())], stroke);
+        x += HATCH_SPACING;
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_the_tip_is_rounded() {
+        let up = tip_rounding(Orientation::BottomUp, 4.0);
+        assert_eq!((up.nw, up.ne, up.sw, up.se), (4.0, 4.0, 0.0, 0.0));
+        let right = tip_rounding(Orientation::LeftRight, 4.0);
+        assert_eq!((right.nw, right.ne, right.sw, right.se), (0.0, 4.0, 0.0, 4.0));
+    }
+
+    #[test]
+    fn test_cap_radius_fits_short_bars() {
+        assert_eq!(cap_radius(10.0, 100.0), 5.0);
+        assert_eq!(cap_radius(10.0, 4.0), 2.0);
+        assert_eq!(cap_radius(10.0, -1.0), 0.0);
+    }
+}
@@ -44,7 +44,7 @@ pub fn draw_beos_window_frame(
     background_color: egui::Color32,
 ) -> ChromeLayout {
     
-    if !config.profile.beos_enabled{
+    if !config.profile.beos_enabled || config.mini_mode {
         return ChromeLayout { 
             content_rect: window_rect, 
             is_collapsed: false,
@@ -0,0 +1,135 @@
+//! Dot Matrix mode: the spectrum quantized onto a fixed grid of dots, like a
+//! retro LCD or an LED sign. Unlit dots stay faintly visible the way a real
+//! panel's segments do, and lit ones can get a phosphor-style halo.
+//!
+//! The Characters style draws each cell as a monospace block glyph, with the
+//! top cell of a column partly filled (▁▂▃…█), for a text-mode look that
+//! goes with the Monospace overlay font.
+
+use eframe::egui;
+
+use crate::gui::theme::{bar_color, db_to_px, gradient_colors, lerp_color, to_egui_color};
+use crate::shared_state::{ColorProfile, DotStyle, VisualProfile, VisualizationData};
+
+/// Partial blocks for the top character cell, an eighth at a time
+const BLOCKS: [&str; 8] = ["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+/// How visible unlit dots are (0 = invisible, 1 = the low color)
+const UNLIT: f32 = 0.12;
+
+/// `levels` (0..1) squeezed into `columns`, each column the loudest level it
+/// covers so narrow peaks don't vanish
+pub fn column_levels(levels: &[f32], columns: usize) -> Vec<f32> {
+    if levels.is_empty() || columns == 0 {
+        return vec![0.0; columns];
+    }
+    (0..columns)
+        .map(|c| {
+            let start = c * levels.len() / columns;
+            let end = ((c + 1) * levels.len() / columns).max(start + 1).min(levels.len());
+            levels[start.min(levels.len() - 1)..end].iter().fold(0.0f32, |m, &l| m.max(l))
+        })
+        .collect()
+}
+
+/// Fully lit cells in a column of `rows`, and how full the next one is (0..1)
+pub fn lit_cells(level: f32, rows: usize) -> (usize, f32) {
+    let cells = level.clamp(0.0, 1.0) * rows as f32;
+    let full = (cells.floor() as usize).min(rows);
+    (full, if full < rows { cells - full as f32 } else { 0.0 })
+}
+
+/// Draw the Dot Matrix mode, bass on the left. Always upright, whatever the
+/// orientation, like the panel it imitates.
+pub fn draw_dot_matrix(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    profile: &VisualProfile,
+    colors: &ColorProfile,
+    data: &VisualizationData,
+    noise_floor_db: f32,
+) {
+    let settings = &profile.dot_matrix;
+    let (columns, rows) = (settings.columns.max(1) as usize, settings.rows.max(1) as usize);
+    let cell = egui::vec2(rect.width() / columns as f32, rect.height() / rows as f32);
+
+    let to_level = |db: &f32| db_to_px(*db, noise_floor_db, 1.0);
+    let levels = column_levels(&data.bars.iter().map(to_level).collect::<Vec<_>>(), columns);
+    let peaks = column_levels(&data.peaks.iter().map(to_level).collect::<Vec<_>>(), columns);
+
+    let gradient = gradient_colors(colors, |c| c.linear_multiply(profile.bar_opacity));
+    let peak_color = to_egui_color(colors.peak).linear_multiply(profile.bar_opacity);
+    let unlit = lerp_color(to_egui_color(colors.background), to_egui_color(colors.low), UNLIT);
+    let font = egui::FontId::monospace(cell.y * 1.1);
+
+    let center = |c: usize, r: usize| egui::pos2(rect.left() + (c as f32 + 0.5) * cell.x, rect.bottom() - (r as f32 + 0.5) * cell.y);
+    let radius = 0.4 * cell.x.min(cell.y);
+
+    // (center, color, fill) of every lit cell; fill < 1 only for partial characters
+    let mut lit: Vec<(egui::Pos2, egui::Color32, f32)> = Vec::new();
+    for (c, (&level, &peak)) in levels.iter().zip(&peaks).enumerate() {
+        let (full, partial) = lit_cells(level, rows);
+        let peak_row = lit_cells(peak, rows).0.checked_sub(1).filter(|&r| r >= full);
+        for r in 0..rows {
+            let color = bar_color(&gradient, peak_color, (r as f32 + 0.5) / rows as f32, profile.vu_coloring);
+            if r < full {
+                lit.push((center(c, r), color, 1.0));
+            } else if r == full && partial > 0.0 && settings.style == DotStyle::Characters {
+                lit.push((center(c, r), color, partial));
+            } else if peak_row == Some(r) {
+                lit.push((center(c, r), peak_color, 1.0));
+            } else if settings.style != DotStyle::Characters {
+                draw_dot(painter, settings.style, center(c, r), radius, cell, unlit);
+            }
+        }
+    }
+
+    if settings.glow {
+        for &(pos, color, fill) in &lit {
+            painter.circle_filled(pos, radius * 2.0, color.gamma_multiply(0.18 * fill).additive());
+        }
+    }
+    for (pos, color, fill) in lit {
+        match settings.style {
+            DotStyle::Characters => {
+                let block = BLOCKS[((fill * 8.0).ceil() as usize).clamp(1, 8) - 1];
+                painter.text(pos, egui::Align2::CENTER_CENTER, block, font.clone(), color);
+            }
+            style => draw_dot(painter, style, pos, radius, cell, color),
+        }
+    }
+}
+
+fn draw_dot(painter: &egui::Painter, style: DotStyle, center: egui::Pos2, radius: f32, cell: egui::Vec2, color: egui::Color32) {
+    match style {
+        DotStyle::Round => {
+            painter.circle_filled(center, radius, color);
+        }
+        _ => {
+            painter.rect_filled(egui::Rect::from_center_size(center, cell * 0.8), 1.0, color);
+        }
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_levels_keep_the_loudest() {
+        assert_eq!(column_levels(&[0.1, 0.9, 0.2, 0.4], 2), vec![0.9, 0.4]);
+        // Fewer bars than columns: each bar spreads over several
+        assert_eq!(column_levels(&[0.5, 1.0], 4), vec![0.5, 0.5, 1.0, 1.0]);
+        assert_eq!(column_levels(&[], 3), vec![0.0; 3]);
+    }
+
+    #[test]
+    fn test_lit_cells() {
+        assert_eq!(lit_cells(0.0, 10), (0, 0.0));
+        let (full, partial) = lit_cells(0.55, 10);
+        assert_eq!(full, 5);
+        assert!((partial - 0.5).abs() < 1e-4);
+        assert_eq!(lit_cells(1.2, 10), (10, 0.0));
+    }
+}
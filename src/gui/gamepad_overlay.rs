@@ -0,0 +1,155 @@
+// src/gui/gamepad_overlay.rs
+//
+// Controller navigation for the main window (see `crate::gamepad`).
+//
+// With the overlay closed:
+//   - LB / RB        - previous / next visual profile
+//   - D-pad ← / →    - previous / next color preset
+//   - D-pad ↑ / ↓    - background opacity ±10%
+//   - Start          - open the overlay
+//
+// The overlay is a short settings list sized for a TV across the room:
+// ↑ / ↓ picks a row, ← / → changes it, B or Start closes it. Everything
+// applies straight away, like the quick switcher.
+
+use eframe::egui;
+
+use crate::gamepad::PadButton;
+use crate::commands::{self, cycle, Command};
+use crate::gui::shortcuts::{step_bars, step_sensitivity};
+use crate::shared_state::{ColorRef, SharedState};
+
+/// Background opacity change per D-pad press
+const OPACITY_STEP: f32 = 0.1;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Row {
+    Profile,
+    Colors,
+    Mode,
+    Opacity,
+    Sensitivity,
+    Bars,
+}
+
+const ROWS: [Row; 6] = [Row::Profile, Row::Colors, Row::Mode, Row::Opacity, Row::Sensitivity, Row::Bars];
+
+impl Row {
+    fn label(self) -> &'static str {
+        match self {
+            Row::Profile => "Profile",
+            Row::Colors => "Colors",
+            Row::Mode => "Mode",
+            Row::Opacity => "Background",
+            Row::Sensitivity => "Sensitivity",
+            Row::Bars => "Bars",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct GamepadOverlay {
+    open: bool,
+    row: usize,
+}
+
+impl GamepadOverlay {
+    pub fn handle(&mut self, button: PadButton, state: &mut SharedState) {
+        if !self.open {
+            match button {
+                PadButton::Menu => {
+                    self.open = true;
+                    self.row = 0;
+                }
+                PadButton::LeftBumper => step_profile(state, -1),
+                PadButton::RightBumper => step_profile(state, 1),
+                PadButton::Left => step_colors(state, -1),
+                PadButton::Right => step_colors(state, 1),
+                PadButton::Up => step_opacity(state, 1),
+                PadButton::Down => step_opacity(state, -1),
+                PadButton::Confirm | PadButton::Back => {}
+            }
+            return;
+        }
+
+        match button {
+            PadButton::Back | PadButton::Menu => self.open = false,
+            PadButton::Up => self.row = cycle(ROWS.len(), Some(self.row), -1).unwrap_or(0),
+            PadButton::Down | PadButton::Confirm => self.row = cycle(ROWS.len(), Some(self.row), 1).unwrap_or(0),
+            PadButton::Left | PadButton::LeftBumper => self.change(state, -1),
+            PadButton::Right | PadButton::RightBumper => self.change(state, 1),
+        }
+    }
+
+    fn change(&self, state: &mut SharedState, delta: i32) {
+        let profile = &mut state.config.profile;
+        match ROWS[self.row] {
+            Row::Profile => step_profile(state, delta),
+            Row::Colors => step_colors(state, delta),
+            Row::Mode => {
+                let _ = commands::execute(if delta > 0 { &Command::NextMode } else { &Command::PrevMode }, state);
+            }
+            Row::Opacity => step_opacity(state, delta),
+            Row::Sensitivity => step_sensitivity(profile, delta),
+            Row::Bars => step_bars(profile, delta),
+        }
+    }
+
+    pub fn show(&self, ctx: &egui::Context, state: &SharedState) {
+        if !self.open {
+            return;
+        }
+        let profile = &state.config.profile;
+        let colors = state.config.resolve_colors(&state.user_color_presets);
+        let value = |row: Row| match row {
+            Row::Profile => profile.name.clone(),
+            Row::Colors => match &profile.color_link {
+                ColorRef::Preset(name) => name.clone(),
+                ColorRef::Custom(_) => "Custom".to_string(),
+            },
+            Row::Mode => format!("{:?}", profile.visual_mode),
+            Row::Opacity => format!("{:.0}%", colors.background.a as f32 / 2.55),
+            Row::Sensitivity => format!("{:+.0} dB", 20.0 * profile.sensitivity.log10()),
+            Row::Bars => profile.num_bars.to_string(),
+        };
+
+        egui::Area::new(egui::Id::new("gamepad_overlay"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).inner_margin(16.0).show(ui, |ui| {
+                    egui::Grid::new("gamepad_overlay_grid").num_columns(2).spacing([32.0, 10.0]).show(ui, |ui| {
+                        for (i, row) in ROWS.into_iter().enumerate() {
+                            let selected = i == self.row;
+                            let label = egui::RichText::new(row.label()).size(20.0);
+                            ui.label(if selected { label.strong() } else { label.weak() });
+                            let text = egui::RichText::new(if selected { format!("◀ {} ▶", value(row)) } else { value(row) }).size(20.0);
+                            ui.label(if selected { text.color(ui.visuals().selection.stroke.color) } else { text });
+                            ui.end_row();
+                        }
+                    });
+                    ui.add_space(6.0);
+                    ui.label(egui::RichText::new("↑ ↓ choose  ·  ← → change  ·  B close").weak());
+                });
+            });
+    }
+}
+
+fn step_profile(state: &mut SharedState, delta: i32) {
+    let _ = commands::execute(if delta > 0 { &Command::NextProfile } else { &Command::PrevProfile }, state);
+    tracing::debug!("[GUI] Gamepad profile: {}", state.config.profile.name);
+}
+
+fn step_colors(state: &mut SharedState, delta: i32) {
+    let _ = commands::execute(if delta > 0 { &Command::NextColors } else { &Command::PrevColors }, state);
+    if let ColorRef::Preset(name) = &state.config.profile.color_link {
+        tracing::debug!("[GUI] Gamepad colors: {}", name);
+    }
+}
+
+fn step_opacity(state: &mut SharedState, delta: i32) {
+    let alpha = state.config.resolve_colors(&state.user_color_presets).background.a as f32 / 255.0;
+    let alpha = ((alpha + delta as f32 * OPACITY_STEP) * 10.0).round() / 10.0;
+    state.config.profile.set_background_alpha(&state.user_color_presets, alpha);
+}
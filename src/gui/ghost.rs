@@ -0,0 +1,153 @@
+// src/gui/ghost.rs
+//
+// Ways out of ghost mode besides alt-tab. A click-through window gets no
+// mouse events, so while ghosted the pointer is asked of the OS a few times
+// a second instead (`window_integration::pointer`):
+//   - Hot corner: resting the pointer in the chosen corner of any screen
+//     makes the window clickable for a few seconds, and for as long as the
+//     pointer is over it after that. Clicking it focuses it, which ends the
+//     ghosting the usual way.
+//   - Handle: the lock button's corner of the window is always clickable,
+//     so the lock can be opened right where it is.
+//
+// Neither works on Wayland, where the pointer outside our window is unknown.
+
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+use raw_window_handle::RawWindowHandle;
+
+use crate::shared_state::HotCorner;
+use crate::window_integration::{self, ScreenRect};
+
+/// How often the pointer is looked up while ghosted
+const POLL_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Size of the hot corner, in physical pixels from the screen's corner
+const CORNER_SIZE: i32 = 8;
+
+/// The pointer has to rest in the corner this long, so passing through it
+/// on the way somewhere else doesn't count
+const CORNER_DWELL: Duration = Duration::from_millis(400);
+
+/// Time to get from the corner to the window
+const CORNER_GRACE: Duration = Duration::from_secs(5);
+
+/// Stays clickable this long after the pointer leaves the window
+const LEAVE_GRACE: Duration = Duration::from_secs(1);
+
+/// The handle, in points from the window's bottom-left corner (covers the
+/// lock button with some room around it)
+const HANDLE_SIZE: f32 = 40.0;
+
+/// Monitor rects are read again after this long
+const MONITOR_REFRESH: Duration = Duration::from_secs(10);
+
+#[derive(Default)]
+pub struct GhostWake {
+    monitors: Vec<ScreenRect>,
+    monitors_at: Option<Instant>,
+    polled_at: Option<Instant>,
+    /// Since when the pointer has been resting in the hot corner
+    in_corner_since: Option<Instant>,
+    /// Clickable until then, after a hot corner visit
+    awake_until: Option<Instant>,
+    /// Result of the last poll
+    awake: bool,
+}
+
+impl GhostWake {
+    /// Whether a ghosted window should take clicks right now. Only call it
+    /// while ghosted; `reset` when not.
+    pub fn awake(&mut self, ctx: &egui::Context, window: RawWindowHandle, corner: HotCorner, handle: bool, now: Instant) -> bool {
+        if corner == HotCorner::Off && !handle {
+            self.reset();
+            return false;
+        }
+        ctx.request_repaint_after(POLL_INTERVAL);
+        if self.polled_at.is_some_and(|at| now.duration_since(at) < POLL_INTERVAL) {
+            return self.awake;
+        }
+        self.polled_at = Some(now);
+
+        let Some(pointer) = window_integration::pointer(window) else {
+            self.awake = false;
+            return false;
+        };
+        let ppp = ctx.pixels_per_point();
+        let size = ctx.input(|i| i.viewport().inner_rect).map_or(egui::Vec2::ZERO, |r| r.size() * ppp);
+        let [x, y] = pointer.window;
+        let over_window = x >= 0 && y >= 0 && (x as f32) < size.x && (y as f32) < size.y;
+        let over_handle = handle && over_window && (x as f32) < HANDLE_SIZE * ppp && (y as f32) >= size.y - HANDLE_SIZE * ppp;
+
+        if corner != HotCorner::Off {
+            if !self.monitors_at.is_some_and(|at| now.duration_since(at) < MONITOR_REFRESH) {
+                self.monitors = window_integration::monitors();
+                self.monitors_at = Some(now);
+            }
+            let [sx, sy] = pointer.screen;
+            let monitor = self.monitors.iter().find(|m| m.contains(sx, sy));
+            if monitor.is_some_and(|m| in_corner(m, corner, sx, sy)) {
+                let since = *self.in_corner_since.get_or_insert(now);
+                if now.duration_since(since) >= CORNER_DWELL && self.awake_until.is_none() {
+                    tracing::info!("[GUI] Ghost mode: hot corner, window clickable");
+                    self.awake_until = Some(now + CORNER_GRACE);
+                }
+            } else {
+                self.in_corner_since = None;
+            }
+        }
+
+        if let Some(until) = self.awake_until {
+            if over_window {
+                self.awake_until = Some(until.max(now + LEAVE_GRACE));
+            } else if now >= until && self.in_corner_since.is_none() {
+                self.awake_until = None;
+            }
+        }
+
+        self.awake = over_handle || self.awake_until.is_some();
+        self.awake
+    }
+
+    /// Forget any wake-up, e.g. once ghost mode is over
+    pub fn reset(&mut self) {
+        self.in_corner_since = None;
+        self.awake_until = None;
+        self.awake = false;
+        self.polled_at = None;
+    }
+}
+
+/// Whether (`x`, `y`) is within `CORNER_SIZE` of `corner` of `monitor`
+fn in_corner(monitor: &ScreenRect, corner: HotCorner, x: i32, y: i32) -> bool {
+    let left = x < monitor.x + CORNER_SIZE;
+    let right = x >= monitor.x + monitor.width - CORNER_SIZE;
+    let top = y < monitor.y + CORNER_SIZE;
+    let bottom = y >= monitor.y + monitor.height - CORNER_SIZE;
+    match corner {
+        HotCorner::Off => false,
+        HotCorner::TopLeft => top && left,
+        HotCorner::TopRight => top && right,
+        HotCorner::BottomLeft => bottom && left,
+        HotCorner::BottomRight => bottom && right,
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corners_of_a_second_monitor() {
+        let monitor = ScreenRect { x: 1920, y: 0, width: 2560, height: 1440 };
+        assert!(in_corner(&monitor, HotCorner::TopLeft, 1920, 0));
+        assert!(in_corner(&monitor, HotCorner::BottomRight, 4479, 1439));
+        assert!(in_corner(&monitor, HotCorner::TopRight, 4475, 3));
+        assert!(!in_corner(&monitor, HotCorner::TopLeft, 1940, 0));
+        assert!(!in_corner(&monitor, HotCorner::BottomLeft, 1920, 0));
+        assert!(!in_corner(&monitor, HotCorner::Off, 1920, 0));
+    }
+}
@@ -0,0 +1,82 @@
+//! Audio-reactive glow: a bloom post effect over the bar modes and the Line
+//! Spectrum, painted with egui shapes in a few passes like the sonar ping.
+//!
+//! Each pass redraws the bars (or the line) wider and fainter in additive
+//! blending, so overlapping halos add up to light the way a real bloom does,
+//! and never darken what's underneath. How strong it is follows the overall
+//! loudness of the frame, taken from the bar levels the renderer already
+//! has, which the attack / release smoothing keeps from flickering.
+
+use eframe::egui;
+
+use crate::shared_state::GlowSettings;
+
+/// (how far past the shape, fraction of the strength) per pass, widest first
+const PASSES: [(f32, f32); 3] = [(12.0, 0.08), (6.0, 0.16), (2.5, 0.3)];
+
+/// How loud the frame is (0..1), from the bar heights (each 0..1)
+pub fn loudness(heights: impl Iterator<Item = f32>) -> f32 {
+    let (sum, count) = heights.fold((0.0, 0usize), |(sum, count), h| (sum + h.clamp(0.0, 1.0), count + 1));
+    if count == 0 {
+        return 0.0;
+    }
+    // Music rarely fills every bar, so an average of 0.5 is already loud
+    (2.0 * sum / count as f32).min(1.0)
+}
+
+/// Glow strength (0..1) at `loudness`
+pub fn strength(settings: &GlowSettings, loudness: f32) -> f32 {
+    let reactivity = settings.reactivity.clamp(0.0, 1.0);
+    settings.intensity.clamp(0.0, 1.0) * (1.0 - reactivity + reactivity * loudness.clamp(0.0, 1.0))
+}
+
+/// Halos around bar rectangles, each with its own (tip) color
+pub fn bars(painter: &egui::Painter, bars: &[(egui::Rect, egui::Color32)], strength: f32) {
+    if strength <= 0.0 {
+        return;
+    }
+    for (spread, alpha) in PASSES {
+        for &(rect, color) in bars {
+            painter.rect_filled(rect.expand(spread), spread, color.gamma_multiply(alpha * strength).additive());
+        }
+    }
+}
+
+/// Halo along a line `width` px wide
+pub fn line(painter: &egui::Painter, points: &[egui::Pos2], width: f32, color: egui::Color32, strength: f32) {
+    if strength <= 0.0 || points.len() < 2 {
+        return;
+    }
+    for (spread, alpha) in PASSES {
+        let stroke = egui::Stroke::new(width + 2.0 * spread, color.gamma_multiply(alpha * strength).additive());
+        painter.add(egui::Shape::line(points.to_vec(), stroke));
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strength_follows_loudness() {
+        let settings = GlowSettings { enabled: true, intensity: 0.5, reactivity: 1.0 };
+        assert_eq!(strength(&settings, 0.0), 0.0);
+        assert_eq!(strength(&settings, 1.0), 0.5);
+
+        // Not reactive: the same glow in silence
+        let steady = GlowSettings { reactivity: 0.0, ..settings };
+        assert_eq!(strength(&steady, 0.0), 0.5);
+        let half = GlowSettings { reactivity: 0.5, ..settings };
+        assert_eq!(strength(&half, 0.0), 0.25);
+    }
+
+    #[test]
+    fn test_loudness_of_the_bars() {
+        assert_eq!(loudness(std::iter::empty()), 0.0);
+        assert_eq!(loudness([0.0, 0.0].into_iter()), 0.0);
+        assert_eq!(loudness([0.5, 0.0].into_iter()), 0.5);
+        assert_eq!(loudness([1.0, 0.6].into_iter()), 1.0);
+    }
+}
@@ -0,0 +1,50 @@
+//! Gradients and the gradient-filled bar mesh.
+//!
+//! Plain egui types in, egui types out, no app state: the benches
+//! (`benches/processing.rs`) build bar meshes with this module alone.
+
+/// Straight blend of two colors, `t` clamped to 0..1
+pub fn lerp_color(a: egui::Color32, b: egui::Color32, t: f32) -> egui::Color32 {
+    let t = t.clamp(0.0, 1.0);
+    egui::Color32::from_rgba_premultiplied(
+        (a.r() as f32 + (b.r() as f32 - a.r() as f32) * t) as u8,
+        (a.g() as f32 + (b.g() as f32 - a.g() as f32) * t) as u8,
+        (a.b() as f32 + (b.b() as f32 - a.b() as f32) * t) as u8,
+        (a.a() as f32 + (b.a() as f32 - a.a() as f32) * t) as u8,
+    )
+}
+
+/// Color at `t` along a sorted gradient, between the two stops around it
+pub fn sample_gradient(stops: &[(f32, egui::Color32)], t: f32) -> egui::Color32 {
+    let Some(&(_, first)) = stops.first() else { return egui::Color32::TRANSPARENT };
+    let upper = match stops.iter().position(|&(p, _)| p >= t) {
+        Some(0) => return first,
+        Some(i) => i,
+        None => return stops[stops.len() - 1].1,
+    };
+    let (p0, c0) = stops[upper - 1];
+    let (p1, c1) = stops[upper];
+    lerp_color(c0, c1, (t - p0) / (p1 - p0).max(1e-6))
+}
+
+/// One bar filled with the gradient up to `level` (0..1): a row of vertices
+/// at the base, at every stop below the tip and at the tip, so the GPU
+/// blends between stops. `edge(t)` gives the two corners at height `t`.
+pub fn gradient_bar_mesh(gradient: &[(f32, egui::Color32)], level: f32, edge: impl Fn(f32) -> [egui::Pos2; 2]) -> egui::Mesh {
+    let mut rows = vec![(0.0, sample_gradient(gradient, 0.0))];
+    rows.extend(gradient.iter().copied().filter(|&(p, _)| p > 0.0 && p < level));
+    rows.push((level, sample_gradient(gradient, level)));
+
+    let mut mesh = egui::Mesh::default();
+    for (row, &(t, color)) in rows.iter().enumerate() {
+        for pos in edge(t) {
+            mesh.vertices.push(egui::epaint::Vertex { pos, uv: egui::Pos2::ZERO, color });
+        }
+        if row > 0 {
+            let v_idx = 2 * (row as u32 - 1);
+            mesh.add_triangle(v_idx, v_idx + 1, v_idx + 3);
+            mesh.add_triangle(v_idx, v_idx + 3, v_idx + 2);
+        }
+    }
+    mesh
+}
@@ -0,0 +1,345 @@
+// src/gui/headless.rs
+//
+// Headless "render to image" for the main visualizer.
+//
+// Runs a throw-away egui Context (no window, no GPU), lets the normal
+// `draw_main_visualizer` paint into it, then rasterizes the tessellated
+// meshes on the CPU. Slow compared to wgpu, but fine for one-off frames:
+//   - the "📷 Screenshot" context menu entry
+//   - golden-image tests for each visual mode (see tests below)
+
+use eframe::egui::{self, epaint, Color32, Pos2, Rect};
+use image::RgbaImage;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::gui::theme::to_egui_color;
+use crate::gui::visualizers as viz;
+use crate::shared_state::SharedState;
+
+/// Render the visualizer for `state` into a `size[0] x size[1]` RGBA image.
+/// Uses the full requested bar count (no LOD) and never draws the inspector.
+pub fn render_to_image(state: &SharedState, size: [u32; 2]) -> RgbaImage {
+    let [w, h] = [size[0].max(1), size[1].max(1)];
+    let screen = Rect::from_min_size(Pos2::ZERO, egui::vec2(w as f32, h as f32));
+
+    let ctx = egui::Context::default();
+    let output = paint_frame(&ctx, state, screen);
+
+    // Collect textures (font atlas mostly) so text renders too
+    let mut textures: HashMap<egui::TextureId, Texture> = HashMap::new();
+    for (id, delta) in &output.textures_delta.set {
+        textures.entry(*id).or_insert_with(|| Texture::empty(delta)).apply(delta);
+    }
+
+    let primitives = ctx.tessellate(output.shapes, output.pixels_per_point);
+
+    let mut canvas = Canvas::new(w, h);
+    for prim in &primitives {
+        if let epaint::Primitive::Mesh(mesh) = &prim.primitive {
+            canvas.draw_mesh(mesh, prim.clip_rect, textures.get(&mesh.texture_id));
+        }
+    }
+    canvas.into_image()
+}
+
+/// Run one egui frame that paints the background and the visualizer into
+/// `screen`. Reusing `ctx` across calls keeps egui's caches (fonts, galleys)
+/// warm, which is what the stress test wants to time.
+pub fn paint_frame(ctx: &egui::Context, state: &SharedState, screen: Rect) -> egui::FullOutput {
+    let raw_input = egui::RawInput {
+        screen_rect: Some(screen),
+        ..Default::default()
+    };
+
+    let colors = state.config.resolve_colors(&state.user_color_presets);
+    let background = to_egui_color(colors.background);
+
+    ctx.run(raw_input, |ctx| {
+        let painter = ctx.layer_painter(egui::LayerId::background());
+        painter.rect_filled(screen, 0.0, background);
+
+        viz::draw_main_visualizer(
+            &painter,
+            screen,
+            &viz::DrawSettings::new(&state.config, &state.config.profile),
+            &state.config.profile,
+            &colors,
+            &state.visualization,
+            &state.performance,
+            None,
+            state.config.profile.num_bars,
+            None,
+            None,
+        );
+    })
+}
+
+/// Render and save a PNG into `<data_dir>/screenshots/`. Returns the file path.
+pub fn save_screenshot(state: &SharedState, size: [u32; 2]) -> Result<PathBuf, String> {
+    let dir = crate::paths::get().data_dir.join("screenshots");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let path = dir.join(format!("BeSpec_{}.png", stamp));
+
+    render_to_image(state, size).save(&path).map_err(|e| e.to_string())?;
+    tracing::info!("[GUI] Screenshot saved to {:?}", path);
+    Ok(path)
+}
+
+// ====================================================================================
+// Tiny software rasterizer
+// ====================================================================================
+
+/// CPU copy of an egui texture (premultiplied sRGBA)
+struct Texture {
+    size: [usize; 2],
+    pixels: Vec<Color32>,
+}
+
+impl Texture {
+    fn empty(delta: &epaint::ImageDelta) -> Self {
+        let size = delta.image.size();
+        Self { size, pixels: vec![Color32::TRANSPARENT; size[0] * size[1]] }
+    }
+
+    fn apply(&mut self, delta: &epaint::ImageDelta) {
+        let [dw, dh] = delta.image.size();
+        let pixels: Vec<Color32> = match &delta.image {
+            epaint::ImageData::Color(img) => img.pixels.clone(),
+            epaint::ImageData::Font(font) => font.srgba_pixels(None).collect(),
+        };
+
+        let [ox, oy] = delta.pos.unwrap_or([0, 0]);
+        if delta.pos.is_none() {
+            // Full replacement
+            self.size = [dw, dh];
+            self.pixels = pixels;
+            return;
+        }
+        for y in 0..dh {
+            for x in 0..dw {
+                let (tx, ty) = (ox + x, oy + y);
+                if tx < self.size[0] && ty < self.size[1] {
+                    self.pixels[ty * self.size[0] + tx] = pixels[y * dw + x];
+                }
+            }
+        }
+    }
+
+    /// Nearest-neighbour lookup, uv in 0..1
+    fn sample(&self, uv: Pos2) -> Color32 {
+        if self.pixels.is_empty() {
+            return Color32::WHITE;
+        }
+        let x = ((uv.x * self.size[0] as f32) as usize).min(self.size[0] - 1);
+        let y = ((uv.y * self.size[1] as f32) as usize).min(self.size[1] - 1);
+        self.pixels[y * self.size[0] + x]
+    }
+}
+
+/// Premultiplied RGBA float framebuffer
+struct Canvas {
+    w: u32,
+    h: u32,
+    px: Vec<[f32; 4]>,
+}
+
+impl Canvas {
+    fn new(w: u32, h: u32) -> Self {
+        Self { w, h, px: vec![[0.0; 4]; (w * h) as usize] }
+    }
+
+    fn draw_mesh(&mut self, mesh: &epaint::Mesh, clip: Rect, texture: Option<&Texture>) {
+        let clip = clip.intersect(Rect::from_min_size(Pos2::ZERO, egui::vec2(self.w as f32, self.h as f32)));
+        if clip.width() <= 0.0 || clip.height() <= 0.0 {
+            return;
+        }
+
+        for tri in mesh.indices.chunks_exact(3) {
+            let v = [
+                &mesh.vertices[tri[0] as usize],
+                &mesh.vertices[tri[1] as usize],
+                &mesh.vertices[tri[2] as usize],
+            ];
+            self.draw_triangle(v, clip, texture);
+        }
+    }
+
+    fn draw_triangle(&mut self, v: [&epaint::Vertex; 3], clip: Rect, texture: Option<&Texture>) {
+        let (a, b, c) = (v[0].pos, v[1].pos, v[2].pos);
+        let area = edge(a, b, c);
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+
+        let min_x = a.x.min(b.x).min(c.x).max(clip.min.x).floor() as i64;
+        let max_x = a.x.max(b.x).max(c.x).min(clip.max.x).ceil() as i64;
+        let min_y = a.y.min(b.y).min(c.y).max(clip.min.y).floor() as i64;
+        let max_y = a.y.max(b.y).max(c.y).min(clip.max.y).ceil() as i64;
+
+        for y in min_y.max(0)..max_y.min(self.h as i64) {
+            for x in min_x.max(0)..max_x.min(self.w as i64) {
+                let p = Pos2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let w0 = edge(b, c, p) / area;
+                let w1 = edge(c, a, p) / area;
+                let w2 = edge(a, b, p) / area;
+                if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                    continue;
+                }
+
+                // Interpolate vertex color (premultiplied) + uv
+                let mut src = [0.0f32; 4];
+                for (vert, weight) in v.iter().zip([w0, w1, w2]) {
+                    for (s, ch) in src.iter_mut().zip(vert.color.to_array()) {
+                        *s += ch as f32 / 255.0 * weight;
+                    }
+                }
+                if let Some(tex) = texture {
+                    let uv = Pos2::new(
+                        v[0].uv.x * w0 + v[1].uv.x * w1 + v[2].uv.x * w2,
+                        v[0].uv.y * w0 + v[1].uv.y * w1 + v[2].uv.y * w2,
+                    );
+                    for (s, t) in src.iter_mut().zip(tex.sample(uv).to_array()) {
+                        *s *= t as f32 / 255.0;
+                    }
+                }
+
+                // Premultiplied "over"
+                let dst = &mut self.px[(y as u32 * self.w + x as u32) as usize];
+                let inv = 1.0 - src[3];
+                for (d, s) in dst.iter_mut().zip(src) {
+                    *d = s + *d * inv;
+                }
+            }
+        }
+    }
+
+    fn into_image(self) -> RgbaImage {
+        let mut img = RgbaImage::new(self.w, self.h);
+        for (out, px) in img.pixels_mut().zip(self.px) {
+            // Un-premultiply for PNG
+            let a = px[3].clamp(0.0, 1.0);
+            let unmul = |c: f32| if a > 0.0 { (c / a).clamp(0.0, 1.0) } else { 0.0 };
+            *out = image::Rgba([
+                (unmul(px[0]) * 255.0).round() as u8,
+                (unmul(px[1]) * 255.0).round() as u8,
+                (unmul(px[2]) * 255.0).round() as u8,
+                (a * 255.0).round() as u8,
+            ]);
+        }
+        img
+    }
+}
+
+/// Signed area helper for barycentric coords
+fn edge(a: Pos2, b: Pos2, p: Pos2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+// ================== Tests ===================
+
+// Golden-image tests: each built-in visual mode is rendered from a fixed
+// synthetic spectrum and compared against `tests/golden/<mode>.png`.
+// A missing golden fails the test; set BESPEC_BLESS=1 to write it, or to
+// regenerate them after an intentional rendering change.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared_state::{AppConfig, VisualMode};
+
+    const SIZE: [u32; 2] = [320, 160];
+
+    /// Deterministic state: a sloped spectrum + a sine waveform, no user presets
+    fn fixture(mode: VisualMode) -> SharedState {
+        let mut state = SharedState::with_config(AppConfig::default());
+        state.config.profile.visual_mode = mode;
+        state.config.profile.num_bars = 32;
+        state.config.show_stats = false;
+
+        let n = state.config.profile.num_bars;
+        state.visualization.bars = (0..n).map(|i| -10.0 - i as f32 * 1.5).collect();
+        state.visualization.peaks = state.visualization.bars.iter().map(|b| b + 3.0).collect();
+        state.visualization.waveform = (0..2048)
+            .map(|i| (i as f32 / 2048.0 * std::f32::consts::TAU * 4.0).sin() * 0.5)
+            .collect();
+
+        // A few seconds of history with a peak sweeping up, for the modes that scroll
+        let start = std::time::Instant::now();
+        for col in 0..120 {
+            let bars: Vec<f32> = (0..n)
+                .map(|i| if i == col * n / 120 { -6.0 } else { -60.0 + i as f32 })
+                .collect();
+            std::sync::Arc::make_mut(&mut state.visualization.history).push(&bars, start + std::time::Duration::from_secs(col as u64));
+        }
+        state
+    }
+
+    fn golden_path(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("golden")
+            .join(format!("{}.png", name))
+    }
+
+    /// Fraction of pixels that differ by more than a small per-channel tolerance
+    fn diff_ratio(a: &RgbaImage, b: &RgbaImage) -> f32 {
+        if a.dimensions() != b.dimensions() {
+            return 1.0;
+        }
+        let bad = a.pixels().zip(b.pixels())
+            .filter(|(pa, pb)| pa.0.iter().zip(pb.0.iter()).any(|(x, y)| x.abs_diff(*y) > 8))
+            .count();
+        bad as f32 / (a.width() * a.height()) as f32
+    }
+
+    fn check_golden(name: &str, mode: VisualMode) {
+        let img = render_to_image(&fixture(mode), SIZE);
+        let path = golden_path(name);
+
+        if std::env::var("BESPEC_BLESS").is_ok() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            img.save(&path).unwrap();
+            return;
+        }
+        assert!(path.exists(), "{:?} is missing; run with BESPEC_BLESS=1 to write it", path);
+
+        let golden = image::open(&path).unwrap().to_rgba8();
+        let ratio = diff_ratio(&img, &golden);
+        assert!(ratio < 0.01, "{} differs from golden in {:.2}% of pixels", name, ratio * 100.0);
+    }
+
+    #[test]
+    fn test_render_is_not_blank() {
+        let img = render_to_image(&fixture(VisualMode::SolidBars), SIZE);
+        assert_eq!(img.dimensions(), (SIZE[0], SIZE[1]));
+
+        let first = *img.get_pixel(0, 0);
+        assert!(img.pixels().any(|p| *p != first), "render produced a flat image");
+    }
+
+    #[test]
+    fn test_golden_solid_bars() { check_golden("solid_bars", VisualMode::SolidBars); }
+
+    #[test]
+    fn test_golden_segmented_bars() { check_golden("segmented_bars", VisualMode::SegmentedBars); }
+
+    #[test]
+    fn test_golden_line_spectrum() { check_golden("line_spectrum", VisualMode::LineSpectrum); }
+
+    #[test]
+    fn test_golden_oscilloscope() { check_golden("oscilloscope", VisualMode::Oscilloscope); }
+
+    #[test]
+    fn test_golden_spectrogram() { check_golden("spectrogram", VisualMode::Spectrogram); }
+
+    #[test]
+    fn test_golden_mountain() { check_golden("mountain", VisualMode::Mountain); }
+
+    #[test]
+    fn test_golden_dot_matrix() { check_golden("dot_matrix", VisualMode::DotMatrix); }
+}
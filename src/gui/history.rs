@@ -0,0 +1,177 @@
+// src/gui/history.rs
+//
+// Short in-memory undo trail for the settings window.
+// Nothing here is persisted - it only exists to catch slips like dragging
+// the bar count slider from 64 to 500 by accident.
+
+use std::collections::VecDeque;
+use crate::shared_state::AppConfig;
+
+/// How many settled changes we remember
+const MAX_HISTORY: usize = 20;
+
+/// Tracks "settled" config snapshots while the settings window is open.
+///
+/// A change only counts as settled once the pointer is released, so a single
+/// slider drag becomes one undo step instead of hundreds.
+#[derive(Default)]
+pub struct SettingsHistory {
+    /// Older configs, newest at the back
+    undo_stack: VecDeque<AppConfig>,
+
+    /// The last settled config we compared against
+    baseline: Option<AppConfig>,
+}
+
+impl SettingsHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call once per settings frame, after the widgets have run.
+    /// `interacting` should be true while the user is still mid-drag/click.
+    pub fn observe(&mut self, current: &AppConfig, interacting: bool) {
+        let Some(baseline) = &self.baseline else {
+            self.baseline = Some(current.clone());
+            return;
+        };
+
+        if interacting || same_settings(baseline, current) {
+            return;
+        }
+
+        if self.undo_stack.len() == MAX_HISTORY {
+            self.undo_stack.pop_front();
+        }
+        let previous = self.baseline.replace(current.clone());
+        if let Some(previous) = previous {
+            tracing::debug!("[GUI] Settings change recorded ({} undo steps)", self.undo_stack.len() + 1);
+            self.undo_stack.push_back(previous);
+        }
+    }
+
+    /// Roll `current` back to the previous settled config.
+    /// Window geometry is left alone, the user moving the window is not a "setting".
+    /// Returns true if something was undone.
+    pub fn undo(&mut self, current: &mut AppConfig) -> bool {
+        let Some(mut previous) = self.undo_stack.pop_back() else {
+            return false;
+        };
+
+        keep_geometry(&mut previous, current);
+        *current = previous;
+        self.baseline = Some(current.clone());
+        tracing::info!("[GUI] Undid last settings change ({} left)", self.undo_stack.len());
+        true
+    }
+
+    pub fn depth(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    /// Forget the baseline, e.g. when the settings window closes.
+    /// The undo stack survives so re-opening settings can still undo.
+    pub fn reset_baseline(&mut self) {
+        self.baseline = None;
+    }
+}
+
+/// Copy the bits of config that track the main window (not user settings) from `src`.
+/// Connected accounts mirror the OS keyring, so undo must not touch them either.
+fn keep_geometry(dst: &mut AppConfig, src: &AppConfig) {
+    dst.window_size = src.window_size;
+    dst.window_position = src.window_position;
+    dst.beos_tab_offset = src.beos_tab_offset;
+    dst.beos_window_collapsed = src.beos_window_collapsed;
+    dst.mini_mode = src.mini_mode;
+    dst.mini_window_size = src.mini_window_size;
+    dst.mini_window_position = src.mini_window_position;
+    for win in &mut dst.extra_windows {
+        if let Some(current) = src.extra_windows.iter().find(|w| w.id == win.id) {
+            win.size = current.size;
+            win.position = current.position;
+        }
+    }
+    dst.connected_accounts = src.connected_accounts.clone();
+}
+
+/// Compare two configs, ignoring window geometry
+fn same_settings(a: &AppConfig, b: &AppConfig) -> bool {
+    let mut b = b.clone();
+    keep_geometry(&mut b, a);
+    *a == b
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drag_becomes_one_step() {
+        let mut history = SettingsHistory::new();
+        let mut config = AppConfig::default();
+        history.observe(&config, false);
+
+        // Simulate a slider drag across several frames
+        for bars in [80, 200, 500] {
+            config.profile.num_bars = bars;
+            history.observe(&config, true);
+        }
+        history.observe(&config, false);
+
+        assert_eq!(history.depth(), 1);
+        assert!(history.undo(&mut config));
+        assert_eq!(config.profile.num_bars, AppConfig::default().profile.num_bars);
+        assert_eq!(history.depth(), 0);
+    }
+
+    #[test]
+    fn test_geometry_is_not_a_change() {
+        let mut history = SettingsHistory::new();
+        let mut config = AppConfig::default();
+        history.observe(&config, false);
+
+        config.window_size = [1234.0, 321.0];
+        config.window_position = Some([10.0, 20.0]);
+        history.observe(&config, false);
+        assert_eq!(history.depth(), 0);
+
+        // ...and undo doesn't snap the window back either
+        config.show_stats = !config.show_stats;
+        history.observe(&config, false);
+        assert!(history.undo(&mut config));
+        assert_eq!(config.window_size, [1234.0, 321.0]);
+        assert_eq!(config.show_stats, AppConfig::default().show_stats);
+    }
+
+    #[test]
+    fn test_undo_keeps_extra_window_geometry() {
+        let mut history = SettingsHistory::new();
+        let mut config = AppConfig::default();
+        config.extra_windows.push(crate::shared_state::ExtraWindow::new(1, config.profile.clone()));
+        history.observe(&config, false);
+
+        config.extra_windows[0].profile.num_bars = 32;
+        history.observe(&config, false);
+        config.extra_windows[0].size = [640.0, 200.0];
+
+        assert!(history.undo(&mut config));
+        assert_eq!(config.extra_windows[0].profile.num_bars, AppConfig::default().profile.num_bars);
+        assert_eq!(config.extra_windows[0].size, [640.0, 200.0]);
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let mut history = SettingsHistory::new();
+        let mut config = AppConfig::default();
+        history.observe(&config, false);
+
+        for i in 0..(MAX_HISTORY + 5) {
+            config.noise_floor_db = -100.0 + i as f32;
+            history.observe(&config, false);
+        }
+        assert_eq!(history.depth(), MAX_HISTORY);
+    }
+}
@@ -0,0 +1,104 @@
+//! Interpolation between bars, for everything drawn as a curve through the
+//! bar levels: the Line Spectrum mode and the reference / response lines
+//! over any bar mode.
+//!
+//! The input is one level per bar, the output a list of (bar position,
+//! level) points where a position of 2.5 is halfway between bars 2 and 3.
+//! Each renderer maps positions to the baseline the way it places its bars,
+//! so the same curve lines up with every mode.
+//!
+//! The spline is Catmull-Rom: it passes through every bar's level (hovering
+//! a bar still reads the real value) and only looks at the neighbours, so a
+//! loud bar doesn't ripple across the whole spectrum. It can overshoot a
+//! little next to a steep edge; the renderers clamp to the window anyway.
+
+use crate::shared_state::BarInterpolation;
+
+/// Points along the curve through `levels`, `steps` per gap between bars
+/// (only used by the linear and spline modes)
+pub fn interpolate(levels: &[f32], mode: BarInterpolation, steps: usize) -> Vec<(f32, f32)> {
+    let n = levels.len();
+    if n < 2 {
+        return levels.iter().enumerate().map(|(i, &v)| (i as f32, v)).collect();
+    }
+    let steps = steps.max(1);
+
+    match mode {
+        BarInterpolation::None => {
+            // Each level held flat across its gap: a staircase
+            let mut points = Vec::with_capacity(2 * n);
+            for (i, pair) in levels.windows(2).enumerate() {
+                points.push((i as f32, pair[0]));
+                points.push(((i + 1) as f32, pair[0]));
+            }
+            points.push(((n - 1) as f32, levels[n - 1]));
+            points
+        }
+        BarInterpolation::Linear | BarInterpolation::CubicSpline => {
+            let mut points = Vec::with_capacity((n - 1) * steps + 1);
+            for i in 0..n - 1 {
+                for step in 0..steps {
+                    let t = step as f32 / steps as f32;
+                    let value = if mode == BarInterpolation::Linear {
+                        levels[i] + (levels[i + 1] - levels[i]) * t
+                    } else {
+                        // Past the ends, continue the outer gap's slope
+                        let p0 = if i > 0 { levels[i - 1] } else { 2.0 * levels[0] - levels[1] };
+                        let p3 = if i + 2 < n { levels[i + 2] } else { 2.0 * levels[n - 1] - levels[n - 2] };
+                        catmull_rom(p0, levels[i], levels[i + 1], p3, t)
+                    };
+                    points.push((i as f32 + t, value));
+                }
+            }
+            points.push(((n - 1) as f32, levels[n - 1]));
+            points
+        }
+    }
+}
+
+/// Spline segment between `p1` and `p2`, `t` in 0..1
+fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1 + (p2 - p0) * t + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2 + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+/// Subdivisions per bar gap so spline points land about every 3 px
+pub fn steps_for(gap_px: f32) -> usize {
+    ((gap_px / 3.0).ceil() as usize).clamp(1, 16)
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modes_pass_through_the_bars() {
+        let levels = [-60.0, -20.0, -40.0, -30.0];
+        for mode in [BarInterpolation::None, BarInterpolation::Linear, BarInterpolation::CubicSpline] {
+            let points = interpolate(&levels, mode, 4);
+            for (i, &level) in levels.iter().enumerate() {
+                assert!(points.contains(&(i as f32, level)), "{:?} misses bar {}", mode, i);
+            }
+            assert_eq!(points.last(), Some(&(3.0, -30.0)));
+        }
+
+        assert_eq!(interpolate(&levels, BarInterpolation::Linear, 2)[1], (0.5, -40.0));
+        // Staircase: flat until the next bar
+        assert_eq!(&interpolate(&levels, BarInterpolation::None, 4)[..3], &[(0.0, -60.0), (1.0, -60.0), (1.0, -20.0)]);
+    }
+
+    #[test]
+    fn test_spline_is_smooth_on_a_straight_line() {
+        // A straight line stays straight
+        let levels = [0.0, 10.0, 20.0, 30.0];
+        for (x, v) in interpolate(&levels, BarInterpolation::CubicSpline, 5) {
+            assert!((v - x * 10.0).abs() < 1e-4, "{} at {}", v, x);
+        }
+        assert_eq!(interpolate(&[-10.0], BarInterpolation::CubicSpline, 5), vec![(0.0, -10.0)]);
+        assert_eq!(steps_for(2.0), 1);
+        assert_eq!(steps_for(20.0), 7);
+    }
+}
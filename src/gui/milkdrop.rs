@@ -0,0 +1,388 @@
+//! MilkDrop visual mode: `.milk` presets from the `milkdrop/` folder,
+//! rendered by libprojectM and fed with the same PCM the oscilloscope gets.
+//!
+//! projectM draws with OpenGL, so it runs inside an egui_glow paint callback
+//! (the glow renderer is eframe's default). It renders into its own
+//! framebuffer at the visualizer's size, which is then blitted into place;
+//! projectM sets its own viewport and would otherwise draw over the whole
+//! window.
+//!
+//! libprojectM 4.1+ is loaded at runtime, like plugins, rather than linked,
+//! so a build with the `projectm` feature still starts on machines without
+//! it and the mode just says what's missing. Without the feature the mode
+//! explains how to get it.
+//!
+//! Each window keeps its projectM instance in egui's temp data, keyed by the
+//! viewport, since each one renders in its own GL context.
+
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+
+use crate::gui::theme::to_egui_color;
+use crate::shared_state::{ColorProfile, VisualizationData};
+
+/// Whether this build can render MilkDrop presets at all
+pub const SUPPORTED: bool = cfg!(feature = "projectm");
+/// Preset file extension
+pub const PRESET_EXTENSION: &str = "milk";
+/// Preset packs nest folders, but not deeply
+const MAX_DEPTH: usize = 4;
+
+/// Where presets are listed from
+pub fn presets_dir() -> PathBuf {
+    crate::paths::get().data_dir.join("milkdrop")
+}
+
+/// Every preset under the folder, as `/`-separated paths relative to it,
+/// sorted
+pub fn list_presets() -> Vec<String> {
+    let dir = presets_dir();
+    let mut presets = Vec::new();
+    collect(&dir, &dir, 0, &mut presets);
+    presets.sort_by_key(|p| p.to_lowercase());
+    presets
+}
+
+fn collect(root: &Path, dir: &Path, depth: usize, out: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.is_dir() {
+            if depth < MAX_DEPTH {
+                collect(root, &path, depth + 1, out);
+            }
+        } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case(PRESET_EXTENSION)) {
+            if let Ok(relative) = path.strip_prefix(root) {
+                let parts: Vec<_> = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+                out.push(parts.join("/"));
+            }
+        }
+    }
+}
+
+/// The preset `step` places from `current`, wrapping around. Starts at the
+/// first (or last) one when `current` isn't in the list.
+pub fn step_preset(presets: &[String], current: &str, step: i32) -> Option<String> {
+    if presets.is_empty() {
+        return None;
+    }
+    let len = presets.len() as i32;
+    let next = match presets.iter().position(|p| p == current) {
+        Some(i) => (i as i32 + step).rem_euclid(len),
+        None if step < 0 => len - 1,
+        None => 0,
+    };
+    Some(presets[next as usize].clone())
+}
+
+/// The latest audio block as projectM takes it: interleaved L/R when the
+/// stereo channels match the mono block, else mono. Returns the samples and
+/// the channel count.
+#[cfg_attr(not(feature = "projectm"), allow(dead_code))]
+fn pcm(data: &VisualizationData) -> (Vec<f32>, u32) {
+    let (left, right) = (&data.waveform_left, &data.waveform_right);
+    if !left.is_empty() && left.len() == data.waveform.len() && right.len() == left.len() {
+        (left.iter().zip(right).flat_map(|(&l, &r)| [l, r]).collect(), 2)
+    } else {
+        (data.waveform.clone(), 1)
+    }
+}
+
+/// Draw the MilkDrop mode with `preset` (relative to the presets folder)
+pub fn draw_milkdrop(painter: &egui::Painter, rect: egui::Rect, preset: &str, colors: &ColorProfile, data: &VisualizationData) {
+    #[cfg(feature = "projectm")]
+    let message = imp::draw(painter, rect, preset, data);
+    #[cfg(not(feature = "projectm"))]
+    let message = {
+        let _ = (preset, data);
+        Some("This build has no MilkDrop support.\nBuild with `--features projectm` and install libprojectM 4.1+.".to_string())
+    };
+
+    if let Some(message) = message {
+        painter.text(
+            rect.left_top() + egui::vec2(8.0, 8.0),
+            egui::Align2::LEFT_TOP,
+            message,
+            egui::FontId::monospace(12.0),
+            to_egui_color(colors.text),
+        );
+    }
+    // Presets animate on their own time, not just when audio arrives
+    painter.ctx().request_repaint();
+}
+
+#[cfg(feature = "projectm")]
+mod imp {
+    use std::ffi::{c_char, c_void, CString};
+    use std::sync::{Arc, Mutex, OnceLock};
+    use std::time::Instant;
+
+    use eframe::egui;
+    use eframe::egui_glow;
+    use eframe::glow::{self, HasContext};
+
+    use crate::shared_state::VisualizationData;
+
+    /// Samples (per channel) kept for the next frame; older ones are dropped
+    const MAX_PCM_FRAMES: usize = 4096;
+
+    #[cfg(target_os = "windows")]
+    const LIBRARY_NAMES: &[&str] = &["projectM-4.dll"];
+    #[cfg(target_os = "macos")]
+    const LIBRARY_NAMES: &[&str] = &["libprojectM-4.4.dylib", "libprojectM-4.dylib"];
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    const LIBRARY_NAMES: &[&str] = &["libprojectM-4.so.4", "libprojectM-4.so"];
+
+    type Handle = *mut c_void;
+
+    /// The parts of the libprojectM 4 C API we use
+    struct Api {
+        create: unsafe extern "C" fn() -> Handle,
+        destroy: unsafe extern "C" fn(Handle),
+        set_window_size: unsafe extern "C" fn(Handle, usize, usize),
+        pcm_add_float: unsafe extern "C" fn(Handle, *const f32, u32, u32),
+        load_preset_file: unsafe extern "C" fn(Handle, *const c_char, bool),
+        render_frame_fbo: unsafe extern "C" fn(Handle, u32),
+    }
+
+    static API: OnceLock<Result<Api, String>> = OnceLock::new();
+
+    fn api() -> Result<&'static Api, &'static str> {
+        API.get_or_init(load).as_ref().map_err(|e| e.as_str())
+    }
+
+    fn load() -> Result<Api, String> {
+        let mut error = String::new();
+        for name in LIBRARY_NAMES {
+            // SAFETY: libprojectM is a regular shared library the user installed
+            let library = match unsafe { libloading::Library::new(name) } {
+                Ok(library) => library,
+                Err(e) => {
+                    error = e.to_string();
+                    continue;
+                }
+            };
+            // SAFETY: the field types match projectM 4's public C API
+            let api = Api {
+                create: symbol(&library, b"projectm_create\0")?,
+                destroy: symbol(&library, b"projectm_destroy\0")?,
+                set_window_size: symbol(&library, b"projectm_set_window_size\0")?,
+                pcm_add_float: symbol(&library, b"projectm_pcm_add_float\0")?,
+                load_preset_file: symbol(&library, b"projectm_load_preset_file\0")?,
+                render_frame_fbo: symbol(&library, b"projectm_opengl_render_frame_fbo\0")?,
+            };
+            tracing::info!("[MilkDrop] Loaded {}", name);
+            // Never unloaded, the function pointers above live in it
+            std::mem::forget(library);
+            return Ok(api);
+        }
+        tracing::warn!("[MilkDrop] libprojectM not found: {}", error);
+        Err(format!("libprojectM 4.1+ was not found ({}).\nInstall projectM to use MilkDrop presets.", error))
+    }
+
+    fn symbol<T: Copy>(library: &libloading::Library, name: &[u8]) -> Result<T, String> {
+        unsafe { library.get::<T>(name) }
+            .map(|symbol| *symbol)
+            .map_err(|_| format!("{} is too old, MilkDrop needs projectM 4.1 or newer", LIBRARY_NAMES[0]))
+    }
+
+    /// The offscreen framebuffer projectM draws into
+    #[derive(Clone, Copy)]
+    struct Target {
+        fbo: glow::Framebuffer,
+        texture: glow::Texture,
+        size: [i32; 2],
+    }
+
+    /// One window's projectM instance. Only touched from inside paint
+    /// callbacks (GL context current) and by `draw` to queue audio.
+    #[derive(Default)]
+    struct Renderer {
+        handle: Option<Handle>,
+        /// Preset the profile wants, and the one projectM has
+        wanted: String,
+        loaded: Option<String>,
+        pcm: Vec<f32>,
+        channels: u32,
+        last_audio: Option<Instant>,
+        target: Option<Target>,
+        /// GL context `target` was made in, for deleting it on drop
+        gl: Option<Arc<glow::Context>>,
+        error: Option<String>,
+    }
+
+    // projectM is single-threaded; the mutex keeps it to one caller at a time
+    unsafe impl Send for Renderer {}
+
+    impl Renderer {
+        fn queue_audio(&mut self, data: &VisualizationData) {
+            if self.last_audio == Some(data.timestamp) {
+                return;
+            }
+            self.last_audio = Some(data.timestamp);
+            let (samples, channels) = super::pcm(data);
+            if channels != self.channels {
+                self.pcm.clear();
+                self.channels = channels;
+            }
+            self.pcm.extend_from_slice(&samples);
+            let max = MAX_PCM_FRAMES * channels as usize;
+            if self.pcm.len() > max {
+                self.pcm.drain(..self.pcm.len() - max);
+            }
+        }
+
+        fn render(&mut self, info: egui::PaintCallbackInfo, painter: &egui_glow::Painter) {
+            let Ok(api) = api() else { return };
+            let viewport = info.viewport_in_pixels();
+            let (w, h) = (viewport.width_px, viewport.height_px);
+            if w <= 0 || h <= 0 {
+                return;
+            }
+            let handle = match self.handle {
+                Some(handle) => handle,
+                None if self.error.is_some() => return,
+                None => {
+                    let handle = unsafe { (api.create)() };
+                    if handle.is_null() {
+                        tracing::error!("[MilkDrop] projectM failed to start");
+                        self.error = Some("projectM failed to start (it needs OpenGL 3.3)".to_string());
+                        return;
+                    }
+                    self.handle = Some(handle);
+                    handle
+                }
+            };
+
+            if self.loaded.as_ref() != Some(&self.wanted) {
+                self.loaded = Some(self.wanted.clone());
+                if !self.wanted.is_empty() {
+                    let path = super::presets_dir().join(&self.wanted);
+                    if let Ok(path) = CString::new(path.to_string_lossy().as_bytes()) {
+                        unsafe { (api.load_preset_file)(handle, path.as_ptr(), true) };
+                    }
+                }
+            }
+
+            if self.channels > 0 && !self.pcm.is_empty() {
+                let frames = self.pcm.len() / self.channels as usize;
+                unsafe { (api.pcm_add_float)(handle, self.pcm.as_ptr(), frames as u32, self.channels) };
+                self.pcm.clear();
+            }
+
+            let gl = painter.gl();
+            self.gl.get_or_insert_with(|| gl.clone());
+            let Some(target) = self.target(gl, [w, h]) else { return };
+            unsafe {
+                (api.set_window_size)(handle, w as usize, h as usize);
+                (api.render_frame_fbo)(handle, target.fbo.0.get());
+
+                gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(target.fbo));
+                gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, painter.intermediate_fbo());
+                let (x, y) = (viewport.left_px, viewport.from_bottom_px);
+                gl.blit_framebuffer(0, 0, w, h, x, y, x + w, y + h, glow::COLOR_BUFFER_BIT, glow::NEAREST);
+                gl.bind_framebuffer(glow::FRAMEBUFFER, painter.intermediate_fbo());
+            }
+        }
+
+        /// The framebuffer at `size`, (re)created as needed
+        fn target(&mut self, gl: &glow::Context, size: [i32; 2]) -> Option<Target> {
+            if let Some(target) = self.target.filter(|t| t.size == size) {
+                return Some(target);
+            }
+            unsafe {
+                if let Some(old) = self.target.take() {
+                    gl.delete_framebuffer(old.fbo);
+                    gl.delete_texture(old.texture);
+                }
+                let texture = gl.create_texture().ok()?;
+                gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+                gl.tex_image_2d(glow::TEXTURE_2D, 0, glow::RGBA8 as i32, size[0], size[1], 0, glow::RGBA, glow::UNSIGNED_BYTE, None);
+                gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, glow::LINEAR as i32);
+                gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, glow::LINEAR as i32);
+                gl.bind_texture(glow::TEXTURE_2D, None);
+
+                let fbo = gl.create_framebuffer().ok()?;
+                gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+                gl.framebuffer_texture_2d(glow::FRAMEBUFFER, glow::COLOR_ATTACHMENT0, glow::TEXTURE_2D, Some(texture), 0);
+                self.target = Some(Target { fbo, texture, size });
+            }
+            self.target
+        }
+    }
+
+    /// The last reference goes with the window's temp data or its paint
+    /// callback, both on the GUI thread, where eframe keeps the context current
+    impl Drop for Renderer {
+        fn drop(&mut self) {
+            if let (Some(handle), Ok(api)) = (self.handle.take(), api()) {
+                unsafe { (api.destroy)(handle) };
+            }
+            if let (Some(target), Some(gl)) = (self.target.take(), self.gl.as_ref()) {
+                unsafe {
+                    gl.delete_framebuffer(target.fbo);
+                    gl.delete_texture(target.texture);
+                }
+            }
+        }
+    }
+
+    /// Queue this frame's audio and the render callback. Returns a message
+    /// to show instead when projectM isn't available.
+    pub(super) fn draw(painter: &egui::Painter, rect: egui::Rect, preset: &str, data: &VisualizationData) -> Option<String> {
+        if let Err(e) = api() {
+            return Some(e.to_string());
+        }
+        let id = egui::Id::new("milkdrop_renderer").with(painter.ctx().viewport_id());
+        let renderer = painter.ctx().data_mut(|d| d.get_temp_mut_or_default::<Arc<Mutex<Renderer>>>(id).clone());
+        let error = {
+            let Ok(mut r) = renderer.lock() else { return None };
+            r.wanted = preset.to_string();
+            r.queue_audio(data);
+            r.error.clone()
+        };
+        if error.is_some() {
+            return error;
+        }
+
+        painter.add(egui::PaintCallback {
+            rect,
+            callback: Arc::new(egui_glow::CallbackFn::new(move |info, painter| {
+                if let Ok(mut r) = renderer.lock() {
+                    r.render(info, painter);
+                }
+            })),
+        });
+        preset.is_empty().then(|| format!("Pick a preset under Settings → Visual\n({})", super::presets_dir().display()))
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_preset_wraps() {
+        let presets: Vec<String> = ["a.milk", "b.milk", "c.milk"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(step_preset(&presets, "a.milk", 1).as_deref(), Some("b.milk"));
+        assert_eq!(step_preset(&presets, "c.milk", 1).as_deref(), Some("a.milk"));
+        assert_eq!(step_preset(&presets, "a.milk", -1).as_deref(), Some("c.milk"));
+        // Nothing picked yet: start at either end
+        assert_eq!(step_preset(&presets, "", 1).as_deref(), Some("a.milk"));
+        assert_eq!(step_preset(&presets, "", -1).as_deref(), Some("c.milk"));
+        assert_eq!(step_preset(&[], "", 1), None);
+    }
+
+    #[test]
+    fn test_pcm_interleaves_stereo() {
+        let mut data = VisualizationData::new(8);
+        data.waveform = vec![0.0, 0.5];
+        assert_eq!(pcm(&data), (vec![0.0, 0.5], 1));
+
+        data.waveform_left = vec![0.1, 0.2];
+        data.waveform_right = vec![-0.1, -0.2];
+        assert_eq!(pcm(&data), (vec![0.1, -0.1, 0.2, -0.2], 2));
+    }
+}
@@ -1,8 +1,31 @@
 // src/gui/mod.rs
 pub mod theme;
+pub mod gradient;
 pub mod visualizers;
 pub mod decorations;
 pub mod widgets;
+pub mod history;
+pub mod headless;
+pub mod tasks;
+pub mod pacing;
+pub mod shortcuts;
+pub mod quick_switch;
+pub mod opacity;
+pub mod ghost;
+pub mod gamepad_overlay;
+pub mod settings_search;
+pub mod thumbnails;
+pub mod remote;
+pub mod interpolation;
+pub mod background;
+pub mod glow;
+pub mod bar_style;
+pub mod dot_matrix;
+#[cfg(feature = "scripting")]
+pub mod script;
+#[cfg(feature = "plugins")]
+pub mod plugins;
+pub mod milkdrop;
 
 use crate::gui::theme::*;
 use crate::gui::visualizers as viz;
@@ -10,13 +33,36 @@ use crate::gui::visualizers as viz;
 
 use crossbeam_channel::Receiver;
 use eframe:: egui;
+use raw_window_handle::HasWindowHandle;
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use crate::media::{PlatformMedia};
-use crate::shared_state::{Color32 as StateColor32, SharedState};
+use crate::shared_state::{Color32 as StateColor32, ColorRef, DockEdge, SharedState};
 
 use crate::gui::widgets::{SaveTarget, SettingsTab};
+use crate::gui::history::SettingsHistory;
+use crate::gui::tasks::TaskRunner;
+use crate::lyrics::LyricsStatus;
+use crate::clip_indicator::ClipStyle;
+
+/// Repaint interval while auto sleep is on (2 FPS)
+const AUTO_SLEEP_REPAINT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Black overlay alpha when "dim while asleep" is on
+const AUTO_SLEEP_DIM_ALPHA: u8 = 140;
+
+/// How long the window manager gets to apply a mini mode resize/move
+const MINI_MODE_SETTLE: Duration = Duration::from_millis(500);
+
+/// The window has to stop moving this long before a drag counts as finished
+const DRAG_SNAP_SETTLE: Duration = Duration::from_millis(250);
+
+/// Repaint (and occlusion check) interval while the window is hidden
+const HIDDEN_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often an idle window re-checks the color schedule
+const THEME_SCHEDULE_CHECK: Duration = Duration::from_secs(30);
 
 pub struct LodDebouncer {
     pub target_limit: usize,
@@ -49,6 +95,9 @@ pub struct SpectrumApp {
 
     /// cached album art texture
     album_art_texture: Option<egui::TextureHandle>,
+
+    /// Frames of the profile's background image
+    background_image: background::BackgroundTexture,
     
     /// Opacity for entire media overlay
     media_opacity: f32,
@@ -59,18 +108,91 @@ pub struct SpectrumApp {
     /// Settings window state
     settings_open: bool,
 
+    /// Keyboard shortcut cheat sheet is showing (toggled with '?')
+    cheat_sheet_open: bool,
+
+    /// Profile / color carousel (Ctrl + quick switch key)
+    quick_switch: quick_switch::QuickSwitcher,
+
+    /// Color preset the time-of-day schedule last switched to. Only a change
+    /// switches again, so a preset picked by hand sticks until the next slot.
+    scheduled_preset: Option<String>,
+
+    /// Snapshot drawn instead of the live data while the display is paused
+    /// from the right-click menu (shares the spectrogram history, see
+    /// `VisualizationData::history`)
+    frozen: Option<crate::shared_state::VisualizationData>,
+
+    /// "Artist – Title" toast after a track change, and when it popped up
+    track_toast: Option<(String, Instant)>,
+
+    /// Report from a crash in the previous session, until the user answers
+    last_crash: Option<std::path::PathBuf>,
+
+    /// Controller presses, once gamepad input has been switched on
+    gamepad_rx: Option<Receiver<crate::gamepad::PadButton>>,
+    gamepad: gamepad_overlay::GamepadOverlay,
+
+    /// [ / ] and Ctrl + wheel opacity changes, and their readout
+    opacity: opacity::OpacityNudge,
+
+    /// Hot corner / handle that make a ghosted window clickable
+    ghost_wake: ghost::GhostWake,
+
+    /// Global hotkey that hides the window until a timer or track change
+    peek: crate::peek_away::PeekAway,
+
+    /// MIDI / OSC remote control listeners and bindings
+    remote: remote::RemoteBridge,
+
+    /// Localhost HTTP endpoint and the port it was started on
+    http_server: Option<(u16, crate::http_api::Server)>,
+    deck_server: Option<(u16, crate::stream_deck::Server)>,
+    /// Pink noise for the measurement mode, with the device it plays on
+    noise_player: Option<(String, crate::audio_output::Player)>,
+    /// Tools tab signal generator while it plays
+    generator: Option<crate::signal_gen::Playing>,
+    /// Clicks for the latency self-test while it runs
+    click_player: Option<crate::audio_output::Player>,
+
     /// Current active settings tab
     active_tab: SettingsTab,
 
+    /// Settings search box text
+    settings_search: String,
+
     /// Performance tracking
     last_frame_time :  Instant, 
     frame_times: Vec<f32>,
+    /// Lock wait and paint time of the last visualizer draw
+    viz_stage_times: (Duration, Duration),
+
+    /// Last occlusion check and its answer
+    occlusion: Option<(Instant, bool)>,
+
+    /// Repaint interval (frame rate cap or the monitor's refresh) and dropped frames
+    pacer: pacing::FramePacer,
 
     /// Track window size to only log changes
     last_window_size: Option<egui::Vec2>,
     last_window_pos: Option<egui::Pos2>,
     last_passthrough_state: bool,
 
+    /// Ignore geometry changes until then (the OS is still applying a mini mode toggle)
+    geometry_settle_until: Option<Instant>,
+
+    /// Last time the user moved the window (snapped once it settles)
+    window_dragged_at: Option<Instant>,
+
+    /// Dock edge and thickness currently applied to the window
+    docked: Option<(DockEdge, f32)>,
+    /// Window the dock reserved screen space for, to give it back on exit
+    /// (there's no `Frame` then to ask for the handle)
+    dock_window: Option<raw_window_handle::RawWindowHandle>,
+
+    /// The window currently sits behind the desktop icons
+    wallpaper: bool,
+
     // Sonar Ping State
     was_focused: bool,
     flash_start: Option<Instant>,
@@ -79,6 +201,15 @@ pub struct SpectrumApp {
     save_target: SaveTarget,
     new_preset_name: String,
 
+    /// Undo trail for the settings window (Ctrl+Z)
+    settings_history: SettingsHistory,
+
+    /// Worker pool for GUI-triggered background jobs
+    tasks: TaskRunner,
+
+    /// When the GUI started (counts as the last signal until audio shows up)
+    launched_at: Instant,
+
     /// Level Of Detail Debouncer
     pub lod_debouncer: LodDebouncer,
 }
@@ -92,7 +223,8 @@ impl SpectrumApp {
 
         let initial_size = {
             let state = shared_state.lock().expect("failed to lock shared state");
-            egui::Vec2::new(state.config.window_size[0], state.config.window_size[1])
+            let (size, _) = state.config.active_geometry();
+            egui::Vec2::new(size[0], size[1])
         };
         Self {
             shared_state,
@@ -101,17 +233,47 @@ impl SpectrumApp {
             media_opacity: 0.0,
             last_media_interaction: None,
             album_art_texture: None,
+            background_image: Default::default(),
             settings_open: false,
+            cheat_sheet_open: false,
+            quick_switch: quick_switch::QuickSwitcher::new(),
+            scheduled_preset: None,
+            frozen: None,
+            track_toast: None,
+            last_crash: crate::crash_report::take_pending(),
+            gamepad_rx: None,
+            gamepad: Default::default(),
+            opacity: Default::default(),
+            ghost_wake: Default::default(),
+            peek: Default::default(),
+            remote: Default::default(),
+            http_server: None,
+            deck_server: None,
+            noise_player: None,
+            generator: None,
+            click_player: None,
             active_tab: SettingsTab::Visual,
+            settings_search: String::new(),
             last_frame_time: Instant::now(),
             frame_times: Vec::with_capacity(60),
+            viz_stage_times: Default::default(),
+            occlusion: None,
+            pacer: Default::default(),
             last_window_size: Some(initial_size),
             last_window_pos: None,
             last_passthrough_state: false,
+            geometry_settle_until: None,
+            window_dragged_at: None,
+            docked: None,
+            dock_window: None,
+            wallpaper: false,
             was_focused: true,
             flash_start: Some(Instant::now()),
             save_target: SaveTarget::None,
             new_preset_name: String::new(),
+            settings_history: SettingsHistory::new(),
+            tasks: TaskRunner::new(),
+            launched_at: Instant::now(),
             lod_debouncer: LodDebouncer::new(),
         }
     }
@@ -121,11 +283,24 @@ impl eframe::App for SpectrumApp {
 
     // This is called by eframe periodicatlly and/or on exit
     fn save(&mut self, _storage: &mut dyn eframe::Storage) {
-        // On exit, save the current config to disk
-        if let Ok(state) = self.shared_state.lock() {
+        // On exit, save the current config to disk, with the geometry
+        // filed under the monitor setup it belongs to
+        let layout_key = crate::window_integration::layout_key(&crate::window_integration::monitors());
+        if let Ok(mut state) = self.shared_state.lock() {
+            if let Some(key) = &layout_key {
+                state.config.remember_layout(key);
+            }
             state.config.save();
         }
     }
+
+    /// Give a docked window's screen strip back, or the desktop keeps it
+    /// reserved after we're gone
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if let Some(handle) = self.dock_window.take() {
+            crate::window_integration::release(handle);
+        }
+    }
     
     /// Tell eframe to clear the window with total transparency
     /// this alllows the OS background to show through when our CentralPanel
@@ -135,7 +310,7 @@ impl eframe::App for SpectrumApp {
         [0.0, 0.0, 0.0, 0.0] 
     }
 
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         
         let minimize_key = self.shared_state.lock().expect("failed to lock shared state").config.minimize_key;
         let shortcut = egui::KeyboardShortcut::new(egui::Modifiers::CTRL, minimize_key);
@@ -144,6 +319,46 @@ impl eframe::App for SpectrumApp {
             ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
         }
 
+        let mini_key = self.shared_state.lock().expect("failed to lock shared state").config.mini_mode_key;
+        if ctx.input_mut(|i| i.consume_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::CTRL, mini_key))) {
+            self.toggle_mini_mode(ctx);
+        }
+
+        // The guard can't borrow `self` while the per-frame helpers below take `&mut self`
+        let shared_state = self.shared_state.clone();
+        if let Ok(mut state) = shared_state.lock() {
+            let quick_key = state.config.quick_switch_key;
+            if ctx.input_mut(|i| i.consume_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::CTRL, quick_key))) {
+                self.quick_switch.toggle(ctx, &state);
+            }
+            self.apply_theme_schedule(ctx, &mut state);
+            if self.quick_switch.is_open() {
+                self.quick_switch.handle(ctx, &mut state);
+            } else {
+                shortcuts::handle(ctx, &mut state, &mut self.settings_open, &mut self.cheat_sheet_open);
+                self.opacity.handle(ctx, &mut state);
+            }
+            self.poll_gamepad(ctx, &mut state);
+            self.poll_peek(ctx, &state);
+            self.remote.poll(ctx, &mut state);
+            self.sync_http_api(ctx, state.config.http_api);
+            self.sync_stream_deck(ctx, state.config.stream_deck);
+            self.sync_noise_output(&state);
+            crate::signal_gen::Playing::sync(&mut self.generator, state.signal_gen_running, &state.config.signal_gen);
+            self.sync_latency_test(&mut state);
+        }
+
+        self.update_wallpaper(ctx, frame);
+        if !self.wallpaper {
+            self.update_dock(ctx, frame);
+        }
+
+        // Don't record the old layout's geometry into the new one mid-toggle,
+        // and never save the docked strip or the wallpaper as the floating geometry
+        let geometry_settling = self.geometry_settle_until.map_or(false, |t| Instant::now() < t)
+            || self.docked.is_some()
+            || self.wallpaper;
+
         // --- Poll for Media Updates ---
         let mut new_track = None;
         while let Ok(info) = self.media_rx.try_recv() {
@@ -151,20 +366,33 @@ impl eframe::App for SpectrumApp {
         }
 
         if let Some(track) = new_track {
+            let mut seek_only = false;
             if let Ok(mut state) = self.shared_state.lock() {
-                // Explicitly check the user's preference before logging metadata
-                if state.config.log_media_metadata {
-                    tracing::info!("[GUI] New Track: {} - {}", track.artist, track.title);
-                } else {
-                    tracing::info!("[GUI] New Track received (Metadata logging disabled by user)");
+                // A seek only moves the progress bar, it doesn't bring the overlay back
+                seek_only = state.media_info.as_ref().map_or(false, |old| old.same_track(&track));
+                if !seek_only {
+                    // Explicitly check the user's preference before logging metadata
+                    if state.config.log_media_metadata {
+                        tracing::info!("[GUI] New Track: {} - {}", track.artist, track.title);
+                    } else {
+                        tracing::info!("[GUI] New Track received (Metadata logging disabled by user)");
+                    }
+                    state.last_media_update = Some(Instant::now());
+                }
+
+                state.lyrics.track_update(&track, Instant::now());
+                let max_len = state.config.track_history_len;
+                let new_entry = state.track_history.record(&track, max_len, crate::theme_schedule::local_minute_of_day(), Instant::now());
+                if new_entry && state.config.track_toast {
+                    self.track_toast = state.track_history.entries().next().map(|e| (e.display(), Instant::now()));
                 }
-                
                 state.media_info = Some(track.clone());
-                state.last_media_update = Some(Instant::now());
             }
 
             // Process album art
-            if let Some((pixels, size)) = &track.album_art {
+            if seek_only {
+                // Same picture as before
+            } else if let Some((pixels, size)) = &track.album_art {
                 let color_image = egui::ColorImage::from_rgba_unmultiplied(
                     *size,
                     pixels.as_slice(),
@@ -175,8 +403,10 @@ impl eframe::App for SpectrumApp {
                     color_image,
                     egui::TextureOptions::LINEAR
                 ));
+                self.background_image.set_album_art(ctx, Some((pixels.as_slice(), *size)));
             } else {
                 self.album_art_texture = None;
+                self.background_image.set_album_art(ctx, None);
             }
         }
         
@@ -189,6 +419,9 @@ impl eframe::App for SpectrumApp {
                 // Determine if we should log (don't log first detection to avoid spam on startup)
                 if self.last_window_pos.is_some() {
                     tracing::debug!("[GUI] Main Window Moved: x: {:.0}, y: {:.0}", current_pos.x, current_pos.y);
+                    if !geometry_settling {
+                        self.window_dragged_at = Some(Instant::now());
+                    }
                 }
                 
                 self.last_window_pos = Some(current_pos);
@@ -201,10 +434,10 @@ impl eframe::App for SpectrumApp {
                     { true }
                 };
 
-                if should_save_pos {
+                if should_save_pos && !geometry_settling {
                     tracing::debug!("[GUI/Trace] Saving new position to config: {:?}", current_pos);
                     if let Ok(mut state) = self.shared_state.lock() {
-                        state.config.window_position = Some([current_pos.x, current_pos.y]);
+                        *state.config.active_geometry_mut().1 = Some([current_pos.x, current_pos.y]);
                     }
                 } else {
                     tracing::debug!("[GUI/Trace] Window moved, but Wayland detected. Skipping config position overwrite.");
@@ -212,6 +445,8 @@ impl eframe::App for SpectrumApp {
             }
         }
 
+        self.handle_window_drag(ctx);
+
         // --- Enforce Initial Size on Wayland ---
         // If the OS forced a size that doesn't match our config, try to command a resize.
         // We use screen_rect() because Wayland blinds viewport().inner_rect
@@ -232,8 +467,8 @@ impl eframe::App for SpectrumApp {
             
             if let Ok(mut state) = self.shared_state.lock() {
                 // FIX: Only save if "Normal".
-                if !state.config.beos_window_collapsed && !is_maximized {
-                    state.config.window_size = [current_size.x, current_size.y];
+                if !state.config.beos_window_collapsed && !is_maximized && !geometry_settling {
+                    *state.config.active_geometry_mut().0 = [current_size.x, current_size.y];
                 }
             }
             self.last_window_size = Some(current_size);
@@ -254,13 +489,52 @@ impl eframe::App for SpectrumApp {
         let avg_frame_time = self.frame_times.iter().sum::<f32>() / self.frame_times.len() as f32;
         let fps = 1.0 / avg_frame_time;
 
+        let dropped = self.pacer.note_frame(Duration::from_secs_f32(frame_time));
+        self.pacer.refresh(now, || {
+            frame.window_handle().ok().and_then(|h| crate::window_integration::refresh_rate(h.as_raw()))
+        });
+
         // Update the FPS in shared state
         if let Ok(mut state) = self.shared_state.lock() {
             state.performance.gui_fps = fps;
+            state.performance.display_hz = self.pacer.display_hz();
+            state.performance.dropped_frames += dropped as u64;
+            let (lock_wait, paint) = self.viz_stage_times;
+            state.performance.frame_timings.note_frame(lock_wait, paint);
+            let published = state.visualization.timestamp;
+            state.performance.latency.note_drawn(published, now);
+
+            // Heartbeat for the renderer watchdog (minimized windows may stop drawing)
+            let minimized = ctx.input(|i| i.viewport().minimized.unwrap_or(false));
+            if let Some(stalled) = state.render_watchdog.frame(now, minimized) {
+                let err = crate::error::BeSpecError::RendererStalled(stalled.as_secs());
+                err.log();
+                state.last_error = Some(err);
+            }
          }
 
-        // Request continuous repainting for smooth animation
-        ctx.request_repaint();
+        // Request continuous repainting for smooth animation,
+        // unless nothing audible has happened for a while (auto sleep)
+        // or nobody can see the window
+        let hidden = self.update_hidden(ctx, frame, now);
+        let asleep = self.update_sleep(ctx, now);
+        let fps_cap = self.shared_state.lock().ok().and_then(|s| s.config.fps_cap.target());
+        let mut capped_interval = None;
+        if hidden {
+            ctx.request_repaint_after(HIDDEN_CHECK_INTERVAL);
+            self.pacer.expect(None);
+        } else if asleep {
+            ctx.request_repaint_after(AUTO_SLEEP_REPAINT_INTERVAL);
+            self.pacer.expect(None);
+        } else if fps_cap.is_some() {
+            // Requested at the end of the frame, once we know how long it took
+            capped_interval = Some(self.pacer.interval(fps_cap));
+            self.pacer.expect(capped_interval);
+        } else {
+            // Vsync paces this to the monitor's refresh rate
+            ctx.request_repaint();
+            self.pacer.expect(Some(self.pacer.interval(None)));
+        }
 
         // === Main Window ===
 
@@ -284,7 +558,7 @@ impl eframe::App for SpectrumApp {
         }
 
         // Use Profile Background Color
-        let (window_fill, content_fill, window_locked, background_alpha) = if let Ok(state) = self.shared_state.lock() {
+        let (window_fill, content_fill, window_locked, background_alpha, ghost_exits) = if let Ok(state) = self.shared_state.lock() {
             let colors = state.config.resolve_colors(&state.user_color_presets);
             let bg = to_egui_color(colors.background);
             let base_alpha = bg.a() as f32 / 255.0;
@@ -301,13 +575,14 @@ impl eframe::App for SpectrumApp {
             // If BeOS mode is active, the "Window" (CentralPanel) must be TRANSPARENT 
             // so the area around the tab is clear. We will paint the 'user_bg_color' 
             // manually inside the decorations module.
+            let ghost_exits = (state.config.ghost_hot_corner, state.config.ghost_handle);
             if state.config.profile.beos_enabled {
-                (egui::Color32::TRANSPARENT, user_bg_color, state.config.window_locked, final_alpha)
+                (egui::Color32::TRANSPARENT, user_bg_color, state.config.window_locked, final_alpha, ghost_exits)
             } else {
-                (user_bg_color, user_bg_color, state.config.window_locked, final_alpha)
+                (user_bg_color, user_bg_color, state.config.window_locked, final_alpha, ghost_exits)
             }
         } else {
-            (egui::Color32::BLACK, egui::Color32::BLACK, false, 1.0) 
+            (egui::Color32::BLACK, egui::Color32::BLACK, false, 1.0, Default::default())
         };
 
         // === 3. Ghost Mode Logic === (Focus-to-Wake) ===
@@ -318,8 +593,21 @@ impl eframe::App for SpectrumApp {
         // 3. !is_focused: The window is NOT currently active.
         //    CRITICAL: This allows "Alt-Tab to Wake". If the user Alt-Tabs to this window,
         //    it gains focus, passthrough turns OFF, and the user can click the unlock button.
+        // 4. Unless a hot corner or the handle woke it up (see `ghost`).
         let is_transparent = background_alpha <= 0.05; // Threshold for "invisible"
-        let should_passthrough = window_locked && is_transparent && !is_focused;
+        let ghosted = window_locked && is_transparent && !is_focused;
+        let woken = match frame.window_handle() {
+            Ok(handle) if ghosted => {
+                let (corner, handle_strip) = ghost_exits;
+                self.ghost_wake.awake(ctx, handle.as_raw(), corner, handle_strip, Instant::now())
+            }
+            _ => {
+                self.ghost_wake.reset();
+                false
+            }
+        };
+        // A wallpaper is always click-through, the desktop icons get the clicks.
+        let should_passthrough = (ghosted && !woken) || self.wallpaper;
 
         // Only send command if state changed (prevents spamming the OS Window manager)
         if should_passthrough != self.last_passthrough_state {
@@ -376,8 +664,43 @@ impl eframe::App for SpectrumApp {
                 }
 
                 // Handle Dragging
+                let mut menu_actions = widgets::MenuActions::default();
                 if !chrome_layout.is_collapsed {
-                    widgets::handle_window_interaction(ui, ctx, viz_rect, &mut self.settings_open);
+                    menu_actions = widgets::handle_window_interaction(ui, ctx, viz_rect, &self.shared_state, &mut self.settings_open, self.frozen.is_some());
+
+                    if let Ok(state) = self.shared_state.lock() {
+                        let spectrogram = viz::mode_rects(viz_rect, &state.config.profile)
+                            .into_iter()
+                            .find(|&(mode, _)| mode == crate::shared_state::VisualMode::Spectrogram);
+                        if let Some((_, rect)) = spectrogram {
+                            widgets::handle_spectrogram_input(ui, rect, &state.visualization.history);
+                        }
+                    }
+                }
+
+                if menu_actions.toggle_mini {
+                    self.toggle_mini_mode(ctx);
+                }
+
+                if menu_actions.toggle_pause {
+                    self.frozen = match self.frozen.take() {
+                        Some(_) => None,
+                        None => self.shared_state.lock().ok().map(|s| s.visualization.clone()),
+                    };
+                }
+
+                // Screenshot (rendered off-screen so menus/overlays don't end up in it)
+                if menu_actions.screenshot {
+                    let state = self.shared_state.lock().expect("failed to lock shared state for screenshot");
+                    let size = [viz_rect.width() as u32, viz_rect.height() as u32];
+                    match crate::gui::headless::save_screenshot(&state, size) {
+                        Ok(path) => {
+                            if let Some(dir) = path.parent() {
+                                let _ = open::that(dir);
+                            }
+                        }
+                        Err(e) => tracing::error!("[GUI] Screenshot failed: {}", e),
+                    }
                 }
                 
                 // === Orchestration Setup: Calculate Opacity
@@ -395,6 +718,9 @@ impl eframe::App for SpectrumApp {
 
                 //=== Update Notification Setup ===
                 let mut dismissed_click = false;
+                let mut error_dismissed = false;
+                let mut hint_dismissed = None;
+                let mut no_device_action = None;
                 let mut update_url_copy: Option<String> = None;
                 let mut show_banner = false;
 
@@ -403,7 +729,7 @@ impl eframe::App for SpectrumApp {
 
                 // Quick check! (small scope lock)
                 if let Ok(state) = self.shared_state.lock(){
-                    if state.update_url.is_some() && !state.update_dismissed {
+                    if state.update_url.is_some() && !state.update_dismissed && !state.config.mini_mode {
                         show_banner = true;
                         update_url_copy = state.update_url.clone();
                     }
@@ -412,11 +738,14 @@ impl eframe::App for SpectrumApp {
                 //Scope management for State Lock!!!
                 {
                     // Visualization (requres Read-only Lock)
+                    let lock_start = Instant::now();
                     let state = self.shared_state.lock().expect("failed to lock shared state for viz render"); //lock once!
+                    let lock_wait = lock_start.elapsed();
                     let mut final_viz_rect = viz_rect;
 
                     // ======= Level of Detail Calculation =======
-                    let available_size = final_viz_rect.size();
+                    // Content zoom draws on a smaller virtual canvas, fewer bars fit
+                    let available_size = final_viz_rect.size() / state.config.profile.content_zoom.max(1.0);
                     let limiting_dimension = match state.config.profile.orientation {
                         crate::shared_state::Orientation::BottomUp | crate::shared_state::Orientation::TopDown => available_size.x,
                         crate::shared_state::Orientation::LeftRight | crate::shared_state::Orientation::RightLeft => available_size.y,
@@ -430,7 +759,7 @@ impl eframe::App for SpectrumApp {
                         self.lod_debouncer.last_change = std::time::Instant::now();
                     }
 
-                    let requested_bars = state.config.profile.num_bars;
+                    let requested_bars = state.config.requested_bars();
                     let effective_num_bars = requested_bars.min(self.lod_debouncer.target_limit);
 
                     if self.lod_debouncer.last_change.elapsed() > self.lod_debouncer.delay {
@@ -488,46 +817,141 @@ impl eframe::App for SpectrumApp {
                         });
                     }
                     //=============================================
-                    
-                    let viz_data = &state.visualization;
+
+                    // ======= Error Banner =========
+                    if let Some(err) = state.last_error.as_ref().filter(|_| !state.config.mini_mode) {
+                        let banner_height = 28.0;
+                        let (banner_rect, rest) = final_viz_rect.split_top_bottom_at_y(final_viz_rect.top() + banner_height);
+                        final_viz_rect = rest;
+                        if widgets::draw_error_banner(ui, banner_rect, err) {
+                            error_dismissed = true;
+                        }
+                    }
+
+                    let viz_data = self.frozen.as_ref().unwrap_or(&state.visualization);
 
                     let perf = &state.performance;
                     let media_info = state.media_info.as_ref();
-                    let colors = state.config.resolve_colors(&state.user_color_presets);
+                    let mut colors = state.config.resolve_colors(&state.user_color_presets);
+
+                    let clip = state.config.clip;
+                    let clipping = state.clip.lit(&clip, Instant::now());
+                    if clipping && clip.style == ClipStyle::Bars {
+                        let red = StateColor32::from_rgb(255, 40, 40);
+                        (colors.low, colors.high, colors.peak) = (red, red, red);
+                        colors.gradient.clear();
+                    }
+
+                    // ======= Background Image =========
+                    self.background_image.draw(ui.painter(), final_viz_rect, &state.config.profile.background_image, Instant::now());
+
+                    // ======= Level Meter Strip =========
+                    if state.config.meter_strip && !state.config.mini_mode {
+                        let (rest, strip_rect) = final_viz_rect.split_left_right_at_x(final_viz_rect.right() - viz::METER_STRIP_WIDTH);
+                        final_viz_rect = rest;
+                        viz::draw_meter_strip(ui.painter(), strip_rect, &colors, &state.meter);
+                    }
 
                     // === Render Visualization ===
-                    viz::draw_main_visualizer(
+                    let paint_start = Instant::now();
+                    viz::draw_zoomed(
                         ui.painter(),
                         final_viz_rect,
-                        &state.config,
-                        &state.config.profile,
-                        &colors,
-                        viz_data,
-                        perf,
+                        state.config.profile.content_zoom,
                         ui.input(|i| i.pointer.hover_pos()),
-                        safe_bar_count,
+                        |painter, rect, mouse_pos| {
+                            viz::draw_main_visualizer(
+                                painter,
+                                rect,
+                                &viz::DrawSettings::new(&state.config, &state.config.profile),
+                                &state.config.profile,
+                                &colors,
+                                viz_data,
+                                perf,
+                                mouse_pos,
+                                safe_bar_count,
+                                state.reference_snapshot.as_ref(),
+                                Some(state.measurement.curve.as_slice()).filter(|c| !c.is_empty()),
+                            );
+                        },
                     );
+                    self.viz_stage_times = (lock_wait, paint_start.elapsed());
+
+                    if self.frozen.is_some() {
+                        ui.painter().text(
+                            final_viz_rect.left_top() + egui::vec2(8.0, 6.0),
+                            egui::Align2::LEFT_TOP,
+                            "⏸ Paused",
+                            egui::FontId::proportional(12.0),
+                            to_egui_color(colors.text).gamma_multiply(0.7),
+                        );
+                    }
+
+                    if clipping && clip.style == ClipStyle::Led {
+                        viz::draw_clip_led(ui.painter(), final_viz_rect);
+                    }
+
+                    if state.config.tuner.enabled && !state.config.mini_mode {
+                        viz::draw_tuner_overlay(ui.painter(), final_viz_rect, &colors, state.tuner_reading, state.config.tuner.a4_hz);
+                    }
+
+                    // No audio device: say so instead of showing a flat spectrum
+                    if state.no_audio_device && !state.config.mini_mode {
+                        no_device_action = widgets::draw_no_device_card(ui, final_viz_rect, state.refresh_devices_requested);
+                    }
+
+                    // Gain Advisor Hint
+                    if let Some(hint) = state.gain_hint.filter(|h| !state.dismissed_hints.contains(h)) {
+                        if !state.config.mini_mode && widgets::draw_hint_pill(ui, final_viz_rect, hint.message()) {
+                            hint_dismissed = Some(hint);
+                        }
+                    }
 
                     // Sonar Ping Effect
-                    if flash_strength > 0.0 {
+                    if flash_strength > 0.0 && !state.config.mini_mode {
                       
 
                         viz::draw_sonar_ping(ui, final_viz_rect.shrink(5.0), flash_strength, &colors);
                     }
                     
+                    // Lyrics (own visibility, they're meant to be followed along)
+                    if let LyricsStatus::Ready(lines) = &state.lyrics.status {
+                        if state.config.lyrics.enabled && !state.config.mini_mode {
+                            let now = Instant::now();
+                            let position = state.media_info.as_ref()
+                                .and_then(|info| info.position_now(now))
+                                .unwrap_or_else(|| state.lyrics.clock.position(now));
+                            viz::draw_lyrics(ui, final_viz_rect, lines, position, &state.config.lyrics, &state.config.media_overlay, &colors);
+                        }
+                    }
+
+                    // Track change toast (independent of the overlay mode)
+                    if let Some((text, since)) = &self.track_toast {
+                        let age = since.elapsed().as_secs_f32();
+                        if age < viz::TRACK_TOAST_SECS && !state.config.mini_mode {
+                            viz::draw_track_toast(ui.painter(), final_viz_rect, text, age, &colors);
+                            ui.ctx().request_repaint();
+                        } else {
+                            self.track_toast = None;
+                        }
+                    }
+
                     // Media Overlay
-                    if self.media_opacity > 0.01 {
+                    if self.media_opacity > 0.01 && !state.config.mini_mode {
                         if let Some(info) = media_info{
                             viz::draw_media_overlay(
                                 ui,
                                 final_viz_rect,
                                 Some(info),
                                 state.config.media_display_mode,
+                                &state.config.media_overlay,
+                                &state.config.track_link,
                                 &state.config.profile.overlay_font,
                                 self.media_opacity,
                                 &colors,
                                 self.album_art_texture.as_ref(),
                                 self.media_controller.as_ref(),
+                                &self.tasks,
                             );
                         }
                     }
@@ -540,12 +964,34 @@ impl eframe::App for SpectrumApp {
                         state.update_dismissed = true;
                     }
                 }
+                if let Some(hint) = hint_dismissed {
+                    if let Ok(mut state) = self.shared_state.lock() {
+                        state.dismissed_hints.push(hint);
+                    }
+                }
+                if error_dismissed {
+                    if let Ok(mut state) = self.shared_state.lock() {
+                        state.last_error = None;
+                    }
+                }
+                match no_device_action {
+                    Some(widgets::NoDeviceAction::Retry) => {
+                        if let Ok(mut state) = self.shared_state.lock() {
+                            state.refresh_devices_requested = true;
+                        }
+                    }
+                    Some(widgets::NoDeviceAction::OpenSettings) => {
+                        self.settings_open = true;
+                        self.active_tab = SettingsTab::Audio;
+                    }
+                    None => {}
+                }
 
                 // Safely apply the LOD limit to wake up the FFT thread
                 if let Some(new_limit) = pending_lod_update {
                     if let Ok(mut state) = self.shared_state.lock() {
                         state.lod_bar_limit = Some(new_limit);
-                        tracing::debug!("[GUI] Debounce complete: Requested FFT rebuild for {} bars", new_limit);
+                        tracing::debug!("[GUI] Debounce complete: Requested {} bars from the FFT thread", new_limit);
                     }
                 }
 
@@ -553,14 +999,14 @@ impl eframe::App for SpectrumApp {
                 // 1. Resize Grip (Needs Context + Window Rect)
                 // We check the inverted state first (read-only lock)
                 //REFACTOR PATCH
-                let (is_inverted, is_collapsed) = if let Ok(s) = self.shared_state.lock() {
-                    (false, s.config.beos_window_collapsed)
+                let (is_inverted, is_collapsed, aspect_lock) = if let Ok(s) = self.shared_state.lock() {
+                    (false, s.config.beos_window_collapsed, s.config.aspect_lock)
                 } else {
-                    (false, false)
+                    (false, false, None)
                 };
                 
                 if !is_collapsed{
-                    widgets::draw_resize_grip(ui, ctx, window_rect, is_inverted);
+                    widgets::draw_resize_grip(ui, ctx, window_rect, is_inverted, aspect_lock);
                 }
 
                 // 2. Lock Button (needs mutable State Access)
@@ -572,6 +1018,20 @@ impl eframe::App for SpectrumApp {
         
         
         
+        if self.cheat_sheet_open {
+            shortcuts::show_cheat_sheet(ctx);
+        }
+        self.opacity.show(ctx);
+        self.show_last_crash(ctx);
+        if self.quick_switch.is_open() {
+            if let Ok(state) = self.shared_state.lock() {
+                self.quick_switch.show(ctx, &state);
+            }
+        }
+        if let Ok(state) = self.shared_state.lock() {
+            self.gamepad.show(ctx, &state);
+        }
+
         //  === SETTINGS WINDOW (Separate Viewport) ===
         if self.settings_open {
             let mut state = self.shared_state.lock().expect("failed to lock shared state for settings");
@@ -590,21 +1050,636 @@ impl eframe::App for SpectrumApp {
                             self.settings_open = false;
                         }
 
-                        crate::gui::widgets::show_settings_window(
+                        // Ctrl+Z: undo last settings change.
+                        // Skip while a text box has focus so it keeps its own undo.
+                        let undo_shortcut = ctx.memory(|m| m.focused().is_none())
+                            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z));
+
+                        let undo_clicked = crate::gui::widgets::show_settings_window(
                             ui,
                             &mut state,
                             &mut self.active_tab,
                             &mut self.save_target,
-                            &mut self.new_preset_name
+                            &mut self.new_preset_name,
+                            &mut self.settings_search,
+                            self.settings_history.depth(),
                         );
+
+                        if undo_shortcut || undo_clicked {
+                            let device_before = state.config.selected_device.clone();
+                            if self.settings_history.undo(&mut state.config)
+                                && state.config.selected_device != device_before
+                            {
+                                state.device_changed = true;
+                            }
+                            crate::net::set_offline(state.config.offline_mode);
+                        }
+
+                        // Only settle a change once the mouse is released
+                        let interacting = ctx.input(|i| i.pointer.any_down());
+                        self.settings_history.observe(&state.config, interacting);
                     });
                 }
             );
+        } else {
+            self.settings_history.reset_baseline();
+        }
+
+        // === EXTRA VISUALIZER WINDOWS (Separate Viewports) ===
+        self.show_extra_windows(ctx);
+
+        // === Community preset gallery fetch (off the GUI thread) ===
+        let gallery_url = {
+            let mut state = self.shared_state.lock().expect("failed to lock shared state for gallery");
+            if state.gallery_fetch_requested {
+                state.gallery_fetch_requested = false;
+                state.gallery = crate::preset_gallery::GalleryStatus::Loading;
+                Some(state.config.preset_gallery_url.clone())
+            } else {
+                None
+            }
+        };
+        if let Some(url) = gallery_url {
+            // Failures are shown inline in the gallery panel, not the banner
+            self.tasks.spawn(ctx, "Preset gallery", move || {
+                let result = match crate::preset_gallery::fetch_gallery(&url) {
+                    Ok(entries) => crate::preset_gallery::GalleryStatus::Ready(entries),
+                    Err(e) => {
+                        tracing::warn!("[Gallery] ⚠️ Fetch failed: {:#}", e);
+                        crate::preset_gallery::GalleryStatus::Failed(format!("{:#}", e))
+                    }
+                };
+                Ok(Box::new(move |state: &mut SharedState| state.gallery = result))
+            });
+        }
+
+        // === Preset thumbnails (queued by the pickers and preset saves) ===
+        let thumbnail_requests = std::mem::take(
+            &mut self.shared_state.lock().expect("failed to lock shared state for thumbnails").thumbnail_requests,
+        );
+        for request in thumbnail_requests {
+            let cache_ctx = ctx.clone();
+            self.tasks.spawn(ctx, "Preset thumbnail", move || {
+                request.write();
+                crate::gui::thumbnails::forget(&cache_ctx, request.kind, &request.name);
+                crate::gui::tasks::done()
+            });
+        }
+
+        // === Support bundle (user confirmed in the Stats tab) ===
+        let bundle_inputs = {
+            let mut state = self.shared_state.lock().expect("failed to lock shared state for support bundle");
+            if state.support_bundle == crate::support_bundle::BundleStatus::Requested {
+                state.support_bundle = crate::support_bundle::BundleStatus::Working;
+                Some(crate::support_bundle::BundleInputs {
+                    config: state.config.clone(),
+                    devices: state.audio_devices.clone(),
+                    log_dir: crate::paths::get().log_dir.clone(),
+                })
+            } else {
+                None
+            }
+        };
+        if let Some(inputs) = bundle_inputs {
+            self.tasks.spawn(ctx, "Support bundle", move || {
+                let result = crate::support_bundle::create(inputs);
+                if let Ok(path) = &result {
+                    if let Some(dir) = path.parent() {
+                        let _ = open::that(dir);
+                    }
+                }
+                Ok(Box::new(move |state: &mut SharedState| match result {
+                    Ok(path) => state.support_bundle = crate::support_bundle::BundleStatus::Done(path),
+                    Err(e) => {
+                        state.support_bundle = crate::support_bundle::BundleStatus::Idle;
+                        state.report_error(e);
+                    }
+                }))
+            });
+        }
+
+        // === Stress test (Stats tab button) ===
+        let stress_inputs = {
+            let mut state = self.shared_state.lock().expect("failed to lock shared state for stress test");
+            if state.stress_test == crate::stress_test::StressStatus::Requested {
+                state.stress_test = crate::stress_test::StressStatus::Working;
+                Some((state.config.clone(), state.user_color_presets.clone()))
+            } else {
+                None
+            }
+        };
+        if let Some((config, presets)) = stress_inputs {
+            self.tasks.spawn(ctx, "Stress test", move || {
+                let report = crate::stress_test::run(config, presets);
+                Ok(Box::new(move |state: &mut SharedState| {
+                    state.stress_test = crate::stress_test::StressStatus::Done(report)
+                }))
+            });
+        }
+
+        // Collect finished background jobs
+        if let Ok(mut state) = self.shared_state.lock() {
+            self.tasks.poll(&mut state);
+            self.background_image.sync(ctx, &self.tasks, &mut state);
+            self.fetch_lyrics(ctx, &mut state);
+        }
+
+        // Auto sleep dimming goes over everything else
+        if asleep && self.shared_state.lock().map_or(false, |s| s.config.auto_sleep_dim) {
+            ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("auto_sleep_dim")))
+                .rect_filled(ctx.screen_rect(), 0.0, egui::Color32::from_black_alpha(AUTO_SLEEP_DIM_ALPHA));
+        }
+
+        // Frame rate cap: the next frame is due one interval after this one
+        // started, so the time spent drawing this one doesn't stretch it
+        if let Some(interval) = capped_interval {
+            ctx.request_repaint_after(interval.saturating_sub(now.elapsed()));
         }
     }
 }
 
 impl SpectrumApp {
+    /// "BeSpec closed unexpectedly" notice on the first launch after a crash
+    fn show_last_crash(&mut self, ctx: &egui::Context) {
+        let Some(report) = &self.last_crash else { return };
+        let mut close = false;
+        egui::Window::new("BeSpec closed unexpectedly")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label("A crash report was saved the last time BeSpec stopped:");
+                ui.label(egui::RichText::new(report.display().to_string()).small().weak());
+                ui.add_space(4.0);
+                ui.label(egui::RichText::new(
+                    "Attaching it to a GitHub issue helps get the crash fixed. Nothing is sent anywhere.",
+                ).small().weak());
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("📄 Open Report").clicked() {
+                        let _ = open::that(report);
+                        close = true;
+                    }
+                    if ui.button("📂 Open Folder").clicked() {
+                        let _ = open::that(&crate::paths::get().log_dir);
+                        close = true;
+                    }
+                    if ui.button("Dismiss").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        if close {
+            self.last_crash = None;
+        }
+    }
+
+    /// Switch between the full layout and the mini strip, moving the window
+    /// to the geometry the other layout last had.
+    fn toggle_mini_mode(&mut self, ctx: &egui::Context) {
+        let Ok(mut state) = self.shared_state.lock() else { return };
+        state.config.mini_mode = !state.config.mini_mode;
+        let (size, pos) = state.config.active_geometry();
+        tracing::info!("[GUI] Mini mode {}", if state.config.mini_mode { "on" } else { "off" });
+
+        let size = egui::vec2(size[0], size[1]);
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+        if let Some([x, y]) = pos {
+            ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(x, y)));
+        }
+
+        self.last_window_size = Some(size);
+        self.geometry_settle_until = Some(Instant::now() + MINI_MODE_SETTLE);
+    }
+
+    /// Switch to the color preset scheduled for this time of day, once per slot
+    fn apply_theme_schedule(&mut self, ctx: &egui::Context, state: &mut SharedState) {
+        let minute = crate::theme_schedule::local_minute_of_day();
+        let scheduled = state.config.theme_schedule.preset_at(minute).map(str::to_string);
+        if scheduled.is_some() {
+            // Keep checking even while nothing else asks for a frame
+            ctx.request_repaint_after(THEME_SCHEDULE_CHECK);
+        }
+        if scheduled == self.scheduled_preset {
+            return;
+        }
+        if let Some(name) = &scheduled {
+            tracing::info!("[GUI] Scheduled color preset: {}", name);
+            state.config.profile.color_link = ColorRef::Preset(name.clone());
+            state.config.profile.background = None;
+        }
+        self.scheduled_preset = scheduled;
+    }
+
+    /// Apply controller presses. The input thread starts the first time
+    /// gamepad input is on; while it's off presses are dropped.
+    fn poll_gamepad(&mut self, ctx: &egui::Context, state: &mut SharedState) {
+        if state.config.gamepad_enabled && self.gamepad_rx.is_none() {
+            self.gamepad_rx = Some(crate::gamepad::start(ctx.clone()));
+        }
+        let Some(rx) = &self.gamepad_rx else { return };
+        while let Ok(button) = rx.try_recv() {
+            if state.config.gamepad_enabled {
+                self.gamepad.handle(button, state);
+            }
+        }
+    }
+
+    /// Hide the window on the peek-away hotkey and bring it back when its
+    /// timer runs out or the next track starts
+    fn poll_peek(&mut self, ctx: &egui::Context, state: &SharedState) {
+        self.peek.sync(ctx, state.config.peek);
+        if !state.config.peek.enabled && !self.peek.is_away() {
+            return;
+        }
+        let track = state.media_info.as_ref()
+            .filter(|info| !info.title.is_empty())
+            .map(|info| format!("{} - {}", info.artist, info.title));
+        let minimized = ctx.input(|i| i.viewport().minimized).unwrap_or(false);
+        let now = Instant::now();
+        match self.peek.poll(state.config.peek, track, minimized, now) {
+            Some(crate::peek_away::PeekAction::Minimize) => ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true)),
+            Some(crate::peek_away::PeekAction::Restore) => ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false)),
+            None => {}
+        }
+        if let Some(left) = self.peek.remaining(now) {
+            ctx.request_repaint_after(left);
+        }
+    }
+
+    /// Start, stop or move the HTTP endpoint to match its settings
+    fn sync_http_api(&mut self, ctx: &egui::Context, settings: crate::http_api::HttpApiSettings) {
+        let wanted = settings.enabled.then_some(settings.port);
+        if self.http_server.as_ref().map(|(port, _)| *port) == wanted {
+            return;
+        }
+        // Stop the old server before binding again
+        self.http_server = None;
+        if let Some(port) = wanted {
+            let server = crate::http_api::start(port, self.shared_state.clone(), ctx.clone());
+            self.http_server = Some((port, server));
+        }
+    }
+
+    /// Same for the Stream Deck socket
+    fn sync_stream_deck(&mut self, ctx: &egui::Context, settings: crate::stream_deck::StreamDeckSettings) {
+        let wanted = settings.enabled.then_some(settings.port);
+        if self.deck_server.as_ref().map(|(port, _)| *port) == wanted {
+            return;
+        }
+        self.deck_server = None;
+        if let Some(port) = wanted {
+            let server = crate::stream_deck::start(port, self.shared_state.clone(), ctx.clone());
+            self.deck_server = Some((port, server));
+        }
+    }
+
+    /// Play pink noise while a measurement wants it, restarting when the
+    /// output device changes
+    fn sync_noise_output(&mut self, state: &SharedState) {
+        let settings = &state.config.measurement;
+        let wanted = (state.measurement.running && settings.play_noise).then_some(&settings.output_device);
+        if self.noise_player.as_ref().map(|(device, _)| device) != wanted {
+            self.noise_player = wanted.map(|device| (device.clone(), crate::audio_output::start(device, settings.level_db, crate::audio_output::pink_noise())));
+        }
+        if let Some((_, player)) = &self.noise_player {
+            player.set_level(settings.level_db);
+        }
+    }
+
+    /// Play clicks while the latency self-test runs; the FFT thread ends the
+    /// test, which stops them here. Ends it here when the output fails or it
+    /// runs too long.
+    fn sync_latency_test(&mut self, state: &mut SharedState) {
+        let test = &mut state.latency_test;
+        if let Some(error) = self.click_player.as_ref().filter(|_| test.running).and_then(|p| p.error()) {
+            test.fail(error);
+        }
+        test.check_timeout(Instant::now());
+        if test.running != self.click_player.is_some() {
+            self.click_player = test.click_train().map(|clicks| {
+                crate::audio_output::start(&test.output_device, crate::latency_test::CLICK_LEVEL_DB, clicks)
+            });
+        }
+    }
+
+    /// Look up synced lyrics for a track that hasn't had a lookup yet.
+    /// A failed lookup just means no lyrics, it doesn't raise the error banner.
+    fn fetch_lyrics(&self, ctx: &egui::Context, state: &mut SharedState) {
+        if !state.config.lyrics.enabled || crate::net::is_offline() {
+            return;
+        }
+        let Some(track) = state.lyrics.start_fetch() else { return };
+        self.tasks.spawn(ctx, "Lyrics lookup", move || {
+            let lines = crate::lyrics::fetch(&track.0, &track.1).unwrap_or_else(|e| {
+                tracing::warn!("[Lyrics] Lookup failed: {}", e);
+                Vec::new()
+            });
+            Ok(Box::new(move |state: &mut SharedState| state.lyrics.finish_fetch(&track, lines)))
+        });
+    }
+
+    /// Draw every extra visualizer window. Each one gets its own viewport and
+    /// draws the data the FFT thread computed with that window's profile.
+    /// Closing one with the OS "X" button removes it from the config.
+    fn show_extra_windows(&mut self, ctx: &egui::Context) {
+        let windows = match self.shared_state.lock() {
+            Ok(state) => state.config.extra_windows.clone(),
+            Err(_) => return,
+        };
+        let mut closed = Vec::new();
+
+        for win in windows {
+            let mut builder = egui::ViewportBuilder::default()
+                .with_title(format!("BeSpec — {}", win.profile.name))
+                .with_inner_size(win.size);
+            if let Some(pos) = win.position {
+                builder = builder.with_position(pos);
+            }
+
+            ctx.show_viewport_immediate(
+                egui::ViewportId::from_hash_of(("extra_window", win.id)),
+                builder,
+                |ctx, _class| {
+                    if ctx.input(|i| i.viewport().close_requested()) {
+                        closed.push(win.id);
+                        return;
+                    }
+
+                    let Ok(mut state) = self.shared_state.lock() else { return };
+                    let colors = win.profile.resolve_colors(&state.user_color_presets);
+                    // Never the main window's mini mode
+                    let settings = viz::DrawSettings { mini_mode: false, ..viz::DrawSettings::new(&state.config, &win.profile) };
+                    let frame = egui::Frame::none().fill(to_egui_color(colors.background));
+
+                    egui::CentralPanel::default().frame(frame).show(ctx, |ui| {
+                        let rect = ui.available_rect_before_wrap();
+                        let Some(data) = state.extra_visualization.get(&win.id) else { return };
+
+                        let zoom = win.profile.content_zoom.max(1.0);
+                        let limiting_dimension = match win.profile.orientation {
+                            crate::shared_state::Orientation::BottomUp | crate::shared_state::Orientation::TopDown => rect.width() / zoom,
+                            crate::shared_state::Orientation::LeftRight | crate::shared_state::Orientation::RightLeft => rect.height() / zoom,
+                        };
+                        let min_slot_width = (win.profile.bar_gap_px as f32).max(1.0) + 2.0;
+                        let max_phys_bars = (limiting_dimension / min_slot_width).floor() as usize;
+
+                        viz::draw_zoomed(ui.painter(), rect, zoom, ui.input(|i| i.pointer.hover_pos()), |painter, rect, mouse_pos| {
+                            viz::draw_main_visualizer(
+                                painter,
+                                rect,
+                                &settings,
+                                &win.profile,
+                                &colors,
+                                data,
+                                &state.performance,
+                                mouse_pos,
+                                win.profile.num_bars.min(max_phys_bars).max(1),
+                                None,
+                                None,
+                            );
+                        });
+                    });
+
+                    // Remember where the user put it (position is unreliable on Wayland)
+                    let size = ctx.screen_rect().size();
+                    let pos = ctx.input(|i| i.viewport().outer_rect).map(|r| [r.min.x, r.min.y]);
+                    let save_pos = {
+                        #[cfg(target_os = "linux")]
+                        { !crate::shared_state::is_wayland() }
+                        #[cfg(not(target_os = "linux"))]
+                        { true }
+                    };
+                    if let Some(saved) = state.config.extra_windows.iter_mut().find(|w| w.id == win.id) {
+                        saved.size = [size.x, size.y];
+                        if save_pos && pos.is_some() {
+                            saved.position = pos;
+                        }
+                    }
+                },
+            );
+        }
+
+        if !closed.is_empty() {
+            if let Ok(mut state) = self.shared_state.lock() {
+                state.config.extra_windows.retain(|w| !closed.contains(&w.id));
+                tracing::info!("[GUI] Closed {} extra window(s)", closed.len());
+            }
+        }
+    }
+
+    /// Apply the wallpaper setting when it changes: cover the monitor and move
+    /// behind the desktop icons, or come back as a normal window. Any dock is
+    /// released first, the two don't mix.
+    fn update_wallpaper(&mut self, ctx: &egui::Context, frame: &eframe::Frame) {
+        let (wanted, floating) = match self.shared_state.lock() {
+            Ok(state) => (state.config.wallpaper_mode, state.config.active_geometry()),
+            Err(_) => return,
+        };
+        if wanted == self.wallpaper {
+            return;
+        }
+        let Ok(handle) = frame.window_handle().map(|h| h.as_raw()) else { return };
+
+        if !wanted {
+            crate::window_integration::detach_from_desktop(handle);
+            ctx.send_viewport_cmd(egui::ViewportCommand::MousePassthrough(false));
+            let (size, pos) = floating;
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(size[0], size[1])));
+            if let Some([x, y]) = pos {
+                ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(x, y)));
+            }
+            self.wallpaper = false;
+            self.last_passthrough_state = false;
+            self.geometry_settle_until = Some(Instant::now() + MINI_MODE_SETTLE);
+            tracing::info!("[GUI] Left wallpaper mode");
+            return;
+        }
+
+        let Some(monitor_size) = ctx.input(|i| i.viewport().monitor_size) else { return };
+        if self.docked.take().is_some() {
+            crate::window_integration::release(handle);
+            self.dock_window = None;
+        }
+
+        if let Err(e) = crate::window_integration::attach_to_desktop(handle) {
+            tracing::warn!("[GUI] Wallpaper mode unavailable: {}", e);
+            if let Ok(mut state) = self.shared_state.lock() {
+                state.config.wallpaper_mode = false;
+                state.report_error(crate::error::BeSpecError::Task {
+                    task: "Wallpaper mode".to_string(),
+                    reason: e,
+                });
+            }
+            return;
+        }
+
+        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(0.0, 0.0)));
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(monitor_size));
+        ctx.send_viewport_cmd(egui::ViewportCommand::MousePassthrough(true));
+        self.last_passthrough_state = true;
+        self.wallpaper = true;
+        tracing::info!("[GUI] Wallpaper mode on ({:.0}x{:.0})", monitor_size.x, monitor_size.y);
+    }
+
+    /// Snap the window to nearby screen edges once a drag has finished.
+    /// The OS runs the drag itself (`StartDrag`), so we can't pull the window
+    /// while it's moving; instead it settles onto the edge when let go.
+    fn handle_window_drag(&mut self, ctx: &egui::Context) {
+        let Some(moved_at) = self.window_dragged_at else { return };
+        if moved_at.elapsed() < DRAG_SNAP_SETTLE {
+            ctx.request_repaint_after(DRAG_SNAP_SETTLE);
+            return;
+        }
+        self.window_dragged_at = None;
+
+        #[cfg(target_os = "linux")]
+        if crate::shared_state::is_wayland() {
+            return;
+        }
+
+        let (enabled, threshold) = match self.shared_state.lock() {
+            Ok(state) => (state.config.snap_to_edges, state.config.snap_threshold),
+            Err(_) => return,
+        };
+        let is_maximized = ctx.input(|i| i.viewport().maximized.unwrap_or(false));
+        if !enabled || is_maximized {
+            return;
+        }
+
+        let Some(rect) = ctx.input(|i| i.viewport().outer_rect) else { return };
+        // Snap in physical pixels: the window's rect goes back through the
+        // scale egui took it out with, each monitor keeps its own scale
+        let ppp = ctx.pixels_per_point();
+        let window = egui::Rect::from_min_max(rect.min * ppp, rect.max * ppp);
+        let mut monitors: Vec<(egui::Rect, f32)> = crate::window_integration::monitors()
+            .iter()
+            .map(|m| {
+                let scale = crate::window_integration::monitor_scale(m).unwrap_or(ppp);
+                (egui::Rect::from_min_size(egui::pos2(m.x as f32, m.y as f32), egui::vec2(m.width as f32, m.height as f32)), scale)
+            })
+            .collect();
+        // Where the OS can't list them, the current monitor is taken to be the primary one
+        if monitors.is_empty() {
+            let Some(monitor_size) = ctx.input(|i| i.viewport().monitor_size) else { return };
+            monitors.push((egui::Rect::from_min_size(egui::Pos2::ZERO, monitor_size * ppp), ppp));
+        }
+        let snapped = widgets::snap_window_position(window, &monitors, threshold);
+        // Snapping again after our own move lands on the same spot, so this settles
+        if snapped != window.min {
+            let snapped = snapped / ppp;
+            tracing::debug!("[GUI] Snapping window to x: {:.0}, y: {:.0}", snapped.x, snapped.y);
+            ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(snapped));
+        }
+    }
+
+    /// Apply the dock setting when it changes: reserve the screen strip with
+    /// the OS and move the window into it, or give it back and restore the
+    /// floating geometry.
+    fn update_dock(&mut self, ctx: &egui::Context, frame: &eframe::Frame) {
+        let (edge, thickness, floating) = match self.shared_state.lock() {
+            Ok(state) => (state.config.dock_edge, state.config.dock_thickness, state.config.active_geometry()),
+            Err(_) => return,
+        };
+        let wanted = (edge != DockEdge::Off).then_some((edge, thickness));
+        if wanted == self.docked {
+            return;
+        }
+        let Ok(handle) = frame.window_handle().map(|h| h.as_raw()) else { return };
+
+        let Some((edge, thickness)) = wanted else {
+            crate::window_integration::release(handle);
+            let (size, pos) = floating;
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(size[0], size[1])));
+            if let Some([x, y]) = pos {
+                ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(x, y)));
+            }
+            self.docked = None;
+            self.dock_window = None;
+            self.geometry_settle_until = Some(Instant::now() + MINI_MODE_SETTLE);
+            tracing::info!("[GUI] Undocked");
+            return;
+        };
+
+        // egui only knows the monitor size, so assume it starts at the origin
+        let Some(monitor_size) = ctx.input(|i| i.viewport().monitor_size) else { return };
+        let ppp = ctx.pixels_per_point();
+        let monitor = crate::window_integration::ScreenRect {
+            x: 0,
+            y: 0,
+            width: (monitor_size.x * ppp).round() as i32,
+            height: (monitor_size.y * ppp).round() as i32,
+        };
+        let thickness_px = (thickness * ppp).round() as i32;
+
+        let rect = crate::window_integration::reserve(handle, edge, monitor, thickness_px).unwrap_or_else(|e| {
+            tracing::warn!("[GUI] Can't reserve screen space ({}), only moving the window", e);
+            monitor.edge_strip(edge, thickness_px)
+        });
+
+        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(rect.x as f32 / ppp, rect.y as f32 / ppp)));
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(rect.width as f32 / ppp, rect.height as f32 / ppp)));
+        self.docked = wanted;
+        self.dock_window = Some(handle);
+        tracing::info!("[GUI] Docked to the {} edge ({} pt)", edge.label().to_lowercase(), thickness);
+    }
+
+    /// Decide whether the GUI should idle this frame.
+    /// Never sleeps while the settings window is open or the sonar ping is running.
+    fn update_sleep(&mut self, ctx: &egui::Context, now: Instant) -> bool {
+        let Ok(mut state) = self.shared_state.lock() else { return false };
+
+        if state.sleep.repaint_ctx.is_none() {
+            state.sleep.repaint_ctx = Some(ctx.clone());
+        }
+
+        let after = Duration::from_secs_f32(state.config.auto_sleep_after_sec.max(1.0));
+        let asleep = state.config.auto_sleep_enabled
+            && !self.settings_open
+            && self.flash_start.is_none()
+            && state.sleep.silent_for(after, self.launched_at, now);
+
+        if asleep != state.sleep.asleep {
+            tracing::debug!("[GUI] Auto sleep {}", if asleep { "on" } else { "off" });
+            state.sleep.asleep = asleep;
+        }
+        asleep
+    }
+
+    /// Decide whether the window is hidden (minimized, or covered by other
+    /// windows) and can stop drawing. Never while it has focus, while extra
+    /// windows are open (they're drawn from this loop) or as a wallpaper.
+    fn update_hidden(&mut self, ctx: &egui::Context, frame: &eframe::Frame, now: Instant) -> bool {
+        let minimized = ctx.input(|i| i.viewport().minimized.unwrap_or(false));
+        let throttle = match self.shared_state.lock() {
+            Ok(state) => state.config.hidden_throttle && state.config.extra_windows.is_empty(),
+            Err(_) => return false,
+        };
+        let may_hide = throttle && !self.wallpaper && !ctx.input(|i| i.focused);
+
+        // Not under the state lock: the occlusion check is a round trip to the OS
+        let hidden = may_hide && (minimized || {
+            // The occlusion check asks the OS about other windows, so at most once per interval
+            if self.occlusion.map_or(true, |(at, _)| now.duration_since(at) >= HIDDEN_CHECK_INTERVAL) {
+                let occluded = frame
+                    .window_handle()
+                    .map(|h| crate::window_integration::is_occluded(h.as_raw()))
+                    .unwrap_or(false);
+                self.occlusion = Some((now, occluded));
+            }
+            self.occlusion.is_some_and(|(_, occluded)| occluded)
+        });
+        if !may_hide {
+            self.occlusion = None;
+        }
+
+        let Ok(mut state) = self.shared_state.lock() else { return false };
+        if hidden != state.sleep.hidden {
+            tracing::debug!("[GUI] Window {}", if hidden { "hidden, repaints paused" } else { "visible again" });
+            state.sleep.hidden = hidden;
+        }
+        hidden
+    }
 
     /// Logic to determine if the media overlay should be visible
     /// Updates 'last_media_interaction' if the user hovers the mouse
@@ -0,0 +1,168 @@
+// src/gui/opacity.rs
+//
+// Transparency tweaks without opening settings:
+//   - [ / ]                  - background opacity -/+ 5%
+//   - Shift + [ / ]          - bar opacity -/+ 5%
+//   - Ctrl + wheel           - background opacity, over the main window
+//   - Ctrl + Shift + wheel   - bar opacity
+//
+// Each change pops up a small readout at the top of the window for a moment.
+// In Spectrogram mode Ctrl + wheel stays with the spectrogram's time zoom.
+
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+
+use crate::shared_state::{SharedState, VisualMode};
+
+/// Opacity change per key press or wheel notch
+const STEP: f32 = 0.05;
+
+/// Scroll distance of one wheel notch (egui reports a line as 50 points)
+const NOTCH: f32 = 50.0;
+
+/// How long the readout stays up after the last change
+const OSD_TIME: Duration = Duration::from_millis(1200);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Layer {
+    Background,
+    Bars,
+}
+
+impl Layer {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Layer::Background => "Background",
+            Layer::Bars => "Bars",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct OpacityNudge {
+    /// Last change and when it happened, for the readout
+    shown: Option<(Layer, f32, Instant)>,
+    /// Scrolled distance not yet worth a whole step (trackpads)
+    wheel: f32,
+}
+
+impl OpacityNudge {
+    /// Apply this frame's keys and Ctrl + wheel
+    pub fn handle(&mut self, ctx: &egui::Context, state: &mut SharedState) {
+        let mut steps = [0; 2];
+
+        if !ctx.wants_keyboard_input() {
+            // Shift is ignored by `consume_key` unless asked for, so read it first
+            let shift = ctx.input(|i| i.modifiers.shift);
+            let layer = if shift { Layer::Bars } else { Layer::Background };
+            let pressed = |key: egui::Key| ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, key));
+            if pressed(egui::Key::OpenBracket) {
+                steps[layer as usize] -= 1;
+            }
+            if pressed(egui::Key::CloseBracket) {
+                steps[layer as usize] += 1;
+            }
+        }
+
+        let spectrogram = state.config.profile.shown_modes().any(|m| m == VisualMode::Spectrogram);
+        let (ctrl, shift, hovered, delta) = ctx.input(|i| (
+            i.modifiers.ctrl,
+            i.modifiers.shift,
+            i.pointer.hover_pos().is_some(),
+            // Shift turns the wheel sideways, so take both axes
+            i.raw_scroll_delta.x + i.raw_scroll_delta.y,
+        ));
+        if ctrl && hovered && !spectrogram && delta != 0.0 {
+            let layer = if shift { Layer::Bars } else { Layer::Background };
+            steps[layer as usize] += wheel_steps(&mut self.wheel, delta);
+        } else if !ctrl {
+            self.wheel = 0.0;
+        }
+
+        for layer in [Layer::Background, Layer::Bars] {
+            if steps[layer as usize] != 0 {
+                let value = nudge(state, layer, steps[layer as usize]);
+                tracing::debug!("[GUI] {} opacity: {:.0}%", layer.label(), value * 100.0);
+                self.shown = Some((layer, value, Instant::now()));
+            }
+        }
+    }
+
+    /// The readout, anchored to the top of the main window
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let Some((layer, value, since)) = self.shown else { return };
+        let age = since.elapsed();
+        if age >= OSD_TIME {
+            self.shown = None;
+            return;
+        }
+        ctx.request_repaint_after(OSD_TIME - age);
+
+        egui::Area::new(egui::Id::new("opacity_osd"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 16.0))
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} Opacity", layer.label()));
+                        ui.add(egui::ProgressBar::new(value).desired_width(120.0).text(format!("{:.0}%", value * 100.0)));
+                    });
+                });
+            });
+    }
+}
+
+/// Move `layer`'s opacity `steps` steps, landing on the 5% grid. Returns
+/// the new value.
+fn nudge(state: &mut SharedState, layer: Layer, steps: i32) -> f32 {
+    match layer {
+        Layer::Background => {
+            let alpha = state.config.resolve_colors(&state.user_color_presets).background.a as f32 / 255.0;
+            let alpha = stepped(alpha, steps);
+            state.config.profile.set_background_alpha(&state.user_color_presets, alpha);
+            alpha
+        }
+        Layer::Bars => {
+            state.config.profile.bar_opacity = stepped(state.config.profile.bar_opacity, steps);
+            state.config.profile.bar_opacity
+        }
+    }
+}
+
+fn stepped(value: f32, steps: i32) -> f32 {
+    ((value / STEP).round() + steps as f32).clamp(0.0, 1.0 / STEP) * STEP
+}
+
+/// Whole notches in the scrolled distance so far, keeping the remainder
+fn wheel_steps(acc: &mut f32, delta: f32) -> i32 {
+    *acc += delta;
+    let steps = (*acc / NOTCH).trunc();
+    *acc -= steps * NOTCH;
+    steps as i32
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_snap_and_clamp() {
+        assert!((stepped(0.5, 1) - 0.55).abs() < 1e-6);
+        assert!((stepped(0.62, -1) - 0.55).abs() < 1e-6);
+        assert_eq!(stepped(0.98, 3), 1.0);
+        assert_eq!(stepped(0.02, -2), 0.0);
+    }
+
+    #[test]
+    fn test_wheel_adds_up_small_scrolls() {
+        let mut acc = 0.0;
+        assert_eq!(wheel_steps(&mut acc, 50.0), 1);
+        assert_eq!(wheel_steps(&mut acc, 20.0), 0);
+        assert_eq!(wheel_steps(&mut acc, 35.0), 1);
+        assert_eq!(wheel_steps(&mut acc, -110.0), -2);
+    }
+}
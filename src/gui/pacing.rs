@@ -0,0 +1,102 @@
+// src/gui/pacing.rs
+//
+// Repaint pacing: how long a frame should take, and counting the frames
+// that came later than that.
+//
+// With the frame rate cap on "Display Refresh" the GUI asks for a new frame
+// the moment the last one is done and vsync paces it to the monitor (144
+// updates a second on a 144 Hz panel). A timer can't do that job: it fires
+// between vsyncs, and the frame then waits for the one after, halving the
+// rate. The monitor's rate is only used to tell a dropped frame from an
+// on-time one. It's asked of the OS every few seconds, as the window can
+// move to another monitor; where the OS won't say, 60 Hz is assumed.
+
+use std::time::{Duration, Instant};
+
+/// Assumed refresh rate when the OS doesn't report one
+const FALLBACK_HZ: f32 = 60.0;
+
+/// How often the monitor's refresh rate is asked for again
+const REFRESH_RATE_CHECK: Duration = Duration::from_secs(5);
+
+/// A frame taking this many intervals (or more) counts as dropped
+const DROPPED_FACTOR: f32 = 1.5;
+
+#[derive(Default)]
+pub struct FramePacer {
+    /// Refresh rate of the window's monitor, and when it was asked for
+    display_hz: Option<f32>,
+    checked_at: Option<Instant>,
+    /// Interval the last frame was paced to, None when it wasn't paced
+    /// (asleep, hidden), so a slow frame there isn't a dropped one
+    expected: Option<Duration>,
+}
+
+impl FramePacer {
+    /// Re-read the refresh rate through `query` when the last check is old
+    pub fn refresh(&mut self, now: Instant, query: impl FnOnce() -> Option<f32>) {
+        if self.checked_at.is_some_and(|at| now.duration_since(at) < REFRESH_RATE_CHECK) {
+            return;
+        }
+        let hz = query().filter(|hz| hz.is_finite() && *hz >= 1.0);
+        if hz != self.display_hz {
+            tracing::info!("[GUI] Display refresh rate: {}", hz.map_or("unknown".to_string(), |hz| format!("{:.2} Hz", hz)));
+        }
+        self.display_hz = hz;
+        self.checked_at = Some(now);
+    }
+
+    pub fn display_hz(&self) -> Option<f32> {
+        self.display_hz
+    }
+
+    /// Frame interval for a frame rate cap, `None` meaning the display's rate
+    pub fn interval(&self, cap: Option<u32>) -> Duration {
+        let hz = cap.map(|fps| fps as f32).or(self.display_hz).unwrap_or(FALLBACK_HZ);
+        Duration::from_secs_f32(1.0 / hz.max(1.0))
+    }
+
+    /// The frame that follows is paced to `interval` (None: not paced)
+    pub fn expect(&mut self, interval: Option<Duration>) {
+        self.expected = interval;
+    }
+
+    /// A frame came `frame_time` after the one before. True when it's late
+    /// enough that the display showed an old frame at least once.
+    pub fn note_frame(&self, frame_time: Duration) -> bool {
+        self.expected.is_some_and(|interval| frame_time > interval.mul_f32(DROPPED_FACTOR))
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval_follows_cap_then_display() {
+        let mut pacer = FramePacer::default();
+        assert_eq!(pacer.interval(None), Duration::from_secs_f32(1.0 / 60.0));
+
+        pacer.refresh(Instant::now(), || Some(144.0));
+        assert_eq!(pacer.display_hz(), Some(144.0));
+        assert_eq!(pacer.interval(None), Duration::from_secs_f32(1.0 / 144.0));
+        assert_eq!(pacer.interval(Some(30)), Duration::from_secs_f32(1.0 / 30.0));
+
+        // Not asked again until the check is due
+        pacer.refresh(Instant::now(), || panic!("queried too soon"));
+    }
+
+    #[test]
+    fn test_dropped_frames_only_when_paced() {
+        let mut pacer = FramePacer::default();
+        let interval = pacer.interval(None);
+        let late = interval * 2;
+        assert!(!pacer.note_frame(late));
+
+        pacer.expect(Some(interval));
+        assert!(!pacer.note_frame(interval));
+        assert!(pacer.note_frame(late));
+    }
+}
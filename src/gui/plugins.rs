@@ -0,0 +1,353 @@
+//! Native visualizer plugins: dynamic libraries from the `plugins/` folder,
+//! loaded once at startup and listed in the Mode combo next to the built-in
+//! modes. The ABI they implement is in `crate::plugin_api`.
+//!
+//! Libraries are never unloaded. A plugin instance may still be alive in a
+//! window's temp data when it would be, and reloading native code mid-run
+//! isn't worth the risk; dropping a new file in the folder takes a restart.
+//!
+//! Like scripts, each window keeps its plugin instance in egui's temp data,
+//! created the first time the mode is drawn and replaced when the profile
+//! switches to a different plugin.
+//!
+//! Only built with the `plugins` feature.
+
+use std::ffi::{c_void, CStr};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+
+use eframe::egui;
+
+use crate::gui::theme::{db_to_px, to_egui_color};
+use crate::plugin_api::{PaintHost, PluginInfo, PluginVTable, API_VERSION, ENTRY_SYMBOL};
+use crate::shared_state::{ColorProfile, VisualizationData};
+
+/// Shapes a plugin may draw per frame; the rest are dropped
+const MAX_SHAPES: usize = 20_000;
+
+/// Where plugins are loaded from
+pub fn plugins_dir() -> PathBuf {
+    crate::paths::get().data_dir.join("plugins")
+}
+
+/// A loaded plugin library
+pub struct Plugin {
+    pub name: String,
+    pub file: PathBuf,
+    vtable: &'static PluginVTable,
+}
+
+static PLUGINS: OnceLock<Vec<Plugin>> = OnceLock::new();
+
+/// Load the plugins folder. Called once at startup so problems show up in
+/// the log straight away rather than the first time the Mode combo opens.
+pub fn init() {
+    let plugins = all();
+    if !plugins.is_empty() {
+        tracing::info!("[Plugins] Loaded {} plugin(s) from {:?}", plugins.len(), plugins_dir());
+    }
+}
+
+/// Every plugin that loaded, sorted by file name
+pub fn all() -> &'static [Plugin] {
+    PLUGINS.get_or_init(|| load_dir(&plugins_dir()))
+}
+
+fn load_dir(dir: &Path) -> Vec<Plugin> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == std::env::consts::DLL_EXTENSION))
+        .collect();
+    files.sort();
+
+    let mut plugins: Vec<Plugin> = Vec::new();
+    for file in files {
+        match load(&file) {
+            Ok(plugin) if plugins.iter().any(|p| p.name == plugin.name) => {
+                tracing::warn!("[Plugins] Skipping {:?}: another plugin is already called '{}'", file, plugin.name);
+            }
+            Ok(plugin) => {
+                tracing::info!("[Plugins] {} ({:?})", plugin.name, file);
+                plugins.push(plugin);
+            }
+            Err(e) => tracing::warn!("[Plugins] Skipping {:?}: {}", file, e),
+        }
+    }
+    plugins
+}
+
+fn load(file: &Path) -> Result<Plugin, String> {
+    // SAFETY: loading runs the library's initializers. Plugins are native
+    // code the user chose to install, the same trust as running BeSpec itself.
+    let library = unsafe { libloading::Library::new(file) }.map_err(|e| e.to_string())?;
+    let vtable = unsafe {
+        let entry = library
+            .get::<extern "C" fn() -> *const PluginVTable>(ENTRY_SYMBOL)
+            .map_err(|_| "not a BeSpec plugin (no bespec_plugin_v1 export)".to_string())?;
+        entry()
+    };
+    // SAFETY: the library is leaked below, so the vtable stays valid for the
+    // rest of the process
+    let vtable: &'static PluginVTable = unsafe { vtable.as_ref() }.ok_or("the plugin returned no vtable")?;
+    let name = check(vtable)?;
+    std::mem::forget(library);
+    Ok(Plugin { name, file: file.to_path_buf(), vtable })
+}
+
+/// The plugin's name, if its vtable is one this build understands
+fn check(vtable: &PluginVTable) -> Result<String, String> {
+    if vtable.api_version != API_VERSION {
+        return Err(format!("built for plugin API v{}, this BeSpec has v{}", vtable.api_version, API_VERSION));
+    }
+    if vtable.name.is_null() {
+        return Err("the plugin has no name".to_string());
+    }
+    let name = unsafe { CStr::from_ptr(vtable.name) }.to_string_lossy().trim().to_string();
+    if name.is_empty() {
+        return Err("the plugin has no name".to_string());
+    }
+    Ok(name)
+}
+
+/// One plugin instance, destroyed on drop
+struct Instance {
+    vtable: &'static PluginVTable,
+    ptr: *mut c_void,
+    last: Option<Instant>,
+}
+
+// `VisualizerPlugin` requires `Send`, and the host mutex serializes calls
+unsafe impl Send for Instance {}
+
+impl Instance {
+    fn new(vtable: &'static PluginVTable, info: PluginInfo) -> Option<Self> {
+        let ptr = (vtable.create)();
+        if ptr.is_null() {
+            return None;
+        }
+        (vtable.init)(ptr, &info);
+        Some(Self { vtable, ptr, last: None })
+    }
+
+    fn process(&mut self, bars: &[f32], wave: &[f32], now: Instant) {
+        let dt = self.last.map_or(0.0, |last| now.duration_since(last).as_secs_f32());
+        self.last = Some(now);
+        (self.vtable.process)(self.ptr, bars.as_ptr(), bars.len(), wave.as_ptr(), wave.len(), dt);
+    }
+
+    fn paint(&self, rect: egui::Rect) -> Vec<egui::Shape> {
+        let mut sink = Sink { rect, shapes: Vec::new() };
+        let host = PaintHost {
+            ctx: &mut sink as *mut Sink as *mut c_void,
+            width: rect.width(),
+            height: rect.height(),
+            line: sink_line,
+            rect: sink_rect,
+            circle: sink_circle,
+        };
+        (self.vtable.paint)(self.ptr, &host);
+        sink.shapes
+    }
+}
+
+impl Drop for Instance {
+    fn drop(&mut self) {
+        (self.vtable.destroy)(self.ptr);
+    }
+}
+
+/// Where the `PaintHost` callbacks put their shapes
+struct Sink {
+    rect: egui::Rect,
+    shapes: Vec<egui::Shape>,
+}
+
+impl Sink {
+    fn pos(&self, x: f32, y: f32) -> egui::Pos2 {
+        self.rect.min + egui::vec2(x * self.rect.width(), y * self.rect.height())
+    }
+
+    fn push(&mut self, shape: egui::Shape) {
+        if self.shapes.len() < MAX_SHAPES {
+            self.shapes.push(shape);
+        }
+    }
+}
+
+fn sink<'a>(ctx: *mut c_void) -> &'a mut Sink {
+    // SAFETY: `ctx` is the `Sink` in `Instance::paint`, alive for the call
+    unsafe { &mut *(ctx as *mut Sink) }
+}
+
+fn color(rgba: u32) -> egui::Color32 {
+    let [r, g, b, a] = rgba.to_be_bytes();
+    egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
+extern "C" fn sink_line(ctx: *mut c_void, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, rgba: u32) {
+    let sink = sink(ctx);
+    let points = [sink.pos(x1, y1), sink.pos(x2, y2)];
+    sink.push(egui::Shape::line_segment(points, egui::Stroke::new(thickness.max(0.0), color(rgba))));
+}
+
+extern "C" fn sink_rect(ctx: *mut c_void, x: f32, y: f32, w: f32, h: f32, rgba: u32) {
+    let sink = sink(ctx);
+    let rect = egui::Rect::from_two_pos(sink.pos(x, y), sink.pos(x + w, y + h));
+    sink.push(egui::Shape::rect_filled(rect, 0.0, color(rgba)));
+}
+
+extern "C" fn sink_circle(ctx: *mut c_void, x: f32, y: f32, radius: f32, rgba: u32) {
+    let sink = sink(ctx);
+    let center = sink.pos(x, y);
+    let radius = radius.max(0.0) * sink.rect.height();
+    sink.push(egui::Shape::circle_filled(center, radius, color(rgba)));
+}
+
+/// The window's instance of the profile's plugin
+#[derive(Default)]
+struct PluginHost {
+    name: String,
+    instance: Option<Instance>,
+    error: Option<String>,
+}
+
+impl PluginHost {
+    fn prepare(&mut self, name: &str, sample_rate: u32) {
+        if self.name == name {
+            return;
+        }
+        self.name = name.to_string();
+        self.instance = None;
+        self.error = None;
+        let Some(plugin) = all().iter().find(|p| p.name == name) else {
+            self.error = Some("not installed".to_string());
+            return;
+        };
+        self.instance = Instance::new(plugin.vtable, PluginInfo { sample_rate });
+        if self.instance.is_none() {
+            tracing::warn!("[Plugins] {} failed to start", name);
+            self.error = Some("failed to start".to_string());
+        }
+    }
+}
+
+/// Draw the plugin mode with the plugin called `name`
+pub fn draw_plugin(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    name: &str,
+    colors: &ColorProfile,
+    data: &VisualizationData,
+    noise_floor_db: f32,
+    sample_rate: u32,
+) {
+    let id = egui::Id::new("plugin_host").with(painter.ctx().viewport_id());
+    let host = painter.ctx().data_mut(|d| d.get_temp_mut_or_default::<Arc<Mutex<PluginHost>>>(id).clone());
+    let Ok(mut host) = host.lock() else { return };
+    host.prepare(name, sample_rate);
+
+    let message = if let Some(instance) = host.instance.as_mut() {
+        let bars: Vec<f32> = data.bars.iter().map(|&db| db_to_px(db, noise_floor_db, 1.0)).collect();
+        instance.process(&bars, &data.waveform, Instant::now());
+        painter.with_clip_rect(rect).extend(instance.paint(rect));
+        None
+    } else if name.is_empty() {
+        Some(format!("Pick a plugin under Settings → Visual → Mode\n({})", plugins_dir().display()))
+    } else {
+        Some(format!("{}: {}", name, host.error.as_deref().unwrap_or_default()))
+    };
+
+    if let Some(message) = message {
+        painter.text(
+            rect.left_top() + egui::vec2(8.0, 8.0),
+            egui::Align2::LEFT_TOP,
+            message,
+            egui::FontId::monospace(12.0),
+            to_egui_color(colors.text),
+        );
+    }
+    // Plugins animate on their own time, not just when audio arrives
+    painter.ctx().request_repaint();
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin_api::{Canvas, VisualizerPlugin};
+
+    #[derive(Default)]
+    struct Bars {
+        levels: Vec<f32>,
+        sample_rate: u32,
+    }
+
+    impl VisualizerPlugin for Bars {
+        fn init(&mut self, info: &PluginInfo) {
+            self.sample_rate = info.sample_rate;
+        }
+
+        fn process(&mut self, bars: &[f32], _waveform: &[f32], _dt: f32) {
+            self.levels = bars.to_vec();
+        }
+
+        fn paint(&self, canvas: &mut Canvas) {
+            assert_eq!(self.sample_rate, 48000);
+            let n = self.levels.len() as f32;
+            for (i, level) in self.levels.iter().enumerate() {
+                canvas.rect(i as f32 / n, 1.0 - level, 1.0 / n, *level, 0xff0000ff);
+            }
+            canvas.circle(0.5, 0.5, 0.1, 0xffffff80);
+        }
+    }
+
+    static BARS: PluginVTable = PluginVTable::of::<Bars>("Bars\0");
+
+    #[test]
+    fn test_plugin_draws_through_the_vtable() {
+        assert_eq!(check(&BARS).as_deref(), Ok("Bars"));
+
+        let mut instance = Instance::new(&BARS, PluginInfo { sample_rate: 48000 }).unwrap();
+        instance.process(&[0.5, 1.0], &[], Instant::now());
+        let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(200.0, 100.0));
+        let shapes = instance.paint(rect);
+        assert_eq!(shapes.len(), 3);
+
+        let egui::Shape::Rect(bar) = &shapes[0] else { panic!("expected a rect") };
+        assert_eq!(bar.rect, egui::Rect::from_min_max(egui::pos2(0.0, 50.0), egui::pos2(100.0, 100.0)));
+        assert_eq!(bar.fill, egui::Color32::RED);
+        let egui::Shape::Circle(circle) = &shapes[2] else { panic!("expected a circle") };
+        assert_eq!(circle.radius, 10.0);
+    }
+
+    #[derive(Default)]
+    struct Panics;
+
+    impl VisualizerPlugin for Panics {
+        fn process(&mut self, _bars: &[f32], _waveform: &[f32], _dt: f32) {
+            panic!("plugin bug");
+        }
+
+        fn paint(&self, _canvas: &mut Canvas) {
+            panic!("plugin bug");
+        }
+    }
+
+    #[test]
+    fn test_plugin_panics_stay_in_the_plugin() {
+        static PANICS: PluginVTable = PluginVTable::of::<Panics>("Panics\0");
+        let mut instance = Instance::new(&PANICS, PluginInfo { sample_rate: 48000 }).unwrap();
+        instance.process(&[1.0], &[], Instant::now());
+        let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(10.0, 10.0));
+        assert!(instance.paint(rect).is_empty());
+    }
+
+    #[test]
+    fn test_plugin_from_another_api_version_is_refused() {
+        static OLD: PluginVTable = PluginVTable { api_version: API_VERSION + 1, ..PluginVTable::of::<Bars>("Old\0") };
+        assert!(check(&OLD).unwrap_err().contains("plugin API"));
+    }
+}
@@ -0,0 +1,192 @@
+// src/gui/quick_switch.rs
+//
+// Quick switcher: an on-screen carousel for flipping through visual profiles
+// and color presets without opening the settings viewport. Ctrl + the
+// configured key (Space by default) brings it up at the bottom of the main
+// window:
+//   - ← / → or the scroll wheel - previous / next entry
+//   - ↑ / ↓                     - switch between the profile and color row
+//   - Enter (or the hotkey)     - keep what's showing
+//   - Esc                       - put back what was there before
+//
+// Every step is applied to the live config right away, so the visualizer
+// behind the overlay is the preview. Left alone for a few seconds it closes
+// and keeps the current pick, like a TV's OSD.
+
+use eframe::egui;
+
+use crate::commands::{self, color_preset_names, cycle, profile_list, Command};
+use crate::shared_state::{ColorRef, SharedState, VisualProfile};
+
+/// Idle time before the switcher closes on its own (seconds)
+const IDLE_CLOSE_SECS: f64 = 4.0;
+
+/// Scroll distance that counts as one step (points, about one wheel notch)
+const SCROLL_STEP: f32 = 40.0;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Row {
+    Profiles,
+    Colors,
+}
+
+pub struct QuickSwitcher {
+    open: bool,
+    row: Row,
+    /// Profile as it was when the switcher opened, Esc restores it
+    original: Option<VisualProfile>,
+    /// `InputState::time` of the last key or scroll
+    last_input: f64,
+    /// Scroll not yet turned into a step
+    scroll: f32,
+}
+
+impl QuickSwitcher {
+    pub fn new() -> Self {
+        Self { open: false, row: Row::Profiles, original: None, last_input: 0.0, scroll: 0.0 }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Hotkey pressed: open, or keep the current pick and close
+    pub fn toggle(&mut self, ctx: &egui::Context, state: &SharedState) {
+        if self.open {
+            self.close();
+            return;
+        }
+        self.open = true;
+        self.row = Row::Profiles;
+        self.original = Some(state.config.profile.clone());
+        self.last_input = ctx.input(|i| i.time);
+        self.scroll = 0.0;
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+        self.original = None;
+    }
+
+    /// Keys and scrolling while open. They're consumed here, so the regular
+    /// shortcuts (arrows = bar count) don't also fire.
+    pub fn handle(&mut self, ctx: &egui::Context, state: &mut SharedState) {
+        if !self.open {
+            return;
+        }
+
+        let pressed = |key: egui::Key| ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, key));
+        let now = ctx.input(|i| i.time);
+
+        if pressed(egui::Key::Escape) {
+            if let Some(original) = self.original.take() {
+                state.config.profile = original;
+            }
+            self.close();
+            return;
+        }
+        if pressed(egui::Key::Enter) {
+            self.close();
+            return;
+        }
+
+        let mut step = 0;
+        if pressed(egui::Key::ArrowUp) || pressed(egui::Key::ArrowDown) {
+            self.row = match self.row {
+                Row::Profiles => Row::Colors,
+                Row::Colors => Row::Profiles,
+            };
+            self.last_input = now;
+        }
+        if pressed(egui::Key::ArrowRight) {
+            step += 1;
+        }
+        if pressed(egui::Key::ArrowLeft) {
+            step -= 1;
+        }
+
+        // Take the wheel from whatever is under the overlay (spectrogram zoom)
+        self.scroll += ctx.input_mut(|i| {
+            let delta = i.raw_scroll_delta.y + i.raw_scroll_delta.x;
+            i.raw_scroll_delta = egui::Vec2::ZERO;
+            i.smooth_scroll_delta = egui::Vec2::ZERO;
+            delta
+        });
+        while self.scroll.abs() >= SCROLL_STEP {
+            // Wheel down / away moves forward
+            step += if self.scroll < 0.0 { 1 } else { -1 };
+            self.scroll -= SCROLL_STEP.copysign(self.scroll);
+        }
+
+        if step != 0 {
+            self.step(state, step);
+            self.last_input = now;
+        }
+
+        if now - self.last_input > IDLE_CLOSE_SECS {
+            self.close();
+            return;
+        }
+        ctx.request_repaint_after(std::time::Duration::from_millis(250));
+    }
+
+    fn step(&self, state: &mut SharedState, delta: i32) {
+        let command = match (self.row, delta > 0) {
+            (Row::Profiles, true) => Command::NextProfile,
+            (Row::Profiles, false) => Command::PrevProfile,
+            (Row::Colors, true) => Command::NextColors,
+            (Row::Colors, false) => Command::PrevColors,
+        };
+        for _ in 0..delta.unsigned_abs() {
+            let _ = commands::execute(&command, state);
+        }
+        match (self.row, &state.config.profile.color_link) {
+            (Row::Profiles, _) => tracing::debug!("[GUI] Quick switch profile: {}", state.config.profile.name),
+            (Row::Colors, ColorRef::Preset(name)) => tracing::debug!("[GUI] Quick switch colors: {}", name),
+            (Row::Colors, ColorRef::Custom(_)) => {}
+        }
+    }
+
+    /// The carousel, anchored to the bottom of the main window
+    pub fn show(&self, ctx: &egui::Context, state: &SharedState) {
+        if !self.open {
+            return;
+        }
+
+        let profile_names: Vec<String> = profile_list(state).into_iter().map(|p| p.name).collect();
+        let color_names = color_preset_names(state);
+        let current_color = match &state.config.profile.color_link {
+            ColorRef::Preset(name) => name.clone(),
+            ColorRef::Custom(_) => "Custom".to_string(),
+        };
+
+        egui::Area::new(egui::Id::new("quick_switcher"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -16.0))
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    egui::Grid::new("quick_switcher_grid").num_columns(4).spacing([12.0, 6.0]).show(ui, |ui| {
+                        carousel_row(ui, "Profile", &profile_names, &state.config.profile.name, self.row == Row::Profiles);
+                        carousel_row(ui, "Colors", &color_names, &current_color, self.row == Row::Colors);
+                    });
+                    ui.add_space(2.0);
+                    ui.label(egui::RichText::new("← → or scroll  ·  ↑ ↓ row  ·  Enter keep  ·  Esc undo").small().weak());
+                });
+            });
+    }
+}
+
+/// One carousel line: label, the previous entry, the current one, the next
+fn carousel_row(ui: &mut egui::Ui, label: &str, names: &[String], current: &str, active: bool) {
+    let index = names.iter().position(|n| n == current);
+    let neighbour = |delta| cycle(names.len(), index, delta).map(|i| names[i].as_str()).unwrap_or("");
+
+    let label = egui::RichText::new(label);
+    ui.label(if active { label.strong() } else { label.weak() });
+    ui.label(egui::RichText::new(format!("◀ {}", neighbour(-1))).weak());
+    let current = egui::RichText::new(current).size(16.0);
+    ui.label(if active { current.strong().color(ui.visuals().selection.stroke.color) } else { current });
+    ui.label(egui::RichText::new(format!("{} ▶", neighbour(1))).weak());
+    ui.end_row();
+}
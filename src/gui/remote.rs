@@ -0,0 +1,150 @@
+// src/gui/remote.rs
+//
+// Applies MIDI / OSC remote control messages (see `crate::remote_control`)
+// through the bindings table in `AppConfig::remote`.
+//
+// Values are absolute, so a fader or knob maps straight onto the setting:
+//   - Sensitivity    - -20 dB at the bottom, +20 dB at the top
+//   - Color Preset   - the preset list spread across the range
+//   - Visual Mode    - the modes spread across the range
+//   - Window         - every press (value going over half) toggles minimized
+//
+// While a binding is armed for learning (`SharedState::remote_learn`) the
+// next message sets its source instead of doing anything.
+
+use eframe::egui;
+
+use crate::commands::{self, color_preset_names, Command, MODES};
+use crate::remote_control::{self, RemoteAction, RemoteEvent, RemoteInput, RemoteSettings, RemoteSource};
+use crate::shared_state::{ColorRef, SharedState};
+
+/// Sensitivity at the ends of a fader (dB)
+const SENSITIVITY_DB: f32 = 20.0;
+
+#[derive(Default)]
+pub struct RemoteBridge {
+    input: Option<RemoteInput>,
+    /// Settings the listeners were started with
+    started_for: Option<RemoteSettings>,
+    /// Last value per window toggle source, to act on presses only
+    pressed: Vec<(RemoteSource, bool)>,
+}
+
+impl RemoteBridge {
+    /// (Re)start the listeners when their settings changed, then apply
+    /// what came in
+    pub fn poll(&mut self, ctx: &egui::Context, state: &mut SharedState) {
+        let wanted = &state.config.remote;
+        let listening = wanted.midi_enabled || wanted.osc_enabled;
+        let changed = self.started_for.as_ref().map_or(true, |s| !same_listeners(s, wanted));
+        if changed {
+            // Stop the old listeners before binding the same port again
+            self.input = None;
+            self.started_for = Some(wanted.clone());
+            if listening {
+                self.input = Some(remote_control::start(wanted, ctx));
+            }
+        }
+
+        let Some(input) = &self.input else { return };
+        let events: Vec<RemoteEvent> = input.rx.try_iter().collect();
+        for event in events {
+            self.handle(ctx, state, event);
+        }
+    }
+
+    fn handle(&mut self, ctx: &egui::Context, state: &mut SharedState, event: RemoteEvent) {
+        state.remote_last = Some(event.source.label());
+
+        if let Some(i) = state.remote_learn.take() {
+            if let Some(binding) = state.config.remote.bindings.get_mut(i) {
+                tracing::info!("[Remote] Learned {} for {}", event.source.label(), binding.action.label());
+                binding.source = Some(event.source);
+            }
+            return;
+        }
+
+        let actions: Vec<RemoteAction> = state
+            .config
+            .remote
+            .bindings
+            .iter()
+            .filter(|b| b.source.as_ref() == Some(&event.source))
+            .map(|b| b.action)
+            .collect();
+        for action in actions {
+            self.apply(ctx, state, action, &event);
+        }
+    }
+
+    fn apply(&mut self, ctx: &egui::Context, state: &mut SharedState, action: RemoteAction, event: &RemoteEvent) {
+        let value = event.value;
+        match action {
+            RemoteAction::Sensitivity => {
+                let db = (value * 2.0 - 1.0) * SENSITIVITY_DB;
+                state.config.profile.sensitivity = 10f32.powf(db / 20.0);
+            }
+            RemoteAction::ColorPreset => {
+                let names = color_preset_names(state);
+                if let Some(name) = pick(&names, value) {
+                    if state.config.profile.color_link != ColorRef::Preset(name.clone()) {
+                        tracing::debug!("[Remote] Colors: {}", name);
+                        let _ = commands::execute(&Command::SetColors { name: name.clone() }, state);
+                    }
+                }
+            }
+            RemoteAction::VisualMode => {
+                if let Some(mode) = pick(&MODES, value) {
+                    state.config.profile.visual_mode = *mode;
+                }
+            }
+            RemoteAction::ToggleWindow => {
+                let down = value >= 0.5;
+                let was_down = match self.pressed.iter_mut().find(|(s, _)| *s == event.source) {
+                    Some((_, held)) => std::mem::replace(held, down),
+                    None => {
+                        self.pressed.push((event.source.clone(), down));
+                        false
+                    }
+                };
+                if down && !was_down {
+                    let minimized = ctx.input(|i| i.viewport().minimized).unwrap_or(false);
+                    tracing::debug!("[Remote] {} window", if minimized { "Restoring" } else { "Minimizing" });
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(!minimized));
+                    if minimized {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Only a change to these restarts the listeners (not the bindings)
+fn same_listeners(a: &RemoteSettings, b: &RemoteSettings) -> bool {
+    (a.midi_enabled, &a.midi_port, a.osc_enabled, a.osc_port, a.osc_lan)
+        == (b.midi_enabled, &b.midi_port, b.osc_enabled, b.osc_port, b.osc_lan)
+}
+
+/// Item of `items` at `value` (0..1) along the list
+fn pick<T>(items: &[T], value: f32) -> Option<&T> {
+    let last = items.len().checked_sub(1)?;
+    items.get((value.clamp(0.0, 1.0) * last as f32).round() as usize)
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_spreads_the_range() {
+        let items = [1, 2, 3, 4, 5];
+        assert_eq!(pick(&items, 0.0), Some(&1));
+        assert_eq!(pick(&items, 0.5), Some(&3));
+        assert_eq!(pick(&items, 1.0), Some(&5));
+        assert_eq!(pick(&items, 7.0), Some(&5));
+        assert_eq!(pick::<i32>(&[], 0.5), None);
+    }
+}
@@ -0,0 +1,364 @@
+//! Scripted visual mode, in the spirit of Winamp's AVS: a Rhai script from
+//! the `scripts/` folder draws every frame with a handful of primitives, so
+//! new visualizers can be shared without recompiling BeSpec.
+//!
+//! The script runs top to bottom once per frame. Bars, peaks and waveform
+//! come in as arrays, drawing calls queue egui shapes, and the `state` map
+//! survives from one frame to the next for anything that animates. The
+//! full API is in `docs/scripting.md`.
+//!
+//! A script gets `MAX_OPERATIONS` steps a frame, so one that loops forever
+//! or throws shows its error in place of the visualizer instead of freezing
+//! the window. Edits to the file are picked up within `RELOAD_CHECK`.
+//!
+//! The host lives in egui's temp data (like the spectrogram view), one per
+//! window (viewport), so the renderer needs no extra state threaded through.
+//!
+//! Only built with the `scripting` feature.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use eframe::egui;
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST, INT};
+
+use crate::gui::theme::{db_to_px, gradient_colors, sample_gradient, to_egui_color};
+use crate::shared_state::{ColorProfile, VisualizationData};
+
+/// Script file extension
+pub const SCRIPT_EXTENSION: &str = "rhai";
+/// Steps a script may take per frame
+const MAX_OPERATIONS: u64 = 500_000;
+/// Shapes a script may draw per frame; the rest are dropped
+const MAX_SHAPES: usize = 20_000;
+/// How often the file is checked for edits
+const RELOAD_CHECK: Duration = Duration::from_secs(1);
+
+/// Examples written into a new scripts folder
+const EXAMPLES: [(&str, &str); 2] = [
+    ("radial_bars.rhai", include_str!("../../assets/scripts/radial_bars.rhai")),
+    ("starfield.rhai", include_str!("../../assets/scripts/starfield.rhai")),
+];
+
+pub fn scripts_dir() -> PathBuf {
+    crate::paths::get().data_dir.join("scripts")
+}
+
+/// Script file names in the scripts folder, sorted. A missing folder is
+/// created with the examples in it.
+pub fn list_scripts() -> Vec<String> {
+    let dir = scripts_dir();
+    if !dir.exists() {
+        let written = std::fs::create_dir_all(&dir)
+            .and_then(|_| EXAMPLES.iter().try_for_each(|(name, source)| std::fs::write(dir.join(name), source)));
+        if let Err(e) = written {
+            tracing::warn!("[Script] Couldn't set up {}: {}", dir.display(), e);
+        }
+    }
+    let mut names: Vec<String> = std::fs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == SCRIPT_EXTENSION))
+                .filter_map(|p| p.file_name().map(|f| f.to_string_lossy().to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// What the drawing functions write into while a frame runs
+struct Canvas {
+    rect: egui::Rect,
+    gradient: Vec<(f32, egui::Color32)>,
+    shapes: Vec<egui::Shape>,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Self { rect: egui::Rect::NOTHING, gradient: Vec::new(), shapes: Vec::new() }
+    }
+}
+
+impl Canvas {
+    fn pos(&self, x: f32, y: f32) -> egui::Pos2 {
+        self.rect.min + egui::vec2(x * self.rect.width(), y * self.rect.height())
+    }
+
+    fn push(&mut self, shape: egui::Shape) {
+        if self.shapes.len() < MAX_SHAPES {
+            self.shapes.push(shape);
+        }
+    }
+}
+
+/// One frame's input
+pub struct Frame<'a> {
+    pub rect: egui::Rect,
+    /// Bar and peak levels, 0 (floor) to 1 (full scale)
+    pub bars: &'a [f32],
+    pub peaks: &'a [f32],
+    pub wave: &'a [f32],
+    pub colors: &'a ColorProfile,
+    pub now: Instant,
+}
+
+/// A compiled script with its engine and the state it keeps between frames
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    canvas: Arc<Mutex<Canvas>>,
+    state: Map,
+    started: Option<Instant>,
+}
+
+impl Script {
+    pub fn compile(source: &str) -> Result<Self, String> {
+        let canvas = Arc::new(Mutex::new(Canvas::default()));
+        let engine = engine(&canvas);
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        Ok(Self { engine, ast, canvas, state: Map::new(), started: None })
+    }
+
+    /// Run the script for one frame: the shapes it drew, and its error if it failed
+    pub fn frame(&mut self, frame: &Frame) -> (Vec<egui::Shape>, Option<String>) {
+        if let Ok(mut canvas) = self.canvas.lock() {
+            canvas.rect = frame.rect;
+            canvas.gradient = gradient_colors(frame.colors, |c| c);
+            canvas.shapes.clear();
+        }
+        let started = *self.started.get_or_insert(frame.now);
+        let to_array = |values: &[f32]| values.iter().map(|&v| Dynamic::from_float(v as rhai::FLOAT)).collect::<Array>();
+
+        let mut scope = Scope::new();
+        scope.push_constant("bars", to_array(frame.bars));
+        scope.push_constant("peaks", to_array(frame.peaks));
+        scope.push_constant("wave", to_array(frame.wave));
+        scope.push_constant("time", frame.now.duration_since(started).as_secs_f64() as rhai::FLOAT);
+        scope.push_constant("width", frame.rect.width() as rhai::FLOAT);
+        scope.push_constant("height", frame.rect.height() as rhai::FLOAT);
+        scope.push_constant("low", color_to_int(to_egui_color(frame.colors.low)));
+        scope.push_constant("high", color_to_int(to_egui_color(frame.colors.high)));
+        scope.push_constant("peak", color_to_int(to_egui_color(frame.colors.peak)));
+        scope.push("state", std::mem::take(&mut self.state));
+
+        let result = self.engine.run_ast_with_scope(&mut scope, &self.ast);
+        self.state = scope.get_value::<Map>("state").unwrap_or_default();
+
+        // Whatever it drew before an error still shows
+        let shapes = self.canvas.lock().map(|mut c| std::mem::take(&mut c.shapes)).unwrap_or_default();
+        (shapes, result.err().map(|e| e.to_string()))
+    }
+}
+
+/// Engine with the drawing API bound to `canvas`
+fn engine(canvas: &Arc<Mutex<Canvas>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(32);
+    engine.set_max_array_size(100_000);
+    engine.set_max_map_size(10_000);
+    engine.set_max_string_size(10_000);
+
+    let c = canvas.clone();
+    engine.register_fn("line", move |x1: Dynamic, y1: Dynamic, x2: Dynamic, y2: Dynamic, thickness: Dynamic, color: INT| {
+        if let Ok(mut canvas) = c.lock() {
+            let points = [canvas.pos(num(&x1), num(&y1)), canvas.pos(num(&x2), num(&y2))];
+            canvas.push(egui::Shape::line_segment(points, egui::Stroke::new(num(&thickness), int_to_color(color))));
+        }
+    });
+    let c = canvas.clone();
+    engine.register_fn("rect", move |x: Dynamic, y: Dynamic, w: Dynamic, h: Dynamic, color: INT| {
+        if let Ok(mut canvas) = c.lock() {
+            let (x, y) = (num(&x), num(&y));
+            let rect = egui::Rect::from_two_pos(canvas.pos(x, y), canvas.pos(x + num(&w), y + num(&h)));
+            canvas.push(egui::Shape::rect_filled(rect, 0.0, int_to_color(color)));
+        }
+    });
+    let c = canvas.clone();
+    engine.register_fn("circle", move |x: Dynamic, y: Dynamic, radius: Dynamic, color: INT| {
+        if let Ok(mut canvas) = c.lock() {
+            let center = canvas.pos(num(&x), num(&y));
+            let radius = num(&radius) * canvas.rect.height();
+            canvas.push(egui::Shape::circle_filled(center, radius, int_to_color(color)));
+        }
+    });
+    let c = canvas.clone();
+    engine.register_fn("gradient", move |t: Dynamic| {
+        c.lock().map_or(0, |canvas| color_to_int(sample_gradient(&canvas.gradient, num(&t))))
+    });
+
+    engine.register_fn("rgb", |r: Dynamic, g: Dynamic, b: Dynamic| rgba(num(&r), num(&g), num(&b), 255.0));
+    engine.register_fn("rgba", |r: Dynamic, g: Dynamic, b: Dynamic, a: Dynamic| rgba(num(&r), num(&g), num(&b), num(&a)));
+    engine.register_fn("hsv", |h: Dynamic, s: Dynamic, v: Dynamic| {
+        color_to_int(egui::ecolor::Hsva::new(num(&h).rem_euclid(1.0), num(&s), num(&v), 1.0).into())
+    });
+    engine.register_fn("fade", |color: INT, alpha: Dynamic| color_to_int(int_to_color(color).gamma_multiply(num(&alpha).clamp(0.0, 1.0))));
+    engine
+}
+
+/// Scripts mix integers and floats freely
+fn num(value: &Dynamic) -> f32 {
+    value.as_float().map(|f| f as f32).or_else(|_| value.as_int().map(|i| i as f32)).unwrap_or(0.0)
+}
+
+fn rgba(r: f32, g: f32, b: f32, a: f32) -> INT {
+    let ch = |v: f32| v.round().clamp(0.0, 255.0) as u8;
+    color_to_int(egui::Color32::from_rgba_unmultiplied(ch(r), ch(g), ch(b), ch(a)))
+}
+
+/// Colors are 0xRRGGBBAA integers on the script side (straight alpha)
+fn color_to_int(color: egui::Color32) -> INT {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    ((r as INT) << 24) | ((g as INT) << 16) | ((b as INT) << 8) | a as INT
+}
+
+fn int_to_color(color: INT) -> egui::Color32 {
+    let byte = |shift: u32| ((color >> shift) & 0xff) as u8;
+    egui::Color32::from_rgba_unmultiplied(byte(24), byte(16), byte(8), byte(0))
+}
+
+/// Loads the profile's script and reloads it when the file changes
+#[derive(Default)]
+struct ScriptHost {
+    name: String,
+    modified: Option<SystemTime>,
+    checked: Option<Instant>,
+    script: Option<Script>,
+    error: Option<String>,
+}
+
+impl ScriptHost {
+    fn prepare(&mut self, name: &str, now: Instant) {
+        let same = self.name == name;
+        if same && self.checked.is_some_and(|t| now.duration_since(t) < RELOAD_CHECK) {
+            return;
+        }
+        self.checked = Some(now);
+        let path = scripts_dir().join(name);
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        if same && modified == self.modified {
+            return;
+        }
+
+        self.name = name.to_string();
+        self.modified = modified;
+        self.script = None;
+        self.error = None;
+        if name.is_empty() {
+            return;
+        }
+        let compiled = std::fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|source| Script::compile(&source));
+        match compiled {
+            Ok(script) => {
+                tracing::info!("[Script] Loaded {}", path.display());
+                self.script = Some(script);
+            }
+            Err(e) => {
+                tracing::warn!("[Script] {}: {}", path.display(), e);
+                self.error = Some(e);
+            }
+        }
+    }
+}
+
+/// Script visual mode: run the profile's script into `rect`
+pub fn draw_script(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    script_name: &str,
+    colors: &ColorProfile,
+    data: &VisualizationData,
+    noise_floor_db: f32,
+) {
+    let id = egui::Id::new("script_host").with(painter.ctx().viewport_id());
+    let host = painter.ctx().data_mut(|d| d.get_temp_mut_or_default::<Arc<Mutex<ScriptHost>>>(id).clone());
+    let Ok(mut host) = host.lock() else { return };
+    let now = Instant::now();
+    host.prepare(script_name, now);
+
+    let message = if let Some(script) = host.script.as_mut() {
+        let bars: Vec<f32> = data.bars.iter().map(|&db| db_to_px(db, noise_floor_db, 1.0)).collect();
+        let peaks: Vec<f32> = data.peaks.iter().map(|&db| db_to_px(db, noise_floor_db, 1.0)).collect();
+        let frame = Frame { rect, bars: &bars, peaks: &peaks, wave: &data.waveform, colors, now };
+        let (shapes, error) = script.frame(&frame);
+        painter.with_clip_rect(rect).extend(shapes);
+        if error.is_some() && host.error.is_none() {
+            tracing::warn!("[Script] {}: {}", host.name, error.as_deref().unwrap_or_default());
+        }
+        host.error = error;
+        host.error.clone().map(|e| format!("{}: {}", host.name, e))
+    } else if let Some(e) = &host.error {
+        Some(format!("{}: {}", host.name, e))
+    } else {
+        Some(format!("Pick a script under Settings → Visual\n({})", scripts_dir().display()))
+    };
+
+    if let Some(message) = message {
+        painter.text(
+            rect.left_top() + egui::vec2(8.0, 8.0),
+            egui::Align2::LEFT_TOP,
+            message,
+            egui::FontId::monospace(12.0),
+            to_egui_color(colors.text),
+        );
+    }
+    // Scripts animate on their own time, not just when audio arrives
+    painter.ctx().request_repaint();
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame<'a>(colors: &'a ColorProfile, bars: &'a [f32], now: Instant) -> Frame<'a> {
+        let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(200.0, 100.0));
+        Frame { rect, bars, peaks: bars, wave: &[], colors, now }
+    }
+
+    #[test]
+    fn test_script_draws_and_keeps_state() {
+        let colors = ColorProfile::default();
+        let mut script = Script::compile(
+            r#"
+            state.frames = (state.frames ?? 0) + 1;
+            for i in 0..bars.len() {
+                rect(i / bars.len().to_float(), 1.0 - bars[i], 0.1, bars[i], gradient(bars[i]));
+            }
+            line(0, 0.5, 1, 0.5, 2, rgb(255, 0, 0));
+            "#,
+        )
+        .unwrap();
+
+        let now = Instant::now();
+        let (shapes, error) = script.frame(&frame(&colors, &[0.5, 1.0], now));
+        assert_eq!(error, None);
+        assert_eq!(shapes.len(), 3);
+        script.frame(&frame(&colors, &[0.5, 1.0], now));
+        assert_eq!(script.state.get("frames").and_then(|v| v.as_int().ok()), Some(2));
+    }
+
+    #[test]
+    fn test_runaway_and_broken_scripts_fail_cleanly() {
+        let colors = ColorProfile::default();
+        let mut endless = Script::compile("circle(0.5, 0.5, 0.1, high); loop { }").unwrap();
+        let (shapes, error) = endless.frame(&frame(&colors, &[], Instant::now()));
+        assert!(error.is_some());
+        // What it drew before running away still shows
+        assert_eq!(shapes.len(), 1);
+
+        assert!(Script::compile("rect(0, 0, ").is_err());
+    }
+
+    #[test]
+    fn test_colors_round_trip() {
+        let color = egui::Color32::from_rgba_unmultiplied(10, 20, 30, 255);
+        assert_eq!(int_to_color(color_to_int(color)), color);
+        assert_eq!(color_to_int(egui::Color32::from_rgb(255, 0, 0)), 0xff0000ff);
+    }
+}
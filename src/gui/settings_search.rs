@@ -0,0 +1,483 @@
+// src/gui/settings_search.rs
+//
+// Search box for the settings window. egui builds the tabs on the fly, so
+// there's nothing to crawl; instead `INDEX` lists every setting by the label
+// it has in the UI, plus a few extra words people might search for.
+//
+// Typing shows the matching settings (and a match count on each tab).
+// Picking one switches to its tab, and the row's label - drawn through
+// `row_label` - scrolls into view and flashes for a moment.
+//
+// Adding a setting: add its label to `label`, draw it with `row_label` (or
+// `section_label` for a group heading) and give it an `INDEX` entry. A test
+// checks that no label is left out of `INDEX`.
+
+use eframe::egui::{self, Ui};
+
+use crate::gui::widgets::SettingsTab;
+
+/// How long a jumped-to row stays highlighted (seconds)
+const HIGHLIGHT_SECS: f64 = 1.5;
+
+/// A settings row's label. Only the constants in `label` exist, so every row
+/// drawn through `row_label` is one `INDEX` can point at.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Label(&'static str);
+
+macro_rules! labels {
+    ($($name:ident = $text:literal,)*) => {
+        $(pub const $name: Label = Label($text);)*
+        /// Every label, for the test that keeps `INDEX` complete
+        #[cfg(test)]
+        pub const ALL: &[Label] = &[$($name),*];
+    };
+}
+
+/// Label text exactly as shown in the settings window
+pub mod label {
+    use super::Label;
+
+    labels! {
+        // --- Visual ---
+        VISUAL_PROFILE = "Visual Profile:",
+        A_B_COMPARE = "A/B Compare",
+        MODE = "Mode",
+        VU_COLORING = "VU Coloring",
+        COLOR_MAPPING = "Color Mapping",
+        INTERPOLATION = "Interpolation",
+        AUTOSCALE = "Autoscale",
+        TRACE_COLOR = "Trace Color",
+        TRACES = "Traces",
+        BAR_COUNT = "Bar Count",
+        BAR_GAP = "Bar Gap",
+        BAR_OPACITY = "Bar Opacity",
+        CONTENT_ZOOM = "Content Zoom",
+        GLOW = "Glow",
+        SCRIPT = "Script",
+        MILKDROP_PRESET = "MilkDrop Preset",
+        DOT_GRID = "Dot Grid",
+        DOT_STYLE = "Dot Style",
+        MOUNTAIN_DEPTH = "Mountain Depth",
+        SPLIT_VIEW = "Split View",
+        BACKGROUND_OPACITY = "Background Opacity",
+        BACKGROUND_IMAGE = "Background Image",
+        ALBUM_ART_BACKGROUND = "Album Art Background",
+        IMAGE_FIT = "Image Fit",
+        SEGMENT_HEIGHT = "Segment Height",
+        SEGMENT_GAP = "Segment Gap",
+        BAR_STYLE = "Bar Style",
+        PEAK_INDICATORS = "Peak Indicators",
+        FONT_STYLE = "Font Style",
+        BASELINE_ORIENTATION = "Baseline Orientation:",
+        // --- Colors ---
+        PRESET = "Preset:",
+        LOW = "Low",
+        HIGH = "High",
+        GRADIENT_STOPS = "Gradient Stops",
+        PEAK = "Peak",
+        BACKGROUND = "Background",
+        OVERLAY_TEXT = "Overlay Text",
+        INSPECTOR_BOX = "Inspector Box",
+        INSPECTOR_TEXT_LINE = "Inspector Text/Line",
+        AUTO_THEME_BY_TIME = "Auto Theme by Time",
+        // --- Audio ---
+        DEVICE = "Device",
+        PER_DEVICE_LEVELS = "Per-Device Levels",
+        FFT_WINDOW_SIZE = "FFT Window Size",
+        SAMPLE_RATE = "Sample Rate",
+        CAPTURE_BUFFER = "Capture Buffer",
+        EXCLUSIVE_MODE = "Exclusive Mode",
+        SENSITIVITY = "Sensitivity",
+        NOISE_FLOOR = "Noise Floor",
+        SILENCE_THRESHOLD = "Silence Threshold",
+        HOLD_BEFORE_CLEAR = "Hold Before Clear",
+        REFERENCE_SNAPSHOT = "Reference Snapshot",
+        GAIN_ADVISOR = "Gain Advisor",
+        CLIP_INDICATOR = "Clip Indicator",
+        CLIP_LEVEL = "Clip Level",
+        ENGINE_SETTINGS = "Engine Settings",
+        BAR_ATTACK_RISE = "Bar Attack (Rise)",
+        BAR_RELEASE_FALL = "Bar Release (Fall)",
+        PEAK_HOLD_TIME = "Peak Hold Time",
+        PEAK_FALL_SPEED = "Peak Fall Speed",
+        MIDI_OUT = "MIDI Out",
+        MIDI_PORT = "MIDI Port",
+        // --- Window ---
+        OS_TITLE_BAR = "OS Title Bar",
+        CUSTOM_THEME = "Custom Theme",
+        MAIN_WINDOW = "Main Window",
+        WINDOW_SIZE = "Window Size",
+        LOCK_ASPECT_RATIO = "Lock Aspect Ratio",
+        MINIMIZE_SHORTCUT = "Minimize Shortcut",
+        MINI_MODE_SHORTCUT = "Mini Mode Shortcut",
+        QUICK_SWITCHER_SHORTCUT = "Quick Switcher Shortcut",
+        PEEK_AWAY_HOTKEY = "Peek Away Hotkey",
+        PEEK_AWAY_FOR = "Peek Away For",
+        RETURN_ON_NEXT_TRACK = "Return on Next Track",
+        MINI_MODE_BARS = "Mini Mode Bars",
+        SNAP_TO_EDGES = "Snap to Edges",
+        DOCK_TO_SCREEN_EDGE = "Dock to Screen Edge",
+        DOCK_HEIGHT = "Dock Height",
+        LIVE_WALLPAPER = "Live Wallpaper",
+        RIGHT_CLICK_MENU = "Right-Click Menu",
+        GAMEPAD = "Gamepad 🎮",
+        GHOST_MODE = "Ghost Mode 👻",
+        GHOST_HOT_CORNER = "Ghost Hot Corner",
+        GHOST_HANDLE = "Ghost Handle",
+        WINDOWS = "Windows",
+        NOW_PLAYING_OVERLAY = "Now Playing Overlay",
+        OVERLAY_CORNER = "Overlay Corner",
+        OVERLAY_SIZE = "Overlay Size",
+        OVERLAY_ELEMENTS = "Overlay Elements",
+        TRACK_CHANGE_TOAST = "Track Change Toast",
+        TRACK_HISTORY = "Track History",
+        OSC_INPUT = "OSC Input",
+        OSC_OUTPUT = "OSC Output",
+        MIDI_INPUT = "MIDI Input",
+        HTTP_API = "HTTP API",
+        STREAM_DECK = "Stream Deck",
+        LAST_MESSAGE = "Last Message",
+        ALBUM_ART_LINK = "Album Art Link",
+        LINK_TEMPLATE = "Link Template",
+        SYNCED_LYRICS = "Synced Lyrics",
+        LYRICS_FONT = "Lyrics Font",
+        LYRICS_OPACITY = "Lyrics Opacity",
+        LYRICS_CONTEXT_LINES = "Lyrics Context Lines",
+        LYRICS_OFFSET = "Lyrics Offset",
+        PRIVACY = "Privacy",
+        NETWORK = "Network",
+        CONNECTED_ACCOUNTS = "Connected Accounts",
+        // --- Tools ---
+        GENERATOR = "Generator",
+        WAVEFORM = "Waveform",
+        TONE_FREQUENCY = "Tone Frequency",
+        SWEEP_RANGE = "Sweep Range",
+        GENERATOR_LEVEL = "Generator Level",
+        GENERATOR_OUTPUT = "Generator Output",
+        MEASUREMENT = "Measurement",
+        TEST_SIGNAL = "Test Signal",
+        SIGNAL_OUTPUT = "Signal Output",
+        SIGNAL_LEVEL = "Signal Level",
+        CURVE_SMOOTHING = "Curve Smoothing",
+        RESPONSE_CURVE = "Response Curve",
+        TUNER_OVERLAY = "Tuner Overlay",
+        CONCERT_PITCH = "Concert Pitch",
+        // --- Stats ---
+        FRAME_RATE_CAP = "Frame Rate Cap:",
+        LEVEL_METER = "Level Meter:",
+        GUI_FRAME_RATE = "GUI Frame Rate",
+        DISPLAY_REFRESH = "Display Refresh",
+        DROPPED_FRAMES = "Dropped Frames",
+        CPU_USAGE = "CPU Usage",
+        MEMORY_USAGE = "Memory Usage",
+        RENDERER_STALLS = "Renderer Stalls",
+        FFT_SIZE = "FFT Size",
+        FREQUENCY_RESOLUTION = "Frequency Resolution",
+        THEORETICAL_LATENCY = "Theoretical Latency",
+        MEASURED_LATENCY = "Measured Latency",
+        LATENCY_SELF_TEST = "Latency Self-Test",
+        CLICK_OUTPUT = "Click Output",
+        STRESS_TEST = "Stress Test",
+        SLEEP_ON_SILENCE = "Sleep on Silence",
+        SLEEP_AFTER = "Sleep After",
+        DIM_WHILE_ASLEEP = "Dim While Asleep",
+        PAUSE_WHEN_HIDDEN = "Pause When Hidden",
+        PAUSE_FFT_WHEN_HIDDEN = "Pause FFT When Hidden",
+        SPECTRUM_LOG = "Spectrum Log",
+        LOG_FORMAT = "Log Format",
+        KEEP_EVERY = "Keep Every",
+        NEW_FILE_AFTER = "New File After",
+        SUPPORT = "Support",
+        LOG_LEVEL = "Log Level",
+        RESET_SETTINGS = "Reset Settings",
+        BACKUPS = "Backups",
+        // Reserve a spot behind the text, fill it once the label's rect is known
+    }
+}
+
+pub struct SearchEntry {
+    /// Row label exactly as shown in the settings window
+    pub label: &'static str,
+    pub tab: SettingsTab,
+    /// Other words that should find it
+    pub keywords: &'static str,
+}
+
+const fn entry(label: Label, tab: SettingsTab, keywords: &'static str) -> SearchEntry {
+    SearchEntry { label: label.0, tab, keywords }
+}
+
+pub const INDEX: &[SearchEntry] = &[
+    // --- Visual ---
+    entry(label::VISUAL_PROFILE, SettingsTab::Visual, "preset save load"),
+    entry(label::A_B_COMPARE, SettingsTab::Visual, "ab toggle flip swap copy look preset"),
+    entry(label::MODE, SettingsTab::Visual, "visual mode bars line oscilloscope scope spectrogram waterfall plugin native dll"),
+    entry(label::VU_COLORING, SettingsTab::Visual, "gradient retro"),
+    entry(label::COLOR_MAPPING, SettingsTab::Visual, "rainbow hue frequency band per bar height amplitude"),
+    entry(label::INTERPOLATION, SettingsTab::Visual, "smooth smoothing curve spline cubic linear steps line spectrum"),
+    entry(label::AUTOSCALE, SettingsTab::Visual, "oscilloscope scope zoom gain"),
+    entry(label::TRACE_COLOR, SettingsTab::Visual, "oscilloscope scope frequency bass treble"),
+    entry(label::TRACES, SettingsTab::Visual, "oscilloscope scope stereo channel left right difference side"),
+    entry(label::BAR_COUNT, SettingsTab::Visual, "bars number resolution"),
+    entry(label::BAR_GAP, SettingsTab::Visual, "spacing"),
+    entry(label::BAR_OPACITY, SettingsTab::Visual, "transparency alpha"),
+    entry(label::CONTENT_ZOOM, SettingsTab::Visual, "scale pixel retro chunky resolution size"),
+    entry(label::GLOW, SettingsTab::Visual, "bloom halo post effect reactive loudness"),
+    entry(label::SCRIPT, SettingsTab::Visual, "rhai avs custom visualizer code scripting"),
+    entry(label::MILKDROP_PRESET, SettingsTab::Visual, "projectm milk winamp preset browse"),
+    entry(label::DOT_GRID, SettingsTab::Visual, "dot matrix lcd led sign retro columns rows resolution"),
+    entry(label::DOT_STYLE, SettingsTab::Visual, "dot matrix round square pixels characters monospace phosphor glow"),
+    entry(label::MOUNTAIN_DEPTH, SettingsTab::Visual, "3d landscape perspective ridges history waterfall"),
+    entry(label::SPLIT_VIEW, SettingsTab::Visual, "dual split screen two modes spectrum oscilloscope waveform ratio"),
+    entry(label::BACKGROUND_OPACITY, SettingsTab::Visual, "transparency alpha window"),
+    entry(label::BACKGROUND_IMAGE, SettingsTab::Visual, "picture wallpaper png jpeg gif animated"),
+    entry(label::ALBUM_ART_BACKGROUND, SettingsTab::Visual, "cover blur blurred dim media track picture"),
+    entry(label::IMAGE_FIT, SettingsTab::Visual, "background picture fill stretch tile opacity"),
+    entry(label::SEGMENT_HEIGHT, SettingsTab::Visual, "led segmented"),
+    entry(label::SEGMENT_GAP, SettingsTab::Visual, "led segmented"),
+    entry(label::BAR_STYLE, SettingsTab::Visual, "rounded caps outline hatched texture fill drop shadow solid segmented"),
+    entry(label::PEAK_INDICATORS, SettingsTab::Visual, "peaks show fill style line dot falling trail"),
+    entry(label::FONT_STYLE, SettingsTab::Visual, "text overlay size"),
+    entry(label::BASELINE_ORIENTATION, SettingsTab::Visual, "direction rotate vertical horizontal"),
+    // --- Colors ---
+    entry(label::PRESET, SettingsTab::Colors, "color theme import export"),
+    entry(label::LOW, SettingsTab::Colors, "color gradient"),
+    entry(label::HIGH, SettingsTab::Colors, "color gradient"),
+    entry(label::GRADIENT_STOPS, SettingsTab::Colors, "color gradient multi stop editor custom"),
+    entry(label::PEAK, SettingsTab::Colors, "color"),
+    entry(label::BACKGROUND, SettingsTab::Colors, "color"),
+    entry(label::OVERLAY_TEXT, SettingsTab::Colors, "color media"),
+    entry(label::INSPECTOR_BOX, SettingsTab::Colors, "color hover"),
+    entry(label::INSPECTOR_TEXT_LINE, SettingsTab::Colors, "color hover"),
+    entry(label::AUTO_THEME_BY_TIME, SettingsTab::Colors, "scheduling schedule clock night day sunset preset"),
+    // --- Audio ---
+    entry(label::DEVICE, SettingsTab::Audio, "input output source capture loopback microphone details capabilities formats channels"),
+    entry(label::PER_DEVICE_LEVELS, SettingsTab::Audio, "offset sensitivity gain floor microphone loopback switch"),
+    entry(label::FFT_WINDOW_SIZE, SettingsTab::Audio, "resolution"),
+    entry(label::SAMPLE_RATE, SettingsTab::Audio, "resample internal hz"),
+    entry(label::CAPTURE_BUFFER, SettingsTab::Audio, "buffer size period callback frames low latency delay lag"),
+    entry(label::EXCLUSIVE_MODE, SettingsTab::Audio, "wasapi exclusive input microphone low latency"),
+    entry(label::SENSITIVITY, SettingsTab::Audio, "gain volume boost db"),
+    entry(label::NOISE_FLOOR, SettingsTab::Audio, "minimum db range"),
+    entry(label::SILENCE_THRESHOLD, SettingsTab::Audio, "quiet detection dbfs"),
+    entry(label::HOLD_BEFORE_CLEAR, SettingsTab::Audio, "silence timeout gap"),
+    entry(label::REFERENCE_SNAPSHOT, SettingsTab::Audio, "compare before after eq ghost line capture average delta"),
+    entry(label::GAIN_ADVISOR, SettingsTab::Audio, "clipping level hints"),
+    entry(label::CLIP_INDICATOR, SettingsTab::Audio, "clipping over level full scale warning led light red bars"),
+    entry(label::CLIP_LEVEL, SettingsTab::Audio, "clipping threshold dbfs hold time"),
+    entry(label::ENGINE_SETTINGS, SettingsTab::Audio, "per profile"),
+    entry(label::BAR_ATTACK_RISE, SettingsTab::Audio, "envelope speed smoothing"),
+    entry(label::BAR_RELEASE_FALL, SettingsTab::Audio, "envelope speed smoothing decay"),
+    entry(label::PEAK_HOLD_TIME, SettingsTab::Audio, "envelope peaks"),
+    entry(label::PEAK_FALL_SPEED, SettingsTab::Audio, "envelope peaks release gravity accelerate"),
+    entry(label::MIDI_OUT, SettingsTab::Audio, "midi note cc trigger kick daw lighting band threshold"),
+    entry(label::MIDI_PORT, SettingsTab::Audio, "midi output device loopmidi"),
+    // --- Window ---
+    entry(label::OS_TITLE_BAR, SettingsTab::Window, "decorations frame border"),
+    entry(label::CUSTOM_THEME, SettingsTab::Window, "beos haiku tab"),
+    entry(label::MAIN_WINDOW, SettingsTab::Window, "always on top"),
+    entry(label::WINDOW_SIZE, SettingsTab::Window, "resize preset strip square widescreen dimensions"),
+    entry(label::LOCK_ASPECT_RATIO, SettingsTab::Window, "resize grip proportions keep shape"),
+    entry(label::MINIMIZE_SHORTCUT, SettingsTab::Window, "hotkey keyboard hide"),
+    entry(label::MINI_MODE_SHORTCUT, SettingsTab::Window, "hotkey keyboard compact strip"),
+    entry(label::QUICK_SWITCHER_SHORTCUT, SettingsTab::Window, "hotkey keyboard profile preset carousel osd"),
+    entry(label::PEEK_AWAY_HOTKEY, SettingsTab::Window, "global hotkey keyboard hide minimize boss key"),
+    entry(label::PEEK_AWAY_FOR, SettingsTab::Window, "hide restore timer seconds duration"),
+    entry(label::RETURN_ON_NEXT_TRACK, SettingsTab::Window, "hide restore song change media"),
+    entry(label::MINI_MODE_BARS, SettingsTab::Window, "compact strip"),
+    entry(label::SNAP_TO_EDGES, SettingsTab::Window, "magnetic monitor drag"),
+    entry(label::DOCK_TO_SCREEN_EDGE, SettingsTab::Window, "appbar taskbar reserve"),
+    entry(label::DOCK_HEIGHT, SettingsTab::Window, "appbar thickness"),
+    entry(label::LIVE_WALLPAPER, SettingsTab::Window, "desktop background icons"),
+    entry(label::RIGHT_CLICK_MENU, SettingsTab::Window, "context menu quick actions pause screenshot device"),
+    entry(label::GAMEPAD, SettingsTab::Window, "controller joystick xbox htpc couch tv"),
+    entry(label::GHOST_MODE, SettingsTab::Window, "click through lock passthrough"),
+    entry(label::GHOST_HOT_CORNER, SettingsTab::Window, "click through unlock wake screen corner mouse"),
+    entry(label::GHOST_HANDLE, SettingsTab::Window, "click through unlock lock button strip always clickable"),
+    entry(label::WINDOWS, SettingsTab::Window, "extra visualizer multiple"),
+    entry(label::NOW_PLAYING_OVERLAY, SettingsTab::Window, "media track album art"),
+    entry(label::OVERLAY_CORNER, SettingsTab::Window, "media now playing position left right top bottom"),
+    entry(label::OVERLAY_SIZE, SettingsTab::Window, "media now playing scale bigger smaller"),
+    entry(label::OVERLAY_ELEMENTS, SettingsTab::Window, "media now playing art title artist progress controls source hide"),
+    entry(label::TRACK_CHANGE_TOAST, SettingsTab::Window, "notification popup new song track changed"),
+    entry(label::TRACK_HISTORY, SettingsTab::Window, "recent played songs list copy what was that"),
+    entry(label::OSC_INPUT, SettingsTab::Window, "remote control osc udp port touchosc lan"),
+    entry(label::OSC_OUTPUT, SettingsTab::Window, "osc udp send spectral centroid rolloff flatness brightness touchdesigner generative"),
+    entry(label::MIDI_INPUT, SettingsTab::Window, "remote control midi cc controller learn"),
+    entry(label::HTTP_API, SettingsTab::Window, "rest json endpoint script autohotkey stream deck localhost status"),
+    entry(label::STREAM_DECK, SettingsTab::Window, "elgato plugin socket tcp ghost mode levels keys localhost"),
+    entry(label::LAST_MESSAGE, SettingsTab::Window, "remote control osc midi learn bindings"),
+    entry(label::ALBUM_ART_LINK, SettingsTab::Window, "wikipedia musicbrainz discogs click url lookup"),
+    entry(label::LINK_TEMPLATE, SettingsTab::Window, "custom url album art click last.fm"),
+    entry(label::SYNCED_LYRICS, SettingsTab::Window, "lyrics lrc karaoke lrclib words"),
+    entry(label::LYRICS_FONT, SettingsTab::Window, "lyrics text size monospace"),
+    entry(label::LYRICS_OPACITY, SettingsTab::Window, "lyrics transparency"),
+    entry(label::LYRICS_CONTEXT_LINES, SettingsTab::Window, "lyrics lines before after"),
+    entry(label::LYRICS_OFFSET, SettingsTab::Window, "lyrics delay sync timing"),
+    entry(label::PRIVACY, SettingsTab::Window, "log metadata"),
+    entry(label::NETWORK, SettingsTab::Window, "offline internet"),
+    entry(label::CONNECTED_ACCOUNTS, SettingsTab::Window, "login token keyring"),
+    // --- Tools ---
+    // "noise" is kept out of these, it should only find the noise floor
+    entry(label::GENERATOR, SettingsTab::Tools, "signal test tone play sine sweep white pink speaker"),
+    entry(label::WAVEFORM, SettingsTab::Tools, "signal generator sine sweep white pink"),
+    entry(label::TONE_FREQUENCY, SettingsTab::Tools, "signal generator sine hz pitch"),
+    entry(label::SWEEP_RANGE, SettingsTab::Tools, "signal generator chirp hz seconds"),
+    entry(label::GENERATOR_LEVEL, SettingsTab::Tools, "signal volume dbfs"),
+    entry(label::GENERATOR_OUTPUT, SettingsTab::Tools, "signal speaker playback device"),
+    entry(label::MEASUREMENT, SettingsTab::Tools, "pink room speaker response curve microphone average"),
+    entry(label::TEST_SIGNAL, SettingsTab::Tools, "measurement pink play generator"),
+    entry(label::SIGNAL_OUTPUT, SettingsTab::Tools, "measurement pink speaker playback device"),
+    entry(label::SIGNAL_LEVEL, SettingsTab::Tools, "measurement pink volume dbfs"),
+    entry(label::CURVE_SMOOTHING, SettingsTab::Tools, "measurement octave 1/3 1/6 1/12 response"),
+    entry(label::RESPONSE_CURVE, SettingsTab::Tools, "measurement keep reference clear"),
+    entry(label::TUNER_OVERLAY, SettingsTab::Tools, "pitch note cents guitar bass instrument tune yin"),
+    entry(label::CONCERT_PITCH, SettingsTab::Tools, "tuner a4 440 432 reference"),
+    // --- Stats ---
+    entry(label::FRAME_RATE_CAP, SettingsTab::Performance, "fps limit vsync display refresh monitor hz"),
+    entry(label::LEVEL_METER, SettingsTab::Performance, "peak rms crest factor dbfs vu loudness strip side"),
+    entry(label::GUI_FRAME_RATE, SettingsTab::Performance, "fps performance"),
+    entry(label::DISPLAY_REFRESH, SettingsTab::Performance, "monitor refresh rate hz vsync pacing 144"),
+    entry(label::DROPPED_FRAMES, SettingsTab::Performance, "stutter jank skipped late fps vsync pacing"),
+    entry(label::CPU_USAGE, SettingsTab::Performance, "processor load heavy task manager performance"),
+    entry(label::MEMORY_USAGE, SettingsTab::Performance, "ram rss working set heavy task manager"),
+    entry(label::RENDERER_STALLS, SettingsTab::Performance, "freeze frozen gpu driver reset watchdog"),
+    entry(label::FFT_SIZE, SettingsTab::Performance, "diagnostics"),
+    entry(label::FREQUENCY_RESOLUTION, SettingsTab::Performance, "diagnostics hz bin"),
+    entry(label::THEORETICAL_LATENCY, SettingsTab::Performance, "delay diagnostics"),
+    entry(label::MEASURED_LATENCY, SettingsTab::Performance, "delay lag end to end buffer callback diagnostics"),
+    entry(label::LATENCY_SELF_TEST, SettingsTab::Performance, "delay lag round trip click loopback speaker microphone measure"),
+    entry(label::CLICK_OUTPUT, SettingsTab::Performance, "latency test speaker playback device"),
+    entry(label::STRESS_TEST, SettingsTab::Performance, "benchmark headroom fps performance max bars load cpu"),
+    entry(label::SLEEP_ON_SILENCE, SettingsTab::Performance, "auto sleep idle"),
+    entry(label::SLEEP_AFTER, SettingsTab::Performance, "auto sleep idle timeout"),
+    entry(label::DIM_WHILE_ASLEEP, SettingsTab::Performance, "auto sleep"),
+    entry(label::PAUSE_WHEN_HIDDEN, SettingsTab::Performance, "minimized covered occluded background battery laptop gpu repaint throttle"),
+    entry(label::PAUSE_FFT_WHEN_HIDDEN, SettingsTab::Performance, "minimized covered occluded battery laptop cpu processing"),
+    entry(label::SPECTRUM_LOG, SettingsTab::Performance, "record data logger csv json jsonl export analysis rotate"),
+    entry(label::LOG_FORMAT, SettingsTab::Performance, "spectrum log csv json jsonl file"),
+    entry(label::KEEP_EVERY, SettingsTab::Performance, "spectrum log frames skip decimate downsample rate"),
+    entry(label::NEW_FILE_AFTER, SettingsTab::Performance, "spectrum log rotate size mb split"),
+    entry(label::SUPPORT, SettingsTab::Performance, "bundle bug report logs zip"),
+    entry(label::LOG_LEVEL, SettingsTab::Performance, "logs viewer diagnostics debug error warning copy file"),
+    entry(label::RESET_SETTINGS, SettingsTab::Performance, "defaults factory restore section all maintenance"),
+    entry(label::BACKUPS, SettingsTab::Performance, "config backup restore undo maintenance"),
+];
+
+/// Every word of `query` appears in the label or keywords (case-insensitive)
+pub fn matches(entry: &SearchEntry, query: &str) -> bool {
+    let haystack = format!("{} {}", entry.label, entry.keywords).to_lowercase();
+    let mut words = query.split_whitespace().peekable();
+    words.peek().is_some() && words.all(|w| haystack.contains(&w.to_lowercase()))
+}
+
+pub fn search(query: &str) -> impl Iterator<Item = &'static SearchEntry> + '_ {
+    INDEX.iter().filter(move |e| matches(e, query))
+}
+
+/// Matches on `tab`, for the count shown next to the tab name
+pub fn count_in_tab(query: &str, tab: &SettingsTab) -> usize {
+    search(query).filter(|e| e.tab == *tab).count()
+}
+
+#[derive(Clone)]
+struct Highlight {
+    label: &'static str,
+    /// `InputState::time` when the jump happened
+    since: f64,
+    scrolled: bool,
+}
+
+fn highlight_id() -> egui::Id {
+    egui::Id::new("settings_search_highlight")
+}
+
+/// Result list shown instead of the tab content while searching.
+/// Picking a result switches tab, clears the query and flashes the row.
+pub fn ui_results(ui: &mut Ui, query: &mut String, active_tab: &mut SettingsTab) {
+    let mut picked = None;
+    for entry in search(query) {
+        let text = format!("{}   ·  {}", entry.label.trim_end_matches(':'), entry.tab.label());
+        if ui.selectable_label(false, text).clicked() {
+            picked = Some(entry);
+        }
+    }
+    if picked.is_none() && search(query).next().is_none() {
+        ui.label(egui::RichText::new("No matching settings").weak());
+    }
+
+    if let Some(entry) = picked {
+        *active_tab = entry.tab;
+        query.clear();
+        let since = ui.input(|i| i.time);
+        ui.ctx().data_mut(|d| d.insert_temp(highlight_id(), Highlight { label: entry.label, since, scrolled: false }));
+    }
+}
+
+/// Grid row label that lights up (and scrolls into view) after a search jump
+pub fn row_label(ui: &mut Ui, label: Label) -> egui::Response {
+    search_label(ui, label.0, false)
+}
+
+/// Bold group heading, for settings that are a whole group rather than a row
+pub fn section_label(ui: &mut Ui, label: Label) -> egui::Response {
+    search_label(ui, label.0, true)
+}
+
+fn search_label(ui: &mut Ui, text: &'static str, strong: bool) -> egui::Response {
+    let plain = if strong { egui::RichText::new(text).strong() } else { egui::RichText::new(text) };
+    let highlight = ui.ctx().data(|d| d.get_temp::<Highlight>(highlight_id()));
+    let Some(mut highlight) = highlight.filter(|h| h.label == text) else {
+        return ui.label(plain);
+    };
+
+    let age = ui.input(|i| i.time) - highlight.since;
+    if age > HIGHLIGHT_SECS {
+        ui.ctx().data_mut(|d| d.remove::<Highlight>(highlight_id()));
+        return ui.label(plain);
+    }
+
+    // Reserve a spot behind the text, fill it once the label's rect is known
+    let background = ui.painter().add(egui::Shape::Noop);
+    let response = ui.label(egui::RichText::new(text).strong());
+    let fade = 1.0 - (age / HIGHLIGHT_SECS) as f32;
+    ui.painter().set(
+        background,
+        egui::Shape::rect_filled(response.rect.expand(3.0), 4.0, ui.visuals().selection.bg_fill.gamma_multiply(0.6 * fade)),
+    );
+    if !highlight.scrolled {
+        response.scroll_to_me(Some(egui::Align::Center));
+        highlight.scrolled = true;
+        ui.ctx().data_mut(|d| d.insert_temp(highlight_id(), highlight));
+    }
+    ui.ctx().request_repaint();
+    response
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_matches_labels_and_keywords() {
+        let labels = |q: &str| search(q).map(|e| e.label).collect::<Vec<_>>();
+        assert_eq!(labels("noise"), vec!["Noise Floor"]);
+        assert_eq!(labels("SENSITIVITY"), vec!["Per-Device Levels", "Sensitivity"]);
+        // Keywords count too, and every word has to match
+        assert!(labels("gain").contains(&"Sensitivity"));
+        assert_eq!(labels("sleep dim"), vec!["Dim While Asleep"]);
+        assert!(labels("   ").is_empty());
+        assert_eq!(count_in_tab("peak", &SettingsTab::Audio), 2);
+    }
+
+    #[test]
+    fn test_every_label_is_indexed_once() {
+        for label in label::ALL {
+            let entries = INDEX.iter().filter(|e| e.label == label.0).count();
+            assert_eq!(entries, 1, "\"{}\" should have exactly one INDEX entry", label.0);
+        }
+        assert_eq!(INDEX.len(), label::ALL.len());
+    }
+}
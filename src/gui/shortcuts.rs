@@ -0,0 +1,138 @@
+// src/gui/shortcuts.rs
+//
+// Single-key shortcuts for the main window, so the common tweaks don't need
+// the mouse or the settings viewport:
+//   - Arrow keys   - more / fewer bars
+//   - + / -        - sensitivity up / down by 1 dB
+//   - M            - next visual mode
+//   - P            - next color preset (user presets first, then built-ins)
+//   - S            - open / close settings
+//   - B            - flip between the A/B compare slots
+//   - [ / ]        - background opacity (with Shift: bars), see `opacity`
+//   - ?            - cheat sheet overlay (Esc or ? again closes it)
+//
+// Keys only count while the main window has focus and no text box does.
+// Ctrl+<key> combos (minimize, mini mode, quick switcher) are handled in
+// `SpectrumApp::update` and consumed before we get here. While the quick
+// switcher is open it gets the keys instead.
+
+use eframe::egui;
+
+use crate::commands::{self, Command};
+use crate::shared_state::{ColorRef, SharedState, VisualProfile};
+
+/// Bars added or removed per arrow key press
+const BAR_STEP: usize = 8;
+
+/// Same limits as the settings sliders
+const BAR_RANGE: std::ops::RangeInclusive<usize> = 10..=512;
+const SENSITIVITY_RANGE: std::ops::RangeInclusive<f32> = 0.01..=100.0;
+
+/// 1 dB as a gain factor
+const SENSITIVITY_STEP: f32 = 1.122_018_5;
+
+/// Rows of the cheat sheet: (keys, what they do)
+const CHEAT_SHEET: [(&str, &str); 13] = [
+    ("← → ↑ ↓", "Fewer / more bars"),
+    ("+  -", "Sensitivity ±1 dB"),
+    ("M", "Next visual mode"),
+    ("P", "Next color preset"),
+    ("S", "Settings"),
+    ("B", "A/B compare"),
+    ("[  ]", "Background opacity ±5%"),
+    ("Shift + [  ]", "Bar opacity ±5%"),
+    ("Ctrl + Wheel", "Background opacity (Shift: bars)"),
+    ("Ctrl + M", "Mini mode"),
+    ("Ctrl + H", "Minimize"),
+    ("Ctrl + Space", "Quick switcher"),
+    ("?", "This help"),
+];
+
+/// Apply any shortcut pressed this frame
+pub fn handle(ctx: &egui::Context, state: &mut SharedState, settings_open: &mut bool, cheat_sheet_open: &mut bool) {
+    if ctx.wants_keyboard_input() {
+        return;
+    }
+
+    // Shift is ignored by `consume_key` unless asked for, Ctrl/Cmd is not
+    let pressed = |key: egui::Key| ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, key));
+    let shift = ctx.input(|i| i.modifiers.shift);
+
+    // '?' arrives as its own key on most layouts, as Shift+/ on some
+    if pressed(egui::Key::Questionmark) || (shift && pressed(egui::Key::Slash)) {
+        *cheat_sheet_open = !*cheat_sheet_open;
+    }
+    if *cheat_sheet_open && pressed(egui::Key::Escape) {
+        *cheat_sheet_open = false;
+    }
+
+    let profile = &mut state.config.profile;
+    if pressed(egui::Key::ArrowUp) || pressed(egui::Key::ArrowRight) {
+        step_bars(profile, 1);
+    }
+    if pressed(egui::Key::ArrowDown) || pressed(egui::Key::ArrowLeft) {
+        step_bars(profile, -1);
+    }
+
+    // '+' is Shift+= on many layouts, and there's the numpad
+    if pressed(egui::Key::Plus) || pressed(egui::Key::Equals) {
+        step_sensitivity(profile, 1);
+    }
+    if pressed(egui::Key::Minus) {
+        step_sensitivity(profile, -1);
+    }
+
+    // Same as the remote's next_mode / next_colors
+    if pressed(egui::Key::M) {
+        let _ = commands::execute(&Command::NextMode, state);
+    }
+
+    if pressed(egui::Key::P) {
+        let _ = commands::execute(&Command::NextColors, state);
+        if let ColorRef::Preset(name) = &state.config.profile.color_link {
+            tracing::debug!("[GUI] Color preset: {}", name);
+        }
+    }
+
+    if pressed(egui::Key::S) {
+        *settings_open = !*settings_open;
+    }
+
+    if pressed(egui::Key::B) {
+        state.ab_compare.flip(&mut state.config.profile);
+        tracing::debug!("[GUI] A/B compare: showing {}", state.ab_compare.active());
+    }
+}
+
+/// `delta` steps of `BAR_STEP` bars, within the slider range
+pub(super) fn step_bars(profile: &mut VisualProfile, delta: i32) {
+    let bars = profile.num_bars as i64 + delta as i64 * BAR_STEP as i64;
+    profile.num_bars = (bars.max(0) as usize).clamp(*BAR_RANGE.start(), *BAR_RANGE.end());
+}
+
+/// `delta` dB more or less sensitivity, within the slider range
+pub(super) fn step_sensitivity(profile: &mut VisualProfile, delta: i32) {
+    let gain = SENSITIVITY_STEP.powi(delta);
+    profile.sensitivity = (profile.sensitivity * gain).clamp(*SENSITIVITY_RANGE.start(), *SENSITIVITY_RANGE.end());
+}
+
+/// Cheat sheet overlay, centered over the main window
+pub fn show_cheat_sheet(ctx: &egui::Context) {
+    egui::Area::new(egui::Id::new("shortcut_cheat_sheet"))
+        .order(egui::Order::Foreground)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(egui::RichText::new("Keyboard Shortcuts").strong());
+                ui.separator();
+                egui::Grid::new("cheat_sheet_grid").num_columns(2).spacing([24.0, 4.0]).show(ui, |ui| {
+                    for (keys, action) in CHEAT_SHEET {
+                        ui.label(egui::RichText::new(keys).monospace());
+                        ui.label(action);
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+}
@@ -0,0 +1,153 @@
+// src/gui/tasks.rs
+//
+// Small worker pool for blocking work the GUI kicks off (Wikipedia lookups,
+// gallery downloads, ...). The GUI thread must never block on the network,
+// but raw `thread::spawn` calls lose their errors to the log file.
+//
+// A job runs on one of `WORKERS` threads and returns either:
+//   - Ok(apply)  - a closure run on the GUI thread with the shared state,
+//                  so results land in `SharedState` without extra locking dances
+//   - Err(error) - shown in the GUI error banner via `SharedState::report_error`
+//
+// `SpectrumApp::update` calls `poll` once per frame to collect finished jobs.
+
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use eframe::egui;
+
+use crate::error::BeSpecError;
+use crate::shared_state::SharedState;
+
+/// Worker threads in the pool. Jobs are short and mostly waiting on I/O.
+const WORKERS: usize = 2;
+
+/// Applied to the shared state on the GUI thread when a job succeeds
+pub type ApplyFn = Box<dyn FnOnce(&mut SharedState) + Send>;
+
+/// What a job hands back
+pub type TaskOutcome = Result<ApplyFn, BeSpecError>;
+
+struct Job {
+    name: &'static str,
+    ctx: egui::Context,
+    work: Box<dyn FnOnce() -> TaskOutcome + Send>,
+}
+
+struct Finished {
+    name: &'static str,
+    outcome: TaskOutcome,
+}
+
+pub struct TaskRunner {
+    job_tx: Sender<Job>,
+    done_rx: Receiver<Finished>,
+}
+
+impl Default for TaskRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskRunner {
+    /// Start the worker threads. They exit when the runner is dropped.
+    pub fn new() -> Self {
+        let (job_tx, job_rx) = unbounded::<Job>();
+        let (done_tx, done_rx) = unbounded::<Finished>();
+
+        for i in 0..WORKERS {
+            let job_rx = job_rx.clone();
+            let done_tx = done_tx.clone();
+            let spawned = std::thread::Builder::new()
+                .name(format!("bespec-task-{}", i))
+                .spawn(move || {
+                    while let Ok(job) = job_rx.recv() {
+                        tracing::debug!("[Tasks] Running '{}'", job.name);
+                        let outcome = (job.work)();
+                        if done_tx.send(Finished { name: job.name, outcome }).is_err() {
+                            break;
+                        }
+                        job.ctx.request_repaint();
+                    }
+                });
+            if let Err(e) = spawned {
+                tracing::error!("[Tasks] Failed to start worker {}: {}", i, e);
+            }
+        }
+
+        Self { job_tx, done_rx }
+    }
+
+    /// Queue `work` on the pool. `ctx` is repainted when it finishes.
+    pub fn spawn(
+        &self,
+        ctx: &egui::Context,
+        name: &'static str,
+        work: impl FnOnce() -> TaskOutcome + Send + 'static,
+    ) {
+        let job = Job { name, ctx: ctx.clone(), work: Box::new(work) };
+        if self.job_tx.send(job).is_err() {
+            tracing::error!("[Tasks] Worker pool is gone, dropped '{}'", name);
+        }
+    }
+
+    /// Apply every finished job to `state`. Call once per frame.
+    pub fn poll(&self, state: &mut SharedState) {
+        while let Ok(done) = self.done_rx.try_recv() {
+            match done.outcome {
+                Ok(apply) => {
+                    tracing::debug!("[Tasks] '{}' finished", done.name);
+                    apply(state);
+                }
+                Err(e) => state.report_error(e),
+            }
+        }
+    }
+}
+
+/// Ok outcome that doesn't need to touch the shared state
+pub fn done() -> TaskOutcome {
+    Ok(Box::new(|_| {}))
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared_state::AppConfig;
+    use std::time::{Duration, Instant};
+
+    /// Poll until `check` passes or a second goes by
+    fn wait_for(runner: &TaskRunner, state: &mut SharedState, check: impl Fn(&SharedState) -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while Instant::now() < deadline {
+            runner.poll(state);
+            if check(state) {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        false
+    }
+
+    #[test]
+    fn test_results_are_applied_on_poll() {
+        let runner = TaskRunner::new();
+        let ctx = egui::Context::default();
+        let mut state = SharedState::with_config(AppConfig::default());
+
+        runner.spawn(&ctx, "apply", || Ok(Box::new(|s: &mut SharedState| s.config.show_stats = true)));
+        assert!(wait_for(&runner, &mut state, |s| s.config.show_stats));
+    }
+
+    #[test]
+    fn test_errors_reach_the_banner() {
+        let runner = TaskRunner::new();
+        let ctx = egui::Context::default();
+        let mut state = SharedState::with_config(AppConfig::default());
+
+        runner.spawn(&ctx, "fail", || Err(BeSpecError::Task { task: "fail".into(), reason: "boom".into() }));
+        assert!(wait_for(&runner, &mut state, |s| s.last_error.is_some()));
+        assert_eq!(state.last_error.as_ref().unwrap().kind(), "task");
+    }
+}
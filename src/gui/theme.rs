@@ -1,7 +1,9 @@
 use egui::{Color32, FontId, FontFamily};
-use crate::shared_state::{Color32 as SharedColor, ThemeFont};
+use crate::shared_state::{Color32 as SharedColor, ColorProfile, ThemeFont};
 use crate::gui::StateColor32;
 
+pub use crate::gui::gradient::{lerp_color, sample_gradient};
+
 // === BeOS / Haiku Design Tokens ====
 
 // 1. Tab Gradients (Warm & Buttery, not White)
@@ -76,16 +78,6 @@ pub fn db_to_px(db: f32, noise_floor: f32, max_height: f32) -> f32 {
     normalized * max_height
 }
 
-pub fn lerp_color(a: egui::Color32, b: egui::Color32, t: f32) -> egui::Color32 {
-    let t = t.clamp(0.0, 1.0);
-    egui::Color32::from_rgba_premultiplied(
-        (a.r() as f32 + (b.r() as f32 - a.r() as f32) * t) as u8,
-        (a.g() as f32 + (b.g() as f32 - a.g() as f32) * t) as u8,
-        (a.b() as f32 + (b.b() as f32 - a.b() as f32) * t) as u8,
-        (a.a() as f32 + (b.a() as f32 - a.a() as f32) * t) as u8,
-    )
-}
-
 /// Retro VU meter coloring: 3 discrete color zones instead of a smooth gradient.
 ///
 /// Mimics classic hardware spectrum analyzers with distinct color bands:
@@ -98,27 +90,53 @@ pub fn retro_color(low: egui::Color32, high: egui::Color32, peak: egui::Color32,
     else { peak }
 }
 
+/// A color profile's gradient as (position, color) pairs, sorted, at least two.
+/// `adjust` is applied to every color (bar opacity, usually).
+pub fn gradient_colors(colors: &ColorProfile, adjust: impl Fn(egui::Color32) -> egui::Color32) -> Vec<(f32, egui::Color32)> {
+    colors.gradient_stops().map(|s| (s.position, adjust(to_egui_color(s.color)))).collect()
+}
+
 /// Choose the appropriate bar color based on the profile's `vu_coloring` setting.
 ///
-/// - Retro mode: 3 discrete color zones via [`retro_color`].
-/// - Gradient mode: smooth linear interpolation via [`lerp_color`].
+/// - Retro mode: 3 discrete color zones via [`retro_color`], using the
+///   gradient's two ends as `low` and `high`.
+/// - Gradient mode: the gradient sampled via [`sample_gradient`].
 pub fn bar_color(
-    low: egui::Color32,
-    high: egui::Color32,
+    gradient: &[(f32, egui::Color32)],
     peak: egui::Color32,
     t: f32,
     vu_coloring: crate::shared_state::VuColoring,
 ) -> egui::Color32 {
     match vu_coloring {
-        crate::shared_state::VuColoring::Retro => retro_color(low, high, peak, t),
-        crate::shared_state::VuColoring::Gradient => lerp_color(low, high, t),
+        crate::shared_state::VuColoring::Retro => {
+            let low = gradient.first().map_or(peak, |s| s.1);
+            let high = gradient.last().map_or(peak, |s| s.1);
+            retro_color(low, high, peak, t)
+        }
+        crate::shared_state::VuColoring::Gradient => sample_gradient(gradient, t),
     }
 }
 
+/// Rainbow color for a position across the spectrum, `t` 0 (bass, red)
+/// to 1 (treble, violet). Stops short of going round to red again.
+pub fn rainbow_color(t: f32) -> egui::Color32 {
+    egui::ecolor::Hsva::new(t.clamp(0.0, 1.0) * 0.8, 1.0, 1.0, 1.0).into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_rainbow_runs_red_to_violet() {
+        assert_eq!(rainbow_color(0.0), Color32::from_rgb(255, 0, 0));
+        let mid = rainbow_color(0.5);
+        assert!(mid.g() > mid.r() && mid.g() > mid.b(), "{:?}", mid);
+        let end = rainbow_color(1.0);
+        assert!(end.b() > 200 && end.r() > end.g(), "{:?}", end);
+        assert_eq!(rainbow_color(2.0), end);
+    }
+
     #[test]
     fn test_retro_color_low_zone() {
         let low = Color32::from_rgb(0, 255, 0);
@@ -159,7 +177,7 @@ mod tests {
         let high = Color32::from_rgb(255, 255, 0);
         let peak = Color32::from_rgb(255, 0, 0);
 
-        assert_eq!(bar_color(low, high, peak, 0.5, VuColoring::Retro), low);
+        assert_eq!(bar_color(&[(0.0, low), (1.0, high)], peak, 0.5, VuColoring::Retro), low);
     }
 
     #[test]
@@ -169,11 +187,34 @@ mod tests {
         let high = Color32::from_rgb(255, 255, 255);
         let peak = Color32::from_rgb(255, 0, 0);
 
-        let result = bar_color(low, high, peak, 0.5, VuColoring::Gradient);
+        let result = bar_color(&[(0.0, low), (1.0, high)], peak, 0.5, VuColoring::Gradient);
         assert_ne!(result, low);
         assert_ne!(result, high);
     }
 
+    #[test]
+    fn test_sample_gradient_between_stops() {
+        let red = Color32::from_rgb(255, 0, 0);
+        let green = Color32::from_rgb(0, 255, 0);
+        let blue = Color32::from_rgb(0, 0, 255);
+        let stops = [(0.0, red), (0.5, green), (1.0, blue)];
+
+        assert_eq!(sample_gradient(&stops, 0.0), red);
+        assert_eq!(sample_gradient(&stops, 0.5), green);
+        assert_eq!(sample_gradient(&stops, 1.0), blue);
+        assert_eq!(sample_gradient(&stops, 0.75), Color32::from_rgb(0, 127, 127));
+        // Flat past the outer stops
+        assert_eq!(sample_gradient(&[(0.2, red), (0.8, blue)], 0.1), red);
+        assert_eq!(sample_gradient(&[(0.2, red), (0.8, blue)], 0.9), blue);
+    }
+
+    #[test]
+    fn test_gradient_colors_migrates_low_high() {
+        let colors = ColorProfile::default();
+        let stops = gradient_colors(&colors, |c| c);
+        assert_eq!(stops, vec![(0.0, to_egui_color(colors.low)), (1.0, to_egui_color(colors.high))]);
+    }
+
     #[test]
     fn test_lerp_color_endpoints() {
         let a = Color32::from_rgb(0, 0, 0);
@@ -0,0 +1,72 @@
+// src/gui/thumbnails.rs
+//
+// Textures for the user preset thumbnails shown in the pickers. Each PNG is
+// loaded once and kept in egui memory; a preset without one gets it queued
+// for rendering (see `preset_thumbnail::Request`). The render job calls
+// `forget` once the PNG is written so the new image is picked up.
+
+use std::collections::HashMap;
+
+use eframe::egui;
+
+use crate::preset_thumbnail::{self, PresetKind};
+
+/// Size a thumbnail is shown at in the pickers (half the PNG)
+pub const DISPLAY_SIZE: egui::Vec2 = egui::vec2(48.0, 16.0);
+
+/// `None` while the PNG is being rendered, or for one that wouldn't load,
+/// so neither is retried every frame
+#[derive(Clone, Default)]
+struct Cache(HashMap<(PresetKind, String), Option<egui::TextureHandle>>);
+
+fn cache_id() -> egui::Id {
+    egui::Id::new("preset_thumbnails")
+}
+
+/// Thumbnail texture for a user preset. `request` queues it for rendering if
+/// there's no PNG yet.
+pub fn texture(
+    ctx: &egui::Context,
+    kind: PresetKind,
+    name: &str,
+    request: impl FnOnce(),
+) -> Option<egui::TextureHandle> {
+    let key = (kind, name.to_string());
+    if let Some(cached) = ctx.data(|d| d.get_temp::<Cache>(cache_id()).and_then(|c| c.0.get(&key).cloned())) {
+        return cached;
+    }
+
+    let path = preset_thumbnail::path_for(kind, name);
+    let img = if path.exists() {
+        match image::open(&path) {
+            Ok(img) => Some(img.to_rgba8()),
+            Err(e) => {
+                tracing::warn!("[GUI] Couldn't load thumbnail {:?}: {}", path, e);
+                None
+            }
+        }
+    } else {
+        request();
+        None
+    };
+
+    let texture = img.map(|img| {
+        let size = [img.width() as usize, img.height() as usize];
+        let color_image = egui::ColorImage::from_rgba_unmultiplied(size, img.as_raw());
+        ctx.load_texture(format!("thumb_{:?}_{}", kind, name), color_image, egui::TextureOptions::LINEAR)
+    });
+    ctx.data_mut(|d| d.get_temp_mut_or_default::<Cache>(cache_id()).0.insert(key, texture.clone()));
+    texture
+}
+
+/// Drop the cached texture after the preset was deleted or its PNG rewritten
+pub fn forget(ctx: &egui::Context, kind: PresetKind, name: &str) {
+    ctx.data_mut(|d| {
+        d.get_temp_mut_or_default::<Cache>(cache_id()).0.remove(&(kind, name.to_string()));
+    });
+}
+
+/// Small thumbnail image, for the start of a picker row
+pub fn show(ui: &mut egui::Ui, texture: &egui::TextureHandle) {
+    ui.add(egui::Image::new((texture.id(), DISPLAY_SIZE)).rounding(2.0));
+}
@@ -1,25 +1,161 @@
 use egui::{Painter, Rect, Stroke};
 use crate::media::MediaController;
-use crate::shared_state::{ColorProfile, PerformanceStats, VisualMode, 
-    VisualProfile, VisualizationData, MediaDisplayMode};
-use crate::gui::theme::{to_egui_color, db_to_px, lerp_color, bar_color};
+use crate::shared_state::{BarFill, ColorMapping, ColorProfile, PeakStyle, PerformanceStats, SplitDirection, SplitView, VisualMode,
+    VisualProfile, VisualizationData, MediaDisplayMode, TrackLinkSource};
+use crate::gui::theme::{to_egui_color, db_to_px, lerp_color, bar_color, gradient_colors, rainbow_color, sample_gradient};
+use crate::gui::gradient::gradient_bar_mesh;
 use crate::gui::widgets::draw_transport_controls;
 use crate::fft_processor::FFTProcessor;
+use crate::frame_timing::{FrameTimings, Stage};
+use crate::spectrogram::{self, SpectrogramView};
+use crate::gui::interpolation;
+use crate::gui::glow;
+use crate::gui::bar_style;
+use crate::gui::dot_matrix;
+#[cfg(feature = "scripting")]
+use crate::gui::script;
+#[cfg(feature = "plugins")]
+use crate::gui::plugins;
+use crate::gui::milkdrop;
 
 /// The physical thickness (in points) of the peak indicator blocks
 const PEAK_THICKNESS: f32 = 2.0;
 
+/// Most the content zoom slider allows
+pub const MAX_CONTENT_ZOOM: f32 = 4.0;
 
+/// Spectrogram minimap strip: height (points) and texture resolution
+const MINIMAP_HEIGHT: f32 = 14.0;
+const MINIMAP_COLUMNS: usize = 256;
+const MINIMAP_ROWS: usize = 32;
+
+
+/// Content zoom: run `draw` on a virtual canvas `zoom` times smaller than
+/// `rect` (same top-left corner), then scale everything it painted up to fill
+/// `rect`. Bars, gaps, strokes and text all grow together, so a small window
+/// gets the chunky look of a low-res display. The mouse position is mapped
+/// into the virtual canvas for the inspector.
+pub fn draw_zoomed(
+    painter: &Painter,
+    rect: Rect,
+    zoom: f32,
+    mouse_pos: Option<egui::Pos2>,
+    draw: impl FnOnce(&Painter, Rect, Option<egui::Pos2>),
+) {
+    let zoom = zoom.clamp(1.0, MAX_CONTENT_ZOOM);
+    if zoom <= 1.0 {
+        draw(painter, rect, mouse_pos);
+        return;
+    }
+
+    // Virtual top-left stays put: zoom * min + translation = min
+    let transform = egui::emath::TSTransform::new(rect.min.to_vec2() * (1.0 - zoom), zoom);
+    let virtual_rect = Rect::from_min_size(rect.min, rect.size() / zoom);
+    let virtual_mouse = mouse_pos.map(|p| transform.inverse() * p);
+
+    // Scale only the shapes added in between, not the rest of the layer
+    let layer = painter.layer_id();
+    let start = painter.ctx().graphics_mut(|g| g.entry(layer).next_idx());
+    draw(&painter.with_clip_rect(virtual_rect), virtual_rect, virtual_mouse);
+    painter.ctx().graphics_mut(|g| {
+        let list = g.entry(layer);
+        let end = list.next_idx();
+        list.transform_range(start, end, transform);
+    });
+}
+
+/// Smallest share of the area either half of the split view gets
+const MIN_SPLIT: f32 = 0.1;
+
+/// The two halves of the split view: the main mode's, then the second mode's
+pub fn split_rects(rect: Rect, split: &SplitView) -> (Rect, Rect) {
+    let ratio = split.ratio.clamp(MIN_SPLIT, 1.0 - MIN_SPLIT);
+    match split.direction {
+        SplitDirection::Horizontal => rect.split_top_bottom_at_fraction(ratio),
+        SplitDirection::Vertical => rect.split_left_right_at_fraction(ratio),
+    }
+}
+
+/// Where each mode on screen is drawn
+pub fn mode_rects(rect: Rect, profile: &VisualProfile) -> Vec<(VisualMode, Rect)> {
+    let modes: Vec<VisualMode> = profile.shown_modes().collect();
+    match modes[..] {
+        [main, second] => {
+            let (main_rect, second_rect) = split_rects(rect, &profile.split);
+            vec![(main, main_rect), (second, second_rect)]
+        }
+        _ => vec![(profile.visual_mode, rect)],
+    }
+}
+
+/// The few config values drawing needs, taken per frame so an extra window
+/// can pair the main settings with its own profile without copying the config
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DrawSettings {
+    pub noise_floor: f32,
+    pub inspector_enabled: bool,
+    pub show_stats: bool,
+    pub mini_mode: bool,
+    pub fps_target: Option<u32>,
+}
+
+impl DrawSettings {
+    /// `config`'s settings with `profile`'s engine overrides (the noise floor)
+    pub fn new(config: &crate::shared_state::AppConfig, profile: &VisualProfile) -> Self {
+        Self {
+            noise_floor: config.noise_floor_for(profile),
+            inspector_enabled: config.inspector_enabled,
+            show_stats: config.show_stats,
+            mini_mode: config.mini_mode,
+            fps_target: config.fps_cap.target(),
+        }
+    }
+}
+
+/// Draw the profile's mode, or both halves of its split view
 pub fn draw_main_visualizer(
     painter: &Painter,
     rect: Rect,
-    config: &crate::shared_state::AppConfig,
+    config: &DrawSettings,
+    profile: &VisualProfile,
+    colors: &crate::shared_state::ColorProfile,
+    data: &VisualizationData,
+    perf: &PerformanceStats,
+    mouse_pos: Option<egui::Pos2>,
+    safe_bar_count: usize,
+    reference: Option<&crate::snapshot::ReferenceSnapshot>,
+    response: Option<&[f32]>,
+) {
+    let views = mode_rects(rect, profile);
+    if views.len() == 1 {
+        draw_view(painter, rect, config, profile, colors, data, perf, mouse_pos, safe_bar_count, reference, response);
+        return;
+    }
+
+    for &(mode, view_rect) in &views {
+        let view = VisualProfile { visual_mode: mode, ..profile.clone() };
+        draw_view(&painter.with_clip_rect(view_rect), view_rect, config, &view, colors, data, perf, mouse_pos, safe_bar_count, reference, response);
+    }
+    // Hairline between the halves
+    let divider = match profile.split.direction {
+        SplitDirection::Horizontal => [views[1].1.left_top(), views[1].1.right_top()],
+        SplitDirection::Vertical => [views[1].1.left_top(), views[1].1.left_bottom()],
+    };
+    painter.line_segment(divider, Stroke::new(1.0, to_egui_color(colors.text).gamma_multiply(0.25)));
+}
+
+fn draw_view(
+    painter: &Painter,
+    rect: Rect,
+    config: &DrawSettings,
     profile: &VisualProfile, 
     colors: &crate::shared_state::ColorProfile,
     data: &VisualizationData,
     perf: &PerformanceStats,
     mouse_pos: Option<egui::Pos2>,
     safe_bar_count: usize,
+    reference: Option<&crate::snapshot::ReferenceSnapshot>,
+    response: Option<&[f32]>,
 ){
 
     // Determine the primary axis length (in physical/logical points) based on orientation
@@ -46,7 +182,7 @@ pub fn draw_main_visualizer(
 
     // Resolve hover interactions. We use the exact float slot width to reverse calculate
     // which mathematical slot the mouse cursor is currently residing in.
-    let hovered_bar_index = if config.inspector_enabled && profile.visual_mode != VisualMode::Oscilloscope {
+    let hovered_bar_index = if config.inspector_enabled && !config.mini_mode && profile.visual_mode != VisualMode::Oscilloscope {
         mouse_pos.and_then(|pos| {
             if rect.contains(pos) {
                 // Determine logical 'u' position based on orientation
@@ -80,7 +216,7 @@ pub fn draw_main_visualizer(
                 bar_width,
                 bar_slot_width,
                 hovered_bar_index,
-                config.noise_floor_db);
+                config.noise_floor);
         },
         VisualMode::SegmentedBars => {
             draw_segmented_bars(
@@ -92,7 +228,7 @@ pub fn draw_main_visualizer(
                 bar_width,
                 bar_slot_width,
                 hovered_bar_index,
-                config.noise_floor_db);
+                config.noise_floor);
         },
         VisualMode::LineSpectrum => {
             draw_line_spectrum(
@@ -102,7 +238,7 @@ pub fn draw_main_visualizer(
                 colors,
                 data,
                 hovered_bar_index,
-                config.noise_floor_db);
+                config.noise_floor);
         },
         VisualMode::Oscilloscope => {
             draw_oscilloscope(
@@ -113,8 +249,103 @@ pub fn draw_main_visualizer(
                 data,
             );
         },
+        VisualMode::Spectrogram => {
+            draw_spectrogram(
+                painter,
+                rect,
+                profile,
+                colors,
+                data,
+                !config.mini_mode && mouse_pos.is_some_and(|pos| rect.contains(pos)),
+                config.noise_floor);
+        },
+        VisualMode::DotMatrix => {
+            dot_matrix::draw_dot_matrix(painter, rect, profile, colors, data, config.noise_floor);
+        },
+        VisualMode::Mountain => {
+            draw_mountain(painter, rect, profile, colors, data, config.noise_floor);
+        },
+        VisualMode::Script => {
+            #[cfg(feature = "scripting")]
+            script::draw_script(painter, rect, &profile.script, colors, data, config.noise_floor);
+            #[cfg(not(feature = "scripting"))]
+            draw_missing_feature(painter, rect, colors, "This build has no script support.\nBuild with `--features scripting`.");
+        },
+        VisualMode::Plugin => {
+            #[cfg(feature = "plugins")]
+            plugins::draw_plugin(painter, rect, &profile.plugin, colors, data, config.noise_floor, perf.fft_info.sample_rate);
+            #[cfg(not(feature = "plugins"))]
+            draw_missing_feature(painter, rect, colors, "This build has no plugin support.\nBuild with `--features plugins`.");
+        },
+        VisualMode::MilkDrop => {
+            milkdrop::draw_milkdrop(painter, rect, &profile.milkdrop_preset, colors, data);
+        },
     }
-        
+
+    // Reference snapshot ghost line and measured response, at the same
+    // positions the mode draws at. Both only line up with their own bar count.
+    let overlay_points = |levels: &[f32]| -> Vec<egui::Pos2> {
+        if levels.len() != data.bars.len() {
+            return Vec::new();
+        }
+        match profile.visual_mode {
+            VisualMode::SolidBars | VisualMode::SegmentedBars => {
+                let levels = &levels[..display_bars.min(levels.len())];
+                let curve = interpolation::interpolate(levels, profile.bar_interpolation, interpolation::steps_for(bar_slot_width));
+                curve_points(rect, profile, &curve, |x| (x + 0.5) * bar_slot_width, config.noise_floor)
+            }
+            VisualMode::LineSpectrum => {
+                let count = levels.len() as f32;
+                let curve = interpolation::interpolate(levels, profile.bar_interpolation, interpolation::steps_for(max_u / count));
+                curve_points(rect, profile, &curve, |x| (x / count) * max_u, config.noise_floor)
+            }
+            VisualMode::Oscilloscope | VisualMode::Spectrogram | VisualMode::Mountain | VisualMode::DotMatrix | VisualMode::Script | VisualMode::Plugin | VisualMode::MilkDrop => Vec::new(),
+        }
+    };
+    // Audio-reactive bloom over the bars or the line
+    if profile.glow.enabled {
+        let floor = config.noise_floor;
+        let heights = || data.bars.iter().take(display_bars).map(move |&db| db_to_px(db, floor, 1.0));
+        let strength = glow::strength(&profile.glow, glow::loudness(heights()));
+        match profile.visual_mode {
+            VisualMode::SolidBars | VisualMode::SegmentedBars => {
+                let max_v = match profile.orientation {
+                    crate::shared_state::Orientation::BottomUp | crate::shared_state::Orientation::TopDown => rect.height(),
+                    crate::shared_state::Orientation::LeftRight | crate::shared_state::Orientation::RightLeft => rect.width(),
+                };
+                let gradient = gradient_colors(colors, |c| c.linear_multiply(profile.bar_opacity));
+                let band_color = band_color_fn(profile, display_bars);
+                let halos: Vec<(egui::Rect, egui::Color32)> = heights()
+                    .enumerate()
+                    .filter(|&(_, h)| h > 0.0)
+                    .map(|(i, h)| {
+                        let u = i as f32 * bar_slot_width;
+                        let p1 = map_uv_to_xy(rect, u, 0.0, profile.orientation);
+                        let p2 = map_uv_to_xy(rect, u + bar_width, h * max_v, profile.orientation);
+                        let color = band_color.as_ref().map_or_else(|| sample_gradient(&gradient, h), |band_color| band_color(i));
+                        (egui::Rect::from_two_pos(p1, p2), color)
+                    })
+                    .collect();
+                glow::bars(painter, &halos, strength);
+            }
+            VisualMode::LineSpectrum => {
+                glow::line(painter, &overlay_points(&data.bars), 2.0, to_egui_color(colors.high), strength);
+            }
+            VisualMode::Oscilloscope | VisualMode::Spectrogram | VisualMode::Mountain | VisualMode::DotMatrix | VisualMode::Script | VisualMode::Plugin | VisualMode::MilkDrop => {}
+        }
+    }
+
+    if let Some(reference) = reference {
+        let stroke = Stroke::new(1.5, to_egui_color(colors.inspector_fg).linear_multiply(0.7));
+        painter.extend(egui::Shape::dashed_line(&overlay_points(&reference.bars), stroke, 6.0, 4.0));
+    }
+    if let Some(levels) = response {
+        let points = overlay_points(levels);
+        if points.len() >= 2 {
+            painter.add(egui::Shape::line(points, Stroke::new(2.0, to_egui_color(colors.peak))));
+        }
+    }
+
     // Render Overlay UI...
     if let Some(index) = hovered_bar_index {
         draw_inspector_overlay(
@@ -126,21 +357,66 @@ pub fn draw_main_visualizer(
             perf,
             index,
             bar_slot_width,
-            config.noise_floor_db);
+            reference.and_then(|r| r.level(index, data.bars.len())),
+            config.noise_floor);
     }
 
-    if config.show_stats {
+    if config.show_stats && !config.mini_mode {
         draw_stats_overlay(
             painter,
             rect,
             colors,
             perf,
             display_bars,
-            profile.num_bars
+            profile.num_bars,
+            config.fps_target,
         );
     }
 }
 
+/// Stand-in for a mode this build was compiled without
+#[cfg(not(all(feature = "scripting", feature = "plugins")))]
+fn draw_missing_feature(painter: &Painter, rect: Rect, colors: &ColorProfile, message: &str) {
+    painter.text(
+        rect.left_top() + egui::vec2(8.0, 8.0),
+        egui::Align2::LEFT_TOP,
+        message,
+        egui::FontId::monospace(12.0),
+        to_egui_color(colors.text),
+    );
+}
+
+/// Screen points for a curve of (bar position, level dB) points, as
+/// `interpolation::interpolate` makes them. `u_of` maps a bar position to
+/// the baseline.
+fn curve_points(
+    rect: Rect,
+    profile: &VisualProfile,
+    curve: &[(f32, f32)],
+    u_of: impl Fn(f32) -> f32,
+    noise_floor: f32,
+) -> Vec<egui::Pos2> {
+    if curve.len() < 2 {
+        return Vec::new();
+    }
+    let max_v = match profile.orientation {
+        crate::shared_state::Orientation::BottomUp | crate::shared_state::Orientation::TopDown => rect.height(),
+        crate::shared_state::Orientation::LeftRight | crate::shared_state::Orientation::RightLeft => rect.width(),
+    };
+    curve
+        .iter()
+        .map(|&(x, db)| map_uv_to_xy(rect, u_of(x), db_to_px(db, noise_floor, max_v), profile.orientation))
+        .collect()
+}
+
+/// Per-bar color when the profile colors by frequency, `None` when it
+/// colors by amplitude
+fn band_color_fn(profile: &VisualProfile, bars: usize) -> Option<impl Fn(usize) -> egui::Color32> {
+    let last = bars.saturating_sub(1).max(1) as f32;
+    let opacity = profile.bar_opacity;
+    (profile.color_mapping == ColorMapping::Frequency).then_some(move |i: usize| rainbow_color(i as f32 / last).gamma_multiply(opacity))
+}
+
 /// Draw solid gradient bars
 pub fn draw_solid_bars(
     painter: &Painter,
@@ -156,6 +432,7 @@ pub fn draw_solid_bars(
     let low = to_egui_color(colors.low).gamma_multiply(profile.bar_opacity);
     let high = to_egui_color(colors.high).gamma_multiply(profile.bar_opacity);
     let peak = to_egui_color(colors.peak).gamma_multiply(profile.bar_opacity);
+    let gradient = gradient_colors(colors, |c| c.gamma_multiply(profile.bar_opacity));
 
     // Determine the maximum magnitude dimension for db_to_px scaling
     let (max_u, max_v) = match profile.orientation {
@@ -165,6 +442,8 @@ pub fn draw_solid_bars(
 
     // Protect against drawing phantom bars off-screen during rapid window shrink
     let display_bars = (max_u / bar_slot_width).floor() as usize;
+    let band_color = band_color_fn(profile, display_bars.min(data.bars.len()));
+    let style = profile.bar_style;
 
     for (i, &db) in data.bars.iter().take(display_bars).enumerate() {
         // Calculate the logical baseline coordinate.
@@ -179,10 +458,34 @@ pub fn draw_solid_bars(
         let norm_height = (bar_v / max_v).clamp(0.0, 1.0);
 
         let is_hovered = Some(i) == hovered_index;
+        let hover = |color: egui::Color32| if is_hovered { lerp_color(color, egui::Color32::WHITE, 0.5) } else { color };
+        // The bar's top color, for outlines and hatch lines
+        let tip_color = || hover(match &band_color {
+            Some(band_color) => band_color(i),
+            None => bar_color(&gradient, peak, norm_height, profile.vu_coloring),
+        });
+
+        let bar_rect = egui::Rect::from_two_pos(
+            map_uv_to_xy(rect, u, 0.0, profile.orientation),
+            map_uv_to_xy(rect, u + bar_width, bar_v, profile.orientation),
+        );
+        let cap = if style.rounded_caps { bar_style::cap_radius(bar_width, bar_v) } else { 0.0 };
+        let rounding = bar_style::tip_rounding(profile.orientation, cap);
+        // Hatched bars get a dim fill under their lines
+        let fill_alpha = if style.fill == BarFill::Hatched { bar_style::HATCH_FILL } else { 1.0 };
 
-        use egui::epaint::Vertex;
+        if style.shadow && bar_v > 0.0 {
+            bar_style::shadow(painter, bar_rect, rounding);
+        }
 
-        if profile.vu_coloring == crate::shared_state::VuColoring::Retro {
+        if style.fill == BarFill::Outline {
+            if bar_v <= 0.0 { continue; }
+            bar_style::outline(painter, bar_rect, rounding, tip_color());
+        } else if let Some(band_color) = &band_color {
+            // Frequency mode: one flat color per bar
+            if bar_v <= 0.0 { continue; }
+            painter.rect_filled(bar_rect, rounding, hover(band_color(i)).gamma_multiply(fill_alpha));
+        } else if profile.vu_coloring == crate::shared_state::VuColoring::Retro {
             if bar_v <= 0.0 { continue; }
             // Retro mode: draw up to 3 discrete color zones within each bar
             let zone_boundaries: &[(f32, egui::Color32)] = &[
@@ -194,53 +497,95 @@ pub fn draw_solid_bars(
             for &(zone_end_norm, zone_color) in zone_boundaries {
                 let zone_end_v = (zone_end_norm * max_v).min(bar_v);
                 if zone_start_v >= bar_v { break; }
-                let mut color = zone_color;
-                if is_hovered { color = lerp_color(color, egui::Color32::WHITE, 0.5); }
+                let color = hover(zone_color).gamma_multiply(fill_alpha);
 
                 let p1 = map_uv_to_xy(rect, u, zone_start_v, profile.orientation);
                 let p2 = map_uv_to_xy(rect, u + bar_width, zone_end_v, profile.orientation);
                 let zone_rect = egui::Rect::from_two_pos(p1, p2);
-                painter.rect_filled(zone_rect, 0.0, color);
+                // Only the zone holding the tip gets the rounded cap
+                let zone_rounding = if zone_end_v >= bar_v { rounding } else { egui::Rounding::ZERO };
+                painter.rect_filled(zone_rect, zone_rounding, color);
 
                 zone_start_v = zone_end_v;
             }
         } else {
-            // Gradient mode: GPU-interpolated smooth gradient from low to tip color
-            let mut color = bar_color(low, high, peak, norm_height, profile.vu_coloring);
-            if is_hovered { color = lerp_color(color, egui::Color32::WHITE, 0.5); }
-
-            let p_base_left = map_uv_to_xy(rect, u, 0.0, profile.orientation);
-            let p_base_right = map_uv_to_xy(rect, u + bar_width, 0.0, profile.orientation);
-            let p_tip_right = map_uv_to_xy(rect, u + bar_width, bar_v, profile.orientation);
-            let p_tip_left = map_uv_to_xy(rect, u, bar_v, profile.orientation);
-
-            let mut mesh = egui::Mesh::default();
-            let v_idx = mesh.vertices.len() as u32;
-            mesh.vertices.push(Vertex { pos: p_base_left, uv: egui::Pos2::ZERO, color: low });
-            mesh.vertices.push(Vertex { pos: p_base_right, uv: egui::Pos2::ZERO, color: low });
-            mesh.vertices.push(Vertex { pos: p_tip_right, uv: egui::Pos2::ZERO, color: color });
-            mesh.vertices.push(Vertex { pos: p_tip_left, uv: egui::Pos2::ZERO, color: color });
-
-            mesh.add_triangle(v_idx, v_idx + 1, v_idx + 2);
-            mesh.add_triangle(v_idx, v_idx + 2, v_idx + 3);
+            // Gradient mode: GPU-interpolated between the gradient's stops.
+            // With a rounded cap the mesh stops where the cap begins and a
+            // circle in the same color finishes the bar.
+            let body = if cap > 0.0 { ((bar_v - cap) / max_v).clamp(0.0, 1.0) } else { norm_height };
+            let mut mesh = gradient_bar_mesh(&gradient, body, |t| [
+                map_uv_to_xy(rect, u, t * max_v, profile.orientation),
+                map_uv_to_xy(rect, u + bar_width, t * max_v, profile.orientation),
+            ]);
+            for vertex in &mut mesh.vertices { vertex.color = vertex.color.gamma_multiply(fill_alpha); }
+            if is_hovered {
+                // Highlight the tip, like the bar's top color
+                let tip = mesh.vertices.len() - 2;
+                for vertex in &mut mesh.vertices[tip..] { vertex.color = lerp_color(vertex.color, egui::Color32::WHITE, 0.5); }
+            }
             painter.add(egui::Shape::mesh(mesh));
+            if cap > 0.0 {
+                let center = map_uv_to_xy(rect, u + bar_width / 2.0, bar_v - cap, profile.orientation);
+                painter.circle_filled(center, cap, hover(sample_gradient(&gradient, body)).gamma_multiply(fill_alpha));
+            }
+        }
+
+        if style.fill == BarFill::Hatched && bar_v > 0.0 {
+            bar_style::hatch(painter, bar_rect, tip_color());
         }
 
         // Peaks
         if profile.show_peaks && i < data.peaks.len() {
             let peak_v = db_to_px(data.peaks[i], noise_floor_db, max_v);
-            let peak_thickness = PEAK_THICKNESS;
-            
-            // Just grab the two opposing logical corners
-            let p1 = map_uv_to_xy(rect, u, peak_v, profile.orientation);
-            let p2 = map_uv_to_xy(rect, u + bar_width, peak_v + peak_thickness, profile.orientation);
+            draw_peak(painter, rect, profile, u, bar_width, bar_v, peak_v, peak);
+        }
+    }    
+}
 
+/// Draw one bar's held peak in the profile's `PeakStyle`. `bar_v` and
+/// `peak_v` are logical heights along the bar, like everything `map_uv_to_xy` takes.
+fn draw_peak(
+    painter: &Painter,
+    rect: Rect,
+    profile: &VisualProfile,
+    u: f32,
+    bar_width: f32,
+    bar_v: f32,
+    peak_v: f32,
+    color: egui::Color32,
+) {
+    let at = |u: f32, v: f32| map_uv_to_xy(rect, u, v, profile.orientation);
+    match profile.peak_style {
+        PeakStyle::Block => {
             // from_two_pos automatically handles sorting the coordinates, 
             // no matter which cardinal direction they were mapped to!
-            let peak_rect = egui::Rect::from_two_pos(p1, p2);
-            painter.rect_filled(peak_rect, 0.0, peak);
+            let peak_rect = egui::Rect::from_two_pos(at(u, peak_v), at(u + bar_width, peak_v + PEAK_THICKNESS));
+            painter.rect_filled(peak_rect, 0.0, color);
         }
-    }    
+        PeakStyle::Line => {
+            painter.line_segment([at(u, peak_v), at(u + bar_width, peak_v)], Stroke::new(1.0, color));
+        }
+        PeakStyle::Dot | PeakStyle::FallingDot => {
+            // Sits on top of the peak level, so it rests on the bar
+            let radius = (bar_width / 2.0).clamp(1.5, 4.0);
+            painter.circle_filled(at(u + bar_width / 2.0, peak_v + radius), radius, color);
+        }
+        PeakStyle::Trail => {
+            // Fades out from the peak down to the bar's tip
+            if peak_v > bar_v {
+                let mut mesh = egui::Mesh::default();
+                for (v, c) in [(bar_v, egui::Color32::TRANSPARENT), (peak_v, color.gamma_multiply(0.6))] {
+                    for pos in [at(u, v), at(u + bar_width, v)] {
+                        mesh.vertices.push(egui::epaint::Vertex { pos, uv: egui::Pos2::ZERO, color: c });
+                    }
+                }
+                mesh.add_triangle(0, 1, 3);
+                mesh.add_triangle(0, 3, 2);
+                painter.add(egui::Shape::mesh(mesh));
+            }
+            painter.line_segment([at(u, peak_v), at(u + bar_width, peak_v)], Stroke::new(1.0, color));
+        }
+    }
 }
 
 /// Draws the "Segmented" (LED-style) audio visualizer mode.
@@ -281,8 +626,7 @@ pub fn draw_segmented_bars(
     noise_floor_db: f32
 ) {
     // 1. Resolve Colors & Opacity
-    let low = to_egui_color(colors.low).linear_multiply(profile.bar_opacity);
-    let high = to_egui_color(colors.high).linear_multiply(profile.bar_opacity);
+    let gradient = gradient_colors(colors, |c| c.linear_multiply(profile.bar_opacity));
     let peak_color = to_egui_color(colors.peak).linear_multiply(profile.bar_opacity);
 
     // Determine the maximum magnitude dimension for LOD and scalling
@@ -344,10 +688,30 @@ pub fn draw_segmented_bars(
         mesh.add_triangle(idx, idx + 1 , idx + 2);
         mesh.add_triangle(idx, idx + 2 , idx + 3);
     };
+    // Outline-only segments: four thin edges, still in the one mesh
+    let push_frame = |mesh: &mut egui::Mesh, r: egui::Rect, color: egui::Color32| {
+        let w = bar_style::OUTLINE_WIDTH.min(r.width() / 2.0).min(r.height() / 2.0);
+        push_rect(mesh, egui::Rect::from_min_max(r.min, egui::pos2(r.max.x, r.min.y + w)), color);
+        push_rect(mesh, egui::Rect::from_min_max(egui::pos2(r.min.x, r.max.y - w), r.max), color);
+        push_rect(mesh, egui::Rect::from_min_max(egui::pos2(r.min.x, r.min.y + w), egui::pos2(r.min.x + w, r.max.y - w)), color);
+        push_rect(mesh, egui::Rect::from_min_max(egui::pos2(r.max.x - w, r.min.y + w), egui::pos2(r.max.x, r.max.y - w)), color);
+    };
+
+    let style = profile.bar_style;
+    let fill_alpha = if style.fill == BarFill::Hatched { bar_style::HATCH_FILL } else { 1.0 };
+    let cap_rounding = bar_style::tip_rounding(
+        profile.orientation,
+        if style.rounded_caps { bar_style::cap_radius(bar_width, seg_h) } else { 0.0 },
+    );
+    // Drawn over the mesh once it's submitted: (rect, color) of rounded tip
+    // segments and of hatched bars
+    let mut caps: Vec<(egui::Rect, egui::Color32)> = Vec::new();
+    let mut hatches: Vec<(egui::Rect, egui::Color32)> = Vec::new();
     
 
     // Protect against geometry overdraw during rapid resize events
     let display_bars = (max_u / bar_slot_width).floor() as usize;
+    let band_color = band_color_fn(profile, display_bars.min(data.bars.len()));
 
     // 3. Render Each Bar
     for (i, &db) in data.bars.iter().take(display_bars).enumerate() {
@@ -362,6 +726,16 @@ pub fn draw_segmented_bars(
             
             // Determine how many segments fit in this height
             let num_segments = (total_v / total_seg_h).floor() as i32;
+
+            // The lit part of the bar, for shadows and hatching. Shadows go
+            // on the painter now so they land under the mesh.
+            let lit_rect = egui::Rect::from_two_pos(
+                map_uv_to_xy(rect, u, 0.0, profile.orientation),
+                map_uv_to_xy(rect, u + bar_width, num_segments as f32 * total_seg_h - seg_gap, profile.orientation),
+            );
+            if style.shadow && num_segments > 0 {
+                bar_style::shadow(painter, lit_rect, egui::Rounding::ZERO);
+            }
             
             // --- Draw Active Segments ---
             for s in 0..num_segments {
@@ -372,13 +746,27 @@ pub fn draw_segmented_bars(
                 // Use segment midpoint for zone classification to avoid off-by-one at boundaries
                 let seg_center_v = v_offset + seg_h / 2.0;
                 let norm_h = (seg_center_v / max_v).clamp(0.0, 1.0);
-                let color = bar_color(low, high, peak_color, norm_h, profile.vu_coloring);
+                let color = match &band_color {
+                    Some(band_color) => band_color(i),
+                    None => bar_color(&gradient, peak_color, norm_h, profile.vu_coloring),
+                };
 
                 // Map logical bounds to physical rect
                 let p1 = map_uv_to_xy(rect, u, v_offset, profile.orientation);
                 let p2 = map_uv_to_xy(rect, u + bar_width, v_offset + seg_h, profile.orientation);
                 
-                push_rect(&mut master_mesh, egui::Rect::from_two_pos(p1, p2), color);
+                let seg_rect = egui::Rect::from_two_pos(p1, p2);
+
+                if style.rounded_caps && s == num_segments - 1 {
+                    caps.push((seg_rect, color));
+                } else if style.fill == BarFill::Outline {
+                    push_frame(&mut master_mesh, seg_rect, color);
+                } else {
+                    push_rect(&mut master_mesh, seg_rect, color.gamma_multiply(fill_alpha));
+                }
+                if style.fill == BarFill::Hatched && s == num_segments - 1 {
+                    hatches.push((lit_rect, color));
+                }
             }
 
             // --- Draw Peak Indicators ---
@@ -392,7 +780,24 @@ pub fn draw_segmented_bars(
                 let p1  = map_uv_to_xy(rect, u, v_offset, profile.orientation);
                 let p2 = map_uv_to_xy(rect, u + bar_width, v_offset + seg_h, profile.orientation);
                 
-                push_rect(&mut master_mesh, egui::Rect::from_two_pos(p1, p2), peak_color);
+                match profile.peak_style {
+                    PeakStyle::Block => push_rect(&mut master_mesh, egui::Rect::from_two_pos(p1, p2), peak_color),
+                    PeakStyle::Trail => {
+                        // LED trail: the unlit segments up to the peak, brightening towards it
+                        let first = num_segments.max(0) as f32;
+                        let steps = (peak_seg_idx - first).max(0.0) + 1.0;
+                        for g in 0..steps as i32 {
+                            let gap_v = (first + g as f32) * total_seg_h;
+                            let alpha = (g as f32 + 1.0) / steps;
+                            let gp1 = map_uv_to_xy(rect, u, gap_v, profile.orientation);
+                            let gp2 = map_uv_to_xy(rect, u + bar_width, gap_v + seg_h, profile.orientation);
+                            push_rect(&mut master_mesh, egui::Rect::from_two_pos(gp1, gp2), peak_color.linear_multiply(alpha));
+                        }
+                    }
+                    // Thin marks; the painter puts them under the mesh, but
+                    // above the bar there's nothing to cover them
+                    _ => draw_peak(painter, rect, profile, u, bar_width, total_v, v_offset, peak_color),
+                }
 
                 // --- Fill Gap to Peak (Warning Mode) ---
                 // If enabled, fills the empty space between the current bar level and the peak
@@ -416,6 +821,18 @@ pub fn draw_segmented_bars(
 
     // 4. Submit the massive batch to the GPU for ONE draw call
     painter.add(egui::Shape::mesh(master_mesh));
+
+    // 5. Bar style extras, which the mesh can't express
+    for (seg_rect, color) in caps {
+        if style.fill == BarFill::Outline {
+            bar_style::outline(painter, seg_rect, cap_rounding, color);
+        } else {
+            painter.rect_filled(seg_rect, cap_rounding, color.gamma_multiply(fill_alpha));
+        }
+    }
+    for (lit_rect, color) in hatches {
+        bar_style::hatch(painter, lit_rect, color);
+    }
 }
 
 /// Draw line representation of spetrum data
@@ -443,10 +860,13 @@ pub fn draw_line_spectrum(
         }
     };
 
-    // Pre-calculate points using logical (u,v) mapping
-    let points: Vec<egui::Pos2> = data.bars.iter().enumerate().map(|(i, &db)| {
+    // Pre-calculate points using logical (u,v) mapping, through the
+    // profile's interpolation between bars
+    let count = data.bars.len() as f32;
+    let curve = interpolation::interpolate(&data.bars, profile.bar_interpolation, interpolation::steps_for(max_u / count));
+    let points: Vec<egui::Pos2> = curve.iter().map(|&(x, db)| {
         // Logical position along the baseline
-        let u = (i as f32 / data.bars.len() as f32) * max_u;
+        let u = (x / count) * max_u;
 
         // Logical magnitude extending from the baseline
         let v = db_to_px(db, noise_floor_db, max_v);
@@ -477,7 +897,10 @@ pub fn draw_line_spectrum(
 
     // Draw hover Indicator - Restored!
     if let Some(idx) = hovered_index {
-        if let Some(point) = points.get(idx){
+        let bar_point = data.bars.get(idx).map(|&db| {
+            map_uv_to_xy(rect, (idx as f32 / count) * max_u, db_to_px(db, noise_floor_db, max_v), profile.orientation)
+        });
+        if let Some(point) = bar_point.as_ref() {
             // Bright white dot with colored glow
             painter.circle_filled(*point, 4.0, egui::Color32::WHITE);
             painter.circle_stroke(*point, 5.0, egui::Stroke::new(1.0, core_c));
@@ -485,7 +908,10 @@ pub fn draw_line_spectrum(
     }
 }
 
-/// Draws a classic oscillioscope waveform
+/// Draws a classic oscillioscope waveform: the mono mix, plus the left,
+/// right and difference traces when the profile asks for them and the
+/// source is stereo. Each trace spans the full width, so the L/R blocks
+/// (captured before resampling) still line up with the mix in time.
 pub fn draw_oscilloscope(
     painter: &Painter,
     rect: Rect,
@@ -507,33 +933,331 @@ pub fn draw_oscilloscope(
         }
     };
 
-    let samples = &data.waveform;
-    let len = samples.len();
-
     // Shift our logical "0" to the middle of the magnitdue axis
     let middle_v = max_v / 2.0;
-    let v_scale = max_v * 0.45; // Leave some cushion for the pushing
+    let v_scale = max_v * 0.45 * scope_gain(profile, data.scope_level); // Leave some cushion for the pushing
+
+    let trace_points = |samples: &mut dyn ExactSizeIterator<Item = f32>| -> Vec<egui::Pos2> {
+        let len = samples.len();
+        samples
+            .enumerate()
+            .map(|(i, sample)| {
+                // u progresses steadily across the available baseline
+                let u = (i as f32 / len as f32) * max_u;
+
+                // v oscillates around the middle point (clamped, a trace past the edge is no use)
+                let v = middle_v + (sample * v_scale).clamp(-middle_v, middle_v);
+
+                //Translate to physical screen coordinates
+                map_uv_to_xy(rect, u, v, profile.orientation)
+            })
+            .collect()
+    };
 
-    let points: Vec<egui::Pos2> = samples
-        .iter()
-        .enumerate()
-        .map(|(i, &sample)|{
-            // u progresses steadily across the available baseline
-            let u = (i as f32 / len as f32) * max_u;
+    // 2. Stereo traces go underneath the mix
+    let traces = profile.scope_traces;
+    let (left, right) = (&data.waveform_left, &data.waveform_right);
+    let stereo = !left.is_empty() && left.len() == right.len();
+    let mut legend: Vec<(&str, egui::Color32)> = Vec::new();
+    if stereo {
+        let left_color = to_egui_color(colors.low).gamma_multiply(profile.bar_opacity);
+        let right_color = to_egui_color(colors.peak).gamma_multiply(profile.bar_opacity);
+        let difference_color = to_egui_color(colors.text).gamma_multiply(profile.bar_opacity * 0.8);
+
+        if traces.left {
+            painter.add(egui::Shape::line(trace_points(&mut left.iter().copied()), Stroke::new(1.2, left_color)));
+            legend.push(("L", left_color));
+        }
+        if traces.right {
+            painter.add(egui::Shape::line(trace_points(&mut right.iter().copied()), Stroke::new(1.2, right_color)));
+            legend.push(("R", right_color));
+        }
+        if traces.difference {
+            let mut difference = left.iter().zip(right).map(|(l, r)| (l - r) * 0.5);
+            painter.add(egui::Shape::line(trace_points(&mut difference), Stroke::new(1.2, difference_color)));
+            legend.push(("L-R", difference_color));
+        }
+    }
 
-            // v oscillates around the middle point
-            let v = middle_v + (sample * v_scale);
-            
-            //Translate to physical screen coordinates
-            map_uv_to_xy(rect, u, v, profile.orientation)
+    // Label the traces once there's more than one to tell apart
+    if traces.mix && !legend.is_empty() {
+        legend.insert(0, ("L+R", line_color));
+    }
+    if legend.len() > 1 {
+        let mut pos = rect.left_top() + egui::vec2(6.0, 4.0);
+        for (name, color) in &legend {
+            let label = painter.text(pos, egui::Align2::LEFT_TOP, *name, egui::FontId::monospace(11.0), *color);
+            pos.x = label.right() + 8.0;
+        }
+    }
+
+    if !traces.mix {
+        return;
+    }
+
+    let points = trace_points(&mut data.waveform.iter().copied());
+
+    if !profile.scope_color_by_frequency {
+        painter.add(egui::Shape::line(points, Stroke::new(1.5, line_color)));
+        return;
+    }
+
+    // Color by band: split the trace into runs of the same (quantized) tint,
+    // so a few dozen polylines are drawn instead of one segment per sample
+    const TINT_STEPS: f32 = 16.0;
+    let low = to_egui_color(colors.low).gamma_multiply(profile.bar_opacity);
+    let high = line_color;
+    let step_of = |i: usize| (data.waveform_tint.get(i).copied().unwrap_or(0.5) * TINT_STEPS).round();
+
+    let mut run_start = 0;
+    for i in 1..=points.len() {
+        let run_ends = i == points.len() || step_of(i) != step_of(run_start);
+        if run_ends {
+            // Runs share their end point so the trace stays connected
+            let end = i.min(points.len() - 1);
+            let color = lerp_color(low, high, step_of(run_start) / TINT_STEPS);
+            painter.add(egui::Shape::line(points[run_start..=end].to_vec(), Stroke::new(1.5, color)));
+            run_start = i;
+        }
+    }
+}
+
+/// Vertical gain for the scope: the profile's sensitivity, times the
+/// autoscale zoom (recent peak to full height, at most `scope_max_zoom`)
+pub fn scope_gain(profile: &VisualProfile, scope_level: f32) -> f32 {
+    let zoom = if profile.scope_autoscale {
+        (1.0 / scope_level.max(1e-6)).min(profile.scope_max_zoom.max(1.0))
+    } else {
+        1.0
+    };
+    profile.sensitivity * zoom
+}
+
+/// Waterfall: frequency runs along the baseline like the bar modes, time runs
+/// away from it (newest at the baseline). Which slice of history is shown comes
+/// from the `SpectrogramView` kept in egui memory (see `spectrogram_view`).
+pub fn draw_spectrogram(
+    painter: &Painter,
+    rect: Rect,
+    profile: &VisualProfile,
+    colors: &ColorProfile,
+    data: &VisualizationData,
+    hovered: bool,
+    noise_floor_db: f32,
+) {
+    let history = &data.history;
+    let view = spectrogram_view(painter.ctx());
+
+    let (max_u, max_v) = match profile.orientation {
+        crate::shared_state::Orientation::BottomUp | crate::shared_state::Orientation::TopDown => (rect.width(), rect.height()),
+        crate::shared_state::Orientation::LeftRight | crate::shared_state::Orientation::RightLeft => (rect.height(), rect.width()),
+    };
+
+    // One color per quantized level, recomputed each frame (256 entries)
+    let gradient = gradient_colors(colors, |c| c.gamma_multiply(profile.bar_opacity));
+    let peak = to_egui_color(colors.peak).gamma_multiply(profile.bar_opacity);
+    let lut: Vec<egui::Color32> = (0..=255u8)
+        .map(|q| {
+            let t = db_to_px(spectrogram::level_db(q), noise_floor_db, 1.0);
+            if t <= 0.0 {
+                egui::Color32::TRANSPARENT
+            } else {
+                bar_color(&gradient, peak, t, profile.vu_coloring).gamma_multiply(t)
+            }
         })
         .collect();
 
-   
-    painter.add(egui::Shape::line(
-        points,
-        Stroke::new(1.5, line_color),
-    ));
+    // No more texture rows than there are pixels along the time axis
+    let (start, end) = view.visible(history);
+    let span = (end - start).max(1);
+    let rows = (span as usize).min(max_v.max(1.0) as usize).max(1);
+
+    let mut image = egui::ColorImage::new([spectrogram::ROWS, rows], egui::Color32::TRANSPARENT);
+    for j in 0..rows {
+        let back = j as u64 * span / rows as u64;
+        if let Some(column) = end.checked_sub(back + 1).and_then(|i| history.column(i)) {
+            let row = &mut image.pixels[j * spectrogram::ROWS..(j + 1) * spectrogram::ROWS];
+            for (px, &q) in row.iter_mut().zip(column) {
+                *px = lut[q as usize];
+            }
+        }
+    }
+    let texture = upload_texture(painter.ctx(), "spectrogram", image);
+
+    // Texture u = frequency, v = age; map_uv_to_xy handles the orientation
+    let mut mesh = egui::Mesh::with_texture(texture.id());
+    for (fu, tv) in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)] {
+        mesh.vertices.push(egui::epaint::Vertex {
+            pos: map_uv_to_xy(rect, fu * max_u, tv * max_v, profile.orientation),
+            uv: egui::pos2(fu, tv),
+            color: egui::Color32::WHITE,
+        });
+    }
+    mesh.add_triangle(0, 1, 2);
+    mesh.add_triangle(2, 1, 3);
+    painter.add(egui::Shape::mesh(mesh));
+
+    if hovered || !view.is_live() {
+        draw_spectrogram_minimap(painter, rect, colors, history, &view, &lut);
+    }
+}
+
+/// Whole retained history in a strip along the bottom edge, with the visible
+/// window outlined. Click/drag handling lives in `widgets::handle_spectrogram_input`.
+fn draw_spectrogram_minimap(
+    painter: &Painter,
+    rect: Rect,
+    colors: &ColorProfile,
+    history: &spectrogram::SpectrogramHistory,
+    view: &SpectrogramView,
+    lut: &[egui::Color32],
+) {
+    let strip = spectrogram_minimap_rect(rect);
+    let bg_color = to_egui_color(colors.inspector_bg);
+    let text_color = to_egui_color(colors.inspector_fg);
+    painter.rect_filled(strip.expand(1.0), 2.0, bg_color);
+
+    if !history.is_empty() {
+        // Time left to right, low frequencies at the bottom
+        let len = history.len() as u64;
+        let mut image = egui::ColorImage::new([MINIMAP_COLUMNS, MINIMAP_ROWS], egui::Color32::TRANSPARENT);
+        for x in 0..MINIMAP_COLUMNS {
+            let index = history.oldest() + x as u64 * len / MINIMAP_COLUMNS as u64;
+            let Some(column) = history.column(index) else { continue };
+            for (y, group) in column.chunks(spectrogram::ROWS / MINIMAP_ROWS).enumerate() {
+                let q = group.iter().copied().max().unwrap_or(0);
+                image.pixels[(MINIMAP_ROWS - 1 - y) * MINIMAP_COLUMNS + x] = lut[q as usize];
+            }
+        }
+        let texture = upload_texture(painter.ctx(), "spectrogram_minimap", image);
+        painter.image(
+            texture.id(),
+            strip,
+            Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+            egui::Color32::WHITE,
+        );
+
+        // Visible window
+        let (start, end) = view.visible(history);
+        let to_x = |i: u64| {
+            let t = i.saturating_sub(history.oldest()) as f32 / len as f32;
+            strip.left() + t.clamp(0.0, 1.0) * strip.width()
+        };
+        let window = Rect::from_x_y_ranges(to_x(start)..=to_x(end).max(to_x(start) + 2.0), strip.y_range());
+        painter.rect_stroke(window, 1.0, Stroke::new(1.0, to_egui_color(colors.peak)));
+    }
+
+    let status = if view.is_live() {
+        format!("● LIVE   {:.0} s", view.span_sec)
+    } else {
+        format!("⏸ -{:.1} s   {:.0} s", view.seconds_behind(history), view.span_sec)
+    };
+    painter.text(
+        strip.right_top() - egui::vec2(0.0, 3.0),
+        egui::Align2::RIGHT_BOTTOM,
+        status,
+        egui::FontId::monospace(10.0),
+        text_color,
+    );
+}
+
+/// Where the spectrogram minimap sits inside the visualizer rect
+pub fn spectrogram_minimap_rect(rect: Rect) -> Rect {
+    Rect::from_min_size(
+        egui::pos2(rect.left() + 6.0, rect.bottom() - MINIMAP_HEIGHT - 6.0),
+        egui::vec2((rect.width() - 12.0).max(1.0), MINIMAP_HEIGHT),
+    )
+}
+
+/// History columns between two Mountain ridges (0.1 s at 30 columns/s)
+const MOUNTAIN_STRIDE: u64 = 3;
+/// Points per ridge across the frequency axis
+const MOUNTAIN_POINTS: usize = 128;
+/// How much smaller the farthest ridge is than the nearest (perspective)
+const MOUNTAIN_FAR_SCALE: f32 = 0.3;
+/// Where the horizon sits, from the top (fraction of the height)
+const MOUNTAIN_HORIZON: f32 = 0.25;
+
+/// Pseudo-3D landscape: the spectrogram history as ridges receding towards
+/// the horizon, newest in front. Drawn back to front, each ridge filled down
+/// to its baseline so it hides the ones behind. Always upright, whatever the
+/// orientation.
+pub fn draw_mountain(
+    painter: &Painter,
+    rect: Rect,
+    profile: &VisualProfile,
+    colors: &ColorProfile,
+    data: &VisualizationData,
+    noise_floor_db: f32,
+) {
+    let ridges: Vec<&[u8]> = data.history.recent(profile.mountain_lines.max(2) as usize, MOUNTAIN_STRIDE).collect();
+    if ridges.is_empty() {
+        return;
+    }
+    let gradient = gradient_colors(colors, |c| c.gamma_multiply(profile.bar_opacity));
+    let background = to_egui_color(colors.background);
+    let horizon = rect.top() + rect.height() * MOUNTAIN_HORIZON;
+    let last = (profile.mountain_lines.max(2) - 1) as f32;
+
+    for (k, column) in ridges.iter().enumerate().rev() {
+        // 0 at the front, 1 at the horizon
+        let depth = k as f32 / last;
+        let scale = 1.0 / (1.0 + depth * (1.0 / MOUNTAIN_FAR_SCALE - 1.0));
+        let baseline = horizon + (rect.bottom() - horizon) * (scale - MOUNTAIN_FAR_SCALE) / (1.0 - MOUNTAIN_FAR_SCALE);
+        let width = rect.width() * scale;
+        let left = rect.center().x - width / 2.0;
+        let amplitude = rect.height() * (1.0 - MOUNTAIN_HORIZON) * scale;
+        // Far ridges fade into the background like haze
+        let haze = depth * 0.7;
+
+        let mut mesh = egui::Mesh::default();
+        let mut ridge = Vec::with_capacity(MOUNTAIN_POINTS);
+        for p in 0..MOUNTAIN_POINTS {
+            let t = p as f32 / (MOUNTAIN_POINTS - 1) as f32;
+            let row = ((t * (spectrogram::ROWS - 1) as f32).round() as usize).min(spectrogram::ROWS - 1);
+            let level = db_to_px(spectrogram::level_db(column[row]), noise_floor_db, 1.0);
+            let x = left + t * width;
+            let top = egui::pos2(x, baseline - level * amplitude);
+            ridge.push(top);
+
+            let idx = mesh.vertices.len() as u32;
+            mesh.colored_vertex(top, lerp_color(sample_gradient(&gradient, level), background, haze + (1.0 - level) * 0.5));
+            mesh.colored_vertex(egui::pos2(x, baseline), background);
+            if p > 0 {
+                mesh.add_triangle(idx - 2, idx - 1, idx);
+                mesh.add_triangle(idx - 1, idx, idx + 1);
+            }
+        }
+        painter.add(egui::Shape::mesh(mesh));
+        let line = lerp_color(to_egui_color(colors.high), background, haze).gamma_multiply(profile.bar_opacity);
+        painter.add(egui::Shape::line(ridge, Stroke::new(1.0 + scale, line)));
+    }
+}
+
+/// The spectrogram view lives in egui memory so the input handler and the
+/// renderer share it without another field threaded through every call.
+pub fn spectrogram_view(ctx: &egui::Context) -> SpectrogramView {
+    ctx.data(|d| d.get_temp(egui::Id::new("spectrogram_view"))).unwrap_or_default()
+}
+
+pub fn set_spectrogram_view(ctx: &egui::Context, view: SpectrogramView) {
+    ctx.data_mut(|d| d.insert_temp(egui::Id::new("spectrogram_view"), view));
+}
+
+/// Reuse one texture per `name` across frames instead of allocating a new one
+fn upload_texture(ctx: &egui::Context, name: &str, image: egui::ColorImage) -> egui::TextureHandle {
+    let id = egui::Id::new(name);
+    match ctx.data(|d| d.get_temp::<egui::TextureHandle>(id)) {
+        Some(mut texture) => {
+            texture.set(image, egui::TextureOptions::LINEAR);
+            texture
+        }
+        None => {
+            let texture = ctx.load_texture(name, image, egui::TextureOptions::LINEAR);
+            ctx.data_mut(|d| d.insert_temp(id, texture.clone()));
+            texture
+        }
+    }
 }
 
 
@@ -546,7 +1270,8 @@ pub fn draw_inspector_overlay(
     perf: &crate::shared_state::PerformanceStats,
     hovered_index: usize,
     bar_slot_width: f32,
-    _noise_floor: f32,
+    reference_db: Option<f32>,
+    noise_floor: f32,
 ) {
     let num_bars = data.bars.len();
     if hovered_index >= num_bars { return; }
@@ -584,6 +1309,57 @@ pub fn draw_inspector_overlay(
         },
     );
 
+    // [Compare]: Held peak and how far above the live level it sits, so the
+    // transient headroom of each band can be read off directly
+    if profile.show_peaks {
+        if let Some(&held_db) = data.peaks.get(hovered_index) {
+            job.append(
+                &format!("Hold {:.1} dB  |  Δ {:+.1} dB\n", held_db, held_db - db_value),
+                0.0,
+                egui::text::TextFormat {
+                    font_id: egui::FontId::proportional(12.0),
+                    color: text_color,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    // [Compare]: Reference snapshot level and how far the live level is from it
+    if let Some(ref_db) = reference_db {
+        job.append(
+            &format!("Ref {:.1} dB  |  Δ {:+.1} dB\n", ref_db, db_value - ref_db),
+            0.0,
+            egui::text::TextFormat {
+                font_id: egui::FontId::proportional(12.0),
+                color: text_color,
+                ..Default::default()
+            },
+        );
+    }
+
+    // [Harmonics]: Is the bar's strongest tone a fundamental, and how
+    // distorted is it
+    let harmonics = crate::harmonics::analyze(&data.spectral_peaks, min_freq, max_freq, sr as f32 / fft_size as f32, sr as f32 / 2.0);
+    let small = egui::text::TextFormat { font_id: egui::FontId::proportional(12.0), color: text_color, ..Default::default() };
+    let mono = egui::text::TextFormat { font_id: egui::FontId::monospace(10.0), color: faint_color, ..Default::default() };
+    match harmonics {
+        Some(crate::harmonics::Analysis::Fundamental { peak, harmonics, thd_percent }) => {
+            job.append(&format!("Fundamental {:.1} Hz  |  THD {:.2} %\n", peak.freq_hz, thd_percent), 0.0, small);
+            const SHOWN: usize = 5;
+            for h in harmonics.iter().take(SHOWN) {
+                job.append(&format!("H{:<2} {:>6.0} Hz  {:>6.1} dBc\n", h.number, h.freq_hz, h.db - peak.db), 0.0, mono.clone());
+            }
+            if harmonics.len() > SHOWN {
+                job.append(&format!("+{} more\n", harmonics.len() - SHOWN), 0.0, mono.clone());
+            }
+        }
+        Some(crate::harmonics::Analysis::HarmonicOf { peak, number, fundamental_hz }) => {
+            job.append(&format!("{:.1} Hz is H{} of {:.1} Hz\n", peak.freq_hz, number, fundamental_hz), 0.0, small);
+        }
+        None => {}
+    }
+
     // [Secondary]: Band # and Range (Small, Monospace for alignment)
     job.append(
         &format!("Band {}  [{:.0} - {:.0} Hz]", hovered_index + 1, min_freq, max_freq),
@@ -652,6 +1428,134 @@ pub fn draw_inspector_overlay(
 
     // === 6. Draw Target Dot ===
     painter.circle_filled(dot_pos, 2.5, text_color.linear_multiply(0.8));
+
+    // === 7. Mark the held peak on the hovered bar ===
+    if profile.show_peaks {
+        if let Some(&held_db) = data.peaks.get(hovered_index) {
+            let live_v = db_to_px(db_value, noise_floor, max_v);
+            let held_v = db_to_px(held_db, noise_floor, max_v);
+            if held_v > live_v {
+                let stroke = Stroke::new(1.0, text_color.linear_multiply(0.6));
+                painter.line_segment(
+                    [map_uv_to_xy(rect, u_center, live_v, profile.orientation),
+                     map_uv_to_xy(rect, u_center, held_v, profile.orientation)],
+                    stroke,
+                );
+                let tick = bar_slot_width.clamp(4.0, 12.0) / 2.0;
+                painter.line_segment(
+                    [map_uv_to_xy(rect, u_center - tick, held_v, profile.orientation),
+                     map_uv_to_xy(rect, u_center + tick, held_v, profile.orientation)],
+                    stroke,
+                );
+            }
+        }
+    }
+}
+
+/// Width of the peak / RMS strip
+pub const METER_STRIP_WIDTH: f32 = 64.0;
+/// Lowest level on the strip (dBFS)
+const METER_FLOOR_DB: f32 = -60.0;
+
+/// Peak / RMS strip: two vertical meters on a dBFS scale with the readings
+/// above and the crest factor below
+pub fn draw_meter_strip(painter: &egui::Painter, rect: egui::Rect, colors: &ColorProfile, meter: &crate::level_meter::MeterReading) {
+    let bg = to_egui_color(colors.inspector_bg);
+    let fg = to_egui_color(colors.inspector_fg);
+    let gradient = gradient_colors(colors, |c| c);
+    painter.rect_filled(rect, 0.0, bg);
+
+    let font = egui::FontId::monospace(10.0);
+    let db_text = |db: f32| if db <= METER_FLOOR_DB { "-inf".to_string() } else { format!("{:.1}", db) };
+    let text_height = 28.0;
+    let meters = egui::Rect::from_min_max(rect.min + egui::vec2(8.0, text_height), rect.max - egui::vec2(8.0, text_height));
+
+    // Scale ticks
+    for db in [-6.0, -12.0, -24.0, -48.0] {
+        let y = meters.bottom() - db_to_px(db, METER_FLOOR_DB, meters.height());
+        painter.line_segment([egui::pos2(meters.left(), y), egui::pos2(meters.right(), y)], Stroke::new(1.0, fg.gamma_multiply(0.2)));
+    }
+
+    let column = (meters.width() - 6.0) / 2.0;
+    for (i, (label, db)) in [("PK", meter.peak_db), ("RMS", meter.rms_db)].into_iter().enumerate() {
+        let left = meters.left() + i as f32 * (column + 6.0);
+        let height = db_to_px(db, METER_FLOOR_DB, meters.height());
+        let fill = egui::Rect::from_min_max(egui::pos2(left, meters.bottom() - height), egui::pos2(left + column, meters.bottom()));
+        painter.rect_filled(fill, 1.0, sample_gradient(&gradient, height / meters.height().max(1.0)));
+
+        let x = left + column / 2.0;
+        painter.text(egui::pos2(x, rect.top() + 4.0), egui::Align2::CENTER_TOP, label, font.clone(), fg.gamma_multiply(0.7));
+        painter.text(egui::pos2(x, rect.top() + 15.0), egui::Align2::CENTER_TOP, db_text(db), font.clone(), fg);
+    }
+
+    painter.text(egui::pos2(rect.center().x, rect.bottom() - 22.0), egui::Align2::CENTER_TOP, "CREST", font.clone(), fg.gamma_multiply(0.7));
+    painter.text(egui::pos2(rect.center().x, rect.bottom() - 11.0), egui::Align2::CENTER_TOP, format!("{:.1} dB", meter.crest_db), font, fg);
+}
+
+/// Clip light: a red dot with a soft halo and "CLIP" in the top right corner
+pub fn draw_clip_led(painter: &egui::Painter, rect: egui::Rect) {
+    let red = egui::Color32::from_rgb(255, 40, 40);
+    let center = rect.right_top() + egui::vec2(-14.0, 14.0);
+    painter.circle_filled(center, 11.0, red.gamma_multiply(0.25));
+    painter.circle_filled(center, 6.0, red);
+    painter.text(
+        center - egui::vec2(12.0, 0.0),
+        egui::Align2::RIGHT_CENTER,
+        "CLIP",
+        egui::FontId::proportional(11.0),
+        red,
+    );
+}
+
+/// Tuner: note name, octave and a cents meter, centered over the visualizer
+pub fn draw_tuner_overlay(
+    painter: &Painter,
+    rect: Rect,
+    colors: &ColorProfile,
+    reading: Option<crate::tuner::Reading>,
+    a4_hz: f32,
+) {
+    /// Cents counted as in tune
+    const IN_TUNE_CENTS: f32 = 5.0;
+
+    let text_color = to_egui_color(colors.inspector_fg);
+    let faint_color = text_color.linear_multiply(0.6);
+    let panel = Rect::from_center_size(rect.center(), egui::vec2(260.0, 150.0).min(rect.size()));
+    painter.rect_filled(panel, 8.0, to_egui_color(colors.inspector_bg));
+    painter.rect_stroke(panel, 8.0, Stroke::new(1.0, text_color.linear_multiply(0.2)));
+
+    let Some(reading) = reading else {
+        painter.text(panel.center(), egui::Align2::CENTER_CENTER, "Play a note", egui::FontId::proportional(16.0), faint_color);
+        return;
+    };
+    let note = crate::tuner::Note::from_freq(reading.freq_hz, a4_hz);
+    let in_tune = note.cents.abs() <= IN_TUNE_CENTS;
+    let accent = if in_tune { to_egui_color(colors.high) } else { text_color };
+
+    // Note name, octave as a subscript-ish suffix
+    let name_pos = panel.center_top() + egui::vec2(0.0, 50.0);
+    let name_rect = painter.text(name_pos, egui::Align2::CENTER_CENTER, note.name, egui::FontId::proportional(56.0), accent);
+    painter.text(name_rect.right_bottom() + egui::vec2(2.0, -10.0), egui::Align2::LEFT_BOTTOM, note.octave.to_string(), egui::FontId::proportional(20.0), accent);
+
+    painter.text(
+        panel.center_top() + egui::vec2(0.0, 95.0),
+        egui::Align2::CENTER_CENTER,
+        format!("{:+.0} ¢   {:.1} Hz", note.cents, reading.freq_hz),
+        egui::FontId::monospace(13.0),
+        faint_color,
+    );
+
+    // Cents meter: -50 .. +50 with a needle
+    let meter = Rect::from_center_size(panel.center_bottom() - egui::vec2(0.0, 22.0), egui::vec2(panel.width() - 40.0, 14.0));
+    let x_of = |cents: f32| meter.left() + (cents.clamp(-50.0, 50.0) + 50.0) / 100.0 * meter.width();
+    painter.line_segment([meter.left_center(), meter.right_center()], Stroke::new(1.0, faint_color));
+    for cents in [-50.0, -25.0, 0.0, 25.0, 50.0] {
+        let half = if cents == 0.0 { meter.height() / 2.0 } else { meter.height() / 4.0 };
+        let x = x_of(cents);
+        painter.line_segment([egui::pos2(x, meter.center().y - half), egui::pos2(x, meter.center().y + half)], Stroke::new(1.0, faint_color));
+    }
+    let x = x_of(note.cents);
+    painter.line_segment([egui::pos2(x, meter.top() - 3.0), egui::pos2(x, meter.bottom() + 3.0)], Stroke::new(3.0, accent));
 }
 
 /// Render performance statistics overlay
@@ -662,20 +1566,36 @@ pub fn draw_stats_overlay(
     perf: &crate::shared_state::PerformanceStats,
     display_bars: usize,
     requested_bars: usize,
+    fps_cap: Option<u32>,
 ) {
     // Position in top-left (with padding)
     let pos = rect.left_top() + egui::vec2(10.0, 10.0);
+
+    // Achieved vs. cap, so it's obvious when the cap (not the machine) is the limit
+    let fps_cap = fps_cap.or(perf.display_hz.map(|hz| hz.round() as u32));
+    let fps = match fps_cap {
+        Some(cap) => format!("{:.0} / {}", perf.gui_fps, cap),
+        None => format!("{:.0}", perf.gui_fps),
+    };
     
-    let text = format!(
-        "FPS: {:.0}\nFFT: {:.1}ms\nMin/Max: {:.1}/{:.1}ms\nFFT Res: {:.2} Hz/bin\nBars: {} / {}",
-        perf.gui_fps,
+    let mut text = format!(
+        "FPS: {} ({} dropped)\nFFT: {:.1}ms\nMin/Max: {:.1}/{:.1}ms\nFFT Res: {:.2} Hz/bin\nBars: {} / {}\nCentroid: {:.0} Hz\nRolloff: {:.0} Hz\nFlatness: {:.2}",
+        fps,
+        perf.dropped_frames,
         perf.fft_ave_time.as_micros() as f32 / 1000.0,
         perf.fft_min_time.as_micros() as f32 / 1000.0,
         perf.fft_max_time.as_micros() as f32 / 1000.0,
         perf.fft_info.frequency_resolution, // Strictly the raw FFT math
         display_bars,      
-        requested_bars     
+        requested_bars,
+        perf.spectral.centroid_hz,
+        perf.spectral.rolloff_hz,
+        perf.spectral.flatness,
     );
+    let timings = &perf.frame_timings;
+    if let Some(bottleneck) = timings.bottleneck() {
+        text += &format!("\nFrame: {:.1}ms ({} bound)", timings.average().total(), bottleneck.label());
+    }
 
     // Reuse Inspector colors for consistency
     let bg_color = crate::gui::theme::to_egui_color(colors.inspector_bg);
@@ -688,12 +1608,85 @@ pub fn draw_stats_overlay(
         egui::FontId::proportional(12.0), 
         text_color
     );
-    
+
+    // Stage legend, each label in its graph color
+    let mut legend = egui::text::LayoutJob::default();
+    for stage in Stage::ALL {
+        let format = egui::TextFormat::simple(egui::FontId::proportional(10.0), stage_color(stage));
+        let leading_space = if legend.text.is_empty() { 0.0 } else { 6.0 };
+        legend.append(stage.label(), leading_space, format);
+    }
+    let legend = painter.layout_job(legend);
+
     let pad = 6.0;
-    let bg_rect = egui::Rect::from_min_size(pos, galley.size() + egui::vec2(pad*2.0, pad*2.0));
+    let graph_size = egui::vec2(galley.size().x.max(legend.size().x), STAGE_GRAPH_HEIGHT);
+    let content = egui::vec2(graph_size.x, galley.size().y + pad + graph_size.y + legend.size().y);
+    let bg_rect = egui::Rect::from_min_size(pos, content + egui::vec2(pad*2.0, pad*2.0));
     
     painter.rect_filled(bg_rect, 4.0, bg_color);
+    let graph_top = pos.y + pad + galley.size().y + pad;
     painter.galley(pos + egui::vec2(pad, pad), galley, egui::Color32::WHITE); // Text color is baked into galley
+
+    let graph = egui::Rect::from_min_size(egui::pos2(pos.x + pad, graph_top), graph_size);
+    draw_stage_graph(painter, graph, timings, text_color);
+    painter.galley(graph.left_bottom(), legend, egui::Color32::WHITE);
+}
+
+/// Height of the per-stage frame time graph in the stats overlay
+const STAGE_GRAPH_HEIGHT: f32 = 36.0;
+
+/// 60 FPS frame budget, marked on the stage graph
+const FRAME_BUDGET_MS: f32 = 1000.0 / 60.0;
+
+fn stage_color(stage: Stage) -> egui::Color32 {
+    match stage {
+        Stage::LockWait => egui::Color32::from_rgb(150, 150, 150),
+        Stage::Fft => egui::Color32::from_rgb(80, 150, 255),
+        Stage::Aggregation => egui::Color32::from_rgb(90, 210, 120),
+        Stage::Paint => egui::Color32::from_rgb(255, 160, 60),
+    }
+}
+
+/// Stacked columns, one per recent frame (newest on the right), scaled so
+/// the frame budget line sits halfway up unless a frame goes over it
+fn draw_stage_graph(painter: &egui::Painter, rect: egui::Rect, timings: &FrameTimings, line_color: egui::Color32) {
+    let frames = timings.history();
+    let peak = timings.history().map(|f| f.total()).fold(0.0f32, f32::max);
+    let scale_ms = peak.max(2.0 * FRAME_BUDGET_MS);
+    let column = rect.width() / crate::frame_timing::HISTORY as f32;
+    let first = crate::frame_timing::HISTORY - frames.len();
+
+    for (i, frame) in frames.enumerate() {
+        let x = rect.left() + (first + i) as f32 * column;
+        let mut bottom = rect.bottom();
+        for stage in Stage::ALL {
+            let height = frame.get(stage) / scale_ms * rect.height();
+            if height > 0.0 {
+                let segment = egui::Rect::from_min_max(egui::pos2(x, bottom - height), egui::pos2(x + column, bottom));
+                painter.rect_filled(segment, 0.0, stage_color(stage));
+            }
+            bottom -= height;
+        }
+    }
+
+    let budget_y = rect.bottom() - FRAME_BUDGET_MS / scale_ms * rect.height();
+    painter.hline(rect.x_range(), budget_y, egui::Stroke::new(1.0, line_color.gamma_multiply(0.5)));
+}
+
+/// Overlay content height from the last frame (it depends on the elements
+/// shown and the scale), so bottom corners can sit flush
+fn media_overlay_height_id() -> egui::Id {
+    egui::Id::new("media_overlay_height")
+}
+
+/// Where the media overlay goes in `rect`
+pub fn media_overlay_rect(ctx: &egui::Context, rect: egui::Rect, layout: &crate::shared_state::MediaOverlayLayout) -> egui::Rect {
+    let margin = 20.0;
+    let width = (rect.width() * 0.5 * layout.scale).min(rect.width() - 2.0 * margin);
+    let height = ctx.data(|d| d.get_temp::<f32>(media_overlay_height_id())).unwrap_or(100.0 * layout.scale);
+    let x = if layout.corner.is_left() { rect.left() + margin } else { rect.right() - margin - width };
+    let y = if layout.corner.is_top() { rect.top() + margin } else { rect.bottom() - margin - height };
+    egui::Rect::from_min_size(egui::pos2(x, y), egui::vec2(width, height))
 }
 
 pub fn draw_media_overlay(
@@ -701,11 +1694,14 @@ pub fn draw_media_overlay(
     rect: egui::Rect,
     media_info: Option<&crate::media::MediaTrackInfo>,
     media_display_mode: crate::shared_state::MediaDisplayMode,
+    layout: &crate::shared_state::MediaOverlayLayout,
+    track_link: &crate::shared_state::TrackLinkSettings,
     overlay_font: &crate::shared_state::ThemeFont,
     media_opacity: f32,
     colors: &crate::shared_state::ColorProfile,
     album_art_texture: Option<&egui::TextureHandle>,
     controller: &dyn MediaController,
+    tasks: &crate::gui::tasks::TaskRunner,
 ) {
 
     // 1. Early Exit (Invisible or Off)
@@ -720,7 +1716,8 @@ pub fn draw_media_overlay(
         return;
     }
 
-    let show_album_art = win_width > 450.0;
+    let show_album_art = layout.show_art && win_width > 450.0;
+    let scale = layout.scale;
 
     // 2. Setup Styles
     let base_text_color = to_egui_color(colors.text);
@@ -729,20 +1726,20 @@ pub fn draw_media_overlay(
     
     // 3. Layout calculation
     // Anchor relative to the visulalizer 'rect' passed in
-    let overlay_w = rect.width() * 0.5;
-    let overlay_h = 100.0;
-
-    // Position top-right with padding
-    let pos = egui::pos2(rect.right() - overlay_w - 20.0, rect.top() + 20.0);
-    let overlay_rect = egui::Rect::from_min_size(pos, egui::vec2(overlay_w, overlay_h));
+    let overlay_rect = media_overlay_rect(ui.ctx(), rect, layout);
 
+    // Art on the outer edge, text lined up against it
+    let (row_layout, text_align) = if layout.corner.is_left() {
+        (egui::Layout::left_to_right(egui::Align::Min), egui::Align::Min)
+    } else {
+        (egui::Layout::right_to_left(egui::Align::Min), egui::Align::Max)
+    };
 
     // 4. Draw Content in an Allocatd Rect 
-    ui.allocate_new_ui(egui::UiBuilder::new().max_rect(overlay_rect), |ui| {
-        // Force right-to-left layout
-        ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
+    let drawn = ui.allocate_new_ui(egui::UiBuilder::new().max_rect(overlay_rect), |ui| {
+        ui.with_layout(row_layout, |ui| {
             // Manually restrict size
-            ui.set_max_width(overlay_w);
+            ui.set_max_width(overlay_rect.width());
 
             match media_info{
                 Some(info) =>{
@@ -755,35 +1752,44 @@ pub fn draw_media_overlay(
 
                             let response = ui.add(
                                 egui::Image::new(texture)
-                                    .max_height(50.0)
+                                    .max_height(50.0 * scale)
                                     .rounding(4.0)
                                     .tint(tint)
                                     .sense(egui::Sense::click())
                                 );   
 
-                            // Interaction: Initiate Wiki Search!!
+                            // Interaction: Open the track's page
                             if response.clicked() {
-                                // Clone string data to move into the thread
+                                // Clone string data to move into the task
                                 let artist = info.artist.clone();
                                 let title = info.title.clone();
                                 let album = info.album.clone();
-
-                                // Spawn a thread to prevent blocking the GUI during the network request
-                                std::thread::spawn(move || {
-                                    // Generate the URL (Blocking call to ureq inside fetch_wikipedia_url)
-                                    let url = crate::media::fetch_wikipedia_url(&artist, &title, &album);
-                                    tracing::info!("[GUI] Opening Wiki URL: {}", url);
+                                let link = track_link.clone();
+
+                                // Run on the task pool to prevent blocking the GUI during the network request
+                                tasks.spawn(ui.ctx(), "Track page lookup", move || {
+                                    // Generate the URL (Wikipedia and MusicBrainz block on the request manager)
+                                    let url = match link.source {
+                                        TrackLinkSource::Wikipedia => crate::media::fetch_wikipedia_url(&artist, &title, &album),
+                                        TrackLinkSource::MusicBrainz => crate::media::fetch_musicbrainz_url(&artist, &title, &album),
+                                        TrackLinkSource::Discogs => crate::media::discogs_search_url(&artist, &title, &album),
+                                        TrackLinkSource::Custom => crate::media::fill_link_template(&link.template, &artist, &title, &album),
+                                    };
+                                    // The URL carries artist and title, so it stays out of the log
+                                    tracing::info!("[GUI] Opening {} page", link.source.label());
 
                                     // Open in default system browser
-                                    if let Err(e) = open::that(&url) {
-                                        tracing::error!("[GUI] Failed to open URL: {}", e);
-                                    }
+                                    open::that(&url).map_err(|e| crate::error::BeSpecError::Task {
+                                        task: "Opening browser".to_string(),
+                                        reason: e.to_string(),
+                                    })?;
+                                    crate::gui::tasks::done()
                                 });
                             }
 
                             if response.hovered() {
                                 ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
-                                response.on_hover_text(format!("Search Wikipedia for '{}'", info.artist));
+                                response.on_hover_text(format!("Look up '{}' on {}", info.artist, track_link.source.label()));
                             }
                             
                             ui.add_space(10.0); 
@@ -793,17 +1799,28 @@ pub fn draw_media_overlay(
                     
                     // Text Stack
                     ui.vertical(|ui| {
-                        ui.with_layout(egui::Layout::top_down(egui::Align::Max), |ui| {
+                        ui.with_layout(egui::Layout::top_down(text_align), |ui| {
                             
                             // Title (Scrolling)
-                            let title_font = egui::FontId::new(16.0, font_family.clone());
-                            let title_color = base_text_color.linear_multiply(media_opacity);
-                            draw_scrolling_label(ui, &info.title, title_font, title_color);
+                            if layout.show_title {
+                                let title_font = egui::FontId::new(16.0 * scale, font_family.clone());
+                                let title_color = base_text_color.linear_multiply(media_opacity);
+                                draw_scrolling_label(ui, &info.title, title_font, title_color);
+                            }
 
                             // Artist
-                            let artist_font = egui::FontId::new( 12.0, font_family.clone());
-                            let artist_color = base_text_color.linear_multiply(media_opacity);
-                            draw_scrolling_label(ui, &info.artist, artist_font, artist_color);
+                            if layout.show_artist {
+                                let artist_font = egui::FontId::new(12.0 * scale, font_family.clone());
+                                let artist_color = base_text_color.linear_multiply(media_opacity);
+                                draw_scrolling_label(ui, &info.artist, artist_font, artist_color);
+                            }
+
+                            // Seek bar, when the player reports a position
+                            let timeline = info.position.filter(|_| layout.show_progress);
+                            if let (Some(timeline), Some(now)) = (timeline, info.position_now(std::time::Instant::now())) {
+                                ui.add_space(4.0);
+                                draw_seek_bar(ui, timeline.duration, now, controller, base_text_color, media_opacity, &font_family);
+                            }
 
                             ui.add_space(2.0);
 
@@ -821,21 +1838,24 @@ pub fn draw_media_overlay(
 
                             */
 
-                            if cfg!(not(target_os = "macos")) {
+                            if layout.show_controls && cfg!(not(target_os = "macos")) {
                                 ui.add_space(4.0);
                                 draw_transport_controls(
                                     ui,
                                     controller,
                                     info.is_playing,
+                                    info.volume,
                                     media_opacity,
                                     base_text_color);
                             } 
 
-                            ui.add(egui::Label::new(
-                                egui::RichText::new(format!("via {}", info.source_app))
-                                        .font(egui::FontId::new(10.0, font_family.clone()))
-                                        .color(base_text_color.linear_multiply(0.5).linear_multiply(media_opacity))
-                            ));
+                            if layout.show_source {
+                                ui.add(egui::Label::new(
+                                    egui::RichText::new(format!("via {}", info.source_app))
+                                            .font(egui::FontId::new(10.0 * scale, font_family.clone()))
+                                            .color(base_text_color.linear_multiply(0.5).linear_multiply(media_opacity))
+                                ));
+                            }
                         });
                     });
                 },
@@ -843,10 +1863,10 @@ pub fn draw_media_overlay(
                     // === Case B: No info, but Always On ===
                     if media_display_mode == crate::shared_state::MediaDisplayMode::AlwaysOn {
                         ui.vertical(|ui| {
-                            ui.with_layout(egui::Layout::top_down(egui::Align::Max), |ui| {
+                            ui.with_layout(egui::Layout::top_down(text_align), |ui| {
                                 ui.label(egui::RichText::new("Waiting for media...")
                                     .family(font_family.clone())
-                                    .size(14.0)
+                                    .size(14.0 * scale)
                                     .color(base_text_color.gamma_multiply(media_opacity * 0.6))
                                 );
                             });
@@ -856,6 +1876,168 @@ pub fn draw_media_overlay(
             }
         });
     });
+    let height = drawn.response.rect.height();
+    if height > 0.0 {
+        ui.ctx().data_mut(|d| d.insert_temp(media_overlay_height_id(), height));
+    }
+}
+
+/// Thin track progress bar with the time next to it. Clicking or dragging
+/// seeks; the player confirms by reporting the new position.
+fn draw_seek_bar(
+    ui: &mut egui::Ui,
+    duration: std::time::Duration,
+    position: std::time::Duration,
+    controller: &dyn MediaController,
+    color: egui::Color32,
+    opacity: f32,
+    font_family: &egui::FontFamily,
+) {
+    let fraction = (position.as_secs_f32() / duration.as_secs_f32().max(1.0)).clamp(0.0, 1.0);
+    let time_text = format!(
+        "{} / {}",
+        crate::media::format_position(position),
+        crate::media::format_position(duration)
+    );
+
+    // Fixed width, so the overlay's alignment places it like the text
+    let width = ui.available_width().min(240.0);
+    ui.allocate_ui_with_layout(egui::vec2(width, 12.0), egui::Layout::right_to_left(egui::Align::Center), |ui| {
+        ui.label(
+            egui::RichText::new(time_text)
+                .font(egui::FontId::new(10.0, font_family.clone()))
+                .color(color.linear_multiply(0.6 * opacity)),
+        );
+
+        let width = ui.available_width().min(160.0);
+        let (rect, response) = ui.allocate_exact_size(egui::vec2(width, 8.0), egui::Sense::click_and_drag());
+        let track = egui::Rect::from_center_size(rect.center(), egui::vec2(rect.width(), 3.0));
+        let hovered = response.hovered() || response.dragged();
+        let painter = ui.painter();
+        painter.rect_filled(track, 1.5, color.linear_multiply(0.25 * opacity));
+        let filled = egui::Rect::from_min_max(track.min, egui::pos2(track.left() + track.width() * fraction, track.max.y));
+        painter.rect_filled(filled, 1.5, color.linear_multiply(opacity));
+
+        if let Some(pointer) = response.hover_pos().filter(|_| hovered) {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+            let target = ((pointer.x - track.left()) / track.width()).clamp(0.0, 1.0);
+            painter.circle_filled(egui::pos2(track.left() + track.width() * target, track.center().y), 4.0, color.linear_multiply(opacity));
+
+            let target = duration.mul_f32(target);
+            if response.clicked() || response.drag_stopped() {
+                tracing::debug!("[GUI] Seek to {}", crate::media::format_position(target));
+                controller.try_seek(target);
+            }
+            response.on_hover_text(crate::media::format_position(target));
+        }
+    });
+}
+
+/// How long a line takes to scroll into place before its time comes
+const LYRICS_SCROLL_SECS: f32 = 0.25;
+
+/// How long the track change toast stays up, the last half second fading
+pub const TRACK_TOAST_SECS: f32 = 4.0;
+
+/// Small "♪ Artist – Title" pill at the bottom of the window, for when the
+/// track changes. `age` is seconds since it popped up.
+pub fn draw_track_toast(painter: &Painter, rect: Rect, text: &str, age: f32, colors: &ColorProfile) {
+    let alpha = ((TRACK_TOAST_SECS - age) / 0.5).clamp(0.0, 1.0);
+    if alpha <= 0.0 || rect.width() < 150.0 {
+        return;
+    }
+    let bg_color = to_egui_color(colors.inspector_bg).gamma_multiply(alpha);
+    let text_color = to_egui_color(colors.inspector_fg).gamma_multiply(alpha);
+
+    let mut job = egui::text::LayoutJob::simple_singleline(
+        format!("♪ {}", text),
+        egui::FontId::proportional(13.0),
+        text_color,
+    );
+    let pad = egui::vec2(12.0, 6.0);
+    job.wrap = egui::text::TextWrapping::truncate_at_width(rect.width() - 40.0 - pad.x * 2.0);
+    let galley = painter.layout_job(job);
+
+    // Slides up a little as it appears
+    let rise = (age / 0.2).min(1.0) * 8.0;
+    let size = galley.size() + pad * 2.0;
+    let bg_rect = egui::Rect::from_min_size(
+        egui::pos2(rect.center().x - size.x / 2.0, rect.bottom() - 12.0 - rise - size.y),
+        size,
+    );
+    painter.rect_filled(bg_rect, size.y / 2.0, bg_color);
+    painter.galley(bg_rect.min + pad, galley, text_color);
+}
+
+/// Synced lyrics next to the Now Playing overlay (below it in the top
+/// corners, above it in the bottom ones): the current line in the middle,
+/// `context_lines` before and after it fading out, scrolling up as each
+/// line starts.
+pub fn draw_lyrics(
+    ui: &mut egui::Ui,
+    rect: egui::Rect,
+    lines: &[crate::lyrics::LyricLine],
+    position: std::time::Duration,
+    settings: &crate::shared_state::LyricsSettings,
+    layout: &crate::shared_state::MediaOverlayLayout,
+    colors: &ColorProfile,
+) {
+    if rect.width() < 250.0 || lines.is_empty() {
+        return;
+    }
+    let shift = std::time::Duration::from_millis(settings.offset_ms.unsigned_abs() as u64);
+    let position = if settings.offset_ms >= 0 { position + shift } else { position.saturating_sub(shift) };
+
+    // Same column as the media overlay
+    let overlay = media_overlay_rect(ui.ctx(), rect, layout);
+    let width = overlay.width();
+    let font = crate::gui::theme::to_egui_font(&settings.font);
+    let line_h = font.size * 1.5;
+    let context = settings.context_lines as f32;
+    let half_block = (context + 0.5) * line_h;
+    let center_y = if layout.corner.is_top() {
+        overlay.bottom() + 10.0 + half_block
+    } else {
+        overlay.top() - 10.0 - half_block
+    };
+    if center_y - half_block < rect.top() || center_y + half_block > rect.bottom() {
+        return;
+    }
+
+    // Fractional line index: whole while a line is sung, sliding towards the
+    // next one during the last LYRICS_SCROLL_SECS before it starts
+    let current = crate::lyrics::current_line(lines, position);
+    let next = current.map_or(0, |c| c + 1);
+    let mut scroll = current.map_or(-1.0, |c| c as f32);
+    if let Some(next_line) = lines.get(next) {
+        let until_next = next_line.time.saturating_sub(position).as_secs_f32();
+        scroll += 1.0 - (until_next / LYRICS_SCROLL_SECS).min(1.0);
+    }
+
+    let painter = ui.painter();
+    let text_color = to_egui_color(colors.text);
+    let first = (scroll - context - 1.0).floor().max(0.0) as usize;
+    let last = ((scroll + context + 1.0).ceil().max(0.0) as usize).min(lines.len() - 1);
+    for (i, line) in lines.iter().enumerate().take(last + 1).skip(first) {
+        let offset = i as f32 - scroll;
+        let fade = 1.0 - (offset.abs() - context).clamp(0.0, 1.0);
+        if fade <= 0.0 {
+            continue;
+        }
+        let is_current = Some(i) == current;
+        let alpha = settings.opacity * fade * if is_current { 1.0 } else { 0.45 };
+        let text = if line.text.is_empty() { "♪" } else { line.text.as_str() };
+
+        let mut job = egui::text::LayoutJob::single_section(
+            text.to_string(),
+            egui::TextFormat::simple(font.clone(), text_color.gamma_multiply(alpha)),
+        );
+        job.wrap = egui::text::TextWrapping::truncate_at_width(width);
+        let galley = painter.layout_job(job);
+        let x = if layout.corner.is_left() { overlay.left() } else { overlay.right() - galley.size().x };
+        let pos = egui::pos2(x, center_y + offset * line_h - galley.size().y / 2.0);
+        painter.galley(pos, galley, text_color);
+    }
 }
 
 pub fn draw_sonar_ping(
@@ -924,16 +2106,10 @@ pub fn draw_preview_spectrum(
     let bg_color = to_egui_color(current_colors.background);
     painter.rect_filled(rect, 4.0, bg_color);
 
-    // Mock Data Pattern (bass heavy, dip in mids, sparkle in highs)
-    let mock_levels = [
-        0.10, 0.40, 0.75, 0.95, 0.90, 0.85, 0.70, // Bass
-        0.55, 0.40, 0.30, 0.25,                   // Mids
-        0.40, 0.60, 0.50, 0.35,                   // High Mids
-        0.25, 0.15, 0.25, 0.40, 0.30, 0.20, 0.15, 0.10, 0.08, 0.04, 0.01 // Highs
-    ];
+    // Mock Data Pattern (same one the preset thumbnails use)
+    let mock_levels = crate::preset_thumbnail::MOCK_LEVELS;
 
-    let low = to_egui_color(current_colors.low).linear_multiply(bar_opacity);
-    let high = to_egui_color(current_colors.high).linear_multiply(bar_opacity);
+    let gradient = gradient_colors(current_colors, |c| c.linear_multiply(bar_opacity));
     let peak = to_egui_color(current_colors.peak).linear_multiply(bar_opacity);
 
     let bar_width = rect.width() / mock_levels.len() as f32;
@@ -944,24 +2120,15 @@ pub fn draw_preview_spectrum(
         let w = (bar_width - gap).max(1.0);
         let h = level * rect.height();
 
-        // Gradient
-        let bar_color = lerp_color(low, high, level);
-
         // Draw Bar (Bottom-up standard for preview)
         let bar_rect = egui::Rect::from_min_size(
             egui::pos2(x, rect.bottom() - h), 
             egui::vec2(w, h)
         );
-        
-        // Simple gradient mesh for preview
-        use egui::epaint::{Mesh, Vertex};
-        let mut mesh = Mesh::default();
-        mesh.vertices.push(Vertex { pos: bar_rect.left_bottom(), uv: egui::Pos2::ZERO, color: low });
-        mesh.vertices.push(Vertex { pos: bar_rect.right_bottom(), uv: egui::Pos2::ZERO, color: low });
-        mesh.vertices.push(Vertex { pos: bar_rect.right_top(), uv: egui::Pos2::ZERO, color: bar_color });
-        mesh.vertices.push(Vertex { pos: bar_rect.left_top(), uv: egui::Pos2::ZERO, color: bar_color });
-        mesh.add_triangle(0, 1, 2);
-        mesh.add_triangle(0, 2, 3);
+        let mesh = gradient_bar_mesh(&gradient, level, |t| {
+            let y = rect.bottom() - t * rect.height();
+            [egui::pos2(x, y), egui::pos2(x + w, y)]
+        });
         painter.add(egui::Shape::mesh(mesh));
 
         // Peak
@@ -1,43 +1,442 @@
 use eframe::egui::{self, Ui, Rect, Context, Color32};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
-use crate::shared_state::{SharedState};
-use crate::shared_state::{ColorProfile, MediaDisplayMode, VisualMode, VisualProfile, VuColoring};
-use crate::shared_state::ColorRef;use crate::media::MediaController;
-use crate::gui::{theme::*, visualizers};
+use crate::shared_state::{SharedState, SILENCE_DB};
+use crate::shared_state::{BarFill, BarInterpolation, ColorMapping, ColorProfile, DockEdge, DotStyle, ExtraWindow, FpsCap, HotCorner, MediaDisplayMode, OverlayCorner, PeakStyle, SizePreset, SplitDirection, TrackLinkSource, VisualMode, VisualProfile, VuColoring};
+use crate::shared_state::{ColorRef, GradientStop};
+use crate::media::MediaController;
+use crate::gui::{milkdrop, theme::*, visualizers};
+#[cfg(feature = "plugins")]
+use crate::gui::plugins;
+#[cfg(feature = "scripting")]
+use crate::gui::script;
+use crate::gui::settings_search::{self, label, row_label, section_label};
+use crate::gui::thumbnails;
+use crate::preset_thumbnail::{self, PresetKind};
+use crate::theme_file::ThemeFile;
+use crate::preset_gallery::GalleryStatus;
 
 /// Settings Tab Definition
-#[derive(PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum SettingsTab {
     Visual, 
     Audio,
     Colors,
     Window,
+    Tools,
     Performance,
 }
 
+impl SettingsTab {
+    /// Name on the tab button
+    pub fn label(&self) -> &'static str {
+        match self {
+            SettingsTab::Visual => "Visual",
+            SettingsTab::Audio => "Audio",
+            SettingsTab::Colors => "Colors",
+            SettingsTab::Window => "Window",
+            SettingsTab::Tools => "Tools",
+            SettingsTab::Performance => "Stats",
+        }
+    }
+}
+
 /// Save Dialog Box Types
 #[derive(PartialEq)]
 pub enum SaveTarget {
     None,
     Visual,
     Color,
+    /// Import `.bespec-theme` dialog (name buffer holds the file path)
+    Import,
 }
 
 // =======================================================================================
 // WINDOW CONTROLS  (Drag, Resize, Lock)
 // =======================================================================================
 
+/// Remote Control group of the Window tab: listeners and the learnable
+/// bindings table
+fn ui_remote_control(ui: &mut Ui, state: &mut SharedState) {
+    use crate::remote_control::{RemoteAction, RemoteBinding};
+
+    let ports_id = egui::Id::new("remote_midi_ports");
+    let mut ports = ui.ctx().data(|d| d.get_temp::<Vec<String>>(ports_id));
+
+    egui::Grid::new("remote_control_grid")
+        .num_columns(2)
+        .spacing(egui::vec2(40.0, 12.0))
+        .min_col_width(150.0)
+        .show(ui, |ui| {
+            let remote = &mut state.config.remote;
+
+            row_label(ui, label::OSC_INPUT);
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut remote.osc_enabled, "Listen on UDP port");
+                ui.add(egui::DragValue::new(&mut remote.osc_port).range(1024..=65535));
+                ui.checkbox(&mut remote.osc_lan, "From LAN")
+                    .on_hover_text("Accept messages from other devices (a tablet, a lighting desk).\nOff: only apps on this computer.");
+            });
+            ui.end_row();
+
+            row_label(ui, label::OSC_OUTPUT);
+            ui.horizontal(|ui| {
+                let osc_out = &mut state.config.osc_out;
+                ui.checkbox(&mut osc_out.enabled, "Send features to")
+                    .on_hover_text("Spectral centroid, rolloff and flatness as\n/bespec/centroid, /bespec/rolloff and /bespec/flatness, 30 times a second");
+                ui.add(egui::TextEdit::singleline(&mut osc_out.target).hint_text("127.0.0.1:9001").desired_width(140.0))
+                    .on_hover_text("IP address and port (host names aren't looked up)");
+            });
+            ui.end_row();
+
+            row_label(ui, label::MIDI_INPUT);
+            ui.horizontal(|ui| {
+                ui.add_enabled(crate::midi_out::SUPPORTED, egui::Checkbox::new(&mut remote.midi_enabled, "Listen to"))
+                    .on_disabled_hover_text("This build has no MIDI support (cargo feature 'midi').");
+                let selected = if remote.midi_port.is_empty() { "Choose…" } else { remote.midi_port.as_str() };
+                egui::ComboBox::from_id_salt("remote_midi_port")
+                    .selected_text(selected.to_string())
+                    .show_ui(ui, |ui| {
+                        let ports = ports.get_or_insert_with(crate::remote_control::list_midi_inputs);
+                        if ports.is_empty() {
+                            ui.label(egui::RichText::new("No MIDI inputs found").weak());
+                        }
+                        for port in ports.iter() {
+                            ui.selectable_value(&mut remote.midi_port, port.clone(), port);
+                        }
+                    });
+                if ui.small_button("⟳").on_hover_text("Look for MIDI inputs again").clicked() {
+                    ports = Some(crate::remote_control::list_midi_inputs());
+                }
+            });
+            ui.end_row();
+
+            row_label(ui, label::HTTP_API);
+            ui.horizontal(|ui| {
+                let api = &mut state.config.http_api;
+                ui.checkbox(&mut api.enabled, "Serve on localhost port")
+                    .on_hover_text("JSON status, bars and now playing, plus POST endpoints to switch
+profiles and colors. Only reachable from this computer.");
+                ui.add(egui::DragValue::new(&mut api.port).range(1024..=65535));
+                if api.enabled {
+                    ui.hyperlink_to("status", format!("http://127.0.0.1:{}/api/status", api.port));
+                }
+            });
+            ui.end_row();
+
+            row_label(ui, label::STREAM_DECK);
+            ui.horizontal(|ui| {
+                let deck = &mut state.config.stream_deck;
+                ui.checkbox(&mut deck.enabled, "Plugin socket on port")
+                    .on_hover_text("Line-based JSON over TCP for Stream Deck plugins: toggle ghost mode,
+cycle presets and stream live levels to keys. See docs/stream_deck_protocol.md.");
+                ui.add(egui::DragValue::new(&mut deck.port).range(1024..=65535));
+            });
+            ui.end_row();
+
+            row_label(ui, label::LAST_MESSAGE);
+            ui.label(egui::RichText::new(state.remote_last.as_deref().unwrap_or("—")).monospace().weak());
+            ui.end_row();
+        });
+
+    if let Some(ports) = ports {
+        ui.ctx().data_mut(|d| d.insert_temp(ports_id, ports));
+    }
+
+    ui.add_space(6.0);
+    let mut remove = None;
+    egui::Grid::new("remote_bindings_grid")
+        .num_columns(4)
+        .spacing(egui::vec2(8.0, 6.0))
+        .striped(true)
+        .show(ui, |ui| {
+            for (i, binding) in state.config.remote.bindings.iter_mut().enumerate() {
+                egui::ComboBox::from_id_salt(("remote_action", i))
+                    .width(150.0)
+                    .selected_text(binding.action.label())
+                    .show_ui(ui, |ui| {
+                        for action in RemoteAction::ALL {
+                            ui.selectable_value(&mut binding.action, action, action.label());
+                        }
+                    });
+
+                let source = binding.source.as_ref().map_or("—".to_string(), |s| s.label());
+                ui.label(egui::RichText::new(source).monospace());
+
+                let learning = state.remote_learn == Some(i);
+                if ui.selectable_label(learning, if learning { "Move a control…" } else { "Learn" })
+                    .on_hover_text("Arm, then send a CC or OSC message to bind it")
+                    .clicked()
+                {
+                    state.remote_learn = if learning { None } else { Some(i) };
+                }
+                if ui.small_button("✖").on_hover_text("Remove binding").clicked() {
+                    remove = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(i) = remove {
+        state.config.remote.bindings.remove(i);
+        state.remote_learn = None;
+    }
+    if ui.button("➕ Add Binding").clicked() {
+        state.config.remote.bindings.push(RemoteBinding { action: RemoteAction::Sensitivity, source: None });
+        state.remote_learn = Some(state.config.remote.bindings.len() - 1);
+    }
+}
+
+/// MIDI Out group of the Audio tab: port, and the band → note / CC mapping
+fn ui_midi_out(ui: &mut Ui, state: &mut SharedState) {
+    use crate::midi_out::{MidiAction, MidiTrigger};
+
+    let ports_id = egui::Id::new("midi_out_ports");
+    let mut ports = ui.ctx().data(|d| d.get_temp::<Vec<String>>(ports_id));
+
+    egui::Grid::new("midi_out_grid")
+        .num_columns(2)
+        .spacing(egui::vec2(40.0, 12.0))
+        .show(ui, |ui| {
+            row_label(ui, label::MIDI_OUT);
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut state.config.midi.enabled, "Send band triggers");
+                if !crate::midi_out::SUPPORTED {
+                    ui.label(egui::RichText::new("(this build has no MIDI, see README)").small().weak());
+                }
+            });
+            ui.end_row();
+
+            row_label(ui, label::MIDI_PORT);
+            ui.horizontal(|ui| {
+                let selected = if state.config.midi.port.is_empty() { "Choose…" } else { state.config.midi.port.as_str() };
+                egui::ComboBox::from_id_salt("midi_out_port")
+                    .selected_text(selected.to_string())
+                    .show_ui(ui, |ui| {
+                        let ports = ports.get_or_insert_with(crate::midi_out::list_ports);
+                        if ports.is_empty() {
+                            ui.label(egui::RichText::new("No MIDI outputs found").weak());
+                        }
+                        for port in ports.iter() {
+                            ui.selectable_value(&mut state.config.midi.port, port.clone(), port);
+                        }
+                    });
+                if ui.small_button("⟳").on_hover_text("Look for MIDI outputs again").clicked() {
+                    ports = Some(crate::midi_out::list_ports());
+                }
+                if state.config.midi.enabled && crate::midi_out::SUPPORTED {
+                    let open = state.midi_port_open.is_some();
+                    ui.label(egui::RichText::new(if open { "Connected" } else { "Not connected" }).small().weak());
+                }
+            });
+            ui.end_row();
+        });
+
+    if let Some(ports) = ports {
+        ui.ctx().data_mut(|d| d.insert_temp(ports_id, ports));
+    }
+
+    ui.add_space(6.0);
+    let held = state.midi_held.clone();
+    let triggers = &mut state.config.midi.triggers;
+    let mut remove = None;
+    egui::Grid::new("midi_trigger_grid")
+        .num_columns(8)
+        .spacing(egui::vec2(8.0, 6.0))
+        .striped(true)
+        .show(ui, |ui| {
+            for label in ["", "Name", "From Hz", "To Hz", "Threshold", "Ch", "Send", ""] {
+                ui.label(egui::RichText::new(label).small().weak());
+            }
+            ui.end_row();
+
+            for (i, trigger) in triggers.iter_mut().enumerate() {
+                // Lit while the trigger is on, for setting the threshold by eye
+                let on = held.get(i).copied().unwrap_or(false);
+                let (dot, _) = ui.allocate_exact_size(egui::vec2(10.0, 10.0), egui::Sense::hover());
+                let color = if on { ui.visuals().selection.bg_fill } else { ui.visuals().widgets.inactive.bg_fill };
+                ui.painter().circle_filled(dot.center(), 4.0, color);
+
+                ui.add(egui::TextEdit::singleline(&mut trigger.name).desired_width(70.0));
+                ui.add(egui::DragValue::new(&mut trigger.low_hz).range(20.0..=trigger.high_hz).speed(5.0));
+                ui.add(egui::DragValue::new(&mut trigger.high_hz).range(trigger.low_hz..=20000.0).speed(5.0));
+                ui.add(egui::DragValue::new(&mut trigger.threshold_db).range(SILENCE_DB..=20.0).speed(0.5).suffix(" dB"));
+                ui.add(egui::DragValue::new(&mut trigger.channel).range(1..=16));
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt(("midi_trigger_action", i))
+                        .width(56.0)
+                        .selected_text(trigger.action.label())
+                        .show_ui(ui, |ui| {
+                            let number = *trigger.action.number_mut();
+                            ui.selectable_value(&mut trigger.action, MidiAction::Note(number), "Note");
+                            ui.selectable_value(&mut trigger.action, MidiAction::Cc(number), "CC");
+                        });
+                    ui.add(egui::DragValue::new(trigger.action.number_mut()).range(0..=127));
+                });
+                if ui.small_button("✖").on_hover_text("Remove trigger").clicked() {
+                    remove = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(i) = remove {
+        triggers.remove(i);
+    }
+    if ui.button("➕ Add Trigger").clicked() {
+        triggers.push(MidiTrigger {
+            name: format!("Band {}", triggers.len() + 1),
+            low_hz: 500.0,
+            high_hz: 2000.0,
+            threshold_db: -18.0,
+            channel: 1,
+            action: MidiAction::Cc(20 + triggers.len() as u8),
+        });
+    }
+}
+
+/// Recently played list under the Media Integration grid. Click a track to
+/// copy "Artist – Title".
+fn ui_track_history(ui: &mut Ui, state: &mut SharedState) {
+    egui::CollapsingHeader::new(format!("🕘 Track History ({})", state.track_history.len()))
+        .id_salt("track_history")
+        .show(ui, |ui| {
+            if state.track_history.is_empty() {
+                ui.label(egui::RichText::new("Nothing played yet this session").weak());
+                return;
+            }
+
+            let now = Instant::now();
+            egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                for entry in state.track_history.entries() {
+                    ui.horizontal(|ui| {
+                        let minutes = now.saturating_duration_since(entry.seen_at).as_secs() / 60;
+                        ui.label(egui::RichText::new(entry.clock()).monospace().weak())
+                            .on_hover_text(format!("{} min ago, via {}", minutes, entry.source_app));
+
+                        let text = entry.display();
+                        let response = ui.add(egui::Label::new(&text).truncate().sense(egui::Sense::click()))
+                            .on_hover_text("Click to copy");
+                        if response.hovered() {
+                            ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+                        }
+                        if response.clicked() {
+                            ui.ctx().copy_text(text);
+                        }
+                    });
+                }
+            });
+        });
+}
+
+/// Synced lyrics rows of the Media Integration grid
+fn ui_media_overlay(ui: &mut Ui, state: &mut SharedState) {
+    if state.config.media_display_mode == MediaDisplayMode::Off {
+        return;
+    }
+    let layout = &mut state.config.media_overlay;
+
+    row_label(ui, label::OVERLAY_CORNER);
+    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+        egui::ComboBox::from_id_salt("media_overlay_corner")
+            .selected_text(layout.corner.label())
+            .show_ui(ui, |ui| {
+                for corner in OverlayCorner::ALL {
+                    ui.selectable_value(&mut layout.corner, corner, corner.label());
+                }
+            });
+    });
+    ui.end_row();
+
+    row_label(ui, label::OVERLAY_SIZE);
+    ui.add(egui::Slider::new(&mut layout.scale, 0.5..=2.0).fixed_decimals(2).suffix("×"));
+    ui.end_row();
+
+    row_label(ui, label::OVERLAY_ELEMENTS);
+    ui.horizontal_wrapped(|ui| {
+        ui.checkbox(&mut layout.show_art, "Art");
+        ui.checkbox(&mut layout.show_title, "Title");
+        ui.checkbox(&mut layout.show_artist, "Artist");
+        ui.checkbox(&mut layout.show_progress, "Progress");
+        ui.checkbox(&mut layout.show_controls, "Controls");
+        ui.checkbox(&mut layout.show_source, "Source")
+            .on_hover_text("Which player the track comes from");
+    });
+    ui.end_row();
+}
+
+fn ui_lyrics(ui: &mut Ui, state: &mut SharedState) {
+    let lyrics = &mut state.config.lyrics;
+    row_label(ui, label::SYNCED_LYRICS);
+    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+        ui.checkbox(&mut lyrics.enabled, "Show")
+            .on_hover_text("Look up time-synced lyrics on lrclib.net and scroll them\nunder the Now Playing overlay.");
+        if lyrics.enabled {
+            let status = match &state.lyrics.status {
+                crate::lyrics::LyricsStatus::Idle => "",
+                crate::lyrics::LyricsStatus::Loading => "Looking up…",
+                crate::lyrics::LyricsStatus::Ready(_) => "Found",
+                crate::lyrics::LyricsStatus::Missing => "None for this track",
+            };
+            ui.label(egui::RichText::new(status).small().weak());
+        }
+    });
+    ui.end_row();
+
+    if !lyrics.enabled {
+        return;
+    }
+
+    row_label(ui, label::LYRICS_FONT);
+    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+        egui::ComboBox::from_id_salt("lyrics_font")
+            .selected_text(format!("{:?}", lyrics.font))
+            .show_ui(ui, |ui| {
+                use crate::shared_state::ThemeFont;
+                for font in [ThemeFont::Small, ThemeFont::Medium, ThemeFont::Large, ThemeFont::Monospace] {
+                    ui.selectable_value(&mut lyrics.font, font, format!("{:?}", font));
+                }
+            });
+    });
+    ui.end_row();
+
+    row_label(ui, label::LYRICS_OPACITY);
+    ui.add(egui::Slider::new(&mut lyrics.opacity, 0.1..=1.0));
+    ui.end_row();
+
+    row_label(ui, label::LYRICS_CONTEXT_LINES);
+    ui.add(egui::Slider::new(&mut lyrics.context_lines, 0..=4))
+        .on_hover_text("Lines shown before and after the current one");
+    ui.end_row();
+
+    row_label(ui, label::LYRICS_OFFSET);
+    ui.add(egui::DragValue::new(&mut lyrics.offset_ms).speed(50.0).range(-30_000..=30_000).suffix(" ms"))
+        .on_hover_text("Positive shows lines earlier. Players that don't report their\nposition are timed from when the track was first seen,\nso a track joined midway needs a nudge.");
+    ui.end_row();
+}
+
+/// Right-click menu actions the caller carries out after the menu closes
+#[derive(Default)]
+pub struct MenuActions {
+    pub screenshot: bool,
+    pub toggle_mini: bool,
+    pub toggle_pause: bool,
+}
+
 /// Handle high-level windo interaction:
 /// 1. Dragging (primary click)
 /// 2. Maximize/Restore (double click)
-/// 3. Settings Menu (right click)
+/// 3. Quick action menu (right click), contents picked in Settings → Window
 pub fn handle_window_interaction(
     ui: &mut Ui,
     ctx: &Context,
     rect: Rect,
-    settings_open: &mut bool
-){
+    shared_state: &Arc<Mutex<SharedState>>,
+    settings_open: &mut bool,
+    paused: bool,
+) -> MenuActions {
+    let mut actions = MenuActions::default();
+
     // 1. REVERT to Sense::click()
     // We MUST NOT use Sense::drag() here. It consumes the events needed for 
     // the context menu and double-click logic.
@@ -69,6 +468,9 @@ pub fn handle_window_interaction(
     // Works now because Sense::click() allows the secondary click to register.
     interaction.context_menu(|ui| {
         ui.set_min_width(100.0);
+        let Ok(mut state) = shared_state.lock() else { return };
+        let items = state.config.context_menu;
+
         if ui.button("⚙ Settings").clicked() {
             *settings_open = true;
             ctx.send_viewport_cmd_to(
@@ -77,19 +479,294 @@ pub fn handle_window_interaction(
             );
             ui.close_menu();
         }
+        if items.presets {
+            ui.menu_button("🎨 Visual Profile", |ui| menu_visual_profiles(ui, &mut state));
+            ui.menu_button("🖌 Colors", |ui| menu_color_presets(ui, &mut state));
+        }
+        if items.devices {
+            ui.menu_button("🎤 Device", |ui| menu_devices(ui, &mut state));
+        }
+        if items.ghost_mode || items.pause || items.screenshot || items.mini_mode {
+            ui.separator();
+        }
+        if items.ghost_mode {
+            let label = if state.config.window_locked { "🔓 Leave Ghost Mode" } else { "👻 Ghost Mode" };
+            if ui.button(label).on_hover_text("Click-through while another app has focus\n(needs a transparent background)").clicked() {
+                state.config.window_locked = !state.config.window_locked;
+                ui.close_menu();
+            }
+        }
+        if items.pause {
+            let label = if paused { "▶ Resume" } else { "⏸ Pause" };
+            if ui.button(label).on_hover_text("Freeze the display (audio keeps being analyzed)").clicked() {
+                actions.toggle_pause = true;
+                ui.close_menu();
+            }
+        }
+        if items.screenshot && ui.button("📷 Screenshot").on_hover_text("Save a PNG of the spectrum").clicked() {
+            actions.screenshot = true;
+            ui.close_menu();
+        }
+        if items.mini_mode {
+            let mini_label = if state.config.mini_mode { "▣ Full Mode" } else { "▭ Mini Mode" };
+            if ui.button(mini_label).on_hover_text("Toggle the compact strip (Ctrl+M by default)").clicked() {
+                actions.toggle_mini = true;
+                ui.close_menu();
+            }
+        }
         ui.separator();
         if ui.button("❌ Exit").clicked() {
             ctx.send_viewport_cmd(egui::ViewportCommand::Close);
         }
     });
+    actions
+}
+
+fn menu_visual_profiles(ui: &mut Ui, state: &mut SharedState) {
+    let mut profiles = state.user_visual_presets.clone();
+    profiles.extend(VisualProfile::built_in());
+    for vp in profiles {
+        if ui.selectable_label(state.config.profile.name == vp.name, &vp.name).clicked() {
+            state.config.profile = vp;
+            ui.close_menu();
+        }
+    }
+}
+
+fn menu_color_presets(ui: &mut Ui, state: &mut SharedState) {
+    for name in crate::commands::color_preset_names(state) {
+        let current = matches!(&state.config.profile.color_link, ColorRef::Preset(n) if *n == name);
+        if ui.selectable_label(current, &name).clicked() {
+            state.config.profile.color_link = ColorRef::Preset(name);
+            state.config.profile.background = None;
+            ui.close_menu();
+        }
+    }
+}
+
+fn menu_devices(ui: &mut Ui, state: &mut SharedState) {
+    let mut picked = None;
+    if ui.selectable_label(state.config.selected_device == "Default", "Default System Device").clicked() {
+        picked = Some(("Default".to_string(), String::new()));
+    }
+    for dev in &state.audio_devices {
+        if ui.selectable_label(state.config.selected_device == dev.id, &dev.name).clicked() {
+            picked = Some((dev.id.clone(), dev.name.clone()));
+        }
+    }
+    if let Some((id, name)) = picked {
+        tracing::info!("[GUI] User selected device from menu: '{}' (id: {})", name, id);
+        state.select_device(&id, &name);
+        ui.close_menu();
+    }
+}
+
+/// Where a window dragged to `window` should settle: each axis snaps to the
+/// closest edge within `threshold` of a monitor the window overlaps, by
+/// either side of the window (corners fall out of snapping both axes).
+/// Everything is in physical pixels; each monitor comes with its own scale
+/// factor, so `threshold` (points) covers the same distance on all of them.
+pub fn snap_window_position(window: Rect, monitors: &[(Rect, f32)], threshold: f32) -> egui::Pos2 {
+    fn snap_axis(pos: f32, len: f32, edges: impl Iterator<Item = (f32, f32)>) -> f32 {
+        let mut best = pos;
+        let mut best_dist = f32::INFINITY;
+        for (edge, threshold) in edges {
+            for candidate in [edge, edge - len] {
+                let dist = (candidate - pos).abs();
+                if dist <= threshold && dist <= best_dist {
+                    best = candidate;
+                    best_dist = dist;
+                }
+            }
+        }
+        best
+    }
+
+    let touching: Vec<(Rect, f32)> = monitors
+        .iter()
+        .filter(|(m, _)| m.intersects(window))
+        .map(|&(m, scale)| (m, threshold * scale))
+        .collect();
+    egui::pos2(
+        snap_axis(window.min.x, window.width(), touching.iter().flat_map(|&(m, t)| [(m.min.x, t), (m.max.x, t)])),
+        snap_axis(window.min.y, window.height(), touching.iter().flat_map(|&(m, t)| [(m.min.y, t), (m.max.y, t)])),
+    )
+}
+
+/// Spectrogram history navigation over the visualizer:
+/// 1. Mouse wheel scrolls back/forward in time, Ctrl+wheel (or pinch) zooms
+/// 2. Space pauses/resumes, End jumps back to live
+/// 3. Click or drag the minimap to jump anywhere in the history
+pub fn handle_spectrogram_input(ui: &mut Ui, rect: Rect, history: &crate::spectrogram::SpectrogramHistory) {
+    let ctx = ui.ctx().clone();
+    let mut view = visualizers::spectrogram_view(&ctx);
+    let before = view;
+
+    let minimap = ui.interact(
+        visualizers::spectrogram_minimap_rect(rect),
+        ui.id().with("spectrogram_minimap"),
+        egui::Sense::click_and_drag(),
+    );
+    if minimap.clicked() || minimap.dragged() {
+        if let Some(pos) = minimap.interact_pointer_pos() {
+            view.jump_to((pos.x - minimap.rect.left()) / minimap.rect.width(), history);
+        }
+    }
+
+    let (scroll, zoom, space, end) = ui.input(|i| (
+        i.smooth_scroll_delta,
+        i.zoom_delta(),
+        i.key_pressed(egui::Key::Space),
+        i.key_pressed(egui::Key::End),
+    ));
+    if ui.rect_contains_pointer(rect) {
+        if zoom != 1.0 {
+            view.zoom(zoom);
+        }
+        // One wheel notch (~50 pt) moves a tenth of the visible span
+        let wheel = scroll.x + scroll.y;
+        if wheel != 0.0 {
+            view.scroll(wheel / 50.0 * view.span_sec * 0.1, history);
+        }
+    }
+    if space {
+        view.toggle_pause(history);
+    }
+    if end {
+        view.anchor = None;
+    }
+
+    if view != before {
+        visualizers::set_spectrogram_view(&ctx, view);
+    }
+}
+
+/// Red banner across the top of the visualizer for the latest error.
+/// Returns true when the user dismissed it.
+pub fn draw_error_banner(ui: &mut Ui, rect: Rect, err: &crate::error::BeSpecError) -> bool {
+    let mut dismissed = false;
+
+    ui.allocate_new_ui(egui::UiBuilder::new().max_rect(rect), |ui| {
+        ui.painter().rect_filled(rect, 0.0, Color32::from_rgb(150, 30, 30));
+        ui.painter().line_segment(
+            [rect.left_bottom(), rect.right_bottom()],
+            egui::Stroke::new(1.0, Color32::from_rgb(200, 60, 60))
+        );
+
+        ui.horizontal_centered(|ui| {
+            ui.add_space(8.0);
+            ui.label(egui::RichText::new(format!("⚠ {}", err)).color(Color32::WHITE).strong())
+                .on_hover_text("Details are in the log file");
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.add_space(8.0);
+                if ui.add(egui::Button::new(egui::RichText::new(" 🗙 ").color(Color32::WHITE).strong()).frame(false))
+                    .on_hover_text("Dismiss")
+                    .clicked()
+                {
+                    dismissed = true;
+                }
+            });
+        });
+    });
+
+    dismissed
+}
+
+/// Small dismissible hint pill along the bottom of the visualizer (gain advisor).
+/// Drawn over the spectrum instead of pushing it around like the banners do,
+/// since hints come and go with the music. Returns true when dismissed.
+pub fn draw_hint_pill(ui: &mut Ui, viz_rect: Rect, message: &str) -> bool {
+    let mut dismissed = false;
+
+    let height = 24.0;
+    let width = (viz_rect.width() - 20.0).clamp(40.0, 520.0);
+    let rect = Rect::from_center_size(
+        egui::pos2(viz_rect.center().x, viz_rect.bottom() - height / 2.0 - 10.0),
+        egui::vec2(width, height),
+    );
+
+    ui.allocate_new_ui(egui::UiBuilder::new().max_rect(rect), |ui| {
+        ui.painter().rect_filled(rect, 12.0, Color32::from_rgba_unmultiplied(40, 40, 40, 220));
+        ui.painter().rect_stroke(rect, 12.0, egui::Stroke::new(1.0, Color32::from_rgb(220, 170, 40)));
+
+        ui.horizontal_centered(|ui| {
+            ui.add_space(10.0);
+            ui.label(egui::RichText::new(format!("💡 {}", message)).color(Color32::WHITE).small())
+                .on_hover_text("Gain advisor. Turn hints off in Settings → Audio.");
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.add_space(8.0);
+                if ui.add(egui::Button::new(egui::RichText::new("🗙").color(Color32::WHITE)).frame(false))
+                    .on_hover_text("Dismiss")
+                    .clicked()
+                {
+                    dismissed = true;
+                }
+            });
+        });
+    });
+
+    dismissed
+}
+
+/// What the user picked on the "no audio device" card
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoDeviceAction {
+    Retry,
+    OpenSettings,
+}
+
+/// Card in the middle of the visualizer while no audio device could be
+/// opened, in place of a flat spectrum. `retrying` shows a spinner while a
+/// retry is waiting for the capture thread.
+pub fn draw_no_device_card(ui: &mut Ui, viz_rect: Rect, retrying: bool) -> Option<NoDeviceAction> {
+    let mut action = None;
+
+    let size = egui::vec2((viz_rect.width() - 20.0).clamp(40.0, 360.0), 110.0);
+    let rect = Rect::from_center_size(viz_rect.center(), size);
+
+    ui.allocate_new_ui(egui::UiBuilder::new().max_rect(rect), |ui| {
+        ui.painter().rect_filled(rect, 8.0, Color32::from_rgba_unmultiplied(40, 40, 40, 230));
+        ui.painter().rect_stroke(rect, 8.0, egui::Stroke::new(1.0, Color32::from_gray(90)));
+
+        ui.vertical_centered(|ui| {
+            ui.add_space(12.0);
+            ui.label(egui::RichText::new("🔇 No audio device").color(Color32::WHITE).strong());
+            ui.label(egui::RichText::new("Connect a device or check your sound settings, then retry.")
+                .color(Color32::from_gray(200))
+                .small());
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                // Center the two buttons
+                ui.add_space((ui.available_width() - 190.0).max(0.0) / 2.0);
+                if retrying {
+                    ui.add_enabled(false, egui::Button::new("⟳ Retrying…"));
+                } else if ui.button("⟳ Retry").on_hover_text("Scan for audio devices and try again").clicked() {
+                    action = Some(NoDeviceAction::Retry);
+                }
+                if ui.button("⚙ Audio Settings").clicked() {
+                    action = Some(NoDeviceAction::OpenSettings);
+                }
+            });
+        });
+    });
+
+    action
 }
 
-/// Draw the discrete resize grip in the bottom-right (or tope-right if inverted)
+/// Smallest window the aspect-locked grip shrinks to (width or height)
+const MIN_LOCKED_SIDE: f32 = 60.0;
+
+/// Draw the discrete resize grip in the bottom-right (or tope-right if inverted).
+/// With `aspect` (width / height) the grip resizes the window itself, keeping
+/// that ratio; the OS resize it starts otherwise can't be held to one.
 pub fn draw_resize_grip(
     ui: &mut Ui,
     ctx: &Context,
     rect: Rect,
-    is_inverted: bool
+    is_inverted: bool,
+    aspect: Option<f32>,
 ){
   
     let corner_size = 20.0;
@@ -121,8 +798,17 @@ pub fn draw_resize_grip(
     }
 
     // 4. Logic: Trigger resize
+    // Locked: follow the drag ourselves. The window grows away from its
+    // top-left corner, which stays put, so drag deltas stay meaningful.
+    if let Some(ratio) = aspect.filter(|_| !is_inverted) {
+        let delta = response.drag_delta();
+        if response.dragged() && delta != egui::Vec2::ZERO {
+            let size = locked_resize(ctx.screen_rect().size(), delta, ratio);
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+        }
+    }
     // Use button_pressed() for instant resize start
-    if response.hovered() && ui.input(|i| i.pointer.primary_down()){
+    else if response.hovered() && ui.input(|i| i.pointer.primary_down()){
         ctx.send_viewport_cmd(egui::ViewportCommand::BeginResize(direction));
     }
 
@@ -153,6 +839,15 @@ pub fn draw_resize_grip(
     }
 }
 
+/// `size` dragged by `delta` and held to `ratio` (width / height). The
+/// drag's average stretch of both sides decides the scale.
+fn locked_resize(size: egui::Vec2, delta: egui::Vec2, ratio: f32) -> egui::Vec2 {
+    let stretch = ((size.x + delta.x) / size.x.max(1.0) + (size.y + delta.y) / size.y.max(1.0)) / 2.0;
+    let min_width = MIN_LOCKED_SIDE * ratio.max(1.0);
+    let width = (size.x * stretch).max(min_width);
+    egui::vec2(width, width / ratio)
+}
+
 pub fn draw_lock_button(
     ui: &mut Ui,
     rect: Rect,
@@ -302,6 +997,7 @@ pub fn draw_transport_controls(
     ui: &mut Ui,
     controller: &dyn MediaController,
     is_playing: bool,
+    volume: Option<f32>,
     opacity: f32,
     base_color: egui::Color32
 ) {
@@ -309,9 +1005,17 @@ pub fn draw_transport_controls(
     let color = base_color.linear_multiply(opacity);
     let hover_bg = base_color.linear_multiply(0.15 * opacity);
 
-    // Use Right-to-Left to anchor to the right side
-    ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
-        ui.spacing_mut().item_spacing.x = 4.0;
+    // Exactly as wide as the buttons, so the overlay's own alignment
+    // (left or right corner) decides where they go
+    let spacing = 4.0;
+    let mut width = 3.0 * btn_size.x + 2.0 * spacing;
+    if volume.is_some() {
+        width += spacing + VOLUME_GAP + VOLUME_SLIDER_WIDTH + spacing + btn_size.x + spacing;
+    }
+
+    // Use Right-to-Left to fill in from Next
+    ui.allocate_ui_with_layout(egui::vec2(width, btn_size.y), egui::Layout::right_to_left(egui::Align::Min), |ui| {
+        ui.spacing_mut().item_spacing.x = spacing;
 
         // === 3. NEXT (ISO 60417-5862) ===
         // Drawn FIRST so it appears on the Far Right
@@ -427,52 +1131,177 @@ pub fn draw_transport_controls(
                 egui::Stroke::NONE
             ));
         }
+
+        // === 0. VOLUME ===
+        // Left of Previous, only when the backend reports a volume
+        if let Some(volume) = volume {
+            ui.add_space(VOLUME_GAP);
+            draw_volume_control(ui, controller, volume, btn_size.y, color, hover_bg);
+        }
     });
 }
 
+/// What the volume slider last set, shown until the media monitor reports
+/// it back (it polls about once a second)
+#[derive(Clone, Copy)]
+struct PendingVolume {
+    volume: f32,
+    set_at: f64,
+    /// Volume before muting, restored by unmuting
+    unmuted: f32,
+}
+
+const VOLUME_PENDING_SECS: f64 = 1.5;
+
+const VOLUME_GAP: f32 = 6.0;
+const VOLUME_SLIDER_WIDTH: f32 = 56.0;
+
+/// Mute toggle and a small slider. Mute is volume 0; clicking again puts
+/// back what was there before.
+fn draw_volume_control(
+    ui: &mut Ui,
+    controller: &dyn MediaController,
+    reported: f32,
+    height: f32,
+    color: egui::Color32,
+    hover_bg: egui::Color32,
+) {
+    let id = ui.id().with("volume_control");
+    let now = ui.input(|i| i.time);
+    let mut pending = ui.data(|d| d.get_temp::<PendingVolume>(id));
+    let volume = pending.filter(|p| now - p.set_at < VOLUME_PENDING_SECS).map_or(reported, |p| p.volume);
+    // Muted somewhere else: unmute to half volume
+    let unmuted = pending.map_or(0.5, |p| p.unmuted);
+    let mut set = |new: f32, unmuted: f32, send: bool| {
+        if send {
+            controller.try_set_volume(new);
+        }
+        pending = Some(PendingVolume { volume: new, set_at: now, unmuted });
+    };
+
+    // Slider (drawn first, it sits right of the speaker in this layout)
+    let (rect, resp) = ui.allocate_exact_size(egui::vec2(VOLUME_SLIDER_WIDTH, height), egui::Sense::click_and_drag());
+    let track = egui::Rect::from_center_size(rect.center(), egui::vec2(rect.width(), 3.0));
+    let released = resp.drag_stopped();
+    if let Some(pointer) = resp.interact_pointer_pos().filter(|_| resp.clicked() || resp.dragged() || released) {
+        let target = ((pointer.x - track.left()) / track.width()).clamp(0.0, 1.0);
+        let unmuted = if target > 0.0 { target } else { unmuted };
+        // Every set is a D-Bus / COM round trip, so a drag only moves the
+        // knob and the player hears about it on release
+        if resp.dragged() {
+            set(target, unmuted, false);
+        } else if released || (target - volume).abs() >= 0.01 {
+            set(target, unmuted, true);
+        }
+    }
+    if resp.hovered() || resp.dragged() {
+        ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+    }
+    let painter = ui.painter();
+    painter.rect_filled(track, 1.5, color.linear_multiply(0.25));
+    let knob_x = track.left() + track.width() * volume;
+    painter.rect_filled(egui::Rect::from_min_max(track.min, egui::pos2(knob_x, track.max.y)), 1.5, color);
+    painter.circle_filled(egui::pos2(knob_x, track.center().y), 4.0, color);
+    resp.on_hover_text(format!("Volume {:.0}%", volume * 100.0));
+
+    // Speaker / mute button
+    let (rect, mute_resp) = ui.allocate_exact_size(egui::vec2(height, height), egui::Sense::click());
+    if mute_resp.hovered() { ui.painter().rect_filled(rect.expand(2.0), 4.0, hover_bg); }
+    if mute_resp.clicked() {
+        if volume > 0.0 {
+            set(0.0, volume, true);
+        } else {
+            set(unmuted, unmuted, true);
+        }
+    }
+    let icon = if volume <= 0.0 { "🔇" } else if volume < 0.5 { "🔉" } else { "🔊" };
+    ui.painter().text(rect.center(), egui::Align2::CENTER_CENTER, icon, egui::FontId::proportional(14.0), color);
+    mute_resp.on_hover_text(if volume > 0.0 { "Mute" } else { "Unmute" });
+
+    if let Some(pending) = pending {
+        ui.data_mut(|d| d.insert_temp(id, pending));
+    }
+}
+
 
 // =======================================================================================
 // SETTINGS 
 // =======================================================================================
 /// Render settings window content
+/// Returns true if the footer "Undo" button was clicked this frame
 pub fn show_settings_window(
     ui: &mut egui::Ui,
     state: &mut SharedState,
     active_tab: &mut SettingsTab,
     save_target: &mut SaveTarget,
-    new_preset_name: &mut String
-) {
-    // Tabs
+    new_preset_name: &mut String,
+    search_query: &mut String,
+    undo_depth: usize,
+) -> bool {
+    let mut undo_clicked = false;
+
+    // Search
+    ui.add_space(5.0);
+    ui.horizontal(|ui| {
+        ui.add(egui::TextEdit::singleline(search_query)
+            .hint_text("🔍 Search settings")
+            .desired_width(ui.available_width() - 30.0));
+        if !search_query.is_empty() && ui.small_button("✖").on_hover_text("Clear search").clicked() {
+            search_query.clear();
+        }
+    });
+
+    // Tabs (with the number of matches while searching)
     ui.add_space(5.0);
     ui.horizontal(|ui| {
         let colors = state.config.resolve_colors(&state.user_color_presets);
         let highlight = to_egui_color(colors.high);
-        ui_tab_button(ui, " Visual ", SettingsTab::Visual, active_tab, highlight);
-        ui_tab_button(ui, " Colors ", SettingsTab::Colors, active_tab, highlight);
-        ui_tab_button(ui, " Audio ", SettingsTab::Audio, active_tab, highlight);
-        ui_tab_button(ui, " Window ", SettingsTab::Window, active_tab, highlight);
-        ui_tab_button(ui, " Stats ", SettingsTab::Performance, active_tab, highlight);
+        for tab in [SettingsTab::Visual, SettingsTab::Colors, SettingsTab::Audio, SettingsTab::Window, SettingsTab::Tools, SettingsTab::Performance] {
+            let label = match settings_search::count_in_tab(search_query, &tab) {
+                0 => format!(" {} ", tab.label()),
+                n => format!(" {} ({}) ", tab.label(), n),
+            };
+            ui_tab_button(ui, &label, tab, active_tab, highlight);
+        }
     });
     ui.separator();
 
     egui::ScrollArea::vertical().show(ui, |ui| {
+        if !search_query.trim().is_empty() {
+            settings_search::ui_results(ui, search_query, active_tab);
+            return;
+        }
         match active_tab {
             SettingsTab::Visual => settings_tab_visual(ui, state, save_target, new_preset_name),
             SettingsTab::Audio => settings_tab_audio(ui, state),
             SettingsTab::Colors => settings_tab_colors(ui, state, save_target, new_preset_name),
             SettingsTab::Window => settings_tab_window(ui, state),
+            SettingsTab::Tools => settings_tab_tools(ui, state),
             SettingsTab::Performance => settings_tab_performance(ui, state),
         }
     });
 
     ui.separator();
-        ui.vertical_centered(|ui| {
+        ui.horizontal(|ui| {
             ui.add_space(5.0);
-            ui.label(
-                egui::RichText::new(format!("BeSpec v{}", env!("CARGO_PKG_VERSION")))
-                .small()
-            );
+            let version = match &crate::paths::get().user {
+                Some(user) => format!("BeSpec v{}  ·  user: {}", env!("CARGO_PKG_VERSION"), user),
+                None => format!("BeSpec v{}", env!("CARGO_PKG_VERSION")),
+            };
+            ui.label(egui::RichText::new(version).small());
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let label = if undo_depth > 0 { format!("↶ Undo ({})", undo_depth) } else { "↶ Undo".to_string() };
+                if ui.add_enabled(undo_depth > 0, egui::Button::new(label).small())
+                    .on_hover_text("Undo last settings change (Ctrl+Z)")
+                    .clicked()
+                {
+                    undo_clicked = true;
+                }
+            });
         });
+
+    undo_clicked
 }
 
 pub fn settings_tab_visual(
@@ -488,7 +1317,7 @@ pub fn settings_tab_visual(
             ui.add_space(4.0);
             
             ui.horizontal(|ui| {
-                ui.label("Visual Profile:");
+                row_label(ui, label::VISUAL_PROFILE);
                 egui::ComboBox::from_id_salt("viz_profile_combo")
                     .selected_text(&state.config.profile.name)
                     .show_ui(ui, |ui| {
@@ -498,12 +1327,21 @@ pub fn settings_tab_visual(
                             let _ = ui.selectable_label(false, egui::RichText::new("--- User Presets ---").strong());
                             for vp in &user_visuals {
                                 ui.horizontal(|ui| {
+                                    let thumb = thumbnails::texture(ui.ctx(), PresetKind::Visual, &vp.name, || {
+                                        let request = preset_thumbnail::Request::visual(vp, &state.user_color_presets);
+                                        state.thumbnail_requests.push(request);
+                                    });
+                                    if let Some(thumb) = thumb {
+                                        thumbnails::show(ui, &thumb);
+                                    }
                                     if ui.selectable_label(state.config.profile.name == vp.name, &vp.name).clicked() {
                                         state.config.profile = vp.clone();
                                     }
                                     // Delete button
                                     if ui.small_button("🗑").clicked() {
+                                        backup_before(state, "delete visual preset");
                                         let _ = crate::shared_state::AppConfig::delete_user_visual_preset(&vp.name);
+                                        thumbnails::forget(ui.ctx(), PresetKind::Visual, &vp.name);
                                         // Remove from memory immediately
                                         state.user_visual_presets.retain(|p| p.name != vp.name);
                                     }
@@ -529,7 +1367,29 @@ pub fn settings_tab_visual(
                 });
             });
 
-        // -- Save Popup --
+            ui_theme_file_buttons(ui, state, save_target, new_preset_name);
+
+            ui.horizontal(|ui| {
+                row_label(ui, label::A_B_COMPARE);
+                let on_b = state.ab_compare.on_b;
+                for (slot, showing) in [("A", !on_b), ("B", on_b)] {
+                    if ui.selectable_label(showing, slot).on_hover_text("Press B in the main window to flip").clicked() && !showing {
+                        state.ab_compare.flip(&mut state.config.profile);
+                    }
+                }
+                ui.separator();
+                if ui.button("Copy A→B").clicked() {
+                    state.ab_compare.copy_a_to_b(&mut state.config.profile);
+                }
+                if ui.add_enabled(state.ab_compare.other.is_some(), egui::Button::new("Swap"))
+                    .on_hover_text("Exchange the two looks")
+                    .clicked()
+                {
+                    state.ab_compare.swap(&mut state.config.profile);
+                }
+            });
+
+        // -- Save Popup --
         if *save_target == SaveTarget::Visual {
             ui.add_space(4.0);
             ui_save_popup(ui, new_preset_name, |name| {
@@ -537,6 +1397,8 @@ pub fn settings_tab_visual(
                 if let Err(e) = crate::shared_state::AppConfig::save_user_visual_preset(&state.config.profile) {
                     eprintln!("Error saving visual preset: {}", e);
                 } else {
+                    let request = preset_thumbnail::Request::visual(&state.config.profile, &state.user_color_presets);
+                    state.thumbnail_requests.push(request);
                     if let Some(existing) = state.user_visual_presets.iter_mut().find(|p| p.name == name) {
                         *existing = state.config.profile.clone();
                     } else {
@@ -545,6 +1407,14 @@ pub fn settings_tab_visual(
                 }
             }, save_target);
         }
+
+        if *save_target == SaveTarget::Import {
+            ui.add_space(4.0);
+            ui_import_popup(ui, state, save_target, new_preset_name);
+        }
+
+        ui.add_space(4.0);
+        ui_preset_gallery(ui, state);
     });
 
     ui.add_space(10.0);
@@ -554,19 +1424,130 @@ pub fn settings_tab_visual(
         // --- Visual Controls ---
         ui.group(|ui| {
             egui::Grid::new("visual_grid").num_columns(2).spacing(grid_spacing).show(ui, |ui| {
-                ui.label("Mode");
+                row_label(ui, label::MODE);
+                let mode_text = match state.config.profile.visual_mode {
+                    VisualMode::Plugin => state.config.profile.plugin.clone(),
+                    mode => format!("{:?}", mode),
+                };
                 egui::ComboBox::from_id_salt("viz_mode")
-                    .selected_text(format!("{:?}", state.config.profile.visual_mode))
+                    .selected_text(mode_text)
                     .show_ui(ui, |ui| {
                         ui.selectable_value(&mut state.config.profile.visual_mode, VisualMode::SolidBars, "Solid Bars");
                         ui.selectable_value(&mut state.config.profile.visual_mode, VisualMode::SegmentedBars, "Segmented (LED)");
                         ui.selectable_value(&mut state.config.profile.visual_mode, VisualMode::LineSpectrum, "Line Spectrum");
                         ui.selectable_value(&mut state.config.profile.visual_mode, VisualMode::Oscilloscope, "Oscilloscope");
+                        ui.selectable_value(&mut state.config.profile.visual_mode, VisualMode::Spectrogram, "Spectrogram");
+                        ui.selectable_value(&mut state.config.profile.visual_mode, VisualMode::Mountain, "Mountain (3D)");
+                        ui.selectable_value(&mut state.config.profile.visual_mode, VisualMode::DotMatrix, "Dot Matrix");
+                        if cfg!(feature = "scripting") {
+                            ui.selectable_value(&mut state.config.profile.visual_mode, VisualMode::Script, "Script");
+                        }
+                        if milkdrop::SUPPORTED {
+                            ui.selectable_value(&mut state.config.profile.visual_mode, VisualMode::MilkDrop, "MilkDrop");
+                        }
+                        // Native plugins from the plugins folder, one entry each
+                        #[cfg(feature = "plugins")]
+                        let profile = &mut state.config.profile;
+                        #[cfg(feature = "plugins")]
+                        for plugin in plugins::all() {
+                            let selected = profile.visual_mode == VisualMode::Plugin && profile.plugin == plugin.name;
+                            if ui.selectable_label(selected, format!("🧩 {}", plugin.name))
+                                .on_hover_text(format!("Plugin: {}", plugin.file.display()))
+                                .clicked()
+                            {
+                                profile.visual_mode = VisualMode::Plugin;
+                                profile.plugin = plugin.name.clone();
+                            }
+                        }
+                    });
+                ui.end_row();
+
+                #[cfg(feature = "scripting")]
+                if state.config.profile.visual_mode == VisualMode::Script {
+                    row_label(ui, label::SCRIPT);
+                    ui.horizontal(|ui| {
+                        let selected = if state.config.profile.script.is_empty() { "(none)" } else { state.config.profile.script.as_str() };
+                        egui::ComboBox::from_id_salt("viz_script")
+                            .selected_text(selected.to_string())
+                            .show_ui(ui, |ui| {
+                                for name in script::list_scripts() {
+                                    ui.selectable_value(&mut state.config.profile.script, name.clone(), name);
+                                }
+                            });
+                        if ui.small_button("📂").on_hover_text("Open the scripts folder").clicked() {
+                            let _ = open::that(script::scripts_dir());
+                        }
+                    })
+                    .response
+                    .on_hover_text("A .rhai file from the scripts folder, run every frame.\nSaved edits show up within a second. See docs/scripting.md.");
+                    ui.end_row();
+                }
+
+                if state.config.profile.visual_mode == VisualMode::Mountain {
+                    row_label(ui, label::MOUNTAIN_DEPTH);
+                    ui.add(egui::Slider::new(&mut state.config.profile.mountain_lines, 8..=120).suffix(" ridges"))
+                        .on_hover_text("How many past frames recede into the distance, 0.1 s apart.\nShares its history with the Spectrogram.");
+                    ui.end_row();
+                }
+
+                if state.config.profile.visual_mode == VisualMode::DotMatrix {
+                    let dots = &mut state.config.profile.dot_matrix;
+                    row_label(ui, label::DOT_GRID);
+                    ui.horizontal(|ui| {
+                        ui.add(egui::Slider::new(&mut dots.columns, 8..=160).suffix(" across"));
+                        ui.add(egui::Slider::new(&mut dots.rows, 4..=64).suffix(" high"));
+                    });
+                    ui.end_row();
+
+                    row_label(ui, label::DOT_STYLE);
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_salt("dot_style")
+                            .selected_text(dots.style.label())
+                            .show_ui(ui, |ui| {
+                                for style in DotStyle::ALL {
+                                    ui.selectable_value(&mut dots.style, style, style.label());
+                                }
+                            });
+                        ui.checkbox(&mut dots.glow, "Phosphor Glow");
+                    });
+                    ui.end_row();
+                }
+
+                if state.config.profile.visual_mode == VisualMode::MilkDrop {
+                    row_label(ui, label::MILKDROP_PRESET);
+                    ui_milkdrop_presets(ui, &mut state.config.profile.milkdrop_preset);
+                    ui.end_row();
+                }
+
+                row_label(ui, label::SPLIT_VIEW);
+                ui.horizontal(|ui| {
+                    let split = &mut state.config.profile.split;
+                    ui.checkbox(&mut split.enabled, "");
+                    ui.add_enabled_ui(split.enabled, |ui| {
+                        egui::ComboBox::from_id_salt("split_mode")
+                            .selected_text(format!("{:?}", split.mode))
+                            .show_ui(ui, |ui| {
+                                for mode in [VisualMode::SolidBars, VisualMode::SegmentedBars, VisualMode::LineSpectrum, VisualMode::Oscilloscope, VisualMode::Spectrogram, VisualMode::Mountain, VisualMode::DotMatrix] {
+                                    ui.selectable_value(&mut split.mode, mode, format!("{:?}", mode));
+                                }
+                            });
+                        egui::ComboBox::from_id_salt("split_direction")
+                            .selected_text(split.direction.label())
+                            .show_ui(ui, |ui| {
+                                for direction in SplitDirection::ALL {
+                                    ui.selectable_value(&mut split.direction, direction, direction.label());
+                                }
+                            });
+                        ui.add(egui::Slider::new(&mut split.ratio, 0.1..=0.9).show_value(false))
+                            .on_hover_text("How much of the area the main mode gets");
                     });
+                })
+                .response
+                .on_hover_text("Draw a second mode next to the main one, e.g. the spectrum on top and the oscilloscope below.\nBoth halves use this profile's settings.");
                 ui.end_row();
 
                 if state.config.profile.visual_mode != VisualMode::Oscilloscope {
-                    ui.label("VU Coloring");
+                    row_label(ui, label::VU_COLORING);
                     egui::ComboBox::from_id_salt("vu_coloring")
                         .selected_text(format!("{:?}", state.config.profile.vu_coloring))
                         .show_ui(ui, |ui| {
@@ -574,26 +1555,102 @@ pub fn settings_tab_visual(
                             ui.selectable_value(&mut state.config.profile.vu_coloring, VuColoring::Retro, "Retro");
                         });
                     ui.end_row();
+
+                    if matches!(state.config.profile.visual_mode, VisualMode::SolidBars | VisualMode::SegmentedBars) {
+                        row_label(ui, label::COLOR_MAPPING);
+                        ui.horizontal(|ui| {
+                            let mapping = &mut state.config.profile.color_mapping;
+                            ui.selectable_value(mapping, ColorMapping::Amplitude, "By Height");
+                            ui.selectable_value(mapping, ColorMapping::Frequency, "Rainbow by Frequency");
+                        })
+                        .response
+                        .on_hover_text("By Height follows VU Coloring and the color preset.\nRainbow by Frequency gives every bar its own hue, red bass to violet treble.");
+                        ui.end_row();
+                    }
+
+                    if !state.config.profile.visual_mode.uses_history() {
+                        row_label(ui, label::INTERPOLATION);
+                        egui::ComboBox::from_id_salt("bar_interpolation")
+                            .selected_text(state.config.profile.bar_interpolation.label())
+                            .show_ui(ui, |ui| {
+                                for mode in BarInterpolation::ALL {
+                                    ui.selectable_value(&mut state.config.profile.bar_interpolation, mode, mode.label());
+                                }
+                            })
+                            .response
+                            .on_hover_text("How the Line Spectrum (and the reference / response lines) get from one bar to the next.\nCubic Spline keeps low bar counts smooth.");
+                        ui.end_row();
+                    }
+                } else {
+                    row_label(ui, label::AUTOSCALE);
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut state.config.profile.scope_autoscale, "")
+                            .on_hover_text("Zoom the trace to the recent peak level, so quiet speech and loud music\nboth fill the view. Sensitivity still trims the result.");
+                        ui.add_enabled(
+                            state.config.profile.scope_autoscale,
+                            egui::Slider::new(&mut state.config.profile.scope_max_zoom, 1.0..=100.0)
+                                .logarithmic(true)
+                                .prefix("max ×"),
+                        );
+                    });
+                    ui.end_row();
+
+                    row_label(ui, label::TRACE_COLOR);
+                    ui.checkbox(&mut state.config.profile.scope_color_by_frequency, "By Frequency")
+                        .on_hover_text("Bass-heavy parts of the trace use the low color, treble the high color.");
+                    ui.end_row();
+
+                    row_label(ui, label::TRACES);
+                    ui.horizontal(|ui| {
+                        let traces = &mut state.config.profile.scope_traces;
+                        ui.checkbox(&mut traces.mix, "L+R");
+                        ui.checkbox(&mut traces.left, "L");
+                        ui.checkbox(&mut traces.right, "R");
+                        ui.checkbox(&mut traces.difference, "L-R");
+                    })
+                    .response
+                    .on_hover_text("Channels to draw. L, R and L-R (difference) need a stereo source\nand use the low, peak and overlay text colors.");
+                    ui.end_row();
                 }
 
                 // Specific Controls
                 if state.config.profile.visual_mode != VisualMode::Oscilloscope {
-                    ui.label("Bar Count");
+                    row_label(ui, label::BAR_COUNT);
                     ui.add(egui::Slider::new(&mut state.config.profile.num_bars, 10..=512)
                         .step_by(1.0).drag_value_speed(1.0).smart_aim(false));
                     ui.end_row();
 
-                    ui.label("Bar Gap");
+                    row_label(ui, label::BAR_GAP);
                     ui.add(egui::Slider::new(&mut state.config.profile.bar_gap_px, 0..=10).suffix(" px"));
                     ui.end_row();
                 }
                 
-                ui.label("Bar Opacity");
+                row_label(ui, label::BAR_OPACITY);
                 ui.add(egui::Slider::new(&mut state.config.profile.bar_opacity, 0.0..=1.0));
                 ui.end_row();
 
+                if matches!(state.config.profile.visual_mode, VisualMode::SolidBars | VisualMode::SegmentedBars | VisualMode::LineSpectrum) {
+                    row_label(ui, label::GLOW);
+                    let glow = &mut state.config.profile.glow;
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut glow.enabled, "")
+                            .on_hover_text("Bloom around the bars, brighter when the music is louder.");
+                        ui.add_enabled_ui(glow.enabled, |ui| {
+                            ui.add(egui::Slider::new(&mut glow.intensity, 0.0..=1.0).text("strength"));
+                            ui.add(egui::Slider::new(&mut glow.reactivity, 0.0..=1.0).text("reactive"))
+                                .on_hover_text("How much the glow follows the loudness.\n0 glows the same all the time, 1 goes dark in silence.");
+                        });
+                    });
+                    ui.end_row();
+                }
+
+                row_label(ui, label::CONTENT_ZOOM);
+                ui.add(egui::Slider::new(&mut state.config.profile.content_zoom, 1.0..=visualizers::MAX_CONTENT_ZOOM).step_by(0.25).prefix("×"))
+                    .on_hover_text("Draw at a lower virtual resolution and scale it up to the window.\nChunky, readable bars in a small window; 1× is off.");
+                ui.end_row();
+
                 // NEW: Background Opacity Slider Logic
-                ui.label("Background Opacity");
+                row_label(ui, label::BACKGROUND_OPACITY);
                 // FIX: Resolve immutable colors first, don't hold lock long if possible, 
                 // but here we are modifying state in UI so we need the lock anyway.
                 // The error was that we borrowed `state.config` (immutable via resolve_colors) 
@@ -607,13 +1664,7 @@ pub fn settings_tab_visual(
                 ui.horizontal(|ui|{
                     if ui.add(egui::Slider::new(&mut alpha, 0.0..=1.0).show_value(true)).changed() {
                         // Override: Keep active RGB, but enforce new Alpha
-                        let new_bg = crate::shared_state::Color32 {
-                            r: current_bg.r,
-                            g: current_bg.g,
-                            b: current_bg.b,
-                            a: (alpha * 255.0) as u8
-                        };
-                        state.config.profile.background = Some(new_bg);
+                        state.config.profile.set_background_alpha(&state.user_color_presets, alpha);
                     }
                     
                     // Show Reset button if override is active
@@ -625,28 +1676,97 @@ pub fn settings_tab_visual(
                 });
                 ui.end_row();
 
+                row_label(ui, label::BACKGROUND_IMAGE);
+                let image = &mut state.config.profile.background_image;
+                ui.horizontal(|ui| {
+                    // Loaded on Enter / focus loss, not on every keystroke
+                    let edit_id = ui.id().with("background_image_path");
+                    let mut path: String = ui.data_mut(|d| d.get_temp(edit_id)).unwrap_or_else(|| image.path.clone());
+                    let response = ui.add(egui::TextEdit::singleline(&mut path).hint_text("PNG, JPEG or GIF file").desired_width(180.0))
+                        .on_hover_text("Path of a picture to show behind the bars (press Enter to load). Animated GIFs loop.");
+                    if response.lost_focus() {
+                        image.path = path.trim().to_string();
+                    }
+                    if response.has_focus() {
+                        ui.data_mut(|d| d.insert_temp(edit_id, path));
+                    } else {
+                        ui.data_mut(|d| d.remove::<String>(edit_id));
+                    }
+                    if !image.path.is_empty() && ui.small_button("🗙").on_hover_text("No background image").clicked() {
+                        image.path.clear();
+                    }
+                });
+                ui.end_row();
+
+                row_label(ui, label::ALBUM_ART_BACKGROUND);
+                ui.checkbox(&mut image.album_art, "")
+                    .on_hover_text("Show the playing track's cover, blurred and dimmed, behind the bars.\nThe image above comes back for tracks without art.");
+                ui.end_row();
+
+                if !image.path.is_empty() || image.album_art {
+                    row_label(ui, label::IMAGE_FIT);
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_salt("background_image_fit")
+                            .selected_text(image.fit.label())
+                            .show_ui(ui, |ui| {
+                                for fit in crate::shared_state::ImageFit::ALL {
+                                    ui.selectable_value(&mut image.fit, fit, fit.label());
+                                }
+                            });
+                        ui.add(egui::Slider::new(&mut image.opacity, 0.0..=1.0).text("opacity"));
+                    });
+                    ui.end_row();
+                }
+
                 if state.config.profile.visual_mode == VisualMode::SegmentedBars {
-                    ui.label("Segment Height");
+                    row_label(ui, label::SEGMENT_HEIGHT);
                     ui.add(egui::Slider::new(&mut state.config.profile.segment_height_px, 1.0..=20.0).suffix(" px"));
                     ui.end_row();
 
-                    ui.label("Segment Gap");
+                    row_label(ui, label::SEGMENT_GAP);
                     ui.add(egui::Slider::new(&mut state.config.profile.segment_gap_px, 0.0..=10.0).suffix(" px"));
                     ui.end_row();
                 }
 
+                if matches!(state.config.profile.visual_mode, VisualMode::SolidBars | VisualMode::SegmentedBars) {
+                    let bar_style = &mut state.config.profile.bar_style;
+                    row_label(ui, label::BAR_STYLE);
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_salt("bar_fill")
+                            .selected_text(bar_style.fill.label())
+                            .show_ui(ui, |ui| {
+                                for fill in BarFill::ALL {
+                                    ui.selectable_value(&mut bar_style.fill, fill, fill.label());
+                                }
+                            });
+                        ui.checkbox(&mut bar_style.rounded_caps, "Rounded Caps");
+                        ui.checkbox(&mut bar_style.shadow, "Shadow");
+                    });
+                    ui.end_row();
+                }
+
                 if state.config.profile.visual_mode != VisualMode::Oscilloscope {
-                    ui.label("Peak Indicators");
+                    row_label(ui, label::PEAK_INDICATORS);
                     ui.horizontal(|ui| {
                         ui.checkbox(&mut state.config.profile.show_peaks, "Show");
                         if state.config.profile.show_peaks && state.config.profile.visual_mode == VisualMode::SegmentedBars {
                             ui.checkbox(&mut state.config.profile.fill_peaks, "Fill to Peak");
                         }
+                        if state.config.profile.show_peaks && matches!(state.config.profile.visual_mode, VisualMode::SolidBars | VisualMode::SegmentedBars) {
+                            let style = &mut state.config.profile.peak_style;
+                            egui::ComboBox::from_id_salt("peak_style")
+                                .selected_text(style.label())
+                                .show_ui(ui, |ui| {
+                                    for option in PeakStyle::ALL {
+                                        ui.selectable_value(style, option, option.label());
+                                    }
+                                });
+                        }
                     });
                     ui.end_row();
                 }
 
-                ui.label("Font Style");
+                row_label(ui, label::FONT_STYLE);
                 
                 // Match the internal enum to the user-friendly display text
                 let font_display_text = match state.config.profile.overlay_font {
@@ -672,7 +1792,7 @@ pub fn settings_tab_visual(
             // Hardcode to Aggregation Mode to Peak to overwrite anyt old saved profiles silently
             state.config.profile.aggregation_mode = crate::shared_state::AggregationMode::Peak;
             
-            ui.label("Baseline Orientation:");
+            row_label(ui, label::BASELINE_ORIENTATION);
             
             // Clean D-Pad style toggle buttons
             let current_colors = state.config.resolve_colors(&state.user_color_presets);
@@ -727,7 +1847,7 @@ pub fn settings_tab_audio(ui: &mut egui::Ui, state: &mut SharedState) {
             .num_columns(2)
             .spacing(grid_spacing)
             .show(ui, |ui| {
-                ui.label("Device");
+                row_label(ui, label::DEVICE);
                 
                 ui.horizontal(|ui| {
                     let (current_sel_id, devices) = {
@@ -745,6 +1865,7 @@ pub fn settings_tab_audio(ui: &mut egui::Ui, state: &mut SharedState) {
                     // allocation in the GUI render loop (the dropdown redraws
                     // every frame). egui's `ComboBox::selected_text` takes
                     // `impl Into<WidgetText>`, which accepts `&str` directly.
+                    let saved_name = state.config.selected_device_name.clone();
                     let current_label: &str = if current_sel_id == "Default" {
                         "Default System Device"
                     } else {
@@ -752,6 +1873,7 @@ pub fn settings_tab_audio(ui: &mut egui::Ui, state: &mut SharedState) {
                             .iter()
                             .find(|d| d.id == current_sel_id)
                             .map(|d| d.name.as_str())
+                            .or((!saved_name.is_empty()).then_some(saved_name.as_str()))
                             .unwrap_or(current_sel_id.as_str())
                     };
 
@@ -763,27 +1885,68 @@ pub fn settings_tab_audio(ui: &mut egui::Ui, state: &mut SharedState) {
                             // Default Option
                             if ui.selectable_label(current_sel_id == "Default", "Default System Device").clicked() {
                                 tracing::info!("[GUI] User selected device: Default");
-                                state.config.selected_device = "Default".to_string();
-                                state.device_changed = true;
+                                state.select_device("Default", "");
                             }
 
                             ui.separator();
 
                             // Enumerated Hardware Devices: display `name`,
                             // store `id` in selected_device.
-                            for dev in devices {
+                            // Inputs (WASAPI, then ASIO) come last from the
+                            // enumerator and get their own sections; ASIO shows
+                            // the channel count.
+                            let (mut input_header_shown, mut asio_header_shown) = (false, false);
+                            for dev in &devices {
+                                if crate::audio_device::is_input_id(&dev.id) && !input_header_shown {
+                                    ui.separator();
+                                    ui.label(egui::RichText::new("Inputs").weak());
+                                    input_header_shown = true;
+                                }
+                                let is_asio = crate::audio_device::is_asio_id(&dev.id);
+                                if is_asio && !asio_header_shown {
+                                    ui.separator();
+                                    ui.label(egui::RichText::new("ASIO").weak());
+                                    asio_header_shown = true;
+                                }
+                                let mut label = if is_asio {
+                                    format!("{} ({} ch)", dev.name, dev.channels)
+                                } else {
+                                    dev.name.clone()
+                                };
+                                // Still listed, but picking it is refused with the reason
+                                let problem = dev.validate().err();
+                                if problem.is_some() {
+                                    label = format!("⚠ {}", label);
+                                }
                                 let is_selected = current_sel_id == dev.id;
-                                if ui.selectable_label(is_selected, &dev.name).clicked() {
+                                let response = ui.selectable_label(is_selected, label);
+                                let response = match &problem {
+                                    Some(err) => response.on_hover_text(format!("Can't capture from this device: {}", err)),
+                                    None => response,
+                                };
+                                if response.clicked() {
                                     tracing::info!(
                                         "[GUI] User selected device: '{}' (id: {})",
                                         dev.name, dev.id
                                     );
-                                    state.config.selected_device = dev.id.clone();
-                                    state.device_changed = true;
+                                    state.select_device(&dev.id, &dev.name);
                                 }
                             }
                         });
 
+                    // Details Popover
+                    let current = devices.iter().find(|d| d.id == current_sel_id)
+                        .or_else(|| devices.iter().find(|d| d.is_default));
+                    ui.add_enabled_ui(current.is_some(), |ui| {
+                        ui.menu_button("ℹ", |ui| {
+                            if let Some(dev) = current {
+                                ui_device_details(ui, dev);
+                            }
+                        })
+                        .response
+                        .on_hover_text("Device Details");
+                    });
+
                     // Refresh Button
                     if ui.button("🔄").on_hover_text("Refresh Device List").clicked() {
                         tracing::info!("[GUI] User requested device list refresh");
@@ -792,9 +1955,67 @@ pub fn settings_tab_audio(ui: &mut egui::Ui, state: &mut SharedState) {
                 });
                 ui.end_row();
 
-                ui.label("FFT Window Size");
+                row_label(ui, label::PER_DEVICE_LEVELS);
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut state.config.per_device_levels, "Offset this device by")
+                        .on_hover_text("Each device gets its own gain and noise floor offset on top of the\nprofile's (e.g. a quiet mic vs. loud desktop audio).");
+                    if state.config.per_device_levels {
+                        let mut levels = state.config.device_offsets();
+                        let gain = ui.add(egui::DragValue::new(&mut levels.gain_db).range(-40.0..=40.0).speed(0.1).prefix("gain ").suffix(" dB"));
+                        let floor = ui.add(egui::DragValue::new(&mut levels.noise_floor_offset_db).range(-40.0..=40.0).speed(0.1).prefix("floor ").suffix(" dB"));
+                        if gain.changed() || floor.changed() {
+                            state.config.device_levels.insert(state.config.selected_device.clone(), levels);
+                        }
+                    }
+                });
+                ui.end_row();
+
+                row_label(ui, label::FFT_WINDOW_SIZE);
                 ui.label(egui::RichText::new(format!("{} samples (fixed)", crate::fft_config::FIXED_FFT_SIZE)).weak());
                 ui.end_row();
+
+                row_label(ui, label::SAMPLE_RATE);
+                ui.checkbox(
+                    state.config.resample_enabled_mut(),
+                    format!("Resample to {} kHz", crate::resampler::INTERNAL_SAMPLE_RATE / 1000),
+                )
+                .on_hover_text("Convert 44.1 / 88.2 / 192 kHz devices to one fixed rate before analysis.\nKeeps frequency resolution identical on every device and avoids\nrebuilding the FFT when the device rate changes.");
+                ui.end_row();
+
+                row_label(ui, label::CAPTURE_BUFFER);
+                let rate = state.performance.fft_info.sample_rate.max(1) as f32;
+                let buffer_label = |frames: Option<u32>| match frames {
+                    Some(frames) => format!("{} frames ({:.1} ms)", frames, frames as f32 * 1000.0 / rate),
+                    None => "Driver Default".to_string(),
+                };
+                let before = state.config.capture_buffer_frames;
+                egui::ComboBox::from_id_salt("capture_buffer_combo")
+                    .selected_text(buffer_label(before))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut state.config.capture_buffer_frames, None, buffer_label(None));
+                        for frames in crate::audio_capture::CAPTURE_BUFFER_SIZES {
+                            ui.selectable_value(&mut state.config.capture_buffer_frames, Some(frames), buffer_label(Some(frames)));
+                        }
+                    })
+                    .response
+                    .on_hover_text("Smaller buffers make the bars react sooner but wake the CPU more often.\nIf the driver refuses a size, the default is used (with a warning in the log).\nThe resulting latency is measured in Settings → Stats.");
+                if state.config.capture_buffer_frames != before {
+                    // Reopen the stream on the same device
+                    state.device_changed = true;
+                }
+                ui.end_row();
+
+                row_label(ui, label::EXCLUSIVE_MODE);
+                if ui.add_enabled(
+                    cfg!(target_os = "windows"),
+                    egui::Checkbox::new(&mut state.config.exclusive_capture, "Exclusive Input"),
+                )
+                .on_hover_text("Open input devices (microphones, line-ins) in WASAPI exclusive mode:\nno mixing or resampling by Windows and the smallest buffer the driver allows.\nOther apps can't use the input meanwhile. Loopback is always shared.\nIf the device refuses, shared mode is used and a notice shows up.\nWindows only.")
+                .changed()
+                {
+                    state.device_changed = true;
+                }
+                ui.end_row();
             });
     });
 
@@ -810,7 +2031,7 @@ pub fn settings_tab_audio(ui: &mut egui::Ui, state: &mut SharedState) {
             .spacing(grid_spacing)
             .striped(true)
             .show(ui, |ui| {
-                ui.label("Sensitivity");
+                row_label(ui, label::SENSITIVITY);
                 ui.add(egui::Slider::new(&mut state.config.profile.sensitivity, 0.01..=100.0)
                     .logarithmic(true)
                     .custom_formatter(|v, _| format!("{:+.1} dB", 20.0 * v.log10()))
@@ -822,8 +2043,86 @@ pub fn settings_tab_audio(ui: &mut egui::Ui, state: &mut SharedState) {
                 );
                 ui.end_row();
 
-                ui.label("Noise Floor");
-                ui.add(egui::Slider::new(&mut state.config.noise_floor_db, -120.0..=-20.0).suffix(" dB"));
+                row_label(ui, label::NOISE_FLOOR);
+                ui.add(egui::Slider::new(state.config.noise_floor_mut(), -120.0..=-20.0).suffix(" dB"));
+                ui.end_row();
+
+                row_label(ui, label::SILENCE_THRESHOLD);
+                ui.add(egui::Slider::new(state.config.silence_threshold_mut(), SILENCE_DB..=-40.0).suffix(" dBFS"))
+                    .on_hover_text("Input quieter than this counts as silence. Quiet audio is still drawn,\nthis only decides when the hold below starts.");
+                ui.end_row();
+
+                row_label(ui, label::HOLD_BEFORE_CLEAR);
+                ui.add(egui::Slider::new(state.config.silence_hold_mut(), 0.0..=5000.0)
+                    .logarithmic(true)
+                    .suffix(" ms"))
+                    .on_hover_text("How long the bars stay up after the audio stops arriving\nbefore they start falling.");
+                ui.end_row();
+
+                row_label(ui, label::GAIN_ADVISOR);
+                ui.checkbox(&mut state.config.gain_advisor_enabled, "Show Level Hints")
+                    .on_hover_text("Suggests fixes when the input clips, is very quiet, or is heavily limited.");
+                ui.end_row();
+
+                let clip = &mut state.config.clip;
+                row_label(ui, label::CLIP_INDICATOR);
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut clip.enabled, "")
+                        .on_hover_text("Flash a warning the moment any input sample reaches the clip level.");
+                    ui.add_enabled_ui(clip.enabled, |ui| {
+                        egui::ComboBox::from_id_salt("clip_style")
+                            .selected_text(clip.style.label())
+                            .show_ui(ui, |ui| {
+                                for style in crate::clip_indicator::ClipStyle::ALL {
+                                    ui.selectable_value(&mut clip.style, style, style.label());
+                                }
+                            });
+                    });
+                });
+                ui.end_row();
+
+                row_label(ui, label::CLIP_LEVEL);
+                ui.add_enabled_ui(clip.enabled, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut clip.threshold_db).range(-12.0..=0.0).speed(0.05).suffix(" dBFS"));
+                        ui.label("held for");
+                        ui.add(egui::DragValue::new(&mut clip.hold_ms).range(100.0..=10_000.0).speed(10.0).suffix(" ms"));
+                    });
+                });
+                ui.end_row();
+
+                row_label(ui, label::REFERENCE_SNAPSHOT);
+                ui.horizontal(|ui| {
+                    let now = std::time::Instant::now();
+                    if let Some(capture) = &state.snapshot_capture {
+                        ui.add(egui::ProgressBar::new(capture.progress(now)).desired_width(120.0).text("Capturing…"));
+                        if ui.small_button("Cancel").clicked() {
+                            state.snapshot_capture = None;
+                        }
+                        ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+                    } else {
+                        if ui.button("📸 Capture")
+                            .on_hover_text("Average the spectrum for a few seconds and draw it as a dashed line over the live bars.\nHover a bar to read the difference, e.g. before and after an EQ change.")
+                            .clicked()
+                        {
+                            state.snapshot_capture = Some(crate::snapshot::SnapshotCapture::new(state.config.snapshot_seconds, now));
+                        }
+                        ui.add(egui::DragValue::new(&mut state.config.snapshot_seconds).range(0.5..=30.0).speed(0.1).suffix(" s"));
+                        if state.reference_snapshot.is_some() && ui.small_button("Clear").clicked() {
+                            state.reference_snapshot = None;
+                        }
+                    }
+                });
+                ui.end_row();
+
+                row_label(ui, label::ENGINE_SETTINGS);
+                let mut per_profile = state.config.profile.engine.is_some();
+                if ui.checkbox(&mut per_profile, "Save with Visual Profile")
+                    .on_hover_text("Noise floor, resampling and silence detection follow the active visual profile\ninstead of being global, so switching profiles reconfigures the whole engine.\nSensitivity and envelope timing are always part of the profile.")
+                    .changed()
+                {
+                    state.config.profile.engine = per_profile.then(|| state.config.current_engine());
+                }
                 ui.end_row();
             });
     });
@@ -840,11 +2139,11 @@ pub fn settings_tab_audio(ui: &mut egui::Ui, state: &mut SharedState) {
             .spacing(grid_spacing)
             .striped(true)
             .show(ui, |ui| {
-                ui.label("Bar Attack (Rise)");
+                row_label(ui, label::BAR_ATTACK_RISE);
                 ui.add(egui::Slider::new(&mut state.config.profile.attack_time_ms, 1.0..=500.0).suffix(" ms"));
                 ui.end_row();
 
-                ui.label("Bar Release (Fall)");
+                row_label(ui, label::BAR_RELEASE_FALL);
                 ui.add(egui::Slider::new(&mut state.config.profile.release_time_ms, 1.0..=2000.0).suffix(" ms"));
                 ui.end_row();
 
@@ -852,19 +2151,240 @@ pub fn settings_tab_audio(ui: &mut egui::Ui, state: &mut SharedState) {
                 let peaks_enabled = state.config.profile.show_peaks;
 
                 // Use `add_enabled` so the UI elements remain visible but greyed out when peaks are disabled
-                ui.add_enabled(peaks_enabled, egui::Label::new("Peak Hold Time"))
+                ui.add_enabled_ui(peaks_enabled, |ui| row_label(ui, label::PEAK_HOLD_TIME))
+                  .inner
                   .on_disabled_hover_text("Enable 'Show Peaks' in the Visual tab to use this.");
                 ui.add_enabled(peaks_enabled, egui::Slider::new(&mut state.config.profile.peak_hold_time_ms, 0.0..=2000.0).suffix(" ms"));
                 ui.end_row();
 
-                ui.add_enabled(peaks_enabled, egui::Label::new("Peak Fall Speed"))
+                ui.add_enabled_ui(peaks_enabled, |ui| row_label(ui, label::PEAK_FALL_SPEED))
+                  .inner
                   .on_disabled_hover_text("Enable 'Show Peaks' in the Visual tab to use this.");
-                ui.add_enabled(peaks_enabled, egui::Slider::new(&mut state.config.profile.peak_release_time_ms, 10.0..=2000.0).suffix(" ms"));
+                ui.horizontal(|ui| {
+                    ui.add_enabled(peaks_enabled, egui::Slider::new(&mut state.config.profile.peak_release_time_ms, 10.0..=2000.0).suffix(" ms"));
+                    let falling_dot = state.config.profile.peak_style == PeakStyle::FallingDot;
+                    ui.add_enabled(peaks_enabled && !falling_dot, egui::Checkbox::new(&mut state.config.profile.peak_gravity, "Gravity"))
+                        .on_hover_text("Peaks drop slowly, then faster and faster, like a falling object.\nAlways on for the Falling Dot peak style.");
+                });
+                ui.end_row();
+            });
+    });
+
+    ui.add_space(10.0);
+
+    // === 4. MIDI Out ===
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("MIDI Out").strong());
+        ui.separator();
+        ui_midi_out(ui, state);
+    });
+}
+
+pub fn settings_tab_tools(ui: &mut egui::Ui, state: &mut SharedState) {
+    let grid_spacing = egui::vec2(40.0, 12.0);
+
+    ui.add_space(10.0);
+
+    // === 1. Signal Generator ===
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("Signal Generator").strong());
+        ui.separator();
+        ui_signal_generator(ui, state, grid_spacing);
+    });
+
+    ui.add_space(10.0);
+
+    // === 2. Measurement ===
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("Pink Noise Measurement").strong());
+        ui.separator();
+        ui_measurement(ui, state, grid_spacing);
+    });
+
+    ui.add_space(10.0);
+
+    // === 3. Tuner ===
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("Tuner").strong());
+        ui.separator();
+        egui::Grid::new("tuner_grid")
+            .num_columns(2)
+            .spacing(grid_spacing)
+            .show(ui, |ui| {
+                row_label(ui, label::TUNER_OVERLAY);
+                ui.checkbox(&mut state.config.tuner.enabled, "Show Note and Cents")
+                    .on_hover_text("Detects the pitch of a single instrument or voice on the input.\nWorks best with an instrument input or a close mic, not a full mix.");
+                ui.end_row();
+
+                row_label(ui, label::CONCERT_PITCH);
+                ui.add(egui::DragValue::new(&mut state.config.tuner.a4_hz).range(415.0..=466.0).speed(0.1).prefix("A4 = ").suffix(" Hz"));
                 ui.end_row();
             });
     });
 }
 
+/// Output device picker for the generator and the measurement noise. The
+/// list is read when the combo is first opened, the walk isn't free.
+fn output_device_combo(ui: &mut egui::Ui, id_salt: &str, device: &mut String) {
+    let outputs_id = egui::Id::new("audio_outputs");
+    let mut outputs: Vec<(String, String)> = ui.data(|d| d.get_temp(outputs_id)).unwrap_or_default();
+    let selected_label = if device.is_empty() {
+        "Default Output".to_string()
+    } else {
+        outputs.iter().find(|(id, _)| id == device).map_or_else(|| device.clone(), |(_, name)| name.clone())
+    };
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(selected_label)
+        .width(220.0)
+        .show_ui(ui, |ui| {
+            if outputs.is_empty() {
+                outputs = crate::audio_output::list_devices();
+                ui.data_mut(|d| d.insert_temp(outputs_id, outputs.clone()));
+            }
+            ui.selectable_value(device, String::new(), "Default Output");
+            for (id, name) in &outputs {
+                ui.selectable_value(device, id.clone(), name);
+            }
+        });
+}
+
+/// Waveform, frequency, level and output of the signal generator
+fn ui_signal_generator(ui: &mut egui::Ui, state: &mut SharedState, grid_spacing: egui::Vec2) {
+    use crate::signal_gen::{Waveform, MAX_HZ, MIN_HZ};
+
+    egui::Grid::new("signal_gen_grid")
+        .num_columns(2)
+        .spacing(grid_spacing)
+        .show(ui, |ui| {
+            row_label(ui, label::GENERATOR);
+            let running = state.signal_gen_running;
+            let label = if running { "⏹ Stop" } else { "▶ Play" };
+            if ui.button(label).on_hover_text("Play a test tone or noise, e.g. to check speakers, cables or the bars themselves").clicked() {
+                state.signal_gen_running = !running;
+                tracing::info!("[GUI] Signal generator {}", if running { "stopped" } else { "started" });
+            }
+            ui.end_row();
+
+            let settings = &mut state.config.signal_gen;
+            row_label(ui, label::WAVEFORM);
+            ui.horizontal(|ui| {
+                for waveform in Waveform::ALL {
+                    ui.selectable_value(&mut settings.waveform, waveform, waveform.label());
+                }
+            });
+            ui.end_row();
+
+            match settings.waveform {
+                Waveform::Sine => {
+                    row_label(ui, label::TONE_FREQUENCY);
+                    ui.add(egui::Slider::new(&mut settings.frequency_hz, MIN_HZ..=MAX_HZ).logarithmic(true).suffix(" Hz"));
+                    ui.end_row();
+                }
+                Waveform::Sweep => {
+                    row_label(ui, label::SWEEP_RANGE);
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut settings.sweep_from_hz).range(MIN_HZ..=MAX_HZ).speed(1.0).suffix(" Hz"));
+                        ui.label("to");
+                        ui.add(egui::DragValue::new(&mut settings.sweep_to_hz).range(MIN_HZ..=MAX_HZ).speed(10.0).suffix(" Hz"));
+                        ui.label("in");
+                        ui.add(egui::DragValue::new(&mut settings.sweep_seconds).range(1.0..=120.0).speed(0.1).suffix(" s"));
+                    });
+                    ui.end_row();
+                }
+                Waveform::WhiteNoise | Waveform::PinkNoise => {}
+            }
+
+            row_label(ui, label::GENERATOR_LEVEL);
+            ui.add(egui::Slider::new(&mut settings.level_db, -60.0..=0.0).suffix(" dBFS"))
+                .on_hover_text("Start low and turn it up; a full-scale tone is loud");
+            ui.end_row();
+
+            row_label(ui, label::GENERATOR_OUTPUT);
+            output_device_combo(ui, "signal_gen_output_combo", &mut settings.output_device);
+            ui.end_row();
+        });
+}
+
+/// Start / stop for the pink-noise measurement, the noise output and the
+/// response curve
+fn ui_measurement(ui: &mut egui::Ui, state: &mut SharedState, grid_spacing: egui::Vec2) {
+    use crate::measurement::Smoothing;
+
+    egui::Grid::new("measurement_grid")
+        .num_columns(2)
+        .spacing(grid_spacing)
+        .show(ui, |ui| {
+            row_label(ui, label::MEASUREMENT);
+            ui.horizontal(|ui| {
+                let running = state.measurement.running;
+                let label = if running { "⏹ Stop" } else { "▶ Measure" };
+                if ui.button(label)
+                    .on_hover_text("Average the spectrum over time and draw it as a smoothed response curve.\nWith a microphone as the input and pink noise playing, this shows roughly what\nyour speakers and room do to a flat signal.")
+                    .clicked()
+                {
+                    if running {
+                        state.measurement.stop();
+                    } else {
+                        state.measurement.start(std::time::Instant::now());
+                    }
+                    tracing::info!("[GUI] Measurement {}", if running { "stopped" } else { "started" });
+                }
+                if let Some(started) = state.measurement.started {
+                    if running {
+                        ui.label(format!("{:.0} s, {} frames", started.elapsed().as_secs_f32(), state.measurement.frames()));
+                        ui.ctx().request_repaint_after(std::time::Duration::from_millis(250));
+                    } else {
+                        ui.label(format!("{} frames", state.measurement.frames()));
+                    }
+                }
+            });
+            ui.end_row();
+
+            row_label(ui, label::TEST_SIGNAL);
+            ui.checkbox(&mut state.config.measurement.play_noise, "Play Pink Noise While Measuring")
+                .on_hover_text("BeSpec plays the noise itself. Turn off to measure a noise source of your own.");
+            ui.end_row();
+
+            row_label(ui, label::SIGNAL_OUTPUT);
+            let settings = &mut state.config.measurement;
+            ui.add_enabled_ui(settings.play_noise, |ui| {
+                output_device_combo(ui, "noise_output_combo", &mut settings.output_device);
+            });
+            ui.end_row();
+
+            row_label(ui, label::SIGNAL_LEVEL);
+            ui.add_enabled(settings.play_noise, egui::Slider::new(&mut settings.level_db, -60.0..=0.0).suffix(" dBFS"))
+                .on_hover_text("Start low and turn it up; pink noise at full scale is loud");
+            ui.end_row();
+
+            row_label(ui, label::CURVE_SMOOTHING);
+            ui.horizontal(|ui| {
+                for smoothing in Smoothing::ALL {
+                    ui.selectable_value(&mut settings.smoothing, smoothing, smoothing.label());
+                }
+            });
+            ui.end_row();
+
+            row_label(ui, label::RESPONSE_CURVE);
+            ui.horizontal(|ui| {
+                let has_curve = !state.measurement.curve.is_empty();
+                if ui.add_enabled(has_curve, egui::Button::new("Keep as Reference"))
+                    .on_hover_text("Turn the curve into the dashed reference snapshot, to compare against the next measurement")
+                    .clicked()
+                {
+                    state.reference_snapshot = Some(crate::snapshot::ReferenceSnapshot {
+                        bars: state.measurement.curve.clone(),
+                        frames: state.measurement.frames(),
+                    });
+                }
+                if ui.add_enabled(has_curve && !state.measurement.running, egui::Button::new("Clear")).clicked() {
+                    state.measurement.clear();
+                }
+            });
+            ui.end_row();
+        });
+}
+
 pub fn settings_tab_colors(
     ui: &mut egui::Ui, 
     state: &mut SharedState,
@@ -882,7 +2402,7 @@ pub fn settings_tab_colors(
         ui.add_space(4.0);
         
         ui.horizontal(|ui| {
-            ui.label("Preset:");
+            row_label(ui, label::PRESET);
             let combo_text = match &state.config.profile.color_link {
                 ColorRef::Preset(name) => name.clone(),
                 ColorRef::Custom(_) => "Custom (Unsaved)".to_string(),
@@ -896,12 +2416,18 @@ pub fn settings_tab_colors(
                         let _ = ui.selectable_label(false, egui::RichText::new("--- User Presets ---").strong());
                         for p in &user_presets {
                             ui.horizontal(|ui| {
+                                let request = || state.thumbnail_requests.push(preset_thumbnail::Request::color(p));
+                                if let Some(thumb) = thumbnails::texture(ui.ctx(), PresetKind::Color, &p.name, request) {
+                                    thumbnails::show(ui, &thumb);
+                                }
                                 if ui.selectable_label(false, &p.name).clicked() {
                                     state.config.profile.color_link = ColorRef::Preset(p.name.clone());
                                     state.config.profile.background = None;
                                 }
                                 if ui.small_button("🗑").clicked() {
+                                    backup_before(state, "delete color preset");
                                     let _ = crate::shared_state::AppConfig::delete_user_color_preset(&p.name);
+                                    thumbnails::forget(ui.ctx(), PresetKind::Color, &p.name);
                                     state.user_color_presets.retain(|x| x.name != p.name);
                                 }
                             });
@@ -926,6 +2452,8 @@ pub fn settings_tab_colors(
             });
         });
 
+        ui_theme_file_buttons(ui, state, save_target, new_preset_name);
+
         // -- Save Popup --
         if *save_target == SaveTarget::Color {
             ui.add_space(4.0);
@@ -935,6 +2463,7 @@ pub fn settings_tab_colors(
                 if let Err(e) = crate::shared_state::AppConfig::save_user_color_preset(&new_profile) {
                     tracing::error!("Failed to save preset: {}", e);
                 } else {
+                    state.thumbnail_requests.push(preset_thumbnail::Request::color(&new_profile));
                     if let Some(existing) = state.user_color_presets.iter_mut().find(|p| p.name == name) {
                         *existing = new_profile.clone();
                     } else {
@@ -945,6 +2474,11 @@ pub fn settings_tab_colors(
                 }
             }, save_target);
         }
+
+        if *save_target == SaveTarget::Import {
+            ui.add_space(4.0);
+            ui_import_popup(ui, state, save_target, new_preset_name);
+        }
     });
 
     ui.add_space(10.0);
@@ -964,43 +2498,54 @@ pub fn settings_tab_colors(
             egui::Grid::new("color_grid").num_columns(2).spacing(grid_spacing).show(ui, |ui| {
                 // 2. The Logic Fix: Only write back Egui -> State IF CHANGED
                 
-                ui.label("Low"); 
+                row_label(ui, label::LOW);
                 if ui.color_edit_button_srgba(&mut egui_low).changed() {
                     current_colors.low = from_egui_color(egui_low);
+                    // Low and High are the gradient's ends
+                    if let Some(first) = current_colors.gradient.first_mut() {
+                        first.color = current_colors.low;
+                    }
                 }
                 ui.end_row();
 
-                ui.label("High"); 
+                row_label(ui, label::HIGH);
                 if ui.color_edit_button_srgba(&mut egui_high).changed() {
                     current_colors.high = from_egui_color(egui_high);
+                    if let Some(last) = current_colors.gradient.last_mut() {
+                        last.color = current_colors.high;
+                    }
                 }
                 ui.end_row();
 
-                ui.label("Peak"); 
+                row_label(ui, label::GRADIENT_STOPS);
+                ui_gradient_editor(ui, &mut current_colors);
+                ui.end_row();
+
+                row_label(ui, label::PEAK);
                 if ui.color_edit_button_srgba(&mut egui_peak).changed() {
                     current_colors.peak = from_egui_color(egui_peak);
                 }
                 ui.end_row();
 
-                ui.label("Background"); 
+                row_label(ui, label::BACKGROUND);
                 if ui.color_edit_button_srgba(&mut egui_bg).changed() {
                     current_colors.background = from_egui_color(egui_bg);
                 }
                 ui.end_row();
 
-                ui.label("Overlay Text"); 
+                row_label(ui, label::OVERLAY_TEXT);
                 if ui.color_edit_button_srgba(&mut egui_text).changed() {
                     current_colors.text = from_egui_color(egui_text);
                 }
                 ui.end_row();
 
-                ui.label("Inspector Box"); 
+                row_label(ui, label::INSPECTOR_BOX);
                 if ui.color_edit_button_srgba(&mut egui_insp_bg).changed() {
                     current_colors.inspector_bg = from_egui_color(egui_insp_bg);
                 }
                 ui.end_row();
 
-                ui.label("Inspector Text/Line"); 
+                row_label(ui, label::INSPECTOR_TEXT_LINE);
                 if ui.color_edit_button_srgba(&mut egui_insp_fg).changed() {
                     current_colors.inspector_fg = from_egui_color(egui_insp_fg);
                 }
@@ -1015,6 +2560,187 @@ pub fn settings_tab_colors(
         state.config.profile.color_link = ColorRef::Custom(current_colors);
         state.config.profile.background = None; 
         }
+
+    ui.add_space(10.0);
+    ui_theme_schedule(ui, state);
+}
+
+/// MilkDrop preset picker: a filterable list of the presets folder plus
+/// previous / next buttons. The folder is only scanned when it's needed,
+/// since preset packs can hold thousands of files.
+fn ui_milkdrop_presets(ui: &mut egui::Ui, preset: &mut String) {
+    ui.horizontal(|ui| {
+        let selected = preset.rsplit('/').next().filter(|name| !name.is_empty()).unwrap_or("(none)").to_string();
+        egui::ComboBox::from_id_salt("milkdrop_preset")
+            .selected_text(selected)
+            .width(220.0)
+            .height(400.0)
+            .show_ui(ui, |ui| {
+                let filter_id = ui.id().with("milkdrop_filter");
+                let mut filter: String = ui.data_mut(|d| d.get_temp(filter_id).unwrap_or_default());
+                ui.add(egui::TextEdit::singleline(&mut filter).hint_text("🔍 Filter"));
+                let needle = filter.to_lowercase();
+                for name in milkdrop::list_presets().into_iter().filter(|p| p.to_lowercase().contains(&needle)) {
+                    let label = name.clone();
+                    ui.selectable_value(preset, name, label);
+                }
+                ui.data_mut(|d| d.insert_temp(filter_id, filter));
+            });
+        for (icon, step, hint) in [("⏮", -1, "Previous preset"), ("⏭", 1, "Next preset")] {
+            if ui.small_button(icon).on_hover_text(hint).clicked() {
+                if let Some(next) = milkdrop::step_preset(&milkdrop::list_presets(), preset, step) {
+                    *preset = next;
+                }
+            }
+        }
+        if ui.small_button("📂").on_hover_text("Open the MilkDrop presets folder").clicked() {
+            let dir = milkdrop::presets_dir();
+            let _ = std::fs::create_dir_all(&dir);
+            let _ = open::that(dir);
+        }
+    })
+    .response
+    .on_hover_text("A .milk preset from the milkdrop folder (subfolders included), rendered by projectM.");
+}
+
+/// Gradient strip with a handle per stop: drag a handle to move the stop,
+/// click it to edit its color. The ends stay in sync with Low and High.
+fn ui_gradient_editor(ui: &mut egui::Ui, colors: &mut ColorProfile) {
+    let mut stops: Vec<GradientStop> = colors.gradient_stops().collect();
+    let selected_id = ui.id().with("gradient_selected_stop");
+    let mut selected = ui.data(|d| d.get_temp::<usize>(selected_id)).unwrap_or(0).min(stops.len() - 1);
+    let mut changed = false;
+
+    ui.vertical(|ui| {
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(240.0, 34.0), egui::Sense::hover());
+        let strip = egui::Rect::from_min_max(rect.min + egui::vec2(6.0, 0.0), egui::pos2(rect.right() - 6.0, rect.top() + 18.0));
+        let x_of = |p: f32| strip.left() + p * strip.width();
+
+        let gradient: Vec<(f32, Color32)> = stops.iter().map(|s| (s.position, to_egui_color(s.color))).collect();
+        ui.painter().add(egui::Shape::mesh(crate::gui::gradient::gradient_bar_mesh(&gradient, 1.0, |t| {
+            [egui::pos2(x_of(t), strip.bottom()), egui::pos2(x_of(t), strip.top())]
+        })));
+        ui.painter().rect_stroke(strip, 0.0, ui.visuals().widgets.noninteractive.bg_stroke);
+
+        for i in 0..stops.len() {
+            let handle = egui::Rect::from_center_size(egui::pos2(x_of(stops[i].position), strip.bottom() + 8.0), egui::vec2(10.0, 12.0));
+            let response = ui.interact(handle, selected_id.with(i), egui::Sense::click_and_drag())
+                .on_hover_text(format!("{:.0}%", stops[i].position * 100.0));
+            if response.clicked() || response.drag_started() {
+                selected = i;
+            }
+            if response.dragged() {
+                if let Some(pointer) = response.interact_pointer_pos() {
+                    // Stops keep their order: a handle stops at its neighbours
+                    let min = if i > 0 { stops[i - 1].position } else { 0.0 };
+                    let max = stops.get(i + 1).map_or(1.0, |s| s.position);
+                    stops[i].position = ((pointer.x - strip.left()) / strip.width()).clamp(min, max);
+                    changed = true;
+                }
+            }
+            let stroke_color = if i == selected { ui.visuals().strong_text_color() } else { ui.visuals().weak_text_color() };
+            ui.painter().rect(handle, 2.0, to_egui_color(stops[i].color), egui::Stroke::new(1.5, stroke_color));
+        }
+
+        ui.horizontal(|ui| {
+            let mut color = to_egui_color(stops[selected].color);
+            if ui.color_edit_button_srgba(&mut color).on_hover_text("Color of the selected stop").changed() {
+                stops[selected].color = from_egui_color(color);
+                changed = true;
+            }
+            if ui.small_button("➕").on_hover_text("Add a stop in the widest gap").clicked() {
+                let gap = stops.windows(2).enumerate()
+                    .max_by(|a, b| (a.1[1].position - a.1[0].position).total_cmp(&(b.1[1].position - b.1[0].position)))
+                    .map_or(0, |(i, _)| i);
+                let position = (stops[gap].position + stops[gap + 1].position) / 2.0;
+                let sampled = crate::gui::gradient::sample_gradient(&gradient, position);
+                stops.insert(gap + 1, GradientStop { position, color: from_egui_color(sampled) });
+                selected = gap + 1;
+                changed = true;
+            }
+            if ui.add_enabled(stops.len() > 2, egui::Button::new("🗑").small()).on_hover_text("Remove the selected stop").clicked() {
+                stops.remove(selected);
+                selected = selected.min(stops.len() - 1);
+                changed = true;
+            }
+            if !colors.gradient.is_empty() && ui.small_button("Reset").on_hover_text("Back to a plain Low to High gradient").clicked() {
+                colors.gradient.clear();
+                selected = 0;
+            }
+        });
+    });
+
+    ui.data_mut(|d| d.insert_temp(selected_id, selected));
+    if changed {
+        colors.low = stops[0].color;
+        colors.high = stops[stops.len() - 1].color;
+        colors.gradient = stops;
+    }
+}
+
+/// "Scheduling" block: color presets switched by local time of day
+fn ui_theme_schedule(ui: &mut egui::Ui, state: &mut SharedState) {
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("Scheduling").strong());
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            row_label(ui, label::AUTO_THEME_BY_TIME);
+            ui.checkbox(&mut state.config.theme_schedule.enabled, "")
+                .on_hover_text("Switch color presets at set times of day. Each entry holds until the next one;\npicking a preset by hand sticks until the next switch.");
+        });
+
+        let preset_names = crate::commands::color_preset_names(state);
+        // New entries start out with the preset that's showing now
+        let new_entry_preset = match &state.config.profile.color_link {
+            ColorRef::Preset(name) => name.clone(),
+            ColorRef::Custom(_) => preset_names.first().cloned().unwrap_or_default(),
+        };
+        let schedule = &mut state.config.theme_schedule;
+        let mut remove = None;
+        ui.add_enabled_ui(schedule.enabled, |ui| {
+            egui::Grid::new("theme_schedule_grid").num_columns(3).spacing([12.0, 6.0]).show(ui, |ui| {
+                for (i, entry) in schedule.entries.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        let mut hour = entry.start_minute / 60;
+                        let mut minute = entry.start_minute % 60;
+                        let hour_changed = ui.add(egui::DragValue::new(&mut hour).range(0..=23).custom_formatter(|n, _| format!("{:02}", n as u16))).changed();
+                        ui.label(":");
+                        let minute_changed = ui.add(egui::DragValue::new(&mut minute).range(0..=59).custom_formatter(|n, _| format!("{:02}", n as u16))).changed();
+                        if hour_changed || minute_changed {
+                            entry.start_minute = hour * 60 + minute;
+                        }
+                    });
+                    egui::ComboBox::from_id_salt(("theme_schedule_preset", i))
+                        .selected_text(&entry.preset)
+                        .show_ui(ui, |ui| {
+                            for name in &preset_names {
+                                ui.selectable_value(&mut entry.preset, name.clone(), name);
+                            }
+                        });
+                    if ui.small_button("🗑").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+
+            if ui.button("➕ Add").clicked() {
+                // Next free hour after the last entry, so new rows don't pile up at one time
+                let start_minute = schedule.entries.iter().map(|e| e.start_minute).max().map_or(18 * 60, |m| (m / 60 + 1) % 24 * 60);
+                schedule.entries.push(crate::theme_schedule::ScheduleEntry { start_minute, preset: new_entry_preset.clone() });
+                schedule.entries.sort_by_key(|e| e.start_minute);
+            }
+        });
+        if let Some(i) = remove {
+            state.config.theme_schedule.entries.remove(i);
+        }
+
+        let now = crate::theme_schedule::local_minute_of_day();
+        if let Some(preset) = state.config.theme_schedule.preset_at(now) {
+            ui.label(egui::RichText::new(format!("Now ({}): {}", crate::theme_schedule::format_minute(now), preset)).small().weak());
+        }
+    });
 }
 
 pub fn settings_tab_window(ui: &mut egui::Ui, state: &mut SharedState) {
@@ -1035,7 +2761,7 @@ pub fn settings_tab_window(ui: &mut egui::Ui, state: &mut SharedState) {
                 .min_col_width(150.0)
                 .show(ui, |ui| {
                     // OS Title Bar
-                    ui.label("OS Title Bar");
+                    row_label(ui, label::OS_TITLE_BAR);
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         if ui.checkbox(&mut state.config.window_decorations, "Show Title Bar").changed() {
                             let show = state.config.window_decorations;
@@ -1045,7 +2771,7 @@ pub fn settings_tab_window(ui: &mut egui::Ui, state: &mut SharedState) {
                     ui.end_row();
 
                     // BeOS Mode
-                    ui.label("Custom Theme");
+                    row_label(ui, label::CUSTOM_THEME);
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         if ui.checkbox(&mut state.config.profile.beos_enabled, "Enable BeOS / Haiku Mode").changed() {
                             if state.config.profile.beos_enabled && state.config.beos_tab_offset < 1.0 {
@@ -1079,7 +2805,7 @@ pub fn settings_tab_window(ui: &mut egui::Ui, state: &mut SharedState) {
                 .min_col_width(150.0)
                 .show(ui, |ui| {
                     // Always on Top
-                    ui.label("Main Window");
+                    row_label(ui, label::MAIN_WINDOW);
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         if ui.checkbox(&mut state.config.always_on_top, "Always on Top").changed() {
                             let level = if state.config.always_on_top {
@@ -1092,8 +2818,47 @@ pub fn settings_tab_window(ui: &mut egui::Ui, state: &mut SharedState) {
                     });
                     ui.end_row();
 
+                    // Size
+                    row_label(ui, label::WINDOW_SIZE);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let [width, height] = state.config.active_geometry().0;
+                        egui::ComboBox::from_id_salt("size_preset_combo")
+                            .selected_text(format!("{:.0} × {:.0}", width, height))
+                            .show_ui(ui, |ui| {
+                                for preset in SizePreset::ALL {
+                                    let [w, h] = preset.size();
+                                    if ui.selectable_label(false, format!("{} ({:.0} × {:.0})", preset.label(), w, h)).clicked() {
+                                        ui.ctx().send_viewport_cmd_to(egui::ViewportId::ROOT, egui::ViewportCommand::InnerSize(egui::vec2(w, h)));
+                                        // A locked ratio follows the preset
+                                        if state.config.aspect_lock.is_some() {
+                                            state.config.aspect_lock = Some(w / h);
+                                        }
+                                    }
+                                }
+                            })
+                            .response
+                            .on_hover_text("Resize the main window to a preset");
+                    });
+                    ui.end_row();
+
+                    row_label(ui, label::LOCK_ASPECT_RATIO);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let mut locked = state.config.aspect_lock.is_some();
+                        if let Some(ratio) = state.config.aspect_lock {
+                            ui.label(format!("{:.2} : 1", ratio));
+                        }
+                        if ui.checkbox(&mut locked, "")
+                            .on_hover_text("Keep the current width-to-height ratio when resizing with the grip (bottom-right)")
+                            .changed()
+                        {
+                            let [width, height] = state.config.active_geometry().0;
+                            state.config.aspect_lock = locked.then(|| width / height.max(1.0));
+                        }
+                    });
+                    ui.end_row();
+
                     // Shortcut
-                    ui.label("Minimize Shortcut");
+                    row_label(ui, label::MINIMIZE_SHORTCUT);
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         ui.horizontal(|ui| {
                             ui.label("Ctrl + ");
@@ -1102,20 +2867,183 @@ pub fn settings_tab_window(ui: &mut egui::Ui, state: &mut SharedState) {
                     });
                     ui.end_row();
 
+                    // Mini Mode
+                    row_label(ui, label::MINI_MODE_SHORTCUT);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Ctrl + ");
+                            crate::gui::widgets::key_binder_widget(ui, &mut state.config.mini_mode_key);
+                        });
+                    });
+                    ui.end_row();
+
+                    row_label(ui, label::QUICK_SWITCHER_SHORTCUT);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Ctrl + ");
+                            crate::gui::widgets::key_binder_widget(ui, &mut state.config.quick_switch_key);
+                        });
+                    })
+                    .response
+                    .on_hover_text("Flip through visual profiles and color presets with the arrow keys or scroll wheel");
+                    ui.end_row();
+
+                    // Peek away
+                    row_label(ui, label::PEEK_AWAY_HOTKEY);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.add_enabled_ui(state.config.peek.enabled, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Ctrl + Shift + ");
+                                crate::gui::widgets::key_binder_widget(ui, &mut state.config.peek.key);
+                            });
+                        });
+                        ui.checkbox(&mut state.config.peek.enabled, "")
+                            .on_hover_text("Minimize from anywhere, even while another app has focus,\nand come back by itself. Letters, digits and F1-F12. Not available on Wayland.");
+                    });
+                    ui.end_row();
+
+                    row_label(ui, label::PEEK_AWAY_FOR);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.add_enabled(
+                            state.config.peek.enabled,
+                            egui::Slider::new(&mut state.config.peek.restore_after_sec, 0.0..=120.0)
+                                .step_by(1.0)
+                                .custom_formatter(|v, _| if v <= 0.0 { "Until Hotkey".to_string() } else { format!("{:.0} s", v) }),
+                        )
+                        .on_hover_text("Seconds until the window comes back. At 0 it stays away until the hotkey\n(or the next track, below) brings it back.");
+                    });
+                    ui.end_row();
+
+                    row_label(ui, label::RETURN_ON_NEXT_TRACK);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.add_enabled(state.config.peek.enabled, egui::Checkbox::without_text(&mut state.config.peek.restore_on_track))
+                            .on_hover_text("Bring the window back as soon as the media player moves to another track");
+                    });
+                    ui.end_row();
+
+                    row_label(ui, label::MINI_MODE_BARS);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.add(egui::Slider::new(&mut state.config.mini_num_bars, 8..=128))
+                            .on_hover_text("Bar count while in the compact strip (never more than the profile asks for)");
+                    });
+                    ui.end_row();
+
+                    // Snapping
+                    row_label(ui, label::SNAP_TO_EDGES);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.add_enabled(
+                            state.config.snap_to_edges,
+                            egui::Slider::new(&mut state.config.snap_threshold, 4.0..=64.0).suffix(" px"),
+                        );
+                        ui.checkbox(&mut state.config.snap_to_edges, "")
+                            .on_hover_text("After a drag, pull the window flush against nearby screen edges and corners,\nincluding the edges of neighbouring monitors. Not available on Wayland.");
+                    });
+                    ui.end_row();
+
+                    // Docking
+                    row_label(ui, label::DOCK_TO_SCREEN_EDGE);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        egui::ComboBox::from_id_salt("dock_edge_combo")
+                            .selected_text(state.config.dock_edge.label())
+                            .show_ui(ui, |ui| {
+                                for edge in [DockEdge::Off, DockEdge::Top, DockEdge::Bottom] {
+                                    ui.selectable_value(&mut state.config.dock_edge, edge, edge.label());
+                                }
+                            })
+                            .response
+                            .on_hover_text("Reserve a strip along the screen edge so maximized windows don't cover it.\nNot available on Wayland, where the window can't reserve space.");
+                    });
+                    ui.end_row();
+
+                    if state.config.dock_edge != DockEdge::Off {
+                        row_label(ui, label::DOCK_HEIGHT);
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.add(egui::Slider::new(&mut state.config.dock_thickness, 20.0..=300.0).suffix(" px"));
+                        });
+                        ui.end_row();
+                    }
+
+                    // Wallpaper
+                    row_label(ui, label::LIVE_WALLPAPER);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.checkbox(&mut state.config.wallpaper_mode, "Behind Desktop Icons")
+                            .on_hover_text("Fill the screen behind the desktop icons (Windows and X11).\nThe window can't be clicked while it's a wallpaper: untick this here,\nor start BeSpec with --no-wallpaper to get the normal window back.");
+                    });
+                    ui.end_row();
+
+                    row_label(ui, label::RIGHT_CLICK_MENU);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let items = &mut state.config.context_menu;
+                        ui.horizontal_wrapped(|ui| {
+                            ui.checkbox(&mut items.presets, "Presets");
+                            ui.checkbox(&mut items.devices, "Devices");
+                            ui.checkbox(&mut items.ghost_mode, "Ghost Mode");
+                            ui.checkbox(&mut items.pause, "Pause");
+                            ui.checkbox(&mut items.screenshot, "Screenshot");
+                            ui.checkbox(&mut items.mini_mode, "Mini Mode");
+                        });
+                    })
+                    .response
+                    .on_hover_text("Quick actions in the right-click menu. Settings and Exit are always there.");
+                    ui.end_row();
+
+                    row_label(ui, label::GAMEPAD);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if crate::gamepad::SUPPORTED {
+                            ui.checkbox(&mut state.config.gamepad_enabled, "Enabled").on_hover_text(
+                                "LB / RB: visual profile  ·  D-pad ← →: colors  ·  D-pad ↑ ↓: background opacity\nStart: settings overlay (B closes it)",
+                            );
+                        } else {
+                            ui.add_enabled(false, egui::Checkbox::new(&mut state.config.gamepad_enabled, "Enabled"))
+                                .on_disabled_hover_text("This build has no controller support (cargo feature 'gamepad').");
+                        }
+                    });
+                    ui.end_row();
+
                     // Ghost Mode
-                    ui.label("Ghost Mode 👻");
+                    row_label(ui, label::GHOST_MODE);
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         ui.add(egui::Label::new("❓").sense(egui::Sense::hover()))
-                            .on_hover_text("How to use Ghost Mode:\n1. Click the Lock icon (bottom-left) to enable click-through.\n2. The window will ignore mouse clicks.\n3. To UNLOCK: Alt-Tab back to this window.");
+                            .on_hover_text("How to use Ghost Mode:\n1. Click the Lock icon (bottom-left) to enable click-through.\n2. The window will ignore mouse clicks.\n3. To UNLOCK: Alt-Tab back to this window,\n   or use the hot corner or handle below.");
                         ui.label("Enable via Lock Icon 🔒");
                     });
                     ui.end_row();
+
+                    row_label(ui, label::GHOST_HOT_CORNER);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        egui::ComboBox::from_id_salt("ghost_hot_corner_combo")
+                            .selected_text(state.config.ghost_hot_corner.label())
+                            .show_ui(ui, |ui| {
+                                for corner in HotCorner::ALL {
+                                    ui.selectable_value(&mut state.config.ghost_hot_corner, corner, corner.label());
+                                }
+                            })
+                            .response
+                            .on_hover_text("Rest the mouse in this corner of any screen for a moment and the ghosted window\ntakes clicks again for a few seconds. Not available on Wayland.");
+                    });
+                    ui.end_row();
+
+                    row_label(ui, label::GHOST_HANDLE);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.checkbox(&mut state.config.ghost_handle, "")
+                            .on_hover_text("Keep the Lock icon's corner clickable while the rest of the window is click-through.\nNot available on Wayland.");
+                    });
+                    ui.end_row();
                 });
         });
 
         ui.add_space(10.0);
 
-        // === 3. Media & Integration ===
+        // === 3. Extra Windows ===
+        ui.group(|ui| {
+            section_label(ui, label::WINDOWS);
+            ui.separator();
+            ui_extra_windows(ui, state);
+        });
+
+        ui.add_space(10.0);
+
+        // === 4. Media & Integration ===
         ui.group(|ui| {
             ui.label(egui::RichText::new("Media Integration").strong());
             ui.separator();
@@ -1126,7 +3054,7 @@ pub fn settings_tab_window(ui: &mut egui::Ui, state: &mut SharedState) {
                 .min_col_width(150.0)
                 .show(ui, |ui| {
                     // Media Mode
-                    ui.label("Now Playing Overlay");
+                    row_label(ui, label::NOW_PLAYING_OVERLAY);
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         egui::ComboBox::from_id_salt("media_mode")
                             .selected_text(format!("{:?}", state.config.media_display_mode))
@@ -1138,14 +3066,90 @@ pub fn settings_tab_window(ui: &mut egui::Ui, state: &mut SharedState) {
                     });
                     ui.end_row();
 
+                    ui_media_overlay(ui, state);
+
+                    // Album art click-through
+                    row_label(ui, label::ALBUM_ART_LINK);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        egui::ComboBox::from_id_salt("track_link_source")
+                            .selected_text(state.config.track_link.source.label())
+                            .show_ui(ui, |ui| {
+                                for source in TrackLinkSource::ALL {
+                                    ui.selectable_value(&mut state.config.track_link.source, source, source.label());
+                                }
+                            })
+                            .response
+                            .on_hover_text("What clicking the album art opens");
+                    });
+                    ui.end_row();
+
+                    if state.config.track_link.source == TrackLinkSource::Custom {
+                        row_label(ui, label::LINK_TEMPLATE);
+                        ui.add(egui::TextEdit::singleline(&mut state.config.track_link.template).desired_width(220.0))
+                            .on_hover_text("{artist}, {title} and {album} are filled in from the current track");
+                        ui.end_row();
+                    }
+
+                    ui_lyrics(ui, state);
+
+                    row_label(ui, label::TRACK_CHANGE_TOAST);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.checkbox(&mut state.config.track_toast, "Show")
+                            .on_hover_text("Pop up the new track's artist and title at the bottom of the window
+for a few seconds, whatever the overlay is set to.");
+                    });
+                    ui.end_row();
+
+                    row_label(ui, label::TRACK_HISTORY);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Clear").clicked() {
+                            state.track_history.clear();
+                        }
+                        ui.add(egui::DragValue::new(&mut state.config.track_history_len).range(5..=500).suffix(" tracks"))
+                            .on_hover_text("How many recent tracks to keep. The list is never saved to disk.");
+                    });
+                    ui.end_row();
+
                     // Privacy
-                    ui.label("Privacy");
+                    row_label(ui, label::PRIVACY);
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         ui.checkbox(&mut state.config.log_media_metadata, "Log Media Metadata")
                             .on_hover_text("When enabled, song titles and artists are written to the daily log files.\nKeep this off to prevent tracking your listening history.");
                     });
                     ui.end_row();
+
+                    // Network
+                    row_label(ui, label::NETWORK);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.checkbox(&mut state.config.offline_mode, "Offline Mode")
+                            .on_hover_text("Block every network request: update check, preset gallery,\nWikipedia, MusicBrainz and lyrics lookups and album art downloads.")
+                            .changed()
+                        {
+                            crate::net::set_offline(state.config.offline_mode);
+                        }
+                    });
+                    ui.end_row();
                 });
+
+            ui_track_history(ui, state);
+        });
+
+        ui.add_space(10.0);
+
+        // === 5. Remote Control ===
+        ui.group(|ui| {
+            ui.label(egui::RichText::new("Remote Control").strong());
+            ui.separator();
+            ui_remote_control(ui, state);
+        });
+
+        ui.add_space(10.0);
+
+        // === 6. Connected Accounts ===
+        ui.group(|ui| {
+            section_label(ui, label::CONNECTED_ACCOUNTS);
+            ui.separator();
+            ui_connected_accounts(ui, state);
         });
 
         ui.add_space(10.0); // Bottom padding
@@ -1154,6 +3158,50 @@ pub fn settings_tab_window(ui: &mut egui::Ui, state: &mut SharedState) {
     });
 }
 
+/// List of extra visualizer windows: profile picker and remove button per
+/// window, plus "Add Window" (starts from the current profile)
+fn ui_extra_windows(ui: &mut egui::Ui, state: &mut SharedState) {
+    if state.config.extra_windows.is_empty() {
+        ui.label(egui::RichText::new("Open more visualizer windows, each with its own visual profile.").small().weak());
+    }
+
+    let mut profiles = state.user_visual_presets.clone();
+    profiles.extend(VisualProfile::built_in());
+    let mut remove = None;
+
+    for (i, win) in state.config.extra_windows.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(format!("Window {}", i + 1));
+            egui::ComboBox::from_id_salt(("extra_window_profile", win.id))
+                .selected_text(&win.profile.name)
+                .show_ui(ui, |ui| {
+                    for vp in &profiles {
+                        if ui.selectable_label(win.profile.name == vp.name, &vp.name).clicked() {
+                            win.profile = vp.clone();
+                        }
+                    }
+                });
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("🗑").on_hover_text("Close this window").clicked() {
+                    remove = Some(win.id);
+                }
+            });
+        });
+    }
+
+    if let Some(id) = remove {
+        state.config.extra_windows.retain(|w| w.id != id);
+    }
+
+    ui.add_space(4.0);
+    if ui.button("+ Add Window").on_hover_text("All windows show the selected audio device").clicked() {
+        let id = state.config.extra_windows.iter().map(|w| w.id).max().map_or(1, |m| m + 1);
+        let profile = state.config.profile.clone();
+        tracing::info!("[GUI] Opening extra window {} ({})", id, profile.name);
+        state.config.extra_windows.push(ExtraWindow::new(id, profile));
+    }
+}
+
 pub fn settings_tab_performance(ui: &mut egui::Ui, state: &mut SharedState) {
 let grid_spacing = egui::vec2(40.0, 12.0);
 
@@ -1164,18 +3212,39 @@ let grid_spacing = egui::vec2(40.0, 12.0);
         ui.label(egui::RichText::new("On-Screen HUD").strong());
         ui.separator();
         
-        ui.checkbox(&mut state.config.show_stats, "Show Performance Overlay");
+        ui.checkbox(&mut state.config.show_stats, "Show Performance Overlay")
+            .on_hover_text("FPS, FFT timing and a graph of each frame split into lock wait, FFT, bin aggregation and paint,\nso you can tell whether drawing or processing is the slow part.");
+
+        ui.horizontal(|ui| {
+            row_label(ui, label::LEVEL_METER);
+            ui.checkbox(&mut state.config.meter_strip, "Peak / RMS / crest factor strip")
+                .on_hover_text("Input level in dBFS at the right edge, before sensitivity.\nCrest factor is peak minus RMS: about 3 dB for a sine, more for dynamic material.");
+        });
+
+        ui.horizontal(|ui| {
+            row_label(ui, label::FRAME_RATE_CAP);
+            egui::ComboBox::from_id_salt("fps_cap_combo")
+                .selected_text(state.config.fps_cap.label())
+                .show_ui(ui, |ui| {
+                    for cap in [FpsCap::Fps30, FpsCap::Fps60, FpsCap::Fps120, FpsCap::Display] {
+                        ui.selectable_value(&mut state.config.fps_cap, cap, cap.label());
+                    }
+                })
+                .response
+                .on_hover_text("Lower caps save CPU/GPU. Display Refresh repaints as often as the window's monitor refreshes\n(60 FPS where the OS doesn't say).");
+        });
         
         // Explainer text matching the exact order of the overlay render
         ui.add_space(4.0);
         ui.indent("stats_explainer", |ui| {
             ui.spacing_mut().item_spacing.y = 2.0;
             ui.label(egui::RichText::new("Displays real-time metrics in the top-left corner:").small());
-            ui.label(egui::RichText::new("• FPS: Visual rendering speed").small());
+            ui.label(egui::RichText::new("• FPS: Visual rendering speed (and the cap, if one is set)").small());
             ui.label(egui::RichText::new("• FFT: Average time to process an audio block").small());
             ui.label(egui::RichText::new("• Min/Max: Fastest and slowest recent processing times").small());
             ui.label(egui::RichText::new("• FFT Res: Mathematical engine precision (Hertz per FFT bin)").small());
             ui.label(egui::RichText::new("• Bars: Actual bars drawn vs. Profile requested limit").small());
+            ui.label(egui::RichText::new("• Centroid / Rolloff / Flatness: How bright the sound is, and how noise-like").small());
         });
     });
 
@@ -1192,27 +3261,557 @@ let grid_spacing = egui::vec2(40.0, 12.0);
             .spacing(grid_spacing)
             .striped(true)
             .show(ui, |ui| {
-                ui.label("Sample Rate");
+                row_label(ui, label::SAMPLE_RATE);
                 ui.label(format!("{} Hz", info.sample_rate));
                 ui.end_row();
 
-                ui.label("FFT Size");
+                row_label(ui, label::FFT_SIZE);
                 ui.label(format!("{} samples", info.fft_size));
                 ui.end_row();
 
-                ui.label("Frequency Resolution");
+                row_label(ui, label::FREQUENCY_RESOLUTION);
                 ui.label(format!("{:.2} Hz / bin", info.frequency_resolution));
                 ui.end_row();
 
-                ui.label("Theoretical Latency");
+                row_label(ui, label::THEORETICAL_LATENCY);
                 ui.label(format!("{:.2} ms", info.latency_ms));
                 ui.end_row();
 
-                ui.label("GUI Frame Rate");
+                let latency = &state.performance.latency;
+                row_label(ui, label::MEASURED_LATENCY);
+                ui.label(format!("{:.1} ms", latency.total_ms()))
+                    .on_hover_text(format!(
+                        "Capture to screen, not counting the FFT window:\n  buffer {:.1} ms ({} frames per callback)\n  processing {:.1} ms\n  display {:.1} ms",
+                        latency.buffer_ms(),
+                        latency.buffer_frames,
+                        latency.processing_ms,
+                        latency.display_ms,
+                    ));
+                ui.end_row();
+
+                let test = &mut state.latency_test;
+                row_label(ui, label::LATENCY_SELF_TEST);
+                ui.horizontal(|ui| {
+                    let label = if test.running { "⏹ Cancel" } else { "▶ Run" };
+                    if ui.button(label)
+                        .on_hover_text("Play clicks on the output below and time how long each takes to show up in the spectrum.\nThe whole round trip: output, speaker to microphone (or the loopback), capture, FFT and smoothing.\nRun it in a quiet room, with a spectrum mode on screen.")
+                        .clicked()
+                    {
+                        if test.running {
+                            test.cancel();
+                        } else {
+                            test.start();
+                        }
+                        tracing::info!("[GUI] Latency self-test {}", if test.running { "started" } else { "cancelled" });
+                    }
+                    if test.running {
+                        let (heard, wanted) = test.progress();
+                        ui.label(format!("Listening… {}/{}", heard, wanted));
+                        ui.ctx().request_repaint_after(std::time::Duration::from_millis(250));
+                    } else if let Some(result) = &test.result {
+                        match result {
+                            Err(e) => {
+                                ui.colored_label(ui.visuals().warn_fg_color, "Couldn't play the clicks")
+                                    .on_hover_text(e.as_str());
+                            }
+                            Ok(result) if result.heard == 0 => {
+                                ui.colored_label(ui.visuals().warn_fg_color, "No clicks heard")
+                                    .on_hover_text("Is the input listening to this output? Check the devices and the volume.");
+                            }
+                            Ok(result) => {
+                                ui.label(format!("{:.0} ms", result.median_ms))
+                                    .on_hover_text(format!(
+                                        "Median of {} clicks ({} played)\nfastest {:.0} ms, slowest {:.0} ms",
+                                        result.heard, result.played, result.min_ms, result.max_ms,
+                                    ));
+                            }
+                        }
+                    }
+                });
+                ui.end_row();
+
+                row_label(ui, label::CLICK_OUTPUT);
+                ui.add_enabled_ui(!test.running, |ui| {
+                    output_device_combo(ui, "latency_test_output_combo", &mut test.output_device);
+                });
+                ui.end_row();
+
+                row_label(ui, label::GUI_FRAME_RATE);
                 ui.label(format!("{:.1} FPS", state.performance.gui_fps));
                 ui.end_row();
+
+                row_label(ui, label::DISPLAY_REFRESH);
+                let refresh = match state.performance.display_hz {
+                    Some(hz) => ui.label(format!("{:.0} Hz", hz)),
+                    None => ui.label("Unknown (pacing at 60 Hz)"),
+                };
+                refresh.on_hover_text("Refresh rate of the monitor the window is on.\nWith the frame rate cap on Display Refresh, the GUI repaints this often.");
+                ui.end_row();
+
+                row_label(ui, label::DROPPED_FRAMES);
+                ui.label(state.performance.dropped_frames.to_string())
+                    .on_hover_text("Frames that came 1.5× later than the pacing asked for this session,\nso the screen showed the previous frame again. Not counted while asleep or hidden.");
+                ui.end_row();
+
+                let usage = state.performance.process;
+                row_label(ui, label::CPU_USAGE);
+                match usage {
+                    Some(usage) => ui.label(format!("{:.1} %", usage.cpu_percent_of_machine()))
+                        .on_hover_text(format!(
+                            "Share of all {} cores, like Task Manager shows it\n({:.0} % of one core)",
+                            usage.cores, usage.cpu_percent,
+                        )),
+                    None => ui.label("Measuring…"),
+                };
+                ui.end_row();
+
+                row_label(ui, label::MEMORY_USAGE);
+                match usage {
+                    Some(usage) => ui.label(format!("{:.0} MB", usage.memory_mib()))
+                        .on_hover_text("Resident memory (working set), updated every 2 s"),
+                    None => ui.label("Measuring…"),
+                };
+                ui.end_row();
+
+                row_label(ui, label::RENDERER_STALLS);
+                ui.label(state.render_watchdog.stalls.to_string())
+                    .on_hover_text("Times the window stopped drawing for 5 s or more this session\n(GPU or driver resets). Details are in the log.");
+                ui.end_row();
+
+                use crate::stress_test::StressStatus;
+                row_label(ui, label::STRESS_TEST);
+                ui.horizontal(|ui| {
+                    let busy = matches!(state.stress_test, StressStatus::Requested | StressStatus::Working);
+                    if ui.add_enabled(!busy, egui::Button::new("▶ Run"))
+                        .on_hover_text(format!(
+                            "Process and draw synthetic audio at {} bars in the current mode, off-screen,\nand report how much of a 60 FPS frame is left. Takes a second or two.",
+                            crate::stress_test::MAX_BARS,
+                        ))
+                        .clicked()
+                    {
+                        state.stress_test = StressStatus::Requested;
+                        tracing::info!("[GUI] Stress test started");
+                    }
+                    match &state.stress_test {
+                        StressStatus::Requested | StressStatus::Working => {
+                            ui.spinner();
+                        }
+                        StressStatus::Done(report) => {
+                            let text = format!("{:.0}% headroom", report.headroom() * 100.0);
+                            let label = if report.headroom() < 0.25 {
+                                ui.colored_label(ui.visuals().warn_fg_color, text)
+                            } else {
+                                ui.label(text)
+                            };
+                            label.on_hover_text(format!(
+                                "Per frame at {} bars, averaged over {} frames:\n  FFT {:.2} ms\n  draw {:.2} ms\n  tessellate {:.2} ms\nUp to {:.0} FPS before the GPU (not measured)",
+                                report.bars, report.frames, report.fft_ms, report.draw_ms, report.tessellate_ms, report.max_fps(),
+                            ));
+                        }
+                        StressStatus::Idle => {}
+                    }
+                });
+                ui.end_row();
+            });
+    });
+
+    ui.add_space(10.0);
+
+    // === 3. Auto Sleep ===
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("Auto Sleep").strong());
+        ui.separator();
+
+        egui::Grid::new("auto_sleep_grid")
+            .num_columns(2)
+            .spacing(grid_spacing)
+            .min_col_width(150.0)
+            .show(ui, |ui| {
+                row_label(ui, label::SLEEP_ON_SILENCE);
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.checkbox(&mut state.config.auto_sleep_enabled, "")
+                        .on_hover_text("Repaint at 2 FPS when nothing is above the noise floor.\nWakes instantly when audio returns.");
+                });
+                ui.end_row();
+
+                row_label(ui, label::SLEEP_AFTER);
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.add_enabled(
+                        state.config.auto_sleep_enabled,
+                        egui::Slider::new(&mut state.config.auto_sleep_after_sec, 5.0..=300.0)
+                            .suffix(" s")
+                            .logarithmic(true),
+                    );
+                });
+                ui.end_row();
+
+                row_label(ui, label::DIM_WHILE_ASLEEP);
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.add_enabled(
+                        state.config.auto_sleep_enabled,
+                        egui::Checkbox::new(&mut state.config.auto_sleep_dim, ""),
+                    );
+                });
+                ui.end_row();
+
+                row_label(ui, label::PAUSE_WHEN_HIDDEN);
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.checkbox(&mut state.config.hidden_throttle, "")
+                        .on_hover_text("Stop drawing while the window is minimized or completely covered by other windows\n(checked once a second), and pick up again when it shows.\nCovered windows are detected on Windows and X11.");
+                });
+                ui.end_row();
+
+                row_label(ui, label::PAUSE_FFT_WHEN_HIDDEN);
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.add_enabled(
+                        state.config.hidden_throttle,
+                        egui::Checkbox::new(&mut state.config.hidden_pause_fft, ""),
+                    )
+                    .on_hover_text("Skip the audio processing too while hidden, for the most battery.\nMIDI, OSC, the spectrum log and the remote APIs stop getting data meanwhile.");
+                });
+                ui.end_row();
             });
     });
+
+    ui.add_space(10.0);
+
+    // === 4. Spectrum Log ===
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("Spectrum Data Logger").strong());
+        ui.separator();
+        ui_spectrum_log(ui, state, grid_spacing);
+    });
+
+    ui.add_space(10.0);
+
+    // === 5. Support ===
+    ui.group(|ui| {
+        section_label(ui, label::SUPPORT);
+        ui.separator();
+        ui_support_bundle(ui, state);
+    });
+
+    ui.add_space(10.0);
+
+    // === 6. Logs ===
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("Logs").strong());
+        ui.separator();
+        ui_log_viewer(ui, state);
+    });
+
+    ui.add_space(10.0);
+
+    // === 7. Maintenance ===
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("Maintenance").strong());
+        ui.separator();
+        ui_maintenance(ui, state, grid_spacing);
+    });
+}
+
+/// Recent log lines with a level and text filter, copy and open-folder buttons
+fn ui_log_viewer(ui: &mut egui::Ui, state: &SharedState) {
+    use tracing::Level;
+
+    let level_id = egui::Id::new("log_viewer_level");
+    let text_id = egui::Id::new("log_viewer_text");
+    let mut level = ui.data(|d| d.get_temp::<Level>(level_id)).unwrap_or(Level::INFO);
+    let mut text = ui.data(|d| d.get_temp::<String>(text_id)).unwrap_or_default();
+
+    ui.horizontal(|ui| {
+        row_label(ui, label::LOG_LEVEL);
+        egui::ComboBox::from_id_salt("log_level_combo")
+            .selected_text(level.as_str())
+            .show_ui(ui, |ui| {
+                for option in [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG, Level::TRACE] {
+                    ui.selectable_value(&mut level, option, option.as_str());
+                }
+            })
+            .response
+            .on_hover_text("Show this level and anything more severe.\nDebug and trace lines only exist when BeSpec runs with RUST_LOG set.");
+        ui.add(egui::TextEdit::singleline(&mut text).hint_text("Filter…").desired_width(140.0));
+    });
+
+    let lines = crate::log_buffer::lines(level, &text);
+    egui::ScrollArea::vertical()
+        .id_salt("log_viewer_scroll")
+        .max_height(220.0)
+        .stick_to_bottom(true)
+        .auto_shrink([false, true])
+        .show(ui, |ui| {
+            for line in &lines {
+                let color = match line.level {
+                    Level::ERROR => ui.visuals().error_fg_color,
+                    Level::WARN => ui.visuals().warn_fg_color,
+                    _ => ui.visuals().text_color(),
+                };
+                ui.label(egui::RichText::new(line.to_string()).monospace().small().color(color));
+            }
+        });
+
+    ui.horizontal(|ui| {
+        if ui.button("📋 Copy").on_hover_text("Copy the lines shown, e.g. into a bug report.\nDevice names and song titles are left out, as in a support bundle.").clicked() {
+            let all: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+            ui.ctx().copy_text(crate::support_bundle::scrub_log(&all.join("\n"), &state.audio_devices, &state.config));
+        }
+        if ui.button("📂 Log Folder").on_hover_text("Daily log files, the last week kept").clicked() {
+            let _ = open::that(&crate::paths::get().log_dir);
+        }
+        ui.label(egui::RichText::new(format!("{} lines", lines.len())).small().weak());
+    });
+
+    ui.data_mut(|d| {
+        d.insert_temp(level_id, level);
+        d.insert_temp(text_id, text);
+    });
+}
+
+/// Back up the settings before something that throws work away. A failed
+/// backup is logged, it doesn't stop the action.
+fn backup_before(state: &SharedState, reason: &str) {
+    if let Err(e) = crate::config_backup::create(&state.config, reason) {
+        tracing::warn!("[Backup] Couldn't back up before '{}': {}", reason, e);
+    }
+}
+
+/// Reset to defaults, by section or all at once, and the backup picker
+fn ui_maintenance(ui: &mut egui::Ui, state: &mut SharedState, grid_spacing: egui::Vec2) {
+    use crate::shared_state::ConfigSection;
+
+    let status_id = egui::Id::new("maintenance_status");
+    let pick_id = egui::Id::new("backup_pick");
+    let mut status: Option<String> = None;
+
+    egui::Grid::new("maintenance_grid")
+        .num_columns(2)
+        .spacing(grid_spacing)
+        .show(ui, |ui| {
+            row_label(ui, label::RESET_SETTINGS);
+            ui.menu_button("↺ Reset to Defaults…", |ui| {
+                for section in ConfigSection::ALL {
+                    if ui.button(section.label()).clicked() {
+                        backup_before(state, &format!("reset {}", section.label()));
+                        state.config.reset(section);
+                        tracing::info!("[Config] Reset {} settings", section.label());
+                        status = Some(format!("{} settings reset. The old ones are in a backup.", section.label()));
+                        ui.close_menu();
+                    }
+                }
+            })
+            .response
+            .on_hover_text("A backup is saved first, so a reset can be undone below.");
+            ui.end_row();
+
+            let backups = crate::config_backup::list();
+            let mut pick: Option<std::path::PathBuf> = ui.data(|d| d.get_temp(pick_id));
+            if pick.as_ref().map_or(true, |p| !backups.iter().any(|b| &b.path == p)) {
+                pick = backups.first().map(|b| b.path.clone());
+            }
+
+            row_label(ui, label::BACKUPS);
+            ui.horizontal(|ui| {
+                let selected = backups.iter().find(|b| Some(&b.path) == pick.as_ref()).map_or("None yet", |b| b.label.as_str());
+                egui::ComboBox::from_id_salt("backup_combo")
+                    .selected_text(selected)
+                    .width(260.0)
+                    .show_ui(ui, |ui| {
+                        for backup in &backups {
+                            ui.selectable_value(&mut pick, Some(backup.path.clone()), &backup.label);
+                        }
+                    });
+                if ui.add_enabled(pick.is_some(), egui::Button::new("Restore"))
+                    .on_hover_text("Put back these settings and presets.\nWhat you have now is backed up first.")
+                    .clicked()
+                {
+                    if let Some(path) = &pick {
+                        status = Some(match crate::config_backup::restore(&state.config, path) {
+                            Ok(config) => {
+                                for p in &state.user_visual_presets {
+                                    thumbnails::forget(ui.ctx(), PresetKind::Visual, &p.name);
+                                }
+                                for p in &state.user_color_presets {
+                                    thumbnails::forget(ui.ctx(), PresetKind::Color, &p.name);
+                                }
+                                state.config = config;
+                                state.user_visual_presets = crate::shared_state::AppConfig::load_user_visual_presets();
+                                state.user_color_presets = crate::shared_state::AppConfig::load_user_color_presets();
+                                "Backup restored.".to_string()
+                            }
+                            Err(e) => {
+                                tracing::error!("[Backup] Restore failed: {}", e);
+                                format!("Restore failed: {}", e)
+                            }
+                        });
+                    }
+                }
+                if ui.button("💾 Back Up Now").clicked() {
+                    status = Some(match crate::config_backup::create(&state.config, "manual") {
+                        Ok(_) => "Backup saved.".to_string(),
+                        Err(e) => format!("Backup failed: {}", e),
+                    });
+                }
+                if ui.button("📂").on_hover_text("Open the backups folder").clicked() {
+                    let dir = crate::config_backup::backups_dir();
+                    let _ = std::fs::create_dir_all(&dir);
+                    let _ = open::that(dir);
+                }
+            });
+            ui.end_row();
+            ui.data_mut(|d| d.insert_temp(pick_id, pick));
+        });
+
+    if let Some(status) = status {
+        ui.data_mut(|d| d.insert_temp(status_id, status));
+    }
+    if let Some(status) = ui.data(|d| d.get_temp::<String>(status_id)) {
+        ui.label(egui::RichText::new(status).small().weak());
+    }
+}
+
+/// Start / stop for the spectrum logger, its format and file options
+fn ui_spectrum_log(ui: &mut egui::Ui, state: &mut SharedState, grid_spacing: egui::Vec2) {
+    use crate::spectrum_log::LogFormat;
+
+    let running = state.spectrum_log_running;
+    egui::Grid::new("spectrum_log_grid")
+        .num_columns(2)
+        .spacing(grid_spacing)
+        .show(ui, |ui| {
+            row_label(ui, label::SPECTRUM_LOG);
+            ui.horizontal(|ui| {
+                let label = if running { "⏹ Stop" } else { "⏺ Record" };
+                if ui.button(label).on_hover_text("Append bar levels with timestamps to a file for offline analysis").clicked() {
+                    state.spectrum_log_running = !running;
+                    tracing::info!("[GUI] Spectrum log {}", if running { "stopped" } else { "started" });
+                }
+                if running {
+                    ui.label(format!("{} frames", state.spectrum_log_status.frames));
+                }
+            });
+            ui.end_row();
+
+            // Changing these mid-recording would mix formats in one file
+            row_label(ui, label::LOG_FORMAT);
+            ui.add_enabled_ui(!running, |ui| {
+                ui.horizontal(|ui| {
+                    let log = &mut state.config.spectrum_log;
+                    for format in LogFormat::ALL {
+                        ui.selectable_value(&mut log.format, format, format.label());
+                    }
+                });
+            });
+            ui.end_row();
+
+            row_label(ui, label::KEEP_EVERY);
+            ui.add_enabled(
+                !running,
+                egui::DragValue::new(&mut state.config.spectrum_log.every_nth_frame)
+                    .range(1..=120)
+                    .prefix("1 in ")
+                    .suffix(" frames"),
+            )
+            .on_hover_text("Write only every Nth frame to keep long recordings small");
+            ui.end_row();
+
+            row_label(ui, label::NEW_FILE_AFTER);
+            ui.add_enabled(
+                !running,
+                egui::DragValue::new(&mut state.config.spectrum_log.max_file_mb).range(1..=2048).suffix(" MB"),
+            )
+            .on_hover_text("Files roll over to a new part at this size");
+            ui.end_row();
+        });
+
+    let status = &state.spectrum_log_status;
+    if let Some(error) = &status.error {
+        ui.colored_label(ui.visuals().warn_fg_color, format!("Stopped: {}", error));
+    } else if let Some(file) = &status.file {
+        ui.label(egui::RichText::new(file.display().to_string()).small().weak());
+    }
+}
+
+/// Capabilities of one audio device, for the popover next to the device combo
+fn ui_device_details(ui: &mut egui::Ui, dev: &crate::audio_device::AudioDeviceInfo) {
+    fn join<T: ToString>(items: &[T]) -> String {
+        if items.is_empty() {
+            "not reported".to_string()
+        } else {
+            items.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(", ")
+        }
+    }
+
+    ui.label(egui::RichText::new(&dev.name).strong());
+    ui.separator();
+    egui::Grid::new("device_details_grid").num_columns(2).spacing([12.0, 4.0]).show(ui, |ui| {
+        ui.label("In use");
+        ui.label(format!("{} Hz, {} ch, {}", dev.default_sample_rate, dev.channels, dev.default_sample_format));
+        ui.end_row();
+        ui.label("Sample rates");
+        ui.label(join(&dev.sample_rates));
+        ui.end_row();
+        ui.label("Channels");
+        ui.label(join(&dev.channel_counts));
+        ui.end_row();
+        ui.label("Formats");
+        ui.label(join(&dev.sample_formats));
+        ui.end_row();
+    });
+    if let Err(err) = dev.validate() {
+        ui.add_space(4.0);
+        ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ Can't capture: {}", err));
+    }
+}
+
+/// "Create Support Bundle" button, its consent dialog and the result line
+fn ui_support_bundle(ui: &mut egui::Ui, state: &mut SharedState) {
+    use crate::support_bundle::BundleStatus;
+
+    ui.horizontal(|ui| {
+        let busy = matches!(state.support_bundle, BundleStatus::Requested | BundleStatus::Working);
+        if ui
+            .add_enabled(!busy, egui::Button::new("📦 Create Support Bundle…"))
+            .on_hover_text("A zip with your config, recent logs and device list to attach to a GitHub issue.\nNothing is uploaded.")
+            .clicked()
+        {
+            state.support_bundle = BundleStatus::Confirming;
+        }
+        if busy {
+            ui.spinner();
+        }
+    });
+
+    if let BundleStatus::Done(path) = &state.support_bundle {
+        ui.label(egui::RichText::new(format!("Saved: {}", path.display())).small().weak());
+    }
+
+    // Consent every time, and say exactly what goes in
+    if state.support_bundle == BundleStatus::Confirming {
+        egui::Window::new("Create Support Bundle?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ui.ctx(), |ui| {
+                ui.label("This writes a zip file to your BeSpec log folder containing:");
+                ui.label("  • your settings (config.json)");
+                ui.label("  • the most recent log files");
+                ui.label("  • sample rates and channel counts of your audio devices");
+                ui.add_space(4.0);
+                ui.label(egui::RichText::new(
+                    "Device names, your user name and song titles are removed. \
+                     Nothing is sent anywhere: you decide whether to attach the file to an issue.",
+                ).small().weak());
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Create").clicked() {
+                        state.support_bundle = BundleStatus::Requested;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        state.support_bundle = BundleStatus::Idle;
+                    }
+                });
+            });
+    }
 }
 
 
@@ -1281,6 +3880,255 @@ pub fn ui_save_popup(
     });
 }
 
+// =======================================================================================
+// THEME FILES (Import / Export)
+// =======================================================================================
+
+/// "Export" / "Import" row shared by the Visual and Colors preset managers
+fn ui_theme_file_buttons(
+    ui: &mut Ui,
+    state: &mut SharedState,
+    save_target: &mut SaveTarget,
+    path_buffer: &mut String,
+) {
+    ui.horizontal(|ui| {
+        if ui.small_button("⬆ Export theme…")
+            .on_hover_text("Write the current visual + colors to a shareable .bespec-theme file")
+            .clicked()
+        {
+            let theme = ThemeFile::from_config(&state.config, &state.user_color_presets);
+            match theme.export() {
+                Ok(path) => {
+                    set_theme_status(ui, format!("Exported to {}", path.display()));
+                    if let Some(dir) = path.parent() {
+                        let _ = open::that(dir);
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("[Presets] Theme export failed: {}", e);
+                    set_theme_status(ui, format!("Export failed: {}", e));
+                }
+            }
+        }
+
+        if ui.small_button("⬇ Import theme…")
+            .on_hover_text("Load a .bespec-theme file as user presets")
+            .clicked()
+        {
+            *save_target = SaveTarget::Import;
+            path_buffer.clear();
+        }
+    });
+
+    if let Some(status) = ui.data(|d| d.get_temp::<String>(egui::Id::new("theme_file_status"))) {
+        ui.label(egui::RichText::new(status).small().weak());
+    }
+}
+
+/// Pick a theme file (from the themes folder, or any path) and install it
+fn ui_import_popup(
+    ui: &mut Ui,
+    state: &mut SharedState,
+    save_target: &mut SaveTarget,
+    path_buffer: &mut String,
+) {
+    ui.group(|ui| {
+        let found = crate::theme_file::list_theme_files();
+        if found.is_empty() {
+            ui.label(egui::RichText::new("No .bespec-theme files in the themes folder yet").small().weak());
+        }
+        for path in &found {
+            let label = path.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
+            if ui.selectable_label(path_buffer.as_str() == path.to_string_lossy(), label).clicked() {
+                *path_buffer = path.to_string_lossy().to_string();
+            }
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("File:");
+            ui.text_edit_singleline(path_buffer);
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button("Import").clicked() && !path_buffer.is_empty() {
+                let status = match import_theme(state, std::path::Path::new(path_buffer.trim())) {
+                    Ok(name) => format!("Imported '{}'", name),
+                    Err(e) => {
+                        tracing::error!("[Presets] Theme import failed: {}", e);
+                        format!("Import failed: {}", e)
+                    }
+                };
+                set_theme_status(ui, status);
+                *save_target = SaveTarget::None;
+            }
+            if ui.button("Cancel").clicked() {
+                *save_target = SaveTarget::None;
+            }
+            if ui.button("📂 Folder").on_hover_text("Open the themes folder").clicked() {
+                let dir = crate::theme_file::themes_dir();
+                let _ = std::fs::create_dir_all(&dir);
+                let _ = open::that(dir);
+            }
+        });
+    });
+}
+
+/// Read a theme file and install it
+fn import_theme(state: &mut SharedState, path: &std::path::Path) -> Result<String, String> {
+    let theme = ThemeFile::import(path)?;
+    let name = install_theme(state, theme)?;
+    tracing::info!("[Presets] Imported theme '{}' from {:?}", name, path);
+    Ok(name)
+}
+
+/// Install both halves of a theme as user presets and switch to it
+fn install_theme(state: &mut SharedState, mut theme: ThemeFile) -> Result<String, String> {
+    backup_before(state, "import theme");
+    theme.resolve_collisions(&state.user_visual_presets, &state.user_color_presets);
+
+    crate::shared_state::AppConfig::save_user_color_preset(&theme.colors).map_err(|e| e.to_string())?;
+    crate::shared_state::AppConfig::save_user_visual_preset(&theme.visual).map_err(|e| e.to_string())?;
+
+    if !state.user_color_presets.iter().any(|p| p.name == theme.colors.name) {
+        state.user_color_presets.push(theme.colors.clone());
+    }
+    if !state.user_visual_presets.iter().any(|p| p.name == theme.visual.name) {
+        state.user_visual_presets.push(theme.visual.clone());
+    }
+
+    state.config.profile = theme.visual.clone();
+    Ok(theme.visual.name)
+}
+
+/// Collapsible "Community presets" browser (Visual tab)
+fn ui_preset_gallery(ui: &mut Ui, state: &mut SharedState) {
+    egui::CollapsingHeader::new("🌐 Browse community presets")
+        .id_salt("preset_gallery")
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Index URL:");
+                ui.add(egui::TextEdit::singleline(&mut state.config.preset_gallery_url).desired_width(220.0));
+            });
+
+            ui.horizontal(|ui| {
+                let loading = matches!(state.gallery, GalleryStatus::Loading);
+                if ui.add_enabled(!loading, egui::Button::new("⟳ Fetch")).clicked() {
+                    state.gallery_fetch_requested = true;
+                }
+                if loading {
+                    ui.spinner();
+                }
+            });
+
+            let mut to_install: Option<ThemeFile> = None;
+            match &state.gallery {
+                GalleryStatus::Idle => {
+                    ui.label(egui::RichText::new("Nothing is downloaded until you press Fetch.").small().weak());
+                }
+                GalleryStatus::Loading => {}
+                GalleryStatus::Failed(e) => {
+                    ui.colored_label(egui::Color32::from_rgb(220, 80, 80), e);
+                }
+                GalleryStatus::Ready(entries) => {
+                    if entries.is_empty() {
+                        ui.label("The gallery is empty.");
+                    }
+                    for (i, entry) in entries.iter().enumerate() {
+                        ui.push_id(i, |ui| {
+                            ui.separator();
+                            ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new(&entry.theme.visual.name).strong());
+                                if !entry.author.is_empty() {
+                                    ui.label(egui::RichText::new(format!("by {}", entry.author)).small().weak());
+                                }
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    if ui.small_button("⬇ Install").clicked() {
+                                        to_install = Some(entry.theme.clone());
+                                    }
+                                });
+                            });
+                            if !entry.description.is_empty() {
+                                ui.label(egui::RichText::new(&entry.description).small());
+                            }
+                            visualizers::draw_preview_spectrum(ui, &entry.theme.colors, entry.theme.visual.bar_opacity);
+                        });
+                    }
+                }
+            }
+
+            if let Some(theme) = to_install {
+                let status = match install_theme(state, theme) {
+                    Ok(name) => {
+                        tracing::info!("[Gallery] Installed '{}'", name);
+                        format!("Installed '{}'", name)
+                    }
+                    Err(e) => {
+                        tracing::error!("[Gallery] Install failed: {}", e);
+                        format!("Install failed: {}", e)
+                    }
+                };
+                set_theme_status(ui, status);
+            }
+        });
+}
+
+/// One row per known service: paste a token to connect, or disconnect.
+/// Tokens go straight to the OS keyring; the config only keeps the service id.
+fn ui_connected_accounts(ui: &mut Ui, state: &mut SharedState) {
+    egui::Grid::new("connected_accounts_grid")
+        .num_columns(2)
+        .spacing(egui::vec2(40.0, 12.0))
+        .min_col_width(150.0)
+        .show(ui, |ui| {
+            for &(service, label) in crate::secrets::KNOWN_SERVICES {
+                ui.label(label);
+                let connected = state.config.connected_accounts.iter().any(|s| s == service);
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if connected {
+                        if ui.button("Disconnect").clicked() {
+                            match crate::secrets::delete_token(service) {
+                                Ok(()) => {
+                                    state.config.connected_accounts.retain(|s| s != service);
+                                    state.clear_error("credentials");
+                                }
+                                Err(e) => state.report_error(e),
+                            }
+                        }
+                        ui.label(egui::RichText::new("✔ Connected").color(egui::Color32::LIGHT_GREEN));
+                        return;
+                    }
+
+                    // Token buffer lives in egui temp memory so it's never persisted
+                    let buf_id = egui::Id::new(("account_token", service));
+                    let mut token: String = ui.data_mut(|d| d.get_temp(buf_id).unwrap_or_default());
+
+                    let connect = ui.add_enabled(!token.trim().is_empty(), egui::Button::new("Connect"));
+                    ui.add(egui::TextEdit::singleline(&mut token).password(true).hint_text("API token").desired_width(160.0));
+
+                    if connect.clicked() {
+                        match crate::secrets::store_token(service, token.trim()) {
+                            Ok(()) => {
+                                state.config.connected_accounts.push(service.to_string());
+                                state.clear_error("credentials");
+                                token.clear();
+                            }
+                            Err(e) => state.report_error(e),
+                        }
+                    }
+                    ui.data_mut(|d| d.insert_temp(buf_id, token));
+                });
+                ui.end_row();
+            }
+        });
+
+    ui.label(egui::RichText::new("Tokens are kept in the system keychain, never in config.json.").small().weak());
+}
+
+fn set_theme_status(ui: &Ui, status: String) {
+    ui.data_mut(|d| d.insert_temp(egui::Id::new("theme_file_status"), status));
+}
+
 /// Method to capture key binding from user\
 pub fn key_binder_widget(
     ui: &mut egui::Ui,
@@ -1329,4 +4177,48 @@ pub fn key_binder_widget(
         // Request a repaint so the UI updates immediately to show the new key
         ui.ctx().request_repaint();
     }
-}
\ No newline at end of file
+}
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snap_window_position() {
+        // A 1080p monitor with a taller 1440p one to its right, tops not lined up
+        let monitors = [
+            (Rect::from_min_size(egui::pos2(0.0, 200.0), egui::vec2(1920.0, 1080.0)), 1.0),
+            (Rect::from_min_size(egui::pos2(1920.0, 0.0), egui::vec2(2560.0, 1440.0)), 1.0),
+        ];
+        let window = |x: f32, y: f32| Rect::from_min_size(egui::pos2(x, y), egui::vec2(400.0, 300.0));
+
+        // Left edge, y left alone
+        assert_eq!(snap_window_position(window(10.0, 500.0), &monitors, 16.0), egui::pos2(0.0, 500.0));
+        // Bottom-right corner, by the window's far sides
+        assert_eq!(snap_window_position(window(1515.0, 975.0), &monitors, 16.0), egui::pos2(1520.0, 980.0));
+        // The right monitor's own top and bottom, not the left one's
+        assert_eq!(snap_window_position(window(2500.0, 5.0), &monitors, 16.0), egui::pos2(2500.0, 0.0));
+        assert_eq!(snap_window_position(window(2500.0, 1130.0), &monitors, 16.0), egui::pos2(2500.0, 1140.0));
+        // Out of reach, or no monitors known
+        assert_eq!(snap_window_position(window(100.0, 300.0), &monitors, 16.0), egui::pos2(100.0, 300.0));
+        assert_eq!(snap_window_position(window(10.0, 500.0), &[], 16.0), egui::pos2(10.0, 500.0));
+
+        // The right monitor at 200%: its edges reach twice as far in pixels
+        let mixed = [monitors[0], (monitors[1].0, 2.0)];
+        assert_eq!(snap_window_position(window(2500.0, 25.0), &mixed, 16.0), egui::pos2(2500.0, 0.0));
+        assert_eq!(snap_window_position(window(25.0, 500.0), &mixed, 16.0), egui::pos2(25.0, 500.0));
+    }
+
+    #[test]
+    fn test_locked_resize_keeps_ratio() {
+        let size = egui::vec2(800.0, 400.0);
+        let close = |a: egui::Vec2, b: egui::Vec2| (a - b).length() < 0.01;
+        assert!(close(locked_resize(size, egui::vec2(80.0, 40.0), 2.0), egui::vec2(880.0, 440.0)));
+        // Off-ratio drags and off-ratio windows come out at the ratio
+        assert!(close(locked_resize(size, egui::vec2(0.0, 40.0), 2.0), egui::vec2(840.0, 420.0)));
+        assert!(close(locked_resize(egui::vec2(800.0, 800.0), egui::Vec2::ZERO, 2.0), egui::vec2(800.0, 400.0)));
+        // Never below the minimum
+        assert!(close(locked_resize(size, egui::vec2(-1000.0, -1000.0), 16.0), egui::vec2(960.0, 60.0)));
+    }
+}
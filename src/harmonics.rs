@@ -0,0 +1,133 @@
+//! Harmonic analysis for the inspector: given the spectral peaks the FFT
+//! thread found (`FFTProcessor::spectral_peaks`) and the hovered bar's
+//! frequency range, work out whether the bar holds a fundamental, list its
+//! harmonics and compute THD.
+//!
+//! The strongest peak inside the bar is the candidate. If a peak sits at
+//! half, a third or a quarter of its frequency and isn't much quieter, the
+//! candidate is taken to be that tone's harmonic instead. Harmonics are
+//! matched to the peak nearest each multiple; a missing one counts as
+//! silent. THD is the usual ratio of harmonic to fundamental amplitude:
+//!
+//!   THD = sqrt(A2² + A3² + …) / A1
+//!
+//! Good enough to spot a clipping guitar amp or a distorting speaker, not a
+//! substitute for an audio analyzer (no noise gating, harmonics above the
+//! 10th are ignored).
+
+use crate::fft_processor::SpectralPeak;
+
+/// Harmonics looked for, counting the fundamental as 1
+pub const MAX_HARMONIC: u32 = 10;
+
+/// How far a peak may sit from a multiple and still count (fraction of the
+/// multiple's frequency, but never less than `MIN_TOLERANCE_BINS` bins)
+const TOLERANCE: f32 = 0.02;
+const MIN_TOLERANCE_BINS: f32 = 1.5;
+
+/// A lower peak within this many dB of the candidate makes the candidate its
+/// harmonic rather than a fundamental
+const SUBHARMONIC_MARGIN_DB: f32 = 12.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Harmonic {
+    /// 2 for the second harmonic, and so on
+    pub number: u32,
+    pub freq_hz: f32,
+    pub db: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Analysis {
+    /// The bar's strongest peak is a fundamental
+    Fundamental {
+        peak: SpectralPeak,
+        harmonics: Vec<Harmonic>,
+        thd_percent: f32,
+    },
+    /// It's harmonic `number` of a lower tone at `fundamental_hz`
+    HarmonicOf { peak: SpectralPeak, number: u32, fundamental_hz: f32 },
+}
+
+/// Analyze the strongest peak between `low_hz` and `high_hz`. `bin_hz` is the
+/// FFT resolution, `max_hz` the highest frequency worth looking at (Nyquist).
+pub fn analyze(peaks: &[SpectralPeak], low_hz: f32, high_hz: f32, bin_hz: f32, max_hz: f32) -> Option<Analysis> {
+    let peak = *peaks
+        .iter()
+        .filter(|p| (low_hz..=high_hz).contains(&p.freq_hz))
+        .max_by(|a, b| a.db.total_cmp(&b.db))?;
+
+    // Below a few bins there's no telling tones apart
+    if peak.freq_hz < bin_hz * 3.0 {
+        return None;
+    }
+
+    for number in 2..=4 {
+        let lower = peak.freq_hz / number as f32;
+        if lower < bin_hz * 3.0 {
+            break;
+        }
+        if let Some(p) = nearest(peaks, lower, bin_hz).filter(|p| p.db >= peak.db - SUBHARMONIC_MARGIN_DB) {
+            return Some(Analysis::HarmonicOf { peak, number, fundamental_hz: p.freq_hz });
+        }
+    }
+
+    let harmonics: Vec<Harmonic> = (2..=MAX_HARMONIC)
+        .map(|number| (number, peak.freq_hz * number as f32))
+        .take_while(|&(_, freq)| freq < max_hz)
+        .filter_map(|(number, freq)| nearest(peaks, freq, bin_hz).map(|p| Harmonic { number, freq_hz: p.freq_hz, db: p.db }))
+        .collect();
+    Some(Analysis::Fundamental { peak, thd_percent: thd_percent(peak.db, &harmonics), harmonics })
+}
+
+/// THD (%) from the fundamental's and the harmonics' levels (dB)
+pub fn thd_percent(fundamental_db: f32, harmonics: &[Harmonic]) -> f32 {
+    let power: f32 = harmonics.iter().map(|h| 10f32.powf((h.db - fundamental_db) / 10.0)).sum();
+    100.0 * power.sqrt()
+}
+
+/// The peak closest to `freq` within tolerance
+fn nearest(peaks: &[SpectralPeak], freq: f32, bin_hz: f32) -> Option<SpectralPeak> {
+    let tolerance = (freq * TOLERANCE).max(bin_hz * MIN_TOLERANCE_BINS);
+    peaks
+        .iter()
+        .filter(|p| (p.freq_hz - freq).abs() <= tolerance)
+        .min_by(|a, b| (a.freq_hz - freq).abs().total_cmp(&(b.freq_hz - freq).abs()))
+        .copied()
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peak(freq_hz: f32, db: f32) -> SpectralPeak {
+        SpectralPeak { freq_hz, db }
+    }
+
+    #[test]
+    fn test_fundamental_with_harmonics() {
+        // 2nd at -40 dB and 3rd at -46 dB relative: 1% and 0.5% -> THD 1.118%
+        let peaks = [peak(440.5, 0.0), peak(881.0, -40.0), peak(1320.0, -46.02), peak(5000.0, -30.0)];
+        let Some(Analysis::Fundamental { peak, harmonics, thd_percent }) = analyze(&peaks, 430.0, 450.0, 11.7, 24000.0) else {
+            panic!("expected a fundamental");
+        };
+        assert_eq!(peak.freq_hz, 440.5);
+        assert_eq!(harmonics.iter().map(|h| h.number).collect::<Vec<_>>(), vec![2, 3]);
+        assert!((thd_percent - 1.118).abs() < 0.01, "thd {}", thd_percent);
+    }
+
+    #[test]
+    fn test_harmonic_of_a_lower_tone() {
+        let peaks = [peak(220.0, -6.0), peak(440.0, -10.0)];
+        assert_eq!(
+            analyze(&peaks, 430.0, 450.0, 11.7, 24000.0),
+            Some(Analysis::HarmonicOf { peak: peak(440.0, -10.0), number: 2, fundamental_hz: 220.0 })
+        );
+        // A much quieter lower peak doesn't count
+        let peaks = [peak(220.0, -40.0), peak(440.0, -10.0)];
+        assert!(matches!(analyze(&peaks, 430.0, 450.0, 11.7, 24000.0), Some(Analysis::Fundamental { .. })));
+        assert_eq!(analyze(&peaks, 600.0, 700.0, 11.7, 24000.0), None);
+    }
+}
@@ -0,0 +1,324 @@
+//! Optional localhost HTTP endpoint for scripts (AutoHotkey, Stream Deck
+//! plugins, shell one-liners):
+//!
+//!   GET  /api/status       FPS, device, profile, colors, mode, now playing
+//!   GET  /api/bars         current bar and peak levels (dB)
+//!   GET  /api/now-playing  track info, `null` when nothing plays
+//!   GET  /api/profiles     visual profile and color preset names
+//!   POST /api/profile      {"name": "..."} switches the visual profile
+//!   POST /api/colors       {"name": "..."} switches the color preset
+//!   POST /api/command      any command from `commands`, e.g.
+//!                          {"cmd": "toggle_ghost_mode"}
+//!
+//! It only listens on 127.0.0.1. Requests have to name localhost in their
+//! Host header (so a web page can't reach it through DNS rebinding), and a
+//! POST has to be `application/json` without an Origin header, which keeps
+//! browsers from sending one on a page's behalf.
+//!
+//! One request at a time on a plain std socket; it's meant for the odd
+//! script poll, not for streaming bars at 60 fps.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::commands::{self, color_preset_names, profile_list, Command};
+use crate::shared_state::SharedState;
+
+/// Largest request body we read
+const MAX_BODY: usize = 16 * 1024;
+
+/// How often the accept loop checks whether it should stop
+const ACCEPT_POLL: Duration = Duration::from_millis(100);
+
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct HttpApiSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for HttpApiSettings {
+    fn default() -> Self {
+        Self { enabled: false, port: 7878 }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+}
+
+/// Read one HTTP/1.x request
+pub fn read_request(reader: &mut impl BufRead) -> Result<Request, String> {
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|e| e.to_string())?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err("bad request line".to_string());
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        reader.read_line(&mut line).map_err(|e| e.to_string())?;
+        let trimmed = line.trim_end();
+        if trimmed.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = trimmed.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+        if headers.len() > 64 {
+            return Err("too many headers".to_string());
+        }
+    }
+
+    let mut request = Request { method, path, headers, body: Vec::new() };
+    let length: usize = request.header("Content-Length").and_then(|v| v.parse().ok()).unwrap_or(0);
+    if length > MAX_BODY {
+        return Err("body too large".to_string());
+    }
+    request.body.resize(length, 0);
+    reader.read_exact(&mut request.body).map_err(|e| e.to_string())?;
+    Ok(request)
+}
+
+/// Host header names this machine (DNS rebinding guard)
+fn local_host(request: &Request) -> bool {
+    let Some(host) = request.header("Host") else { return false };
+    let name = host.rsplit_once(':').map_or(host, |(name, _)| name);
+    matches!(name, "127.0.0.1" | "localhost" | "[::1]")
+}
+
+/// Status code and JSON body for `request`
+pub fn route(request: &Request, state: &mut SharedState) -> (u16, Value) {
+    if !local_host(request) {
+        return (403, json!({ "error": "requests must be addressed to localhost" }));
+    }
+    let path = request.path.split('?').next().unwrap_or("");
+
+    match (request.method.as_str(), path) {
+        ("GET", "/api/status") => (200, commands::status(state)),
+        ("GET", "/api/bars") => (200, json!({
+            "bars": state.visualization.bars,
+            "peaks": state.visualization.peaks,
+        })),
+        ("GET", "/api/now-playing") => (200, commands::now_playing(state)),
+        ("GET", "/api/profiles") => (200, json!({
+            "profiles": profile_list(state).into_iter().map(|p| p.name).collect::<Vec<_>>(),
+            "color_presets": color_preset_names(state),
+        })),
+        ("POST", "/api/profile" | "/api/colors" | "/api/command") => {
+            // A browser can't send JSON cross-origin without a preflight we never answer
+            let json_body = request.header("Content-Type").map_or(false, |t| t.starts_with("application/json"));
+            if !json_body || request.header("Origin").is_some() {
+                return (403, json!({ "error": "POST needs Content-Type: application/json and no Origin" }));
+            }
+            let command = if path == "/api/command" {
+                match serde_json::from_slice::<Command>(&request.body) {
+                    Ok(command) => command,
+                    Err(e) => return (400, json!({ "error": e.to_string() })),
+                }
+            } else {
+                let Some(name) = serde_json::from_slice::<Value>(&request.body)
+                    .ok()
+                    .and_then(|v| v.get("name")?.as_str().map(str::to_string))
+                else {
+                    return (400, json!({ "error": "expected {\"name\": \"...\"}" }));
+                };
+                if path == "/api/profile" { Command::SetProfile { name } } else { Command::SetColors { name } }
+            };
+            match commands::execute(&command, state) {
+                Ok(body) => {
+                    tracing::info!("[HTTP] {:?}", command);
+                    (200, body)
+                }
+                Err(e) => (404, json!({ "error": e.to_string() })),
+            }
+        }
+        (_, "/api/status" | "/api/bars" | "/api/now-playing" | "/api/profiles" | "/api/profile" | "/api/colors" | "/api/command") => {
+            (405, json!({ "error": "method not allowed" }))
+        }
+        _ => (404, json!({ "error": "not found" })),
+    }
+}
+
+fn reason(code: u16) -> &'static str {
+    match code {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Error",
+    }
+}
+
+fn handle_client(stream: TcpStream, shared_state: &Arc<Mutex<SharedState>>, ctx: &egui::Context) -> Result<(), String> {
+    stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT)).map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+
+    let (code, body) = match read_request(&mut reader) {
+        Ok(request) => {
+            let Ok(mut state) = shared_state.lock() else { return Err("state poisoned".to_string()) };
+            let before = (state.config.profile.clone(), state.config.window_locked);
+            let response = route(&request, &mut state);
+            if (&state.config.profile, state.config.window_locked) != (&before.0, before.1) {
+                ctx.request_repaint();
+            }
+            response
+        }
+        Err(e) => (400, json!({ "error": e })),
+    };
+
+    let body = body.to_string();
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        code,
+        reason(code),
+        body.len(),
+        body
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Running endpoint. Dropping it stops the listener.
+pub struct Server {
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+pub fn start(port: u16, shared_state: Arc<Mutex<SharedState>>, ctx: egui::Context) -> Server {
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread_stop = stop.clone();
+    let spawned = std::thread::Builder::new().name("bespec-http".to_string()).spawn(move || {
+        let addr = format!("127.0.0.1:{}", port);
+        // A restarted server may have to wait for the old one to let go of the port
+        let mut listener = None;
+        for _ in 0..20 {
+            match TcpListener::bind(&addr) {
+                Ok(l) => {
+                    listener = Some(l);
+                    break;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => std::thread::sleep(ACCEPT_POLL),
+                Err(e) => {
+                    tracing::error!("[HTTP] Couldn't listen on {}: {}", addr, e);
+                    return;
+                }
+            }
+        }
+        let Some(listener) = listener else {
+            tracing::error!("[HTTP] Port {} is in use", port);
+            return;
+        };
+        if let Err(e) = listener.set_nonblocking(true) {
+            tracing::error!("[HTTP] Socket setup failed: {}", e);
+            return;
+        }
+        tracing::info!("[HTTP] Serving the API on http://{}/api/status", addr);
+
+        while !thread_stop.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    if let Err(e) = handle_client(stream, &shared_state, &ctx) {
+                        tracing::debug!("[HTTP] Request failed: {}", e);
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => std::thread::sleep(ACCEPT_POLL),
+                Err(e) => {
+                    tracing::error!("[HTTP] Accept failed: {}", e);
+                    std::thread::sleep(ACCEPT_POLL);
+                }
+            }
+        }
+        tracing::info!("[HTTP] Stopped");
+    });
+    if let Err(e) = spawned {
+        tracing::error!("[HTTP] Failed to start server thread: {}", e);
+    }
+    Server { stop }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(raw: &str) -> Request {
+        read_request(&mut raw.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_read_request() {
+        let req = request("POST /api/colors HTTP/1.1\r\nHost: localhost:7878\r\ncontent-length: 15\r\n\r\n{\"name\": \"Sun\"}");
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.path, "/api/colors");
+        assert_eq!(req.header("Content-Length"), Some("15"));
+        assert_eq!(req.body, b"{\"name\": \"Sun\"}");
+        assert!(local_host(&req));
+
+        assert!(read_request(&mut "\r\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_routes_and_guards() {
+        let mut state = SharedState::with_config(crate::shared_state::AppConfig::default());
+        let name = crate::shared_state::VisualProfile::built_in()[0].name.clone();
+        let post = |path: &str, extra: &str, body: &str| {
+            request(&format!(
+                "POST {} HTTP/1.1\r\nHost: 127.0.0.1:7878\r\n{}Content-Length: {}\r\n\r\n{}",
+                path, extra, body.len(), body
+            ))
+        };
+
+        assert_eq!(route(&request("GET /api/status HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n"), &mut state).0, 200);
+        assert_eq!(route(&request("GET /api/status HTTP/1.1\r\nHost: evil.example\r\n\r\n"), &mut state).0, 403);
+        assert_eq!(route(&request("GET /nope HTTP/1.1\r\nHost: localhost\r\n\r\n"), &mut state).0, 404);
+        assert_eq!(route(&request("DELETE /api/bars HTTP/1.1\r\nHost: localhost\r\n\r\n"), &mut state).0, 405);
+
+        let body = format!("{{\"name\": \"{}\"}}", name);
+        // Form posts and cross-origin requests are refused
+        assert_eq!(route(&post("/api/profile", "", &body), &mut state).0, 403);
+        let json = "Content-Type: application/json\r\n";
+        assert_eq!(route(&post("/api/profile", &format!("{}Origin: https://x\r\n", json), &body), &mut state).0, 403);
+
+        assert_eq!(route(&post("/api/profile", json, "{\"name\": \"Nope\"}"), &mut state).0, 404);
+        let (code, status) = route(&post("/api/profile", json, &body), &mut state);
+        assert_eq!(code, 200);
+        assert_eq!(status["profile"], json!(name));
+
+        assert_eq!(route(&post("/api/command", json, "{\"cmd\": \"nope\"}"), &mut state).0, 400);
+        let (code, status) = route(&post("/api/command", json, "{\"cmd\": \"toggle_ghost_mode\"}"), &mut state);
+        assert_eq!(code, 200);
+        assert_eq!(status["ghost_mode"], json!(true));
+    }
+}
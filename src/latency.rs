@@ -0,0 +1,96 @@
+//! Capture-to-screen latency for the Performance tab, measured rather than
+//! worked out from the settings.
+//!
+//! Three parts add up to the total:
+//!   - the capture buffer: the oldest sample in a buffer is one buffer old
+//!     by the time the callback fires (size taken from the packets, so it's
+//!     what the driver delivers, not what was asked for)
+//!   - processing: callback to the bars being published by the FFT thread
+//!   - display: bars published to the frame that draws them
+//!
+//! The FFT window adds its own delay on top; that one is fixed and shown
+//! next to the FFT size.
+
+use std::time::Instant;
+
+/// How much of each new measurement goes into the running average
+const SMOOTHING: f32 = 0.05;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LatencyStats {
+    /// Frames per callback the driver actually delivers
+    pub buffer_frames: u32,
+    pub sample_rate: u32,
+    /// Callback → bars published, averaged
+    pub processing_ms: f32,
+    /// Bars published → drawn, averaged
+    pub display_ms: f32,
+    /// Publish time of the bars last drawn, so a frame that redraws the same
+    /// bars doesn't count as a slow one
+    last_drawn: Option<Instant>,
+}
+
+impl LatencyStats {
+    pub fn buffer_ms(&self) -> f32 {
+        if self.sample_rate == 0 {
+            return 0.0;
+        }
+        self.buffer_frames as f32 * 1000.0 / self.sample_rate as f32
+    }
+
+    pub fn total_ms(&self) -> f32 {
+        self.buffer_ms() + self.processing_ms + self.display_ms
+    }
+
+    /// FFT thread: bars from a packet of `frames` frames, delivered at
+    /// `callback_at`, were published at `published_at`
+    pub fn note_published(&mut self, frames: u32, sample_rate: u32, callback_at: Instant, published_at: Instant) {
+        self.buffer_frames = frames;
+        self.sample_rate = sample_rate;
+        let ms = published_at.saturating_duration_since(callback_at).as_secs_f32() * 1000.0;
+        self.processing_ms = smooth(self.processing_ms, ms);
+    }
+
+    /// GUI: a frame drawn at `drawn_at` shows the bars published at `published_at`
+    pub fn note_drawn(&mut self, published_at: Instant, drawn_at: Instant) {
+        if self.last_drawn == Some(published_at) {
+            return;
+        }
+        self.last_drawn = Some(published_at);
+        let ms = drawn_at.saturating_duration_since(published_at).as_secs_f32() * 1000.0;
+        self.display_ms = smooth(self.display_ms, ms);
+    }
+}
+
+/// Running average; the first measurement is taken as is
+fn smooth(old: f32, new: f32) -> f32 {
+    if old == 0.0 {
+        new
+    } else {
+        old + (new - old) * SMOOTHING
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_parts_add_up() {
+        let start = Instant::now();
+        let mut stats = LatencyStats::default();
+        stats.note_published(480, 48000, start, start + Duration::from_millis(2));
+        stats.note_drawn(start + Duration::from_millis(2), start + Duration::from_millis(10));
+        assert!((stats.buffer_ms() - 10.0).abs() < 1e-3);
+        assert!((stats.processing_ms - 2.0).abs() < 1e-3);
+        assert!((stats.display_ms - 8.0).abs() < 1e-3);
+        assert!((stats.total_ms() - 20.0).abs() < 1e-3);
+
+        // Redrawing the same bars later isn't more latency
+        stats.note_drawn(start + Duration::from_millis(2), start + Duration::from_millis(500));
+        assert!((stats.display_ms - 8.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,269 @@
+//! Latency self-test: play clicks through an output device and time how long
+//! each one takes to show up in the spectrum.
+//!
+//! This is the whole round trip, which the "Measured Latency" row can't see:
+//! the output buffer, the speaker-to-microphone path (or the loopback), the
+//! capture buffer, the FFT window and the bar smoothing. With a loopback
+//! input there's no air in the path; with a microphone, every 34 cm adds a
+//! millisecond.
+//!
+//! The GUI owns the player, like the signal generator. The click train notes
+//! when it hands each click to the output; the FFT thread feeds the level of
+//! every spectrum it publishes to `LatencyTest::feed`, which watches for the
+//! broadband jump a click makes over the background. The GUI also ends a
+//! test that outlasts `MAX_DURATION` (no spectra coming in) or whose output
+//! failed to play, so it never sits on "Listening…".
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Time between clicks; longer than any latency worth measuring, so every
+/// jump belongs to the last click
+const CLICK_INTERVAL: Duration = Duration::from_millis(600);
+/// Length of each click (a burst of noise, so it's broadband)
+const CLICK_LENGTH: Duration = Duration::from_millis(3);
+/// Level the clicks play at
+pub const CLICK_LEVEL_DB: f32 = -12.0;
+/// Clicks to time before reporting
+const CLICKS: usize = 8;
+/// Give up after this many clicks go by unheard
+const MAX_CLICKS: usize = 2 * CLICKS;
+/// ... or after this long, in case the clicks or the spectra never arrive
+const MAX_DURATION: Duration = CLICK_INTERVAL.saturating_mul(MAX_CLICKS as u32);
+/// How far above the background the average bar has to jump to count
+const ONSET_DB: f32 = 10.0;
+/// How fast the background follows the level between clicks
+const BACKGROUND_SMOOTHING: f32 = 0.1;
+
+/// When the last click went out, shared between the click train (audio
+/// callback) and the test (FFT thread) without locking
+struct ClickClock {
+    base: Instant,
+    /// Nanoseconds after `base`, 0 before the first click
+    last_click_ns: AtomicU64,
+}
+
+impl ClickClock {
+    fn note_click(&self, at: Instant) {
+        let ns = at.saturating_duration_since(self.base).as_nanos() as u64;
+        self.last_click_ns.store(ns.max(1), Ordering::Relaxed);
+    }
+
+    fn last_click(&self) -> Option<Instant> {
+        match self.last_click_ns.load(Ordering::Relaxed) {
+            0 => None,
+            ns => Some(self.base + Duration::from_nanos(ns)),
+        }
+    }
+}
+
+/// The test signal: a short noise burst every `CLICK_INTERVAL`, silence in
+/// between
+pub struct ClickTrain {
+    clock: Arc<ClickClock>,
+    position: u64,
+    noise: u32,
+}
+
+impl crate::audio_output::Source for ClickTrain {
+    fn next_sample(&mut self, sample_rate: f32) -> f32 {
+        let interval = (CLICK_INTERVAL.as_secs_f32() * sample_rate) as u64;
+        let length = (CLICK_LENGTH.as_secs_f32() * sample_rate) as u64;
+        let offset = self.position % interval.max(1);
+        self.position += 1;
+        if offset == 0 {
+            self.clock.note_click(Instant::now());
+        }
+        if offset >= length {
+            return 0.0;
+        }
+        // xorshift, fading out over the burst
+        self.noise ^= self.noise << 13;
+        self.noise ^= self.noise >> 17;
+        self.noise ^= self.noise << 5;
+        let sample = self.noise as f32 / u32::MAX as f32 * 2.0 - 1.0;
+        sample * (1.0 - offset as f32 / length as f32)
+    }
+}
+
+/// What a finished test found
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Summary {
+    pub median_ms: f32,
+    pub min_ms: f32,
+    pub max_ms: f32,
+    /// Clicks timed, out of the clicks played. None heard means the input
+    /// can't hear the output (wrong device, muted, or a scope-only view).
+    pub heard: usize,
+    pub played: usize,
+}
+
+#[derive(Default)]
+pub struct LatencyTest {
+    pub running: bool,
+    /// Where the clicks play, empty for the default output
+    pub output_device: String,
+    /// Last finished run, or why it couldn't run, kept until the next one starts
+    pub result: Option<Result<Summary, String>>,
+    clock: Option<Arc<ClickClock>>,
+    started: Option<Instant>,
+    background_db: Option<f32>,
+    /// Click currently being listened for, and whether it's been heard
+    listening: Option<(Instant, bool)>,
+    played: usize,
+    measurements: Vec<f32>,
+}
+
+impl LatencyTest {
+    pub fn start(&mut self) {
+        *self = Self {
+            running: true,
+            output_device: std::mem::take(&mut self.output_device),
+            clock: Some(Arc::new(ClickClock { base: Instant::now(), last_click_ns: AtomicU64::new(0) })),
+            started: Some(Instant::now()),
+            ..Default::default()
+        };
+    }
+
+    /// GUI, every frame: end a run that's taken longer than it ever should
+    pub fn check_timeout(&mut self, now: Instant) {
+        if self.running && self.started.is_some_and(|at| now.saturating_duration_since(at) > MAX_DURATION) {
+            tracing::warn!("[Latency] Gave up after {:?} ({} clicks heard)", MAX_DURATION, self.measurements.len());
+            self.finish();
+        }
+    }
+
+    /// The clicks couldn't be played
+    pub fn fail(&mut self, reason: String) {
+        self.result = Some(Err(reason));
+        self.cancel();
+    }
+
+    /// Stop without a result
+    pub fn cancel(&mut self) {
+        self.running = false;
+        self.clock = None;
+    }
+
+    /// The signal to play while the test runs
+    pub fn click_train(&self) -> Option<ClickTrain> {
+        let clock = self.clock.clone().filter(|_| self.running)?;
+        Some(ClickTrain { clock, position: 0, noise: 0x9E37_79B9 })
+    }
+
+    /// FFT thread: the spectrum published at `published_at`
+    pub fn feed(&mut self, bars: &[f32], published_at: Instant) {
+        let Some(clock) = self.clock.as_ref().filter(|_| self.running && !bars.is_empty()) else {
+            return;
+        };
+        let level = bars.iter().sum::<f32>() / bars.len() as f32;
+        let background = *self.background_db.get_or_insert(level);
+
+        if let Some(click_at) = clock.last_click() {
+            if self.listening.map(|(at, _)| at) != Some(click_at) {
+                self.listening = Some((click_at, false));
+                self.played += 1;
+            }
+        }
+        let jumped = level - background > ONSET_DB;
+        if let Some((click_at, heard)) = self.listening.as_mut() {
+            if jumped && !*heard {
+                *heard = true;
+                let ms = published_at.saturating_duration_since(*click_at).as_secs_f32() * 1000.0;
+                self.measurements.push(ms);
+            }
+        }
+        if !jumped {
+            self.background_db = Some(background + (level - background) * BACKGROUND_SMOOTHING);
+        }
+
+        if self.measurements.len() >= CLICKS || self.played > MAX_CLICKS {
+            self.finish();
+        }
+    }
+
+    fn finish(&mut self) {
+        self.result = Some(Ok(summarize(&mut self.measurements, self.played)));
+        self.cancel();
+    }
+
+    /// Clicks timed so far, for a progress label
+    pub fn progress(&self) -> (usize, usize) {
+        (self.measurements.len(), CLICKS)
+    }
+}
+
+fn summarize(measurements: &mut [f32], played: usize) -> Summary {
+    measurements.sort_by(f32::total_cmp);
+    Summary {
+        median_ms: measurements.get(measurements.len() / 2).copied().unwrap_or(0.0),
+        min_ms: measurements.first().copied().unwrap_or(0.0),
+        max_ms: measurements.last().copied().unwrap_or(0.0),
+        heard: measurements.len(),
+        played,
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jump_after_click_is_timed() {
+        let mut test = LatencyTest::default();
+        test.start();
+        let clock = test.clock.clone().unwrap();
+        let start = Instant::now();
+        let frame = Duration::from_millis(10);
+
+        // Quiet background, then each click shows up 40 ms after it went out
+        for click in 0..CLICKS {
+            let click_at = start + CLICK_INTERVAL * click as u32;
+            clock.note_click(click_at);
+            for i in 0..60 {
+                let at = click_at + frame * i;
+                let level = if (4..8).contains(&i) { -40.0 } else { -80.0 };
+                test.feed(&[level, level], at);
+            }
+        }
+        let result = test.result.unwrap().unwrap();
+        assert!(!test.running);
+        assert_eq!(result.heard, CLICKS);
+        assert!((result.median_ms - 40.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_gives_up_when_nothing_is_heard() {
+        let mut test = LatencyTest::default();
+        test.start();
+        let clock = test.clock.clone().unwrap();
+        let start = Instant::now();
+        for click in 0..=MAX_CLICKS {
+            let at = start + CLICK_INTERVAL * click as u32;
+            clock.note_click(at);
+            test.feed(&[-80.0], at);
+        }
+        assert!(!test.running);
+        assert_eq!(test.result.and_then(|r| r.ok()).map(|r| r.heard), Some(0));
+    }
+
+    #[test]
+    fn test_times_out_without_spectra_and_reports_output_failures() {
+        let mut test = LatencyTest::default();
+        test.start();
+        let start = Instant::now();
+        test.check_timeout(start + MAX_DURATION / 2);
+        assert!(test.running);
+        test.check_timeout(start + MAX_DURATION + Duration::from_secs(1));
+        assert!(!test.running);
+        assert_eq!(test.result.clone().and_then(|r| r.ok()).map(|r| (r.heard, r.played)), Some((0, 0)));
+
+        test.start();
+        test.fail("no such device".to_string());
+        assert!(!test.running);
+        assert_eq!(test.result, Some(Err("no such device".to_string())));
+    }
+}
@@ -0,0 +1,103 @@
+//! Peak / RMS / crest factor meter for the side strip, independent of the
+//! spectrum bars (no sensitivity, no noise floor: plain dBFS of the input).
+//!
+//! The FFT thread feeds every packet in, all channels interleaved, before
+//! the mono mix. RMS is an exponential average with a 300 ms time constant,
+//! the usual VU-like integration. The peak jumps to the loudest sample at
+//! once and falls back at a fixed rate, so a transient stays readable.
+//! Crest factor is the difference of the two, which is about 3 dB for a
+//! sine and climbs with how dynamic the signal is.
+
+use crate::shared_state::SILENCE_DB;
+
+/// RMS integration time (seconds)
+const RMS_TIME_CONSTANT: f32 = 0.3;
+/// How fast the peak reading falls back (dB per second)
+const PEAK_FALL_DB_PER_SEC: f32 = 20.0;
+
+/// Latest meter values (dBFS, crest in dB)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeterReading {
+    pub peak_db: f32,
+    pub rms_db: f32,
+    pub crest_db: f32,
+}
+
+impl Default for MeterReading {
+    fn default() -> Self {
+        Self { peak_db: SILENCE_DB, rms_db: SILENCE_DB, crest_db: 0.0 }
+    }
+}
+
+/// Lives in the FFT thread
+#[derive(Default)]
+pub struct LevelMeter {
+    mean_square: f32,
+    peak: f32,
+}
+
+impl LevelMeter {
+    /// Feed `samples` (any channel layout) covering `duration_secs` of audio
+    pub fn process(&mut self, samples: &[f32], duration_secs: f32) {
+        if samples.is_empty() || duration_secs <= 0.0 {
+            return;
+        }
+        let block_peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        let block_mean_square = samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32;
+
+        let blend = 1.0 - (-duration_secs / RMS_TIME_CONSTANT).exp();
+        self.mean_square += (block_mean_square - self.mean_square) * blend;
+        let fallen = self.peak * 10f32.powf(-PEAK_FALL_DB_PER_SEC * duration_secs / 20.0);
+        self.peak = block_peak.max(fallen);
+    }
+
+    pub fn reading(&self) -> MeterReading {
+        let to_db = |amplitude: f32| (20.0 * amplitude.max(1e-9).log10()).max(SILENCE_DB);
+        let peak_db = to_db(self.peak);
+        let rms_db = to_db(self.mean_square.sqrt());
+        let crest_db = if peak_db > SILENCE_DB { (peak_db - rms_db).max(0.0) } else { 0.0 };
+        MeterReading { peak_db, rms_db, crest_db }
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two seconds of `wave` in 10 ms packets
+    fn settle(meter: &mut LevelMeter, wave: impl Fn(f32) -> f32) {
+        let rate = 48_000.0;
+        for packet in 0..200 {
+            let samples: Vec<f32> = (0..480).map(|i| wave((packet * 480 + i) as f32 / rate)).collect();
+            meter.process(&samples, 0.01);
+        }
+    }
+
+    #[test]
+    fn test_sine_and_square() {
+        let mut meter = LevelMeter::default();
+        settle(&mut meter, |t| 0.5 * (t * 1000.0 * std::f32::consts::TAU).sin());
+        let reading = meter.reading();
+        assert!((reading.peak_db + 6.02).abs() < 0.1, "{:?}", reading);
+        assert!((reading.rms_db + 9.03).abs() < 0.1, "{:?}", reading);
+        assert!((reading.crest_db - 3.01).abs() < 0.1, "{:?}", reading);
+
+        let mut meter = LevelMeter::default();
+        settle(&mut meter, |t| if (t * 100.0).fract() < 0.5 { 1.0 } else { -1.0 });
+        let reading = meter.reading();
+        assert!(reading.peak_db.abs() < 0.01 && reading.crest_db < 0.1, "{:?}", reading);
+    }
+
+    #[test]
+    fn test_peak_falls_back_and_silence_reads_floor() {
+        let mut meter = LevelMeter::default();
+        assert_eq!(meter.reading(), MeterReading::default());
+
+        meter.process(&[1.0, 0.0], 0.01);
+        meter.process(&vec![0.0; 480], 0.5);
+        // 0.5 s at 20 dB/s
+        assert!((meter.reading().peak_db + 10.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,112 @@
+//! Recent log lines kept in memory for the Logs panel (Settings → Stats),
+//! so a bug report doesn't need BeSpec started from a terminal.
+//!
+//! `BufferLayer` sits next to the file writer in the `tracing` subscriber
+//! and sees the same events (after the `RUST_LOG` filter). Only the newest
+//! `CAPACITY` lines are kept; the full history is in the log files.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::sync::Mutex;
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Lines kept for the panel
+const CAPACITY: usize = 2000;
+
+static LINES: Mutex<VecDeque<LogLine>> = Mutex::new(VecDeque::new());
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogLine {
+    /// Local wall clock, "14:03:22"
+    pub time: String,
+    pub level: Level,
+    pub message: String,
+}
+
+impl std::fmt::Display for LogLine {
+    /// Same shape as a line in the log file
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {:>5} {}", self.time, self.level, self.message)
+    }
+}
+
+impl LogLine {
+    /// At least as severe as `level`, and containing `text` (any case)
+    pub fn matches(&self, level: Level, text: &str) -> bool {
+        self.level <= level && (text.is_empty() || self.message.to_lowercase().contains(&text.to_lowercase()))
+    }
+}
+
+/// Kept lines that pass `LogLine::matches`, oldest first
+pub fn lines(level: Level, text: &str) -> Vec<LogLine> {
+    match LINES.lock() {
+        Ok(lines) => lines.iter().filter(|l| l.matches(level, text)).cloned().collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn push(line: LogLine) {
+    if let Ok(mut lines) = LINES.lock() {
+        if lines.len() == CAPACITY {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+}
+
+/// `tracing` layer feeding the in-memory buffer
+pub struct BufferLayer {
+    offset: time::UtcOffset,
+}
+
+impl BufferLayer {
+    pub fn new(offset: time::UtcOffset) -> Self {
+        Self { offset }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for BufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut message = MessageVisitor(String::new());
+        event.record(&mut message);
+        let time = time::OffsetDateTime::now_utc()
+            .to_offset(self.offset)
+            .format(time::macros::format_description!("[hour]:[minute]:[second]"))
+            .unwrap_or_default();
+        push(LogLine { time, level: *event.metadata().level(), message: message.0 });
+    }
+}
+
+/// The event's message, then any other fields as `name=value`
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, "{:?}", value);
+        } else {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_and_text_filter() {
+        let line = |level, message: &str| LogLine { time: "12:00:00".to_string(), level, message: message.to_string() };
+        let warn = line(Level::WARN, "[Audio] Device lost");
+        assert!(warn.matches(Level::INFO, ""));
+        assert!(warn.matches(Level::WARN, "device"));
+        assert!(!warn.matches(Level::ERROR, ""));
+        assert!(!warn.matches(Level::TRACE, "midi"));
+        assert_eq!(warn.to_string(), "12:00:00  WARN [Audio] Device lost");
+    }
+}
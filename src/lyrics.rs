@@ -0,0 +1,225 @@
+//! Time-synced lyrics for the Now Playing overlay.
+//!
+//! Lyrics come from LRCLIB (https://lrclib.net), a free database of LRC
+//! files that needs no account. When the track changes the GUI asks for the
+//! `syncedLyrics` of the first search hit for artist + title; tracks with
+//! only plain lyrics, or none at all, simply don't get a pane.
+//!
+//! Lines follow the player's reported position where the backend has one
+//! (`MediaTrackInfo::position`). Otherwise they run on a local clock that
+//! starts when the track is first seen and stops while the player is
+//! paused; tracks joined midway drift by that much, and the offset setting
+//! shifts the whole track to make up for it.
+
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::media::MediaTrackInfo;
+use crate::net::NetError;
+
+const SEARCH_URL: &str = "https://lrclib.net/api/search";
+
+/// Lyrics don't change, keep them for the session
+const CACHE_TTL: Duration = Duration::from_secs(6 * 3600);
+
+/// One timed line of an LRC file
+#[derive(Clone, Debug, PartialEq)]
+pub struct LyricLine {
+    pub time: Duration,
+    pub text: String,
+}
+
+/// Parse LRC text (`[mm:ss.xx] words`). Lines may carry several time tags;
+/// metadata tags other than `[offset:]` and untimed lines are skipped.
+pub fn parse_lrc(lrc: &str) -> Vec<LyricLine> {
+    let mut offset_ms: i64 = 0;
+    let mut lines = Vec::new();
+
+    for raw in lrc.lines() {
+        let mut rest = raw.trim();
+        let mut times = Vec::new();
+        while let Some(tag) = rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+            let (inner, after) = tag;
+            if let Some(value) = inner.strip_prefix("offset:") {
+                offset_ms = value.trim().parse().unwrap_or(0);
+            } else if let Some(time) = parse_timestamp(inner) {
+                times.push(time);
+            }
+            rest = after;
+        }
+        let text = rest.trim();
+        for time in times {
+            lines.push(LyricLine { time, text: text.to_string() });
+        }
+    }
+
+    // A positive offset makes the lyrics appear sooner
+    for line in &mut lines {
+        let ms = line.time.as_millis() as i64 - offset_ms;
+        line.time = Duration::from_millis(ms.max(0) as u64);
+    }
+    lines.sort_by_key(|l| l.time);
+    lines
+}
+
+/// "01:23.45" / "01:23" / "01:23.456" -> duration
+fn parse_timestamp(tag: &str) -> Option<Duration> {
+    let (min, sec) = tag.split_once(':')?;
+    let min: u64 = min.trim().parse().ok()?;
+    let sec: f64 = sec.trim().parse().ok()?;
+    if !(0.0..60.0).contains(&sec) {
+        return None;
+    }
+    Some(Duration::from_secs(min * 60) + Duration::from_secs_f64(sec))
+}
+
+/// Index of the line being sung at `position`, `None` before the first one
+pub fn current_line(lines: &[LyricLine], position: Duration) -> Option<usize> {
+    lines.partition_point(|l| l.time <= position).checked_sub(1)
+}
+
+#[derive(Deserialize)]
+struct SearchHit {
+    #[serde(rename = "syncedLyrics")]
+    synced_lyrics: Option<String>,
+}
+
+/// Look up synced lyrics. `Ok(empty)` when LRCLIB has none.
+/// Blocking - call from a worker thread.
+pub fn fetch(artist: &str, title: &str) -> Result<Vec<LyricLine>, NetError> {
+    let hits: Vec<SearchHit> = crate::net::Request::get(SEARCH_URL)
+        .with_query("artist_name", artist)
+        .with_query("track_name", title)
+        .with_cache(CACHE_TTL)
+        .send_json()?;
+
+    let lines = hits
+        .into_iter()
+        .find_map(|h| h.synced_lyrics.filter(|s| !s.trim().is_empty()))
+        .map(|lrc| parse_lrc(&lrc))
+        .unwrap_or_default();
+    tracing::debug!("[Lyrics] {} synced lines found", lines.len());
+    Ok(lines)
+}
+
+/// Track position as far as we can tell without asking the player
+#[derive(Default)]
+pub struct PlaybackClock {
+    /// Played time before the last pause
+    elapsed: Duration,
+    playing_since: Option<Instant>,
+}
+
+impl PlaybackClock {
+    pub fn restart(&mut self, playing: bool, now: Instant) {
+        self.elapsed = Duration::ZERO;
+        self.playing_since = playing.then_some(now);
+    }
+
+    pub fn set_playing(&mut self, playing: bool, now: Instant) {
+        match (self.playing_since, playing) {
+            (None, true) => self.playing_since = Some(now),
+            (Some(since), false) => {
+                self.elapsed += now.saturating_duration_since(since);
+                self.playing_since = None;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn position(&self, now: Instant) -> Duration {
+        self.elapsed + self.playing_since.map_or(Duration::ZERO, |t| now.saturating_duration_since(t))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum LyricsStatus {
+    /// Not asked for yet (lyrics off, or no track)
+    #[default]
+    Idle,
+    Loading,
+    Ready(Vec<LyricLine>),
+    /// Nothing synced for this track (or the lookup failed)
+    Missing,
+}
+
+/// Lyrics of the current track, kept in `SharedState`
+#[derive(Default)]
+pub struct LyricsState {
+    /// (artist, title) the status belongs to
+    track: Option<(String, String)>,
+    pub status: LyricsStatus,
+    pub clock: PlaybackClock,
+}
+
+impl LyricsState {
+    /// Feed every media update. A new track restarts the clock and drops
+    /// the old lyrics.
+    pub fn track_update(&mut self, info: &MediaTrackInfo, now: Instant) {
+        let key = (info.artist.clone(), info.title.clone());
+        if self.track.as_ref() == Some(&key) {
+            self.clock.set_playing(info.is_playing, now);
+            return;
+        }
+        self.track = Some(key);
+        self.status = LyricsStatus::Idle;
+        self.clock.restart(info.is_playing, now);
+    }
+
+    /// (artist, title) to look up, if the current track hasn't been yet.
+    /// Marks it as loading.
+    pub fn start_fetch(&mut self) -> Option<(String, String)> {
+        if self.status != LyricsStatus::Idle {
+            return None;
+        }
+        let track = self.track.clone()?;
+        self.status = LyricsStatus::Loading;
+        Some(track)
+    }
+
+    /// Store a lookup result, unless the track changed while it ran
+    pub fn finish_fetch(&mut self, track: &(String, String), lines: Vec<LyricLine>) {
+        if self.track.as_ref() != Some(track) {
+            return;
+        }
+        self.status = if lines.is_empty() { LyricsStatus::Missing } else { LyricsStatus::Ready(lines) };
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lrc() {
+        let lrc = "[ar:Someone]\n[offset:+500]\n[00:12.00]First line\n[00:05.50][00:20.10] Chorus \nno tag here\n[01:02.3]\n";
+        let lines = parse_lrc(lrc);
+        let times: Vec<u128> = lines.iter().map(|l| l.time.as_millis()).collect();
+        assert_eq!(times, vec![5000, 11500, 19600, 61800]);
+        assert_eq!(lines[0].text, "Chorus");
+        assert_eq!(lines[1].text, "First line");
+        assert_eq!(lines[3].text, "");
+
+        assert_eq!(current_line(&lines, Duration::from_secs(1)), None);
+        assert_eq!(current_line(&lines, Duration::from_secs(12)), Some(1));
+        assert_eq!(current_line(&lines, Duration::from_secs(600)), Some(3));
+    }
+
+    #[test]
+    fn test_clock_stops_while_paused() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut clock = PlaybackClock::default();
+
+        clock.restart(true, at(0));
+        clock.set_playing(false, at(10));
+        assert_eq!(clock.position(at(30)), Duration::from_secs(10));
+        clock.set_playing(true, at(30));
+        assert_eq!(clock.position(at(35)), Duration::from_secs(15));
+        clock.restart(false, at(40));
+        assert_eq!(clock.position(at(50)), Duration::ZERO);
+    }
+}
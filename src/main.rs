@@ -1,17 +1,66 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console on Windows in release
 
+mod ab_compare;
 mod audio_capture;
 #[cfg(target_os = "linux")]
 mod audio_capture_pw;
 mod audio_device;
+mod audio_output;
+mod band_split;
+mod clip_indicator;
+mod commands;
+mod config_backup;
+mod crash_report;
+mod doctor;
+mod error;
 mod fft_config;
 mod fft_processor;
+mod frame_timing;
+mod gain_advisor;
+mod gamepad;
+mod latency;
+mod latency_test;
+mod log_buffer;
+mod lyrics;
 mod gui;
+mod harmonics;
+mod http_api;
 mod shared_state;
+mod snapshot;
+mod measurement;
+mod level_meter;
 mod media;
+mod midi_out;
+mod osc_out;
+mod net;
 mod presets;
+mod process_usage;
+mod preset_thumbnail;
+mod remote_control;
+mod render_watchdog;
+mod resampler;
+mod secrets;
+mod signal_gen;
+mod spectrogram;
+mod spectrum_log;
+mod stress_test;
+mod stream_deck;
+mod support_bundle;
+mod preset_gallery;
+mod paths;
+mod peek_away;
+#[cfg(feature = "plugins")]
+mod plugin_api;
+mod theme_file;
+mod theme_schedule;
+mod track_history;
+mod tuner;
 mod update_check;
+#[cfg(target_os = "windows")]
+mod wasapi_exclusive;
+mod window_integration;
 
+use std::collections::HashMap;
 use std::thread;
 use std::time::{Duration, Instant};
 use std::sync::{Arc, Mutex};
@@ -21,18 +70,20 @@ use std::fs;
 use time::macros::format_description;
 use tracing_subscriber::fmt::time::OffsetTime;
 use tracing_subscriber::EnvFilter;
+use tracing_subscriber::prelude::*;
 
 use crossbeam_channel::bounded;
-use directories::ProjectDirs;
 
 use crate::audio_device::AudioDeviceEnumerator;
+use crate::error::BeSpecError;
 use crate::fft_processor::{FFTProcessor, FFTConfig};
-use crate::shared_state::{SILENCE_DB, VisualMode};
+use crate::shared_state::{SILENCE_DB, VisualMode, VisualProfile, VisualizationData};
 use crate::update_check::check_for_updates;
 use shared_state::SharedState;
 use crate::gui::SpectrumApp;
-use crate::audio_capture::{AudioCaptureManager, AudioPacket};
+use crate::audio_capture::{AudioCaptureManager, AudioPacket, CaptureBackend, StreamOptions};
 use crate::fft_config::{FFTConfigManager, FIXED_FFT_SIZE};
+use crate::resampler::{Resampler, INTERNAL_SAMPLE_RATE};
 use crate::media::{PlatformMedia, MediaMonitor};
 
 // ========================================================================
@@ -47,6 +98,8 @@ const FRAME_TARGET_MS: Duration = Duration::from_millis(16);
 const SILENCE_EPSILON: f32 = 0.1;
 /// Grace period for threads to clean up during shutdown
 const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_millis(500);
+/// Daily log files kept in the log folder
+const MAX_LOG_FILES: usize = 7;
 
 // ========================================================================
 // AUDIO CAPTURE THREAD
@@ -67,6 +120,10 @@ fn start_audio_capture(
         tracing::info!("[Capture] Initializing audio device list...");
         if let Ok(devices) = AudioCaptureManager::list_devices() {
             if let Ok(mut state) = shared_state.lock() {
+                // Old configs stored display names, convert to stable ids
+                if state.config.migrate_selected_device(&devices) {
+                    state.config.save();
+                }
                 state.audio_devices = devices;
 
                 tracing::info!("[Capture] Found {} audio devices", state.audio_devices.len());
@@ -80,36 +137,42 @@ fn start_audio_capture(
             tracing::error!("[Capture] Failed to enumerate initial audio devices");
         }
 
-        // 2. Initial Device Selection
-        let initial_device = if let Ok(state) = shared_state.lock() {
-            state.config.selected_device.clone()
-        } else {
-            "Default".to_string()
-        };
-        tracing::info!("[Capture] Target device: {}", initial_device);
-
-        // 3. Create Audio Capture Manager
-        let mut capture = if initial_device == "Default" {
-            AudioCaptureManager::new().unwrap_or_else(|e|{
-                tracing::error!("[Capture] Critical: Failed to create default audio device: {}", e);
-                // Return a dummy/empty manager that does nothing rather than panicking
-                std::process::exit(1); 
-            })
-        } else {
-            AudioCaptureManager::with_device_id(&initial_device).unwrap_or_else(|_|{
-                tracing::info!("[Capture] Saved device not found, falling back to System Default ");
-                AudioCaptureManager::new().unwrap_or_else(|e|{
-                    tracing::error!("[Capture] Critical: Failed to init fallback default device: {}", e);
-                    std::process::exit(1);
-                })
-            })
-        };
+        // 2. Open the selected device. If there is none that works, BeSpec
+        //    keeps running without audio and tries again when asked
+        //    (Retry in the window, Refresh or a device pick in Settings).
+        let mut capture = loop {
+            let (device, options) = if let Ok(state) = shared_state.lock() {
+                (state.config.selected_device.clone(), state.config.stream_options())
+            } else {
+                ("Default".to_string(), Default::default())
+            };
+            tracing::info!("[Capture] Target device: {}", device);
 
-        // Start capturing
-        if let Err(e) = capture.start_capture() {
-            tracing::error!("[Capture] Failed to start capture: {}", e);
-            return;
-        }
+            match open_capture(&device, options) {
+                Ok(capture) => {
+                    if let Ok(mut state) = shared_state.lock() {
+                        state.no_audio_device = false;
+                        state.clear_error("audio");
+                    }
+                    break capture;
+                }
+                Err(err) => {
+                    tracing::warn!("[Capture] No usable audio device, running without audio");
+                    if let Ok(mut state) = shared_state.lock() {
+                        state.no_audio_device = true;
+                        state.report_error(err);
+                    } else {
+                        err.log();
+                    }
+                    if !wait_for_retry(&shutdown, &shared_state) {
+                        return;
+                    }
+                }
+            }
+        };
+        let info = capture.device_info();
+        crash_report::note("Audio backend", capture.backend_name());
+        crash_report::note("Audio format", format!("{} Hz, {} ch", info.default_sample_rate, info.channels));
         tracing::info!("[Capture] Audio capture thread started");
 
         // Keep receiving audio packets and forward them
@@ -120,8 +183,10 @@ fn start_audio_capture(
             let (needs_refresh, new_device_req) = {
                 if let Ok(mut state) = shared_state.try_lock() {
                     let refresh = state.refresh_devices_requested;
+                    // The stream options go along with a device change,
+                    // which reopens the stream
                     let change = if state.device_changed {
-                        Some(state.config.selected_device.clone())
+                        Some((state.config.selected_device.clone(), state.config.stream_options()))
                     } else {
                         None
                     };
@@ -154,8 +219,9 @@ fn start_audio_capture(
             }
             
             // === ACTION: DEVICE CHANGE ===
-            if let Some(new_name) = new_device_req {
+            if let Some((new_name, options)) = new_device_req {
                 tracing::info!("[Capture] Audio device change requested: {}", new_name);
+                capture.set_stream_options(options);
                 
                 let result = if new_name == "Default" {
                     if let Ok((_, info)) = AudioDeviceEnumerator::get_default_device() {
@@ -169,11 +235,36 @@ fn start_audio_capture(
                 };
 
                 match result {
-                    Ok(_) => tracing::info!("[Capture] Switched to new device: {}", new_name),
-                    Err(e) => tracing::error!("[Capture] Failed to switch device: {}", e),
+                    Ok(_) => {
+                        tracing::info!("[Capture] Switched to new device: {}", new_name);
+                        let info = capture.device_info();
+                        crash_report::note("Audio format", format!("{} Hz, {} ch", info.default_sample_rate, info.channels));
+                        if let Ok(mut state) = shared_state.lock() {
+                            state.clear_error("audio");
+                        }
+                    }
+                    Err(e) => {
+                        let err = e.on_device(&new_name);
+                        if let Ok(mut state) = shared_state.lock() {
+                            state.report_error(err);
+                        } else {
+                            err.log();
+                        }
+                    }
                 }
             }
             
+            // === FALLBACK NOTICE ===
+            // e.g. exclusive mode refused: capture runs, but not as asked
+            if let Some(notice) = capture.take_notice() {
+                let err = notice.on_device(capture.device_info().name);
+                if let Ok(mut state) = shared_state.lock() {
+                    state.report_error(err);
+                } else {
+                    err.log();
+                }
+            }
+
             // === PROCESS AUDIO ===
             match capture.receiver().recv_timeout(CAPTURE_RECV_TIMEOUT) {
                 Ok(packet) => {
@@ -182,7 +273,12 @@ fn start_audio_capture(
                 }
                 Err(crossbeam_channel::RecvTimeoutError::Timeout) => continue,
                 Err(crossbeam_channel::RecvTimeoutError::Disconnected) => {
-                    eprint!("[Capture] Stream disconnected unexpectedly");
+                    let err = BeSpecError::StreamDisconnected(capture.device_info().name);
+                    if let Ok(mut state) = shared_state.lock() {
+                        state.report_error(err);
+                    } else {
+                        err.log();
+                    }
                     break;
                 },
             }
@@ -196,6 +292,60 @@ fn start_audio_capture(
 }
 
 
+/// Open `device_id` ("Default" for the system default) and start capturing.
+/// A saved device that's gone falls back to the system default.
+fn open_capture(device_id: &str, options: StreamOptions) -> Result<Box<dyn CaptureBackend>, BeSpecError> {
+    let manager = if device_id == "Default" {
+        AudioCaptureManager::new().map_err(|e| e.on_device("Default"))?
+    } else {
+        AudioCaptureManager::with_device_id(device_id).or_else(|_| {
+            tracing::info!("[Capture] Saved device not found, falling back to System Default ");
+            AudioCaptureManager::new()
+        }).map_err(|e| e.on_device("Default"))?
+    };
+    let mut capture: Box<dyn CaptureBackend> = Box::new(manager);
+    tracing::info!("[Capture] Using {} backend", capture.backend_name());
+    capture.set_stream_options(options);
+
+    if let Err(e) = capture.start_capture() {
+        return Err(e.on_device(capture.device_info().name));
+    }
+    Ok(capture)
+}
+
+/// No-device state: wait until the user asks for another try (Retry, a
+/// device refresh or picking a device). Rescans the device list on a
+/// refresh. False when BeSpec is shutting down instead.
+fn wait_for_retry(shutdown: &AtomicBool, shared_state: &Mutex<SharedState>) -> bool {
+    while !shutdown.load(Ordering::Relaxed) {
+        thread::sleep(CAPTURE_RECV_TIMEOUT);
+        let (refresh, changed) = match shared_state.try_lock() {
+            Ok(mut state) => {
+                let flags = (state.refresh_devices_requested, state.device_changed);
+                state.refresh_devices_requested = false;
+                state.device_changed = false;
+                flags
+            }
+            Err(_) => continue,
+        };
+        if refresh {
+            tracing::info!("[Capture] Retry requested. Scanning hardware...");
+            match AudioCaptureManager::list_devices() {
+                Ok(devices) => {
+                    if let Ok(mut state) = shared_state.lock() {
+                        state.audio_devices = devices;
+                    }
+                }
+                Err(_) => tracing::error!("[Capture] Device scan failed"),
+            }
+        }
+        if refresh || changed {
+            return true;
+        }
+    }
+    false
+}
+
 // ========================================================================
 // FFT PROCESSING THREAD
 // ========================================================================
@@ -213,6 +363,34 @@ fn start_fft_processing(
         let mut frame_count= 0u64;
 
         let mut mono_buffer: Vec<f32> = Vec::with_capacity(4096);
+
+        // Optional fixed-rate path (see resampler.rs)
+        let mut resampler = Resampler::new(INTERNAL_SAMPLE_RATE);
+        let mut resampled_buffer: Vec<f32> = Vec::with_capacity(4096);
+        let mut advisor = gain_advisor::GainAdvisor::new();
+        // Peak / RMS strip (see level_meter.rs)
+        let mut level_meter = level_meter::LevelMeter::default();
+
+        // Pitch detection for the tuner overlay (see tuner.rs), only while shown
+        let mut tuner = tuner::Tuner::new();
+        let mut tuner_on = false;
+
+        // Bass/treble tint per sample for the scope trace (see band_split.rs)
+        let mut band_splitter = band_split::BandSplitter::new();
+        let mut tint_buffer: Vec<f32> = Vec::with_capacity(4096);
+        // Scope L/R traces
+        let mut left_buffer: Vec<f32> = Vec::with_capacity(4096);
+        let mut right_buffer: Vec<f32> = Vec::with_capacity(4096);
+
+        // Band triggers sent as MIDI (see midi_out.rs)
+        let mut midi = midi_out::MidiEngine::default();
+        // Spectral features sent as OSC (see osc_out.rs)
+        let mut osc = osc_out::OscSender::default();
+        // Bars written to CSV / JSONL while recording (see spectrum_log.rs)
+        let mut spectrum_logger = spectrum_log::SpectrumLogger::new(spectrum_log::SpectrumLogger::default_dir());
+
+        // One processor per extra window, keyed by `ExtraWindow::id`
+        let mut extra_processors: HashMap<u64, FFTProcessor> = HashMap::new();
         
         // === Performance Tracking ====
         let mut total_process_time = Duration::ZERO;
@@ -222,51 +400,87 @@ fn start_fft_processing(
         // === Decay State Tracking ====
         let mut is_decaying = false;
         let mut last_audio_time = Instant::now();
-        // Safety: Stop decaying after 5 seconds!
+        // Safety: Stop decaying 5 seconds after the hold ran out!
         const SILENCE_TIMEOUT: Duration = Duration::from_secs(5);
-        const JITTER_TOLERANCE:Duration = Duration::from_millis(50);
+        // Hold-before-clear from settings, refreshed with every packet
+        let mut silence_hold = Duration::from_millis(50);
         // =============================
 
+        // Renderer watchdog: is the GUI still drawing?
+        const WATCHDOG_INTERVAL: Duration = Duration::from_secs(1);
+        let mut last_watchdog_check = Instant::now();
+
         loop{
             if shutdown.load(Ordering::Relaxed) {
                 break;
             }
+            if last_watchdog_check.elapsed() >= WATCHDOG_INTERVAL {
+                last_watchdog_check = Instant::now();
+                check_renderer(&shared_state);
+            }
             // Timeout set for smooth 60fps decay
             match rx.recv_timeout(FRAME_TARGET_MS) {
                 Ok(packet) => {
                     frame_count += 1;
-                    is_decaying = true;
-                    last_audio_time = Instant::now();
+
+                    // Rate the FFT runs at: the device's, or the fixed internal
+                    // rate when resampling is on
+                    let (resample, silence_threshold_db, paused) = shared_state
+                        .lock()
+                        .map(|s| {
+                            silence_hold = Duration::from_secs_f32(s.config.silence_hold().max(0.0) / 1000.0);
+                            let paused = s.sleep.hidden && s.config.hidden_pause_fft;
+                            (s.config.resample_enabled(), s.config.silence_threshold(), paused)
+                        })
+                        .unwrap_or((false, SILENCE_DB, false));
+
+                    // Nobody can see the window and the user asked to save the work too
+                    if paused {
+                        continue;
+                    }
+
+                    // === SILENCE CHECK ===
+                    // Every packet goes through the FFT, quiet or not. The threshold
+                    // only decides what counts as signal: when packets stop coming,
+                    // the hold and the decay below run from the last one that cleared it.
+                    let peak = packet.samples.iter().fold(0.0f32, |a, &s| a.max(s.abs()));
+                    let peak_db = if peak > 0.0 { 20.0 * peak.log10() } else { SILENCE_DB };
+                    if peak_db > silence_threshold_db {
+                        is_decaying = true;
+                        last_audio_time = Instant::now();
+                    }
+                    let fft_rate = if resample { resampler.target_rate() } else { packet.sample_rate };
 
                     // ====== Initialization: First packet tells us the sample rate
                     if processor.is_none() || fft_config.is_none() {
                         tracing::info!(
-                            "[FFT] First audio packet received at {} Hz",
-                            packet.sample_rate
+                            "[FFT] First audio packet received at {} Hz (FFT at {} Hz)",
+                            packet.sample_rate, fft_rate
                         );
                     
                         // Initialize FFT config with ACTUAL device sample rates!
-                        let new_fft_config = FFTConfigManager::new(packet.sample_rate);
+                        let new_fft_config = FFTConfigManager::new(fft_rate);
 
                         // Get initial settings from shared state
                         let config: FFTConfig = if let Ok(state) = shared_state.lock() {
                             let target_bars = state.lod_bar_limit.unwrap_or(state.config.profile.num_bars);
                             FFTConfig {
                                 fft_size: FIXED_FFT_SIZE,
-                                sample_rate: packet.sample_rate,
+                                sample_rate: fft_rate,
                                 num_bars: target_bars,
-                                sensitivity: state.config.profile.sensitivity,
+                                sensitivity: state.config.sensitivity(),
                                 attack_time_ms: state.config.profile.attack_time_ms,
                                 release_time_ms: state.config.profile.release_time_ms,
                                 peak_hold_time_ms: state.config.profile.peak_hold_time_ms,
                                 peak_release_time_ms: state.config.profile.peak_release_time_ms,
                                 aggregation_mode: state.config.profile.aggregation_mode,
+                                peak_gravity: state.config.profile.gravity_peaks(),
                             }
                         } else {
                             // Provide safe fallback defaults if mutex is poisoned
                             FFTConfig {
                                 fft_size: FIXED_FFT_SIZE,
-                                sample_rate: packet.sample_rate,
+                                sample_rate: fft_rate,
                                 num_bars: 64,
                                 sensitivity: 1.0,
                                 attack_time_ms: 10.0,
@@ -274,6 +488,7 @@ fn start_fft_processing(
                                 peak_hold_time_ms: 50.0,
                                 peak_release_time_ms: 200.0,
                                 aggregation_mode: crate::shared_state::AggregationMode::Peak,
+                                peak_gravity: false,
                             }
                         };
 
@@ -305,16 +520,18 @@ fn start_fft_processing(
                     };
 
                     // ==== CRITICAL: Handle sample rate changes =====
-                    // If device sample rate changed, update FFT config
-                    if packet.sample_rate != fft_config.get_sample_rate() {
+                    // If device sample rate changed, update FFT config.
+                    // With resampling on, fft_rate is fixed so this only fires
+                    // when the option itself is toggled.
+                    if fft_rate != fft_config.get_sample_rate() {
                         tracing::info!(
                             "[FFT] Sample rate changed: {} Hz → {} Hz",
                             fft_config.get_sample_rate(),
-                            packet.sample_rate
+                            fft_rate
                         );
 
                         // Update FFT config (rebuild always follows in this branch)
-                        let _ = fft_config.update_sample_rate(packet.sample_rate);
+                        let _ = fft_config.update_sample_rate(fft_rate);
 
                         
                         // Rebuild FFT processor with new FFT size
@@ -330,12 +547,13 @@ fn start_fft_processing(
                                 fft_size: FIXED_FFT_SIZE, 
                                 sample_rate: info.sample_rate,
                                 num_bars: target_bars,
-                                sensitivity: state.config.profile.sensitivity,
+                                sensitivity: state.config.sensitivity(),
                                 attack_time_ms: state.config.profile.attack_time_ms,
                                 release_time_ms: state.config.profile.release_time_ms,
                                 peak_hold_time_ms: state.config.profile.peak_hold_time_ms,
                                 peak_release_time_ms: state.config.profile.peak_release_time_ms,
                                 aggregation_mode: state.config.profile.aggregation_mode,
+                                peak_gravity: state.config.profile.gravity_peaks(),
                             }
                         } else {
                              // Safe fallback
@@ -346,18 +564,51 @@ fn start_fft_processing(
                              }
                         };
 
-                        *processor = FFTProcessor::new(new_config);
+                        let mut new_processor = FFTProcessor::new(new_config);
+                        new_processor.carry_levels_from(processor);
+                        *processor = new_processor;
                          
                     }
 
+                    // Before the mono mix, which would hide a clip on one channel
+                    let packet_peak = packet.peak();
+                    level_meter.process(&packet.samples, packet.duration_secs());
+
                     // Convert to mono (FFT expects single channel)
                     //let mono = packet.to_mono();
                     packet.to_mono_with_buffer(&mut mono_buffer);
+                    packet.split_stereo_with_buffers(&mut left_buffer, &mut right_buffer);
+
+                    if resample && packet.sample_rate != fft_rate {
+                        resampler.process(&mono_buffer, packet.sample_rate, &mut resampled_buffer);
+                        std::mem::swap(&mut mono_buffer, &mut resampled_buffer);
+                    }
                     
-                    let mode = if let Ok(state) = shared_state.lock() {
-                        state.config.profile.visual_mode
+                    // Gain advisor sees the raw level, before sensitivity
+                    advisor.process(&mono_buffer, fft_rate);
+                    band_splitter.process(&mono_buffer, fft_rate, &mut tint_buffer);
+                    if tuner_on {
+                        tuner.process(&mono_buffer, fft_rate, Instant::now());
+                    }
+
+                    let (mode, wants_history, wants_waveform) = if let Ok(mut state) = shared_state.lock() {
+                        state.gain_hint = if state.config.gain_advisor_enabled {
+                            let sensitivity_db = 20.0 * state.config.sensitivity().max(1e-6).log10();
+                            advisor.hint(sensitivity_db)
+                        } else {
+                            None
+                        };
+                        state.tuner_reading = if tuner_on { tuner.reading(Instant::now()) } else { None };
+                        tuner_on = state.config.tuner.enabled;
+                        let clip_settings = state.config.clip;
+                        state.clip.note(packet_peak, &clip_settings, Instant::now());
+                        state.meter = level_meter.reading();
+                        // The split view's second mode may need the FFT while the main one is the scope
+                        let profile = &state.config.profile;
+                        let mode = profile.shown_modes().find(|&m| m != VisualMode::Oscilloscope).unwrap_or(VisualMode::Oscilloscope);
+                        (mode, profile.shown_modes().any(VisualMode::uses_history), profile.shown_modes().any(VisualMode::uses_waveform))
                     } else {
-                        VisualMode::SolidBars
+                        (VisualMode::SolidBars, false, false)
                     };
 
                     match mode {
@@ -365,9 +616,25 @@ fn start_fft_processing(
                             // === SCOPE MODE: BYPASS FFT ===
                             // Just normalize/copy raw samples directly to visualization
                             // We might want to decimate or window here if the packet is huge.
+                            // Bars aren't drawn here, but let them fall with the release
+                            // time so switching back doesn't start from a wiped spectrum
+                            let (bars, peaks) = processor.process(&[]);
+                            let mut spectrum_log_request = None;
                             if let Ok(mut state) = shared_state.lock() {
-                                state.visualization.waveform = mono_buffer.clone();
-                                state.visualization.bars.fill(SILENCE_DB);
+                                run_midi(&mut midi, &mut state, bars, fft_config.get_sample_rate());
+                                spectrum_log_request = Some(spectrum_log_request_for(&spectrum_logger, &mut state));
+                                state.visualization.set_waveform(&mono_buffer, &tint_buffer, Instant::now());
+                                state.visualization.set_stereo(&left_buffer, &right_buffer);
+                                state.visualization.set_bars(bars, peaks);
+
+                                // Auto sleep: wake the GUI if the trace would visibly move
+                                let peak = mono_buffer.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+                                if 20.0 * peak.max(1e-10).log10() > state.config.noise_floor() {
+                                    state.sleep.note_signal(Instant::now());
+                                }
+                            }
+                            if let Some(request) = spectrum_log_request {
+                                write_spectrum_log(&mut spectrum_logger, request, bars, fft_config.get_sample_rate());
                             }
                         }
                         _ => {
@@ -375,7 +642,8 @@ fn start_fft_processing(
                             let process_start = Instant::now();
 
                             // B. Heavy Math (FFT)
-                            let (bars, peaks) = processor.process(&mono_buffer);
+                            processor.process(&mono_buffer);
+                            let (bars, peaks) = processor.output();
 
                             // C. Stop Timer
                             let process_time = process_start.elapsed();
@@ -386,12 +654,54 @@ fn start_fft_processing(
                             max_process_time = max_process_time.max(process_time);
 
                             // E. Update shared state
+                            let mut spectrum_log_request = None;
                             let pending_config_update = {
+                                let lock_start = Instant::now();
                                 if let Ok(mut state) = shared_state.lock() {
+                                    let (fft_time, aggregation_time) = processor.stage_times();
+                                    state.performance.frame_timings.note_dsp(lock_start.elapsed(), fft_time, aggregation_time);
+
+                                    // Auto sleep: anything above the floor is something to draw
+                                    let floor = state.config.noise_floor();
+                                    if bars.iter().any(|&b| b > floor) {
+                                        state.sleep.note_signal(Instant::now());
+                                    }
+
+                                    if wants_history {
+                                        Arc::make_mut(&mut state.visualization.history).push(bars, Instant::now());
+                                    }
+                                    if let Some(capture) = state.snapshot_capture.as_mut() {
+                                        if let Some(snapshot) = capture.feed(bars, Instant::now()) {
+                                            tracing::info!("[FFT] Reference snapshot taken ({} frames)", snapshot.frames);
+                                            state.snapshot_capture = None;
+                                            state.reference_snapshot = Some(snapshot);
+                                        }
+                                    }
+                                    if state.measurement.running {
+                                        let (smoothing, rate, n) = (state.config.measurement.smoothing, fft_config.get_sample_rate(), bars.len());
+                                        state.measurement.feed(bars, smoothing, |i| {
+                                            FFTProcessor::calculate_bar_frequency(i, n, rate, FIXED_FFT_SIZE)
+                                        });
+                                    }
+                                    run_midi(&mut midi, &mut state, bars, fft_config.get_sample_rate());
+                                    osc.process(&state.config.osc_out, &processor.spectral_features(), Instant::now());
+                                    spectrum_log_request = Some(spectrum_log_request_for(&spectrum_logger, &mut state));
+
                                     // Update  visualization  data
-                                    state.visualization.bars = bars;
-                                    state.visualization.peaks = peaks;
+                                    state.visualization.set_bars(bars, peaks);
+                                    state.visualization.spectral_peaks.clear();
+                                    state.visualization.spectral_peaks.extend_from_slice(processor.spectral_peaks());
+                                    if wants_waveform {
+                                        // Scripts, plugins, MilkDrop and a split-view scope get the waveform as well as the bars
+                                        state.visualization.set_waveform(&mono_buffer, &tint_buffer, Instant::now());
+                                        state.visualization.set_stereo(&left_buffer, &right_buffer);
+                                    }
                                     state.visualization.timestamp = Instant::now();
+                                    let frames = packet.samples.len() / packet.channels.max(1) as usize;
+                                    let published = state.visualization.timestamp;
+                                    state.performance.latency.note_published(frames as u32, packet.sample_rate, packet.timestamp, published);
+                                    let shared = &mut *state;
+                                    shared.latency_test.feed(&shared.visualization.bars, published);
 
                                     // Update performance stats
                                     state.performance.frame_count = frame_count;
@@ -399,6 +709,7 @@ fn start_fft_processing(
                                     state.performance.fft_min_time = min_process_time;
                                     state.performance.fft_max_time = max_process_time;
                                     state.performance.fft_info = fft_config.info();
+                                    state.performance.spectral = processor.spectral_features();
 
                                     // Check if any config parameters changed
                                     // 1. Check for changes that require a rebuild
@@ -406,19 +717,20 @@ fn start_fft_processing(
                                     let needs_update = target_bars != state.visualization.bars.len();
 
                                     let config_differs = |current: &FFTConfig| -> bool {
-                                        state.config.profile.sensitivity != current.sensitivity ||
+                                        state.config.sensitivity() != current.sensitivity ||
                                         state.config.profile.attack_time_ms != current.attack_time_ms ||
                                         state.config.profile.release_time_ms != current.release_time_ms ||
                                         state.config.profile.peak_hold_time_ms != current.peak_hold_time_ms ||
                                         state.config.profile.peak_release_time_ms != current.peak_release_time_ms ||
-                                        state.config.profile.aggregation_mode != current.aggregation_mode
+                                        state.config.profile.aggregation_mode != current.aggregation_mode ||
+                                        state.config.profile.gravity_peaks() != current.peak_gravity
                                     };
                                                         
                                     
                                     if needs_update {
-                                        //Major change - needs FFT rebuild
+                                        // Bar count change - resized in place, old bars cross-fade out
                                         tracing::debug!(
-                                            "[FFT] Config change requires rebuild (bar count: {} → {})",
+                                            "[FFT] Bar count change: {} → {}",
                                             state.visualization.bars.len(),
                                             state.config.profile.num_bars
                                         );
@@ -427,12 +739,13 @@ fn start_fft_processing(
                                             fft_size: FIXED_FFT_SIZE,
                                             sample_rate: fft_config.get_sample_rate(),
                                             num_bars: target_bars,
-                                            sensitivity: state.config.profile.sensitivity,
+                                            sensitivity: state.config.sensitivity(),
                                             attack_time_ms: state.config.profile.attack_time_ms,
                                             release_time_ms: state.config.profile.release_time_ms,
                                             peak_hold_time_ms: state.config.profile.peak_hold_time_ms,
                                             peak_release_time_ms: state.config.profile.peak_release_time_ms,
                                             aggregation_mode: state.config.profile.aggregation_mode,
+                                            peak_gravity: state.config.profile.gravity_peaks(),
                                         })
                                     } else {
                                         // Check for minor config changes that don't require a rebuild
@@ -454,12 +767,13 @@ fn start_fft_processing(
                                                 fft_size: FIXED_FFT_SIZE,
                                                 sample_rate: fft_config.get_sample_rate(),
                                                 num_bars: target_bars,
-                                                sensitivity: state.config.profile.sensitivity,
+                                                sensitivity: state.config.sensitivity(),
                                                 attack_time_ms: state.config.profile.attack_time_ms,
                                                 release_time_ms: state.config.profile.release_time_ms,
                                                 peak_hold_time_ms: state.config.profile.peak_hold_time_ms,
                                                 peak_release_time_ms: state.config.profile.peak_release_time_ms,
                                                 aggregation_mode: state.config.profile.aggregation_mode,
+                                                peak_gravity: state.config.profile.gravity_peaks(),
                                             })
                                         } else {
                                             None
@@ -469,18 +783,19 @@ fn start_fft_processing(
                                     None // Lock failed, no config update
                                 }
                             };
+                            if let Some(request) = spectrum_log_request {
+                                write_spectrum_log(&mut spectrum_logger, request, bars, fft_config.get_sample_rate());
+                            }
                             // Apply confiig update if needed
                             if let Some(new_config) = pending_config_update {
-                                if new_config.num_bars != processor.get_config().num_bars {
-                                    tracing::debug!("[FFT] Recreating processor for new bar count: {}", new_config.num_bars);
-                                    *processor = FFTProcessor::new(new_config);
-                                } else {
-                                    tracing::debug!("[FFT] Updating processor config");
-                                    processor.update_config(new_config);
-                                }
+                                tracing::debug!("[FFT] Updating processor config");
+                                processor.update_config(new_config);
                             }
                         }
                     }
+
+                    // Extra visualizer windows, each with its own profile
+                    process_extra_windows(&shared_state, &mut extra_processors, &mono_buffer, &tint_buffer, (&left_buffer, &right_buffer), fft_rate);
                 }
                 
                 Err(crossbeam_channel::RecvTimeoutError::Timeout) => {
@@ -490,8 +805,9 @@ fn start_fft_processing(
                     // If we timeout at 16ms, it doesn't necessarily mean "Silence".
                     // If we feed silence immediately, we get visual stutter (drop to 0 and back).
                     //
-                    // We only switch to "Decay Mode" if the gap exceeds our tolerance.
-                    if last_audio_time.elapsed() < JITTER_TOLERANCE {
+                    // We only switch to "Decay Mode" if the gap exceeds the
+                    // configured hold (long holds keep quiet passages on screen).
+                    if last_audio_time.elapsed() < silence_hold {
                         // Packet is just late. Hold the current visual state.
                         // This 'continue' restarts the loop and waits another 16ms 
                         // (or until the packet actually arrives).
@@ -503,7 +819,7 @@ fn start_fft_processing(
                         if let Some(proc) = processor.as_mut(){
                             
                             // 1. Safety Check: don't run forever
-                            if last_audio_time.elapsed() > SILENCE_TIMEOUT {
+                            if last_audio_time.elapsed() > silence_hold + SILENCE_TIMEOUT {
                                 is_decaying = false;
                                 tracing::debug!("[FFT] Silence timeout reached, stopping updates.");
                                 continue;
@@ -527,10 +843,11 @@ fn start_fft_processing(
 
                             // 4. Update GUI
                             if let Ok(mut state) = shared_state.lock(){
-                                state.visualization.bars = bars;
-                                state.visualization.peaks = peaks;
+                                state.visualization.set_bars(bars, peaks);
                             }
 
+                            process_extra_windows(&shared_state, &mut extra_processors, &[], &[], (&[], &[]), proc.get_config().sample_rate);
+
                         }
                     }
 
@@ -563,6 +880,184 @@ fn start_fft_processing(
     });
 }
 
+/// Renderer watchdog check, run from the FFT loop about once a second.
+/// See `render_watchdog` for why a repaint request is all we can do.
+fn check_renderer(shared_state: &Arc<Mutex<SharedState>>) {
+    use render_watchdog::WatchdogAction;
+
+    let Ok(mut state) = shared_state.lock() else { return };
+    match state.render_watchdog.check(Instant::now()) {
+        WatchdogAction::None => {}
+        WatchdogAction::Nudge { first } => {
+            if first {
+                tracing::warn!("[FFT] GUI hasn't drawn a frame in 5 s, asking it to repaint");
+            }
+            if let Some(ctx) = &state.sleep.repaint_ctx {
+                ctx.request_repaint();
+            }
+        }
+        WatchdogAction::GiveUp => {
+            tracing::error!("[FFT] GUI still not drawing after 30 s. The renderer may have lost its GPU device; restart BeSpec (and check the graphics driver)");
+        }
+    }
+}
+
+/// MIDI band triggers for this frame's bars, run from the FFT loop with
+/// the state locked. Copies the trigger lights and open port back for the
+/// settings page.
+fn run_midi(midi: &mut midi_out::MidiEngine, state: &mut SharedState, bars: &[f32], sample_rate: u32) {
+    midi.process(&state.config.midi, bars, sample_rate, FIXED_FFT_SIZE);
+    if state.midi_held != midi.held() {
+        state.midi_held = midi.held().to_vec();
+    }
+    if state.midi_port_open.as_deref() != midi.connected_port() {
+        state.midi_port_open = midi.connected_port().map(str::to_string);
+    }
+}
+
+/// With the state locked: publish the logger's file and frame count (and
+/// drop the recording flag on an error so the Stats tab shows it stopped),
+/// then take the settings `write_spectrum_log` needs.
+fn spectrum_log_request_for(logger: &spectrum_log::SpectrumLogger, state: &mut SharedState) -> (bool, spectrum_log::SpectrumLogSettings) {
+    if state.spectrum_log_status != *logger.status() {
+        state.spectrum_log_status = logger.status().clone();
+        if state.spectrum_log_status.error.is_some() {
+            state.spectrum_log_running = false;
+        }
+    }
+    (state.spectrum_log_running, state.config.spectrum_log)
+}
+
+/// Write `bars` to the spectrum log. Runs after the state lock is dropped,
+/// so a slow disk never holds up the GUI; the status it leaves is published
+/// by the next `spectrum_log_request_for`.
+fn write_spectrum_log(
+    logger: &mut spectrum_log::SpectrumLogger,
+    (running, settings): (bool, spectrum_log::SpectrumLogSettings),
+    bars: &[f32],
+    sample_rate: u32,
+) {
+    let count = bars.len();
+    logger.process(running, &settings, bars, |i| {
+        FFTProcessor::calculate_bar_frequency(i, count, sample_rate, FIXED_FFT_SIZE)
+    });
+}
+
+/// Pick the window geometry saved for the monitors connected right now, so
+/// docking or undocking a laptop doesn't leave the window off-screen.
+/// An unknown setup keeps the last geometry unless its position is on no
+/// monitor at all, in which case the OS gets to place the window.
+fn restore_monitor_layout(config: &mut shared_state::AppConfig) {
+    let monitors = window_integration::monitors();
+    let Some(key) = window_integration::layout_key(&monitors) else { return };
+
+    if config.restore_layout(&key) {
+        tracing::info!("[Main] Restored window layout for monitors {}", key);
+        return;
+    }
+    // Saved positions are logical points and the rects physical pixels;
+    // with scaling above 100% this errs on the side of keeping the position
+    for position in [&mut config.window_position, &mut config.mini_window_position] {
+        if let Some([x, y]) = *position {
+            if !monitors.iter().any(|m| m.contains(x as i32, y as i32)) {
+                tracing::info!("[Main] Saved position {:?} is off-screen on monitors {}, resetting", [x, y], key);
+                *position = None;
+            }
+        }
+    }
+}
+
+/// FFT settings for drawing `profile` at `sample_rate`
+fn fft_config_for(profile: &VisualProfile, sample_rate: u32) -> FFTConfig {
+    FFTConfig {
+        fft_size: FIXED_FFT_SIZE,
+        sample_rate,
+        num_bars: profile.num_bars,
+        sensitivity: profile.sensitivity,
+        attack_time_ms: profile.attack_time_ms,
+        release_time_ms: profile.release_time_ms,
+        peak_hold_time_ms: profile.peak_hold_time_ms,
+        peak_release_time_ms: profile.peak_release_time_ms,
+        aggregation_mode: profile.aggregation_mode,
+        peak_gravity: profile.gravity_peaks(),
+    }
+}
+
+/// Run one block of mono `samples` through every extra window's profile.
+/// An empty block feeds silence (decay) and leaves scope windows alone.
+/// `stereo` is the block's L/R channels, for the scope's extra traces.
+///
+/// All windows share the selected capture device; the processors only differ
+/// in bar count and dynamics.
+fn process_extra_windows(
+    shared_state: &Arc<Mutex<SharedState>>,
+    processors: &mut HashMap<u64, FFTProcessor>,
+    samples: &[f32],
+    tint: &[f32],
+    stereo: (&[f32], &[f32]),
+    sample_rate: u32,
+) {
+    let (windows, device_gain): (Vec<(u64, VisualProfile)>, f32) = match shared_state.lock() {
+        Ok(state) => (
+            state.config.extra_windows.iter().map(|w| (w.id, w.profile.clone())).collect(),
+            10f32.powf(state.config.device_offsets().gain_db / 20.0),
+        ),
+        Err(_) => return,
+    };
+    processors.retain(|id, _| windows.iter().any(|(w, _)| w == id));
+
+    let mut processed = Vec::with_capacity(windows.len());
+    for (id, profile) in &windows {
+        if profile.shown_modes().all(|m| m == VisualMode::Oscilloscope) {
+            continue;
+        }
+        // Same device as the main window, so the same device gain
+        let wanted = FFTConfig { sensitivity: profile.sensitivity * device_gain, ..fft_config_for(profile, sample_rate) };
+        let processor = processors.entry(*id).or_insert_with(|| FFTProcessor::new(wanted.clone()));
+        let current = processor.get_config();
+        if current.sample_rate != wanted.sample_rate {
+            let mut rebuilt = FFTProcessor::new(wanted);
+            rebuilt.carry_levels_from(processor);
+            *processor = rebuilt;
+        } else if current.num_bars != wanted.num_bars
+            || current.sensitivity != wanted.sensitivity
+            || current.attack_time_ms != wanted.attack_time_ms
+            || current.release_time_ms != wanted.release_time_ms
+            || current.peak_hold_time_ms != wanted.peak_hold_time_ms
+            || current.peak_release_time_ms != wanted.peak_release_time_ms
+            || current.aggregation_mode != wanted.aggregation_mode
+            || current.peak_gravity != wanted.peak_gravity
+        {
+            processor.update_config(wanted);
+        }
+        processor.process(samples);
+        processed.push(*id);
+    }
+
+    let Ok(mut state) = shared_state.lock() else { return };
+    let now = Instant::now();
+    for (id, profile) in &windows {
+        let data = state
+            .extra_visualization
+            .entry(*id)
+            .or_insert_with(|| VisualizationData::new(profile.num_bars));
+        if profile.shown_modes().any(VisualMode::uses_waveform) && !samples.is_empty() {
+            data.set_waveform(samples, tint, now);
+            data.set_stereo(stereo.0, stereo.1);
+        }
+    }
+    for id in processed {
+        let (Some(data), Some(processor)) = (state.extra_visualization.get_mut(&id), processors.get(&id)) else { continue };
+        let (bars, peaks) = processor.output();
+        if windows.iter().any(|(w, p)| *w == id && p.shown_modes().any(VisualMode::uses_history)) {
+            Arc::make_mut(&mut data.history).push(bars, now);
+        }
+        data.set_bars(bars, peaks);
+        data.timestamp = now;
+    }
+    state.extra_visualization.retain(|id, _| windows.iter().any(|(w, _)| w == id));
+}
+
 // ========================================================================
 // SIGNAL HANDLER THREAD (SIGUSR1 = reload presets)
 // ========================================================================
@@ -645,8 +1140,71 @@ fn load_icon() -> Option<Arc<egui::IconData>> {
     }
 }
 
+// ========================================================================
+// COMMAND LINE
+// ========================================================================
+
+/// Startup options parsed from the command line
+#[derive(Default)]
+struct CliArgs {
+    /// `--user <name>`: use a separate config + presets folder
+    user: Option<String>,
+    /// `--list-users`: print known users and exit
+    list_users: bool,
+    /// `--portable`: keep config/presets/logs next to the executable
+    portable: bool,
+    /// `--doctor`: run the self-test, write a report and exit
+    doctor: bool,
+    /// `--no-wallpaper`: start as a normal window even if wallpaper mode is on
+    /// (the way back when the wallpaper can't be clicked)
+    no_wallpaper: bool,
+}
+
+fn parse_args() -> CliArgs {
+    let mut cli = CliArgs::default();
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--user" => cli.user = args.next(),
+            "--list-users" => cli.list_users = true,
+            "--portable" => cli.portable = true,
+            "--doctor" => cli.doctor = true,
+            "--no-wallpaper" => cli.no_wallpaper = true,
+            other => {
+                if let Some(name) = other.strip_prefix("--user=") {
+                    cli.user = Some(name.to_string());
+                } else {
+                    eprintln!("[Main] Ignoring unknown argument: {}", other);
+                }
+            }
+        }
+    }
+    cli
+}
+
 fn main (){
-    
+
+    // =====================================================================
+    // 0. Command line / config root
+    // =====================================================================
+    let cli = parse_args();
+    let portable = cli.portable || paths::portable_flag_present();
+
+    if cli.list_users {
+        let users = paths::list_users(portable);
+        if users.is_empty() {
+            println!("No named users yet. Start with `--user <name>` to create one.");
+        } else {
+            for user in users {
+                println!("{}", user);
+            }
+        }
+        return;
+    }
+
+    paths::init(paths::AppPaths::resolve(cli.user.as_deref(), portable));
+
     // =====================================================================
     // 1. Setup cross-platforing logging
     // =====================================================================
@@ -655,24 +1213,25 @@ fn main (){
     // Windows: %APPDATA%\BeSpec
     // Linux: ~/.local/share/BeSpec
     // macOs: ~/Library/Application Support/BeSpec
-    let log_dir = if let Some(proj_dirs) = ProjectDirs::from("", "", "BeSpec") {
-        proj_dirs.data_dir().join("logs")
-    } else {
-        // Fallback to local directory wif we can't find the home folder
-        std::path::PathBuf::from("logs")
-    };
+    let log_dir = paths::get().log_dir.clone();
 
     // Ensure the directoy exists (otherwise logging will fail)
     if let Err(e) = fs::create_dir_all(&log_dir) {
         tracing::error!("[Main] Failed to create log directory {:?}: {}", log_dir, e);
     }
 
-    // Set up the file appender
-    let file_appender = tracing_appender::rolling::daily(&log_dir, "BeSpec.log");
+    // Set up the file appender: a new file every day, the last week kept
+    let file_appender = tracing_appender::rolling::RollingFileAppender::builder()
+        .rotation(tracing_appender::rolling::Rotation::DAILY)
+        .filename_prefix("BeSpec.log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(&log_dir)
+        .unwrap_or_else(|_| tracing_appender::rolling::daily(&log_dir, "BeSpec.log"));
     let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
 
     // Get local offset. Fall back to UTC if it fails
     let offset = time::UtcOffset::current_local_offset().unwrap_or(time::UtcOffset::UTC);
+    theme_schedule::set_local_offset(offset);
 
     // Define the time format for logs (human readable, folks!)
     let timer = OffsetTime::new(
@@ -687,20 +1246,39 @@ fn main (){
         .unwrap_or_else(|_| EnvFilter::new("info"));
 
 
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_writer(non_blocking)
-        .with_ansi(false)
-        .with_timer(timer)
-        .with_env_filter(env_filter) // No ANSI codes in log files
+    // Initialize tracing: the log file, plus the recent lines for the Logs panel
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(non_blocking)
+                .with_ansi(false) // No ANSI codes in log files
+                .with_timer(timer),
+        )
+        .with(log_buffer::BufferLayer::new(offset))
         .init();
+    crash_report::install();
 
     // Log startup info
     tracing::info!("=== BeSpec Startup ===");
     tracing::info!("Version: v{}", env!("CARGO_PKG_VERSION"));
     tracing::info!("Platform: {}", std::env::consts::OS);
     tracing::info!("Log Directory: {:?}", log_dir);
+    if paths::get().portable {
+        tracing::info!("Portable mode: on");
+    }
+    match &paths::get().user {
+        Some(user) => tracing::info!("User: {} (config: {:?})", user, paths::get().config_dir),
+        None => tracing::info!("User: default (config: {:?})", paths::get().config_dir),
+    }
     
+    if cli.doctor {
+        std::process::exit(doctor::run());
+    }
+
+    #[cfg(feature = "plugins")]
+    gui::plugins::init();
+
     // ========================================================================
     // 2. INITIALIZE APP STATE
     // ========================================================================
@@ -708,13 +1286,23 @@ fn main (){
     // create shared state
     let shared_state = Arc::new(Mutex::new(SharedState::new()));
 
+    if let Ok(mut state) = shared_state.lock() {
+        net::set_offline(state.config.offline_mode);
+        if cli.no_wallpaper && state.config.wallpaper_mode {
+            tracing::info!("[Main] --no-wallpaper: starting as a normal window");
+            state.config.wallpaper_mode = false;
+        }
+        restore_monitor_layout(&mut state.config);
+    }
+
     let (initial_decorations, initial_on_top, initial_size, initial_pos) = {
         if let Ok(state) = shared_state.lock() {
+            let (size, pos) = state.config.active_geometry();
             (
                 state.config.window_decorations, 
                 state.config.always_on_top,
-                state.config.window_size,
-                state.config.window_position
+                size,
+                pos
             )
         } else {
             // Safe fallback if lock is poisoned during init
@@ -748,6 +1336,11 @@ fn main (){
     // ==================================
     start_signal_handler(shared_state.clone(), shutdown.clone());
 
+    // ==================================
+    // Start CPU / Memory Sampling Thread
+    // ==================================
+    process_usage::start(shared_state.clone(), shutdown.clone());
+
     // ==================================
     // Start Update Update Checker Thread
     // ==================================
@@ -763,6 +1356,7 @@ fn main (){
                 }
             }
             Ok(None) => tracing::info!("[Update] ✓ App is up to date."),
+            Err(e) if net::is_offline() => tracing::info!("[Update] Skipped: {}", e),
             Err(e) => tracing::warn!("[Update] ⚠️ Check failed: {}", e),
         }
     });
@@ -0,0 +1,264 @@
+//! Pink-noise measurement mode: a rough room / speaker response.
+//!
+//! While a measurement runs, the FFT thread keeps a long-term power average
+//! of every bar (`ResponseAverager`) and, a few times a second, a smoothed
+//! copy of it in `MeasurementState::curve`, which is drawn over the live
+//! display. Optionally BeSpec plays pink noise itself (`audio_output`) so
+//! there's something to measure; with a microphone selected as the capture
+//! device, the curve is then what the room does to a flat signal.
+//!
+//! Pink noise has equal energy per octave and the bars are spread
+//! logarithmically, so a flat system reads as a roughly flat curve. Smoothing is over a
+//! fraction of an octave around each bar, the usual way to read a room
+//! response without drowning in comb-filter ripples.
+//!
+//! This is a visual aid, not a calibrated measurement: there's no mic
+//! correction and the capture device's own response is included.
+
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::shared_state::SILENCE_DB;
+
+/// Frames between curve updates (the smoothing is the costly part)
+const CURVE_EVERY: u32 = 8;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum Smoothing {
+    None,
+    ThirdOctave,
+    SixthOctave,
+    TwelfthOctave,
+}
+
+impl Smoothing {
+    pub const ALL: [Self; 4] = [Self::None, Self::ThirdOctave, Self::SixthOctave, Self::TwelfthOctave];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::ThirdOctave => "1/3 Octave",
+            Self::SixthOctave => "1/6 Octave",
+            Self::TwelfthOctave => "1/12 Octave",
+        }
+    }
+
+    /// Window width in octaves
+    pub fn octaves(self) -> f32 {
+        match self {
+            Self::None => 0.0,
+            Self::ThirdOctave => 1.0 / 3.0,
+            Self::SixthOctave => 1.0 / 6.0,
+            Self::TwelfthOctave => 1.0 / 12.0,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct MeasurementSettings {
+    /// Play pink noise while measuring
+    pub play_noise: bool,
+    /// Output device id for the noise, empty for the system default
+    pub output_device: String,
+    /// Noise level (dBFS)
+    pub level_db: f32,
+    pub smoothing: Smoothing,
+}
+
+impl Default for MeasurementSettings {
+    fn default() -> Self {
+        Self { play_noise: true, output_device: String::new(), level_db: -20.0, smoothing: Smoothing::ThirdOctave }
+    }
+}
+
+/// Paul Kellet's pink noise filter over a xorshift white noise source,
+/// accurate to about ±0.05 dB above 9 Hz at 44.1 kHz
+pub struct PinkNoise {
+    rng: u64,
+    b: [f32; 7],
+}
+
+impl PinkNoise {
+    pub fn new(seed: u64) -> Self {
+        Self { rng: seed.max(1), b: [0.0; 7] }
+    }
+
+    /// Unfiltered white noise sample, -1..1
+    pub fn white(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        // Top 24 bits to -1..1
+        (self.rng >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+    }
+
+    /// Next sample, roughly -1..1
+    pub fn next_sample(&mut self) -> f32 {
+        let white = self.white();
+        let b = &mut self.b;
+        b[0] = 0.99886 * b[0] + white * 0.0555179;
+        b[1] = 0.99332 * b[1] + white * 0.0750759;
+        b[2] = 0.96900 * b[2] + white * 0.1538520;
+        b[3] = 0.86650 * b[3] + white * 0.3104856;
+        b[4] = 0.55000 * b[4] + white * 0.5329522;
+        b[5] = -0.7616 * b[5] - white * 0.0168980;
+        let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+        b[6] = white * 0.115926;
+        pink * 0.11
+    }
+}
+
+/// Long-term power average per bar
+#[derive(Clone, Debug, Default)]
+pub struct ResponseAverager {
+    sum_power: Vec<f64>,
+    frames: u32,
+}
+
+impl ResponseAverager {
+    pub fn feed(&mut self, bars: &[f32]) {
+        if self.sum_power.len() != bars.len() {
+            // Bar count changed: the old sums don't line up any more
+            self.sum_power = vec![0.0; bars.len()];
+            self.frames = 0;
+        }
+        for (sum, &db) in self.sum_power.iter_mut().zip(bars) {
+            *sum += 10f64.powf(db as f64 / 10.0);
+        }
+        self.frames += 1;
+    }
+
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Average level per bar (dB)
+    pub fn average_db(&self) -> Vec<f32> {
+        let frames = self.frames.max(1) as f64;
+        self.sum_power.iter().map(|&sum| ((10.0 * (sum / frames).log10()) as f32).max(SILENCE_DB)).collect()
+    }
+}
+
+/// Power average of the bars within `octaves` around each bar's center
+/// frequency. `freqs` has one entry per level.
+pub fn smooth(levels_db: &[f32], freqs: &[f32], octaves: f32) -> Vec<f32> {
+    if octaves <= 0.0 || levels_db.len() != freqs.len() {
+        return levels_db.to_vec();
+    }
+    let half = octaves / 2.0;
+    let power: Vec<f64> = levels_db.iter().map(|&db| 10f64.powf(db as f64 / 10.0)).collect();
+    (0..levels_db.len())
+        .map(|i| {
+            let center = freqs[i].max(1.0);
+            let (low, high) = (center * 2f32.powf(-half), center * 2f32.powf(half));
+            let (sum, n) = freqs
+                .iter()
+                .zip(&power)
+                .filter(|(f, _)| (low..=high).contains(*f))
+                .fold((0.0, 0u32), |(sum, n), (_, p)| (sum + p, n + 1));
+            if n == 0 {
+                levels_db[i]
+            } else {
+                ((10.0 * (sum / n as f64).log10()) as f32).max(SILENCE_DB)
+            }
+        })
+        .collect()
+}
+
+/// What the FFT thread and the GUI share about the current measurement
+#[derive(Clone, Debug, Default)]
+pub struct MeasurementState {
+    pub running: bool,
+    pub started: Option<Instant>,
+    averager: ResponseAverager,
+    /// Smoothed response (dB per bar), kept after stopping until cleared
+    pub curve: Vec<f32>,
+}
+
+impl MeasurementState {
+    pub fn start(&mut self, now: Instant) {
+        *self = Self { running: true, started: Some(now), ..Default::default() };
+    }
+
+    pub fn stop(&mut self) {
+        self.running = false;
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn frames(&self) -> u32 {
+        self.averager.frames()
+    }
+
+    /// Add a frame; `freqs` gives each bar's center frequency
+    pub fn feed(&mut self, bars: &[f32], smoothing: Smoothing, freqs: impl Fn(usize) -> f32) {
+        if !self.running || bars.is_empty() {
+            return;
+        }
+        self.averager.feed(bars);
+        if self.averager.frames() % CURVE_EVERY == 1 || self.curve.len() != bars.len() {
+            let freqs: Vec<f32> = (0..bars.len()).map(freqs).collect();
+            self.curve = smooth(&self.averager.average_db(), &freqs, smoothing.octaves());
+        }
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pink_noise_is_bounded_and_tilted() {
+        let mut noise = PinkNoise::new(42);
+        let samples: Vec<f32> = (0..48_000).map(|_| noise.next_sample()).collect();
+        assert!(samples.iter().all(|s| s.abs() < 1.0));
+
+        let energy: f32 = samples.iter().map(|s| s * s).sum();
+        let rms = (energy / samples.len() as f32).sqrt();
+        assert!((0.05..0.5).contains(&rms), "rms {}", rms);
+
+        // Differencing is a high-pass: white noise keeps about twice its
+        // energy, pink noise (mostly lows) far less
+        let diff: f32 = samples.windows(2).map(|w| (w[1] - w[0]).powi(2)).sum();
+        assert!(diff / energy < 1.0, "ratio {}", diff / energy);
+    }
+
+    #[test]
+    fn test_smoothing_spreads_a_spike() {
+        // Bars a 1/6 octave apart
+        let freqs: Vec<f32> = (0..12).map(|i| 100.0 * 2f32.powf(i as f32 / 6.0)).collect();
+        let mut levels = vec![-60.0; 12];
+        levels[6] = 0.0;
+
+        let smoothed = smooth(&levels, &freqs, 0.4);
+        // The spike and its direct neighbours share it, the rest don't
+        assert!((smoothed[6] + 4.77).abs() < 0.01);
+        assert!((smoothed[5] - smoothed[6]).abs() < 0.01);
+        assert_eq!(smoothed[3], -60.0);
+        assert_eq!(smooth(&levels, &freqs, 0.0), levels);
+    }
+
+    #[test]
+    fn test_measurement_averages_while_running() {
+        let mut m = MeasurementState::default();
+        m.feed(&[-10.0], Smoothing::None, |_| 1000.0);
+        assert_eq!(m.frames(), 0);
+
+        m.start(Instant::now());
+        m.feed(&[-10.0, -20.0], Smoothing::None, |i| 100.0 * (i + 1) as f32);
+        m.feed(&[-10.0, -20.0], Smoothing::None, |i| 100.0 * (i + 1) as f32);
+        assert_eq!(m.frames(), 2);
+        assert!((m.curve[1] + 20.0).abs() < 0.01);
+
+        m.stop();
+        m.feed(&[0.0, 0.0], Smoothing::None, |_| 1000.0);
+        assert_eq!(m.frames(), 2);
+        assert_eq!(m.curve.len(), 2);
+    }
+}
@@ -1,4 +1,5 @@
 use crossbeam_channel::Sender;
+use std::time::Duration;
 use super::{MediaController, MediaMonitor, MediaTrackInfo};
 
 pub struct DummyMediaManager;
@@ -8,6 +9,8 @@ impl MediaController for DummyMediaManager {
     fn try_play_pause(&self) {}
     fn try_next(&self) {}
     fn try_prev(&self) {}
+    fn try_seek(&self, _position: Duration) {}
+    fn try_set_volume(&self, _volume: f32) {}
 }
 
 impl MediaMonitor for DummyMediaManager {
@@ -1,10 +1,9 @@
 use crossbeam_channel::Sender;
 // Removed unused imports: egui::Response, Instant
-use std::time::Duration; 
+use std::time::{Duration, Instant};
 use std::fs;
 use std::path::PathBuf;
-use std::io::Read;
-use super::{MediaController, MediaMonitor, MediaTrackInfo, sanitize_title};
+use super::{MediaController, MediaMonitor, MediaTrackInfo, TrackPosition, sanitize_title};
 use mpris::{PlayerFinder, PlaybackStatus};
 
 pub struct LinuxMediaManager;
@@ -31,19 +30,12 @@ fn load_art_from_url(art_url: &str) -> Option<(Vec<u8>, [usize; 2])> {
     } 
     // 2. Handle HTTP/HTTPS (Common with Spotify/Browsers)
     else if art_url.starts_with("http://") || art_url.starts_with("https://") {
-        let agent = ureq::AgentBuilder::new()
-            .timeout_read(Duration::from_secs(3))
-            .timeout_write(Duration::from_secs(3))
-            .build();
-
-        match agent.get(art_url).call() {
-            Ok(response) => {
-                let mut reader = response.into_reader();
-                let mut bytes = Vec::new();
-                if let Ok(_) = reader.read_to_end(&mut bytes) {
-                    return super::decode_image_to_rgba(&bytes);
-                }
-            },
+        let request = crate::net::Request::get(art_url)
+            .with_timeout(Duration::from_secs(3))
+            .with_max_bytes(super::MAX_ART_BYTES);
+
+        match request.send() {
+            Ok(bytes) => return super::decode_image_to_rgba(&bytes),
             Err(e) => tracing::warn!("[Media/Linux] Failed to download art: {}", e),
         }
     }
@@ -91,6 +83,29 @@ impl MediaController for LinuxMediaManager {
             if let Ok(player) = finder.find_active() { let _ = player.previous(); }
         }
     }
+
+    fn try_seek(&self, position: Duration) {
+        let Ok(finder) = PlayerFinder::new() else { return };
+        let Ok(player) = finder.find_active() else { return };
+        // SetPosition is ignored unless it names the current track
+        let track_id = player.get_metadata().ok().and_then(|m| m.track_id());
+        match track_id {
+            Some(id) => {
+                if let Err(e) = player.set_position(id, &position) {
+                    tracing::warn!("[Media/Linux] Seek failed: {}", e);
+                }
+            }
+            None => tracing::debug!("[Media/Linux] Player has no track id, can't seek"),
+        }
+    }
+
+    fn try_set_volume(&self, volume: f32) {
+        let Ok(finder) = PlayerFinder::new() else { return };
+        let Ok(player) = finder.find_active() else { return };
+        if let Err(e) = player.set_volume(volume.clamp(0.0, 1.0) as f64) {
+            tracing::warn!("[Media/Linux] Setting volume failed: {}", e);
+        }
+    }
 }
 
 impl MediaMonitor for LinuxMediaManager {
@@ -144,6 +159,14 @@ impl MediaMonitor for LinuxMediaManager {
                             // Use cached directly (Fixes "unused assignment" warning)
                             let final_art = cached_art_bytes.clone();
 
+                            // Streams and some browsers have no length
+                            let position = match (meta.length(), player.get_position()) {
+                                (Some(duration), Ok(position)) if !duration.is_zero() => {
+                                    Some(TrackPosition { position, duration, sampled_at: Instant::now() })
+                                }
+                                _ => None,
+                            };
+
                             let current_info = MediaTrackInfo {
                                 title,
                                 artist,
@@ -151,9 +174,11 @@ impl MediaMonitor for LinuxMediaManager {
                                 is_playing,
                                 source_app: identity,
                                 album_art: final_art,
+                                position,
+                                volume: player.get_volume().ok().map(|v| v.clamp(0.0, 1.0) as f32),
                             };
                             
-                            if last_sent_info.as_ref() != Some(&current_info) {
+                            if last_sent_info.as_ref().map_or(true, |last| current_info.differs_from(last)) {
                                 tracing::debug!("[Media/Linux] Update: {} - {} (Art: {})", 
                                     current_info.artist, 
                                     current_info.title,
@@ -1,7 +1,6 @@
 use crossbeam_channel::Sender;
 use std::time::Duration;
 use std::process::Command;
-use std::io::Read; 
 use super::{MediaController, MediaMonitor, MediaTrackInfo, sanitize_title};
 
 use base64::{Engine as _, engine::general_purpose};
@@ -16,6 +15,8 @@ impl MediaController for MacMediaManager {
     fn try_play_pause(&self) {}
     fn try_next(&self) {}
     fn try_prev(&self) {}
+    fn try_seek(&self, _position: Duration) {}
+    fn try_set_volume(&self, _volume: f32) {}
 }
 
 impl MediaMonitor for MacMediaManager {
@@ -58,6 +59,8 @@ impl MediaMonitor for MacMediaManager {
                         is_playing: info.is_playing,
                         source_app: info.source_app,
                         album_art: info.album_art, 
+                        position: None,
+                        volume: None,
                     };
 
                     if last_sent_info.as_ref() != Some(&current_info) {
@@ -226,19 +229,12 @@ fn get_macos_media_info(known_title: &str, known_artist: &str) -> Option<RawTrac
 }
 
 fn download_art(url: &str) -> Option<(Vec<u8>, [usize; 2])> {
-    let agent = ureq::AgentBuilder::new()
-        .timeout_read(Duration::from_secs(2))
-        .timeout_write(Duration::from_secs(2))
-        .build();
-
-    match agent.get(url).call() {
-        Ok(response) => {
-            let mut reader = response.into_reader();
-            let mut bytes = Vec::new();
-            if reader.read_to_end(&mut bytes).is_ok() {
-                return super::decode_image_to_rgba(&bytes);
-            }
-        },
+    let request = crate::net::Request::get(url)
+        .with_timeout(Duration::from_secs(2))
+        .with_max_bytes(super::MAX_ART_BYTES);
+
+    match request.send() {
+        Ok(bytes) => return super::decode_image_to_rgba(&bytes),
         Err(e) => tracing::warn!("[Media/MacOS] Art download failed: {}", e),
     }
     None
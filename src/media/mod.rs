@@ -1,4 +1,5 @@
 use crossbeam_channel::Sender;
+use std::time::{Duration, Instant};
 
 // Module datastructre is self-contained for media handling
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -9,8 +10,74 @@ pub struct MediaTrackInfo {
     pub is_playing: bool,
     pub source_app: String,
     pub album_art: Option<(Vec<u8>, [usize; 2])>,
+    /// Playback position, if the player reports one
+    pub position: Option<TrackPosition>,
+    /// Volume `try_set_volume` controls (0..1), if the backend can read it
+    pub volume: Option<f32>,
 }
 
+/// Where the player was in the track at `sampled_at`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrackPosition {
+    pub position: Duration,
+    pub duration: Duration,
+    pub sampled_at: Instant,
+}
+
+/// A position further than this from where playback should have got to
+/// counts as a seek and is sent on
+const SEEK_TOLERANCE: Duration = Duration::from_secs(2);
+
+impl MediaTrackInfo {
+    /// Position at `now`, running on from the last sample while playing
+    pub fn position_now(&self, now: Instant) -> Option<Duration> {
+        let p = self.position?;
+        let played = if self.is_playing { now.saturating_duration_since(p.sampled_at) } else { Duration::ZERO };
+        Some((p.position + played).min(p.duration))
+    }
+
+    /// Same track in the same state, whatever the position
+    pub fn same_track(&self, other: &MediaTrackInfo) -> bool {
+        self.title == other.title
+            && self.artist == other.artist
+            && self.album == other.album
+            && self.is_playing == other.is_playing
+            && self.source_app == other.source_app
+            && self.album_art == other.album_art
+    }
+
+    /// Worth sending after `last`? Positions move every poll, so only a
+    /// jump (seek, new length) counts, not playback running on.
+    pub fn differs_from(&self, last: &MediaTrackInfo) -> bool {
+        if !self.same_track(last) || self.volume != last.volume {
+            return true;
+        }
+        match (self.position, last.position) {
+            (None, None) => false,
+            (Some(p), Some(l)) => {
+                let expected = last.position_now(p.sampled_at).unwrap_or(l.position);
+                let drift = p.position.abs_diff(expected);
+                drift > SEEK_TOLERANCE || p.duration != l.duration
+            }
+            _ => true,
+        }
+    }
+}
+
+/// "3:07" / "1:02:03"
+pub fn format_position(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// Largest album art download we accept (bytes)
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+pub const MAX_ART_BYTES: u64 = 10 * 1024 * 1024;
+
 /// Shared cross-platform helper to decode images on background threads
 pub fn decode_image_to_rgba(bytes: &[u8]) -> Option<(Vec<u8>, [usize; 2])> {
     match image::load_from_memory(bytes) {
@@ -172,17 +239,19 @@ pub fn fetch_wikipedia_url(artist: &str, title: &str, album: &str) -> String {
     // 3. Perform API Request (Automatic Encoding)
     // We pass the raw query to ureq, which handles URL encoding internally.
     // This prevents double-encoding issues while keeping the API request safe.
-    let resp = ureq::get(api_url)
-        .query("action", "query")
-        .query("list", "search")
-        .query("srsearch", &raw_query)
-        .query("srlimit", "1")
-        .query("format", "json")
-        .call();
+    // Cached for an hour so repeated clicks on the same track stay local.
+    let resp = crate::net::Request::get(api_url)
+        .with_query("action", "query")
+        .with_query("list", "search")
+        .with_query("srsearch", &raw_query)
+        .with_query("srlimit", "1")
+        .with_query("format", "json")
+        .with_cache(std::time::Duration::from_secs(3600))
+        .send();
 
     match resp {
-        Ok(response) => {
-            if let Ok(json) = serde_json::from_reader::<_, serde_json::Value>(response.into_reader()) {
+        Ok(body) => {
+            if let Ok(json) = serde_json::from_slice::<serde_json::Value>(&body) {
                 if let Some(first_result) = json.get("query")
                     .and_then(|q| q.get("search"))
                     .and_then(|s| s.get(0)) 
@@ -224,12 +293,98 @@ pub fn fetch_wikipedia_url(artist: &str, title: &str, album: &str) -> String {
 }
 
 
+/// MusicBrainz search hits below this score (0-100) are someone else's
+const MUSICBRAINZ_MIN_SCORE: u32 = 90;
+
+#[derive(serde::Deserialize)]
+struct MusicBrainzHit {
+    id: String,
+    #[serde(default)]
+    score: u32,
+}
+
+#[derive(serde::Deserialize)]
+struct MusicBrainzSearch {
+    #[serde(default)]
+    releases: Vec<MusicBrainzHit>,
+    #[serde(default)]
+    recordings: Vec<MusicBrainzHit>,
+}
+
+/// Quote a value for a MusicBrainz (Lucene) query field
+fn lucene_phrase(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Attempts to find the MusicBrainz page for the given track.
+///
+/// Searches releases when there's an album, recordings otherwise, and only
+/// takes the top hit if MusicBrainz is confident about it.
+///
+/// Returns the release / recording page, or a MusicBrainz search page as a fallback.
+pub fn fetch_musicbrainz_url(artist: &str, title: &str, album: &str) -> String {
+    let clean_title = sanitize_title(title);
+    let has_album = !album.is_empty() && album != "Unknown Album";
+    let (entity, query) = if has_album {
+        ("release", format!("release:{} AND artist:{}", lucene_phrase(album), lucene_phrase(artist)))
+    } else {
+        ("recording", format!("recording:{} AND artist:{}", lucene_phrase(&clean_title), lucene_phrase(artist)))
+    };
+    tracing::debug!("[MEDIA] MusicBrainz Query: '{}'", query);
+
+    let resp = crate::net::Request::get(&format!("https://musicbrainz.org/ws/2/{}/", entity))
+        .with_query("query", &query)
+        .with_query("limit", "1")
+        .with_query("fmt", "json")
+        .with_cache(std::time::Duration::from_secs(3600))
+        .send_json::<MusicBrainzSearch>();
+
+    match resp {
+        Ok(search) => {
+            let hits = if has_album { search.releases } else { search.recordings };
+            match hits.into_iter().next() {
+                Some(hit) if hit.score >= MUSICBRAINZ_MIN_SCORE => {
+                    tracing::debug!("[MEDIA] MusicBrainz match {} (score {})", hit.id, hit.score);
+                    return format!("https://musicbrainz.org/{}/{}", entity, hit.id);
+                }
+                Some(hit) => tracing::warn!("[MEDIA] MusicBrainz best match scored only {}", hit.score),
+                None => tracing::warn!("[MEDIA] MusicBrainz returned 0 search results."),
+            }
+        }
+        Err(e) => tracing::error!("[MEDIA] MusicBrainz request failed: {}", e),
+    }
+
+    let search = if has_album { format!("{} {}", artist, album) } else { format!("{} {}", artist, clean_title) };
+    format!("https://musicbrainz.org/search?query={}&type={}", url_encode(&search), entity)
+}
+
+/// Discogs release search for the track. Their API needs a personal token,
+/// so this is just the search page, no lookup.
+pub fn discogs_search_url(artist: &str, title: &str, album: &str) -> String {
+    let what = if !album.is_empty() && album != "Unknown Album" { album.to_string() } else { sanitize_title(title) };
+    format!("https://www.discogs.com/search/?q={}&type=release", url_encode(&format!("{} {}", artist, what)))
+}
+
+/// Fill `{artist}`, `{title}` and `{album}` in a user's link template,
+/// URL-encoded so the track can't change the rest of the URL
+pub fn fill_link_template(template: &str, artist: &str, title: &str, album: &str) -> String {
+    template
+        .replace("{artist}", &url_encode(artist))
+        .replace("{title}", &url_encode(&sanitize_title(title)))
+        .replace("{album}", &url_encode(album))
+}
+
 
 /// Trait for controlling media playback (Commands)
 pub trait MediaController: Send + Sync {
     fn try_play_pause(&self);
     fn try_next(&self);
     fn try_prev(&self);
+    /// Jump to `position` in the current track
+    fn try_seek(&self, position: Duration);
+    /// Set the volume (0..1). What that means is up to the backend: the
+    /// default output device on Windows, the player itself over MPRIS.
+    fn try_set_volume(&self, volume: f32);
 }
 
 /// Trait for monitoring media state (Events)
@@ -285,6 +440,38 @@ mod tests {
         assert_eq!(url_encode(messy), "AC%2FDC+-+Who+Made+Who%3F");
     }
 
+    #[test]
+    fn test_link_template_and_query_escaping() {
+        let url = fill_link_template("https://example.com/{artist}/{title}?a={album}", "AC/DC", "Who Made Who (Official Video)", "R&B");
+        assert_eq!(url, "https://example.com/AC%2FDC/Who+Made+Who?a=R%26B");
+        assert_eq!(lucene_phrase(r#"Say "Hi""#), r#""Say \"Hi\"""#);
+    }
+
+    #[test]
+    fn test_position_runs_on_and_seeks_are_sent() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let sample = |pos: u64, when: u64| Some(TrackPosition {
+            position: Duration::from_secs(pos),
+            duration: Duration::from_secs(200),
+            sampled_at: at(when),
+        });
+        let last = MediaTrackInfo { title: "Song".into(), is_playing: true, position: sample(10, 0), ..Default::default() };
+
+        assert_eq!(last.position_now(at(5)), Some(Duration::from_secs(15)));
+        assert_eq!(last.position_now(at(500)), Some(Duration::from_secs(200)));
+        let paused = MediaTrackInfo { is_playing: false, ..last.clone() };
+        assert_eq!(paused.position_now(at(5)), Some(Duration::from_secs(10)));
+
+        // Playing on as expected isn't news, a jump or a pause is
+        assert!(!MediaTrackInfo { position: sample(15, 5), ..last.clone() }.differs_from(&last));
+        assert!(MediaTrackInfo { position: sample(90, 5), ..last.clone() }.differs_from(&last));
+        assert!(paused.differs_from(&last));
+        assert!(MediaTrackInfo { volume: Some(0.5), ..last.clone() }.differs_from(&last));
+        assert_eq!(format_position(Duration::from_secs(187)), "3:07");
+        assert_eq!(format_position(Duration::from_secs(3723)), "1:02:03");
+    }
+
     #[test]
     fn test_sanitize_title() {
         // 1. Standard Case
@@ -1,12 +1,17 @@
 use crossbeam_channel::Sender;
 use windows::Storage::Streams::DataReader;
-use std::time::Duration;
-use super::{MediaController, MediaMonitor, MediaTrackInfo, sanitize_title};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use super::{MediaController, MediaMonitor, MediaTrackInfo, TrackPosition, sanitize_title};
 
 // We use the `windows-media` crate for media control and monitoring
 use windows::Media::Control::GlobalSystemMediaTransportControlsSessionManager;
 use windows::Media::Control::GlobalSystemMediaTransportControlsSessionPlaybackStatus;
 
+use windows::Win32::Media::Audio::Endpoints::IAudioEndpointVolume;
+use windows::Win32::Media::Audio::{eConsole, eRender, IMMDeviceEnumerator, MMDeviceEnumerator};
+use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_ALL, COINIT_MULTITHREADED};
+
+use std::cell::RefCell;
 use std::sync::OnceLock;
 use tokio::runtime::Runtime;
 
@@ -47,6 +52,106 @@ impl WindowsMediaManager {
     }
 }
 
+/// WinRT durations and timestamps count 100 ns ticks
+const TICKS_PER_SEC: i64 = 10_000_000;
+
+/// Seconds from 1601-01-01 (WinRT `DateTime`) to 1970-01-01
+const FILETIME_UNIX_OFFSET_SECS: i64 = 11_644_473_600;
+
+fn ticks_to_duration(ticks: i64) -> Duration {
+    Duration::from_nanos(ticks.max(0) as u64 * 100)
+}
+
+/// Timeline of the session. Apps only update it now and then, so the
+/// position is pinned to when they last did, not to now.
+fn timeline_position(session: &windows::Media::Control::GlobalSystemMediaTransportControlsSession) -> Option<TrackPosition> {
+    let timeline = session.GetTimelineProperties().ok()?;
+    let start = timeline.StartTime().ok()?.Duration;
+    let end = timeline.EndTime().ok()?.Duration;
+    if end <= start {
+        return None;
+    }
+    let position = timeline.Position().ok()?.Duration - start;
+
+    let updated = timeline.LastUpdatedTime().ok()?.UniversalTime;
+    let now_ticks = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_nanos() as i64 / 100
+        + FILETIME_UNIX_OFFSET_SECS * TICKS_PER_SEC;
+    let age = ticks_to_duration(now_ticks - updated);
+    let sampled_at = Instant::now().checked_sub(age).unwrap_or_else(Instant::now);
+
+    Some(TrackPosition {
+        position: ticks_to_duration(position),
+        duration: ticks_to_duration(end - start),
+        sampled_at,
+    })
+}
+
+/// How long a looked-up volume control is reused before asking for the
+/// default output device again (the user may have switched it)
+const ENDPOINT_REFRESH: Duration = Duration::from_secs(5);
+
+/// COM setup and the volume control of the default output device, one per
+/// thread. SMTC has no volume of its own, so this is the system volume the
+/// taskbar slider shows.
+struct EndpointVolume {
+    /// We initialized COM on this thread and owe it a `CoUninitialize`
+    com_initialized: bool,
+    cached: Option<(IAudioEndpointVolume, Instant)>,
+}
+
+impl EndpointVolume {
+    fn new() -> Self {
+        // On the GUI thread winit got there first (as STA), that's fine too,
+        // but then it isn't ours to uninitialize
+        let com_initialized = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) }.is_ok();
+        Self { com_initialized, cached: None }
+    }
+
+    fn get(&mut self) -> windows::core::Result<IAudioEndpointVolume> {
+        if let Some((endpoint, fetched)) = &self.cached {
+            if fetched.elapsed() < ENDPOINT_REFRESH {
+                return Ok(endpoint.clone());
+            }
+        }
+        let endpoint: IAudioEndpointVolume = unsafe {
+            let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+            let device = enumerator.GetDefaultAudioEndpoint(eRender, eConsole)?;
+            device.Activate(CLSCTX_ALL, None)?
+        };
+        self.cached = Some((endpoint.clone(), Instant::now()));
+        Ok(endpoint)
+    }
+}
+
+impl Drop for EndpointVolume {
+    fn drop(&mut self) {
+        // Release the COM object before COM goes away
+        self.cached = None;
+        if self.com_initialized {
+            unsafe { CoUninitialize() };
+        }
+    }
+}
+
+thread_local! {
+    static ENDPOINT_VOLUME: RefCell<EndpointVolume> = RefCell::new(EndpointVolume::new());
+}
+
+/// Run `f` on this thread's cached volume control. A failure drops the
+/// cache, the device may be gone.
+fn with_endpoint_volume<T>(
+    f: impl FnOnce(&IAudioEndpointVolume) -> windows::core::Result<T>,
+) -> windows::core::Result<T> {
+    ENDPOINT_VOLUME.with(|volume| {
+        let mut volume = volume.borrow_mut();
+        let result = volume.get().and_then(|endpoint| f(&endpoint));
+        if result.is_err() {
+            volume.cached = None;
+        }
+        result
+    })
+}
+
 /// Helper functionto clean up Windows App Ids
 fn clean_app_name(raw_id: &str) -> String {
     let stage1 = raw_id.split('!').last().unwrap_or(raw_id);
@@ -73,6 +178,24 @@ impl MediaController for WindowsMediaManager {
         tracing::debug!("[Media/Windows] Skipping Previous");
         Self::with_session(|s| { let _ = s.TrySkipPreviousAsync(); });
     }
+
+    fn try_seek(&self, position: Duration) {
+        tracing::debug!("[Media/Windows] Seeking to {:?}", position);
+        Self::with_session(|s| {
+            // The timeline may not start at zero
+            let start = s.GetTimelineProperties().and_then(|t| t.StartTime()).map_or(0, |t| t.Duration);
+            let ticks = start + (position.as_nanos() / 100) as i64;
+            let _ = s.TryChangePlaybackPositionAsync(ticks);
+        });
+    }
+
+    fn try_set_volume(&self, volume: f32) {
+        let result =
+            with_endpoint_volume(|ep| unsafe { ep.SetMasterVolumeLevelScalar(volume.clamp(0.0, 1.0), std::ptr::null()) });
+        if let Err(e) = result {
+            tracing::warn!("[Media/Windows] Setting volume failed: {}", e);
+        }
+    }
 }
 
 
@@ -175,9 +298,11 @@ impl MediaMonitor for WindowsMediaManager {
                                         is_playing,
                                         source_app: clean_app,
                                         album_art: album_art_data,
+                                        position: timeline_position(&session),
+                                        volume: with_endpoint_volume(|ep| unsafe { ep.GetMasterVolumeLevelScalar() }).ok(),
                                     };
 
-                                    if last_sent_info.as_ref() != Some(&current_info) {
+                                    if last_sent_info.as_ref().map_or(true, |last| current_info.differs_from(last)) {
                                         tracing::info!("[Media/Windows] Update: {} - {} ({})", 
                                             current_info.artist, 
                                             current_info.title, 
@@ -0,0 +1,355 @@
+//! MIDI output of band triggers, so DAWs and lighting consoles can follow
+//! BeSpec's analysis.
+//!
+//! Each trigger watches a frequency range of the spectrum. When the loudest
+//! bar in it rises over the threshold it sends a note-on (velocity from how
+//! far over) or a CC at 127; when it drops `RELEASE_DB` back under, the
+//! matching note-off or CC 0. The FFT thread runs this right after the bars
+//! are computed, so the messages are as early as the bars on screen.
+//!
+//! The port is opened with midir, which is behind the `midi` cargo feature
+//! (it needs the ALSA development files on Linux). Without it the settings
+//! are still there and the triggers still light up, nothing is sent.
+
+use serde::{Deserialize, Serialize};
+
+use crate::fft_processor::FFTProcessor;
+
+/// Whether this build can send MIDI at all
+pub const SUPPORTED: bool = cfg!(feature = "midi");
+
+/// A held trigger lets go this far under its threshold, so a level hovering
+/// right at it doesn't machine-gun notes
+const RELEASE_DB: f32 = 3.0;
+
+/// dB over the threshold that maps to full velocity
+const VELOCITY_RANGE_DB: f32 = 24.0;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+pub enum MidiAction {
+    /// Note on while over the threshold, note off after
+    Note(u8),
+    /// Controller to 127 while over the threshold, 0 after
+    Cc(u8),
+}
+
+impl MidiAction {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Note(_) => "Note",
+            Self::Cc(_) => "CC",
+        }
+    }
+
+    /// Note or controller number
+    pub fn number_mut(&mut self) -> &mut u8 {
+        match self {
+            Self::Note(n) | Self::Cc(n) => n,
+        }
+    }
+}
+
+/// One band → message mapping
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct MidiTrigger {
+    pub name: String,
+    pub low_hz: f32,
+    pub high_hz: f32,
+    /// Bar level (dB, after sensitivity) that fires it
+    pub threshold_db: f32,
+    /// 1..=16
+    pub channel: u8,
+    pub action: MidiAction,
+}
+
+impl MidiTrigger {
+    fn new(name: &str, low_hz: f32, high_hz: f32, threshold_db: f32, action: MidiAction) -> Self {
+        Self { name: name.to_string(), low_hz, high_hz, threshold_db, channel: 10, action }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct MidiSettings {
+    pub enabled: bool,
+    /// Output port name, as listed by `list_ports`
+    pub port: String,
+    pub triggers: Vec<MidiTrigger>,
+}
+
+impl Default for MidiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: String::new(),
+            // General MIDI drum map on channel 10
+            triggers: vec![
+                MidiTrigger::new("Kick", 40.0, 120.0, -12.0, MidiAction::Note(36)),
+                MidiTrigger::new("Snare", 180.0, 350.0, -18.0, MidiAction::Note(38)),
+                MidiTrigger::new("Hi-Hat", 6000.0, 12000.0, -24.0, MidiAction::Note(42)),
+            ],
+        }
+    }
+}
+
+pub fn note_on(channel: u8, note: u8, velocity: u8) -> [u8; 3] {
+    [0x90 | (channel.clamp(1, 16) - 1), note & 0x7F, velocity.clamp(1, 127)]
+}
+
+pub fn note_off(channel: u8, note: u8) -> [u8; 3] {
+    [0x80 | (channel.clamp(1, 16) - 1), note & 0x7F, 0]
+}
+
+pub fn control_change(channel: u8, controller: u8, value: u8) -> [u8; 3] {
+    [0xB0 | (channel.clamp(1, 16) - 1), controller & 0x7F, value & 0x7F]
+}
+
+/// Messages for one frame of band levels (dB, one per trigger). `held`
+/// tracks which triggers are on and is resized to match.
+pub fn evaluate(triggers: &[MidiTrigger], levels: &[f32], held: &mut Vec<bool>) -> Vec<[u8; 3]> {
+    held.resize(triggers.len(), false);
+    let mut messages = Vec::new();
+    for ((trigger, &level), held) in triggers.iter().zip(levels).zip(held.iter_mut()) {
+        if !*held && level >= trigger.threshold_db {
+            *held = true;
+            messages.push(match trigger.action {
+                MidiAction::Note(note) => {
+                    let over = ((level - trigger.threshold_db) / VELOCITY_RANGE_DB).clamp(0.0, 1.0);
+                    note_on(trigger.channel, note, (64.0 + over * 63.0).round() as u8)
+                }
+                MidiAction::Cc(cc) => control_change(trigger.channel, cc, 127),
+            });
+        } else if *held && level < trigger.threshold_db - RELEASE_DB {
+            *held = false;
+            messages.push(release(trigger));
+        }
+    }
+    messages
+}
+
+fn release(trigger: &MidiTrigger) -> [u8; 3] {
+    match trigger.action {
+        MidiAction::Note(note) => note_off(trigger.channel, note),
+        MidiAction::Cc(cc) => control_change(trigger.channel, cc, 0),
+    }
+}
+
+/// Lives in the FFT thread: band levels, trigger state and the port
+#[derive(Default)]
+pub struct MidiEngine {
+    /// Triggers `held` belongs to; an edit releases them before switching
+    triggers: Vec<MidiTrigger>,
+    held: Vec<bool>,
+    /// Center frequency of each bar, for (bar count, sample rate)
+    bar_freqs: Vec<f32>,
+    bar_freqs_for: (usize, u32),
+    output: imp::Output,
+}
+
+impl MidiEngine {
+    /// Run the triggers over this frame's bars and send what they fire
+    pub fn process(&mut self, settings: &MidiSettings, bars: &[f32], sample_rate: u32, fft_size: usize) {
+        if settings.triggers != self.triggers {
+            self.release_all(&settings.port);
+            self.triggers = settings.triggers.clone();
+        }
+        if !settings.enabled || self.triggers.is_empty() {
+            self.release_all(&settings.port);
+            self.output.close();
+            return;
+        }
+
+        if self.bar_freqs_for != (bars.len(), sample_rate) {
+            self.bar_freqs_for = (bars.len(), sample_rate);
+            self.bar_freqs = (0..bars.len())
+                .map(|i| FFTProcessor::calculate_bar_frequency(i, bars.len(), sample_rate, fft_size))
+                .collect();
+        }
+
+        let levels: Vec<f32> = self.triggers.iter().map(|t| self.band_level(bars, t)).collect();
+        let messages = evaluate(&self.triggers, &levels, &mut self.held);
+        if !messages.is_empty() {
+            self.output.send(&settings.port, &messages);
+        }
+    }
+
+    /// Loudest bar whose center is in the trigger's range
+    fn band_level(&self, bars: &[f32], trigger: &MidiTrigger) -> f32 {
+        self.bar_freqs
+            .iter()
+            .zip(bars)
+            .filter(|(f, _)| (trigger.low_hz..=trigger.high_hz).contains(*f))
+            .map(|(_, &db)| db)
+            .fold(crate::shared_state::SILENCE_DB, f32::max)
+    }
+
+    /// Send the offs for everything still on
+    fn release_all(&mut self, port: &str) {
+        let messages: Vec<[u8; 3]> = self
+            .held
+            .iter()
+            .zip(&self.triggers)
+            .filter(|(held, _)| **held)
+            .map(|(_, t)| release(t))
+            .collect();
+        if !messages.is_empty() {
+            self.output.send(port, &messages);
+        }
+        self.held.clear();
+    }
+
+    /// Which triggers are on, for the settings page
+    pub fn held(&self) -> &[bool] {
+        &self.held
+    }
+
+    /// Port currently open, if any
+    pub fn connected_port(&self) -> Option<&str> {
+        self.output.port()
+    }
+}
+
+impl Drop for MidiEngine {
+    /// Don't leave notes hanging in the DAW when BeSpec exits
+    fn drop(&mut self) {
+        if let Some(port) = self.output.port().map(str::to_string) {
+            self.release_all(&port);
+        }
+    }
+}
+
+/// Output port names. Empty without the `midi` feature.
+pub fn list_ports() -> Vec<String> {
+    imp::list_ports()
+}
+
+#[cfg(feature = "midi")]
+mod imp {
+    use midir::{MidiOutput, MidiOutputConnection};
+
+    const CLIENT_NAME: &str = "BeSpec";
+
+    #[derive(Default)]
+    pub struct Output {
+        conn: Option<(String, MidiOutputConnection)>,
+        /// Port that failed to open, not retried until the setting changes
+        failed: Option<String>,
+    }
+
+    impl Output {
+        pub fn send(&mut self, port: &str, messages: &[[u8; 3]]) {
+            if self.conn.as_ref().map_or(true, |(name, _)| name != port) {
+                // Checked before `close`, which forgets the failure
+                if self.failed.as_deref() == Some(port) {
+                    return;
+                }
+                self.close();
+                match open(port) {
+                    Ok(conn) => {
+                        tracing::info!("[MIDI] Sending to '{}'", port);
+                        self.failed = None;
+                        self.conn = Some((port.to_string(), conn));
+                    }
+                    Err(e) => {
+                        tracing::warn!("[MIDI] Couldn't open output '{}': {}", port, e);
+                        self.failed = Some(port.to_string());
+                        return;
+                    }
+                }
+            }
+            let Some((_, conn)) = self.conn.as_mut() else { return };
+            for message in messages {
+                if let Err(e) = conn.send(message) {
+                    tracing::warn!("[MIDI] Send failed: {}", e);
+                    break;
+                }
+            }
+        }
+
+        pub fn close(&mut self) {
+            if let Some((name, conn)) = self.conn.take() {
+                tracing::info!("[MIDI] Closing '{}'", name);
+                conn.close();
+            }
+            self.failed = None;
+        }
+
+        pub fn port(&self) -> Option<&str> {
+            self.conn.as_ref().map(|(name, _)| name.as_str())
+        }
+    }
+
+    fn open(port_name: &str) -> Result<MidiOutputConnection, String> {
+        let midi = MidiOutput::new(CLIENT_NAME).map_err(|e| e.to_string())?;
+        let port = midi
+            .ports()
+            .into_iter()
+            .find(|p| midi.port_name(p).map_or(false, |n| n == port_name))
+            .ok_or_else(|| "no such port".to_string())?;
+        midi.connect(&port, "bespec-out").map_err(|e| e.to_string())
+    }
+
+    pub fn list_ports() -> Vec<String> {
+        match MidiOutput::new(CLIENT_NAME) {
+            Ok(midi) => midi.ports().iter().filter_map(|p| midi.port_name(p).ok()).collect(),
+            Err(e) => {
+                tracing::warn!("[MIDI] Couldn't list outputs: {}", e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "midi"))]
+mod imp {
+    #[derive(Default)]
+    pub struct Output;
+
+    impl Output {
+        pub fn send(&mut self, _port: &str, _messages: &[[u8; 3]]) {}
+        pub fn close(&mut self) {}
+        pub fn port(&self) -> Option<&str> {
+            None
+        }
+    }
+
+    pub fn list_ports() -> Vec<String> {
+        Vec::new()
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages() {
+        assert_eq!(note_on(10, 36, 100), [0x99, 36, 100]);
+        assert_eq!(note_off(1, 60), [0x80, 60, 0]);
+        assert_eq!(control_change(16, 7, 127), [0xBF, 7, 127]);
+        // A note-on with velocity 0 would be a note-off
+        assert_eq!(note_on(1, 36, 0)[2], 1);
+    }
+
+    #[test]
+    fn test_triggers_fire_once_with_hysteresis() {
+        let triggers = vec![
+            MidiTrigger::new("Kick", 40.0, 120.0, -12.0, MidiAction::Note(36)),
+            MidiTrigger { channel: 1, ..MidiTrigger::new("Pad", 200.0, 400.0, -20.0, MidiAction::Cc(20)) },
+        ];
+        let mut held = Vec::new();
+
+        assert!(evaluate(&triggers, &[-30.0, -30.0], &mut held).is_empty());
+        assert_eq!(evaluate(&triggers, &[-12.0, -30.0], &mut held), vec![[0x99, 36, 64]]);
+        // Still over, or just under: nothing new
+        assert!(evaluate(&triggers, &[0.0, -30.0], &mut held).is_empty());
+        assert!(evaluate(&triggers, &[-14.0, -30.0], &mut held).is_empty());
+        assert_eq!(
+            evaluate(&triggers, &[-20.0, 12.0], &mut held),
+            vec![[0x89, 36, 0], [0xB0, 20, 127]]
+        );
+        assert_eq!(held, vec![false, true]);
+    }
+}
@@ -0,0 +1,332 @@
+// src/net.rs
+//
+// Every outbound HTTP request (update check, preset gallery, Wikipedia,
+// album art, ...) goes through here instead of calling ureq directly.
+// Gives us, in one place:
+//   - a shared agent with sane timeouts
+//   - a size cap on responses
+//   - a small in-memory cache for responses that are safe to reuse
+//   - per-host rate limiting (minimum spacing between requests)
+//   - exponential backoff after failures / HTTP 429
+//   - a global "offline mode" switch that refuses all requests
+//
+// Calls are blocking; run them from a worker thread, never the GUI thread.
+//
+// This file has no dependencies on the rest of the crate so the standalone
+// `media_test` harness can pull it in with `#[path]` alongside `media/`.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Sent with every request (GitHub's API refuses requests without one)
+const USER_AGENT: &str = "bespec-client";
+
+/// Minimum spacing between two requests to the same host
+const MIN_HOST_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Backoff after the first failure; doubles per failure up to `MAX_BACKOFF`
+const BASE_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Cached responses kept at most (oldest evicted first)
+const MAX_CACHE_ENTRIES: usize = 32;
+
+/// Set from `AppConfig::offline_mode`
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Turn offline mode on/off. While on, every request fails with `NetError::Offline`.
+pub fn set_offline(offline: bool) {
+    let was = OFFLINE.swap(offline, Ordering::Relaxed);
+    if was != offline {
+        tracing::info!("[Net] Offline mode {}", if offline { "on" } else { "off" });
+    }
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum NetError {
+    #[error("Offline mode is on")]
+    Offline,
+    #[error("Backing off {host} for another {secs}s after earlier failures")]
+    BackingOff { host: String, secs: u64 },
+    #[error("HTTP {status} from {host}")]
+    Status { host: String, status: u16 },
+    #[error("Request to {host} failed: {reason}")]
+    Transport { host: String, reason: String },
+    #[error("Response from {host} is larger than {limit} bytes")]
+    TooLarge { host: String, limit: u64 },
+    #[error("Invalid response: {0}")]
+    Parse(String),
+}
+
+/// One GET request. Build with `Request::get(url)` and the `with_*` methods.
+#[derive(Clone, Debug)]
+pub struct Request {
+    url: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    timeout: Duration,
+    max_bytes: u64,
+    cache_ttl: Option<Duration>,
+}
+
+impl Request {
+    pub fn get(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            query: Vec::new(),
+            headers: Vec::new(),
+            timeout: Duration::from_secs(10),
+            max_bytes: 2 * 1024 * 1024,
+            cache_ttl: None,
+        }
+    }
+
+    /// Add a query parameter (URL-encoded by ureq)
+    pub fn with_query(mut self, key: &str, value: &str) -> Self {
+        self.query.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Refuse responses bigger than this many bytes
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Reuse a successful response for this long instead of asking again
+    pub fn with_cache(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    fn cache_key(&self) -> String {
+        let mut key = self.url.clone();
+        for (k, v) in &self.query {
+            key.push_str(&format!("&{}={}", k, v));
+        }
+        key
+    }
+
+    /// Send the request and return the raw body
+    pub fn send(&self) -> Result<Arc<Vec<u8>>, NetError> {
+        manager().send(self)
+    }
+
+    /// Send the request and deserialize the JSON body
+    pub fn send_json<T: serde::de::DeserializeOwned>(&self) -> Result<T, NetError> {
+        let body = self.send()?;
+        serde_json::from_slice(&body).map_err(|e| NetError::Parse(e.to_string()))
+    }
+}
+
+// ====================================================================================
+// Manager
+// ====================================================================================
+
+#[derive(Default)]
+struct HostState {
+    /// Earliest time the next request to this host may go out
+    next_allowed: Option<Instant>,
+    /// Consecutive failures, drives the backoff length
+    failures: u32,
+    /// Requests are refused until then
+    blocked_until: Option<Instant>,
+}
+
+struct CacheEntry {
+    stored: Instant,
+    ttl: Duration,
+    body: Arc<Vec<u8>>,
+}
+
+#[derive(Default)]
+struct Bookkeeping {
+    hosts: HashMap<String, HostState>,
+    cache: HashMap<String, CacheEntry>,
+}
+
+struct Manager {
+    agent: ureq::Agent,
+    books: Mutex<Bookkeeping>,
+}
+
+fn manager() -> &'static Manager {
+    static MANAGER: OnceLock<Manager> = OnceLock::new();
+    MANAGER.get_or_init(|| Manager {
+        agent: ureq::AgentBuilder::new().user_agent(USER_AGENT).build(),
+        books: Mutex::new(Bookkeeping::default()),
+    })
+}
+
+impl Manager {
+    fn send(&self, req: &Request) -> Result<Arc<Vec<u8>>, NetError> {
+        if is_offline() {
+            return Err(NetError::Offline);
+        }
+        let host = host_of(&req.url).to_string();
+        let key = req.cache_key();
+
+        // Bookkeeping only under the lock, never the network call itself
+        let wait = {
+            let mut books = self.books.lock().unwrap_or_else(|p| p.into_inner());
+            let now = Instant::now();
+
+            if let Some(hit) = books.cache.get(&key) {
+                if now.duration_since(hit.stored) < hit.ttl {
+                    tracing::debug!("[Net] Cache hit: {}", key);
+                    return Ok(Arc::clone(&hit.body));
+                }
+            }
+
+            let state = books.hosts.entry(host.clone()).or_default();
+            if let Some(until) = state.blocked_until.filter(|u| *u > now) {
+                return Err(NetError::BackingOff {
+                    host,
+                    secs: until.duration_since(now).as_secs().max(1),
+                });
+            }
+
+            let slot = state.next_allowed.map_or(now, |t| t.max(now));
+            state.next_allowed = Some(slot + MIN_HOST_INTERVAL);
+            slot.duration_since(now)
+        };
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+
+        let result = self.fetch(req, &host);
+
+        let mut books = self.books.lock().unwrap_or_else(|p| p.into_inner());
+        let state = books.hosts.entry(host.clone()).or_default();
+        match &result {
+            Ok(body) => {
+                state.failures = 0;
+                state.blocked_until = None;
+                if let Some(ttl) = req.cache_ttl {
+                    store_in_cache(&mut books.cache, key, ttl, Arc::clone(body), Instant::now());
+                }
+            }
+            // Our own limits, not the server's fault
+            Err(NetError::TooLarge { .. }) | Err(NetError::Parse(_)) => {}
+            Err(e) => {
+                state.failures += 1;
+                let backoff = backoff_for(state.failures);
+                state.blocked_until = Some(Instant::now() + backoff);
+                tracing::warn!("[Net] {} (backing off {}s)", e, backoff.as_secs());
+            }
+        }
+        result
+    }
+
+    fn fetch(&self, req: &Request, host: &str) -> Result<Arc<Vec<u8>>, NetError> {
+        let mut call = self.agent.get(&req.url).timeout(req.timeout);
+        for (k, v) in &req.query {
+            call = call.query(k, v);
+        }
+        for (k, v) in &req.headers {
+            call = call.set(k, v);
+        }
+
+        let resp = match call.call() {
+            Ok(resp) => resp,
+            Err(ureq::Error::Status(status, _)) => {
+                return Err(NetError::Status { host: host.to_string(), status });
+            }
+            Err(e) => {
+                return Err(NetError::Transport { host: host.to_string(), reason: e.to_string() });
+            }
+        };
+
+        // Read one byte past the limit so "exactly at the limit" still passes
+        let mut body = Vec::new();
+        resp.into_reader()
+            .take(req.max_bytes + 1)
+            .read_to_end(&mut body)
+            .map_err(|e| NetError::Transport { host: host.to_string(), reason: e.to_string() })?;
+        if body.len() as u64 > req.max_bytes {
+            return Err(NetError::TooLarge { host: host.to_string(), limit: req.max_bytes });
+        }
+        Ok(Arc::new(body))
+    }
+}
+
+fn store_in_cache(
+    cache: &mut HashMap<String, CacheEntry>,
+    key: String,
+    ttl: Duration,
+    body: Arc<Vec<u8>>,
+    now: Instant,
+) {
+    if cache.len() >= MAX_CACHE_ENTRIES && !cache.contains_key(&key) {
+        let oldest = cache.iter().min_by_key(|(_, e)| e.stored).map(|(k, _)| k.clone());
+        if let Some(oldest) = oldest {
+            cache.remove(&oldest);
+        }
+    }
+    cache.insert(key, CacheEntry { stored: now, ttl, body });
+}
+
+/// 2s, 4s, 8s ... capped at 5 minutes
+fn backoff_for(failures: u32) -> Duration {
+    let factor = 1u32 << failures.saturating_sub(1).min(16);
+    (BASE_BACKOFF * factor).min(MAX_BACKOFF)
+}
+
+/// "https://en.wikipedia.org/w/api.php" -> "en.wikipedia.org"
+fn host_of(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    rest.split(['/', '?', '#']).next().unwrap_or(rest)
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_of() {
+        assert_eq!(host_of("https://en.wikipedia.org/w/api.php"), "en.wikipedia.org");
+        assert_eq!(host_of("http://localhost:8080?x=1"), "localhost:8080");
+        assert_eq!(host_of("api.github.com/repos"), "api.github.com");
+    }
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        assert_eq!(backoff_for(1), Duration::from_secs(2));
+        assert_eq!(backoff_for(2), Duration::from_secs(4));
+        assert_eq!(backoff_for(3), Duration::from_secs(8));
+        assert_eq!(backoff_for(40), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_cache_evicts_oldest() {
+        let mut cache = HashMap::new();
+        let start = Instant::now();
+        for i in 0..=MAX_CACHE_ENTRIES {
+            let now = start + Duration::from_millis(i as u64);
+            store_in_cache(&mut cache, format!("k{}", i), Duration::from_secs(60), Arc::new(vec![]), now);
+        }
+        assert_eq!(cache.len(), MAX_CACHE_ENTRIES);
+        assert!(!cache.contains_key("k0"));
+        assert!(cache.contains_key(&format!("k{}", MAX_CACHE_ENTRIES)));
+    }
+}
@@ -0,0 +1,163 @@
+//! OSC output of the spectral features (centroid, rolloff, flatness), for
+//! generative-art tools like TouchDesigner, Max or Processing that want to
+//! follow how bright the music is rather than how loud.
+//!
+//! The FFT thread hands every frame's features to `OscSender`, which sends
+//! them over UDP at most `SEND_RATE_HZ` times a second, one message per
+//! feature with a single float argument:
+//!
+//!   /bespec/centroid  f  (Hz)
+//!   /bespec/rolloff   f  (Hz)
+//!   /bespec/flatness  f  (0..1)
+//!
+//! Nothing is sent while it's switched off. The target is a literal
+//! `ip:port`: looking up a host name could block the FFT thread for
+//! seconds. One that doesn't parse is logged once and retried when the
+//! setting changes.
+
+use std::net::{SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::fft_processor::SpectralFeatures;
+
+/// Most feature messages a second; a visual doesn't need every FFT frame
+const SEND_RATE_HZ: u32 = 30;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct OscOutSettings {
+    pub enabled: bool,
+    /// Where to send, "ip:port"
+    pub target: String,
+}
+
+impl Default for OscOutSettings {
+    fn default() -> Self {
+        // 9000 is the OSC input's default port
+        Self { enabled: false, target: "127.0.0.1:9001".to_string() }
+    }
+}
+
+/// Lives in the FFT thread
+#[derive(Default)]
+pub struct OscSender {
+    socket: Option<UdpSocket>,
+    /// Target as configured, and the address it parsed to
+    target: String,
+    addr: Option<SocketAddr>,
+    next_send: Option<Instant>,
+}
+
+impl OscSender {
+    /// Send this frame's features if switched on and due
+    pub fn process(&mut self, settings: &OscOutSettings, features: &SpectralFeatures, now: Instant) {
+        if !settings.enabled {
+            self.socket = None;
+            return;
+        }
+        if self.next_send.is_some_and(|t| now < t) {
+            return;
+        }
+        self.next_send = Some(now + Duration::from_secs(1) / SEND_RATE_HZ);
+
+        if self.target != settings.target {
+            self.target = settings.target.clone();
+            self.addr = self.target.trim().parse().ok();
+            if self.addr.is_none() {
+                tracing::warn!("[OSC] Can't send to '{}': not an ip:port", self.target);
+            }
+        }
+        let Some(addr) = self.addr else { return };
+
+        if self.socket.is_none() {
+            let bind = if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+            match UdpSocket::bind(bind) {
+                Ok(socket) => self.socket = Some(socket),
+                Err(e) => {
+                    tracing::warn!("[OSC] Couldn't open a socket: {}", e);
+                    return;
+                }
+            }
+        }
+        let Some(socket) = &self.socket else { return };
+        for (address, value) in [
+            ("/bespec/centroid", features.centroid_hz),
+            ("/bespec/rolloff", features.rolloff_hz),
+            ("/bespec/flatness", features.flatness),
+        ] {
+            // Nobody listening is fine, UDP doesn't care
+            let _ = socket.send_to(&osc_message(address, value), addr);
+        }
+    }
+}
+
+/// An OSC message with one float argument
+pub fn osc_message(address: &str, value: f32) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(address.len() + 12);
+    push_osc_string(&mut packet, address);
+    push_osc_string(&mut packet, ",f");
+    packet.extend_from_slice(&value.to_be_bytes());
+    packet
+}
+
+/// Null-terminated and padded to 4 bytes
+fn push_osc_string(packet: &mut Vec<u8>, text: &str) {
+    packet.extend_from_slice(text.as_bytes());
+    let padded = (text.len() + 4) & !3;
+    packet.resize(packet.len() + padded - text.len(), 0);
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote_control::{parse_osc, RemoteSource};
+
+    #[test]
+    fn test_message_round_trips_through_the_parser() {
+        let packet = osc_message("/bespec/flatness", 0.25);
+        assert_eq!(packet.len() % 4, 0);
+        let event = parse_osc(&packet).unwrap();
+        assert_eq!(event.source, RemoteSource::Osc("/bespec/flatness".to_string()));
+        assert_eq!(event.value, 0.25);
+    }
+
+    #[test]
+    fn test_sends_features_when_enabled() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let settings = OscOutSettings { enabled: true, target: receiver.local_addr().unwrap().to_string() };
+        let features = SpectralFeatures { centroid_hz: 2000.0, rolloff_hz: 6000.0, flatness: 0.1 };
+
+        let mut sender = OscSender::default();
+        let now = Instant::now();
+        sender.process(&settings, &features, now);
+        // Too soon for another round
+        sender.process(&settings, &features, now + Duration::from_millis(5));
+
+        let mut buf = [0u8; 64];
+        let mut addresses = Vec::new();
+        for _ in 0..3 {
+            let n = receiver.recv(&mut buf).unwrap();
+            addresses.push(String::from_utf8_lossy(&buf[..n]).split('\0').next().unwrap().to_string());
+        }
+        assert_eq!(addresses, ["/bespec/centroid", "/bespec/rolloff", "/bespec/flatness"]);
+        receiver.set_nonblocking(true).unwrap();
+        assert!(receiver.recv(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_host_names_are_not_looked_up() {
+        let settings = OscOutSettings { enabled: true, target: "localhost:9001".to_string() };
+        let mut sender = OscSender::default();
+        sender.process(&settings, &SpectralFeatures::default(), Instant::now());
+        assert_eq!(sender.addr, None);
+
+        let settings = OscOutSettings { enabled: true, target: " [::1]:9001".to_string() };
+        sender.process(&settings, &SpectralFeatures::default(), Instant::now() + Duration::from_secs(1));
+        assert_eq!(sender.addr, Some("[::1]:9001".parse().unwrap()));
+    }
+}
@@ -0,0 +1,188 @@
+// src/paths.rs
+//
+// One place that decides where BeSpec keeps its files on disk.
+//
+// By default everything lives in the standard OS directories (see README).
+// With `--user <name>` the config + presets move into a per-user subfolder,
+// so a shared HTPC can keep a separate setup for each person:
+//
+//   <config_dir>/users/<name>/config.json
+//   <data_dir>/users/<name>/presets/{colors,visuals}/
+//
+// Logs are diagnostics, not "setup", so they always stay in the shared root.
+//
+// Portable mode (`--portable`, or a `portable.flag` file next to the binary)
+// swaps both roots for a `config/` folder next to the executable, so BeSpec
+// can run off a USB stick without touching the OS dirs.
+
+use directories::ProjectDirs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Resolved directories for this run (set once at startup)
+#[derive(Debug, Clone)]
+pub struct AppPaths {
+    /// Active user name, `None` means the default (shared) setup
+    pub user: Option<String>,
+    /// Running in portable mode (everything next to the executable)
+    pub portable: bool,
+    /// Where config.json lives
+    pub config_dir: PathBuf,
+    /// Where presets/ live
+    pub data_dir: PathBuf,
+    /// Where logs/ live (never user-scoped)
+    pub log_dir: PathBuf,
+}
+
+static PATHS: OnceLock<AppPaths> = OnceLock::new();
+
+impl AppPaths {
+    /// Resolve paths for an (optional) named user
+    pub fn resolve(user: Option<&str>, portable: bool) -> Self {
+        if portable {
+            if let Some(root) = portable_root() {
+                let mut paths = Self::from_roots(&root, &root, user);
+                paths.portable = true;
+                return paths;
+            }
+            eprintln!("[Paths] Portable mode requested but the executable folder is unknown, using OS dirs");
+        }
+
+        let (config_root, data_root) = match ProjectDirs::from("", "", "BeSpec") {
+            Some(proj_dirs) => (
+                proj_dirs.config_dir().to_path_buf(),
+                proj_dirs.data_dir().to_path_buf(),
+            ),
+            // Fallback to local directory if we can't find the home folder
+            None => (PathBuf::from("."), PathBuf::from(".")),
+        };
+
+        Self::from_roots(&config_root, &data_root, user)
+    }
+
+    fn from_roots(config_root: &Path, data_root: &Path, user: Option<&str>) -> Self {
+        let user = user
+            .map(sanitize_user_name)
+            .filter(|u| !u.is_empty());
+
+        let (config_dir, data_dir) = match &user {
+            Some(name) => (
+                config_root.join("users").join(name),
+                data_root.join("users").join(name),
+            ),
+            None => (config_root.to_path_buf(), data_root.to_path_buf()),
+        };
+
+        Self {
+            user,
+            portable: false,
+            config_dir,
+            data_dir,
+            log_dir: data_root.join("logs"),
+        }
+    }
+
+    /// Preset folder for a kind ("colors" / "visuals")
+    pub fn preset_dir(&self, kind: &str) -> PathBuf {
+        self.data_dir.join("presets").join(kind)
+    }
+}
+
+/// Folder holding the running executable
+fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|p| p.to_path_buf()))
+}
+
+/// `<exe dir>/config`, the root used in portable mode
+fn portable_root() -> Option<PathBuf> {
+    exe_dir().map(|dir| dir.join("config"))
+}
+
+/// True if a `portable.flag` file sits next to the executable
+pub fn portable_flag_present() -> bool {
+    exe_dir().map_or(false, |dir| dir.join("portable.flag").exists())
+}
+
+/// Set the active paths. Call once from `main()` before anything touches disk.
+/// Later calls are ignored (first one wins).
+pub fn init(paths: AppPaths) {
+    if PATHS.set(paths).is_err() {
+        tracing::warn!("[Paths] init() called twice, keeping the first value");
+    }
+}
+
+/// The active paths (defaults to the shared setup if `init` was never called, e.g. in tests)
+pub fn get() -> &'static AppPaths {
+    PATHS.get_or_init(|| AppPaths::resolve(None, false))
+}
+
+/// List the named users that already have a config folder
+pub fn list_users(portable: bool) -> Vec<String> {
+    let root = AppPaths::resolve(None, portable).config_dir.join("users");
+    let mut users: Vec<String> = std::fs::read_dir(root)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_dir())
+                .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    users.sort();
+    users
+}
+
+/// Keep user names safe to use as a folder name
+fn sanitize_user_name(name: &str) -> String {
+    name.trim()
+        .replace(' ', "_")
+        .replace(|c: char| !c.is_alphanumeric() && c != '_' && c != '-', "")
+        .to_lowercase()
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_user_uses_roots() {
+        let p = AppPaths::from_roots(Path::new("/cfg"), Path::new("/data"), None);
+        assert_eq!(p.config_dir, PathBuf::from("/cfg"));
+        assert_eq!(p.preset_dir("colors"), PathBuf::from("/data/presets/colors"));
+        assert_eq!(p.log_dir, PathBuf::from("/data/logs"));
+        assert!(!p.portable);
+    }
+
+    #[test]
+    fn test_portable_roots_share_one_folder() {
+        // Portable mode passes the same root for config and data
+        let root = Path::new("/usb/BeSpec/config");
+        let p = AppPaths::from_roots(root, root, None);
+        assert_eq!(p.config_dir.join("config.json"), PathBuf::from("/usb/BeSpec/config/config.json"));
+        assert_eq!(p.preset_dir("colors"), PathBuf::from("/usb/BeSpec/config/presets/colors"));
+        assert_eq!(p.log_dir, PathBuf::from("/usb/BeSpec/config/logs"));
+    }
+
+    #[test]
+    fn test_named_user_is_scoped() {
+        let p = AppPaths::from_roots(Path::new("/cfg"), Path::new("/data"), Some("Living Room"));
+        assert_eq!(p.user.as_deref(), Some("living_room"));
+        assert_eq!(p.config_dir, PathBuf::from("/cfg/users/living_room"));
+        assert_eq!(p.preset_dir("visuals"), PathBuf::from("/data/users/living_room/presets/visuals"));
+        // Logs are shared
+        assert_eq!(p.log_dir, PathBuf::from("/data/logs"));
+    }
+
+    #[test]
+    fn test_user_name_cannot_escape() {
+        let p = AppPaths::from_roots(Path::new("/cfg"), Path::new("/data"), Some("../../etc"));
+        assert_eq!(p.config_dir, PathBuf::from("/cfg/users/etc"));
+
+        let p = AppPaths::from_roots(Path::new("/cfg"), Path::new("/data"), Some("  "));
+        assert!(p.user.is_none());
+    }
+}
@@ -0,0 +1,239 @@
+//! "Peek away": a system-wide hotkey that minimizes the window and brings it
+//! back by itself a set number of seconds later, or when the next track
+//! starts, whichever comes first. Pressing the hotkey again while away
+//! restores it early.
+//!
+//! The hotkey is Ctrl+Shift+<key> and works while another app has focus
+//! (Windows, macOS and X11; Wayland doesn't let apps grab keys). It goes
+//! through `global-hotkey`, which on Windows and macOS has to be set up on
+//! the thread running the event loop, so the GUI owns it. Presses arrive on
+//! another thread and wake the GUI, which is minimized and idle by then.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+use global_hotkey::hotkey::{Code, HotKey, Modifiers};
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct PeekSettings {
+    /// Register the hotkey (off by default: it takes the combination away
+    /// from every other app)
+    pub enabled: bool,
+    /// Used with Ctrl+Shift
+    pub key: egui::Key,
+    /// Come back after this long; 0 waits for the track change or the hotkey
+    pub restore_after_sec: f32,
+    /// Come back when the next track starts
+    pub restore_on_track: bool,
+}
+
+impl Default for PeekSettings {
+    fn default() -> Self {
+        Self { enabled: false, key: egui::Key::H, restore_after_sec: 15.0, restore_on_track: true }
+    }
+}
+
+/// What the window should do this frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeekAction {
+    Minimize,
+    Restore,
+}
+
+/// Why we're away, to know when to come back
+#[derive(Clone, Debug, PartialEq)]
+struct Away {
+    until: Option<Instant>,
+    /// Track playing when we left, "Artist - Title"
+    track: Option<String>,
+    /// The window has been seen minimized (the command takes a frame or two),
+    /// so seeing it restored after that means the user brought it back
+    seen_minimized: bool,
+}
+
+#[derive(Default)]
+pub struct PeekAway {
+    hotkey: Option<Registration>,
+    /// Key the settings last asked for, registered or not, so a combination
+    /// another app holds isn't tried again every frame
+    synced: Option<egui::Key>,
+    away: Option<Away>,
+}
+
+struct Registration {
+    manager: GlobalHotKeyManager,
+    hotkey: HotKey,
+    pressed: Arc<AtomicBool>,
+}
+
+impl PeekAway {
+    /// Register, move or drop the hotkey to match `settings`
+    pub fn sync(&mut self, ctx: &egui::Context, settings: PeekSettings) {
+        let wanted = settings.enabled.then_some(settings.key);
+        if self.synced == wanted {
+            return;
+        }
+        self.synced = wanted;
+        if let Some(old) = self.hotkey.take() {
+            let _ = old.manager.unregister(old.hotkey);
+        }
+        let Some(key) = wanted else { return };
+        match register(ctx, key) {
+            Ok(registration) => {
+                tracing::info!("[Peek] Hotkey Ctrl+Shift+{:?} registered", key);
+                self.hotkey = Some(registration);
+            }
+            Err(e) => tracing::warn!("[Peek] ⚠️ Can't register Ctrl+Shift+{:?}: {}", key, e),
+        }
+    }
+
+    pub fn is_away(&self) -> bool {
+        self.away.is_some()
+    }
+
+    /// Once per frame: act on a hotkey press and check the way back.
+    /// `track` is what's playing now, `minimized` the window's state.
+    pub fn poll(&mut self, settings: PeekSettings, track: Option<String>, minimized: bool, now: Instant) -> Option<PeekAction> {
+        let pressed = self.hotkey.as_ref().is_some_and(|r| r.pressed.swap(false, Ordering::Relaxed));
+        self.step(settings, pressed, track, minimized, now)
+    }
+
+    fn step(&mut self, settings: PeekSettings, pressed: bool, track: Option<String>, minimized: bool, now: Instant) -> Option<PeekAction> {
+        let Some(away) = self.away.as_mut() else {
+            if !pressed {
+                return None;
+            }
+            let secs = settings.restore_after_sec;
+            self.away = Some(Away {
+                until: (secs > 0.0).then(|| now + Duration::from_secs_f32(secs)),
+                track,
+                seen_minimized: false,
+            });
+            return Some(PeekAction::Minimize);
+        };
+
+        away.seen_minimized |= minimized;
+        if away.seen_minimized && !minimized {
+            // Restored by hand
+            self.away = None;
+            return None;
+        }
+        let timed_out = away.until.is_some_and(|until| now >= until);
+        let next_track = settings.restore_on_track && track.is_some() && track != away.track;
+        if pressed || timed_out || next_track {
+            self.away = None;
+            return Some(PeekAction::Restore);
+        }
+        None
+    }
+
+    /// How long until the timer brings the window back
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.away.as_ref()?.until.map(|until| until.saturating_duration_since(now))
+    }
+}
+
+fn register(ctx: &egui::Context, key: egui::Key) -> Result<Registration, String> {
+    let code = key_code(key).ok_or_else(|| format!("{:?} can't be used for a global hotkey", key))?;
+    let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), code);
+    let manager = GlobalHotKeyManager::new().map_err(|e| e.to_string())?;
+    manager.register(hotkey).map_err(|e| e.to_string())?;
+
+    let pressed = Arc::new(AtomicBool::new(false));
+    let flag = pressed.clone();
+    let ctx = ctx.clone();
+    let id = hotkey.id();
+    GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+        if event.id == id && event.state == HotKeyState::Pressed {
+            flag.store(true, Ordering::Relaxed);
+            ctx.request_repaint();
+        }
+    }));
+    Ok(Registration { manager, hotkey, pressed })
+}
+
+/// The physical key for an egui key: letters, digits and F1-F12
+fn key_code(key: egui::Key) -> Option<Code> {
+    use egui::Key as K;
+    Some(match key {
+        K::A => Code::KeyA, K::B => Code::KeyB, K::C => Code::KeyC, K::D => Code::KeyD,
+        K::E => Code::KeyE, K::F => Code::KeyF, K::G => Code::KeyG, K::H => Code::KeyH,
+        K::I => Code::KeyI, K::J => Code::KeyJ, K::K => Code::KeyK, K::L => Code::KeyL,
+        K::M => Code::KeyM, K::N => Code::KeyN, K::O => Code::KeyO, K::P => Code::KeyP,
+        K::Q => Code::KeyQ, K::R => Code::KeyR, K::S => Code::KeyS, K::T => Code::KeyT,
+        K::U => Code::KeyU, K::V => Code::KeyV, K::W => Code::KeyW, K::X => Code::KeyX,
+        K::Y => Code::KeyY, K::Z => Code::KeyZ,
+        K::Num0 => Code::Digit0, K::Num1 => Code::Digit1, K::Num2 => Code::Digit2, K::Num3 => Code::Digit3,
+        K::Num4 => Code::Digit4, K::Num5 => Code::Digit5, K::Num6 => Code::Digit6, K::Num7 => Code::Digit7,
+        K::Num8 => Code::Digit8, K::Num9 => Code::Digit9,
+        K::F1 => Code::F1, K::F2 => Code::F2, K::F3 => Code::F3, K::F4 => Code::F4,
+        K::F5 => Code::F5, K::F6 => Code::F6, K::F7 => Code::F7, K::F8 => Code::F8,
+        K::F9 => Code::F9, K::F10 => Code::F10, K::F11 => Code::F11, K::F12 => Code::F12,
+        _ => return None,
+    })
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(name: &str) -> Option<String> {
+        Some(name.to_string())
+    }
+
+    #[test]
+    fn test_timer_brings_the_window_back() {
+        let mut peek = PeekAway::default();
+        let settings = PeekSettings { restore_on_track: false, ..Default::default() };
+        let start = Instant::now();
+
+        assert_eq!(peek.step(settings, true, track("a"), false, start), Some(PeekAction::Minimize));
+        assert_eq!(peek.step(settings, false, track("a"), true, start + Duration::from_secs(14)), None);
+        assert_eq!(peek.step(settings, false, track("b"), true, start + Duration::from_secs(15)), Some(PeekAction::Restore));
+        assert!(!peek.is_away());
+    }
+
+    #[test]
+    fn test_next_track_or_hotkey_bring_it_back_early() {
+        let settings = PeekSettings { restore_after_sec: 0.0, ..Default::default() };
+        let now = Instant::now();
+
+        let mut peek = PeekAway::default();
+        peek.step(settings, true, track("a"), false, now);
+        assert_eq!(peek.remaining(now), None);
+        assert_eq!(peek.step(settings, false, None, true, now), None);
+        assert_eq!(peek.step(settings, false, track("b"), true, now), Some(PeekAction::Restore));
+
+        let mut peek = PeekAway::default();
+        peek.step(settings, true, None, false, now);
+        assert_eq!(peek.step(settings, true, None, true, now), Some(PeekAction::Restore));
+    }
+
+    #[test]
+    fn test_restoring_by_hand_ends_the_peek() {
+        let mut peek = PeekAway::default();
+        let settings = PeekSettings::default();
+        let now = Instant::now();
+        peek.step(settings, true, None, false, now);
+        // Not minimized yet on the next frame: still away
+        assert_eq!(peek.step(settings, false, None, false, now), None);
+        assert!(peek.is_away());
+        peek.step(settings, false, None, true, now);
+        assert_eq!(peek.step(settings, false, None, false, now), None);
+        assert!(!peek.is_away());
+    }
+
+    #[test]
+    fn test_key_codes() {
+        assert_eq!(key_code(egui::Key::H), Some(Code::KeyH));
+        assert_eq!(key_code(egui::Key::F12), Some(Code::F12));
+        assert_eq!(key_code(egui::Key::Escape), None);
+    }
+}
@@ -0,0 +1,173 @@
+//! The visualizer plugin ABI, shared by BeSpec and plugin crates.
+//!
+//! A plugin is a dynamic library (`cdylib`) in the `plugins/` folder that
+//! exports [`ENTRY_SYMBOL`], returning a pointer to a static [`PluginVTable`].
+//! Rust has no stable ABI between separately compiled crates, so everything
+//! that crosses the boundary is `#[repr(C)]` with `extern "C"` functions, and
+//! the vtable starts with [`API_VERSION`] so BeSpec can refuse a plugin built
+//! for a different layout instead of crashing.
+//!
+//! Plugin authors don't write any of that by hand: implement
+//! [`VisualizerPlugin`] and build the vtable with [`PluginVTable::of`]. This
+//! file only depends on `std`, so a plugin crate can include it directly
+//! (`#[path = "…/plugin_api.rs"] mod bespec;`). See `docs/plugins.md`.
+//!
+//! Drawing uses the same conventions as scripts: coordinates go from 0 to 1
+//! across the visualizer area, circle radii are a fraction of its height and
+//! colors are `0xRRGGBBAA`.
+
+// BeSpec only uses the host side; `of` and the trait shims are for plugins
+#![allow(dead_code)]
+
+use std::ffi::{c_char, c_void};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Bumped whenever `PluginVTable` or `PaintHost` change layout
+pub const API_VERSION: u32 = 1;
+/// The function every plugin exports: `extern "C" fn() -> *const PluginVTable`
+pub const ENTRY_SYMBOL: &[u8] = b"bespec_plugin_v1\0";
+
+/// What BeSpec calls `init` with
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PluginInfo {
+    /// Capture sample rate in Hz
+    pub sample_rate: u32,
+}
+
+/// A native visualizer. One instance is created per window that shows it,
+/// and it is only ever called from that window's GUI thread.
+pub trait VisualizerPlugin: Default + Send + 'static {
+    /// Called once, before the first `process`
+    fn init(&mut self, _info: &PluginInfo) {}
+
+    /// New audio for this frame: bar levels from 0 (floor) to 1 (full scale),
+    /// bass first, and the latest waveform samples (-1..1). `dt` is the time
+    /// since the last call in seconds.
+    fn process(&mut self, bars: &[f32], waveform: &[f32], dt: f32);
+
+    /// Draw the current frame
+    fn paint(&self, canvas: &mut Canvas);
+}
+
+/// Drawing callbacks BeSpec passes to `paint`
+#[repr(C)]
+pub struct PaintHost {
+    pub ctx: *mut c_void,
+    /// Size of the visualizer area in pixels
+    pub width: f32,
+    pub height: f32,
+    pub line: extern "C" fn(ctx: *mut c_void, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: u32),
+    pub rect: extern "C" fn(ctx: *mut c_void, x: f32, y: f32, w: f32, h: f32, color: u32),
+    pub circle: extern "C" fn(ctx: *mut c_void, x: f32, y: f32, radius: f32, color: u32),
+}
+
+/// The safe side of `PaintHost`, handed to `VisualizerPlugin::paint`
+pub struct Canvas<'a> {
+    host: &'a PaintHost,
+}
+
+impl Canvas<'_> {
+    pub fn width(&self) -> f32 {
+        self.host.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.host.height
+    }
+
+    /// A line, `thickness` in pixels
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: u32) {
+        (self.host.line)(self.host.ctx, x1, y1, x2, y2, thickness, color);
+    }
+
+    /// A filled rectangle
+    pub fn rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: u32) {
+        (self.host.rect)(self.host.ctx, x, y, w, h, color);
+    }
+
+    /// A filled circle, `radius` as a fraction of the height
+    pub fn circle(&mut self, x: f32, y: f32, radius: f32, color: u32) {
+        (self.host.circle)(self.host.ctx, x, y, radius, color);
+    }
+}
+
+/// The table a plugin exports. All function pointers take the instance
+/// returned by `create`.
+#[repr(C)]
+pub struct PluginVTable {
+    pub api_version: u32,
+    /// Name shown in the Mode combo, nul-terminated UTF-8
+    pub name: *const c_char,
+    pub create: extern "C" fn() -> *mut c_void,
+    pub destroy: extern "C" fn(instance: *mut c_void),
+    pub init: extern "C" fn(instance: *mut c_void, info: *const PluginInfo),
+    pub process: extern "C" fn(instance: *mut c_void, bars: *const f32, bar_count: usize, wave: *const f32, wave_len: usize, dt: f32),
+    pub paint: extern "C" fn(instance: *mut c_void, host: *const PaintHost),
+}
+
+// Only ever points at static data
+unsafe impl Sync for PluginVTable {}
+
+impl PluginVTable {
+    /// The vtable for `T`. `name` must end in `\0`:
+    ///
+    /// ```ignore
+    /// static PLUGIN: PluginVTable = PluginVTable::of::<Rings>("Rings\0");
+    ///
+    /// #[no_mangle]
+    /// pub extern "C" fn bespec_plugin_v1() -> *const PluginVTable {
+    ///     &PLUGIN
+    /// }
+    /// ```
+    pub const fn of<T: VisualizerPlugin>(name: &'static str) -> Self {
+        Self {
+            api_version: API_VERSION,
+            name: name.as_ptr() as *const c_char,
+            create: create::<T>,
+            destroy: destroy::<T>,
+            init: init::<T>,
+            process: process::<T>,
+            paint: paint::<T>,
+        }
+    }
+}
+
+// A panic must not unwind into BeSpec, so each shim stops it at the boundary
+// and that frame is simply skipped.
+
+extern "C" fn create<T: VisualizerPlugin>() -> *mut c_void {
+    catch_unwind(|| Box::into_raw(Box::new(T::default())) as *mut c_void).unwrap_or(std::ptr::null_mut())
+}
+
+extern "C" fn destroy<T: VisualizerPlugin>(instance: *mut c_void) {
+    if !instance.is_null() {
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(unsafe { Box::from_raw(instance as *mut T) })));
+    }
+}
+
+extern "C" fn init<T: VisualizerPlugin>(instance: *mut c_void, info: *const PluginInfo) {
+    let (Some(plugin), Some(info)) = (unsafe { (instance as *mut T).as_mut() }, unsafe { info.as_ref() }) else { return };
+    let _ = catch_unwind(AssertUnwindSafe(|| plugin.init(info)));
+}
+
+extern "C" fn process<T: VisualizerPlugin>(instance: *mut c_void, bars: *const f32, bar_count: usize, wave: *const f32, wave_len: usize, dt: f32) {
+    let Some(plugin) = (unsafe { (instance as *mut T).as_mut() }) else { return };
+    let bars = unsafe { slice(bars, bar_count) };
+    let wave = unsafe { slice(wave, wave_len) };
+    let _ = catch_unwind(AssertUnwindSafe(|| plugin.process(bars, wave, dt)));
+}
+
+extern "C" fn paint<T: VisualizerPlugin>(instance: *mut c_void, host: *const PaintHost) {
+    let (Some(plugin), Some(host)) = (unsafe { (instance as *const T).as_ref() }, unsafe { host.as_ref() }) else { return };
+    let _ = catch_unwind(AssertUnwindSafe(|| plugin.paint(&mut Canvas { host })));
+}
+
+/// `std::slice::from_raw_parts`, but empty for a null pointer
+unsafe fn slice<'a>(ptr: *const f32, len: usize) -> &'a [f32] {
+    if ptr.is_null() || len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(ptr, len)
+    }
+}
@@ -0,0 +1,90 @@
+// src/preset_gallery.rs
+//
+// Optional community preset gallery.
+// Fetches a small JSON index (by default from a GitHub repo) that lists
+// shareable themes in the same layout as `.bespec-theme` files:
+//
+// {
+//   "themes": [
+//     { "author": "someone", "description": "...", "theme": { <ThemeFile> } }
+//   ]
+// }
+//
+// Only runs when the user opens the gallery, never in the background.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::theme_file::ThemeFile;
+
+/// Default gallery index (can be changed in the gallery panel)
+pub(crate) const DEFAULT_GALLERY_URL: &str =
+    "https://raw.githubusercontent.com/BeSpec-Dev/bespec-presets/main/index.json";
+
+/// Refuse absurdly large indexes (bytes)
+const MAX_INDEX_SIZE: u64 = 2 * 1024 * 1024;
+
+/// Re-fetching within this window reuses the last download
+const INDEX_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// One downloadable theme in the gallery
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct GalleryEntry {
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub description: String,
+    pub theme: ThemeFile,
+}
+
+#[derive(Deserialize)]
+struct GalleryIndex {
+    themes: Vec<GalleryEntry>,
+}
+
+/// Progress of a gallery fetch, polled by the GUI
+#[derive(Clone, Debug, Default)]
+pub enum GalleryStatus {
+    #[default]
+    Idle,
+    Loading,
+    Ready(Vec<GalleryEntry>),
+    Failed(String),
+}
+
+/// Download and parse the gallery index. Blocking - call from a worker thread.
+#[must_use = "gallery fetch result should be handled"]
+pub(crate) fn fetch_gallery(url: &str) -> Result<Vec<GalleryEntry>> {
+    let index: GalleryIndex = crate::net::Request::get(url)
+        .with_max_bytes(MAX_INDEX_SIZE)
+        .with_cache(INDEX_CACHE_TTL)
+        .send_json()
+        .with_context(|| format!("Failed to fetch preset gallery from {}", url))?;
+
+    tracing::info!("[Gallery] Fetched {} themes from {}", index.themes.len(), url);
+    Ok(index.themes)
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared_state::AppConfig;
+
+    #[test]
+    fn test_parse_index() {
+        let theme = ThemeFile::from_config(&AppConfig::default(), &[]);
+        let json = serde_json::json!({
+            "themes": [
+                { "author": "tester", "theme": theme },
+                { "theme": theme, "description": "no author" }
+            ]
+        });
+
+        let index: GalleryIndex = serde_json::from_value(json).unwrap();
+        assert_eq!(index.themes.len(), 2);
+        assert_eq!(index.themes[0].author, "tester");
+        assert!(index.themes[1].author.is_empty());
+    }
+}
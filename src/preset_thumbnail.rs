@@ -0,0 +1,160 @@
+//! Small preview images for user presets.
+//!
+//! Saving a user color or visual preset also writes `<name>.png` next to its
+//! JSON: the normal visualizer drawn by `gui::headless` over the same mock
+//! spectrum the Colors tab shows as its preview. The pickers show them so
+//! presets can be told apart at a glance. Visual presets are drawn in their
+//! own mode with their linked colors.
+//!
+//! Rendering runs on the GUI's task pool (`Request`). Presets that arrived
+//! some other way (theme import, gallery, copied in by hand) get their
+//! thumbnail the first time a picker asks for it.
+
+use std::path::PathBuf;
+
+use image::RgbaImage;
+
+use crate::shared_state::{AppConfig, ColorProfile, ColorRef, SharedState, VisualMode, VisualProfile};
+
+pub const THUMB_WIDTH: u32 = 96;
+pub const THUMB_HEIGHT: u32 = 32;
+
+/// Bass heavy, dip in the mids, sparkle in the highs (0..1 of full height).
+/// Shared with the Colors tab preview.
+pub const MOCK_LEVELS: [f32; 26] = [
+    0.10, 0.40, 0.75, 0.95, 0.90, 0.85, 0.70, // Bass
+    0.55, 0.40, 0.30, 0.25,                   // Mids
+    0.40, 0.60, 0.50, 0.35,                   // High Mids
+    0.25, 0.15, 0.25, 0.40, 0.30, 0.20, 0.15, 0.10, 0.08, 0.04, 0.01, // Highs
+];
+
+/// Preset folder kind ("colors" / "visuals"), as in `AppPaths::preset_dir`
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PresetKind {
+    Color,
+    Visual,
+}
+
+impl PresetKind {
+    fn dir_name(self) -> &'static str {
+        match self {
+            Self::Color => "colors",
+            Self::Visual => "visuals",
+        }
+    }
+}
+
+pub fn path_for(kind: PresetKind, name: &str) -> PathBuf {
+    crate::paths::get()
+        .preset_dir(kind.dir_name())
+        .join(format!("{}.png", AppConfig::sanitize_filename(name)))
+}
+
+/// Throw-away state that shows `profile` over the mock spectrum
+fn preview_state(mut profile: VisualProfile, user_colors: Vec<ColorProfile>) -> SharedState {
+    // Scripts, plugins and MilkDrop presets could draw anything; their thumbnail shows the colors as bars
+    if matches!(profile.visual_mode, VisualMode::Script | VisualMode::Plugin | VisualMode::MilkDrop) {
+        profile.visual_mode = VisualMode::SolidBars;
+    }
+    profile.num_bars = MOCK_LEVELS.len();
+
+    let mut state = SharedState::with_config(AppConfig { profile, show_stats: false, ..Default::default() });
+    state.user_color_presets = user_colors;
+
+    let floor = state.config.noise_floor();
+    let bars: Vec<f32> = MOCK_LEVELS.iter().map(|level| floor * (1.0 - level)).collect();
+    state.visualization.peaks = bars.clone();
+    state.visualization.waveform = (0..1024)
+        .map(|i| {
+            let t = i as f32 / 1024.0 * std::f32::consts::TAU * 2.0;
+            0.6 * t.sin() + 0.25 * (t * 3.0).sin()
+        })
+        .collect();
+    // A short stretch of history for the modes that scroll
+    let start = std::time::Instant::now();
+    for col in 0..THUMB_HEIGHT {
+        std::sync::Arc::make_mut(&mut state.visualization.history).push(&bars, start + std::time::Duration::from_millis(col as u64 * 100));
+    }
+    state.visualization.bars = bars;
+    state
+}
+
+/// A thumbnail waiting to be drawn. The GUI queues these in
+/// `SharedState::thumbnail_requests` and renders them on the task pool.
+pub struct Request {
+    pub kind: PresetKind,
+    pub name: String,
+    profile: VisualProfile,
+    user_colors: Vec<ColorProfile>,
+}
+
+impl Request {
+    /// Color preset thumbnail: the preview spectrum as bars in its colors
+    pub fn color(colors: &ColorProfile) -> Self {
+        let profile = VisualProfile {
+            color_link: ColorRef::Custom(colors.clone()),
+            ..Default::default()
+        };
+        Self { kind: PresetKind::Color, name: colors.name.clone(), profile, user_colors: Vec::new() }
+    }
+
+    /// Visual preset thumbnail: its mode, in the colors it links to
+    pub fn visual(profile: &VisualProfile, user_colors: &[ColorProfile]) -> Self {
+        Self {
+            kind: PresetKind::Visual,
+            name: profile.name.clone(),
+            profile: profile.clone(),
+            user_colors: user_colors.to_vec(),
+        }
+    }
+
+    /// Draw the thumbnail with the headless renderer
+    pub fn render(&self) -> RgbaImage {
+        let state = preview_state(self.profile.clone(), self.user_colors.clone());
+        crate::gui::headless::render_to_image(&state, [THUMB_WIDTH, THUMB_HEIGHT])
+    }
+
+    /// Draw and write the PNG. Blocking - run it on the task pool.
+    pub fn write(&self) {
+        save(self.kind, &self.name, &self.render());
+    }
+}
+
+/// Write `img` as the thumbnail of preset `name`
+fn save(kind: PresetKind, name: &str, img: &RgbaImage) {
+    let path = path_for(kind, name);
+    match img.save_with_format(&path, image::ImageFormat::Png) {
+        Ok(()) => tracing::debug!("[Presets] Thumbnail written to {:?}", path),
+        Err(e) => tracing::warn!("[Presets] Couldn't write thumbnail {:?}: {}", path, e),
+    }
+}
+
+/// Remove a deleted preset's thumbnail (missing is fine)
+pub fn delete(kind: PresetKind, name: &str) {
+    let _ = std::fs::remove_file(path_for(kind, name));
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_uses_preset_colors() {
+        let colors = ColorProfile::default();
+        let background = image::Rgba([colors.background.r, colors.background.g, colors.background.b, 255]);
+        let img = Request::color(&colors).render();
+        assert_eq!(img.dimensions(), (THUMB_WIDTH, THUMB_HEIGHT));
+
+        // Top-right corner is above every bar, the loud bass bars reach the bottom
+        assert_eq!(*img.get_pixel(THUMB_WIDTH - 1, 0), background);
+        assert_ne!(*img.get_pixel((3 * THUMB_WIDTH + THUMB_WIDTH / 2) / 26, THUMB_HEIGHT - 1), background);
+
+        for mode in [VisualMode::LineSpectrum, VisualMode::Oscilloscope, VisualMode::Spectrogram, VisualMode::SegmentedBars, VisualMode::Script] {
+            let profile = VisualProfile { visual_mode: mode, ..Default::default() };
+            let img = Request::visual(&profile, &[]).render();
+            assert!(img.pixels().any(|p| *p != background), "{:?} drew nothing", mode);
+        }
+    }
+}
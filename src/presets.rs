@@ -1,4 +1,4 @@
-use crate::shared_state::{Color32, ColorProfile, ColorRef, ThemeFont, VisualMode, VisualProfile, VuColoring};
+use crate::shared_state::{BarInterpolation, Color32, ColorProfile, ColorRef, EngineOverrides, ThemeFont, SILENCE_DB, VisualMode, VisualProfile, VuColoring};
 
 /// Returns all built-in Color Profiles
 pub fn built_in_colors() -> Vec<ColorProfile> {
@@ -14,6 +14,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(0, 255, 255),    // Cyan Text
             inspector_bg: Color32::from_rgb(20, 10, 30).with_opacity(0.9), // Deep Purple
             inspector_fg: Color32::from_rgb(0, 255, 255),
+            ..Default::default()
         },
 
         ColorProfile {
@@ -25,6 +26,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 255, 255),
             inspector_bg: Color32::from_rgb(10, 20, 50).with_opacity(0.9), // Dark Blue Paper
             inspector_fg: Color32::from_rgb(255, 255, 255),
+            ..Default::default()
         },
 
         ColorProfile {
@@ -36,6 +38,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(200, 200, 200),
             inspector_bg: Color32::from_rgb(40, 40, 40).with_opacity(0.8), // Faint Grey
             inspector_fg: Color32::from_rgb(255, 255, 255),
+            ..Default::default()
         },
 
         ColorProfile {
@@ -47,6 +50,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(200, 240, 255),
             inspector_bg: Color32::from_rgb(0, 5, 20).with_opacity(0.9), // Dark Navy
             inspector_fg: Color32::from_rgb(0, 255, 255),
+            ..Default::default()
         },
 
         ColorProfile {
@@ -58,6 +62,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 0, 255),
             inspector_bg: Color32::from_rgb(20, 5, 20).with_opacity(0.9), // Dark Magenta
             inspector_fg: Color32::from_rgb(0, 255, 255),
+            ..Default::default()
         },
 
         // === Restored Legacy Presets ===
@@ -71,6 +76,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(200, 240, 255),
             inspector_bg: Color32::from_rgb(0, 5, 25).with_opacity(0.9), // Deep Blue
             inspector_fg: Color32::from_rgb(0, 255, 255),
+            ..Default::default()
         },
         ColorProfile {
             name: "Sunset".to_string(),
@@ -81,6 +87,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 200, 150),
             inspector_bg: Color32::from_rgb(20, 5, 10).with_opacity(0.9), // Dark Red/Brown
             inspector_fg: Color32::from_rgb(255, 215, 0),
+            ..Default::default()
         },
         ColorProfile {
             name: "Synthwave".to_string(),
@@ -91,6 +98,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 100, 200),
             inspector_bg: Color32::from_rgb(20, 0, 30).with_opacity(0.9), // Deep Purple
             inspector_fg: Color32::from_rgb(0, 255, 255),
+            ..Default::default()
         },
         ColorProfile {
             name: "Spy Black".to_string(),
@@ -101,6 +109,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(200, 200, 200), // Silver
             inspector_bg: Color32::from_rgb(10, 10, 10).with_opacity(0.95), // Matte Black
             inspector_fg: Color32::from_rgb(220, 20, 60),
+            ..Default::default()
         },
         ColorProfile {
             name: "Forest Canopy".to_string(),
@@ -111,6 +120,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(150, 255, 150),
             inspector_bg: Color32::from_rgb(0, 15, 0).with_opacity(0.9), // Dark Green
             inspector_fg: Color32::from_rgb(0, 255, 0),
+            ..Default::default()
         },
         ColorProfile {
             name: "Molten Core".to_string(),
@@ -121,6 +131,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 200, 150),
             inspector_bg: Color32::from_rgb(20, 0, 0).with_opacity(0.9), // Dark Red
             inspector_fg: Color32::from_rgb(255, 165, 0),
+            ..Default::default()
         },
         ColorProfile {
             name: "Arctic Night".to_string(),
@@ -131,6 +142,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(220, 240, 255),
             inspector_bg: Color32::from_rgb(5, 5, 30).with_opacity(0.9), // Dark Blue
             inspector_fg: Color32::from_rgb(173, 216, 230),
+            ..Default::default()
         },
         ColorProfile {
             name: "Matrix".to_string(),
@@ -141,6 +153,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(0, 255, 0),
             inspector_bg: Color32::from_rgb(0, 15, 0).with_opacity(0.9), // Dark Green
             inspector_fg: Color32::from_rgb(0, 255, 0),
+            ..Default::default()
         },
         ColorProfile {
             name: "Bubblegum".to_string(),
@@ -151,6 +164,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 200, 255),
             inspector_bg: Color32::from_rgb(50, 10, 30).with_opacity(0.9), // Deep Plum
             inspector_fg: Color32::from_rgb(0, 255, 255),
+            ..Default::default()
         },
         ColorProfile {
             name: "Monochrome".to_string(),
@@ -161,6 +175,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(220, 220, 220),
             inspector_bg: Color32::from_rgb(10, 10, 10).with_opacity(0.9), // Nearly Black
             inspector_fg: Color32::from_rgb(255, 255, 255),
+            ..Default::default()
         },
         ColorProfile {
             name: "Vintage VU".to_string(),
@@ -171,6 +186,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(240, 230, 200), // Aged Paper
             inspector_bg: Color32::from_rgb(20, 10, 5).with_opacity(0.9), // Dark Wood
             inspector_fg: Color32::from_rgb(255, 215, 0),
+            ..Default::default()
         },
          ColorProfile {
             name: "BeOS Desktop".to_string(),
@@ -181,6 +197,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(220, 220, 220), 
             inspector_bg: Color32::from_rgb(133, 133, 133).with_opacity(0.9), // Standard Grey
             inspector_fg: Color32::from_rgb(255, 255, 255),
+            ..Default::default()
         },
         ColorProfile {
             name: "Deep Space".to_string(),
@@ -191,6 +208,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 255, 255), // Stars
             inspector_bg: Color32::from_rgb(10, 0, 20).with_opacity(0.9), // Deep Violet
             inspector_fg: Color32::from_rgb(0, 255, 255),
+            ..Default::default()
         },
         ColorProfile {
             name: "8-Bit Blueberry".to_string(),
@@ -201,6 +219,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 255, 255),
             inspector_bg: Color32::from_rgb(0, 0, 60).with_opacity(0.9), // Lighter Blue
             inspector_fg: Color32::from_rgb(255, 255, 255),
+            ..Default::default()
         },
         ColorProfile {
             name: "Desert Heat".to_string(),
@@ -211,6 +230,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 255, 200),
             inspector_bg: Color32::from_rgb(30, 10, 0).with_opacity(0.9), // Dark Rust
             inspector_fg: Color32::from_rgb(255, 69, 0),
+            ..Default::default()
         },
         ColorProfile {
             name: "Super Mario Bros.".to_string(),
@@ -221,6 +241,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 215, 0), // Coin Gold
             inspector_bg: Color32::from_rgb(30, 0, 10).with_opacity(0.9), // Dark Brick Red
             inspector_fg: Color32::from_rgb(255, 255, 255),
+            ..Default::default()
         },
         ColorProfile {
             name: "Halo".to_string(),
@@ -231,6 +252,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(0, 200, 255), // Cortana Blue
             inspector_bg: Color32::from_rgb(15, 25, 15).with_opacity(0.9), // Master Chief Green
             inspector_fg: Color32::from_rgb(218, 165, 32),
+            ..Default::default()
         },
         ColorProfile {
             name: "Fallout".to_string(),
@@ -241,6 +263,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(0, 255, 0), // Phosphor Green
             inspector_bg: Color32::from_rgb(0, 40, 0).with_opacity(0.9), // Pip-Boy Green
             inspector_fg: Color32::from_rgb(0, 255, 0),
+            ..Default::default()
         },
         ColorProfile {
             name: "Sith Lord".to_string(),
@@ -251,6 +274,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 50, 50),
             inspector_bg: Color32::from_rgb(20, 0, 0).with_opacity(0.95), // Deep Red
             inspector_fg: Color32::from_rgb(255, 0, 0),
+            ..Default::default()
         },
         ColorProfile {
             name: "Neon Genesis Evangelion".to_string(),
@@ -261,6 +285,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 140, 0), // HUD Orange
             inspector_bg: Color32::from_rgb(50, 10, 80).with_opacity(0.9), // Eva Purple
             inspector_fg: Color32::from_rgb(57, 255, 20),
+            ..Default::default()
         },
         ColorProfile {
             name: "Lava Lamp".to_string(),
@@ -271,6 +296,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 255, 200),
             inspector_bg: Color32::from_rgb(30, 0, 20).with_opacity(0.9), // Purple Magma
             inspector_fg: Color32::from_rgb(255, 140, 0),
+            ..Default::default()
         },
         ColorProfile {
             name: "Northern Lights".to_string(),
@@ -281,6 +307,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(100, 255, 200),
             inspector_bg: Color32::from_rgb(0, 20, 30).with_opacity(0.9), // Aurora Green/Blue
             inspector_fg: Color32::from_rgb(138, 43, 226),
+            ..Default::default()
         },
         ColorProfile {
             name: "Radioactive".to_string(),
@@ -291,6 +318,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 255, 0),
             inspector_bg: Color32::from_rgb(30, 30, 0).with_opacity(0.9), // Toxic Sludge
             inspector_fg: Color32::from_rgb(255, 0, 0),
+            ..Default::default()
         },
         ColorProfile {
             name: "Ice Fire".to_string(),
@@ -301,6 +329,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 255, 255),
             inspector_bg: Color32::from_rgb(10, 0, 30).with_opacity(0.9), // Deep Indigo
             inspector_fg: Color32::from_rgb(255, 165, 0),
+            ..Default::default()
         },
         ColorProfile {
             name: "Retrowave".to_string(),
@@ -311,6 +340,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(0, 255, 255),
             inspector_bg: Color32::from_rgb(30, 0, 50).with_opacity(0.9), // Retro Purple
             inspector_fg: Color32::from_rgb(255, 0, 128),
+            ..Default::default()
         },
         ColorProfile {
             name: "Blood Moon".to_string(),
@@ -321,6 +351,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 100, 100),
             inspector_bg: Color32::from_rgb(30, 0, 0).with_opacity(0.9), // Dried Blood
             inspector_fg: Color32::from_rgb(255, 69, 0),
+            ..Default::default()
         },
         ColorProfile {
             name: "Mint Condition".to_string(),
@@ -331,6 +362,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(240, 255, 250),
             inspector_bg: Color32::from_rgb(0, 50, 50).with_opacity(0.9), // Minty Green
             inspector_fg: Color32::from_rgb(255, 255, 255),
+            ..Default::default()
         },
         ColorProfile {
             name: "Golden Hour".to_string(),
@@ -341,6 +373,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 215, 0),
             inspector_bg: Color32::from_rgb(50, 30, 0).with_opacity(0.9), // Golden Brown
             inspector_fg: Color32::from_rgb(255, 250, 205),
+            ..Default::default()
         },
         ColorProfile {
             name: "Tequila Sunrise".to_string(),
@@ -351,6 +384,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 255, 200),
             inspector_bg: Color32::from_rgb(50, 10, 10).with_opacity(0.9), // Grenadine
             inspector_fg: Color32::from_rgb(255, 165, 0),
+            ..Default::default()
         },
         ColorProfile {
             name: "Espresso Martini".to_string(),
@@ -361,6 +395,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(210, 180, 140), // Crema
             inspector_bg: Color32::from_rgb(30, 20, 15).with_opacity(0.9), // Mocha
             inspector_fg: Color32::from_rgb(255, 255, 255),
+            ..Default::default()
         },
         ColorProfile {
             name: "Cotton Candy".to_string(),
@@ -371,6 +406,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 192, 203), // Pink
             inspector_bg: Color32::from_rgb(50, 25, 40).with_opacity(0.9), // Dark Pink
             inspector_fg: Color32::from_rgb(0, 255, 255),
+            ..Default::default()
         },
         // --- Winamp Classic ---
         ColorProfile {
@@ -382,6 +418,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(0, 255, 0),    // Bitmap font green
             inspector_bg: Color32::from_rgb(20, 25, 20).with_opacity(0.9), // Dark Greenish Grey
             inspector_fg: Color32::from_rgb(0, 255, 0),
+            ..Default::default()
         },
 
         // --- CRT Phosphor (P1 Green) ---
@@ -394,6 +431,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(50, 255, 50),
             inspector_bg: Color32::from_rgb(0, 25, 0).with_opacity(0.8), // Glass Green
             inspector_fg: Color32::from_rgb(50, 255, 50),
+            ..Default::default()
         },
 
         // --- VFD Amber (Marantz/Pioneer) ---
@@ -406,6 +444,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(255, 160, 0),
             inspector_bg: Color32::from_rgb(30, 15, 0).with_opacity(0.9), // Dark Amber
             inspector_fg: Color32::from_rgb(255, 160, 0),
+            ..Default::default()
         },
 
         // --- VFD Blue (Sony/Panasonic) ---
@@ -418,6 +457,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(0, 200, 255),
             inspector_bg: Color32::from_rgb(0, 15, 30).with_opacity(0.9), // Dark VFD Blue
             inspector_fg: Color32::from_rgb(0, 255, 255),
+            ..Default::default()
         },
 
         // --- Gameboy (Dot Matrix) ---
@@ -430,6 +470,7 @@ pub fn built_in_colors() -> Vec<ColorProfile> {
             text: Color32::from_rgb(15, 56, 15),   // Text is usually dark on GB
             inspector_bg: Color32::from_rgb(15, 56, 15).with_opacity(0.9), // Authentic Darkest Green
             inspector_fg: Color32::from_rgb(139, 172, 15), // LCD Green Text
+            ..Default::default()
         },
     ]
 }
@@ -476,6 +517,13 @@ pub fn built_in_visuals() -> Vec<VisualProfile> {
             color_link: ColorRef::Preset("Monochrome".to_string()), 
             show_peaks: false, // Peaks are distracting in analysis
             sensitivity: 1.5,
+            // Deeper floor and a fixed analysis rate, whatever the global settings say
+            engine: Some(EngineOverrides {
+                noise_floor_db: -90.0,
+                resample_to_internal_rate: true,
+                silence_threshold_db: SILENCE_DB,
+                silence_hold_ms: 50.0,
+            }),
             ..VisualProfile::default()
         },
 
@@ -556,6 +604,7 @@ pub fn built_in_visuals() -> Vec<VisualProfile> {
             color_link: ColorRef::Preset("Blueprint (Light)".to_string()),
             attack_time_ms: 80.0,
             release_time_ms: 300.0,
+            bar_interpolation: BarInterpolation::CubicSpline,
             ..VisualProfile::default()
         },
 
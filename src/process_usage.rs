@@ -0,0 +1,79 @@
+//! CPU and memory use of the BeSpec process, for the Stats tab.
+//!
+//! A background thread asks the OS (through `sysinfo`) every couple of
+//! seconds; that's slow enough to cost nothing and fast enough for a
+//! readout. CPU is averaged over the interval between samples.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+use crate::shared_state::SharedState;
+
+/// Time between samples
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ProcessUsage {
+    /// CPU time over the last interval, 100% = one core fully busy
+    pub cpu_percent: f32,
+    /// Resident memory (RSS / working set)
+    pub memory_bytes: u64,
+    /// Logical cores, to put `cpu_percent` in proportion
+    pub cores: usize,
+}
+
+impl ProcessUsage {
+    /// Share of the whole machine, what Task Manager shows
+    pub fn cpu_percent_of_machine(&self) -> f32 {
+        self.cpu_percent / self.cores.max(1) as f32
+    }
+
+    pub fn memory_mib(&self) -> f32 {
+        self.memory_bytes as f32 / (1024.0 * 1024.0)
+    }
+}
+
+/// Start sampling into `SharedState::performance.process` until `shutdown`
+pub fn start(shared_state: Arc<Mutex<SharedState>>, shutdown: Arc<AtomicBool>) {
+    let spawned = std::thread::Builder::new().name("bespec-usage".to_string()).spawn(move || {
+        let pid = Pid::from_u32(std::process::id());
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let refresh = ProcessRefreshKind::new().with_cpu().with_memory();
+        let mut system = System::new();
+        // The first refresh only sets the baseline for CPU time
+        system.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), true, refresh);
+
+        while !shutdown.load(Ordering::Relaxed) {
+            std::thread::sleep(SAMPLE_INTERVAL);
+            system.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), true, refresh);
+            let Some(process) = system.process(pid) else {
+                tracing::warn!("[Usage] ⚠️ Can't read our own process stats, giving up");
+                return;
+            };
+            let usage = ProcessUsage { cpu_percent: process.cpu_usage(), memory_bytes: process.memory(), cores };
+            if let Ok(mut state) = shared_state.lock() {
+                state.performance.process = Some(usage);
+            }
+        }
+    });
+    if let Err(e) = spawned {
+        tracing::warn!("[Usage] ⚠️ Failed to start usage sampling: {}", e);
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpu_share_of_machine() {
+        let usage = ProcessUsage { cpu_percent: 50.0, memory_bytes: 64 * 1024 * 1024, cores: 4 };
+        assert_eq!(usage.cpu_percent_of_machine(), 12.5);
+        assert_eq!(usage.memory_mib(), 64.0);
+    }
+}
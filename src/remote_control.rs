@@ -0,0 +1,369 @@
+//! Remote control over MIDI CC and OSC, for driving BeSpec from a hardware
+//! controller or a tablet during a live set.
+//!
+//! Listener threads turn incoming messages into `RemoteEvent`s (a source
+//! and a value in 0..1) and hand them to the GUI, which looks the source up
+//! in the bindings table (`gui::remote`). A binding can be learned: arm it
+//! in settings and the next message that arrives becomes its source.
+//!
+//! Values: a CC is value / 127. An OSC message takes its first argument: a
+//! float as is (clamped to 0..1), an int as MIDI style 0..127, true / false
+//! as 1 / 0, and no argument at all as 1.
+//!
+//! OSC listens on UDP, on localhost unless LAN access is switched on.
+//! MIDI input uses midir and needs the `midi` cargo feature, like MIDI out.
+
+use std::net::UdpSocket;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, Sender};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// What a binding changes
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum RemoteAction {
+    /// -20 dB ... +20 dB across the range
+    Sensitivity,
+    /// Picks from the color presets (user presets first)
+    ColorPreset,
+    /// Picks from the visual modes
+    VisualMode,
+    /// Minimizes / restores the main window on every press
+    ToggleWindow,
+}
+
+impl RemoteAction {
+    pub const ALL: [RemoteAction; 4] = [Self::Sensitivity, Self::ColorPreset, Self::VisualMode, Self::ToggleWindow];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Sensitivity => "Sensitivity",
+            Self::ColorPreset => "Color Preset",
+            Self::VisualMode => "Visual Mode",
+            Self::ToggleWindow => "Show / Hide Window",
+        }
+    }
+}
+
+/// Where a message came from
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub enum RemoteSource {
+    /// Channel 1..=16
+    MidiCc { channel: u8, controller: u8 },
+    /// OSC address, e.g. "/bespec/mode"
+    Osc(String),
+}
+
+impl RemoteSource {
+    pub fn label(&self) -> String {
+        match self {
+            Self::MidiCc { channel, controller } => format!("CC {} (ch {})", controller, channel),
+            Self::Osc(address) => address.clone(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct RemoteBinding {
+    pub action: RemoteAction,
+    /// `None` until learned
+    pub source: Option<RemoteSource>,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct RemoteSettings {
+    pub midi_enabled: bool,
+    /// MIDI input port name, as listed by `list_midi_inputs`
+    pub midi_port: String,
+    pub osc_enabled: bool,
+    pub osc_port: u16,
+    /// Listen on every interface instead of just localhost
+    pub osc_lan: bool,
+    pub bindings: Vec<RemoteBinding>,
+}
+
+impl Default for RemoteSettings {
+    fn default() -> Self {
+        let osc = |action, address: &str| RemoteBinding { action, source: Some(RemoteSource::Osc(address.to_string())) };
+        Self {
+            midi_enabled: false,
+            midi_port: String::new(),
+            osc_enabled: false,
+            osc_port: 9000,
+            osc_lan: false,
+            bindings: vec![
+                osc(RemoteAction::Sensitivity, "/bespec/sensitivity"),
+                osc(RemoteAction::ColorPreset, "/bespec/colors"),
+                osc(RemoteAction::VisualMode, "/bespec/mode"),
+                osc(RemoteAction::ToggleWindow, "/bespec/window"),
+            ],
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct RemoteEvent {
+    pub source: RemoteSource,
+    /// 0..1
+    pub value: f32,
+}
+
+/// A Control Change message, `None` for anything else
+pub fn parse_midi_cc(message: &[u8]) -> Option<RemoteEvent> {
+    let [status, controller, value] = *message else { return None };
+    if status & 0xF0 != 0xB0 {
+        return None;
+    }
+    Some(RemoteEvent {
+        source: RemoteSource::MidiCc { channel: (status & 0x0F) + 1, controller: controller & 0x7F },
+        value: (value & 0x7F) as f32 / 127.0,
+    })
+}
+
+/// A single OSC message (bundles aren't supported), `None` if malformed
+pub fn parse_osc(packet: &[u8]) -> Option<RemoteEvent> {
+    let (address, rest) = osc_string(packet)?;
+    if !address.starts_with('/') {
+        return None;
+    }
+    // Old senders leave out the type tags entirely
+    let (tags, args) = osc_string(rest).unwrap_or((",", &[]));
+    let tags = tags.strip_prefix(',')?;
+
+    let int = |args: &[u8]| args.get(..4).map(|b| i32::from_be_bytes([b[0], b[1], b[2], b[3]]));
+    let value = match tags.chars().next() {
+        None => 1.0,
+        // NaN would survive the clamp below and end up in a setting
+        Some('f') => Some(f32::from_bits(int(args)? as u32)).filter(|v| v.is_finite())?,
+        Some('i') => int(args)? as f32 / 127.0,
+        Some('T') => 1.0,
+        Some('F') => 0.0,
+        Some(_) => return None,
+    };
+    Some(RemoteEvent { source: RemoteSource::Osc(address.to_string()), value: value.clamp(0.0, 1.0) })
+}
+
+/// Null-terminated string padded to 4 bytes, and what follows it
+fn osc_string(data: &[u8]) -> Option<(&str, &[u8])> {
+    let end = data.iter().position(|&b| b == 0)?;
+    let padded = (end + 4) & !3;
+    let text = std::str::from_utf8(&data[..end]).ok()?;
+    Some((text, data.get(padded..).unwrap_or(&[])))
+}
+
+/// Running listeners. Dropping it stops them.
+pub struct RemoteInput {
+    pub rx: Receiver<RemoteEvent>,
+    stop: Arc<AtomicBool>,
+    _midi: imp::MidiConnection,
+}
+
+impl Drop for RemoteInput {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Start the listeners `settings` asks for. `ctx` is repainted on every
+/// message so the GUI reacts while idling.
+pub fn start(settings: &RemoteSettings, ctx: &egui::Context) -> RemoteInput {
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let stop = Arc::new(AtomicBool::new(false));
+
+    if settings.osc_enabled {
+        let host = if settings.osc_lan { "0.0.0.0" } else { "127.0.0.1" };
+        let addr = format!("{}:{}", host, settings.osc_port);
+        let (tx, ctx, stop) = (tx.clone(), ctx.clone(), stop.clone());
+        let spawned = std::thread::Builder::new()
+            .name("bespec-osc".to_string())
+            .spawn(move || run_osc(&addr, tx, ctx, stop));
+        if let Err(e) = spawned {
+            tracing::error!("[Remote] Failed to start OSC thread: {}", e);
+        }
+    }
+
+    let midi = if settings.midi_enabled && !settings.midi_port.is_empty() {
+        imp::connect(&settings.midi_port, tx, ctx.clone())
+    } else {
+        Default::default()
+    };
+    RemoteInput { rx, stop, _midi: midi }
+}
+
+/// How long the OSC thread waits between checks of the stop flag
+const OSC_POLL: Duration = Duration::from_millis(250);
+
+fn run_osc(addr: &str, tx: Sender<RemoteEvent>, ctx: egui::Context, stop: Arc<AtomicBool>) {
+    // A restarted listener may have to wait for the old one to let go of the port
+    let mut socket = None;
+    for _ in 0..8 {
+        match UdpSocket::bind(addr) {
+            Ok(s) => {
+                socket = Some(s);
+                break;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => std::thread::sleep(OSC_POLL),
+            Err(e) => {
+                tracing::error!("[Remote] Couldn't listen for OSC on {}: {}", addr, e);
+                return;
+            }
+        }
+    }
+    let Some(socket) = socket else {
+        tracing::error!("[Remote] OSC port {} is in use", addr);
+        return;
+    };
+    if let Err(e) = socket.set_read_timeout(Some(OSC_POLL)) {
+        tracing::error!("[Remote] OSC socket setup failed: {}", e);
+        return;
+    }
+    tracing::info!("[Remote] Listening for OSC on {}", addr);
+
+    let mut buf = [0u8; 1536];
+    while !stop.load(Ordering::Relaxed) {
+        match socket.recv(&mut buf) {
+            Ok(len) => {
+                let Some(event) = parse_osc(&buf[..len]) else {
+                    tracing::debug!("[Remote] Ignored an OSC packet ({} bytes)", len);
+                    continue;
+                };
+                if tx.send(event).is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+            }
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {}
+            Err(e) => {
+                tracing::error!("[Remote] OSC receive failed: {}", e);
+                break;
+            }
+        }
+    }
+    tracing::debug!("[Remote] OSC listener on {} stopped", addr);
+}
+
+/// MIDI input port names. Empty without the `midi` feature.
+pub fn list_midi_inputs() -> Vec<String> {
+    imp::list_inputs()
+}
+
+#[cfg(feature = "midi")]
+mod imp {
+    use super::{parse_midi_cc, RemoteEvent};
+    use crossbeam_channel::Sender;
+    use eframe::egui;
+    use midir::{MidiInput, MidiInputConnection};
+
+    const CLIENT_NAME: &str = "BeSpec";
+
+    /// Closes the port when dropped
+    #[derive(Default)]
+    pub struct MidiConnection(Option<MidiInputConnection<()>>);
+
+    pub fn connect(port_name: &str, tx: Sender<RemoteEvent>, ctx: egui::Context) -> MidiConnection {
+        let result = MidiInput::new(CLIENT_NAME).map_err(|e| e.to_string()).and_then(|midi| {
+            let port = midi
+                .ports()
+                .into_iter()
+                .find(|p| midi.port_name(p).map_or(false, |n| n == port_name))
+                .ok_or_else(|| "no such port".to_string())?;
+            midi.connect(
+                &port,
+                "bespec-in",
+                move |_, message, _| {
+                    if let Some(event) = parse_midi_cc(message) {
+                        let _ = tx.send(event);
+                        ctx.request_repaint();
+                    }
+                },
+                (),
+            )
+            .map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(conn) => {
+                tracing::info!("[Remote] Listening to MIDI from '{}'", port_name);
+                MidiConnection(Some(conn))
+            }
+            Err(e) => {
+                tracing::warn!("[Remote] Couldn't open MIDI input '{}': {}", port_name, e);
+                MidiConnection(None)
+            }
+        }
+    }
+
+    pub fn list_inputs() -> Vec<String> {
+        match MidiInput::new(CLIENT_NAME) {
+            Ok(midi) => midi.ports().iter().filter_map(|p| midi.port_name(p).ok()).collect(),
+            Err(e) => {
+                tracing::warn!("[Remote] Couldn't list MIDI inputs: {}", e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "midi"))]
+mod imp {
+    use super::RemoteEvent;
+    use crossbeam_channel::Sender;
+    use eframe::egui;
+
+    #[derive(Default)]
+    pub struct MidiConnection;
+
+    pub fn connect(_port_name: &str, _tx: Sender<RemoteEvent>, _ctx: egui::Context) -> MidiConnection {
+        tracing::info!("[Remote] Built without the 'midi' feature, no MIDI input");
+        MidiConnection
+    }
+
+    pub fn list_inputs() -> Vec<String> {
+        Vec::new()
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_midi_cc() {
+        let event = parse_midi_cc(&[0xB2, 7, 127]).unwrap();
+        assert_eq!(event.source, RemoteSource::MidiCc { channel: 3, controller: 7 });
+        assert_eq!(event.value, 1.0);
+        // Notes and short messages aren't CCs
+        assert_eq!(parse_midi_cc(&[0x90, 36, 100]), None);
+        assert_eq!(parse_midi_cc(&[0xB0, 7]), None);
+    }
+
+    #[test]
+    fn test_parse_osc() {
+        // "/bespec/mode" (12 bytes + 4 padding), ",f" (+2), 0.5
+        let mut packet = b"/bespec/mode\0\0\0\0,f\0\0".to_vec();
+        packet.extend_from_slice(&0.5f32.to_be_bytes());
+        let event = parse_osc(&packet).unwrap();
+        assert_eq!(event.source, RemoteSource::Osc("/bespec/mode".into()));
+        assert_eq!(event.value, 0.5);
+
+        let mut packet = b"/x\0\0,i\0\0".to_vec();
+        packet.extend_from_slice(&127i32.to_be_bytes());
+        assert_eq!(parse_osc(&packet).unwrap().value, 1.0);
+        assert_eq!(parse_osc(b"/go\0,F\0\0").unwrap().value, 0.0);
+        assert_eq!(parse_osc(b"/go\0").unwrap().value, 1.0);
+
+        assert_eq!(parse_osc(b"#bundle\0"), None);
+        assert_eq!(parse_osc(b"/x\0\0,f\0\0\0"), None);
+
+        for bad in [f32::NAN, f32::INFINITY] {
+            let mut packet = b"/x\0\0,f\0\0".to_vec();
+            packet.extend_from_slice(&bad.to_bits().to_be_bytes());
+            assert_eq!(parse_osc(&packet), None);
+        }
+    }
+}
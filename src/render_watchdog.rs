@@ -0,0 +1,128 @@
+//! Renderer stall watchdog.
+//!
+//! The GUI asks for a new frame every time it draws one, so `update()` runs
+//! at least a couple of times a second even when auto sleep has it idling.
+//! When it stops (GPU device lost, driver reset, a compositor that stopped
+//! sending frame callbacks) the window just sits there frozen or transparent,
+//! and nothing else notices - the FFT thread carries on happily.
+//!
+//! So the GUI stamps a heartbeat every frame, and the FFT thread checks it
+//! about once a second. A stale heartbeat gets:
+//!   1. a warning in the log and a repaint request through the egui context
+//!      (this wakes winit's event loop, which is enough when the loop slept
+//!      through a missed wakeup), repeated every `NUDGE_INTERVAL`
+//!   2. an error in the log if it still hasn't drawn after `GIVE_UP_AFTER`,
+//!      so the freeze at least shows up in a support bundle
+//!   3. a banner once it draws again, telling the user what happened
+//!
+//! eframe has no way to rebuild the render surface from outside the event
+//! loop, so a renderer that's really gone stays gone; restarting BeSpec is
+//! the fix, and the logged error says so.
+
+use std::time::{Duration, Instant};
+
+/// No frame for this long counts as a stall
+const STALL_AFTER: Duration = Duration::from_secs(5);
+
+/// How often a stalled GUI gets another repaint request
+const NUDGE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// After this long stalled, stop hoping and say so in the log
+const GIVE_UP_AFTER: Duration = Duration::from_secs(30);
+
+/// What the FFT thread should do after a check
+#[derive(Debug, PartialEq)]
+pub enum WatchdogAction {
+    None,
+    /// Ask the GUI for a frame (and log, if `first`)
+    Nudge { first: bool },
+    /// Stalled for `GIVE_UP_AFTER`, log an error once
+    GiveUp,
+}
+
+#[derive(Default)]
+pub struct RenderWatchdog {
+    /// Last time the GUI ran `update()`
+    last_frame: Option<Instant>,
+    /// Window is minimized, frames aren't expected
+    paused: bool,
+    /// When the current stall was noticed
+    stalled_since: Option<Instant>,
+    last_nudge: Option<Instant>,
+    gave_up: bool,
+    /// Stalls seen this session (Stats tab)
+    pub stalls: u32,
+}
+
+impl RenderWatchdog {
+    /// GUI heartbeat. Returns how long the GUI was stalled if this frame
+    /// ends a stall.
+    pub fn frame(&mut self, now: Instant, paused: bool) -> Option<Duration> {
+        let previous = self.last_frame.replace(now);
+        self.paused = paused;
+        self.stalled_since.take()?;
+        self.last_nudge = None;
+        self.gave_up = false;
+        previous.map(|t| now.saturating_duration_since(t))
+    }
+
+    /// FFT thread side: is the GUI still drawing?
+    pub fn check(&mut self, now: Instant) -> WatchdogAction {
+        let Some(last_frame) = self.last_frame else { return WatchdogAction::None };
+        if self.paused || now.saturating_duration_since(last_frame) < STALL_AFTER {
+            return WatchdogAction::None;
+        }
+
+        let since = match self.stalled_since {
+            Some(since) => since,
+            None => {
+                self.stalled_since = Some(now);
+                self.last_nudge = Some(now);
+                self.stalls += 1;
+                return WatchdogAction::Nudge { first: true };
+            }
+        };
+        if !self.gave_up && now.saturating_duration_since(since) >= GIVE_UP_AFTER {
+            self.gave_up = true;
+            return WatchdogAction::GiveUp;
+        }
+        if self.last_nudge.map_or(true, |t| now.saturating_duration_since(t) >= NUDGE_INTERVAL) {
+            self.last_nudge = Some(now);
+            return WatchdogAction::Nudge { first: false };
+        }
+        WatchdogAction::None
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stall_nudge_and_recover() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut dog = RenderWatchdog::default();
+
+        // Nothing to watch before the first frame
+        assert_eq!(dog.check(at(100)), WatchdogAction::None);
+
+        dog.frame(at(0), false);
+        assert_eq!(dog.check(at(1)), WatchdogAction::None);
+        assert_eq!(dog.check(at(6)), WatchdogAction::Nudge { first: true });
+        assert_eq!(dog.check(at(7)), WatchdogAction::None);
+        assert_eq!(dog.check(at(8)), WatchdogAction::Nudge { first: false });
+        assert_eq!(dog.check(at(36)), WatchdogAction::GiveUp);
+        assert_eq!(dog.check(at(37)), WatchdogAction::Nudge { first: false });
+        assert_eq!(dog.stalls, 1);
+
+        assert_eq!(dog.frame(at(40), false), Some(Duration::from_secs(40)));
+        assert_eq!(dog.frame(at(41), false), None);
+
+        // Minimized windows aren't expected to draw
+        dog.frame(at(50), true);
+        assert_eq!(dog.check(at(90)), WatchdogAction::None);
+    }
+}
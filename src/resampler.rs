@@ -0,0 +1,194 @@
+//! Streaming mono resampler for the FFT path.
+//!
+//! When "Resample to 48 kHz" is on, every packet is converted to
+//! `INTERNAL_SAMPLE_RATE` before the FFT. The processor then never has to be
+//! rebuilt when a device switches between 44.1 / 88.2 / 192 kHz, and Hz-per-bin
+//! stays the same no matter what the hardware runs at.
+//!
+//! Linear interpolation, with a moving-average pre-filter when downsampling so
+//! content above the new Nyquist is damped instead of folding back into the
+//! spectrum. Not mastering grade, but plenty for a visualizer.
+//! Keeps state between calls so packet boundaries are seamless.
+
+/// Rate the FFT runs at when resampling is enabled (Hz)
+pub const INTERNAL_SAMPLE_RATE: u32 = 48000;
+
+pub struct Resampler {
+    target_rate: u32,
+    source_rate: u32,
+
+    /// Read position for the next output sample, in input samples.
+    /// Index 0 is `last` (final sample of the previous block).
+    pos: f64,
+    last: f32,
+
+    /// Pre-filter: raw samples carried over from the previous block
+    history: Vec<f32>,
+    /// Scratch for the filtered block (reused, no per-packet allocation)
+    filtered: Vec<f32>,
+}
+
+impl Resampler {
+    pub fn new(target_rate: u32) -> Self {
+        Self {
+            target_rate,
+            source_rate: 0,
+            pos: 0.0,
+            last: 0.0,
+            history: Vec::new(),
+            filtered: Vec::with_capacity(4096),
+        }
+    }
+
+    pub fn target_rate(&self) -> u32 {
+        self.target_rate
+    }
+
+    /// Forget all carried-over state (e.g. after a device switch)
+    pub fn reset(&mut self) {
+        self.pos = 0.0;
+        self.last = 0.0;
+        self.history.clear();
+    }
+
+    /// Resample one block of mono `input` at `source_rate` into `output`
+    /// (cleared first).
+    pub fn process(&mut self, input: &[f32], source_rate: u32, output: &mut Vec<f32>) {
+        output.clear();
+        if source_rate == 0 || input.is_empty() {
+            return;
+        }
+        if source_rate != self.source_rate {
+            self.source_rate = source_rate;
+            self.reset();
+        }
+        if source_rate == self.target_rate {
+            output.extend_from_slice(input);
+            return;
+        }
+
+        let step = source_rate as f64 / self.target_rate as f64;
+
+        // Downsampling: box filter roughly one output period wide
+        let width = step.round() as usize;
+        let mut filtered = std::mem::take(&mut self.filtered);
+        if width > 1 {
+            self.prefilter(input, width, &mut filtered);
+        } else {
+            filtered.clear();
+            filtered.extend_from_slice(input);
+        }
+
+        let len = filtered.len() as f64;
+        let sample_at = |k: usize| if k == 0 { self.last } else { filtered[k - 1] };
+
+        while self.pos < len {
+            let i = self.pos.floor() as usize;
+            let frac = (self.pos - i as f64) as f32;
+            let (a, b) = (sample_at(i), sample_at(i + 1));
+            output.push(a + (b - a) * frac);
+            self.pos += step;
+        }
+
+        self.pos -= len;
+        self.last = *filtered.last().unwrap_or(&self.last);
+        self.filtered = filtered;
+    }
+
+    /// Moving average of `width` samples, carrying the tail across blocks
+    fn prefilter(&mut self, input: &[f32], width: usize, out: &mut Vec<f32>) {
+        out.clear();
+
+        // Pad with silence on the very first block
+        if self.history.len() != width - 1 {
+            self.history.clear();
+            self.history.resize(width - 1, 0.0);
+        }
+
+        let mut sum: f32 = self.history.iter().sum();
+        let history_len = self.history.len();
+        let at = |k: usize| if k < history_len { self.history[k] } else { input[k - history_len] };
+
+        for i in 0..input.len() {
+            // Window is [i, i + width) in (history ++ input) coordinates
+            sum += at(i + history_len);
+            out.push(sum / width as f32);
+            sum -= at(i);
+        }
+
+        let total = history_len + input.len();
+        let tail: Vec<f32> = (total - history_len..total).map(at).collect();
+        self.history = tail;
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(rate: u32, freq: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (i as f32 / rate as f32 * freq * std::f32::consts::TAU).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_same_rate_is_passthrough() {
+        let mut r = Resampler::new(48000);
+        let input = sine(48000, 440.0, 512);
+        let mut out = Vec::new();
+        r.process(&input, 48000, &mut out);
+        assert_eq!(out, input);
+    }
+
+    #[test]
+    fn test_output_length_follows_ratio() {
+        let mut r = Resampler::new(48000);
+        let mut out = Vec::new();
+        let mut total = 0;
+        // 1 second of 96k in 100 blocks -> ~48000 samples out
+        for _ in 0..100 {
+            r.process(&[0.0; 960], 96000, &mut out);
+            total += out.len();
+        }
+        assert!((total as i64 - 48000).abs() <= 1, "got {}", total);
+
+        let mut r = Resampler::new(48000);
+        let mut total = 0;
+        for _ in 0..100 {
+            r.process(&[0.0; 441], 44100, &mut out);
+            total += out.len();
+        }
+        assert!((total as i64 - 48000).abs() <= 1, "got {}", total);
+    }
+
+    #[test]
+    fn test_dc_is_preserved() {
+        let mut r = Resampler::new(48000);
+        let mut out = Vec::new();
+        r.process(&[0.5; 4096], 192000, &mut out);
+        // Skip the warm-up where the filter is still filling
+        assert!(out[8..].iter().all(|s| (s - 0.5).abs() < 1e-5));
+    }
+
+    #[test]
+    fn test_blocks_join_seamlessly() {
+        // A low tone split into blocks should match resampling it in one go
+        let input = sine(44100, 100.0, 4410);
+        let mut whole = Vec::new();
+        Resampler::new(48000).process(&input, 44100, &mut whole);
+
+        let mut r = Resampler::new(48000);
+        let mut pieces = Vec::new();
+        let mut out = Vec::new();
+        for chunk in input.chunks(300) {
+            r.process(chunk, 44100, &mut out);
+            pieces.extend_from_slice(&out);
+        }
+
+        assert_eq!(whole.len(), pieces.len());
+        assert!(whole.iter().zip(&pieces).all(|(a, b)| (a - b).abs() < 1e-4));
+    }
+}
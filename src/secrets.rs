@@ -0,0 +1,83 @@
+// src/secrets.rs
+//
+// API tokens for connected services (Last.fm, Spotify, Hue, ...).
+//
+// Tokens never touch config.json. They live in the OS credential store
+// (Windows Credential Manager, macOS Keychain, Secret Service on Linux)
+// via the `keyring` crate. The config only remembers *which* services are
+// connected (`AppConfig::connected_accounts`) so the settings UI can draw
+// without poking the keyring every frame.
+//
+// Entries are keyed per `--user` profile, so two profiles can be connected
+// to different accounts.
+
+use crate::error::BeSpecError;
+
+/// Service name all BeSpec entries are filed under in the OS keyring
+const KEYRING_SERVICE: &str = "BeSpec";
+
+/// Services that can be connected, as (id, display name).
+/// The id is what gets stored in the config and used as the keyring key.
+pub const KNOWN_SERVICES: &[(&str, &str)] = &[
+    ("lastfm", "Last.fm"),
+    ("spotify", "Spotify"),
+    ("hue", "Philips Hue"),
+];
+
+/// Keyring "account" for a service: `<service>` or `<user>/<service>`
+fn account_key(user: Option<&str>, service: &str) -> String {
+    match user {
+        Some(user) => format!("{}/{}", user, service),
+        None => service.to_string(),
+    }
+}
+
+fn entry(service: &str) -> Result<keyring::Entry, BeSpecError> {
+    let key = account_key(crate::paths::get().user.as_deref(), service);
+    keyring::Entry::new(KEYRING_SERVICE, &key)
+        .map_err(|e| BeSpecError::Credentials(format!("{}: {}", service, e)))
+}
+
+/// Save (or replace) the token for `service`
+pub fn store_token(service: &str, token: &str) -> Result<(), BeSpecError> {
+    entry(service)?
+        .set_password(token)
+        .map_err(|e| BeSpecError::Credentials(format!("{}: {}", service, e)))?;
+    tracing::info!("[Secrets] Stored token for {}", service);
+    Ok(())
+}
+
+/// Token for `service`, if one is stored
+// dead_code allowed: read by the integrations themselves, which land separately
+#[allow(dead_code)]
+pub fn load_token(service: &str) -> Result<Option<String>, BeSpecError> {
+    match entry(service)?.get_password() {
+        Ok(token) => Ok(Some(token)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(BeSpecError::Credentials(format!("{}: {}", service, e))),
+    }
+}
+
+/// Forget the token for `service`. Missing entries are not an error.
+pub fn delete_token(service: &str) -> Result<(), BeSpecError> {
+    match entry(service)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => {
+            tracing::info!("[Secrets] Removed token for {}", service);
+            Ok(())
+        }
+        Err(e) => Err(BeSpecError::Credentials(format!("{}: {}", service, e))),
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_key_is_scoped_per_user() {
+        assert_eq!(account_key(None, "lastfm"), "lastfm");
+        assert_eq!(account_key(Some("alice"), "lastfm"), "alice/lastfm");
+    }
+}
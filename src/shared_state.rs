@@ -1,10 +1,11 @@
+use std::collections::HashMap;
 use std::time::{Duration, Instant};
+use std::sync::Arc;
 use crate::audio_device::AudioDeviceInfo;
 use crate::fft_config::FFTInfo;
 use serde::{Serialize, Deserialize};
 use std::fs;
 use std::path::PathBuf;
-use directories::ProjectDirs;
 
 pub const SILENCE_DB: f32 = -140.0;
 
@@ -25,6 +26,31 @@ pub enum VisualMode {
     LineSpectrum,
     /// Time-domain waveform display.
     Oscilloscope,
+    /// Scrolling frequency-over-time history (waterfall).
+    Spectrogram,
+    /// The same history as a pseudo-3D landscape receding into the distance.
+    Mountain,
+    /// The spectrum on a coarse grid of dots, like a retro LCD.
+    DotMatrix,
+    /// Drawn by a user script from the scripts folder.
+    Script,
+    /// Drawn by a native plugin from the plugins folder.
+    Plugin,
+    /// A MilkDrop preset rendered by projectM.
+    MilkDrop,
+}
+
+impl VisualMode {
+    /// Whether the mode draws from the waveform, which the FFT thread only
+    /// keeps up to date for the modes that need it
+    pub fn uses_waveform(self) -> bool {
+        matches!(self, Self::Oscilloscope | Self::Script | Self::Plugin | Self::MilkDrop)
+    }
+
+    /// Whether the mode draws from the spectrogram history
+    pub fn uses_history(self) -> bool {
+        matches!(self, Self::Spectrogram | Self::Mountain)
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
@@ -60,6 +86,251 @@ impl Default for VuColoring {
     }
 }
 
+/// What picks a bar's color in the bar modes.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug, Default)]
+pub enum ColorMapping {
+    /// By height, through the `vu_coloring` gradient or zones.
+    #[default]
+    Amplitude,
+    /// By position: a rainbow from red in the bass to violet in the treble,
+    /// the same for every height.
+    Frequency,
+}
+
+/// How curves through the bar levels (Line Spectrum, reference and response
+/// lines) get from one bar to the next. See `gui::interpolation`.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug, Default)]
+pub enum BarInterpolation {
+    /// Held flat until the next bar: a staircase
+    None,
+    /// Straight lines between bars
+    #[default]
+    Linear,
+    /// A smooth (Catmull-Rom) spline through every bar
+    CubicSpline,
+}
+
+impl BarInterpolation {
+    pub const ALL: [Self; 3] = [Self::None, Self::Linear, Self::CubicSpline];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::None => "None (Steps)",
+            Self::Linear => "Linear",
+            Self::CubicSpline => "Cubic Spline",
+        }
+    }
+}
+
+/// How a background image covers the window. See `gui::background`.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug, Default)]
+pub enum ImageFit {
+    /// Scaled to cover the whole window, cropping the overhang
+    #[default]
+    Fill,
+    /// Scaled to fit inside the window, centered
+    Fit,
+    /// Squeezed to the window's shape
+    Stretch,
+    /// Repeated at its own size from the top left
+    Tile,
+}
+
+impl ImageFit {
+    pub const ALL: [Self; 4] = [Self::Fill, Self::Fit, Self::Stretch, Self::Tile];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Fill => "Fill",
+            Self::Fit => "Fit",
+            Self::Stretch => "Stretch",
+            Self::Tile => "Tile",
+        }
+    }
+}
+
+/// Picture behind the bars (PNG, JPEG or an animated GIF)
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct BackgroundImage {
+    /// Image file; empty for none
+    pub path: String,
+    /// Show the playing track's album art, blurred and dimmed, instead of
+    /// the file (which comes back for tracks without art)
+    pub album_art: bool,
+    pub fit: ImageFit,
+    pub opacity: f32,
+}
+
+impl Default for BackgroundImage {
+    fn default() -> Self {
+        Self { path: String::new(), album_art: false, fit: ImageFit::Fill, opacity: 1.0 }
+    }
+}
+
+/// Bloom around the bars / line, brighter when the music is louder.
+/// See `gui::glow`.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct GlowSettings {
+    pub enabled: bool,
+    /// Strength at full loudness (0..1)
+    pub intensity: f32,
+    /// How much of the strength follows the loudness: 0 is a steady glow,
+    /// 1 is dark in silence
+    pub reactivity: f32,
+}
+
+impl Default for GlowSettings {
+    fn default() -> Self {
+        Self { enabled: false, intensity: 0.6, reactivity: 0.8 }
+    }
+}
+
+/// How the Dot Matrix mode draws a cell
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug, Default)]
+pub enum DotStyle {
+    #[default]
+    Round,
+    Square,
+    /// Monospace block characters
+    Characters,
+}
+
+impl DotStyle {
+    pub const ALL: [Self; 3] = [Self::Round, Self::Square, Self::Characters];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Round => "Round Dots",
+            Self::Square => "Square Pixels",
+            Self::Characters => "Characters",
+        }
+    }
+}
+
+/// Grid and look of the Dot Matrix mode. See `gui::dot_matrix`.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct DotMatrixSettings {
+    pub columns: u32,
+    pub rows: u32,
+    pub style: DotStyle,
+    /// Phosphor halo around lit dots
+    pub glow: bool,
+}
+
+impl Default for DotMatrixSettings {
+    fn default() -> Self {
+        Self { columns: 48, rows: 16, style: DotStyle::Round, glow: true }
+    }
+}
+
+/// How a bar's held peak is drawn
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug, Default)]
+pub enum PeakStyle {
+    /// A short thick block, the classic look
+    #[default]
+    Block,
+    Line,
+    Dot,
+    /// A dot that drops with gravity once its hold runs out
+    FallingDot,
+    /// Fades from the peak down to the bar
+    Trail,
+}
+
+impl PeakStyle {
+    pub const ALL: [Self; 5] = [Self::Block, Self::Line, Self::Dot, Self::FallingDot, Self::Trail];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Block => "Block",
+            Self::Line => "Thin Line",
+            Self::Dot => "Dot",
+            Self::FallingDot => "Falling Dot",
+            Self::Trail => "Gradient Trail",
+        }
+    }
+}
+
+/// How the Solid and Segmented modes fill a bar
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug, Default)]
+pub enum BarFill {
+    #[default]
+    Solid,
+    /// Just the edge
+    Outline,
+    /// Diagonal lines over a dim fill
+    Hatched,
+}
+
+impl BarFill {
+    pub const ALL: [Self; 3] = [Self::Solid, Self::Outline, Self::Hatched];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Solid => "Solid",
+            Self::Outline => "Outline",
+            Self::Hatched => "Hatched",
+        }
+    }
+}
+
+/// Extra styling for the Solid and Segmented modes. See `gui::bar_style`.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct BarStyle {
+    /// Round the tip of each bar (the top segment, in Segmented mode)
+    pub rounded_caps: bool,
+    pub fill: BarFill,
+    pub shadow: bool,
+}
+
+impl Default for BarStyle {
+    fn default() -> Self {
+        Self { rounded_caps: false, fill: BarFill::Solid, shadow: false }
+    }
+}
+
+/// Which way the split view divides the visualizer
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug, Default)]
+pub enum SplitDirection {
+    /// Main mode on top, second mode below
+    #[default]
+    Horizontal,
+    /// Main mode on the left, second mode on the right
+    Vertical,
+}
+
+impl SplitDirection {
+    pub const ALL: [Self; 2] = [Self::Horizontal, Self::Vertical];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Horizontal => "Top / Bottom",
+            Self::Vertical => "Left / Right",
+        }
+    }
+}
+
+/// A second visual mode drawn next to the main one, e.g. the spectrum on
+/// top and the waveform below. Both use the rest of the profile's settings.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct SplitView {
+    pub enabled: bool,
+    pub mode: VisualMode,
+    pub direction: SplitDirection,
+    /// Share of the area the main mode gets (0..1)
+    pub ratio: f32,
+}
+
+impl Default for SplitView {
+    fn default() -> Self {
+        Self { enabled: false, mode: VisualMode::Oscilloscope, direction: SplitDirection::Horizontal, ratio: 0.5 }
+    }
+}
 
 /// Controls how the "Now Playing" media overlay behaves.
 #[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
@@ -72,6 +343,195 @@ pub enum MediaDisplayMode {
     Off,
 }
 
+/// Window corner the "Now Playing" overlay sits in.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug, Default)]
+pub enum OverlayCorner {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl OverlayCorner {
+    pub const ALL: [OverlayCorner; 4] = [Self::TopLeft, Self::TopRight, Self::BottomLeft, Self::BottomRight];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::TopLeft => "Top Left",
+            Self::TopRight => "Top Right",
+            Self::BottomLeft => "Bottom Left",
+            Self::BottomRight => "Bottom Right",
+        }
+    }
+
+    pub fn is_left(self) -> bool {
+        matches!(self, Self::TopLeft | Self::BottomLeft)
+    }
+
+    pub fn is_top(self) -> bool {
+        matches!(self, Self::TopLeft | Self::TopRight)
+    }
+}
+
+/// Where the "Now Playing" overlay goes and what it shows.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct MediaOverlayLayout {
+    pub corner: OverlayCorner,
+    /// Multiplies text, album art and width (1.0 = half the window wide)
+    pub scale: f32,
+    pub show_art: bool,
+    pub show_title: bool,
+    pub show_artist: bool,
+    /// Seek bar, for players that report a position
+    pub show_progress: bool,
+    pub show_controls: bool,
+    /// "via Spotify"
+    pub show_source: bool,
+}
+
+impl Default for MediaOverlayLayout {
+    fn default() -> Self {
+        Self {
+            corner: OverlayCorner::TopRight,
+            scale: 1.0,
+            show_art: true,
+            show_title: true,
+            show_artist: true,
+            show_progress: true,
+            show_controls: true,
+            show_source: true,
+        }
+    }
+}
+
+/// Upper limit on how often the GUI repaints.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug, Default)]
+pub enum FpsCap {
+    Fps30,
+    Fps60,
+    Fps120,
+    /// Repaint at the refresh rate of the monitor the window is on
+    /// (configs from before pacing call it "Unlimited").
+    #[default]
+    #[serde(alias = "Unlimited")]
+    Display,
+}
+
+impl FpsCap {
+    /// Target frames per second, `None` to follow the display
+    pub fn target(self) -> Option<u32> {
+        match self {
+            FpsCap::Fps30 => Some(30),
+            FpsCap::Fps60 => Some(60),
+            FpsCap::Fps120 => Some(120),
+            FpsCap::Display => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FpsCap::Fps30 => "30 FPS",
+            FpsCap::Fps60 => "60 FPS",
+            FpsCap::Fps120 => "120 FPS",
+            FpsCap::Display => "Display Refresh",
+        }
+    }
+}
+
+/// Screen edge the main window is docked to as a reserved strip ("appbar").
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug, Default)]
+pub enum DockEdge {
+    /// Normal floating window.
+    #[default]
+    Off,
+    Top,
+    Bottom,
+}
+
+impl DockEdge {
+    pub fn label(self) -> &'static str {
+        match self {
+            DockEdge::Off => "Off",
+            DockEdge::Top => "Top",
+            DockEdge::Bottom => "Bottom",
+        }
+    }
+}
+
+/// Screen corner that wakes the window from ghost mode when the pointer
+/// rests in it.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug, Default)]
+pub enum HotCorner {
+    #[default]
+    Off,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl HotCorner {
+    pub const ALL: [HotCorner; 5] = [
+        HotCorner::Off,
+        HotCorner::TopLeft,
+        HotCorner::TopRight,
+        HotCorner::BottomLeft,
+        HotCorner::BottomRight,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HotCorner::Off => "Off",
+            HotCorner::TopLeft => "Top Left",
+            HotCorner::TopRight => "Top Right",
+            HotCorner::BottomLeft => "Bottom Left",
+            HotCorner::BottomRight => "Bottom Right",
+        }
+    }
+}
+
+/// Window sizes offered in the Window tab, in points
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SizePreset {
+    Strip,
+    Bar,
+    Widescreen,
+    Classic,
+    Square,
+}
+
+impl SizePreset {
+    pub const ALL: [SizePreset; 5] = [
+        SizePreset::Strip,
+        SizePreset::Bar,
+        SizePreset::Widescreen,
+        SizePreset::Classic,
+        SizePreset::Square,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SizePreset::Strip => "Strip",
+            SizePreset::Bar => "Bar",
+            SizePreset::Widescreen => "Widescreen",
+            SizePreset::Classic => "Classic",
+            SizePreset::Square => "Square",
+        }
+    }
+
+    pub fn size(self) -> [f32; 2] {
+        match self {
+            SizePreset::Strip => [1920.0, 120.0],
+            SizePreset::Bar => [1280.0, 200.0],
+            SizePreset::Widescreen => [800.0, 450.0],
+            SizePreset::Classic => [800.0, 400.0],
+            SizePreset::Square => [400.0, 400.0],
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
 pub enum AggregationMode {
     /// Uses the highest peak in the frequency range
@@ -121,6 +581,27 @@ pub struct ColorProfile {
     // Inspector Colors
     pub inspector_bg: Color32,
     pub inspector_fg: Color32,
+
+    /// Bar gradient from base to tip. Empty means `low` to `high`, which is
+    /// what every built-in preset and older saved profiles use. Kept sorted
+    /// by position: sorted on load, and the editor never reorders stops.
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "deserialize_gradient")]
+    pub gradient: Vec<GradientStop>,
+}
+
+/// Stops from a file may be in any order (hand-edited themes)
+fn deserialize_gradient<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<GradientStop>, D::Error> {
+    let mut stops = Vec::<GradientStop>::deserialize(deserializer)?;
+    stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+    Ok(stops)
+}
+
+/// One color of a multi-stop gradient
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Debug)]
+pub struct GradientStop {
+    /// 0.0 (bar base) to 1.0 (full height)
+    pub position: f32,
+    pub color: Color32,
 }
 
 impl Default for ColorProfile {
@@ -135,6 +616,7 @@ impl Default for ColorProfile {
             
             inspector_bg: Color32::from_rgb(0, 0, 0).with_opacity(0.9),
             inspector_fg: Color32::from_rgb(255, 255, 255), // White
+            gradient: Vec::new(),
         }
     }
 }
@@ -146,6 +628,16 @@ impl ColorProfile {
         crate::presets::built_in_colors()
     }
 
+    /// Gradient stops from base to tip, at least two of them
+    pub fn gradient_stops(&self) -> impl Iterator<Item = GradientStop> + '_ {
+        let low_high = [
+            GradientStop { position: 0.0, color: self.low },
+            GradientStop { position: 1.0, color: self.high },
+        ];
+        let (fallback, stops) = if self.gradient.len() < 2 { (Some(low_high), &[][..]) } else { (None, &self.gradient[..]) };
+        fallback.into_iter().flatten().chain(stops.iter().copied())
+    }
+
     /// Try to find a built-in profile by name.
     #[must_use]
     pub fn find_by_name(name: &str) -> Option<Self> {
@@ -166,6 +658,20 @@ pub enum ColorRef {
 // Visual Profile (Windowing, Bars, and Visualization Colors)
 // =====================================================================================
 
+/// Global audio engine settings a profile can override, all or none.
+/// Dynamics (sensitivity, attack/release, aggregation) are always per profile,
+/// so with an override the profile carries its whole audio response.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Debug)]
+pub struct EngineOverrides {
+    pub noise_floor_db: f32,
+    pub resample_to_internal_rate: bool,
+    /// Older overrides predate these; they fall back to the stock values
+    #[serde(default = "default_silence_threshold_db")]
+    pub silence_threshold_db: f32,
+    #[serde(default = "default_silence_hold_ms")]
+    pub silence_hold_ms: f32,
+}
+
 /// A complete visual profile controlling bar layout, dynamics, and color link.
 #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
 pub struct VisualProfile {
@@ -182,6 +688,11 @@ pub struct VisualProfile {
     pub fill_peaks: bool,
     pub show_peaks: bool,
 
+    /// Draw on a canvas this many times smaller than the window and scale it
+    /// up: chunky bars, gaps and text in a small window, same bar count
+    #[serde(default = "default_content_zoom")]
+    pub content_zoom: f32,
+
     // Font Selection
     pub overlay_font: ThemeFont,
 
@@ -193,18 +704,92 @@ pub struct VisualProfile {
     pub peak_release_time_ms: f32,
     pub aggregation_mode: AggregationMode,
 
+    /// Peaks drop with an accelerating fall instead of easing down
+    #[serde(default)]
+    pub peak_gravity: bool,
+
+    #[serde(default)]
+    pub peak_style: PeakStyle,
+
+    // === Oscilloscope ===
+    /// Zoom the scope trace to the recent peak level, so quiet speech and
+    /// loud music both fill the view. Sensitivity still trims on top.
+    #[serde(default)]
+    pub scope_autoscale: bool,
+
+    /// Most autoscale may zoom in (x gain), so hiss doesn't fill the screen
+    #[serde(default = "default_scope_max_zoom")]
+    pub scope_max_zoom: f32,
+
+    /// Color the trace by its dominant band: bass in the low color,
+    /// treble in the high color
+    #[serde(default)]
+    pub scope_color_by_frequency: bool,
+
+    /// Which traces the scope draws (stereo sources only show L/R/L-R)
+    #[serde(default)]
+    pub scope_traces: ScopeTraces,
+
     // === Color Mode ===
     #[serde(default)]
     pub vu_coloring: VuColoring,
 
+    /// Curve shape between bars
+    #[serde(default)]
+    pub bar_interpolation: BarInterpolation,
+
+    /// Color bars by height or by frequency
+    #[serde(default)]
+    pub color_mapping: ColorMapping,
+
     // === Color Link ===
     pub color_link: ColorRef,
 
+    // === Engine Overrides ===
+    /// Audio engine settings that normally live in `AppConfig`, carried by
+    /// this profile instead (so switching profiles reconfigures the whole engine)
+    #[serde(default)]
+    pub engine: Option<EngineOverrides>,
+
     // === Window Decoration ===
     pub beos_enabled: bool,
 
     // MAY REMOVE THIS LATER?
     pub background: Option<Color32>,
+
+    /// Picture drawn over the background color, behind the visualizer
+    #[serde(default)]
+    pub background_image: BackgroundImage,
+
+    /// Audio-reactive bloom post effect
+    #[serde(default)]
+    pub glow: GlowSettings,
+
+    /// File name in the scripts folder, for `VisualMode::Script`
+    #[serde(default)]
+    pub script: String,
+    /// Plugin name, for `VisualMode::Plugin`
+    #[serde(default)]
+    pub plugin: String,
+    /// Preset path in the milkdrop folder, for `VisualMode::MilkDrop`
+    #[serde(default)]
+    pub milkdrop_preset: String,
+
+    /// Second mode beside the main one
+    #[serde(default)]
+    pub split: SplitView,
+
+    /// Ridges the Mountain mode draws, 0.1 s apart
+    #[serde(default = "default_mountain_lines")]
+    pub mountain_lines: u32,
+
+    /// Grid for `VisualMode::DotMatrix`
+    #[serde(default)]
+    pub dot_matrix: DotMatrixSettings,
+
+    /// Caps, fill and shadow for the Solid and Segmented modes
+    #[serde(default)]
+    pub bar_style: BarStyle,
 }
 
 impl Default for VisualProfile {
@@ -220,6 +805,7 @@ impl Default for VisualProfile {
             segment_gap_px: 2.0,
             fill_peaks: false,
             show_peaks: true,
+            content_zoom: default_content_zoom(),
             overlay_font: ThemeFont::Medium,
 
             sensitivity: 1.0,
@@ -228,22 +814,144 @@ impl Default for VisualProfile {
             peak_hold_time_ms: 1000.0,
             peak_release_time_ms: 1500.0,
             aggregation_mode: AggregationMode::Peak,
+            peak_gravity: false,
+            peak_style: PeakStyle::Block,
             vu_coloring: VuColoring::Gradient,
+            bar_interpolation: BarInterpolation::Linear,
+            color_mapping: ColorMapping::Amplitude,
+            scope_autoscale: false,
+            scope_max_zoom: default_scope_max_zoom(),
+            scope_color_by_frequency: false,
+            scope_traces: ScopeTraces::default(),
 
             color_link: ColorRef::Preset("Default".to_string()),
 
+            engine: None,
+
             beos_enabled: false,
 
             background: None,
+            background_image: BackgroundImage::default(),
+            glow: GlowSettings::default(),
+            script: String::new(),
+            plugin: String::new(),
+            milkdrop_preset: String::new(),
+            split: SplitView::default(),
+            mountain_lines: default_mountain_lines(),
+            dot_matrix: DotMatrixSettings::default(),
+            bar_style: BarStyle::default(),
         }
     }
 }
 
+fn default_content_zoom() -> f32 {
+    1.0
+}
+
+fn default_scope_max_zoom() -> f32 {
+    20.0
+}
+
+fn default_mountain_lines() -> u32 {
+    48
+}
+
+/// Per-trace visibility for the oscilloscope, like the channel buttons on a
+/// bench scope. The mix is what the scope always drew; the others need a
+/// stereo (or wider) source.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct ScopeTraces {
+    /// (L+R)/2, the mono downmix
+    pub mix: bool,
+    pub left: bool,
+    pub right: bool,
+    /// (L-R)/2, what's different between the channels
+    pub difference: bool,
+}
+
+impl Default for ScopeTraces {
+    fn default() -> Self {
+        Self { mix: true, left: false, right: false, difference: false }
+    }
+}
+
 impl VisualProfile {
     /// Built-in Visual Profiles
     pub fn built_in() -> Vec<Self> {
         crate::presets::built_in_visuals()
     }
+
+    /// Resolve the linked color profile by checking user presets first,
+    /// then falling back to built-in presets. Applies background override if set.
+    pub fn resolve_colors(&self, user_presets: &[ColorProfile]) -> ColorProfile {
+        let mut c = match &self.color_link {
+            ColorRef::Custom(colors) => colors.clone(),
+            ColorRef::Preset(name) => user_presets
+                .iter()
+                .find(|p| &p.name == name)
+                .cloned()
+                .unwrap_or_else(|| ColorProfile::find_by_name(name).unwrap_or_default()),
+        };
+        if let Some(bg) = self.background {
+            c.background = bg;
+        }
+        c
+    }
+
+    /// The modes on screen: the main one, then the split view's second one
+    pub fn shown_modes(&self) -> impl Iterator<Item = VisualMode> {
+        let second = (self.split.enabled && self.split.mode != self.visual_mode).then_some(self.split.mode);
+        std::iter::once(self.visual_mode).chain(second)
+    }
+
+    /// Whether peaks fall with gravity; the Falling Dot style always does
+    pub fn gravity_peaks(&self) -> bool {
+        self.peak_gravity || self.peak_style == PeakStyle::FallingDot
+    }
+
+    /// Override the background's alpha (0..1), keeping the linked preset's color
+    pub fn set_background_alpha(&mut self, user_presets: &[ColorProfile], alpha: f32) {
+        let current = self.resolve_colors(user_presets).background;
+        self.background = Some(Color32 { a: (alpha.clamp(0.0, 1.0) * 255.0) as u8, ..current });
+    }
+}
+
+/// An additional visualizer window. Shares the audio stream with the main
+/// window but draws it with its own visual profile.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct ExtraWindow {
+    /// Stable key for the viewport and the per-window visualization data
+    pub id: u64,
+    pub profile: VisualProfile,
+    pub size: [f32; 2],
+    pub position: Option<[f32; 2]>,
+}
+
+impl ExtraWindow {
+    pub fn new(id: u64, profile: VisualProfile) -> Self {
+        Self { id, profile, size: [600.0, 300.0], position: None }
+    }
+}
+
+/// Window geometry saved for one monitor setup (see `AppConfig::monitor_layouts`)
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct WindowLayout {
+    pub window_size: [f32; 2],
+    pub window_position: Option<[f32; 2]>,
+    pub mini_window_size: [f32; 2],
+    pub mini_window_position: Option<[f32; 2]>,
+}
+
+/// Level offsets for one capture device (see `AppConfig::device_levels`),
+/// applied on top of the profile's sensitivity and noise floor
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct DeviceLevels {
+    /// Extra input gain (dB)
+    pub gain_db: f32,
+    /// Added to the noise floor (dB)
+    pub noise_floor_offset_db: f32,
 }
 
 // ====================================================================================
@@ -280,11 +988,20 @@ pub struct SharedState {
     /// Flag: GUI requests a hardware scan (handled by main thread).
     pub refresh_devices_requested: bool,
 
+    /// No audio device could be opened; the capture thread waits for a retry
+    /// (a refresh or device pick) and the window says so instead of showing
+    /// a flat spectrum
+    pub no_audio_device: bool,
+
     // === Media Player State ===
     /// Currently playing track info.
     pub media_info: Option<crate::media::MediaTrackInfo>,
     /// When the track info was last updated
     pub last_media_update: Option<Instant>,
+    /// Synced lyrics of the current track
+    pub lyrics: crate::lyrics::LyricsState,
+    /// Recently played tracks, newest first (memory only)
+    pub track_history: crate::track_history::TrackHistory,
 
     // === User Presets ===
     /// Loaded from JSON file at startup
@@ -298,126 +1015,800 @@ pub struct SharedState {
     // === Level of Detail Config === 
     pub lod_bar_limit: Option<usize>,
 
+    // === Errors ===
+    /// Most recent user-facing error (shown in the GUI error banner until dismissed)
+    pub last_error: Option<crate::error::BeSpecError>,
+
+    // === Community Preset Gallery ===
+    pub gallery: crate::preset_gallery::GalleryStatus,
+    /// Flag: GUI wants the gallery index (re)fetched (handled by the GUI update loop)
+    pub gallery_fetch_requested: bool,
+
+    // === Auto Sleep ===
+    pub sleep: SleepState,
+
+    // === Gain Advisor ===
+    /// Current gain-staging suggestion (set by the FFT thread)
+    pub gain_hint: Option<crate::gain_advisor::GainHint>,
+    /// Hints the user closed this session
+    pub dismissed_hints: Vec<crate::gain_advisor::GainHint>,
+
+    // === Support Bundle ===
+    pub support_bundle: crate::support_bundle::BundleStatus,
+
+    // === Extra Windows ===
+    /// Visualization data per extra window, keyed by `ExtraWindow::id` (written by the FFT thread)
+    pub extra_visualization: HashMap<u64, VisualizationData>,
+
+    // === Renderer Watchdog ===
+    /// GUI heartbeat, checked by the FFT thread
+    pub render_watchdog: crate::render_watchdog::RenderWatchdog,
+
+    // === MIDI Out ===
+    /// Which MIDI triggers are on right now (set by the FFT thread)
+    pub midi_held: Vec<bool>,
+    /// Output port the FFT thread has open
+    pub midi_port_open: Option<String>,
+
+    // === Spectrum Log ===
+    /// Recording requested from the Stats tab
+    pub spectrum_log_running: bool,
+    /// File and frame count (set by the FFT thread)
+    pub spectrum_log_status: crate::spectrum_log::LogStatus,
+
+    // === Remote Control ===
+    /// Binding waiting to learn the next incoming message
+    pub remote_learn: Option<usize>,
+    /// Source of the last remote message, shown in settings
+    pub remote_last: Option<String>,
+
+    // === Reference Snapshot ===
+    /// Averaging in progress (fed by the FFT thread)
+    pub snapshot_capture: Option<crate::snapshot::SnapshotCapture>,
+    /// Ghost line drawn over the live bars
+    pub reference_snapshot: Option<crate::snapshot::ReferenceSnapshot>,
+
+    /// The A/B compare slot that isn't on screen
+    pub ab_compare: crate::ab_compare::AbCompare,
+
+    // === Measurement ===
+    /// Pink-noise measurement (averaged by the FFT thread)
+    pub measurement: crate::measurement::MeasurementState,
+
+    // === Signal Generator ===
+    /// Generator output on (the GUI owns the stream)
+    pub signal_gen_running: bool,
+
+    // === Latency Self-Test ===
+    /// Click round trip (timed by the FFT thread, the GUI plays the clicks)
+    pub latency_test: crate::latency_test::LatencyTest,
+
+    // === Stress Test ===
+    /// Synthetic max-settings run (Stats tab), done on a background task
+    pub stress_test: crate::stress_test::StressStatus,
+
+    // === Background Image ===
+    /// Frames decoded on the task pool, picked up by the GUI's background texture
+    pub background_decoded: Option<crate::gui::background::Decoded>,
+
+    // === Preset Thumbnails ===
+    /// PNGs to draw, queued by the pickers and preset saves, rendered on the task pool
+    pub thumbnail_requests: Vec<crate::preset_thumbnail::Request>,
+
+    // === Tuner ===
+    /// Latest detected pitch (only while the tuner is shown)
+    pub tuner_reading: Option<crate::tuner::Reading>,
+
+    // === Clip Indicator ===
+    /// When the input last clipped (set by the FFT thread)
+    pub clip: crate::clip_indicator::ClipIndicator,
+
+    // === Level Meter ===
+    /// Input peak / RMS / crest factor (set by the FFT thread)
+    pub meter: crate::level_meter::MeterReading,
+}
+
+impl SharedState {
+    /// Create a new `SharedState` with loaded (or default) config.
+    pub fn new() -> Self {
+        let config = AppConfig::load();
+
+        let user_color_presets = AppConfig::load_user_color_presets();
+        let user_visual_presets = AppConfig::load_user_visual_presets();
+        tracing::info!("[State] Loaded {} user color presets", user_color_presets.len());
+        tracing::info!("[State] Loaded {} user visual presets", user_visual_presets.len());
+
+        Self {
+            user_color_presets,
+            user_visual_presets,
+            ..Self::with_config(config)
+        }
+    }
+
+    /// Build state from a given config without touching disk (no user presets).
+    /// Used by headless rendering tests.
+    pub fn with_config(config: AppConfig) -> Self {
+        Self {
+            visualization: VisualizationData::new(config.profile.num_bars),
+            performance: PerformanceStats::default(),
+            config,
+            audio_devices: Vec::new(),
+            device_changed: false,
+            refresh_devices_requested: false,
+            no_audio_device: false,
+            media_info: None,
+            last_media_update: None,
+            lyrics: Default::default(),
+            track_history: Default::default(),
+            user_color_presets: Vec::new(),
+            user_visual_presets: Vec::new(),
+            update_url: None,
+            update_dismissed: false,
+            lod_bar_limit: None,
+            last_error: None,
+            gallery: crate::preset_gallery::GalleryStatus::Idle,
+            gallery_fetch_requested: false,
+            sleep: SleepState::default(),
+            gain_hint: None,
+            dismissed_hints: Vec::new(),
+            support_bundle: crate::support_bundle::BundleStatus::Idle,
+            extra_visualization: HashMap::new(),
+            render_watchdog: crate::render_watchdog::RenderWatchdog::default(),
+            midi_held: Vec::new(),
+            midi_port_open: None,
+            spectrum_log_running: false,
+            spectrum_log_status: Default::default(),
+            remote_learn: None,
+            remote_last: None,
+            snapshot_capture: None,
+            reference_snapshot: None,
+            ab_compare: crate::ab_compare::AbCompare::default(),
+            measurement: Default::default(),
+            signal_gen_running: false,
+            latency_test: Default::default(),
+            stress_test: Default::default(),
+            background_decoded: None,
+            thumbnail_requests: Vec::new(),
+            tuner_reading: None,
+            clip: Default::default(),
+            meter: Default::default(),
+        }
+    }
+
+    /// Log an error and keep it around for the GUI banner
+    pub fn report_error(&mut self, err: crate::error::BeSpecError) {
+        err.log();
+        self.last_error = Some(err);
+    }
+
+    /// Drop the banner error if it is of the given kind (e.g. after a device switch succeeded)
+    pub fn clear_error(&mut self, kind: &str) {
+        if self.last_error.as_ref().map_or(false, |e| e.kind() == kind) {
+            self.last_error = None;
+        }
+    }
+
+    /// Ask the capture thread to switch to device `id` ("Default" for the
+    /// system default). A listed device that can't be captured is refused
+    /// with an error banner and the current device keeps running.
+    /// Returns true if the switch was requested.
+    pub fn select_device(&mut self, id: &str, name: &str) -> bool {
+        if let Some(err) = self.audio_devices.iter().find(|d| d.id == id).and_then(|d| d.validate().err()) {
+            self.report_error(err.on_device(name));
+            return false;
+        }
+        self.config.selected_device = id.to_string();
+        self.config.selected_device_name = name.to_string();
+        self.device_changed = true;
+        true
+    }
+}
+// === Data Structures ====
+
+/// Live visualization data updated by the FFT thread each frame.
+#[derive(Clone)]
+pub struct VisualizationData {
+    /// Bar heights in dB ( typically -80 to +40 range)
+    pub bars: Vec<f32>,
+
+    /// Peak indicator heights in dB
+    pub peaks: Vec<f32>,
+
+    /// Raw audio waveform buffer for oscilloscope mode.
+    // We keep a small buffer for drawing
+    pub waveform: Vec<f32>,
+
+    /// Per-sample bass (0.0) to treble (1.0) hint for coloring the scope trace,
+    /// same length as `waveform`
+    pub waveform_tint: Vec<f32>,
+
+    /// First two channels of the scope block, before resampling.
+    /// Empty when the source is mono.
+    pub waveform_left: Vec<f32>,
+    pub waveform_right: Vec<f32>,
+
+    /// Recent bar history for spectrogram mode (only recorded in that mode).
+    /// Shared, so a paused display's snapshot doesn't copy megabytes of it;
+    /// writers go through `Arc::make_mut`.
+    pub history: Arc<crate::spectrogram::SpectrogramHistory>,
+
+    /// Recent waveform peak (linear, decaying), what scope autoscale zooms to
+    pub scope_level: f32,
+
+    /// Strongest peaks of the raw FFT, for the inspector's harmonic readout
+    pub spectral_peaks: Vec<crate::fft_processor::SpectralPeak>,
+
+    /// When this data was last updated
+    pub timestamp: Instant,
+}
+
+impl VisualizationData {
+    /// Create new visualization data initialized to silence.
+    pub fn new(num_bars: usize) -> Self {
+        Self {
+            bars: vec![SILENCE_DB; num_bars],
+            peaks: vec![SILENCE_DB; num_bars],
+            waveform: vec![0.0; 2048],
+            waveform_tint: vec![0.5; 2048],
+            waveform_left: Vec::new(),
+            waveform_right: Vec::new(),
+            history: Arc::new(crate::spectrogram::SpectrogramHistory::new()),
+            scope_level: 0.0,
+            spectral_peaks: Vec::new(),
+            timestamp: Instant::now(),
+        }
+    }
+
+    /// Store a frame of bars and peaks, reusing the buffers (the FFT thread
+    /// calls this at 60+ Hz)
+    pub fn set_bars(&mut self, bars: &[f32], peaks: &[f32]) {
+        self.bars.clear();
+        self.bars.extend_from_slice(bars);
+        self.peaks.clear();
+        self.peaks.extend_from_slice(peaks);
+    }
+
+    /// Store a new scope block and follow its peak: jumps up at once,
+    /// falls off over a couple of seconds so the zoom doesn't pump
+    pub fn set_waveform(&mut self, samples: &[f32], tint: &[f32], now: Instant) {
+        const SCOPE_LEVEL_RELEASE_SEC: f32 = 1.5;
+
+        let peak = samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        let dt = now.saturating_duration_since(self.timestamp).as_secs_f32();
+        self.scope_level = peak.max(self.scope_level * (-dt / SCOPE_LEVEL_RELEASE_SEC).exp());
+
+        self.waveform.clear();
+        self.waveform.extend_from_slice(samples);
+        self.waveform_tint.clear();
+        self.waveform_tint.extend_from_slice(tint);
+        self.waveform_tint.resize(samples.len(), 0.5);
+        self.timestamp = now;
+    }
+
+    /// Store the L/R channels of the block just given to `set_waveform`.
+    /// A single channel can swing further than the mix, so autoscale
+    /// follows the louder of the two as well.
+    pub fn set_stereo(&mut self, left: &[f32], right: &[f32]) {
+        let peak = left.iter().chain(right).fold(0.0f32, |m, s| m.max(s.abs()));
+        self.scope_level = self.scope_level.max(peak);
+
+        self.waveform_left.clear();
+        self.waveform_left.extend_from_slice(left);
+        self.waveform_right.clear();
+        self.waveform_right.extend_from_slice(right);
+    }
+}
+
+/// Auto sleep bookkeeping. The FFT thread hears the audio, the GUI decides
+/// how often to repaint; this is where the two meet.
+#[derive(Default)]
+pub struct SleepState {
+    /// Last time the FFT thread saw audio above the noise floor
+    pub last_signal: Option<Instant>,
+
+    /// GUI is repainting at the idle rate
+    pub asleep: bool,
+
+    /// Window is minimized or covered and throttled (see `AppConfig::hidden_throttle`)
+    pub hidden: bool,
+
+    /// Handle to the GUI so a sleeping window can be woken from the FFT thread
+    pub repaint_ctx: Option<egui::Context>,
+}
+
+impl SleepState {
+    /// Called by the FFT thread for every block with audible content.
+    /// Wakes the GUI right away instead of waiting for its next idle tick.
+    pub fn note_signal(&mut self, now: Instant) {
+        self.last_signal = Some(now);
+        if self.asleep {
+            self.asleep = false;
+            if let Some(ctx) = &self.repaint_ctx {
+                ctx.request_repaint();
+            }
+        }
+    }
+
+    /// True once nothing audible has been seen for `after`.
+    /// Starting up silent counts as silence since `since`.
+    pub fn silent_for(&self, after: Duration, since: Instant, now: Instant) -> bool {
+        let last = self.last_signal.unwrap_or(since);
+        now.saturating_duration_since(last) >= after
+    }
+}
+
+/// Performance statistics updated by the FFT and GUI threads.
+#[derive(Clone, Default)]
+pub struct PerformanceStats {
+    pub frame_count: u64,
+    pub fft_ave_time: Duration,
+    pub fft_min_time: Duration,
+    pub fft_max_time: Duration,
+    pub gui_fps: f32,
+    pub fft_info: FFTInfo,
+    /// Centroid, rolloff and flatness of the latest frame
+    pub spectral: crate::fft_processor::SpectralFeatures,
+    /// Capture-to-screen latency, measured
+    pub latency: crate::latency::LatencyStats,
+    /// Recent frames split into lock wait, FFT, aggregation and paint
+    pub frame_timings: crate::frame_timing::FrameTimings,
+    /// Refresh rate of the window's monitor, when the OS tells us
+    pub display_hz: Option<f32>,
+    /// Frames that came later than the pacing asked for, this session
+    pub dropped_frames: u64,
+    /// CPU and memory of the whole process, None until the first sample
+    pub process: Option<crate::process_usage::ProcessUsage>,
+}
+
+
+// ==== Configuration ====
+
+/// Persistent application configuration, serialized to/from JSON.
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
+pub struct AppConfig {
+    pub profile: VisualProfile,
+
+    // === System Settings ===
+    
+    /// Saved Window dimensions [width, height]
+    pub window_size:  [f32; 2],
+
+    /// Saved Window position on screen [x, y]
+    pub window_position: Option<[f32; 2]>,
+   
+    pub always_on_top: bool,
+
+    ///  "Ghost Mode": Window is click-through until focused with alt-tab
+    pub window_locked: bool,
+
+    /// Resting the pointer in this screen corner makes a ghosted window
+    /// clickable again
+    #[serde(default)]
+    pub ghost_hot_corner: HotCorner,
+
+    /// The lock button's corner of a ghosted window stays clickable
+    #[serde(default)]
+    pub ghost_handle: bool,
+
+    /// Show window title bar and borders.
+    pub window_decorations: bool,
+
+    /// Width / height the resize grip keeps to, None to resize freely
+    #[serde(default)]
+    pub aspect_lock: Option<f32>,
+
+    /// User-definable Hide shortcut key
+    pub minimize_key: egui::Key,
+
+    // === Mini Mode ===
+    /// Compact strip: fewer bars, no overlays, its own window geometry
+    #[serde(default)]
+    pub mini_mode: bool,
+
+    /// Toggles mini mode together with Ctrl
+    #[serde(default = "default_mini_mode_key")]
+    pub mini_mode_key: egui::Key,
+
+    /// Opens the profile / color quick switcher together with Ctrl
+    #[serde(default = "default_quick_switch_key")]
+    pub quick_switch_key: egui::Key,
+
+    /// System-wide Ctrl+Shift hotkey that hides the window for a while
+    #[serde(default)]
+    pub peek: crate::peek_away::PeekSettings,
+
+    /// Bar count cap while in mini mode
+    #[serde(default = "default_mini_num_bars")]
+    pub mini_num_bars: usize,
+
+    /// Saved mini mode window dimensions [width, height]
+    #[serde(default = "default_mini_window_size")]
+    pub mini_window_size: [f32; 2],
+
+    /// Saved mini mode window position [x, y]
+    #[serde(default)]
+    pub mini_window_position: Option<[f32; 2]>,
+
+    /// Window geometry per monitor setup, keyed by
+    /// `window_integration::layout_key` (monitor count and resolutions).
+    /// Docking a laptop and undocking it again each restore their own layout.
+    #[serde(default)]
+    pub monitor_layouts: HashMap<String, WindowLayout>,
+
+    // === Snapping ===
+    /// Pull the window flush against screen edges and corners after a drag
+    #[serde(default = "default_true")]
+    pub snap_to_edges: bool,
+
+    /// How close (points) an edge has to be before the window snaps to it
+    #[serde(default = "default_snap_threshold")]
+    pub snap_threshold: f32,
+
+    // === Docking ===
+    /// Reserve a strip along a screen edge so maximized windows don't cover us
+    #[serde(default)]
+    pub dock_edge: DockEdge,
+
+    /// Height of the docked strip (points)
+    #[serde(default = "default_dock_thickness")]
+    pub dock_thickness: f32,
+
+    /// Live wallpaper: fill the screen behind the desktop icons.
+    /// Takes priority over docking. `--no-wallpaper` turns it off at startup.
+    #[serde(default)]
+    pub wallpaper_mode: bool,
+
+    // === Extra Windows ===
+    /// Additional visualizer windows, each with its own visual profile
+    #[serde(default)]
+    pub extra_windows: Vec<ExtraWindow>,
+
+    pub show_stats: bool,
+
+    /// Peak / RMS / crest factor strip at the right edge
+    #[serde(default)]
+    pub meter_strip: bool,
+
+    /// Limit on GUI repaints per second
+    #[serde(default)]
+    pub fps_cap: FpsCap,
+
+    pub inspector_enabled: bool,
+
+    pub log_media_metadata: bool,
+
+    /// Pop up a short "Artist – Title" toast when the track changes
+    #[serde(default)]
+    pub track_toast: bool,
+
+    /// How many recent tracks the Track History keeps
+    #[serde(default = "default_track_history_len")]
+    pub track_history_len: usize,
+
+    /// How long a reference snapshot averages for (seconds)
+    #[serde(default = "default_snapshot_seconds")]
+    pub snapshot_seconds: f32,
+
+    /// Pink-noise measurement mode
+    #[serde(default)]
+    pub measurement: crate::measurement::MeasurementSettings,
+
+    /// Tools tab signal generator
+    #[serde(default)]
+    pub signal_gen: crate::signal_gen::GeneratorSettings,
+
+    /// Tuner overlay
+    #[serde(default)]
+    pub tuner: crate::tuner::TunerSettings,
+    /// Full-scale warning light
+    #[serde(default)]
+    pub clip: crate::clip_indicator::ClipSettings,
+
+    /// Refuse all outbound network requests (update check, gallery, Wikipedia, art)
+    #[serde(default)]
+    pub offline_mode: bool,
+
+    /// Stable ID of the selected input device (default: "Default").
+    /// Older configs stored the display name here; `migrate_selected_device` fixes those up.
+    pub selected_device: String,
+
+    /// Friendly name of the selected device, for display only
+    /// (so the picker can still show something sensible while it's unplugged)
+    #[serde(default)]
+    pub selected_device_name: String,
+
+    /// Give each device its own gain and noise floor offset, so a quiet mic
+    /// and a loud loopback don't need re-tuning (or separate profiles)
+    #[serde(default = "default_true")]
+    pub per_device_levels: bool,
+
+    /// Offsets per device, keyed by device id. The profile itself is never
+    /// touched, see `sensitivity` / `noise_floor`.
+    #[serde(default)]
+    pub device_levels: HashMap<String, DeviceLevels>,
+
+    /// The lowest dB value to display (the "floor")
+    pub noise_floor_db: f32,
+
+    /// Resample every device to a fixed internal rate before the FFT,
+    /// so frequency resolution doesn't change with the hardware rate
+    #[serde(default)]
+    pub resample_to_internal_rate: bool,
+
+    /// Capture buffer size (callback period) in frames; `None` = driver default.
+    /// Smaller reacts sooner but costs more wakeups.
+    #[serde(default)]
+    pub capture_buffer_frames: Option<u32>,
+
+    /// Open inputs in WASAPI exclusive mode (Windows); falls back to shared
+    /// mode with a notice if the device refuses
+    #[serde(default)]
+    pub exclusive_capture: bool,
+
+    // === Silence Detection ===
+    /// Audio whose peak stays below this level (dBFS) counts as silence
+    #[serde(default = "default_silence_threshold_db")]
+    pub silence_threshold_db: f32,
+
+    /// How long the bars hold still when packets stop arriving (counted from
+    /// the last one above the threshold) before they start falling. Covers
+    /// late packets and devices that go quiet between tracks.
+    #[serde(default = "default_silence_hold_ms")]
+    pub silence_hold_ms: f32,
+
+    // === Auto Sleep ===
+    /// Drop to a low repaint rate after a stretch of silence
+    #[serde(default = "default_true")]
+    pub auto_sleep_enabled: bool,
+
+    /// Seconds without signal above the noise floor before sleeping
+    #[serde(default = "default_auto_sleep_after_sec")]
+    pub auto_sleep_after_sec: f32,
+
+    /// Dim the window while asleep
+    #[serde(default)]
+    pub auto_sleep_dim: bool,
+
+    // === Hidden Window ===
+    /// Stop repainting while the window is minimized or covered by another
+    /// window (one check a second until it shows again)
+    #[serde(default = "default_true")]
+    pub hidden_throttle: bool,
+
+    /// Skip FFT processing too while hidden
+    #[serde(default)]
+    pub hidden_pause_fft: bool,
+
+    /// Show gain-staging hints (clipping, too quiet, ...)
+    #[serde(default = "default_true")]
+    pub gain_advisor_enabled: bool,
+
+    // === Media Settings ===
+    pub media_display_mode: MediaDisplayMode,
+    pub media_fade_duration_sec: f32,
+    /// Overlay corner, size and elements
+    #[serde(default)]
+    pub media_overlay: MediaOverlayLayout,
+    /// Synced lyrics under the Now Playing overlay
+    #[serde(default)]
+    pub lyrics: LyricsSettings,
+    /// What clicking the album art opens
+    #[serde(default)]
+    pub track_link: TrackLinkSettings,
+
+    // === MIDI Out ===
+    /// Band triggers sent as MIDI notes / CCs
+    #[serde(default)]
+    pub midi: crate::midi_out::MidiSettings,
+    /// MIDI CC / OSC input and its bindings
+    #[serde(default)]
+    pub remote: crate::remote_control::RemoteSettings,
+    /// Spectral features sent as OSC
+    #[serde(default)]
+    pub osc_out: crate::osc_out::OscOutSettings,
+    /// Localhost HTTP endpoint for scripts
+    #[serde(default)]
+    pub http_api: crate::http_api::HttpApiSettings,
+    /// Localhost line-JSON socket for Stream Deck plugins
+    #[serde(default)]
+    pub stream_deck: crate::stream_deck::StreamDeckSettings,
+    /// Format, decimation and rotation of the spectrum data logger
+    #[serde(default)]
+    pub spectrum_log: crate::spectrum_log::SpectrumLogSettings,
+
+    // === BeOS Settings === 
+    /// Horizontal offset for the sliding BeOS tab
+    pub beos_tab_offset: f32,
+
+    /// Whether the window is 'shuttered' (collapsed into the tab)
+    pub beos_window_collapsed: bool,
+
+    // === Community Presets ===
+    /// JSON index for the "Browse community presets" panel
+    #[serde(default = "default_gallery_url")]
+    pub preset_gallery_url: String,
+
+    // === Connected Accounts ===
+    /// Service ids (see `secrets::KNOWN_SERVICES`) that have a token in the
+    /// OS keyring. The tokens themselves are never written to this file.
+    #[serde(default)]
+    pub connected_accounts: Vec<String>,
+
+    /// Color presets switched by local time of day
+    #[serde(default)]
+    pub theme_schedule: crate::theme_schedule::ThemeSchedule,
+
+    /// Controller input (D-pad, bumpers, Start overlay) for living-room PCs
+    #[serde(default)]
+    pub gamepad_enabled: bool,
+
+    /// Quick actions shown in the right-click menu
+    #[serde(default)]
+    pub context_menu: ContextMenuItems,
+}
+
+/// Part of the settings "Reset to Defaults" puts back, roughly one
+/// settings tab each
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConfigSection {
+    /// The visual profile's look (not its colors or audio response)
+    Visual,
+    Audio,
+    Colors,
+    Window,
+    All,
+}
+
+impl ConfigSection {
+    pub const ALL: [Self; 5] = [Self::Visual, Self::Audio, Self::Colors, Self::Window, Self::All];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Visual => "Visual",
+            Self::Audio => "Audio",
+            Self::Colors => "Colors",
+            Self::Window => "Window",
+            Self::All => "Everything",
+        }
+    }
 }
 
-impl SharedState {
-    /// Create a new `SharedState` with loaded (or default) config.
-    pub fn new() -> Self {
-        let config = AppConfig::load();
+/// Which quick actions the right-click menu offers. Settings and Exit are
+/// always there, so the window can't be left without a way back.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct ContextMenuItems {
+    /// Visual profile and color preset submenus
+    pub presets: bool,
+    /// Capture device submenu
+    pub devices: bool,
+    pub ghost_mode: bool,
+    /// Freeze the display
+    pub pause: bool,
+    pub screenshot: bool,
+    pub mini_mode: bool,
+}
 
-        let user_color_presets = AppConfig::load_user_color_presets();
-        let user_visual_presets = AppConfig::load_user_visual_presets();
-        tracing::info!("[State] Loaded {} user color presets", user_color_presets.len());
-        tracing::info!("[State] Loaded {} user visual presets", user_visual_presets.len());
+impl Default for ContextMenuItems {
+    fn default() -> Self {
+        Self { presets: true, devices: false, ghost_mode: true, pause: false, screenshot: true, mini_mode: true }
+    }
+}
 
-        Self {
-            visualization: VisualizationData::new(config.profile.num_bars),
-            performance: PerformanceStats::default(),
-            config,
-            audio_devices: Vec::new(),
-            device_changed: false,
-            refresh_devices_requested: false,
-            media_info: None,
-            last_media_update: None,
-            user_color_presets,
-            user_visual_presets,
-            update_url: None,
-            update_dismissed: false,
-            lod_bar_limit: None,
-        }
+/// Lyrics pane look and timing
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct LyricsSettings {
+    pub enabled: bool,
+    pub font: ThemeFont,
+    pub opacity: f32,
+    /// Lines shown around the current one (before and after)
+    pub context_lines: usize,
+    /// Shift against the playback clock, positive shows lines earlier (ms)
+    pub offset_ms: i32,
+}
+
+impl Default for LyricsSettings {
+    fn default() -> Self {
+        Self { enabled: false, font: ThemeFont::Medium, opacity: 0.85, context_lines: 1, offset_ms: 0 }
     }
 }
-// === Data Structures ====
 
-/// Live visualization data updated by the FFT thread each frame.
-#[derive(Clone)]
-pub struct VisualizationData {
-    /// Bar heights in dB ( typically -80 to +40 range)
-    pub bars: Vec<f32>,
+/// Where clicking the album art goes
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug, Default)]
+pub enum TrackLinkSource {
+    /// Article for the album (or artist + title), checked against the artist
+    #[default]
+    Wikipedia,
+    /// Release (or recording) page from the MusicBrainz search API
+    MusicBrainz,
+    /// Discogs release search; their API wants a token, so no lookup
+    Discogs,
+    /// `TrackLinkSettings::template` with the track filled in
+    Custom,
+}
 
-    /// Peak indicator heights in dB
-    pub peaks: Vec<f32>,
+impl TrackLinkSource {
+    pub const ALL: [TrackLinkSource; 4] = [Self::Wikipedia, Self::MusicBrainz, Self::Discogs, Self::Custom];
 
-    /// Raw audio waveform buffer for oscilloscope mode.
-    // We keep a small buffer for drawing
-    pub waveform: Vec<f32>,
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Wikipedia => "Wikipedia",
+            Self::MusicBrainz => "MusicBrainz",
+            Self::Discogs => "Discogs",
+            Self::Custom => "Custom URL",
+        }
+    }
+}
 
-    /// When this data was last updated
-    pub timestamp: Instant,
+/// Album art click-through
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct TrackLinkSettings {
+    pub source: TrackLinkSource,
+    /// URL with `{artist}`, `{title}` and `{album}` placeholders (Custom)
+    pub template: String,
 }
 
-impl VisualizationData {
-    /// Create new visualization data initialized to silence.
-    pub fn new(num_bars: usize) -> Self {
+impl Default for TrackLinkSettings {
+    fn default() -> Self {
         Self {
-            bars: vec![SILENCE_DB; num_bars],
-            peaks: vec![SILENCE_DB; num_bars],
-            waveform: vec![0.0; 2048],
-            timestamp: Instant::now(),
+            source: TrackLinkSource::Wikipedia,
+            template: "https://www.last.fm/music/{artist}/_/{title}".to_string(),
         }
     }
 }
 
-/// Performance statistics updated by the FFT and GUI threads.
-#[derive(Clone, Default)]
-pub struct PerformanceStats {
-    pub frame_count: u64,
-    pub fft_ave_time: Duration,
-    pub fft_min_time: Duration,
-    pub fft_max_time: Duration,
-    pub gui_fps: f32,
-    pub fft_info: FFTInfo,
+fn default_mini_mode_key() -> egui::Key {
+    egui::Key::M
 }
 
+fn default_quick_switch_key() -> egui::Key {
+    egui::Key::Space
+}
 
-// ==== Configuration ====
-
-/// Persistent application configuration, serialized to/from JSON.
-#[derive(Clone, Serialize, Deserialize)]
-pub struct AppConfig {
-    pub profile: VisualProfile,
-
-    // === System Settings ===
-    
-    /// Saved Window dimensions [width, height]
-    pub window_size:  [f32; 2],
-
-    /// Saved Window position on screen [x, y]
-    pub window_position: Option<[f32; 2]>,
-   
-    pub always_on_top: bool,
-
-    ///  "Ghost Mode": Window is click-through until focused with alt-tab
-    pub window_locked: bool,
+fn default_track_history_len() -> usize {
+    50
+}
 
-    /// Show window title bar and borders.
-    pub window_decorations: bool,
+fn default_snapshot_seconds() -> f32 {
+    3.0
+}
 
-    /// User-definable Hide shortcut key
-    pub minimize_key: egui::Key,
+fn default_mini_num_bars() -> usize {
+    32
+}
 
-    pub show_stats: bool,
+fn default_mini_window_size() -> [f32; 2] {
+    [300.0, 40.0]
+}
 
-    pub inspector_enabled: bool,
+fn default_snap_threshold() -> f32 {
+    16.0
+}
 
-    pub log_media_metadata: bool,
+fn default_dock_thickness() -> f32 {
+    60.0
+}
 
-    /// Name of selected input device (default: "Default")
-    pub selected_device: String,
+fn default_silence_threshold_db() -> f32 {
+    SILENCE_DB
+}
 
-    /// The lowest dB value to display (the "floor")
-    pub noise_floor_db: f32,
+fn default_silence_hold_ms() -> f32 {
+    50.0
+}
 
-    // === Media Settings ===
-    pub media_display_mode: MediaDisplayMode,
-    pub media_fade_duration_sec: f32,
+fn default_true() -> bool {
+    true
+}
 
-    // === BeOS Settings === 
-    /// Horizontal offset for the sliding BeOS tab
-    pub beos_tab_offset: f32,
+fn default_auto_sleep_after_sec() -> f32 {
+    30.0
+}
 
-    /// Whether the window is 'shuttered' (collapsed into the tab)
-    pub beos_window_collapsed: bool,
+fn default_gallery_url() -> String {
+    crate::preset_gallery::DEFAULT_GALLERY_URL.to_string()
 }
 
 impl Default for AppConfig {
@@ -428,40 +1819,318 @@ impl Default for AppConfig {
             window_position: None,
             always_on_top: false,
             window_locked: false,
+            ghost_hot_corner: HotCorner::default(),
+            ghost_handle: false,
             window_decorations: false,
+            aspect_lock: None,
             minimize_key: egui::Key::H,
+            mini_mode: false,
+            mini_mode_key: default_mini_mode_key(),
+            quick_switch_key: default_quick_switch_key(),
+            peek: Default::default(),
+            mini_num_bars: default_mini_num_bars(),
+            mini_window_size: default_mini_window_size(),
+            mini_window_position: None,
+            monitor_layouts: HashMap::new(),
+            snap_to_edges: true,
+            snap_threshold: default_snap_threshold(),
+            dock_edge: DockEdge::default(),
+            dock_thickness: default_dock_thickness(),
+            wallpaper_mode: false,
+            extra_windows: Vec::new(),
             inspector_enabled: true,
             log_media_metadata: false,
+            track_toast: false,
+            track_history_len: default_track_history_len(),
+            snapshot_seconds: default_snapshot_seconds(),
+            measurement: Default::default(),
+            signal_gen: Default::default(),
+            tuner: Default::default(),
+            clip: Default::default(),
+            offline_mode: false,
             show_stats: false,
+            meter_strip: false,
+            fps_cap: FpsCap::default(),
             selected_device: "Default".to_string(),
+            selected_device_name: String::new(),
+            per_device_levels: true,
+            device_levels: HashMap::new(),
             noise_floor_db: -60.0,
+            resample_to_internal_rate: false,
+            capture_buffer_frames: None,
+            exclusive_capture: false,
+            silence_threshold_db: default_silence_threshold_db(),
+            silence_hold_ms: default_silence_hold_ms(),
+            auto_sleep_enabled: true,
+            auto_sleep_after_sec: default_auto_sleep_after_sec(),
+            auto_sleep_dim: false,
+            hidden_throttle: true,
+            hidden_pause_fft: false,
+            gain_advisor_enabled: true,
             media_display_mode: MediaDisplayMode::FadeOnUpdate,
             media_fade_duration_sec: 5.0,
+            media_overlay: MediaOverlayLayout::default(),
+            lyrics: LyricsSettings::default(),
+            track_link: TrackLinkSettings::default(),
+            midi: Default::default(),
+            remote: Default::default(),
+            osc_out: Default::default(),
+            http_api: Default::default(),
+            stream_deck: Default::default(),
+            spectrum_log: Default::default(),
             beos_tab_offset: 20.0,
             beos_window_collapsed: false,
+            preset_gallery_url: default_gallery_url(),
+            connected_accounts: Vec::new(),
+            theme_schedule: Default::default(),
+            context_menu: ContextMenuItems::default(),
+            gamepad_enabled: false,
         }
     }
 }
 
 impl AppConfig {
+    /// Window size and position for the active layout (mini mode keeps its own)
+    pub fn active_geometry(&self) -> ([f32; 2], Option<[f32; 2]>) {
+        if self.mini_mode {
+            (self.mini_window_size, self.mini_window_position)
+        } else {
+            (self.window_size, self.window_position)
+        }
+    }
+
+    pub fn active_geometry_mut(&mut self) -> (&mut [f32; 2], &mut Option<[f32; 2]>) {
+        if self.mini_mode {
+            (&mut self.mini_window_size, &mut self.mini_window_position)
+        } else {
+            (&mut self.window_size, &mut self.window_position)
+        }
+    }
+
+    /// Store the current window geometry as the layout for monitor setup `key`
+    pub fn remember_layout(&mut self, key: &str) {
+        let layout = WindowLayout {
+            window_size: self.window_size,
+            window_position: self.window_position,
+            mini_window_size: self.mini_window_size,
+            mini_window_position: self.mini_window_position,
+        };
+        self.monitor_layouts.insert(key.to_string(), layout);
+    }
+
+    /// Switch to the geometry saved for monitor setup `key`.
+    /// Returns false (and changes nothing) if this setup hasn't been seen before.
+    pub fn restore_layout(&mut self, key: &str) -> bool {
+        let Some(layout) = self.monitor_layouts.get(key).cloned() else { return false };
+        self.window_size = layout.window_size;
+        self.window_position = layout.window_position;
+        self.mini_window_size = layout.mini_window_size;
+        self.mini_window_position = layout.mini_window_position;
+        true
+    }
+
+    /// Bars requested by the profile, capped while in mini mode
+    pub fn requested_bars(&self) -> usize {
+        if self.mini_mode {
+            self.profile.num_bars.min(self.mini_num_bars.max(1))
+        } else {
+            self.profile.num_bars
+        }
+    }
+
+    /// Noise floor in effect: the profile's override, else the global
+    /// setting, moved by the selected device's offset
+    pub fn noise_floor(&self) -> f32 {
+        self.noise_floor_for(&self.profile)
+    }
+
+    /// Noise floor for drawing `profile` (an extra window's) with these settings
+    pub fn noise_floor_for(&self, profile: &VisualProfile) -> f32 {
+        profile.engine.map_or(self.noise_floor_db, |e| e.noise_floor_db) + self.device_offsets().noise_floor_offset_db
+    }
+
+    /// Noise floor without the device offset, what the slider edits
+    fn base_noise_floor(&self) -> f32 {
+        self.profile.engine.map_or(self.noise_floor_db, |e| e.noise_floor_db)
+    }
+
+    /// Input gain in effect: the profile's sensitivity with the selected
+    /// device's gain offset
+    pub fn sensitivity(&self) -> f32 {
+        self.profile.sensitivity * 10f32.powf(self.device_offsets().gain_db / 20.0)
+    }
+
+    /// Offsets for the selected device (none when per-device levels are off)
+    pub fn device_offsets(&self) -> DeviceLevels {
+        if !self.per_device_levels {
+            return DeviceLevels::default();
+        }
+        self.device_levels.get(&self.selected_device).copied().unwrap_or_default()
+    }
+
+    pub fn noise_floor_mut(&mut self) -> &mut f32 {
+        match &mut self.profile.engine {
+            Some(engine) => &mut engine.noise_floor_db,
+            None => &mut self.noise_floor_db,
+        }
+    }
+
+    /// Internal-rate resampling in effect: the profile's override, else the global setting
+    pub fn resample_enabled(&self) -> bool {
+        self.profile.engine.map_or(self.resample_to_internal_rate, |e| e.resample_to_internal_rate)
+    }
+
+    pub fn resample_enabled_mut(&mut self) -> &mut bool {
+        match &mut self.profile.engine {
+            Some(engine) => &mut engine.resample_to_internal_rate,
+            None => &mut self.resample_to_internal_rate,
+        }
+    }
+
+    /// Silence threshold in effect: the profile's override, else the global setting
+    pub fn silence_threshold(&self) -> f32 {
+        self.profile.engine.map_or(self.silence_threshold_db, |e| e.silence_threshold_db)
+    }
+
+    pub fn silence_threshold_mut(&mut self) -> &mut f32 {
+        match &mut self.profile.engine {
+            Some(engine) => &mut engine.silence_threshold_db,
+            None => &mut self.silence_threshold_db,
+        }
+    }
+
+    /// Silence hold in effect: the profile's override, else the global setting
+    pub fn silence_hold(&self) -> f32 {
+        self.profile.engine.map_or(self.silence_hold_ms, |e| e.silence_hold_ms)
+    }
+
+    pub fn silence_hold_mut(&mut self) -> &mut f32 {
+        match &mut self.profile.engine {
+            Some(engine) => &mut engine.silence_hold_ms,
+            None => &mut self.silence_hold_ms,
+        }
+    }
+
+    /// Snapshot of the engine settings currently in effect, for storing in a profile
+    pub fn current_engine(&self) -> EngineOverrides {
+        EngineOverrides {
+            noise_floor_db: self.base_noise_floor(),
+            resample_to_internal_rate: self.resample_enabled(),
+            silence_threshold_db: self.silence_threshold(),
+            silence_hold_ms: self.silence_hold(),
+        }
+    }
+
+    /// How the capture thread should open the stream
+    pub fn stream_options(&self) -> crate::audio_capture::StreamOptions {
+        crate::audio_capture::StreamOptions {
+            buffer_frames: self.capture_buffer_frames,
+            exclusive: self.exclusive_capture,
+        }
+    }
+
+    /// Put `section` back to the defaults, leaving the rest alone.
+    /// The device choice, window geometry and connected accounts only go with `All`
+    /// (and accounts never do: their tokens stay in the keyring).
+    pub fn reset(&mut self, section: ConfigSection) {
+        let defaults = Self::default();
+        let stock = VisualProfile::default();
+        let profile = &mut self.profile;
+        match section {
+            ConfigSection::Visual => {
+                *profile = VisualProfile {
+                    color_link: profile.color_link.clone(),
+                    background: profile.background,
+                    engine: profile.engine,
+                    sensitivity: profile.sensitivity,
+                    attack_time_ms: profile.attack_time_ms,
+                    release_time_ms: profile.release_time_ms,
+                    peak_hold_time_ms: profile.peak_hold_time_ms,
+                    peak_release_time_ms: profile.peak_release_time_ms,
+                    peak_gravity: profile.peak_gravity,
+                    aggregation_mode: profile.aggregation_mode,
+                    ..stock
+                };
+            }
+            ConfigSection::Audio => {
+                profile.sensitivity = stock.sensitivity;
+                profile.attack_time_ms = stock.attack_time_ms;
+                profile.release_time_ms = stock.release_time_ms;
+                profile.peak_hold_time_ms = stock.peak_hold_time_ms;
+                profile.peak_release_time_ms = stock.peak_release_time_ms;
+                profile.peak_gravity = stock.peak_gravity;
+                profile.aggregation_mode = stock.aggregation_mode;
+                profile.engine = stock.engine;
+                self.noise_floor_db = defaults.noise_floor_db;
+                self.resample_to_internal_rate = defaults.resample_to_internal_rate;
+                self.capture_buffer_frames = defaults.capture_buffer_frames;
+                self.exclusive_capture = defaults.exclusive_capture;
+                self.silence_threshold_db = defaults.silence_threshold_db;
+                self.silence_hold_ms = defaults.silence_hold_ms;
+                self.gain_advisor_enabled = defaults.gain_advisor_enabled;
+                self.per_device_levels = defaults.per_device_levels;
+                self.device_levels.clear();
+                self.clip = defaults.clip;
+                self.snapshot_seconds = defaults.snapshot_seconds;
+                self.midi = defaults.midi;
+            }
+            ConfigSection::Colors => {
+                profile.color_link = stock.color_link;
+                profile.background = stock.background;
+                self.theme_schedule = defaults.theme_schedule;
+            }
+            ConfigSection::Window => {
+                self.always_on_top = defaults.always_on_top;
+                self.window_locked = defaults.window_locked;
+                self.ghost_hot_corner = defaults.ghost_hot_corner;
+                self.ghost_handle = defaults.ghost_handle;
+                self.window_decorations = defaults.window_decorations;
+                self.aspect_lock = defaults.aspect_lock;
+                self.minimize_key = defaults.minimize_key;
+                self.mini_mode_key = defaults.mini_mode_key;
+                self.quick_switch_key = defaults.quick_switch_key;
+                self.peek = defaults.peek;
+                self.mini_num_bars = defaults.mini_num_bars;
+                self.snap_to_edges = defaults.snap_to_edges;
+                self.snap_threshold = defaults.snap_threshold;
+                self.dock_edge = defaults.dock_edge;
+                self.dock_thickness = defaults.dock_thickness;
+                self.wallpaper_mode = defaults.wallpaper_mode;
+                self.context_menu = defaults.context_menu;
+                self.gamepad_enabled = defaults.gamepad_enabled;
+                self.media_display_mode = defaults.media_display_mode;
+                self.media_fade_duration_sec = defaults.media_fade_duration_sec;
+                self.media_overlay = defaults.media_overlay;
+                self.track_toast = defaults.track_toast;
+                self.track_history_len = defaults.track_history_len;
+                self.lyrics = defaults.lyrics;
+                self.track_link = defaults.track_link;
+                self.remote = defaults.remote;
+                self.osc_out = defaults.osc_out;
+                self.http_api = defaults.http_api;
+                self.stream_deck = defaults.stream_deck;
+            }
+            ConfigSection::All => {
+                let accounts = std::mem::take(&mut self.connected_accounts);
+                *self = Self { connected_accounts: accounts, ..defaults };
+            }
+        }
+    }
+
     /// Returns the standard OS config path, e.g.:
     /// Windows: C:\Users\Username\AppData\Roaming\BeSpec
     /// MacOS: /Users/Username/Library/Application Support/BeSpec
     /// Linux: /home/username/.config/BeSpec
-    fn get_config_path() -> PathBuf {
-        if let Some(proj_dirs) = ProjectDirs::from("","","BeSpec") {
-            let config_dir = proj_dirs.config_dir();
+    /// (or the per-user subfolder when started with `--user <name>`)
+    pub fn get_config_path() -> PathBuf {
+        let config_dir = &crate::paths::get().config_dir;
 
-            // Ensure directory exists
-            if let Err(e) = fs::create_dir_all(config_dir) {
-                tracing::error!("[Config] Error creating config directory: {}", e);
-            }
-
-            return config_dir.join("config.json");
+        // Ensure directory exists
+        if let Err(e) = fs::create_dir_all(config_dir) {
+            tracing::error!("[Config] Error creating config directory: {}", e);
         }
-        
-        // Fallback
-        PathBuf::from("BeSpec_config.json")
+
+        config_dir.join("config.json")
     }
 
     /// Load application config from disk, falling back to defaults.
@@ -500,57 +2169,64 @@ impl AppConfig {
     }
 
        
+    /// Old configs stored the device *display name* in `selected_device`.
+    /// If it doesn't match any ID but does match a name, swap in the ID.
+    /// Also fills in `selected_device_name` when it's missing.
+    /// Returns true if the config changed.
+    pub fn migrate_selected_device(&mut self, devices: &[AudioDeviceInfo]) -> bool {
+        if self.selected_device == "Default" {
+            return false;
+        }
+
+        if let Some(dev) = devices.iter().find(|d| d.id == self.selected_device) {
+            if self.selected_device_name != dev.name {
+                self.selected_device_name = dev.name.clone();
+                return true;
+            }
+            return false;
+        }
+
+        if let Some(dev) = devices.iter().find(|d| d.name == self.selected_device) {
+            tracing::info!(
+                "[Config] Migrated selected_device from name '{}' to id '{}'",
+                self.selected_device, dev.id
+            );
+            self.selected_device = dev.id.clone();
+            self.selected_device_name = dev.name.clone();
+            return true;
+        }
+        false
+    }
+
     /// Resolve the active color profile by checking user presets first,
     /// then falling back to built-in presets. Applies background override if set.
     pub fn resolve_colors(&self, user_presets: &[ColorProfile]) -> ColorProfile {
-        match &self.profile.color_link {
-            ColorRef::Custom(colors) => {
-                let mut c = colors.clone();
-                //Apply optional background override
-                if let Some(bg) = self.profile.background {c.background = bg; }
-                c
-            },
-            ColorRef::Preset(name) => {
-                // 1. Try finding User presets first
-                if let Some(p)  = user_presets.iter().find(|p| &p.name == name) {
-                    let mut c = p.clone();
-                    if let Some (bg) = self.profile.background {c.background = bg; }
-                    return c;
-                }
-
-                // 2. Fallback into built-in presets
-                let mut c = ColorProfile::find_by_name(name).unwrap_or_default();
-                if let Some (bg) = self.profile.background {c.background = bg; }
-                c
-            }   
-        }
+        self.profile.resolve_colors(user_presets)
     }
 
     pub fn load_user_color_presets() -> Vec<ColorProfile> {
         let mut profiles = Vec::new();
 
         // Path: ../BeSpec/presets/colors/
-        if let Some(proj_dirs) = ProjectDirs::from("","","BeSpec") {
-            let preset_dir = proj_dirs.data_dir().join("presets").join("colors");
-
-            if preset_dir.exists() {
-                if let Ok(entries) = fs::read_dir(&preset_dir) {
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        if path.extension().map_or(false, |ext| ext == "json") {
-                            if let Ok(content) = fs::read_to_string(&path) {
-                                match serde_json::from_str::<ColorProfile>(&content) {
-                                    Ok(profile) => {
-                                        if profiles.iter().any(|p: &ColorProfile| p.name == profile.name) {
-                                            tracing::warn!("[Presets] Duplicate color profile name '{}' in file {:?}. Skipping.", profile.name, path);
-                                        } else {
-                                            tracing::info!("[Config] Loaded user color preset: {}", profile.name);
-                                            profiles.push(profile);
-                                        }
-                                    },
-                                    Err(e) => {
-                                        tracing::error!("[Config] Failed to parse color preset {:?}: {}", path, e);
+        let preset_dir = crate::paths::get().preset_dir("colors");
+
+        if preset_dir.exists() {
+            if let Ok(entries) = fs::read_dir(&preset_dir) {
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    if path.extension().map_or(false, |ext| ext == "json") {
+                        if let Ok(content) = fs::read_to_string(&path) {
+                            match serde_json::from_str::<ColorProfile>(&content) {
+                                Ok(profile) => {
+                                    if profiles.iter().any(|p: &ColorProfile| p.name == profile.name) {
+                                        tracing::warn!("[Presets] Duplicate color profile name '{}' in file {:?}. Skipping.", profile.name, path);
+                                    } else {
+                                        tracing::info!("[Config] Loaded user color preset: {}", profile.name);
+                                        profiles.push(profile);
                                     }
+                                },
+                                Err(e) => {
+                                    tracing::error!("[Config] Failed to parse color preset {:?}: {}", path, e);
                                 }
                             }
                         }
@@ -562,52 +2238,47 @@ impl AppConfig {
     }
 
     pub fn save_user_color_preset(profile: &ColorProfile) -> std::io::Result<()> {
-        if let Some(proj_dirs) = ProjectDirs::from("","","BeSpec") {
-            let preset_dir = proj_dirs.data_dir().join("presets").join("colors");
-            fs::create_dir_all(&preset_dir)?;
+        let preset_dir = crate::paths::get().preset_dir("colors");
+        fs::create_dir_all(&preset_dir)?;
 
-            let filename = format!("{}.json",Self::sanitize_filename(&profile.name));
-            
-            let json = serde_json::to_string_pretty(profile)?;
-            fs::write(preset_dir.join(filename), json)?;
-        }
+        let filename = format!("{}.json",Self::sanitize_filename(&profile.name));
+        
+        let json = serde_json::to_string_pretty(profile)?;
+        fs::write(preset_dir.join(filename), json)?;
         Ok(())
     }
 
     pub fn delete_user_color_preset(name: &str) -> std::io::Result<()> {
-        if let Some(proj_dirs) = ProjectDirs::from("","","BeSpec") {
-            let preset_dir = proj_dirs.data_dir().join("presets").join("colors");
-            let filename = format!("{}.json", Self::sanitize_filename(name));
-            let path = preset_dir.join(filename);
-            if path.exists() {
-                fs::remove_file(path)?;
-                tracing::info!("[Presets] Deleted color preset: {}", name);
-            }
+        let preset_dir = crate::paths::get().preset_dir("colors");
+        let filename = format!("{}.json", Self::sanitize_filename(name));
+        let path = preset_dir.join(filename);
+        if path.exists() {
+            fs::remove_file(path)?;
+            tracing::info!("[Presets] Deleted color preset: {}", name);
         }
+        crate::preset_thumbnail::delete(crate::preset_thumbnail::PresetKind::Color, name);
         Ok(())
     }
 
     pub fn load_user_visual_presets() -> Vec<VisualProfile> {
         let mut profiles = Vec::new();
-        if let Some(proj_dirs) = ProjectDirs::from("","","BeSpec") {
-            let preset_dir = proj_dirs.data_dir().join("presets").join("visuals");
-            if preset_dir.exists() {
-                if let Ok(entries) = fs::read_dir(preset_dir) {
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        if path.extension().map_or(false, |ext| ext == "json") {
-                            if let Ok(content) = fs::read_to_string(&path) {
-                                match serde_json::from_str::<VisualProfile>(&content) {
-                                    Ok(profile) => {
-                                        // FIX: Check for duplicates before adding
-                                        if profiles.iter().any(|p: &VisualProfile| p.name == profile.name) {
-                                            tracing::warn!("[Presets] Duplicate visual profile name '{}' in file {:?}. Skipping.", profile.name, path);
-                                        } else {
-                                            profiles.push(profile);
-                                        }
-                                    },
-                                    Err(e) => tracing::warn!("[Presets] Failed to parse {:?}: {}", path, e),
-                                }
+        let preset_dir = crate::paths::get().preset_dir("visuals");
+        if preset_dir.exists() {
+            if let Ok(entries) = fs::read_dir(preset_dir) {
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    if path.extension().map_or(false, |ext| ext == "json") {
+                        if let Ok(content) = fs::read_to_string(&path) {
+                            match serde_json::from_str::<VisualProfile>(&content) {
+                                Ok(profile) => {
+                                    // FIX: Check for duplicates before adding
+                                    if profiles.iter().any(|p: &VisualProfile| p.name == profile.name) {
+                                        tracing::warn!("[Presets] Duplicate visual profile name '{}' in file {:?}. Skipping.", profile.name, path);
+                                    } else {
+                                        profiles.push(profile);
+                                    }
+                                },
+                                Err(e) => tracing::warn!("[Presets] Failed to parse {:?}: {}", path, e),
                             }
                         }
                     }
@@ -618,33 +2289,30 @@ impl AppConfig {
     }
 
     pub fn save_user_visual_preset(profile: &VisualProfile) -> std::io::Result<()> {
-        if let Some(proj_dirs) = ProjectDirs::from("","","BeSpec") {
-            let preset_dir = proj_dirs.data_dir().join("presets").join("visuals");
-            fs::create_dir_all(&preset_dir)?;
+        let preset_dir = crate::paths::get().preset_dir("visuals");
+        fs::create_dir_all(&preset_dir)?;
 
-            let filename = format!("{}.json",Self::sanitize_filename(&profile.name));
-            
-            let json = serde_json::to_string_pretty(profile)?;
-            fs::write(preset_dir.join(filename), json)?;
-        }
+        let filename = format!("{}.json",Self::sanitize_filename(&profile.name));
+        
+        let json = serde_json::to_string_pretty(profile)?;
+        fs::write(preset_dir.join(filename), json)?;
         Ok(())
     }
 
     pub fn delete_user_visual_preset(name: &str) -> std::io::Result<()> {
-        if let Some(proj_dirs) = ProjectDirs::from("","","BeSpec") {
-            let preset_dir = proj_dirs.data_dir().join("presets").join("visuals");
-            let filename = format!("{}.json", Self::sanitize_filename(name));
-            let path = preset_dir.join(filename);
-            if path.exists() {
-                fs::remove_file(path)?;
-                tracing::info!("[Presets] Deleted visual preset: {}", name);
-            }
+        let preset_dir = crate::paths::get().preset_dir("visuals");
+        let filename = format!("{}.json", Self::sanitize_filename(name));
+        let path = preset_dir.join(filename);
+        if path.exists() {
+            fs::remove_file(path)?;
+            tracing::info!("[Presets] Deleted visual preset: {}", name);
         }
+        crate::preset_thumbnail::delete(crate::preset_thumbnail::PresetKind::Visual, name);
         Ok(())
     }
 
     // Helper: Sanitize Filename to avoid duplicates / illegal chars
-    fn sanitize_filename(name: &str) -> String {
+    pub(crate) fn sanitize_filename(name: &str) -> String {
         name.trim()
             .replace(" ", "_")
             .replace(|c: char| !c.is_alphanumeric() && c != '_', "")
@@ -769,7 +2437,87 @@ mod tests {
         assert_ne!(resolved.low, Color32::BLACK, "Preset colors should still be present");
     }
 
-    // --- 3. Filename Sanitization ---
+    // --- 3. Device ID Migration ---
+    fn device(id: &str, name: &str) -> AudioDeviceInfo {
+        AudioDeviceInfo {
+            id: id.to_string(),
+            name: name.to_string(),
+            sample_rates: vec![48000],
+            default_sample_rate: 48000,
+            channels: 2,
+            channel_counts: vec![2],
+            sample_formats: vec!["f32".to_string()],
+            default_sample_format: "f32".to_string(),
+            is_default: false,
+        }
+    }
+
+    #[test]
+    fn test_migrate_selected_device_from_name() {
+        let devices = vec![
+            device("alsa_output.usb-mic.monitor", "USB Mic (Output Monitor)"),
+            device("alsa_output.hdmi.monitor", "HDMI (Output Monitor)"),
+        ];
+
+        // Old config: display name stored
+        let mut config = AppConfig { selected_device: "HDMI (Output Monitor)".to_string(), ..Default::default() };
+        assert!(config.migrate_selected_device(&devices));
+        assert_eq!(config.selected_device, "alsa_output.hdmi.monitor");
+        assert_eq!(config.selected_device_name, "HDMI (Output Monitor)");
+
+        // Already migrated: nothing to do
+        assert!(!config.migrate_selected_device(&devices));
+
+        // Unknown device (unplugged): left alone so it reconnects later
+        let mut config = AppConfig { selected_device: "gone".to_string(), ..Default::default() };
+        assert!(!config.migrate_selected_device(&devices));
+        assert_eq!(config.selected_device, "gone");
+    }
+
+    #[test]
+    fn test_levels_follow_the_device() {
+        let mut state = SharedState::with_config(AppConfig::default());
+        state.audio_devices = vec![device("mic", "USB Mic"), device("loop", "Speakers (Loopback)")];
+        state.config.profile.sensitivity = 2.0;
+        state.config.device_levels.insert("mic".to_string(), DeviceLevels { gain_db: 20.0, noise_floor_offset_db: -30.0 });
+
+        state.select_device("mic", "USB Mic");
+        assert!((state.config.sensitivity() - 20.0).abs() < 1e-4);
+        assert_eq!(state.config.noise_floor(), -90.0);
+        // The profile itself stays as it was
+        assert_eq!(state.config.profile.sensitivity, 2.0);
+        assert_eq!(state.config.current_engine().noise_floor_db, -60.0);
+
+        // A device without offsets gets the profile's levels
+        state.select_device("loop", "Speakers (Loopback)");
+        assert_eq!(state.config.sensitivity(), 2.0);
+        assert_eq!(state.config.noise_floor(), -60.0);
+
+        // Switched off: the offsets don't apply
+        state.select_device("mic", "USB Mic");
+        state.config.per_device_levels = false;
+        assert_eq!(state.config.sensitivity(), 2.0);
+    }
+
+    #[test]
+    fn test_unusable_device_is_not_selected() {
+        let mut state = SharedState::with_config(AppConfig::default());
+        let mut broken = device("dsd", "DSD Only");
+        broken.default_sample_format = "dsd_u32".to_string();
+        state.audio_devices = vec![device("usb", "USB Audio"), broken];
+        state.config.selected_device = "usb".to_string();
+
+        assert!(!state.select_device("dsd", "DSD Only"));
+        assert_eq!(state.config.selected_device, "usb");
+        assert!(!state.device_changed);
+        assert_eq!(state.last_error.as_ref().map(|e| e.kind()), Some("audio"));
+
+        assert!(state.select_device("Default", ""));
+        assert_eq!(state.config.selected_device, "Default");
+        assert!(state.device_changed);
+    }
+
+    // --- 4. Filename Sanitization ---
     // Critical: Prevents file system errors or overwrites
     #[test]
     fn test_filename_sanitization() {
@@ -801,6 +2549,7 @@ mod tests {
             text: Color32::WHITE,
             inspector_bg: Color32::BLACK,
             inspector_fg: Color32::WHITE,
+            gradient: Vec::new(),
         };
 
         let json = serde_json::to_string_pretty(&preset).unwrap();
@@ -817,6 +2566,41 @@ mod tests {
         let _ = fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn test_shown_modes_with_split_view() {
+        let mut profile = VisualProfile { visual_mode: VisualMode::SolidBars, ..VisualProfile::default() };
+        assert_eq!(profile.shown_modes().collect::<Vec<_>>(), vec![VisualMode::SolidBars]);
+
+        profile.split = SplitView { enabled: true, mode: VisualMode::Oscilloscope, ..SplitView::default() };
+        assert_eq!(profile.shown_modes().collect::<Vec<_>>(), vec![VisualMode::SolidBars, VisualMode::Oscilloscope]);
+
+        // The same mode twice is just the one view
+        profile.split.mode = VisualMode::SolidBars;
+        assert_eq!(profile.shown_modes().count(), 1);
+    }
+
+    #[test]
+    fn test_gradient_stops_fall_back_to_low_high() {
+        // Profiles saved before multi-stop gradients have no "gradient" key
+        let mut colors: ColorProfile = serde_json::from_str(
+            &serde_json::to_string(&ColorProfile::default()).unwrap()
+        ).unwrap();
+        assert!(colors.gradient.is_empty());
+        let stops: Vec<GradientStop> = colors.gradient_stops().collect();
+        assert_eq!(stops.iter().map(|s| s.color).collect::<Vec<_>>(), [colors.low, colors.high]);
+
+        // Stops are sorted on load, whatever order the file has them in
+        colors.gradient = vec![
+            GradientStop { position: 1.0, color: Color32::WHITE },
+            GradientStop { position: 0.0, color: Color32::BLACK },
+            GradientStop { position: 0.25, color: Color32::from_rgb(200, 0, 0) },
+        ];
+        let colors: ColorProfile = serde_json::from_str(&serde_json::to_string(&colors).unwrap()).unwrap();
+        let positions: Vec<f32> = colors.gradient_stops().map(|s| s.position).collect();
+        assert_eq!(positions, [0.0, 0.25, 1.0]);
+        assert!(serde_json::to_string(&colors).unwrap().contains("gradient"));
+    }
+
     #[test]
     fn test_color_preset_update_detected() {
         let dir = std::env::temp_dir().join("bespec_test_presets_update");
@@ -881,4 +2665,163 @@ mod tests {
         assert_eq!(resolved_new.low, Color32::from_rgb(100, 50, 0),
             "resolve_colors should use the latest preset data");
     }
+
+    #[test]
+    fn test_sleep_state_wakes_on_signal() {
+        let start = Instant::now();
+        let mut sleep = SleepState::default();
+        let after = Duration::from_secs(30);
+
+        // Silent from startup
+        assert!(!sleep.silent_for(after, start, start + Duration::from_secs(10)));
+        assert!(sleep.silent_for(after, start, start + Duration::from_secs(31)));
+
+        sleep.asleep = true;
+        sleep.note_signal(start + Duration::from_secs(40));
+        assert!(!sleep.asleep);
+        assert!(!sleep.silent_for(after, start, start + Duration::from_secs(60)));
+        assert!(sleep.silent_for(after, start, start + Duration::from_secs(70)));
+    }
+
+    #[test]
+    fn test_profile_engine_overrides_global() {
+        let mut config = AppConfig::default();
+        config.noise_floor_db = -60.0;
+        assert_eq!(config.noise_floor(), -60.0);
+
+        config.profile.engine = Some(EngineOverrides {
+            noise_floor_db: -100.0,
+            resample_to_internal_rate: true,
+            silence_threshold_db: -70.0,
+            silence_hold_ms: 500.0,
+        });
+        assert_eq!(config.noise_floor(), -100.0);
+        assert!(config.resample_enabled());
+        assert_eq!(config.silence_threshold(), -70.0);
+        assert_eq!(config.silence_hold(), 500.0);
+
+        // Edits go to the override, the global value is left alone
+        *config.noise_floor_mut() = -90.0;
+        assert_eq!(config.profile.engine.unwrap().noise_floor_db, -90.0);
+        assert_eq!(config.noise_floor_db, -60.0);
+
+        config.profile.engine = None;
+        assert_eq!(config.noise_floor(), -60.0);
+        assert!(!config.resample_enabled());
+        assert_eq!(config.silence_hold(), config.silence_hold_ms);
+    }
+
+    #[test]
+    fn test_reset_section_leaves_the_rest() {
+        let mut config = AppConfig::default();
+        config.profile.num_bars = 300;
+        config.profile.sensitivity = 4.0;
+        config.profile.color_link = ColorRef::Preset("Matrix".to_string());
+        config.always_on_top = !config.always_on_top;
+
+        config.reset(ConfigSection::Visual);
+        assert_eq!(config.profile.num_bars, VisualProfile::default().num_bars);
+        assert_eq!(config.profile.sensitivity, 4.0);
+        assert_eq!(config.profile.color_link, ColorRef::Preset("Matrix".to_string()));
+
+        config.reset(ConfigSection::Audio);
+        assert_eq!(config.profile.sensitivity, VisualProfile::default().sensitivity);
+        assert_ne!(config.always_on_top, AppConfig::default().always_on_top);
+
+        config.connected_accounts = vec!["lastfm".to_string()];
+        config.reset(ConfigSection::All);
+        assert_eq!(config.always_on_top, AppConfig::default().always_on_top);
+        assert_eq!(config.connected_accounts, vec!["lastfm".to_string()]);
+    }
+
+    #[test]
+    fn test_old_engine_overrides_load() {
+        // Saved before the silence settings joined the override
+        let engine: EngineOverrides = serde_json::from_str(r#"{"noise_floor_db":-80.0,"resample_to_internal_rate":false}"#).unwrap();
+        assert_eq!(engine.noise_floor_db, -80.0);
+        assert_eq!(engine.silence_threshold_db, default_silence_threshold_db());
+        assert_eq!(engine.silence_hold_ms, default_silence_hold_ms());
+    }
+
+    #[test]
+    fn test_extra_window_noise_floor_uses_its_own_profile() {
+        let main = AppConfig::default();
+        let profile = VisualProfile {
+            engine: Some(EngineOverrides { noise_floor_db: -90.0, ..main.current_engine() }),
+            ..Default::default()
+        };
+
+        assert_eq!(main.noise_floor_for(&profile), -90.0);
+        assert_eq!(main.noise_floor_for(&main.profile), main.noise_floor());
+    }
+
+    #[test]
+    fn test_mini_mode_has_its_own_geometry() {
+        let mut config = AppConfig::default();
+        config.profile.num_bars = 150;
+        *config.active_geometry_mut().0 = [1000.0, 300.0];
+
+        config.mini_mode = true;
+        assert_eq!(config.active_geometry().0, default_mini_window_size());
+        assert_eq!(config.requested_bars(), 32);
+        *config.active_geometry_mut().1 = Some([5.0, 5.0]);
+
+        config.mini_mode = false;
+        assert_eq!(config.active_geometry(), ([1000.0, 300.0], None));
+        assert_eq!(config.requested_bars(), 150);
+    }
+
+    #[test]
+    fn test_scope_level_follows_peak() {
+        let start = Instant::now();
+        let mut data = VisualizationData::new(8);
+        data.timestamp = start;
+
+        data.set_waveform(&[0.1, -0.5, 0.2], &[0.0, 0.5, 1.0], start);
+        assert_eq!(data.scope_level, 0.5);
+        assert_eq!(data.waveform, vec![0.1, -0.5, 0.2]);
+
+        // Quieter block: the level falls off instead of dropping to 0.1
+        data.set_waveform(&[0.1], &[], start + std::time::Duration::from_millis(100));
+        assert!(data.scope_level < 0.5 && data.scope_level > 0.4);
+        // Missing tint reads as neutral
+        assert_eq!(data.waveform_tint, vec![0.5]);
+
+        // An out-of-phase channel can be louder than the mix
+        data.set_stereo(&[0.9], &[-0.9]);
+        assert_eq!(data.scope_level, 0.9);
+        assert_eq!(data.waveform_right, vec![-0.9]);
+    }
+
+    #[test]
+    fn test_snapshot_shares_history_until_written() {
+        let start = Instant::now();
+        let mut live = VisualizationData::new(8);
+        Arc::make_mut(&mut live.history).push(&[-20.0; 8], start);
+
+        let frozen = live.clone();
+        assert!(Arc::ptr_eq(&frozen.history, &live.history));
+
+        // New columns go to the live data only
+        Arc::make_mut(&mut live.history).push(&[-20.0; 8], start + Duration::from_secs(1));
+        assert_eq!(frozen.history.total(), 1);
+        assert_eq!(live.history.total(), 2);
+    }
+
+    #[test]
+    fn test_layout_per_monitor_setup() {
+        let mut config = AppConfig::default();
+        config.window_position = Some([3000.0, 100.0]);
+        config.remember_layout("2:1920x1080+0+0,2560x1440+1920+0");
+
+        // Undocked: unknown setup keeps whatever is current
+        assert!(!config.restore_layout("1:1920x1080+0+0"));
+        config.window_position = Some([100.0, 100.0]);
+        config.remember_layout("1:1920x1080+0+0");
+
+        assert!(config.restore_layout("2:1920x1080+0+0,2560x1440+1920+0"));
+        assert_eq!(config.window_position, Some([3000.0, 100.0]));
+        assert!(config.restore_layout("1:1920x1080+0+0"));
+        assert_eq!(config.window_position, Some([100.0, 100.0]));
+    }
 }
@@ -0,0 +1,189 @@
+//! Signal generator: a sine, a sine sweep or white / pink noise, played
+//! through `audio_output` for testing speakers, cables and BeSpec itself.
+//!
+//! Driven from the Tools tab. The GUI owns the player and sends every
+//! settings change down a channel; the `Generator` picks them up between
+//! samples, so dragging the frequency slider glides the tone instead of
+//! restarting the stream. Level changes go through `Player::set_level`.
+//!
+//! The sweep is logarithmic (equal time per octave) and repeats, which reads
+//! as an even crawl across a log-frequency display.
+
+use crossbeam_channel::{Receiver, Sender};
+use serde::{Deserialize, Serialize};
+
+use crate::measurement::PinkNoise;
+
+/// Lowest and highest frequency the controls allow (Hz)
+pub const MIN_HZ: f32 = 10.0;
+pub const MAX_HZ: f32 = 22_000.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum Waveform {
+    Sine,
+    Sweep,
+    WhiteNoise,
+    PinkNoise,
+}
+
+impl Waveform {
+    pub const ALL: [Self; 4] = [Self::Sine, Self::Sweep, Self::WhiteNoise, Self::PinkNoise];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Sine => "Sine",
+            Self::Sweep => "Sweep",
+            Self::WhiteNoise => "White Noise",
+            Self::PinkNoise => "Pink Noise",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct GeneratorSettings {
+    pub waveform: Waveform,
+    /// Sine frequency (Hz)
+    pub frequency_hz: f32,
+    /// Sweep range (Hz) and how long one pass takes (seconds)
+    pub sweep_from_hz: f32,
+    pub sweep_to_hz: f32,
+    pub sweep_seconds: f32,
+    /// Output level (dBFS)
+    pub level_db: f32,
+    /// Output device id, empty for the system default
+    pub output_device: String,
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        Self {
+            waveform: Waveform::Sine,
+            frequency_hz: 1000.0,
+            sweep_from_hz: 20.0,
+            sweep_to_hz: 20_000.0,
+            sweep_seconds: 10.0,
+            level_db: -20.0,
+            output_device: String::new(),
+        }
+    }
+}
+
+/// Sweep frequency `t` seconds into a pass
+pub fn sweep_frequency(settings: &GeneratorSettings, t: f32) -> f32 {
+    let from = settings.sweep_from_hz.clamp(MIN_HZ, MAX_HZ);
+    let to = settings.sweep_to_hz.clamp(MIN_HZ, MAX_HZ);
+    let progress = (t / settings.sweep_seconds.max(0.1)).clamp(0.0, 1.0);
+    from * (to / from).powf(progress)
+}
+
+/// The `audio_output::Source` the generator plays
+pub struct Generator {
+    settings: GeneratorSettings,
+    updates: Receiver<GeneratorSettings>,
+    /// Oscillator phase, 0..1
+    phase: f64,
+    /// Time into the current sweep pass (seconds)
+    sweep_t: f64,
+    noise: PinkNoise,
+}
+
+impl Generator {
+    pub fn new(settings: GeneratorSettings, updates: Receiver<GeneratorSettings>) -> Self {
+        Self { settings, updates, phase: 0.0, sweep_t: 0.0, noise: crate::audio_output::pink_noise() }
+    }
+
+    fn tone(&mut self, frequency: f32, sample_rate: f32) -> f32 {
+        let sample = (self.phase * std::f64::consts::TAU).sin() as f32;
+        // Keep below Nyquist, an aliased tone would just confuse
+        let frequency = frequency.clamp(MIN_HZ, sample_rate * 0.45);
+        self.phase = (self.phase + frequency as f64 / sample_rate as f64).fract();
+        sample
+    }
+}
+
+impl crate::audio_output::Source for Generator {
+    fn next_sample(&mut self, sample_rate: f32) -> f32 {
+        if let Some(settings) = self.updates.try_iter().last() {
+            if settings.waveform == Waveform::Sweep && self.settings.waveform != Waveform::Sweep {
+                self.sweep_t = 0.0;
+            }
+            self.settings = settings;
+        }
+        match self.settings.waveform {
+            Waveform::Sine => self.tone(self.settings.frequency_hz, sample_rate),
+            Waveform::Sweep => {
+                let frequency = sweep_frequency(&self.settings, self.sweep_t as f32);
+                self.sweep_t += 1.0 / sample_rate as f64;
+                if self.sweep_t >= self.settings.sweep_seconds.max(0.1) as f64 {
+                    self.sweep_t = 0.0;
+                }
+                self.tone(frequency, sample_rate)
+            }
+            Waveform::WhiteNoise => self.noise.white(),
+            Waveform::PinkNoise => self.noise.next_sample(),
+        }
+    }
+}
+
+/// The generator while it plays, owned by the GUI
+pub struct Playing {
+    device: String,
+    player: crate::audio_output::Player,
+    updates: Sender<GeneratorSettings>,
+    sent: GeneratorSettings,
+}
+
+impl Playing {
+    /// Start, stop or update the generator to match the settings. Only a
+    /// different output device restarts the stream.
+    pub fn sync(playing: &mut Option<Self>, running: bool, settings: &GeneratorSettings) {
+        let wanted = running.then_some(&settings.output_device);
+        if playing.as_ref().map(|p| &p.device) != wanted {
+            *playing = wanted.map(|device| {
+                let (updates, rx) = crossbeam_channel::unbounded();
+                let player = crate::audio_output::start(device, settings.level_db, Generator::new(settings.clone(), rx));
+                Self { device: device.clone(), player, updates, sent: settings.clone() }
+            });
+        }
+        if let Some(p) = playing.as_mut().filter(|p| p.sent != *settings) {
+            p.player.set_level(settings.level_db);
+            let _ = p.updates.send(settings.clone());
+            p.sent = settings.clone();
+        }
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_output::Source;
+
+    #[test]
+    fn test_sweep_is_logarithmic() {
+        let settings = GeneratorSettings { sweep_from_hz: 20.0, sweep_to_hz: 20_000.0, sweep_seconds: 10.0, ..Default::default() };
+        assert!((sweep_frequency(&settings, 0.0) - 20.0).abs() < 0.01);
+        // Halfway in time is halfway in octaves
+        assert!((sweep_frequency(&settings, 5.0) - 632.46).abs() < 0.1);
+        assert!((sweep_frequency(&settings, 10.0) - 20_000.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_sine_frequency_and_live_updates() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let mut generator = Generator::new(GeneratorSettings::default(), rx);
+        let rate = 48_000.0;
+
+        // Rising zero crossings in one second count the cycles
+        let count_cycles = |generator: &mut Generator| {
+            let samples: Vec<f32> = (0..48_000).map(|_| generator.next_sample(rate)).collect();
+            samples.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count()
+        };
+        assert!((count_cycles(&mut generator) as i32 - 1000).abs() <= 1);
+
+        tx.send(GeneratorSettings { frequency_hz: 250.0, ..Default::default() }).unwrap();
+        assert!((count_cycles(&mut generator) as i32 - 250).abs() <= 1);
+    }
+}
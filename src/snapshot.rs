@@ -0,0 +1,118 @@
+//! Reference spectrum snapshots, for before/after comparisons (EQ changes,
+//! speaker placement, a different master).
+//!
+//! Capturing averages the bars over a few seconds in the FFT thread. The
+//! average is taken in power, not dB, so a few loud frames count the way
+//! they sound instead of being pulled down by the quiet ones. The result is
+//! drawn as a dashed ghost line over the live bars, and the inspector shows
+//! the difference for the hovered bar.
+//!
+//! A snapshot only lines up with the bars it was taken with, so changing
+//! the bar count mid-capture starts it over, and the ghost line is hidden
+//! while the counts differ.
+
+use std::time::Instant;
+
+use crate::shared_state::SILENCE_DB;
+
+/// Captured average level per bar (dB)
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReferenceSnapshot {
+    pub bars: Vec<f32>,
+    /// Frames that went into the average
+    pub frames: u32,
+}
+
+impl ReferenceSnapshot {
+    /// Reference level for bar `index` when drawn over `bar_count` bars
+    pub fn level(&self, index: usize, bar_count: usize) -> Option<f32> {
+        if bar_count != self.bars.len() {
+            return None;
+        }
+        self.bars.get(index).copied()
+    }
+}
+
+/// Capture in progress
+#[derive(Clone, Debug)]
+pub struct SnapshotCapture {
+    sum_power: Vec<f64>,
+    frames: u32,
+    started: Instant,
+    seconds: f32,
+}
+
+impl SnapshotCapture {
+    pub fn new(seconds: f32, now: Instant) -> Self {
+        Self { sum_power: Vec::new(), frames: 0, started: now, seconds: seconds.max(0.1) }
+    }
+
+    /// Add a frame of bars. Returns the snapshot once the time is up.
+    pub fn feed(&mut self, bars: &[f32], now: Instant) -> Option<ReferenceSnapshot> {
+        if bars.is_empty() {
+            return None;
+        }
+        if self.sum_power.len() != bars.len() {
+            // Bar count changed (or first frame): start over
+            self.sum_power = vec![0.0; bars.len()];
+            self.frames = 0;
+            self.started = now;
+        }
+        for (sum, &db) in self.sum_power.iter_mut().zip(bars) {
+            *sum += 10f64.powf(db as f64 / 10.0);
+        }
+        self.frames += 1;
+
+        if self.progress(now) < 1.0 {
+            return None;
+        }
+        let frames = self.frames as f64;
+        let bars = self
+            .sum_power
+            .iter()
+            .map(|&sum| ((10.0 * (sum / frames).log10()) as f32).max(SILENCE_DB))
+            .collect();
+        Some(ReferenceSnapshot { bars, frames: self.frames })
+    }
+
+    /// 0..1 of the capture time elapsed
+    pub fn progress(&self, now: Instant) -> f32 {
+        (now.duration_since(self.started).as_secs_f32() / self.seconds).min(1.0)
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_capture_averages_power() {
+        let t0 = Instant::now();
+        let mut capture = SnapshotCapture::new(1.0, t0);
+
+        assert_eq!(capture.feed(&[0.0, -20.0], t0), None);
+        // A frame at 0 dB and one at silence average to -3 dB, not halfway to silence
+        let snapshot = capture.feed(&[-200.0, -20.0], t0 + Duration::from_secs(1)).unwrap();
+        assert_eq!(snapshot.frames, 2);
+        assert!((snapshot.bars[0] + 3.01).abs() < 0.01);
+        assert!((snapshot.bars[1] + 20.0).abs() < 0.01);
+
+        assert_eq!(snapshot.level(1, 2), Some(snapshot.bars[1]));
+        assert_eq!(snapshot.level(1, 3), None);
+    }
+
+    #[test]
+    fn test_bar_count_change_restarts() {
+        let t0 = Instant::now();
+        let mut capture = SnapshotCapture::new(1.0, t0);
+        capture.feed(&[0.0, 0.0], t0);
+        assert_eq!(capture.feed(&[0.0, 0.0, 0.0], t0 + Duration::from_millis(900)), None);
+        assert!(capture.progress(t0 + Duration::from_millis(1500)) < 1.0);
+        let snapshot = capture.feed(&[0.0, 0.0, 0.0], t0 + Duration::from_millis(1900)).unwrap();
+        assert_eq!(snapshot.bars.len(), 3);
+        assert_eq!(snapshot.frames, 2);
+    }
+}
@@ -0,0 +1,343 @@
+//! History for the Spectrogram (waterfall) and Mountain modes.
+//!
+//! The FFT thread pushes one column of bar levels every `1 / COLUMNS_PER_SECOND`
+//! into a ring buffer holding `HISTORY_SECONDS` of audio. Each column is
+//! resampled to a fixed `ROWS` so LOD changes during a window resize don't
+//! wipe the history, and every level is stored as one byte
+//! (`QUANT_FLOOR_DB` -> 0, `QUANT_CEILING_DB` -> 255): five minutes is ~2.3 MB.
+//!
+//! The GUI looks at the history through a `SpectrogramView`, which is either
+//! live (follows the newest column) or parked at a fixed column, plus a zoom
+//! level in seconds. Column indices are absolute (counted since the history
+//! was created) so a paused view stays put while new columns keep arriving.
+
+use std::time::{Duration, Instant};
+
+/// How much history is kept
+pub const HISTORY_SECONDS: f32 = 300.0;
+
+/// Columns recorded per second of audio
+pub const COLUMNS_PER_SECOND: f32 = 30.0;
+
+/// Frequency rows stored per column (independent of the bar count)
+pub const ROWS: usize = 256;
+
+/// Columns kept before the oldest is overwritten
+pub const CAPACITY: usize = (HISTORY_SECONDS * COLUMNS_PER_SECOND) as usize;
+
+/// Quantization range. Matches the lowest noise floor the settings allow.
+const QUANT_FLOOR_DB: f32 = -120.0;
+const QUANT_CEILING_DB: f32 = 0.0;
+
+/// Shortest and longest time span the view can zoom to
+const MIN_SPAN_SEC: f32 = 2.0;
+const DEFAULT_SPAN_SEC: f32 = 10.0;
+
+fn column_interval() -> Duration {
+    Duration::from_secs_f32(1.0 / COLUMNS_PER_SECOND)
+}
+
+fn quantize(db: f32) -> u8 {
+    let t = (db - QUANT_FLOOR_DB) / (QUANT_CEILING_DB - QUANT_FLOOR_DB);
+    (t.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Stored byte back to dB
+pub fn level_db(q: u8) -> f32 {
+    QUANT_FLOOR_DB + (q as f32 / 255.0) * (QUANT_CEILING_DB - QUANT_FLOOR_DB)
+}
+
+#[derive(Clone, Default)]
+pub struct SpectrogramHistory {
+    /// Ring of `ROWS`-byte columns, grows up to `CAPACITY` columns
+    data: Vec<u8>,
+    /// Columns ever pushed. The newest column has index `total - 1`.
+    total: u64,
+    last_push: Option<Instant>,
+}
+
+impl SpectrogramHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one frame of bar levels (dB). Frames arriving faster than
+    /// `COLUMNS_PER_SECOND` are dropped.
+    pub fn push(&mut self, bars: &[f32], now: Instant) {
+        if bars.is_empty() {
+            return;
+        }
+        if let Some(last) = self.last_push {
+            if now.saturating_duration_since(last) < column_interval() {
+                return;
+            }
+        }
+        self.last_push = Some(now);
+
+        let slot = (self.total % CAPACITY as u64) as usize;
+        if self.data.len() < (slot + 1) * ROWS {
+            self.data.resize((slot + 1) * ROWS, 0);
+        }
+        let column = &mut self.data[slot * ROWS..(slot + 1) * ROWS];
+
+        // Each row takes the loudest bar it covers (or the nearest bar when
+        // there are fewer bars than rows)
+        for (r, out) in column.iter_mut().enumerate() {
+            let start = r * bars.len() / ROWS;
+            let end = ((r + 1) * bars.len() / ROWS).max(start + 1);
+            let db = bars[start..end].iter().fold(f32::MIN, |m, &b| m.max(b));
+            *out = quantize(db);
+        }
+        self.total += 1;
+    }
+
+    /// Columns ever pushed (absolute index of the next column)
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Columns currently retained
+    pub fn len(&self) -> usize {
+        self.total.min(CAPACITY as u64) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// Absolute index of the oldest retained column
+    pub fn oldest(&self) -> u64 {
+        self.total - self.len() as u64
+    }
+
+    /// Quantized levels of column `index`, low frequencies first.
+    /// `None` if the column was never recorded or has been overwritten.
+    pub fn column(&self, index: u64) -> Option<&[u8]> {
+        if index < self.oldest() || index >= self.total {
+            return None;
+        }
+        let slot = (index % CAPACITY as u64) as usize;
+        Some(&self.data[slot * ROWS..(slot + 1) * ROWS])
+    }
+
+    /// Up to `count` columns, every `stride`th one back from the newest,
+    /// newest first
+    pub fn recent(&self, count: usize, stride: u64) -> impl Iterator<Item = &[u8]> + '_ {
+        let stride = stride.max(1);
+        (0..count as u64).map_while(move |k| self.total.checked_sub(1 + k * stride).and_then(|i| self.column(i)))
+    }
+}
+
+/// Which part of the history the GUI shows
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpectrogramView {
+    /// Newest column shown. `None` follows the live edge.
+    pub anchor: Option<u64>,
+    /// Seconds of history across the time axis
+    pub span_sec: f32,
+}
+
+impl Default for SpectrogramView {
+    fn default() -> Self {
+        Self { anchor: None, span_sec: DEFAULT_SPAN_SEC }
+    }
+}
+
+impl SpectrogramView {
+    pub fn is_live(&self) -> bool {
+        self.anchor.is_none()
+    }
+
+    /// Columns across the time axis at the current zoom
+    pub fn span_columns(&self) -> u64 {
+        ((self.span_sec * COLUMNS_PER_SECOND).round() as u64).max(1)
+    }
+
+    /// Visible absolute column range `[start, end)`, newest at `end - 1`.
+    /// `start` may be older than the retained history (drawn as empty).
+    pub fn visible(&self, history: &SpectrogramHistory) -> (u64, u64) {
+        let end = match self.anchor {
+            None => history.total(),
+            Some(anchor) => (anchor + 1).clamp(history.oldest(), history.total()),
+        };
+        (end.saturating_sub(self.span_columns()), end)
+    }
+
+    /// Freeze on the newest column, or go back to live
+    pub fn toggle_pause(&mut self, history: &SpectrogramHistory) {
+        self.anchor = match self.anchor {
+            Some(_) => None,
+            None => Some(history.total().saturating_sub(1)),
+        };
+    }
+
+    /// Move back (positive) or forward (negative) in time.
+    /// Scrolling forward past the newest column goes live again.
+    pub fn scroll(&mut self, seconds: f32, history: &SpectrogramHistory) {
+        if history.is_empty() {
+            return;
+        }
+        let newest = history.total() - 1;
+        let current = self.anchor.unwrap_or(newest) as f64;
+        let target = current - (seconds * COLUMNS_PER_SECOND) as f64;
+
+        self.anchor = if target >= newest as f64 {
+            None
+        } else {
+            Some((target.max(history.oldest() as f64)).round() as u64)
+        };
+    }
+
+    /// Zoom in (`factor` > 1) or out (`factor` < 1) around the anchor
+    pub fn zoom(&mut self, factor: f32) {
+        if factor > 0.0 {
+            self.span_sec = (self.span_sec / factor).clamp(MIN_SPAN_SEC, HISTORY_SECONDS);
+        }
+    }
+
+    /// Center the view on `fraction` (0 = oldest, 1 = newest) of the retained history
+    pub fn jump_to(&mut self, fraction: f32, history: &SpectrogramHistory) {
+        if history.is_empty() {
+            return;
+        }
+        let len = history.len() as f64;
+        let center = history.oldest() as f64 + fraction.clamp(0.0, 1.0) as f64 * (len - 1.0);
+        let anchor = (center + self.span_columns() as f64 / 2.0).round() as u64;
+        // Don't leave empty space before the oldest column
+        let anchor = anchor.max(history.oldest() + self.span_columns() - 1);
+
+        self.anchor = if anchor >= history.total() - 1 { None } else { Some(anchor) };
+    }
+
+    /// Seconds between the newest visible column and the live edge
+    pub fn seconds_behind(&self, history: &SpectrogramHistory) -> f32 {
+        let (_, end) = self.visible(history);
+        (history.total() - end) as f32 / COLUMNS_PER_SECOND
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Push `n` columns of a constant level, one interval apart
+    fn fill(history: &mut SpectrogramHistory, n: usize, db: f32, start: Instant) -> Instant {
+        let mut now = start;
+        for _ in 0..n {
+            history.push(&[db; 64], now);
+            now += column_interval();
+        }
+        now
+    }
+
+    #[test]
+    fn test_quantization_round_trip() {
+        assert_eq!(quantize(-200.0), 0);
+        assert_eq!(quantize(10.0), 255);
+        assert!((level_db(quantize(-60.0)) + 60.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_recent_steps_back_from_newest() {
+        let mut history = SpectrogramHistory::new();
+        let mut now = Instant::now();
+        for db in [-100.0, -80.0, -60.0, -40.0, -20.0] {
+            history.push(&[db; 8], now);
+            now += column_interval();
+        }
+        let levels: Vec<u8> = history.recent(10, 2).map(|column| column[0]).collect();
+        assert_eq!(levels, vec![quantize(-20.0), quantize(-60.0), quantize(-100.0)]);
+        assert_eq!(history.recent(2, 1).count(), 2);
+        assert_eq!(SpectrogramHistory::new().recent(4, 1).count(), 0);
+    }
+
+    #[test]
+    fn test_push_is_throttled() {
+        let mut history = SpectrogramHistory::new();
+        let now = Instant::now();
+        history.push(&[0.0; 8], now);
+        history.push(&[0.0; 8], now + Duration::from_millis(1));
+        assert_eq!(history.total(), 1);
+        history.push(&[0.0; 8], now + column_interval());
+        assert_eq!(history.total(), 2);
+    }
+
+    #[test]
+    fn test_rows_take_loudest_bar() {
+        let mut history = SpectrogramHistory::new();
+        let mut bars = vec![-100.0; ROWS * 2];
+        bars[1] = -10.0;
+        history.push(&bars, Instant::now());
+        let column = history.column(0).unwrap();
+        assert_eq!(column[0], quantize(-10.0));
+        assert_eq!(column[1], quantize(-100.0));
+    }
+
+    #[test]
+    fn test_ring_overwrites_oldest() {
+        let mut history = SpectrogramHistory::new();
+        fill(&mut history, CAPACITY + 10, -30.0, Instant::now());
+        assert_eq!(history.len(), CAPACITY);
+        assert_eq!(history.oldest(), 10);
+        assert!(history.column(9).is_none());
+        assert!(history.column(10).is_some());
+        assert!(history.column(history.total()).is_none());
+    }
+
+    #[test]
+    fn test_paused_view_stays_put() {
+        let mut history = SpectrogramHistory::new();
+        let now = fill(&mut history, 600, -30.0, Instant::now());
+
+        let mut view = SpectrogramView::default();
+        view.toggle_pause(&history);
+        let frozen = view.visible(&history);
+
+        fill(&mut history, 100, -30.0, now);
+        assert_eq!(view.visible(&history), frozen);
+        assert!(view.seconds_behind(&history) > 3.0);
+
+        view.toggle_pause(&history);
+        assert!(view.is_live());
+        assert_eq!(view.visible(&history).1, history.total());
+    }
+
+    #[test]
+    fn test_scroll_clamps_and_returns_to_live() {
+        let mut history = SpectrogramHistory::new();
+        fill(&mut history, 900, -30.0, Instant::now());
+
+        let mut view = SpectrogramView::default();
+        view.scroll(5.0, &history);
+        assert_eq!(view.anchor, Some(899 - 150));
+
+        view.scroll(1000.0, &history);
+        assert_eq!(view.anchor, Some(history.oldest()));
+
+        view.scroll(-1000.0, &history);
+        assert!(view.is_live());
+    }
+
+    #[test]
+    fn test_zoom_and_jump() {
+        let mut history = SpectrogramHistory::new();
+        fill(&mut history, 3000, -30.0, Instant::now());
+
+        let mut view = SpectrogramView::default();
+        view.zoom(100.0);
+        assert_eq!(view.span_sec, MIN_SPAN_SEC);
+        view.zoom(0.0001);
+        assert_eq!(view.span_sec, HISTORY_SECONDS);
+
+        view.zoom(HISTORY_SECONDS / 10.0);
+        view.jump_to(0.0, &history);
+        let (start, end) = view.visible(&history);
+        assert_eq!(end - start, 300);
+        assert_eq!(start, history.oldest());
+
+        view.jump_to(1.0, &history);
+        assert!(view.is_live());
+    }
+}
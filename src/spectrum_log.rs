@@ -0,0 +1,287 @@
+//! Spectrum data logger: bar levels with timestamps written to CSV or JSON
+//! Lines files, for analysis in a spreadsheet, Python, etc.
+//!
+//! Started and stopped from the Stats tab. The FFT thread hands every frame
+//! to `SpectrumLogger::process`, which keeps every Nth one and appends it
+//! through a buffered writer, so a frame costs a format and a memcpy. Files
+//! go to `<data dir>/spectrum_logs/`, named after the time the recording
+//! started, and roll over to a new part once they pass the size limit (or
+//! when the bar count changes, since the columns would no longer match).
+//!
+//! CSV: a header of bar center frequencies, then one row per frame:
+//!   time_s,unix_ms,31.5,42.1,...
+//!   0.0213,1718000000123,-48.20,-51.75,...
+//! JSONL: a first line with the frequencies, then one object per frame:
+//!   {"freqs_hz":[31.5,42.1,...]}
+//!   {"t":0.0213,"unix_ms":1718000000123,"bars":[-48.20,-51.75,...]}
+//!
+//! Levels are dB after sensitivity, same as the bars on screen.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum LogFormat {
+    Csv,
+    Jsonl,
+}
+
+impl LogFormat {
+    pub const ALL: [Self; 2] = [Self::Csv, Self::Jsonl];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Csv => "CSV",
+            Self::Jsonl => "JSON Lines",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Jsonl => "jsonl",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct SpectrumLogSettings {
+    pub format: LogFormat,
+    /// Keep one frame in this many
+    pub every_nth_frame: u32,
+    /// Start a new part past this size (MB)
+    pub max_file_mb: u32,
+}
+
+impl Default for SpectrumLogSettings {
+    fn default() -> Self {
+        Self { format: LogFormat::Csv, every_nth_frame: 1, max_file_mb: 50 }
+    }
+}
+
+/// What the Stats tab shows about the recording
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LogStatus {
+    /// File being written
+    pub file: Option<PathBuf>,
+    /// Frames written this recording, across parts
+    pub frames: u64,
+    /// Last error; the recording stops on one
+    pub error: Option<String>,
+}
+
+struct LogFile {
+    writer: BufWriter<File>,
+    bytes: u64,
+    /// Bar count the header was written for
+    bars: usize,
+}
+
+/// Lives in the FFT thread, like `midi_out::MidiEngine`
+pub struct SpectrumLogger {
+    dir: PathBuf,
+    /// File name stem of this recording ("spectrum-2024-06-10_21-04-33")
+    session: Option<String>,
+    part: u32,
+    file: Option<LogFile>,
+    started: Instant,
+    seen: u64,
+    /// Hit an error, don't retry until the next start
+    failed: bool,
+    status: LogStatus,
+}
+
+impl SpectrumLogger {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, session: None, part: 0, file: None, started: Instant::now(), seen: 0, failed: false, status: LogStatus::default() }
+    }
+
+    /// Folder the logs go to by default
+    pub fn default_dir() -> PathBuf {
+        crate::paths::get().data_dir.join("spectrum_logs")
+    }
+
+    /// Feed every frame. `freqs` are the bar center frequencies (Hz).
+    pub fn process(&mut self, running: bool, settings: &SpectrumLogSettings, bars: &[f32], freqs: impl Fn(usize) -> f32) {
+        if !running {
+            if self.session.is_some() {
+                self.stop();
+            }
+            self.failed = false;
+            return;
+        }
+        if self.session.is_none() {
+            if self.failed {
+                return;
+            }
+            self.start();
+        }
+
+        self.seen += 1;
+        if (self.seen - 1) % settings.every_nth_frame.max(1) as u64 != 0 || bars.is_empty() {
+            return;
+        }
+
+        let max_bytes = settings.max_file_mb.max(1) as u64 * 1024 * 1024;
+        if let Err(e) = self.write_frame(settings.format, max_bytes, bars, &freqs) {
+            tracing::error!("[Log] Spectrum log failed: {}", e);
+            self.status.error = Some(e);
+            self.failed = true;
+            self.stop();
+        }
+    }
+
+    fn start(&mut self) {
+        let stamp = time::OffsetDateTime::now_local()
+            .unwrap_or_else(|_| time::OffsetDateTime::now_utc())
+            .format(time::macros::format_description!("[year]-[month]-[day]_[hour]-[minute]-[second]"))
+            .unwrap_or_else(|_| "log".to_string());
+        self.session = Some(format!("spectrum-{}", stamp));
+        self.part = 0;
+        self.started = Instant::now();
+        self.seen = 0;
+        self.status = LogStatus::default();
+    }
+
+    fn stop(&mut self) {
+        if let Some(mut file) = self.file.take() {
+            if let Err(e) = file.writer.flush() {
+                tracing::warn!("[Log] Couldn't flush spectrum log: {}", e);
+            }
+        }
+        if self.session.take().is_some() {
+            tracing::info!("[Log] Spectrum log stopped after {} frames", self.status.frames);
+        }
+    }
+
+    fn write_frame(&mut self, format: LogFormat, max_bytes: u64, bars: &[f32], freqs: &impl Fn(usize) -> f32) -> Result<(), String> {
+        let rotate = self.file.as_ref().map_or(true, |f| f.bytes >= max_bytes || f.bars != bars.len());
+        if rotate {
+            self.open_part(format, bars.len(), freqs)?;
+        }
+        let Some(file) = self.file.as_mut() else { return Ok(()) };
+
+        let t = self.started.elapsed().as_secs_f64();
+        let unix_ms = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64);
+        let line = match format {
+            LogFormat::Csv => csv_row(t, unix_ms, bars),
+            LogFormat::Jsonl => jsonl_row(t, unix_ms, bars),
+        };
+        file.writer.write_all(line.as_bytes()).map_err(|e| e.to_string())?;
+        file.bytes += line.len() as u64;
+        self.status.frames += 1;
+        Ok(())
+    }
+
+    fn open_part(&mut self, format: LogFormat, bars: usize, freqs: &impl Fn(usize) -> f32) -> Result<(), String> {
+        if let Some(mut old) = self.file.take() {
+            old.writer.flush().map_err(|e| e.to_string())?;
+        }
+        let Some(session) = &self.session else { return Ok(()) };
+        self.part += 1;
+        std::fs::create_dir_all(&self.dir).map_err(|e| format!("can't create {:?}: {}", self.dir, e))?;
+        let path = part_path(&self.dir, session, self.part, format);
+        let file = File::create(&path).map_err(|e| format!("can't create {:?}: {}", path, e))?;
+        let mut writer = BufWriter::with_capacity(64 * 1024, file);
+
+        let freqs: Vec<f32> = (0..bars).map(freqs).collect();
+        let header = match format {
+            LogFormat::Csv => csv_header(&freqs),
+            LogFormat::Jsonl => format!("{}\n", serde_json::json!({ "freqs_hz": freqs })),
+        };
+        writer.write_all(header.as_bytes()).map_err(|e| e.to_string())?;
+
+        tracing::info!("[Log] Writing spectrum log {:?}", path);
+        self.status.file = Some(path);
+        self.file = Some(LogFile { writer, bytes: header.len() as u64, bars });
+        Ok(())
+    }
+
+    pub fn status(&self) -> &LogStatus {
+        &self.status
+    }
+}
+
+impl Drop for SpectrumLogger {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// "<stem>.csv", then "<stem>_part2.csv" and so on
+fn part_path(dir: &Path, session: &str, part: u32, format: LogFormat) -> PathBuf {
+    let name = if part <= 1 {
+        format!("{}.{}", session, format.extension())
+    } else {
+        format!("{}_part{}.{}", session, part, format.extension())
+    };
+    dir.join(name)
+}
+
+fn csv_header(freqs: &[f32]) -> String {
+    let mut line = String::from("time_s,unix_ms");
+    for f in freqs {
+        line.push_str(&format!(",{:.1}", f));
+    }
+    line.push('\n');
+    line
+}
+
+fn csv_row(t: f64, unix_ms: u64, bars: &[f32]) -> String {
+    let mut line = format!("{:.4},{}", t, unix_ms);
+    for db in bars {
+        line.push_str(&format!(",{:.2}", db));
+    }
+    line.push('\n');
+    line
+}
+
+fn jsonl_row(t: f64, unix_ms: u64, bars: &[f32]) -> String {
+    // Written by hand to keep the keys in reading order
+    let bars: Vec<String> = bars.iter().map(|db| format!("{:.2}", db)).collect();
+    format!("{{\"t\":{:.4},\"unix_ms\":{},\"bars\":[{}]}}\n", t, unix_ms, bars.join(","))
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows() {
+        assert_eq!(csv_header(&[31.5, 1000.0]), "time_s,unix_ms,31.5,1000.0\n");
+        assert_eq!(csv_row(0.5, 1700, &[-12.345, 0.0]), "0.5000,1700,-12.35,0.00\n");
+        assert_eq!(jsonl_row(0.5, 1700, &[-12.5, 0.0]), "{\"t\":0.5000,\"unix_ms\":1700,\"bars\":[-12.50,0.00]}\n");
+    }
+
+    #[test]
+    fn test_decimates_and_rotates() {
+        let dir = std::env::temp_dir().join(format!("bespec-spectrum-log-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut logger = SpectrumLogger::new(dir.clone());
+        let settings = SpectrumLogSettings { every_nth_frame: 2, ..Default::default() };
+        let freqs = |i: usize| 100.0 * (i + 1) as f32;
+
+        for _ in 0..4 {
+            logger.process(true, &settings, &[-10.0, -20.0], freqs);
+        }
+        assert_eq!(logger.status().frames, 2);
+        // A different bar count can't share the header, so it starts part 2
+        logger.process(true, &settings, &[-10.0, -20.0, -30.0], freqs);
+        let second = logger.status().file.clone().unwrap();
+        assert!(second.to_string_lossy().ends_with("_part2.csv"));
+        logger.process(false, &settings, &[], freqs);
+
+        let text = std::fs::read_to_string(&second).unwrap();
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some("time_s,unix_ms,100.0,200.0,300.0"));
+        assert_eq!(lines.count(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,368 @@
+//! Optional localhost socket for Stream Deck plugins (and anything else that
+//! wants a live connection rather than polling `http_api`).
+//!
+//! Plain TCP on 127.0.0.1, one JSON object per line each way. A request is
+//! any command from `commands`, optionally with an `id` that the reply
+//! echoes:
+//!
+//!   → {"id": 1, "cmd": "toggle_ghost_mode"}
+//!   ← {"id": 1, "ok": true, "result": {"profile": "...", "ghost_mode": true, ...}}
+//!   ← {"id": 2, "ok": false, "error": "no preset named 'Nope'"}
+//!
+//! Two more only make sense on a connection:
+//!
+//!   {"cmd": "subscribe", "bands": 4, "fps": 15, "features": false}
+//!       pushes {"event": "levels", "levels": [0.0..1.0, ...]} at `fps`, and
+//!       {"event": "status", ...} whenever the profile, colors, mode or
+//!       ghost mode change (once right away, so keys can draw their state).
+//!       With `features`, also {"event": "features", "centroid_hz": ...}
+//!       at `fps`
+//!   {"cmd": "unsubscribe"}
+//!
+//! The full schema is in docs/stream_deck_protocol.md.
+//!
+//! A line that isn't JSON closes the connection. That's what keeps a web
+//! page from driving it with a form post: the browser's HTTP request line
+//! comes before any body it controls.
+
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::commands::{self, Command};
+use crate::shared_state::SharedState;
+
+/// How often idle loops check whether they should stop
+const POLL: Duration = Duration::from_millis(100);
+
+/// Longest line we buffer before giving up on the client
+const MAX_LINE: usize = 16 * 1024;
+
+/// A Stream Deck plugin needs one; a few more leaves room for testing
+const MAX_CLIENTS: usize = 8;
+
+const FPS_RANGE: std::ops::RangeInclusive<u32> = 1..=30;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct StreamDeckSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for StreamDeckSettings {
+    fn default() -> Self {
+        Self { enabled: false, port: 7879 }
+    }
+}
+
+/// Live level / status pushes for one connection
+#[derive(Debug)]
+pub struct Subscription {
+    bands: usize,
+    /// Also push the spectral features with the levels
+    features: bool,
+    interval: Duration,
+    next_levels: Instant,
+    /// What the last status event showed, `Null` before the first
+    last_status: Value,
+}
+
+impl Subscription {
+    fn new(bands: usize, fps: u32, features: bool, now: Instant) -> Self {
+        let fps = fps.clamp(*FPS_RANGE.start(), *FPS_RANGE.end());
+        Self { bands, features, interval: Duration::from_secs(1) / fps, next_levels: now, last_status: Value::Null }
+    }
+
+    /// Events due at `now`
+    fn events(&mut self, state: &SharedState, now: Instant) -> Vec<Value> {
+        let mut events = Vec::new();
+        let status = commands::status(state);
+        let shown = json!([status["profile"], status["color_preset"], status["visual_mode"], status["ghost_mode"]]);
+        if shown != self.last_status {
+            self.last_status = shown;
+            let mut event = status;
+            event["event"] = json!("status");
+            events.push(event);
+        }
+        if now >= self.next_levels {
+            self.next_levels = now + self.interval;
+            let levels = commands::fold_levels(&state.visualization.bars, state.config.noise_floor(), self.bands);
+            events.push(json!({ "event": "levels", "levels": levels }));
+            if self.features {
+                let mut event = commands::features(state);
+                event["event"] = json!("features");
+                events.push(event);
+            }
+        }
+        events
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+enum SessionCommand {
+    Subscribe {
+        #[serde(default = "default_bands")]
+        bands: usize,
+        #[serde(default = "default_fps")]
+        fps: u32,
+        #[serde(default)]
+        features: bool,
+    },
+    Unsubscribe,
+}
+
+fn default_bands() -> usize {
+    4
+}
+
+fn default_fps() -> u32 {
+    15
+}
+
+/// Reply to one line, or `None` if it wasn't JSON and the connection
+/// should close
+pub fn handle_line(line: &str, state: &mut SharedState, subscription: &mut Option<Subscription>, now: Instant) -> Option<Value> {
+    let message: Value = serde_json::from_str(line).ok()?;
+    let id = message.get("id").cloned().unwrap_or(Value::Null);
+
+    let result = if let Ok(session) = serde_json::from_value::<SessionCommand>(message.clone()) {
+        match session {
+            SessionCommand::Subscribe { bands, fps, features } => {
+                *subscription = Some(Subscription::new(bands.clamp(1, commands::MAX_BANDS), fps, features, now));
+            }
+            SessionCommand::Unsubscribe => *subscription = None,
+        }
+        Ok(Value::Null)
+    } else {
+        match serde_json::from_value::<Command>(message) {
+            Ok(command) => {
+                let result = commands::execute(&command, state).map_err(|e| e.to_string());
+                if result.is_ok() {
+                    tracing::debug!("[Deck] {:?}", command);
+                }
+                result
+            }
+            Err(e) => Err(e.to_string()),
+        }
+    };
+
+    Some(match result {
+        Ok(result) => json!({ "id": id, "ok": true, "result": result }),
+        Err(error) => json!({ "id": id, "ok": false, "error": error }),
+    })
+}
+
+fn write_line(stream: &mut TcpStream, value: &Value) -> std::io::Result<()> {
+    let mut line = value.to_string();
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+fn serve_client(
+    mut stream: TcpStream,
+    shared_state: &Arc<Mutex<SharedState>>,
+    ctx: &egui::Context,
+    stop: &AtomicBool,
+) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut subscription: Option<Subscription> = None;
+    let mut line = Vec::new();
+
+    while !stop.load(Ordering::Relaxed) {
+        let wait = subscription.as_ref().map_or(POLL, |s| s.interval.min(POLL));
+        reader.get_ref().set_read_timeout(Some(wait))?;
+
+        // Partial lines stay in `line` across timeouts
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => return Ok(()),
+            Ok(_) if line.ends_with(b"\n") => {
+                let text = String::from_utf8_lossy(&line).trim().to_string();
+                line.clear();
+                if text.is_empty() {
+                    continue;
+                }
+                let reply = {
+                    let Ok(mut state) = shared_state.lock() else { return Ok(()) };
+                    let before = (state.config.profile.clone(), state.config.window_locked);
+                    let reply = handle_line(&text, &mut state, &mut subscription, Instant::now());
+                    if (&state.config.profile, state.config.window_locked) != (&before.0, before.1) {
+                        ctx.request_repaint();
+                    }
+                    reply
+                };
+                let Some(reply) = reply else {
+                    tracing::debug!("[Deck] Dropping a client that doesn't speak JSON");
+                    return Ok(());
+                };
+                write_line(&mut stream, &reply)?;
+            }
+            Ok(_) => {}
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e),
+        }
+        if line.len() > MAX_LINE {
+            tracing::debug!("[Deck] Dropping a client sending oversized lines");
+            return Ok(());
+        }
+
+        if let Some(sub) = subscription.as_mut() {
+            let events = match shared_state.lock() {
+                Ok(state) => sub.events(&state, Instant::now()),
+                Err(_) => return Ok(()),
+            };
+            for event in &events {
+                write_line(&mut stream, event)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Running socket. Dropping it stops the listener and its connections.
+pub struct Server {
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+pub fn start(port: u16, shared_state: Arc<Mutex<SharedState>>, ctx: egui::Context) -> Server {
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread_stop = stop.clone();
+    let spawned = std::thread::Builder::new().name("bespec-deck".to_string()).spawn(move || {
+        let addr = format!("127.0.0.1:{}", port);
+        // A restarted server may have to wait for the old one to let go of the port
+        let mut listener = None;
+        for _ in 0..20 {
+            match TcpListener::bind(&addr) {
+                Ok(l) => {
+                    listener = Some(l);
+                    break;
+                }
+                Err(e) if e.kind() == ErrorKind::AddrInUse => std::thread::sleep(POLL),
+                Err(e) => {
+                    tracing::error!("[Deck] Couldn't listen on {}: {}", addr, e);
+                    return;
+                }
+            }
+        }
+        let Some(listener) = listener else {
+            tracing::error!("[Deck] Port {} is in use", port);
+            return;
+        };
+        if let Err(e) = listener.set_nonblocking(true) {
+            tracing::error!("[Deck] Socket setup failed: {}", e);
+            return;
+        }
+        tracing::info!("[Deck] Listening on {}", addr);
+
+        let clients = Arc::new(AtomicUsize::new(0));
+        while !thread_stop.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((stream, peer)) => {
+                    if clients.load(Ordering::Relaxed) >= MAX_CLIENTS {
+                        tracing::warn!("[Deck] Too many connections, refusing {}", peer);
+                        continue;
+                    }
+                    clients.fetch_add(1, Ordering::Relaxed);
+                    let (state, ctx, stop, connected) = (shared_state.clone(), ctx.clone(), thread_stop.clone(), clients.clone());
+                    let spawned = std::thread::Builder::new().name("bespec-deck-client".to_string()).spawn(move || {
+                        tracing::info!("[Deck] {} connected", peer);
+                        if let Err(e) = serve_client(stream, &state, &ctx, &stop) {
+                            tracing::debug!("[Deck] {}: {}", peer, e);
+                        }
+                        tracing::info!("[Deck] {} disconnected", peer);
+                        connected.fetch_sub(1, Ordering::Relaxed);
+                    });
+                    if let Err(e) = spawned {
+                        tracing::error!("[Deck] Failed to start client thread: {}", e);
+                        clients.fetch_sub(1, Ordering::Relaxed);
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::sleep(POLL),
+                Err(e) => {
+                    tracing::error!("[Deck] Accept failed: {}", e);
+                    std::thread::sleep(POLL);
+                }
+            }
+        }
+        tracing::info!("[Deck] Stopped");
+    });
+    if let Err(e) = spawned {
+        tracing::error!("[Deck] Failed to start server thread: {}", e);
+    }
+    Server { stop }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared_state::AppConfig;
+
+    #[test]
+    fn test_handle_line() {
+        let mut state = SharedState::with_config(AppConfig::default());
+        let mut sub = None;
+        let now = Instant::now();
+
+        let reply = handle_line(r#"{"id": 7, "cmd": "toggle_ghost_mode"}"#, &mut state, &mut sub, now).unwrap();
+        assert_eq!(reply["id"], json!(7));
+        assert_eq!(reply["ok"], json!(true));
+        assert_eq!(reply["result"]["ghost_mode"], json!(true));
+
+        let reply = handle_line(r#"{"cmd": "set_colors", "name": "Nope"}"#, &mut state, &mut sub, now).unwrap();
+        assert_eq!(reply["ok"], json!(false));
+
+        // An HTTP request line isn't JSON: hang up
+        assert_eq!(handle_line("POST / HTTP/1.1", &mut state, &mut sub, now), None);
+    }
+
+    #[test]
+    fn test_subscription_events() {
+        let mut state = SharedState::with_config(AppConfig::default());
+        let mut sub = None;
+        let now = Instant::now();
+        handle_line(r#"{"cmd": "subscribe", "bands": 2, "fps": 10}"#, &mut state, &mut sub, now).unwrap();
+        let sub = sub.as_mut().unwrap();
+
+        // Status right away, then only when it changes; levels at the rate asked
+        let events = sub.events(&state, now);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["event"], json!("status"));
+        assert_eq!(events[1]["levels"].as_array().unwrap().len(), 2);
+        assert!(sub.events(&state, now + Duration::from_millis(50)).is_empty());
+
+        state.config.window_locked = true;
+        let events = sub.events(&state, now + Duration::from_millis(100));
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["ghost_mode"], json!(true));
+    }
+
+    #[test]
+    fn test_subscription_features() {
+        let mut state = SharedState::with_config(AppConfig::default());
+        state.performance.spectral.centroid_hz = 1500.0;
+        let mut sub = None;
+        let now = Instant::now();
+        handle_line(r#"{"cmd": "subscribe", "features": true}"#, &mut state, &mut sub, now).unwrap();
+
+        let events = sub.as_mut().unwrap().events(&state, now);
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[2]["event"], json!("features"));
+        assert_eq!(events[2]["centroid_hz"], json!(1500.0));
+    }
+}
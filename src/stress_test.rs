@@ -0,0 +1,178 @@
+//! Stress test: run the processing and drawing paths at their heaviest on
+//! synthetic audio and report how much of a 60 FPS frame they leave free.
+//!
+//! Nothing touches the real capture or the window. A background task feeds
+//! noise plus a sweep through its own `FFTProcessor` at the maximum bar
+//! count, then paints the current visual mode into an off-screen egui
+//! Context (the headless renderer) and tessellates it, timing both. The GPU
+//! upload isn't included, so treat the headroom as an upper bound.
+//!
+//! `cargo bench --bench processing` covers the same paths in more detail.
+
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+
+use crate::fft_config::FIXED_FFT_SIZE;
+use crate::fft_processor::{FFTConfig, FFTProcessor};
+use crate::shared_state::{AppConfig, ColorProfile, SharedState};
+
+/// Bar count the test runs at (the slider's maximum)
+pub const MAX_BARS: usize = 512;
+/// Synthetic capture rate
+const SAMPLE_RATE: u32 = 48_000;
+/// The frame budget headroom is measured against (60 FPS)
+const FRAME_BUDGET: Duration = Duration::from_micros(16_667);
+/// Frames to process and draw
+const FRAMES: usize = 300;
+/// Untimed frames first, so caches and buffers are warm
+const WARMUP_FRAMES: usize = 10;
+/// Off-screen view size
+const VIEW_SIZE: [f32; 2] = [1920.0, 1080.0];
+
+/// Where the stress test is (shown in the Stats tab)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum StressStatus {
+    #[default]
+    Idle,
+    /// Button pressed, the GUI update loop starts the job
+    Requested,
+    Working,
+    Done(StressReport),
+}
+
+/// Average cost per frame of each stage
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StressReport {
+    pub bars: usize,
+    pub frames: usize,
+    pub fft_ms: f32,
+    pub draw_ms: f32,
+    pub tessellate_ms: f32,
+}
+
+impl StressReport {
+    pub fn total_ms(&self) -> f32 {
+        self.fft_ms + self.draw_ms + self.tessellate_ms
+    }
+
+    /// Share of the 60 FPS budget left over, 0 when over budget
+    pub fn headroom(&self) -> f32 {
+        let budget_ms = FRAME_BUDGET.as_secs_f32() * 1000.0;
+        (1.0 - self.total_ms() / budget_ms).max(0.0)
+    }
+
+    /// Frame rate the measured stages alone could sustain
+    pub fn max_fps(&self) -> f32 {
+        1000.0 / self.total_ms().max(1e-3)
+    }
+}
+
+/// White noise under a 20 Hz → 20 kHz log sweep, so every bar moves
+struct TestSignal {
+    position: u64,
+    phase: f32,
+    noise: u32,
+}
+
+impl TestSignal {
+    fn fill(&mut self, out: &mut [f32]) {
+        let sweep_samples = 2 * SAMPLE_RATE as u64;
+        for sample in out {
+            let t = (self.position % sweep_samples) as f32 / sweep_samples as f32;
+            let freq = 20.0 * 1000f32.powf(t);
+            self.phase = (self.phase + freq / SAMPLE_RATE as f32).fract();
+            self.position += 1;
+
+            self.noise ^= self.noise << 13;
+            self.noise ^= self.noise >> 17;
+            self.noise ^= self.noise << 5;
+            let noise = self.noise as f32 / u32::MAX as f32 * 2.0 - 1.0;
+            *sample = 0.5 * (self.phase * std::f32::consts::TAU).sin() + 0.1 * noise;
+        }
+    }
+}
+
+/// Run the test with the user's look and mode, but at `MAX_BARS`. Blocks for
+/// a second or two; call it from a background task.
+pub fn run(mut config: AppConfig, user_color_presets: Vec<ColorProfile>) -> StressReport {
+    config.profile.num_bars = MAX_BARS;
+    let profile = &config.profile;
+    let mut processor = FFTProcessor::new(FFTConfig {
+        fft_size: FIXED_FFT_SIZE,
+        sample_rate: SAMPLE_RATE,
+        num_bars: MAX_BARS,
+        sensitivity: profile.sensitivity,
+        attack_time_ms: profile.attack_time_ms,
+        release_time_ms: profile.release_time_ms,
+        peak_hold_time_ms: profile.peak_hold_time_ms,
+        peak_release_time_ms: profile.peak_release_time_ms,
+        aggregation_mode: profile.aggregation_mode,
+        peak_gravity: profile.gravity_peaks(),
+    });
+    let mut state = SharedState::with_config(config);
+    state.user_color_presets = user_color_presets;
+
+    let ctx = egui::Context::default();
+    let screen = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::Vec2::from(VIEW_SIZE));
+    let mut signal = TestSignal { position: 0, phase: 0.0, noise: 0x2545_F491 };
+    // One display frame of audio per frame
+    let mut block = vec![0.0; (FRAME_BUDGET.as_secs_f32() * SAMPLE_RATE as f32) as usize];
+    let (mut fft, mut draw, mut tessellate) = (Duration::ZERO, Duration::ZERO, Duration::ZERO);
+
+    for frame in 0..WARMUP_FRAMES + FRAMES {
+        signal.fill(&mut block);
+        let timed = frame >= WARMUP_FRAMES;
+
+        let start = Instant::now();
+        let (bars, peaks) = processor.process(&block);
+        state.visualization.set_waveform(&block, &[], Instant::now());
+        state.visualization.set_bars(bars, peaks);
+        let processed = Instant::now();
+
+        let output = crate::gui::headless::paint_frame(&ctx, &state, screen);
+        let painted = Instant::now();
+        let primitives = ctx.tessellate(output.shapes, output.pixels_per_point);
+        let tessellated = Instant::now();
+        std::hint::black_box(primitives);
+
+        if timed {
+            fft += processed - start;
+            draw += painted - processed;
+            tessellate += tessellated - painted;
+        }
+    }
+
+    let per_frame_ms = |total: Duration| total.as_secs_f32() * 1000.0 / FRAMES as f32;
+    let report = StressReport {
+        bars: MAX_BARS,
+        frames: FRAMES,
+        fft_ms: per_frame_ms(fft),
+        draw_ms: per_frame_ms(draw),
+        tessellate_ms: per_frame_ms(tessellate),
+    };
+    tracing::info!(
+        "[Stress] {} bars: FFT {:.2} ms, draw {:.2} ms, tessellate {:.2} ms per frame ({:.0}% headroom at 60 FPS)",
+        report.bars, report.fft_ms, report.draw_ms, report.tessellate_ms, report.headroom() * 100.0
+    );
+    report
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(total_ms: f32) -> StressReport {
+        StressReport { bars: MAX_BARS, frames: FRAMES, fft_ms: total_ms / 2.0, draw_ms: total_ms / 4.0, tessellate_ms: total_ms / 4.0 }
+    }
+
+    #[test]
+    fn test_headroom() {
+        assert!((report(4.1667).headroom() - 0.75).abs() < 1e-3);
+        assert!((report(8.0).max_fps() - 125.0).abs() < 1e-3);
+        // Over budget clamps to none
+        assert_eq!(report(40.0).headroom(), 0.0);
+    }
+}
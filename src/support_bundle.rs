@@ -0,0 +1,298 @@
+//! Local support bundle for bug reports.
+//!
+//! Packs what we usually ask for in an issue (config, recent logs, the audio
+//! device list) into one zip next to the logs. Nothing is ever uploaded: the
+//! user picks the file and attaches it to a GitHub issue themselves, and the
+//! GUI asks for consent every time before a bundle is written.
+//!
+//! Before anything goes into the zip it is scrubbed:
+//!   - device names and ids become "Device 1", "Device 2", ..., and devices
+//!     only remembered for their levels "Saved device 1", ...
+//!   - the home folder becomes `~` and the login name `<user>`
+//!   - log lines that can carry artist or title are dropped (`TRACK_MARKERS`)
+//!   - connected account names are left out of the config
+//!
+//! The log viewer's Copy button goes through the same scrubbing
+//! (`scrub_log`), since that text ends up in bug reports too.
+
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::audio_device::AudioDeviceInfo;
+use crate::error::BeSpecError;
+use crate::shared_state::AppConfig;
+
+/// Log files included (newest first)
+const MAX_LOG_FILES: usize = 3;
+
+/// Only the tail of each log file is included
+const MAX_LOG_BYTES: usize = 512 * 1024;
+
+/// Log lines containing any of these can name a song and are dropped:
+/// track changes, track page links (older versions logged the URL), and
+/// the media lookups' debug output
+const TRACK_MARKERS: [&str; 3] = ["New Track:", " URL: ", "[MEDIA]"];
+
+/// Where the support bundle flow is (shown in the Stats tab)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum BundleStatus {
+    #[default]
+    Idle,
+    /// Consent dialog is open
+    Confirming,
+    /// User agreed, the GUI update loop starts the job
+    Requested,
+    Working,
+    /// Bundle written to this path
+    Done(PathBuf),
+}
+
+/// Snapshot of everything the bundle is built from, taken on the GUI thread
+pub struct BundleInputs {
+    pub config: AppConfig,
+    pub devices: Vec<AudioDeviceInfo>,
+    pub log_dir: PathBuf,
+}
+
+/// Plain find-and-replace list applied to every text in the bundle
+struct Scrubber {
+    replacements: Vec<(String, String)>,
+}
+
+impl Scrubber {
+    fn new(devices: &[AudioDeviceInfo], config: &AppConfig) -> Self {
+        let mut replacements = Vec::new();
+
+        for (i, dev) in devices.iter().enumerate() {
+            let label = format!("Device {}", i + 1);
+            replacements.push((dev.id.clone(), label.clone()));
+            replacements.push((dev.name.clone(), label));
+        }
+        // The saved device may be unplugged right now
+        for name in [&config.selected_device, &config.selected_device_name] {
+            if !replacements.iter().any(|(n, _)| n == name) {
+                replacements.push((name.clone(), "Selected device".to_string()));
+            }
+        }
+        // So may every device with saved level offsets; sorted so the
+        // numbering is stable
+        let mut saved: Vec<&String> = config.device_levels.keys().collect();
+        saved.sort();
+        for (i, id) in saved.into_iter().enumerate() {
+            if !replacements.iter().any(|(n, _)| n == id) {
+                replacements.push((id.clone(), format!("Saved device {}", i + 1)));
+            }
+        }
+
+        if let Some(home) = directories::BaseDirs::new().map(|d| d.home_dir().display().to_string()) {
+            replacements.push((home, "~".to_string()));
+        }
+        for var in ["USER", "USERNAME"] {
+            if let Ok(user) = std::env::var(var) {
+                replacements.push((user, "<user>".to_string()));
+            }
+        }
+
+        Self::from_pairs(replacements)
+    }
+
+    fn from_pairs(mut replacements: Vec<(String, String)>) -> Self {
+        // Very short needles would mangle unrelated text, "Default" must stay readable
+        replacements.retain(|(needle, _)| needle.len() >= 3 && needle != "Default");
+        // Longest first, so "Speakers (USB)" goes before "Speakers"
+        replacements.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+        Self { replacements }
+    }
+
+    fn scrub(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        for (needle, replacement) in &self.replacements {
+            out = out.replace(needle.as_str(), replacement);
+        }
+        out
+    }
+}
+
+/// Config as JSON, minus the things that identify the user
+fn scrubbed_config(config: &AppConfig, scrubber: &Scrubber) -> String {
+    let mut config = config.clone();
+    config.connected_accounts.clear();
+    scrubber.scrub(&serde_json::to_string_pretty(&config).unwrap_or_default())
+}
+
+fn device_list(devices: &[AudioDeviceInfo]) -> String {
+    let mut out = format!("{} capture devices\n", devices.len());
+    for (i, dev) in devices.iter().enumerate() {
+        out.push_str(&format!(
+            "Device {}: {} Hz default ({:?}), {} ch ({:?}), {} ({:?})\n",
+            i + 1,
+            dev.default_sample_rate,
+            dev.sample_rates,
+            dev.channels,
+            dev.channel_counts,
+            dev.default_sample_format,
+            dev.sample_formats
+        ));
+    }
+    out
+}
+
+fn scrubbed_log(text: &str, scrubber: &Scrubber) -> String {
+    let kept: Vec<&str> = text
+        .lines()
+        .filter(|l| !TRACK_MARKERS.iter().any(|marker| l.contains(marker)))
+        .collect();
+    scrubber.scrub(&kept.join("\n"))
+}
+
+/// Log text scrubbed the same way as in a bundle, for copying elsewhere
+pub fn scrub_log(text: &str, devices: &[AudioDeviceInfo], config: &AppConfig) -> String {
+    scrubbed_log(text, &Scrubber::new(devices, config))
+}
+
+/// Newest `BeSpec.log*` files in `log_dir`
+fn recent_logs(log_dir: &Path) -> Vec<PathBuf> {
+    let mut logs: Vec<(std::time::SystemTime, PathBuf)> = std::fs::read_dir(log_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.file_name().to_string_lossy().starts_with("BeSpec.log"))
+                .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+                .collect()
+        })
+        .unwrap_or_default();
+    logs.sort_by(|a, b| b.0.cmp(&a.0));
+    logs.into_iter().take(MAX_LOG_FILES).map(|(_, p)| p).collect()
+}
+
+/// Last `MAX_LOG_BYTES` of a file, starting at a line boundary
+fn read_tail(path: &Path) -> std::io::Result<String> {
+    let bytes = std::fs::read(path)?;
+    let start = bytes.len().saturating_sub(MAX_LOG_BYTES);
+    let tail = String::from_utf8_lossy(&bytes[start..]).into_owned();
+    Ok(match (start > 0, tail.find('\n')) {
+        (true, Some(i)) => tail[i + 1..].to_string(),
+        _ => tail,
+    })
+}
+
+/// Write the bundle zip into the log folder and return its path.
+/// Blocking file I/O - run it on the task pool.
+pub fn create(inputs: BundleInputs) -> Result<PathBuf, BeSpecError> {
+    let fail = |what: &str, e: &dyn std::fmt::Display| BeSpecError::Task {
+        task: "Support bundle".to_string(),
+        reason: format!("{}: {}", what, e),
+    };
+
+    let scrubber = Scrubber::new(&inputs.devices, &inputs.config);
+    let mut files = vec![
+        ("README.txt".to_string(), format!(
+            "BeSpec v{} support bundle ({} {})\n\
+             Connected accounts: {}\n\
+             Device names, your user name and song titles have been removed.\n\
+             Attach this zip to your GitHub issue.\n",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            inputs.config.connected_accounts.len()
+        )),
+        ("config.json".to_string(), scrubbed_config(&inputs.config, &scrubber)),
+        ("devices.txt".to_string(), device_list(&inputs.devices)),
+    ];
+    for path in recent_logs(&inputs.log_dir) {
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        match read_tail(&path) {
+            Ok(text) => files.push((format!("logs/{}", name), scrubbed_log(&text, &scrubber))),
+            Err(e) => tracing::warn!("[Support] Skipping {:?}: {}", path, e),
+        }
+    }
+
+    let stamp = time::OffsetDateTime::now_local()
+        .unwrap_or_else(|_| time::OffsetDateTime::now_utc())
+        .format(time::macros::format_description!("[year]-[month]-[day]_[hour]-[minute]-[second]"))
+        .unwrap_or_else(|_| "bundle".to_string());
+    let path = inputs.log_dir.join(format!("bespec-support-{}.zip", stamp));
+
+    let file = std::fs::File::create(&path).map_err(|e| fail("can't create the zip", &e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, contents) in &files {
+        zip.start_file(name.as_str(), options).map_err(|e| fail("can't write the zip", &e))?;
+        zip.write_all(contents.as_bytes()).map_err(|e| fail("can't write the zip", &e))?;
+    }
+    zip.finish().map_err(|e| fail("can't write the zip", &e))?;
+
+    tracing::info!("[Support] Bundle written to {:?} ({} files)", path, files.len());
+    Ok(path)
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared_state::DeviceLevels;
+
+    fn device(id: &str, name: &str) -> AudioDeviceInfo {
+        AudioDeviceInfo {
+            id: id.to_string(),
+            name: name.to_string(),
+            sample_rates: vec![48000],
+            default_sample_rate: 48000,
+            channels: 2,
+            channel_counts: vec![2],
+            sample_formats: vec!["f32".to_string()],
+            default_sample_format: "f32".to_string(),
+            is_default: false,
+        }
+    }
+
+    #[test]
+    fn test_scrubber_longest_first() {
+        let scrubber = Scrubber::from_pairs(vec![
+            ("Speakers".to_string(), "Device 2".to_string()),
+            ("Speakers (USB)".to_string(), "Device 1".to_string()),
+            ("ab".to_string(), "x".to_string()),
+            ("Default".to_string(), "x".to_string()),
+        ]);
+        assert_eq!(
+            scrubber.scrub("Speakers (USB) and Speakers, Default, ab"),
+            "Device 1 and Device 2, Default, ab"
+        );
+    }
+
+    #[test]
+    fn test_bundle_texts_hide_names() {
+        let devices = vec![device("alsa_output.usb-Maono", "Maonocaster E2 (Output Monitor)")];
+        let mut config = AppConfig::default();
+        config.selected_device = devices[0].id.clone();
+        config.selected_device_name = devices[0].name.clone();
+        config.connected_accounts = vec!["lastfm".to_string()];
+        let scrubber = Scrubber::new(&devices, &config);
+
+        let json = scrubbed_config(&config, &scrubber);
+        assert!(!json.contains("Maono"));
+        assert!(!json.contains("lastfm"));
+        assert!(serde_json::from_str::<AppConfig>(&json).is_ok());
+
+        let log = "12:00:00 [Capture] Target device: alsa_output.usb-Maono\n12:00:01 [GUI] New Track: Artist - Song";
+        let log = scrubbed_log(log, &scrubber);
+        assert!(log.contains("Target device: Device 1"));
+        assert!(!log.contains("Song"));
+
+        assert!(!device_list(&devices).contains("Maono"));
+    }
+
+    #[test]
+    fn test_log_drops_track_links_and_saved_devices() {
+        let mut config = AppConfig::default();
+        config.device_levels.insert("Old USB Mic".to_string(), DeviceLevels { gain_db: 6.0, noise_floor_offset_db: 0.0 });
+
+        let log = "12:00:00 [GUI] Opening Wikipedia URL: https://en.wikipedia.org/w/index.php?search=Artist+Song\n\
+                   12:00:01 [Config] Restoring levels for 'Old USB Mic'";
+        let log = scrub_log(log, &[], &config);
+        assert!(!log.contains("Song"));
+        assert!(!log.contains("wikipedia"));
+        assert!(log.contains("Restoring levels for 'Saved device 1'"));
+    }
+}
@@ -0,0 +1,193 @@
+// src/theme_file.rs
+//
+// Shareable `.bespec-theme` files: one JSON file holding a VisualProfile and
+// the ColorProfile it uses, so a look can be passed around as a single file.
+//
+// Exports land in `<data_dir>/themes/`. That same folder is scanned for
+// files to import, so "drop a file in the themes folder" is all it takes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::shared_state::{AppConfig, ColorProfile, ColorRef, VisualProfile};
+
+/// File extension for shared themes (no leading dot)
+pub const THEME_EXTENSION: &str = "bespec-theme";
+
+/// Bumped if the file layout ever changes in a non-additive way
+const THEME_FORMAT_VERSION: u32 = 1;
+
+/// On-disk layout of a `.bespec-theme` file
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ThemeFile {
+    pub format_version: u32,
+    pub visual: VisualProfile,
+    pub colors: ColorProfile,
+}
+
+impl ThemeFile {
+    /// Bundle the active profile + its resolved colors
+    pub fn from_config(config: &AppConfig, user_colors: &[ColorProfile]) -> Self {
+        let mut colors = config.resolve_colors(user_colors);
+        let mut visual = config.profile.clone();
+
+        // Unsaved custom colors don't have a meaningful name yet, borrow the visual's
+        if let ColorRef::Custom(_) = visual.color_link {
+            colors.name = format!("{} Colors", visual.name);
+        }
+        visual.color_link = ColorRef::Preset(colors.name.clone());
+
+        Self {
+            format_version: THEME_FORMAT_VERSION,
+            visual,
+            colors,
+        }
+    }
+
+    /// Write the theme into the themes folder. Returns the file path.
+    pub fn export(&self) -> std::io::Result<PathBuf> {
+        let dir = themes_dir();
+        fs::create_dir_all(&dir)?;
+
+        let path = dir.join(format!("{}.{}", AppConfig::sanitize_filename(&self.visual.name), THEME_EXTENSION));
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json)?;
+
+        tracing::info!("[Presets] Exported theme '{}' to {:?}", self.visual.name, path);
+        Ok(path)
+    }
+
+    /// Read a theme file from anywhere on disk
+    pub fn import(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {:?}: {}", path, e))?;
+        let theme: ThemeFile = serde_json::from_str(&content)
+            .map_err(|e| format!("Not a valid theme file: {}", e))?;
+
+        if theme.format_version > THEME_FORMAT_VERSION {
+            tracing::warn!(
+                "[Presets] Theme {:?} is format v{}, we only know v{}. Trying anyway.",
+                path, theme.format_version, THEME_FORMAT_VERSION
+            );
+        }
+        Ok(theme)
+    }
+
+    /// Rename the visual/color presets so they don't clobber different
+    /// user presets that already use the same name.
+    /// Identical presets keep their name (re-importing is a no-op).
+    pub fn resolve_collisions(&mut self, user_visuals: &[VisualProfile], user_colors: &[ColorProfile]) {
+        let color_taken = |name: &str| {
+            user_colors.iter().any(|c| c.name == name && !same_colors(c, &self.colors, name))
+                || ColorProfile::find_by_name(name).is_some()
+        };
+        let new_color_name = unique_name(&self.colors.name, color_taken);
+
+        if new_color_name != self.colors.name {
+            tracing::info!("[Presets] Imported colors renamed '{}' -> '{}'", self.colors.name, new_color_name);
+            self.colors.name = new_color_name;
+        }
+        self.visual.color_link = ColorRef::Preset(self.colors.name.clone());
+
+        let visual_taken = |name: &str| {
+            user_visuals.iter().any(|v| v.name == name && *v != self.visual)
+                || VisualProfile::built_in().iter().any(|v| v.name == name)
+        };
+        let new_visual_name = unique_name(&self.visual.name, visual_taken);
+
+        if new_visual_name != self.visual.name {
+            tracing::info!("[Presets] Imported visual renamed '{}' -> '{}'", self.visual.name, new_visual_name);
+            self.visual.name = new_visual_name;
+        }
+    }
+}
+
+/// Compare colors ignoring the name field
+fn same_colors(a: &ColorProfile, b: &ColorProfile, name: &str) -> bool {
+    let mut b = b.clone();
+    b.name = name.to_string();
+    *a == b
+}
+
+/// Append " (2)", " (3)"... until `taken` says no
+fn unique_name(base: &str, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{} ({})", base, n))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or_else(|| base.to_string())
+}
+
+/// `<data_dir>/themes`
+pub fn themes_dir() -> PathBuf {
+    crate::paths::get().data_dir.join("themes")
+}
+
+/// All `.bespec-theme` files sitting in the themes folder
+pub fn list_theme_files() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(themes_dir())
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().map_or(false, |ext| ext == THEME_EXTENSION))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_name() {
+        assert_eq!(unique_name("Mine", |_| false), "Mine");
+
+        let existing = ["Mine", "Mine (2)"];
+        assert_eq!(unique_name("Mine", |n| existing.contains(&n)), "Mine (3)");
+    }
+
+    #[test]
+    fn test_roundtrip_json() {
+        let theme = ThemeFile::from_config(&AppConfig::default(), &[]);
+        let json = serde_json::to_string(&theme).unwrap();
+        let back: ThemeFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(theme, back);
+        assert_eq!(back.visual.color_link, ColorRef::Preset(back.colors.name.clone()));
+    }
+
+    #[test]
+    fn test_collision_renames_only_when_different() {
+        let mut visual = VisualProfile::default();
+        visual.name = "Shared".to_string();
+        let mut colors = ColorProfile::default();
+        colors.name = "Shared Colors".to_string();
+        visual.color_link = ColorRef::Preset(colors.name.clone());
+
+        let mut theme = ThemeFile { format_version: 1, visual: visual.clone(), colors: colors.clone() };
+
+        // Identical presets already installed -> keep names
+        theme.resolve_collisions(&[visual.clone()], &[colors.clone()]);
+        assert_eq!(theme.visual.name, "Shared");
+        assert_eq!(theme.colors.name, "Shared Colors");
+
+        // Different presets with the same names -> rename both
+        let mut other_visual = visual.clone();
+        other_visual.num_bars += 1;
+        let mut other_colors = colors.clone();
+        other_colors.low = crate::shared_state::Color32::RED;
+        theme.resolve_collisions(&[other_visual], &[other_colors]);
+        assert_eq!(theme.visual.name, "Shared (2)");
+        assert_eq!(theme.colors.name, "Shared Colors (2)");
+        assert_eq!(theme.visual.color_link, ColorRef::Preset("Shared Colors (2)".to_string()));
+    }
+}
@@ -0,0 +1,98 @@
+//! Color preset scheduling by time of day.
+//!
+//! The user keeps a list of (start time, color preset) pairs, e.g. "Sunset"
+//! from 18:00 and "Arctic Night" from 22:00. Each entry holds until the next
+//! one starts, and the last entry of the day carries on past midnight until
+//! the first one.
+//!
+//! The GUI only switches when the scheduled preset *changes*, so picking
+//! another preset by hand sticks until the next boundary.
+//!
+//! Local time comes from the UTC offset read at startup: on Unix the `time`
+//! crate can't look it up once other threads are running. A DST change
+//! shifts the schedule by an hour until the next restart.
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+static LOCAL_OFFSET: OnceLock<time::UtcOffset> = OnceLock::new();
+
+/// Remember the local UTC offset. Call once from `main` before any threads start.
+pub fn set_local_offset(offset: time::UtcOffset) {
+    let _ = LOCAL_OFFSET.set(offset);
+}
+
+/// Minutes since local midnight (0..1440)
+pub fn local_minute_of_day() -> u16 {
+    let offset = LOCAL_OFFSET.get().copied().unwrap_or(time::UtcOffset::UTC);
+    let now = time::OffsetDateTime::now_utc().to_offset(offset);
+    now.hour() as u16 * 60 + now.minute() as u16
+}
+
+/// One scheduled switch
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct ScheduleEntry {
+    /// Minutes after midnight this entry starts
+    pub start_minute: u16,
+    /// Color preset name (user or built-in)
+    pub preset: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct ThemeSchedule {
+    pub enabled: bool,
+    pub entries: Vec<ScheduleEntry>,
+}
+
+impl ThemeSchedule {
+    /// Preset scheduled at `minute` of the day, `None` if the schedule is off or empty
+    pub fn preset_at(&self, minute: u16) -> Option<&str> {
+        if !self.enabled {
+            return None;
+        }
+        let latest_started = self.entries.iter().filter(|e| e.start_minute <= minute).max_by_key(|e| e.start_minute);
+        // Nothing started yet today: yesterday's last entry is still running
+        latest_started
+            .or_else(|| self.entries.iter().max_by_key(|e| e.start_minute))
+            .map(|e| e.preset.as_str())
+    }
+}
+
+/// "HH:MM" for the settings list
+pub fn format_minute(minute: u16) -> String {
+    format!("{:02}:{:02}", minute / 60, minute % 60)
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule() -> ThemeSchedule {
+        ThemeSchedule {
+            enabled: true,
+            entries: vec![
+                ScheduleEntry { start_minute: 22 * 60, preset: "Arctic Night".to_string() },
+                ScheduleEntry { start_minute: 18 * 60, preset: "Sunset".to_string() },
+                ScheduleEntry { start_minute: 7 * 60, preset: "Default".to_string() },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_preset_at_wraps_past_midnight() {
+        let schedule = schedule();
+        assert_eq!(schedule.preset_at(12 * 60), Some("Default"));
+        assert_eq!(schedule.preset_at(18 * 60), Some("Sunset"));
+        assert_eq!(schedule.preset_at(23 * 60 + 30), Some("Arctic Night"));
+        assert_eq!(schedule.preset_at(3 * 60), Some("Arctic Night"));
+
+        let off = ThemeSchedule { enabled: false, ..schedule };
+        assert_eq!(off.preset_at(12 * 60), None);
+        assert_eq!(ThemeSchedule { enabled: true, entries: vec![] }.preset_at(0), None);
+        assert_eq!(format_minute(22 * 60 + 5), "22:05");
+    }
+}
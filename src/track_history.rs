@@ -0,0 +1,115 @@
+//! Recently played tracks, for "what was that song 10 minutes ago".
+//!
+//! Every media update goes through `TrackHistory::record`; a new entry is
+//! added only when artist + title change, so pausing, seeking or a player
+//! re-sending the same track doesn't repeat it. The oldest entries drop off
+//! past the configured length.
+//!
+//! The history lives in memory only and is gone on restart, same as the
+//! rest of the media state (see `AppConfig::log_media_metadata` for the
+//! one place titles can reach the disk).
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use crate::media::MediaTrackInfo;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryEntry {
+    pub artist: String,
+    pub title: String,
+    pub source_app: String,
+    /// Local minutes since midnight when it started
+    pub minute_of_day: u16,
+    pub seen_at: Instant,
+}
+
+impl HistoryEntry {
+    /// "Artist – Title", what gets copied
+    pub fn display(&self) -> String {
+        if self.artist.is_empty() {
+            self.title.clone()
+        } else {
+            format!("{} – {}", self.artist, self.title)
+        }
+    }
+
+    /// "14:03"
+    pub fn clock(&self) -> String {
+        format!("{:02}:{:02}", self.minute_of_day / 60, self.minute_of_day % 60)
+    }
+}
+
+/// Newest first
+#[derive(Default)]
+pub struct TrackHistory {
+    entries: VecDeque<HistoryEntry>,
+}
+
+impl TrackHistory {
+    /// Feed every media update. Returns true if it started a new entry.
+    pub fn record(&mut self, info: &MediaTrackInfo, max_len: usize, minute_of_day: u16, now: Instant) -> bool {
+        if info.title.is_empty() {
+            return false;
+        }
+        let same = self.entries.front().map_or(false, |e| e.artist == info.artist && e.title == info.title);
+        if same {
+            return false;
+        }
+        self.entries.push_front(HistoryEntry {
+            artist: info.artist.clone(),
+            title: info.title.clone(),
+            source_app: info.source_app.clone(),
+            minute_of_day,
+            seen_at: now,
+        });
+        self.entries.truncate(max_len);
+        true
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_skips_repeats_and_rolls_over() {
+        let now = Instant::now();
+        let track = |artist: &str, title: &str| MediaTrackInfo {
+            artist: artist.into(),
+            title: title.into(),
+            is_playing: true,
+            ..Default::default()
+        };
+        let mut history = TrackHistory::default();
+
+        assert!(history.record(&track("A", "One"), 2, 600, now));
+        // Pausing the same track isn't a new entry
+        assert!(!history.record(&MediaTrackInfo { is_playing: false, ..track("A", "One") }, 2, 601, now));
+        assert!(history.record(&track("B", "Two"), 2, 605, now));
+        assert!(history.record(&track("C", "Three"), 2, 1439, now));
+        assert!(!history.record(&track("", ""), 2, 0, now));
+
+        let shown: Vec<String> = history.entries().map(|e| e.display()).collect();
+        assert_eq!(shown, vec!["C – Three", "B – Two"]);
+        assert_eq!(history.entries().next().unwrap().clock(), "23:59");
+    }
+}
@@ -0,0 +1,240 @@
+//! Tuner: finds the fundamental of a single played note with the YIN
+//! algorithm (de Cheveigné & Kawahara, 2002) and names it.
+//!
+//! Runs in the FFT thread on the same mono samples the spectrum sees, like
+//! `gain_advisor::GainAdvisor`. It keeps its own window of recent samples,
+//! since capture packets are often shorter than a low note's period twice
+//! over, and runs the detection a limited number of times per second; the
+//! overlay doesn't need more, and YIN is quadratic in the window length.
+//!
+//! Meant for one instrument on the input (a guitar through an interface, a
+//! mic in front of a voice). A full mix has no single pitch, and YIN then
+//! mostly reports nothing, which is the right answer.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Lowest note looked for (a little under a 5-string bass's B0 would need
+/// a longer window; this covers a 4-string bass's E1 at 41 Hz)
+const MIN_HZ: f32 = 38.0;
+const MAX_HZ: f32 = 2000.0;
+/// Samples in the difference function's sum
+const WINDOW: usize = 1024;
+/// How often detection runs
+const INTERVAL: Duration = Duration::from_millis(50);
+/// Keep showing the last note this long after detection stops finding one
+const HOLD: Duration = Duration::from_millis(400);
+/// YIN's absolute threshold on the normalized difference
+const THRESHOLD: f32 = 0.15;
+/// Quieter input than this (RMS, dBFS) isn't analysed
+const GATE_DB: f32 = -50.0;
+
+const NOTE_NAMES: [&str; 12] = ["C", "C♯", "D", "D♯", "E", "F", "F♯", "G", "G♯", "A", "A♯", "B"];
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct TunerSettings {
+    /// Show the tuner overlay (detection only runs while it's on)
+    pub enabled: bool,
+    /// Concert pitch (Hz)
+    pub a4_hz: f32,
+}
+
+impl Default for TunerSettings {
+    fn default() -> Self {
+        Self { enabled: false, a4_hz: 440.0 }
+    }
+}
+
+/// A detected pitch
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Reading {
+    pub freq_hz: f32,
+    /// 0..1, how periodic the signal was (1 - YIN's normalized difference)
+    pub clarity: f32,
+}
+
+/// A frequency as the nearest equal-tempered note
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Note {
+    pub name: &'static str,
+    pub octave: i32,
+    /// How far off the note (-50..50)
+    pub cents: f32,
+}
+
+impl Note {
+    pub fn from_freq(freq_hz: f32, a4_hz: f32) -> Self {
+        // MIDI numbering: A4 = 69, C4 = 60
+        let semitones = 69.0 + 12.0 * (freq_hz / a4_hz).log2();
+        let nearest = semitones.round();
+        let midi = nearest as i32;
+        Self {
+            name: NOTE_NAMES[midi.rem_euclid(12) as usize],
+            octave: midi.div_euclid(12) - 1,
+            cents: (semitones - nearest) * 100.0,
+        }
+    }
+}
+
+/// Lives in the FFT thread
+pub struct Tuner {
+    /// Most recent samples, oldest first
+    buffer: Vec<f32>,
+    rate: u32,
+    last_run: Option<Instant>,
+    last_reading: Option<(Reading, Instant)>,
+    /// Difference function scratch, reused between runs
+    diff: Vec<f32>,
+}
+
+impl Default for Tuner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tuner {
+    pub fn new() -> Self {
+        Self { buffer: Vec::new(), rate: 0, last_run: None, last_reading: None, diff: Vec::new() }
+    }
+
+    /// Feed a block of mono samples
+    pub fn process(&mut self, samples: &[f32], rate: u32, now: Instant) {
+        if rate == 0 {
+            return;
+        }
+        if rate != self.rate {
+            *self = Self::new();
+            self.rate = rate;
+        }
+        let max_lag = (rate as f32 / MIN_HZ).ceil() as usize;
+        let needed = WINDOW + max_lag + 1;
+        self.buffer.extend_from_slice(samples);
+        if self.buffer.len() > needed {
+            self.buffer.drain(..self.buffer.len() - needed);
+        }
+        if self.buffer.len() < needed || self.last_run.is_some_and(|t| now.duration_since(t) < INTERVAL) {
+            return;
+        }
+        self.last_run = Some(now);
+
+        if let Some(reading) = yin(&self.buffer, rate, &mut self.diff) {
+            self.last_reading = Some((reading, now));
+        }
+    }
+
+    /// Latest pitch, held for a moment so the overlay doesn't flicker
+    pub fn reading(&self, now: Instant) -> Option<Reading> {
+        self.last_reading.filter(|(_, at)| now.duration_since(*at) <= HOLD).map(|(reading, _)| reading)
+    }
+}
+
+/// YIN over `samples`, which must hold `WINDOW` plus the longest lag
+fn yin(samples: &[f32], rate: u32, diff: &mut Vec<f32>) -> Option<Reading> {
+    let min_lag = ((rate as f32 / MAX_HZ).floor() as usize).max(2);
+    let max_lag = ((rate as f32 / MIN_HZ).ceil() as usize).min(samples.len().saturating_sub(WINDOW + 1));
+    if max_lag <= min_lag + 2 {
+        return None;
+    }
+
+    let window = &samples[..WINDOW];
+    let power = window.iter().map(|s| s * s).sum::<f32>() / WINDOW as f32;
+    if 10.0 * power.max(1e-12).log10() < GATE_DB {
+        return None;
+    }
+
+    // Steps 1-3: difference function and its cumulative mean normalization
+    diff.clear();
+    diff.resize(max_lag + 1, 1.0);
+    let mut running = 0.0;
+    for lag in 1..=max_lag {
+        let d: f32 = window.iter().zip(&samples[lag..lag + WINDOW]).map(|(a, b)| (a - b) * (a - b)).sum();
+        running += d;
+        diff[lag] = if running > 0.0 { d * lag as f32 / running } else { 1.0 };
+    }
+
+    // Step 4: first dip under the threshold, followed to its minimum
+    let mut lag = min_lag;
+    while lag < max_lag {
+        if diff[lag] < THRESHOLD {
+            while lag + 1 < max_lag && diff[lag + 1] < diff[lag] {
+                lag += 1;
+            }
+            break;
+        }
+        lag += 1;
+    }
+    if lag >= max_lag || diff[lag] >= THRESHOLD {
+        return None;
+    }
+
+    // Step 5: parabolic interpolation around the minimum
+    let (a, b, c) = (diff[lag - 1], diff[lag], diff[lag + 1]);
+    let denom = a - 2.0 * b + c;
+    let offset = if denom.abs() > f32::EPSILON { (0.5 * (a - c) / denom).clamp(-1.0, 1.0) } else { 0.0 };
+    Some(Reading { freq_hz: rate as f32 / (lag as f32 + offset), clarity: (1.0 - b).clamp(0.0, 1.0) })
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(freq: f32, rate: u32, len: usize) -> Vec<f32> {
+        // A few harmonics, like a plucked string, so the fundamental isn't
+        // the only thing in there
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / rate as f32;
+                let w = |h: f32| (t * freq * h * std::f32::consts::TAU).sin();
+                0.5 * w(1.0) + 0.3 * w(2.0) + 0.15 * w(3.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detects_low_and_high_notes() {
+        for freq in [41.2, 110.0, 329.63, 1046.5] {
+            let mut tuner = Tuner::new();
+            let now = Instant::now();
+            tuner.process(&tone(freq, 48_000, 4096), 48_000, now);
+            let reading = tuner.reading(now).unwrap_or_else(|| panic!("nothing found at {} Hz", freq));
+            let cents = 1200.0 * (reading.freq_hz / freq).log2();
+            assert!(cents.abs() < 3.0, "{} Hz read as {} Hz", freq, reading.freq_hz);
+        }
+    }
+
+    #[test]
+    fn test_silence_and_noise_read_nothing() {
+        let mut tuner = Tuner::new();
+        let now = Instant::now();
+        tuner.process(&vec![0.0; 4096], 48_000, now);
+        assert_eq!(tuner.reading(now), None);
+
+        let mut seed = 1u32;
+        let noise: Vec<f32> = (0..4096)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+            })
+            .collect();
+        tuner.process(&noise, 48_000, now + INTERVAL);
+        assert_eq!(tuner.reading(now + INTERVAL), None);
+    }
+
+    #[test]
+    fn test_note_names() {
+        assert_eq!(Note::from_freq(440.0, 440.0), Note { name: "A", octave: 4, cents: 0.0 });
+        let e2 = Note::from_freq(82.41, 440.0);
+        assert_eq!((e2.name, e2.octave), ("E", 2));
+        assert!(e2.cents.abs() < 1.0);
+        let sharp = Note::from_freq(261.63 * 2f32.powf(20.0 / 1200.0), 440.0);
+        assert_eq!((sharp.name, sharp.octave), ("C", 4));
+        assert!((sharp.cents - 20.0).abs() < 0.5);
+        // Another concert pitch moves everything
+        assert_eq!(Note::from_freq(432.0, 432.0).name, "A");
+    }
+}
@@ -15,14 +15,11 @@ pub(crate) fn check_for_updates() -> Result<Option<String>> {
     let local_version = Version::parse(current_version_str)
         .map_err(|e| anyhow::anyhow!("Critical: Local version '{}' is not SemVer compliant: {}", current_version_str, e))?;
     
-    // User-Agent is REQUIRED by GitHub API
-    let resp = ureq::get("https://api.github.com/repos/BeSpec-Dev/bespec/releases/latest")
-        .set("User-Agent", "bespec-client")
-        .call()
+    // User-Agent (REQUIRED by GitHub API) is set by the request manager
+    let release: GitHubRelease = crate::net::Request::get("https://api.github.com/repos/BeSpec-Dev/bespec/releases/latest")
+        .send_json()
         .context("Failed to fetch latest release from GitHub API")?;
 
-    let release: GitHubRelease = resp.into_json().context("Failed to parse GitHub release JSON")?;
-
     // handle 'v' prefix (v1.5.1 vs 1.5.1)
     let clean_tag = release.tag_name.trim_start_matches('v');
 
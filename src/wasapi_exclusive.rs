@@ -0,0 +1,261 @@
+//! Exclusive-mode WASAPI capture for Windows inputs.
+//!
+//! cpal only opens WASAPI in shared mode, where the audio engine converts
+//! and mixes the signal and keeps a buffer of its own. In exclusive mode the
+//! stream goes straight to the driver at the device's own format, with the
+//! smallest period the hardware allows, and no other app can use the input
+//! meanwhile. WASAPI loopback (capturing what's playing) is shared-only, so
+//! this is for real inputs: microphones, line-ins, interfaces.
+//!
+//! `AudioCaptureManager` tries this first when Exclusive Mode is on and the
+//! device is an input, and falls back to the cpal shared-mode stream with a
+//! notice for the GUI if the device refuses (busy, exclusive use disabled
+//! in the Sound control panel, no usable format).
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use crossbeam_channel::Sender;
+use windows::core::{GUID, PCWSTR};
+use windows::Win32::Devices::FunctionDiscovery::PKEY_Device_FriendlyName;
+use windows::Win32::Foundation::{CloseHandle, HANDLE, S_OK, WAIT_OBJECT_0};
+use windows::Win32::Media::Audio::{
+    eCapture, IAudioCaptureClient, IAudioClient, IMMDevice, IMMDeviceEnumerator, MMDeviceEnumerator,
+    AUDCLNT_BUFFERFLAGS_SILENT, AUDCLNT_E_BUFFER_SIZE_NOT_ALIGNED, AUDCLNT_SHAREMODE_EXCLUSIVE,
+    AUDCLNT_STREAMFLAGS_EVENTCALLBACK, DEVICE_STATE_ACTIVE, WAVEFORMATEX, WAVEFORMATEXTENSIBLE,
+    WAVEFORMATEXTENSIBLE_0,
+};
+use windows::Win32::System::Com::StructuredStorage::{PropVariantClear, PropVariantToStringAlloc};
+use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CoTaskMemFree, CLSCTX_ALL, COINIT_MULTITHREADED, STGM_READ};
+use windows::Win32::System::Threading::{CreateEventW, WaitForSingleObject};
+
+use crate::audio_capture::{convert_le_bytes, AudioPacket, SampleLayout};
+use crate::audio_device::AudioDeviceError;
+
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+const SUBTYPE_PCM: GUID = GUID::from_u128(0x00000001_0000_0010_8000_00aa00389b71);
+const SUBTYPE_IEEE_FLOAT: GUID = GUID::from_u128(0x00000003_0000_0010_8000_00aa00389b71);
+
+/// Sample layouts offered to the driver, best first. Exclusive mode has no
+/// format conversion, so one of these has to be what the hardware runs at.
+const LAYOUTS: [SampleLayout; 4] = [
+    SampleLayout { bytes: 4, valid_bits: 32, float: true },
+    SampleLayout { bytes: 4, valid_bits: 24, float: false },
+    SampleLayout { bytes: 3, valid_bits: 24, float: false },
+    SampleLayout { bytes: 2, valid_bits: 16, float: false },
+];
+
+/// REFERENCE_TIME (100 ns) units per second
+const HNS_PER_SEC: i64 = 10_000_000;
+
+/// How long a wait for the next period may take before shutdown is checked
+const WAIT_MS: u32 = 100;
+
+/// An initialized, not yet started exclusive-mode capture stream
+pub struct ExclusiveStream {
+    client: IAudioClient,
+    capture: IAudioCaptureClient,
+    event: HANDLE,
+    layout: SampleLayout,
+    sample_rate: u32,
+    channels: u16,
+}
+
+// The COM objects are created in the multithreaded apartment and the stream
+// is only ever used from the capture thread that opened it
+unsafe impl Send for ExclusiveStream {}
+
+impl ExclusiveStream {
+    /// Open the input named `device_name` in exclusive mode at its own rate
+    /// and channel count. `buffer_frames` asks for a period; `None` uses the
+    /// device's default period.
+    pub fn open(device_name: &str, buffer_frames: Option<u32>) -> Result<Self, AudioDeviceError> {
+        unsafe { Self::open_inner(device_name, buffer_frames) }.map_err(|e| match e {
+            OpenError::Wasapi(e) => AudioDeviceError::StreamCreationFailed(e.to_string()),
+            OpenError::Device(e) => e,
+        })
+    }
+
+    unsafe fn open_inner(device_name: &str, buffer_frames: Option<u32>) -> Result<Self, OpenError> {
+        // Fails harmlessly if COM is already set up on this thread
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+        let device = find_input(device_name)?
+            .ok_or_else(|| AudioDeviceError::DeviceNotFound(device_name.to_string()))?;
+        let mut client: IAudioClient = device.Activate(CLSCTX_ALL, None)?;
+
+        // The shared-mode mix format tells the rate and channel layout the
+        // device is set to in the Sound control panel
+        let mix = client.GetMixFormat()?;
+        let mix_format = std::ptr::read_unaligned(mix);
+        let channel_mask = if mix_format.wFormatTag == WAVE_FORMAT_EXTENSIBLE {
+            std::ptr::read_unaligned(mix as *const WAVEFORMATEXTENSIBLE).dwChannelMask
+        } else {
+            0
+        };
+        CoTaskMemFree(Some(mix as *const _));
+        let (sample_rate, channels) = (mix_format.nSamplesPerSec, mix_format.nChannels);
+
+        let (layout, format) = LAYOUTS
+            .iter()
+            .map(|&layout| (layout, wave_format(sample_rate, channels, channel_mask, layout)))
+            .find(|(_, format)| client.IsFormatSupported(AUDCLNT_SHAREMODE_EXCLUSIVE, as_wave_format(format), None) == S_OK)
+            .ok_or_else(|| AudioDeviceError::ConfigurationError(format!(
+                "no exclusive-mode sample format at {} Hz, {} channels", sample_rate, channels
+            )))?;
+
+        let (mut default_period, mut min_period) = (0i64, 0i64);
+        client.GetDevicePeriod(Some(&mut default_period), Some(&mut min_period))?;
+        let period = buffer_frames.map_or(default_period, |frames| {
+            (frames as i64 * HNS_PER_SEC / sample_rate as i64).max(min_period)
+        });
+
+        // Event driven, so buffer size and period are the same
+        let init = |client: &IAudioClient, period: i64| {
+            client.Initialize(
+                AUDCLNT_SHAREMODE_EXCLUSIVE,
+                AUDCLNT_STREAMFLAGS_EVENTCALLBACK,
+                period,
+                period,
+                as_wave_format(&format),
+                None,
+            )
+        };
+        if let Err(e) = init(&client, period) {
+            if e.code() != AUDCLNT_E_BUFFER_SIZE_NOT_ALIGNED {
+                return Err(e.into());
+            }
+            // The driver wants whole buffers of its own size; it reports
+            // that size, and a fresh client gets asked for exactly that
+            let frames = client.GetBufferSize()? as i64;
+            let aligned = (frames * HNS_PER_SEC + sample_rate as i64 / 2) / sample_rate as i64;
+            client = device.Activate(CLSCTX_ALL, None)?;
+            init(&client, aligned)?;
+        }
+
+        let event = CreateEventW(None, false, false, PCWSTR::null())?;
+        let stream = Self { capture: client.GetService()?, client, event, layout, sample_rate, channels };
+        stream.client.SetEventHandle(stream.event)?;
+
+        tracing::info!(
+            "[AudioCapture] Exclusive mode: {} Hz, {} ch, {}-bit {}, {} frame buffer",
+            sample_rate,
+            channels,
+            layout.valid_bits,
+            if layout.float { "float" } else { "int" },
+            stream.client.GetBufferSize().unwrap_or(0),
+        );
+        Ok(stream)
+    }
+
+    /// Start the stream and send packets until `shutdown` is set
+    pub fn run(self, tx: Sender<AudioPacket>, shutdown: &AtomicBool) -> Result<(), AudioDeviceError> {
+        let failed = |e: windows::core::Error| AudioDeviceError::StreamCreationFailed(e.to_string());
+        unsafe { self.client.Start() }.map_err(failed)?;
+        tracing::info!("[AudioCapture] ✓ Exclusive-mode stream started");
+
+        let result = (|| {
+            while !shutdown.load(Ordering::Relaxed) {
+                if unsafe { WaitForSingleObject(self.event, WAIT_MS) } != WAIT_OBJECT_0 {
+                    continue;
+                }
+                while unsafe { self.capture.GetNextPacketSize() }.map_err(failed)? > 0 {
+                    let packet = unsafe { self.read_packet() }.map_err(failed)?;
+                    let _ = tx.try_send(packet);
+                }
+            }
+            Ok(())
+        })();
+
+        tracing::info!("[AudioCapture] Shutting down exclusive-mode stream...");
+        let _ = unsafe { self.client.Stop() };
+        result
+    }
+
+    unsafe fn read_packet(&self) -> windows::core::Result<AudioPacket> {
+        let mut data = std::ptr::null_mut();
+        let (mut frames, mut flags) = (0u32, 0u32);
+        self.capture.GetBuffer(&mut data, &mut frames, &mut flags, None, None)?;
+
+        let count = frames as usize * self.channels as usize;
+        let samples = if flags & AUDCLNT_BUFFERFLAGS_SILENT.0 as u32 != 0 || data.is_null() {
+            vec![0.0; count]
+        } else {
+            let bytes = std::slice::from_raw_parts(data, count * self.layout.bytes);
+            convert_le_bytes(bytes, self.layout)
+        };
+        self.capture.ReleaseBuffer(frames)?;
+
+        Ok(AudioPacket { samples, sample_rate: self.sample_rate, channels: self.channels, timestamp: Instant::now() })
+    }
+}
+
+impl Drop for ExclusiveStream {
+    fn drop(&mut self) {
+        let _ = unsafe { CloseHandle(self.event) };
+    }
+}
+
+enum OpenError {
+    Wasapi(windows::core::Error),
+    Device(AudioDeviceError),
+}
+
+impl From<windows::core::Error> for OpenError {
+    fn from(e: windows::core::Error) -> Self {
+        Self::Wasapi(e)
+    }
+}
+
+impl From<AudioDeviceError> for OpenError {
+    fn from(e: AudioDeviceError) -> Self {
+        Self::Device(e)
+    }
+}
+
+/// The active capture endpoint whose friendly name (what cpal calls the
+/// device's name) is `name`. Two inputs with the same name get the first.
+unsafe fn find_input(name: &str) -> windows::core::Result<Option<IMMDevice>> {
+    let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+    let inputs = enumerator.EnumAudioEndpoints(eCapture, DEVICE_STATE_ACTIVE)?;
+    for i in 0..inputs.GetCount()? {
+        let device = inputs.Item(i)?;
+        if friendly_name(&device).as_deref() == Some(name) {
+            return Ok(Some(device));
+        }
+    }
+    Ok(None)
+}
+
+unsafe fn friendly_name(device: &IMMDevice) -> Option<String> {
+    let store = device.OpenPropertyStore(STGM_READ).ok()?;
+    let mut value = store.GetValue(&PKEY_Device_FriendlyName).ok()?;
+    let text = PropVariantToStringAlloc(&value);
+    let _ = PropVariantClear(&mut value);
+    let text = text.ok()?;
+    let name = text.to_string().ok();
+    CoTaskMemFree(Some(text.0 as *const _));
+    name
+}
+
+fn wave_format(sample_rate: u32, channels: u16, channel_mask: u32, layout: SampleLayout) -> WAVEFORMATEXTENSIBLE {
+    let block_align = channels * layout.bytes as u16;
+    WAVEFORMATEXTENSIBLE {
+        Format: WAVEFORMATEX {
+            wFormatTag: WAVE_FORMAT_EXTENSIBLE,
+            nChannels: channels,
+            nSamplesPerSec: sample_rate,
+            nAvgBytesPerSec: sample_rate * block_align as u32,
+            nBlockAlign: block_align,
+            wBitsPerSample: layout.bytes as u16 * 8,
+            cbSize: (std::mem::size_of::<WAVEFORMATEXTENSIBLE>() - std::mem::size_of::<WAVEFORMATEX>()) as u16,
+        },
+        Samples: WAVEFORMATEXTENSIBLE_0 { wValidBitsPerSample: layout.valid_bits },
+        dwChannelMask: channel_mask,
+        SubFormat: if layout.float { SUBTYPE_IEEE_FLOAT } else { SUBTYPE_PCM },
+    }
+}
+
+fn as_wave_format(format: &WAVEFORMATEXTENSIBLE) -> *const WAVEFORMATEX {
+    format as *const WAVEFORMATEXTENSIBLE as *const WAVEFORMATEX
+}
@@ -0,0 +1,36 @@
+use raw_window_handle::RawWindowHandle;
+
+use super::{Pointer, ScreenRect};
+use crate::shared_state::DockEdge;
+
+pub fn reserve(_window: RawWindowHandle, _edge: DockEdge, _monitor: ScreenRect, _thickness: i32) -> Result<ScreenRect, String> {
+    Err("reserving screen space is not supported on this OS".into())
+}
+
+pub fn release(_window: RawWindowHandle) {}
+
+pub fn attach_to_desktop(_window: RawWindowHandle) -> Result<(), String> {
+    Err("wallpaper mode is not supported on this OS".into())
+}
+
+pub fn detach_from_desktop(_window: RawWindowHandle) {}
+
+pub fn monitors() -> Vec<ScreenRect> {
+    Vec::new()
+}
+
+pub fn monitor_scale(_monitor: &ScreenRect) -> Option<f32> {
+    None
+}
+
+pub fn is_occluded(_window: RawWindowHandle) -> bool {
+    false
+}
+
+pub fn refresh_rate(_window: RawWindowHandle) -> Option<f32> {
+    None
+}
+
+pub fn pointer(_window: RawWindowHandle) -> Option<Pointer> {
+    None
+}
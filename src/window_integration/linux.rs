@@ -0,0 +1,302 @@
+use std::sync::Mutex;
+
+use raw_window_handle::RawWindowHandle;
+use x11rb::connection::Connection;
+use x11rb::protocol::randr::ConnectionExt as _;
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _, MapState, PropMode};
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+
+use super::{Pointer, ScreenRect};
+use crate::shared_state::DockEdge;
+
+fn x11_window(window: RawWindowHandle) -> Option<u32> {
+    match window {
+        RawWindowHandle::Xlib(h) => Some(h.window as u32),
+        RawWindowHandle::Xcb(h) => Some(h.window.get()),
+        _ => None,
+    }
+}
+
+/// X connection shared by every call in here. The pointer, occlusion and
+/// refresh rate queries run every few frames, too often for a new
+/// connection each time.
+struct X11 {
+    conn: RustConnection,
+    screen: usize,
+}
+
+/// Opened on first use; dropped after a failed call, so a dead connection
+/// (X server restarted) is replaced by the next one
+static CONNECTION: Mutex<Option<X11>> = Mutex::new(None);
+
+/// Run `f` on the shared connection, opening it first if needed
+fn with_x11<T>(f: impl FnOnce(&X11) -> Result<T, String>) -> Result<T, String> {
+    let mut slot = CONNECTION.lock().map_err(|e| e.to_string())?;
+    if slot.is_none() {
+        *slot = Some(X11::connect()?);
+    }
+    let result = f(slot.as_ref().expect("X connection was just opened"));
+    if result.is_err() {
+        *slot = None;
+    }
+    result
+}
+
+impl X11 {
+    fn connect() -> Result<Self, String> {
+        let (conn, screen) = x11rb::connect(None).map_err(|e| e.to_string())?;
+        Ok(Self { conn, screen })
+    }
+
+    fn atom(&self, name: &[u8]) -> Result<u32, String> {
+        let cookie = self.conn.intern_atom(false, name).map_err(|e| e.to_string())?;
+        Ok(cookie.reply().map_err(|e| e.to_string())?.atom)
+    }
+
+    fn root(&self) -> u32 {
+        self.conn.setup().roots[self.screen].root
+    }
+
+    fn screen_height(&self) -> i32 {
+        self.conn.setup().roots[self.screen].height_in_pixels as i32
+    }
+
+    fn set_cardinals(&self, window: u32, property: u32, values: &[u32]) -> Result<(), String> {
+        self.conn
+            .change_property32(PropMode::REPLACE, window, property, AtomEnum::CARDINAL, values)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<(), String> {
+        self.conn.flush().map_err(|e| e.to_string())
+    }
+}
+
+/// Set (or with `None`, remove) `_NET_WM_STRUT_PARTIAL` and the older `_NET_WM_STRUT`
+fn set_strut(x11: &X11, window: u32, partial: Option<[u32; 12]>) -> Result<(), String> {
+    let strut_partial = x11.atom(b"_NET_WM_STRUT_PARTIAL")?;
+    let strut = x11.atom(b"_NET_WM_STRUT")?;
+
+    match partial {
+        Some(values) => {
+            x11.set_cardinals(window, strut_partial, &values)?;
+            x11.set_cardinals(window, strut, &values[..4])?;
+        }
+        None => {
+            x11.conn.delete_property(window, strut_partial).map_err(|e| e.to_string())?;
+            x11.conn.delete_property(window, strut).map_err(|e| e.to_string())?;
+        }
+    }
+    x11.flush()
+}
+
+/// Switch `_NET_WM_WINDOW_TYPE` between `_DESKTOP` and `_NORMAL`
+fn set_desktop_type(x11: &X11, window: u32, desktop: bool) -> Result<(), String> {
+    let property = x11.atom(b"_NET_WM_WINDOW_TYPE")?;
+    let name: &[u8] = if desktop { b"_NET_WM_WINDOW_TYPE_DESKTOP" } else { b"_NET_WM_WINDOW_TYPE_NORMAL" };
+    let kind = x11.atom(name)?;
+    x11.conn
+        .change_property32(PropMode::REPLACE, window, property, AtomEnum::ATOM, &[kind])
+        .map_err(|e| e.to_string())?;
+    x11.flush()
+}
+
+pub fn reserve(window: RawWindowHandle, edge: DockEdge, monitor: ScreenRect, thickness: i32) -> Result<ScreenRect, String> {
+    let Some(id) = x11_window(window) else {
+        return Err("Wayland has no way for a regular window to reserve screen space".into());
+    };
+    let rect = monitor.edge_strip(edge, thickness);
+
+    // Struts are measured from the edges of the whole X screen, not the monitor
+    let screen_height = with_x11(|x11| Ok(x11.screen_height()))?;
+
+    // left, right, top, bottom, then start/end pairs for each of those edges
+    let mut values = [0u32; 12];
+    let (start_x, end_x) = (rect.x.max(0) as u32, (rect.x + rect.width - 1).max(0) as u32);
+    match edge {
+        DockEdge::Top => {
+            values[2] = (rect.y + rect.height).max(0) as u32;
+            values[8] = start_x;
+            values[9] = end_x;
+        }
+        DockEdge::Bottom => {
+            values[3] = (screen_height - rect.y).max(0) as u32;
+            values[10] = start_x;
+            values[11] = end_x;
+        }
+        DockEdge::Off => {}
+    }
+
+    with_x11(|x11| set_strut(x11, id, Some(values)))?;
+    tracing::info!("[Window] Reserved {}px strut at the {:?} edge", thickness, edge);
+    Ok(rect)
+}
+
+pub fn release(window: RawWindowHandle) {
+    if let Some(id) = x11_window(window) {
+        if let Err(e) = with_x11(|x11| set_strut(x11, id, None)) {
+            tracing::warn!("[Window] Failed to remove strut: {}", e);
+        }
+    }
+}
+
+pub fn attach_to_desktop(window: RawWindowHandle) -> Result<(), String> {
+    let Some(id) = x11_window(window) else {
+        return Err("Wayland needs the layer-shell protocol for this, which winit doesn't expose".into());
+    };
+    with_x11(|x11| set_desktop_type(x11, id, true))?;
+    tracing::info!("[Window] Marked as desktop window");
+    Ok(())
+}
+
+pub fn detach_from_desktop(window: RawWindowHandle) {
+    if let Some(id) = x11_window(window) {
+        if let Err(e) = with_x11(|x11| set_desktop_type(x11, id, false)) {
+            tracing::warn!("[Window] Failed to restore window type: {}", e);
+        }
+    }
+}
+
+pub fn monitors() -> Vec<ScreenRect> {
+    if crate::shared_state::is_wayland() {
+        return Vec::new();
+    }
+    let query = |x11: &X11| -> Result<Vec<ScreenRect>, String> {
+        let reply = x11
+            .conn
+            .randr_get_monitors(x11.root(), true)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?;
+        Ok(reply
+            .monitors
+            .iter()
+            .map(|m| ScreenRect { x: m.x as i32, y: m.y as i32, width: m.width as i32, height: m.height as i32 })
+            .collect())
+    };
+    with_x11(query).unwrap_or_else(|e| {
+        tracing::warn!("[Window] Can't list monitors: {}", e);
+        Vec::new()
+    })
+}
+
+/// X11 has no per-monitor scaling; the window's own scale applies everywhere
+pub fn monitor_scale(_monitor: &ScreenRect) -> Option<f32> {
+    None
+}
+
+/// Rect of `window`'s client area in root coordinates, or None while it
+/// isn't viewable (iconified, or on another desktop)
+fn viewable_rect(x11: &X11, window: u32) -> Result<Option<ScreenRect>, String> {
+    let attributes = x11.conn.get_window_attributes(window).map_err(|e| e.to_string())?.reply().map_err(|e| e.to_string())?;
+    if attributes.map_state != MapState::VIEWABLE {
+        return Ok(None);
+    }
+    let geometry = x11.conn.get_geometry(window).map_err(|e| e.to_string())?.reply().map_err(|e| e.to_string())?;
+    let origin = x11
+        .conn
+        .translate_coordinates(window, x11.root(), 0, 0)
+        .map_err(|e| e.to_string())?
+        .reply()
+        .map_err(|e| e.to_string())?;
+    Ok(Some(ScreenRect {
+        x: origin.dst_x as i32,
+        y: origin.dst_y as i32,
+        width: geometry.width as i32,
+        height: geometry.height as i32,
+    }))
+}
+
+/// Our window isn't viewable, or a viewable window above it in the window
+/// manager's stacking order contains it. ARGB (translucent) windows count as
+/// opaque here.
+fn occluded(x11: &X11, window: u32) -> Result<bool, String> {
+    let Some(ours) = viewable_rect(x11, window)? else { return Ok(true) };
+    let stacking = x11.atom(b"_NET_CLIENT_LIST_STACKING")?;
+    let reply = x11
+        .conn
+        .get_property(false, x11.root(), stacking, AtomEnum::WINDOW, 0, u32::MAX)
+        .map_err(|e| e.to_string())?
+        .reply()
+        .map_err(|e| e.to_string())?;
+    // Bottom to top; only the windows after ours can cover it
+    let clients: Vec<u32> = reply.value32().map(|v| v.collect()).unwrap_or_default();
+    let Some(position) = clients.iter().position(|&w| w == window) else { return Ok(false) };
+    for &above in &clients[position + 1..] {
+        if let Some(rect) = viewable_rect(x11, above)? {
+            let contains = rect.x <= ours.x
+                && rect.y <= ours.y
+                && rect.x + rect.width >= ours.x + ours.width
+                && rect.y + rect.height >= ours.y + ours.height;
+            if contains {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+pub fn is_occluded(window: RawWindowHandle) -> bool {
+    // Wayland doesn't tell clients about other windows
+    let Some(id) = x11_window(window) else { return false };
+    with_x11(|x11| occluded(x11, id)).unwrap_or_else(|e| {
+        tracing::debug!("[Window] Occlusion check failed: {}", e);
+        false
+    })
+}
+
+/// Refresh rate of the CRTC showing the middle of `window`
+fn crtc_refresh_rate(x11: &X11, window: u32) -> Result<Option<f32>, String> {
+    let Some(rect) = viewable_rect(x11, window)? else { return Ok(None) };
+    let (cx, cy) = (rect.x + rect.width / 2, rect.y + rect.height / 2);
+    let resources = x11
+        .conn
+        .randr_get_screen_resources_current(x11.root())
+        .map_err(|e| e.to_string())?
+        .reply()
+        .map_err(|e| e.to_string())?;
+    for &crtc in &resources.crtcs {
+        let info = x11
+            .conn
+            .randr_get_crtc_info(crtc, resources.config_timestamp)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?;
+        let crtc_rect = ScreenRect { x: info.x as i32, y: info.y as i32, width: info.width as i32, height: info.height as i32 };
+        if info.mode == 0 || !crtc_rect.contains(cx, cy) {
+            continue;
+        }
+        let rate = resources
+            .modes
+            .iter()
+            .find(|m| m.id == info.mode && m.htotal > 0 && m.vtotal > 0)
+            .map(|m| m.dot_clock as f32 / (m.htotal as f32 * m.vtotal as f32));
+        return Ok(rate);
+    }
+    Ok(None)
+}
+
+pub fn refresh_rate(window: RawWindowHandle) -> Option<f32> {
+    let id = x11_window(window)?;
+    with_x11(|x11| crtc_refresh_rate(x11, id)).unwrap_or_else(|e| {
+        tracing::debug!("[Window] Refresh rate query failed: {}", e);
+        None
+    })
+}
+
+pub fn pointer(window: RawWindowHandle) -> Option<Pointer> {
+    let id = x11_window(window)?;
+    let query = |x11: &X11| -> Result<Pointer, String> {
+        let reply = x11.conn.query_pointer(id).map_err(|e| e.to_string())?.reply().map_err(|e| e.to_string())?;
+        Ok(Pointer {
+            screen: [reply.root_x as i32, reply.root_y as i32],
+            window: [reply.win_x as i32, reply.win_y as i32],
+        })
+    };
+    with_x11(query).map(Some).unwrap_or_else(|e| {
+        tracing::debug!("[Window] Pointer query failed: {}", e);
+        None
+    })
+}
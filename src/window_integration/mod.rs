@@ -0,0 +1,200 @@
+//! Platform window features that egui / winit don't cover.
+//!
+//! Docking: the main window sits on the top or bottom screen edge as a
+//! reserved strip ("appbar"), so maximized windows stop at its border instead
+//! of covering it.
+//!   - Windows: registered with the shell through `SHAppBarMessage`
+//!   - Linux (X11): `_NET_WM_STRUT_PARTIAL` hint, honoured by the window manager
+//!   - Wayland / macOS / others: nothing to reserve with; the GUI only moves
+//!     the window to the edge
+//!
+//! Wallpaper: the window is put behind the desktop icons as a live wallpaper.
+//!   - Windows: reparented into Explorer's wallpaper `WorkerW`
+//!   - Linux (X11): marked `_NET_WM_WINDOW_TYPE_DESKTOP`
+//!   - Wayland would need a layer-shell background surface, which winit
+//!     doesn't offer, so it reports an error there (as do other OSes)
+//!
+//! Monitors: the connected monitor rects, read before the window exists so
+//! startup can pick the window layout saved for this monitor setup.
+//!   - Windows: `EnumDisplayMonitors`
+//!   - Linux (X11): RandR monitors
+//!   - Wayland / others: unknown (empty list), windows can't be placed there anyway
+//!
+//! Monitor scale: each monitor's own DPI scale factor, which differs from
+//! the window's when monitors are set to different scaling.
+//!   - Windows: `GetDpiForMonitor`
+//!   - Linux / others: unknown, callers fall back to the window's scale
+//!
+//! Occlusion: whether another window covers ours completely, so the GUI
+//! can stop drawing what nobody sees (minimized is reported by egui).
+//!   - Windows: the windows above ours in the Z-order, by their DWM frames
+//!   - Linux (X11): the window manager's `_NET_CLIENT_LIST_STACKING`
+//!   - Wayland / others: never reported
+//!
+//! Refresh rate: of the monitor the window is on, to pace repaints by.
+//!   - Windows: `EnumDisplaySettingsW` for the window's monitor
+//!   - Linux (X11): the RandR mode of the CRTC under the window
+//!   - Wayland / others: unknown
+//!
+//! Pointer: where the mouse is, also while it's outside our window or the
+//! window is click-through, for ghost mode's hot corner.
+//!   - Windows: `GetCursorPos`
+//!   - Linux (X11): `QueryPointer`
+//!   - Wayland / others: unknown, apps don't get to see the pointer there
+//!
+//! All coordinates here are physical pixels.
+
+use raw_window_handle::RawWindowHandle;
+
+use crate::shared_state::DockEdge;
+
+/// Rectangle on screen in physical pixels
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScreenRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl ScreenRect {
+    /// Strip `thickness` pixels high along `edge` of this (monitor) rect.
+    /// `DockEdge::Off` returns the rect unchanged.
+    pub fn edge_strip(&self, edge: DockEdge, thickness: i32) -> Self {
+        let thickness = thickness.clamp(1, self.height.max(1));
+        match edge {
+            DockEdge::Off => *self,
+            DockEdge::Top => Self { height: thickness, ..*self },
+            DockEdge::Bottom => Self { y: self.y + self.height - thickness, height: thickness, ..*self },
+        }
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+/// Mouse pointer position on screen, and relative to a window's client area
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pointer {
+    pub screen: [i32; 2],
+    pub window: [i32; 2],
+}
+
+/// Key for a monitor setup: monitor count, then each monitor's resolution and
+/// origin, e.g. `2:1920x1080+0+0,2560x1440+1920+0`. Readable on purpose, so
+/// the saved layouts in config.json can be told apart. `None` if unknown.
+pub fn layout_key(monitors: &[ScreenRect]) -> Option<String> {
+    if monitors.is_empty() {
+        return None;
+    }
+    let mut sorted = monitors.to_vec();
+    sorted.sort_by_key(|m| (m.x, m.y));
+    let rects: Vec<String> = sorted
+        .iter()
+        .map(|m| format!("{}x{}+{}+{}", m.width, m.height, m.x, m.y))
+        .collect();
+    Some(format!("{}:{}", sorted.len(), rects.join(",")))
+}
+
+#[cfg(target_os = "windows")]
+mod windows;
+#[cfg(target_os = "windows")]
+use self::windows as platform;
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+use self::linux as platform;
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+mod dummy;
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+use self::dummy as platform;
+
+/// Reserve a `thickness` strip along `edge` of `monitor` for `window`.
+/// Returns where the window should be placed; the OS may shift it (e.g. to
+/// sit next to a taskbar on the same edge). `Err` means nothing was reserved.
+pub fn reserve(window: RawWindowHandle, edge: DockEdge, monitor: ScreenRect, thickness: i32) -> Result<ScreenRect, String> {
+    if edge == DockEdge::Off {
+        release(window);
+        return Ok(monitor);
+    }
+    platform::reserve(window, edge, monitor, thickness)
+}
+
+/// Give the reserved space back. Safe to call when nothing is reserved.
+pub fn release(window: RawWindowHandle) {
+    platform::release(window)
+}
+
+/// Rects of all connected monitors (empty where the OS doesn't tell us)
+pub fn monitors() -> Vec<ScreenRect> {
+    platform::monitors()
+}
+
+/// DPI scale of `monitor` (1.0 = 96 DPI), `None` where the OS doesn't say
+pub fn monitor_scale(monitor: &ScreenRect) -> Option<f32> {
+    platform::monitor_scale(monitor)
+}
+
+/// Put `window` behind the desktop icons
+pub fn attach_to_desktop(window: RawWindowHandle) -> Result<(), String> {
+    platform::attach_to_desktop(window)
+}
+
+/// Make `window` a normal top-level window again
+pub fn detach_from_desktop(window: RawWindowHandle) {
+    platform::detach_from_desktop(window)
+}
+
+/// Another window covers all of `window`, or the OS is hiding it (another
+/// virtual desktop). Best effort: false where the OS can't tell.
+pub fn is_occluded(window: RawWindowHandle) -> bool {
+    platform::is_occluded(window)
+}
+
+/// Refresh rate in Hz of the monitor `window` is on, None where the OS
+/// doesn't say (Wayland, macOS)
+pub fn refresh_rate(window: RawWindowHandle) -> Option<f32> {
+    platform::refresh_rate(window)
+}
+
+/// Where the pointer is, wherever it is. None where the OS doesn't say.
+pub fn pointer(window: RawWindowHandle) -> Option<Pointer> {
+    platform::pointer(window)
+}
+
+// ================== Tests ===================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edge_strip() {
+        let monitor = ScreenRect { x: 1920, y: 0, width: 2560, height: 1440 };
+        assert_eq!(
+            monitor.edge_strip(DockEdge::Top, 60),
+            ScreenRect { x: 1920, y: 0, width: 2560, height: 60 }
+        );
+        assert_eq!(
+            monitor.edge_strip(DockEdge::Bottom, 60),
+            ScreenRect { x: 1920, y: 1380, width: 2560, height: 60 }
+        );
+        // Never taller than the monitor
+        assert_eq!(monitor.edge_strip(DockEdge::Bottom, 5000).y, 0);
+        assert_eq!(monitor.edge_strip(DockEdge::Off, 60), monitor);
+    }
+
+    #[test]
+    fn test_layout_key() {
+        let laptop = ScreenRect { x: 0, y: 0, width: 1920, height: 1080 };
+        let external = ScreenRect { x: 1920, y: 0, width: 2560, height: 1440 };
+        assert_eq!(layout_key(&[]), None);
+        assert_eq!(layout_key(&[laptop]).as_deref(), Some("1:1920x1080+0+0"));
+        // Enumeration order doesn't matter
+        assert_eq!(layout_key(&[external, laptop]), layout_key(&[laptop, external]));
+        assert!(external.contains(2000, 10) && !laptop.contains(2000, 10));
+    }
+}
@@ -0,0 +1,265 @@
+use std::sync::Mutex;
+
+use raw_window_handle::RawWindowHandle;
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM, POINT, RECT, WPARAM};
+use windows::Win32::Graphics::Dwm::{DwmGetWindowAttribute, DWMWA_CLOAKED, DWMWA_EXTENDED_FRAME_BOUNDS};
+use windows::Win32::Graphics::Gdi::{
+    EnumDisplayMonitors, EnumDisplaySettingsW, GetMonitorInfoW, MonitorFromPoint, MonitorFromWindow, ScreenToClient, DEVMODEW,
+    ENUM_CURRENT_SETTINGS, HDC, HMONITOR, MONITORINFO, MONITORINFOEXW, MONITOR_DEFAULTTONEAREST,
+};
+use windows::Win32::UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+use windows::Win32::UI::Shell::{
+    SHAppBarMessage, ABE_BOTTOM, ABE_TOP, ABM_NEW, ABM_QUERYPOS, ABM_REMOVE, ABM_SETPOS, APPBARDATA,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    EnumWindows, FindWindowExW, FindWindowW, GetCursorPos, GetWindow, GetWindowLongW, GetWindowRect, IsIconic, IsWindowVisible,
+    SendMessageTimeoutW, SetParent, GWL_EXSTYLE, GW_HWNDPREV, SMTO_NORMAL, WM_USER, WS_EX_LAYERED, WS_EX_TRANSPARENT,
+};
+
+use super::{Pointer, ScreenRect};
+use crate::shared_state::DockEdge;
+
+/// Message the shell sends us about appbar events. We don't act on them,
+/// but registration requires one.
+const APPBAR_CALLBACK: u32 = WM_USER + 0x42;
+
+/// Windows currently registered as appbars (raw HWND values)
+static REGISTERED: Mutex<Vec<isize>> = Mutex::new(Vec::new());
+
+fn hwnd_of(window: RawWindowHandle) -> Option<HWND> {
+    match window {
+        RawWindowHandle::Win32(h) => Some(HWND(h.hwnd.get())),
+        _ => None,
+    }
+}
+
+fn appbar_data(hwnd: HWND) -> APPBARDATA {
+    APPBARDATA {
+        cbSize: std::mem::size_of::<APPBARDATA>() as u32,
+        hWnd: hwnd,
+        uCallbackMessage: APPBAR_CALLBACK,
+        ..Default::default()
+    }
+}
+
+/// The monitor the window is on. The shell knows real monitor origins,
+/// egui only tells us the size, so this wins over the rect the GUI passed.
+fn monitor_rect(hwnd: HWND) -> Option<RECT> {
+    let mut info = MONITORINFO { cbSize: std::mem::size_of::<MONITORINFO>() as u32, ..Default::default() };
+    let ok = unsafe { GetMonitorInfoW(MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST), &mut info) };
+    ok.as_bool().then_some(info.rcMonitor)
+}
+
+pub fn reserve(window: RawWindowHandle, edge: DockEdge, monitor: ScreenRect, thickness: i32) -> Result<ScreenRect, String> {
+    let hwnd = hwnd_of(window).ok_or("not a Win32 window")?;
+    let mut abd = appbar_data(hwnd);
+
+    {
+        let mut registered = REGISTERED.lock().unwrap_or_else(|p| p.into_inner());
+        if !registered.contains(&hwnd.0) {
+            if unsafe { SHAppBarMessage(ABM_NEW, &mut abd) } == 0 {
+                return Err("the shell refused to register the appbar".into());
+            }
+            registered.push(hwnd.0);
+            tracing::info!("[Window] Registered as appbar");
+        }
+    }
+
+    let screen = monitor_rect(hwnd).unwrap_or(RECT {
+        left: monitor.x,
+        top: monitor.y,
+        right: monitor.x + monitor.width,
+        bottom: monitor.y + monitor.height,
+    });
+    abd.rc = screen;
+    match edge {
+        DockEdge::Bottom => {
+            abd.uEdge = ABE_BOTTOM;
+            abd.rc.top = abd.rc.bottom - thickness;
+        }
+        _ => {
+            abd.uEdge = ABE_TOP;
+            abd.rc.bottom = abd.rc.top + thickness;
+        }
+    }
+
+    // The shell moves the proposed rect out of the way of other appbars
+    // (usually the taskbar), then we put our thickness back and commit
+    unsafe { SHAppBarMessage(ABM_QUERYPOS, &mut abd) };
+    match edge {
+        DockEdge::Bottom => abd.rc.top = abd.rc.bottom - thickness,
+        _ => abd.rc.bottom = abd.rc.top + thickness,
+    }
+    unsafe { SHAppBarMessage(ABM_SETPOS, &mut abd) };
+
+    Ok(ScreenRect {
+        x: abd.rc.left,
+        y: abd.rc.top,
+        width: abd.rc.right - abd.rc.left,
+        height: abd.rc.bottom - abd.rc.top,
+    })
+}
+
+pub fn release(window: RawWindowHandle) {
+    let Some(hwnd) = hwnd_of(window) else { return };
+    let mut registered = REGISTERED.lock().unwrap_or_else(|p| p.into_inner());
+    if let Some(i) = registered.iter().position(|h| *h == hwnd.0) {
+        let mut abd = appbar_data(hwnd);
+        unsafe { SHAppBarMessage(ABM_REMOVE, &mut abd) };
+        registered.remove(i);
+        tracing::info!("[Window] Appbar removed");
+    }
+}
+
+/// Undocumented Progman message that makes Explorer split the desktop into a
+/// wallpaper `WorkerW` behind the icon layer
+const SPAWN_WORKERW: u32 = 0x052C;
+
+/// Finds the `WorkerW` that sits behind the desktop icons: the one right
+/// after the top-level window hosting `SHELLDLL_DefView`
+unsafe extern "system" fn find_wallpaper_layer(hwnd: HWND, found: LPARAM) -> BOOL {
+    let icons = FindWindowExW(hwnd, HWND(0), w!("SHELLDLL_DefView"), PCWSTR::null());
+    if icons.0 != 0 {
+        let worker = FindWindowExW(HWND(0), hwnd, w!("WorkerW"), PCWSTR::null());
+        *(found.0 as *mut HWND) = worker;
+    }
+    BOOL(1)
+}
+
+pub fn attach_to_desktop(window: RawWindowHandle) -> Result<(), String> {
+    let hwnd = hwnd_of(window).ok_or("not a Win32 window")?;
+    unsafe {
+        let progman = FindWindowW(w!("Progman"), PCWSTR::null());
+        if progman.0 == 0 {
+            return Err("no Progman window (is Explorer running?)".into());
+        }
+        let mut result = 0usize;
+        SendMessageTimeoutW(progman, SPAWN_WORKERW, WPARAM(0), LPARAM(0), SMTO_NORMAL, 1000, Some(&mut result as *mut usize));
+
+        let mut worker = HWND(0);
+        let _ = EnumWindows(Some(find_wallpaper_layer), LPARAM(&mut worker as *mut HWND as isize));
+        if worker.0 == 0 {
+            return Err("Explorer didn't create a wallpaper layer".into());
+        }
+        SetParent(hwnd, worker);
+    }
+    tracing::info!("[Window] Attached to the desktop wallpaper layer");
+    Ok(())
+}
+
+pub fn detach_from_desktop(window: RawWindowHandle) {
+    if let Some(hwnd) = hwnd_of(window) {
+        unsafe { SetParent(hwnd, HWND(0)) };
+    }
+}
+
+unsafe extern "system" fn collect_monitor(monitor: HMONITOR, _hdc: HDC, _clip: *mut RECT, found: LPARAM) -> BOOL {
+    let mut info = MONITORINFO { cbSize: std::mem::size_of::<MONITORINFO>() as u32, ..Default::default() };
+    if GetMonitorInfoW(monitor, &mut info).as_bool() {
+        let r = info.rcMonitor;
+        (*(found.0 as *mut Vec<ScreenRect>)).push(ScreenRect {
+            x: r.left,
+            y: r.top,
+            width: r.right - r.left,
+            height: r.bottom - r.top,
+        });
+    }
+    BOOL(1)
+}
+
+pub fn monitors() -> Vec<ScreenRect> {
+    let mut found: Vec<ScreenRect> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(HDC(0), None, Some(collect_monitor), LPARAM(&mut found as *mut Vec<ScreenRect> as isize));
+    }
+    found
+}
+
+pub fn monitor_scale(monitor: &ScreenRect) -> Option<f32> {
+    let center = POINT { x: monitor.x + monitor.width / 2, y: monitor.y + monitor.height / 2 };
+    let (mut dpi_x, mut dpi_y) = (0u32, 0u32);
+    unsafe {
+        let hmonitor = MonitorFromPoint(center, MONITOR_DEFAULTTONEAREST);
+        GetDpiForMonitor(hmonitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y).ok()?;
+    }
+    (dpi_x > 0).then(|| dpi_x as f32 / 96.0)
+}
+
+/// Hidden by the shell, e.g. on another virtual desktop
+fn is_cloaked(hwnd: HWND) -> bool {
+    let mut cloaked = 0u32;
+    let read = unsafe {
+        DwmGetWindowAttribute(hwnd, DWMWA_CLOAKED, &mut cloaked as *mut u32 as *mut _, std::mem::size_of::<u32>() as u32)
+    };
+    read.is_ok() && cloaked != 0
+}
+
+/// What's visible of the window, without the invisible resize borders
+/// `GetWindowRect` includes
+fn frame_rect(hwnd: HWND) -> Option<RECT> {
+    let mut rect = RECT::default();
+    let dwm = unsafe {
+        DwmGetWindowAttribute(hwnd, DWMWA_EXTENDED_FRAME_BOUNDS, &mut rect as *mut RECT as *mut _, std::mem::size_of::<RECT>() as u32)
+    };
+    if dwm.is_ok() {
+        return Some(rect);
+    }
+    unsafe { GetWindowRect(hwnd, &mut rect) }.ok().map(|_| rect)
+}
+
+/// An opaque, showing window whose frame contains `target`. Layered and
+/// click-through windows may be see-through, so they never count.
+fn covers(hwnd: HWND, target: &RECT) -> bool {
+    let see_through = WS_EX_LAYERED.0 | WS_EX_TRANSPARENT.0;
+    let ex_style = unsafe { GetWindowLongW(hwnd, GWL_EXSTYLE) } as u32;
+    let showing = unsafe { IsWindowVisible(hwnd).as_bool() && !IsIconic(hwnd).as_bool() };
+    showing
+        && ex_style & see_through == 0
+        && !is_cloaked(hwnd)
+        && frame_rect(hwnd).is_some_and(|r| {
+            r.left <= target.left && r.top <= target.top && r.right >= target.right && r.bottom >= target.bottom
+        })
+}
+
+pub fn is_occluded(window: RawWindowHandle) -> bool {
+    let Some(hwnd) = hwnd_of(window) else { return false };
+    if is_cloaked(hwnd) {
+        return true;
+    }
+    let Some(ours) = frame_rect(hwnd) else { return false };
+    // Everything above us in the Z-order, nearest first
+    let mut above = unsafe { GetWindow(hwnd, GW_HWNDPREV) };
+    while above.0 != 0 {
+        if covers(above, &ours) {
+            return true;
+        }
+        above = unsafe { GetWindow(above, GW_HWNDPREV) };
+    }
+    false
+}
+
+pub fn refresh_rate(window: RawWindowHandle) -> Option<f32> {
+    let hwnd = hwnd_of(window)?;
+    let mut info = MONITORINFOEXW::default();
+    info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+    let mut mode = DEVMODEW { dmSize: std::mem::size_of::<DEVMODEW>() as u16, ..Default::default() };
+    let found = unsafe {
+        let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+        GetMonitorInfoW(monitor, &mut info as *mut MONITORINFOEXW as *mut MONITORINFO).as_bool()
+            && EnumDisplaySettingsW(PCWSTR(info.szDevice.as_ptr()), ENUM_CURRENT_SETTINGS, &mut mode).as_bool()
+    };
+    // 0 and 1 mean "the hardware default", which doesn't say how fast that is
+    (found && mode.dmDisplayFrequency > 1).then_some(mode.dmDisplayFrequency as f32)
+}
+
+pub fn pointer(window: RawWindowHandle) -> Option<Pointer> {
+    let hwnd = hwnd_of(window)?;
+    let mut screen = POINT::default();
+    unsafe { GetCursorPos(&mut screen) }.ok()?;
+    let mut client = screen;
+    unsafe { ScreenToClient(hwnd, &mut client) }.as_bool().then_some(Pointer {
+        screen: [screen.x, screen.y],
+        window: [client.x, client.y],
+    })
+}